@@ -0,0 +1,244 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Benchmarks for the core palette operations: adding, getting, and setting
+//! colors, finding free addresses in fragmented palettes, iterating pages in
+//! sparse and dense storage, reading and writing large palettes, and nearest
+//! color searches.
+//!
+//! Run with `cargo bench`. The palette sizes are chosen to cover typical 
+//! palettes of a few hundred colors up to the 65536 colors of a full page.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+#[macro_use]
+extern crate criterion;
+extern crate palette;
+
+use palette::{Address, Color, Expression, Format, Palette};
+use palette::data::Data;
+use palette::operation::SetExpression;
+use palette::space::Metric;
+use palette::storage::Storage;
+
+use criterion::{BatchSize, Criterion, black_box};
+
+
+/// The palette sizes used by the size-dependent benchmarks.
+const SIZES: &[usize] = &[256, 4096, 65536];
+
+/// The palette size used by the format benchmarks.
+const FORMAT_SIZE: usize = 16384;
+
+
+/// Returns a deterministic color for the given index.
+fn color(index: usize) -> Color {
+	let value = (index as u32).wrapping_mul(0x9E37_79B9);
+	Color::new(value as u8, (value >> 8) as u8, (value >> 16) as u8)
+}
+
+/// Returns palette data holding the given number of colors.
+fn data(count: usize) -> Data {
+	let mut data: Data = Default::default();
+	for index in 0..count {
+		data.add_color(color(index)).unwrap();
+	}
+	data
+}
+
+/// Returns a palette in the given format holding the given number of colors.
+fn palette(count: usize, format: Format) -> Palette {
+	let mut palette = Palette::new("Benchmark", format, false);
+	for index in 0..count {
+		palette.data_mut().add_color(color(index)).unwrap();
+	}
+	palette
+}
+
+/// Returns palette data holding the given number of colors, with every other
+/// color removed.
+fn fragmented(count: usize) -> Data {
+	let mut dat = data(count);
+	let addresses: Vec<Address> = dat.colors()
+		.map(|(address, _)| address)
+		.collect();
+	for address in addresses.into_iter().step_by(2) {
+		dat.remove_cell(address).unwrap();
+	}
+	dat
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Cells
+////////////////////////////////////////////////////////////////////////////////
+fn add_color(c: &mut Criterion) {
+	for &size in SIZES {
+		c.bench_function(&format!("add_color/{}", size), move |b| {
+			b.iter(|| data(size))
+		});
+	}
+}
+
+fn get_color(c: &mut Criterion) {
+	for &size in SIZES {
+		let dat = data(size);
+		let addresses: Vec<Address> = dat.colors()
+			.map(|(address, _)| address)
+			.collect();
+		c.bench_function(&format!("get_color/{}", size), move |b| {
+			b.iter(|| for &address in &addresses {
+				black_box(dat.cell(address).and_then(|cell| cell.color()));
+			})
+		});
+	}
+}
+
+fn set_color(c: &mut Criterion) {
+	c.bench_function("set_color/4096", |b| {
+		b.iter_batched(
+			|| palette(4096, Format::Default),
+			|mut palette| {
+				for index in 0..4096 {
+					let address = Address::new(0, 0, (index % 256) as u8);
+					palette.apply(Box::new(SetExpression::new(
+						address, 
+						Expression::Color(color(index))))).unwrap();
+				}
+				palette
+			},
+			BatchSize::LargeInput)
+	});
+}
+
+fn first_free_fragmented(c: &mut Criterion) {
+	for &size in SIZES {
+		let dat = fragmented(size);
+		c.bench_function(
+			&format!("first_free_fragmented/{}", size), 
+			move |b| b.iter_batched(
+				|| dat.clone(),
+				|mut dat| {
+					while dat.add_color(Color::new(0, 0, 0)).is_ok() {
+						if dat.len() >= size { break; }
+					}
+					dat
+				},
+				BatchSize::LargeInput));
+	}
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Iteration
+////////////////////////////////////////////////////////////////////////////////
+fn iter_page(c: &mut Criterion) {
+	for &size in SIZES {
+		let dat = data(size);
+		c.bench_function(&format!("iter_page/{}", size), move |b| {
+			b.iter(|| black_box(dat.iter_page(0).count()))
+		});
+		let dat = data(size);
+		c.bench_function(&format!("colors/{}", size), move |b| {
+			b.iter(|| black_box(dat.colors().count()))
+		});
+		let mut dat = data(size);
+		dat.set_storage(Storage::Dense);
+		c.bench_function(&format!("iter_page/dense/{}", size), move |b| {
+			b.iter(|| black_box(dat.iter_page(0).count()))
+		});
+	}
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Formats
+////////////////////////////////////////////////////////////////////////////////
+fn formats(c: &mut Criterion) {
+	for &format in &[Format::Gpl, Format::Csv] {
+		let palette = palette(FORMAT_SIZE, format);
+		let mut buf = Vec::new();
+		format.write_palette(&palette, &mut buf).unwrap();
+
+		c.bench_function(
+			&format!("write/{}/{}", format.name(), FORMAT_SIZE), 
+			move |b| b.iter(|| {
+				let mut out = Vec::with_capacity(buf.len());
+				format.write_palette(&palette, &mut out).unwrap();
+				out
+			}));
+		c.bench_function(
+			&format!("read/{}/{}", format.name(), FORMAT_SIZE), 
+			move |b| b.iter(|| format.read_palette(&mut &buf[..]).unwrap()));
+	}
+}
+
+fn native(c: &mut Criterion) {
+	let dat = data(FORMAT_SIZE);
+	let mut buf = Vec::new();
+	dat.write_native(&mut buf).unwrap();
+	let len = buf.len();
+
+	c.bench_function(&format!("write/native/{}", FORMAT_SIZE), move |b| {
+		b.iter(|| {
+			let mut out = Vec::with_capacity(len);
+			dat.write_native(&mut out).unwrap();
+			out
+		})
+	});
+	c.bench_function(&format!("read/native/{}", FORMAT_SIZE), move |b| {
+		b.iter(|| Data::read_native(&mut &buf[..]).unwrap())
+	});
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Search
+////////////////////////////////////////////////////////////////////////////////
+fn nearest(c: &mut Criterion) {
+	for &size in SIZES {
+		for &metric in &[Metric::Rgb, Metric::DeltaE] {
+			let dat = data(size);
+			c.bench_function(
+				&format!("nearest/{:?}/{}", metric, size), 
+				move |b| b.iter(|| dat.nearest(
+					black_box(Color::new(120, 60, 200)), 
+					metric)));
+		}
+		let dat = data(size);
+		c.bench_function(&format!("nearest_n/16/{}", size), move |b| {
+			b.iter(|| dat.nearest_n(
+				black_box(Color::new(120, 60, 200)), 
+				16, 
+				Metric::DeltaE))
+		});
+	}
+}
+
+
+criterion_group!(cells, add_color, get_color, set_color, first_free_fragmented);
+criterion_group!(iteration, iter_page);
+criterion_group!(io, formats, native);
+criterion_group!(search, nearest);
+criterion_main!(cells, iteration, io, search);
@@ -0,0 +1,18 @@
+//! Feeds arbitrary bytes to the native `.rpal` reader. Any data it accepts 
+//! must be written back and read again without loss.
+#![no_main]
+#[macro_use] extern crate libfuzzer_sys;
+extern crate palette;
+
+use palette::data::Data;
+
+fuzz_target!(|data: &[u8]| {
+	let mut bytes = data;
+	if let Ok(read) = Data::read_native(&mut bytes) {
+		let mut buf = Vec::new();
+		read.write_native(&mut buf).expect("write accepted native data");
+		let reread = Data::read_native(&mut &buf[..])
+			.expect("read written native data");
+		assert!(reread == read);
+	}
+});
@@ -0,0 +1,32 @@
+//! Feeds arbitrary bytes to the readers of the text palette formats. The 
+//! low bits of the first byte select the format, and the high bit selects 
+//! lenient parsing.
+#![no_main]
+#[macro_use] extern crate libfuzzer_sys;
+extern crate palette;
+
+use palette::Format;
+use palette::format::ParseOptions;
+
+const FORMATS: &[Format] = &[
+	Format::Gpl,
+	Format::Jasc,
+	Format::PaintNet,
+	Format::Hex,
+	Format::Gale,
+	Format::Csv,
+	Format::Tsv,
+	Format::Toml,
+];
+
+fuzz_target!(|data: &[u8]| {
+	if let Some((&selector, mut bytes)) = data.split_first() {
+		let format = FORMATS[(selector & 0x7F) as usize % FORMATS.len()];
+		let options = if selector & 0x80 == 0 {
+			ParseOptions::strict()
+		} else {
+			ParseOptions::lenient()
+		};
+		let _ = format.read_palette_with(&mut bytes, &options);
+	}
+});
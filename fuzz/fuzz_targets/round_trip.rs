@@ -0,0 +1,41 @@
+//! Writes arbitrary palettes through each readable format and checks that the
+//! colors the format can represent are read back unchanged.
+#![no_main]
+#[macro_use] extern crate libfuzzer_sys;
+extern crate palette;
+
+use palette::{Color, Palette};
+use palette::depth::{expand, reduce};
+use palette::format::registry::REGISTRY;
+
+fuzz_target!(|palette: Palette| {
+	let colors: Vec<Color> = palette.data().colors()
+		.map(|(_, color)| color)
+		.collect();
+
+	for registration in REGISTRY {
+		if !(registration.readable && registration.writable) { continue; }
+		let format = registration.format;
+		let caps = format.capabilities();
+		if colors.len() < caps.min_colors 
+			|| caps.max_colors.map_or(false, |max| colors.len() > max)
+		{
+			continue;
+		}
+
+		let mut buf = Vec::new();
+		format.write_palette(&palette, &mut buf).expect("write palette");
+		let read = format.read_palette(&mut &buf[..]).expect("read palette");
+
+		let depth = caps.depth;
+		let store = |channel: u8| expand(reduce(channel, depth), depth);
+		let read_colors: Vec<Color> = read.data().colors()
+			.map(|(_, color)| color)
+			.take(colors.len())
+			.collect();
+		let expected: Vec<Color> = colors.iter()
+			.map(|c| Color::new(store(c.r()), store(c.g()), store(c.b())))
+			.collect();
+		assert_eq!(read_colors, expected, "{} round trip", format.name());
+	}
+});
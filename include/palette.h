@@ -0,0 +1,71 @@
+/*
+ * C interface to the palette library. Build the crate with the `ffi` feature
+ * as a static or dynamic library, for example with
+ * `cargo rustc --lib --release --features ffi --crate-type cdylib`, and link
+ * against the result.
+ *
+ * Palettes are accessed through opaque handles. Fallible functions return a
+ * palette_status code, and palette_last_error describes the most recent
+ * error on the calling thread.
+ */
+#ifndef PALETTE_H
+#define PALETTE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct PaletteHandle PaletteHandle;
+
+typedef enum palette_status {
+	PALETTE_OK = 0,
+	PALETTE_NULL_POINTER = 1,
+	PALETTE_INVALID_STRING = 2,
+	PALETTE_INVALID_ADDRESS = 3,
+	PALETTE_EMPTY_ADDRESS = 4,
+	PALETTE_ADDRESS_IN_USE = 5,
+	PALETTE_FULL = 6,
+	PALETTE_IO = 7,
+	PALETTE_FORMAT = 8,
+	PALETTE_PARSE = 9,
+	PALETTE_OTHER = 10
+} palette_status;
+
+typedef struct palette_address {
+	uint16_t page;
+	uint8_t line;
+	uint8_t column;
+} palette_address;
+
+typedef struct palette_color {
+	uint8_t r;
+	uint8_t g;
+	uint8_t b;
+} palette_color;
+
+PaletteHandle *palette_create(const char *name);
+void palette_destroy(PaletteHandle *handle);
+size_t palette_len(PaletteHandle *handle);
+
+palette_status palette_add_color(
+	PaletteHandle *handle,
+	palette_color color,
+	palette_address *address);
+palette_status palette_get_color(
+	PaletteHandle *handle,
+	palette_address address,
+	palette_color *color);
+
+palette_status palette_load(const char *path, PaletteHandle **handle);
+palette_status palette_save(PaletteHandle *handle, const char *path);
+
+const char *palette_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
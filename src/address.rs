@@ -46,10 +46,14 @@ use result::{
 use interval::Interval;
 
 // Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
 use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
 use std::u16;
 use std::u8;
-use std::ops::Add;
+use std::ops::{Add, Range};
 
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -82,7 +86,7 @@ pub type ColumnOffset = i16;
 // Offset
 ////////////////////////////////////////////////////////////////////////////////
 /// Allows computation of relative `Reference`s.
-trait Offset: Sized + Copy {
+pub(crate) trait Offset: Sized + Copy {
 	type Base: Add;
 	fn offset(self, base: &Self::Base) -> Result<Self::Base>;
 	fn is_negative(self) -> bool;
@@ -133,16 +137,17 @@ impl Offset for i16 {
 // Reference
 ////////////////////////////////////////////////////////////////////////////////
 /// A reference to a set of `Cell`s the in the palette.
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, PartialOrd, PartialEq, Eq, Hash, Ord, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Reference {
 	/// The pages being referenced.
-	page: ReferenceComponent<Page, PageOffset>,
+	pub(crate) page: ReferenceComponent<Page, PageOffset>,
 	
 	/// The lines being referenced.
-	line: ReferenceComponent<Line, LineOffset>,
+	pub(crate) line: ReferenceComponent<Line, LineOffset>,
 
 	/// The columns being referenced.
-	column: ReferenceComponent<Column, ColumnOffset>,
+	pub(crate) column: ReferenceComponent<Column, ColumnOffset>,
 }
 
 
@@ -227,6 +232,64 @@ impl Reference {
 			Err(Error::UnresolvedReferenceComponent)
 		}
 	} 
+
+	/// Returns the addresses covered by the `Reference`, in address order, 
+	/// assuming the given page, line, and column counts. `All` and `Any` 
+	/// components cover every index up to the count. Returns an error if any
+	/// component is named or indirect.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::{Address, Reference};
+	/// 
+	/// let line = Reference::line_of(&Address::new(1, 2, 0));
+	/// let addresses = line.addresses(4, 4, 3).unwrap();
+	///
+	/// assert_eq!(addresses, vec![
+	/// 	Address::new(1, 2, 0),
+	/// 	Address::new(1, 2, 1),
+	/// 	Address::new(1, 2, 2),
+	/// ]);
+	/// ```
+	pub fn addresses(&self, pages: Page, lines: Line, columns: Column) 
+		-> Result<Vec<Address>>
+	{
+		let pages = component_indices(&self.page, pages as u32)?;
+		let lines = component_indices(&self.line, lines as u32)?;
+		let columns = component_indices(&self.column, columns as u32)?;
+
+		let mut addresses = Vec::new();
+		for page in pages.clone() {
+			for line in lines.clone() {
+				for column in columns.clone() {
+					addresses.push(Address::new(
+						page as Page, 
+						line as Line, 
+						column as Column));
+				}
+			}
+		}
+		Ok(addresses)
+	}
+}
+
+
+/// Returns the range of indices covered by the given `ReferenceComponent`, 
+/// with `All` and `Any` covering every index below the given count.
+pub(crate) fn component_indices<T, O>(
+	component: &ReferenceComponent<T, O>,
+	count: u32)
+	-> Result<Range<u32>>
+	where T: Copy + Into<u32>
+{
+	use self::ReferenceComponent::*;
+
+	match *component {
+		All | Any => Ok(0..count),
+		Index(i) => Ok(i.into()..i.into() + 1),
+		_ => Err(Error::UnresolvedReferenceComponent),
+	}
 }
 
 
@@ -263,13 +326,106 @@ impl fmt::Display for Reference {
 }
 
 
+impl FromStr for Reference {
+	type Err = Error;
+
+	/// Parses a `Reference` from its components separated by `:` or `/`. Each
+	/// component may be an index, `*` for all indices, `_` for any index, or a
+	/// name, and an index, `_`, or name may be followed by a signed offset 
+	/// such as `+3` or `-1`. The forms `page P`, `line P:L`, and `all` are 
+	/// also accepted.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::{Address, Reference};
+	///
+	/// let page = Reference::page_of(&Address::new(2, 0, 0));
+	/// assert_eq!("2:*:*".parse::<Reference>().unwrap(), page);
+	/// assert_eq!("page 2".parse::<Reference>().unwrap(), page);
+	/// assert_eq!(page.to_string().parse::<Reference>().unwrap(), page);
+	/// assert_eq!("all".parse::<Reference>().unwrap(), Reference::all());
+	/// assert!("2:*".parse::<Reference>().is_err());
+	///
+	/// let relative = "base+3:_-1:0".parse::<Reference>().unwrap();
+	/// assert_eq!(relative.to_string(), "base+3/_-1/0");
+	/// assert_eq!(relative.to_string().parse::<Reference>().unwrap(), 
+	/// 	relative);
+	/// ```
+	fn from_str(text: &str) -> Result<Self> {
+		let invalid = || Error::InvalidAddressSyntax(text.to_owned());
+		let trimmed = text.trim();
+		let lower = trimmed.to_lowercase();
+
+		if lower == "all" || lower == "*" {
+			return Ok(Reference::all());
+		}
+		let (parts, fill) = if lower.starts_with("page ") {
+			(&trimmed[5..], 2)
+		} else if lower.starts_with("line ") {
+			(&trimmed[5..], 1)
+		} else {
+			(trimmed, 0)
+		};
+
+		let mut components: Vec<&str> = parts.trim()
+			.split(|c| c == ':' || c == '/')
+			.map(|part| part.trim())
+			.collect();
+		if components.len() + fill != 3 { return Err(invalid()); }
+		for _ in 0..fill { components.push("*"); }
+
+		Ok(Reference {
+			page: parse_component(components[0]).ok_or_else(invalid)?,
+			line: parse_component(components[1]).ok_or_else(invalid)?,
+			column: parse_component(components[2]).ok_or_else(invalid)?,
+		})
+	}
+}
+
+
+/// Parses a `ReferenceComponent`, or returns None if the text is empty or 
+/// contains a number out of range. A direct component followed by a signed 
+/// decimal offset is parsed as an indirect component.
+fn parse_component<T, O>(text: &str) -> Option<ReferenceComponent<T, O>>
+	where T: FromStr, O: FromStr
+{
+	use self::ReferenceComponent::*;
+
+	// Split off a trailing offset.
+	let split = text.rfind(|c| c == '+' || c == '-')
+		.filter(|&i| i > 0 
+			&& i + 1 < text.len() 
+			&& text[i + 1..].chars().all(|c| c.is_digit(10)));
+	if let Some(i) = split {
+		let direct = match parse_component::<T, O>(&text[..i])? {
+			Any => DirectReferenceComponent::Any,
+			Index(i) => DirectReferenceComponent::Index(i),
+			Named(name) => DirectReferenceComponent::Named(name),
+			_ => return None,
+		};
+		return text[i..].parse().ok().map(|offset| Indirect(direct, offset));
+	}
+
+	match text {
+		"" => None,
+		"*" => Some(All),
+		"_" => Some(Any),
+		_ if text.chars().all(|c| c.is_digit(10)) 
+			=> text.parse().ok().map(Index),
+		_ => Some(Named(text.to_owned())),
+	}
+}
+
+
 
 ////////////////////////////////////////////////////////////////////////////////
 // ReferenceComponent
 ////////////////////////////////////////////////////////////////////////////////
 /// A potentially indirect component of a `Reference`.
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-enum ReferenceComponent<T, O> {
+#[derive(Debug, PartialOrd, PartialEq, Eq, Hash, Ord, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(crate) enum ReferenceComponent<T, O> {
 	Any,
 	Index(T),
 	Named(String),
@@ -352,8 +508,9 @@ impl<T, O> fmt::Display for ReferenceComponent<T, O>
 // DirectReferenceComponent
 ////////////////////////////////////////////////////////////////////////////////
 /// A direct component of a `Reference`.
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-enum DirectReferenceComponent<T> {
+#[derive(Debug, PartialOrd, PartialEq, Eq, Hash, Ord, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(crate) enum DirectReferenceComponent<T> {
 	Any,
 	Index(T),
 	Named(String),
@@ -389,6 +546,7 @@ impl<T> fmt::Display for DirectReferenceComponent<T> where T: fmt::Display {
 ////////////////////////////////////////////////////////////////////////////////
 /// The absolute position of a Cell.
 #[derive(Debug, PartialOrd, PartialEq, Eq, Hash, Ord, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Address {
 	/// The page of the Address.
 	pub page: Page,
@@ -448,6 +606,89 @@ impl Address {
 			(m % c) as Column
 		)
 	}
+
+	/// Returns the `Address` n steps ahead, assuming the given wrapping 
+	/// parameters, or None if the result would lie beyond the last page.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::Address;
+	/// 
+	/// let a = Address::new(0, 9, 9);
+	/// assert_eq!(a.checked_add(1, 10, 10, 10), Some(Address::new(1, 0, 0)));
+	/// assert_eq!(a.checked_add(1, 1, 10, 10), None);
+	/// ```
+	pub fn checked_add(
+		&self, 
+		n: usize,
+		pages: Page,
+		lines: Line, 
+		columns: Column) 
+		-> Option<Address>
+	{
+		let index = self.index(lines, columns).checked_add(n)?;
+		let address = Address::from_index(index, lines, columns)?;
+		if address.page < pages { Some(address) } else { None }
+	}
+
+	/// Returns the position of the `Address` in page, line, column order, 
+	/// assuming the given wrapping parameters.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::Address;
+	/// 
+	/// assert_eq!(Address::new(1, 2, 3).index(10, 10), 123);
+	/// ```
+	pub fn index(&self, lines: Line, columns: Column) -> usize {
+		let (l, c) = (lines as usize, columns as usize);
+		self.page as usize * l * c
+			+ self.line as usize * c
+			+ self.column as usize
+	}
+
+	/// Returns the `Address` at the given position in page, line, column 
+	/// order, assuming the given wrapping parameters. Returns None if the page
+	/// would be out of range.
+	pub fn from_index(index: usize, lines: Line, columns: Column) 
+		-> Option<Address>
+	{
+		let (l, c) = (lines as usize, columns as usize);
+		if l == 0 || c == 0 { return None; }
+		let page = index / (l * c);
+		if page > PAGE_MAX as usize { return None; }
+		let m = index % (l * c);
+		Some(Address::new(page as Page, (m / c) as Line, (m % c) as Column))
+	}
+
+	/// Returns an `AddressRange` over the given range of addresses, assuming
+	/// the given wrapping parameters.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::Address;
+	/// 
+	/// let range = Address::range(
+	/// 	Address::new(0, 1, 8)..Address::new(0, 2, 2), 
+	/// 	10, 
+	/// 	10);
+	/// let addresses: Vec<_> = range.collect();
+	///
+	/// assert_eq!(addresses, vec![
+	/// 	Address::new(0, 1, 8),
+	/// 	Address::new(0, 1, 9),
+	/// 	Address::new(0, 2, 0),
+	/// 	Address::new(0, 2, 1),
+	/// ]);
+	/// ```
+	pub fn range(range: Range<Address>, lines: Line, columns: Column) 
+		-> AddressRange
+	{
+		AddressRange::new(range.start, range.end, lines, columns)
+	}
 }
 
 
@@ -465,22 +706,228 @@ impl fmt::Display for Address {
 }
 
 
+impl FromStr for Address {
+	type Err = Error;
+
+	/// Parses an `Address` from its components in the `P:L:C` form used for 
+	/// display. Components are decimal unless prefixed with `0x`, as written
+	/// by the alternate hexadecimal formats.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::Address;
+	///
+	/// let address = Address::new(2, 0, 15);
+	/// assert_eq!("2:0:15".parse::<Address>().unwrap(), address);
+	/// assert_eq!("0x02:0x00:0x0F".parse::<Address>().unwrap(), address);
+	/// assert_eq!("2:0:0xf".parse::<Address>().unwrap(), address);
+	/// assert_eq!(address.to_string().parse::<Address>().unwrap(), address);
+	/// assert_eq!(format!("{:#X}", address).parse::<Address>().unwrap(), 
+	/// 	address);
+	/// assert!("2:0:256".parse::<Address>().is_err());
+	/// assert!("2:0:0x100".parse::<Address>().is_err());
+	/// ```
+	fn from_str(text: &str) -> Result<Self> {
+		let invalid = || Error::InvalidAddressSyntax(text.to_owned());
+		let parts: Vec<&str> = text.trim().split(':').collect();
+		if parts.len() != 3 { return Err(invalid()); }
+
+		Ok(Address::new(
+			parse_index(parts[0], Page::from_str_radix).ok_or_else(invalid)?,
+			parse_index(parts[1], Line::from_str_radix).ok_or_else(invalid)?,
+			parse_index(parts[2], Column::from_str_radix)
+				.ok_or_else(invalid)?))
+	}
+}
+
+
+/// Parses an address component, which is decimal unless prefixed with `0x`,
+/// using the given radix parsing function. Returns None if the text is not a
+/// number or is out of range.
+fn parse_index<T>(
+	text: &str, 
+	from_str_radix: fn(&str, u32) -> ::std::result::Result<T, ParseIntError>)
+	-> Option<T>
+{
+	let text = text.trim();
+	let (digits, radix) = if text.starts_with("0x") || text.starts_with("0X") {
+		(&text[2..], 16)
+	} else {
+		(text, 10)
+	};
+	if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+		return None;
+	}
+	from_str_radix(digits, radix).ok()
+}
+
+
 impl fmt::UpperHex for Address {
+	/// Formats the address with hexadecimal components. The alternate form 
+	/// prefixes each component with `0x`, so that it can be parsed back.
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		write!(f, "{:02X}:{:02X}:{:02X}", self.page, self.line, self.column)
+		if f.alternate() {
+			write!(f, "{:#04X}:{:#04X}:{:#04X}", 
+				self.page, self.line, self.column)
+		} else {
+			write!(f, "{:02X}:{:02X}:{:02X}", 
+				self.page, self.line, self.column)
+		}
 	}
 }
 
 
 impl fmt::LowerHex for Address {
+	/// Formats the address with hexadecimal components. The alternate form 
+	/// prefixes each component with `0x`, so that it can be parsed back.
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		write!(f, "{:02x}:{:02x}:{:02x}", self.page, self.line, self.column)
+		if f.alternate() {
+			write!(f, "{:#04x}:{:#04x}:{:#04x}", 
+				self.page, self.line, self.column)
+		} else {
+			write!(f, "{:02x}:{:02x}:{:02x}", 
+				self.page, self.line, self.column)
+		}
 	}
 }
 
 
 
 
+////////////////////////////////////////////////////////////////////////////////
+// AddressRange
+////////////////////////////////////////////////////////////////////////////////
+/// A half-open range of `Address`es in page, line, column order. Addresses 
+/// wrap to the next line and page according to the range's line and column 
+/// counts.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct AddressRange {
+	/// The position of the next address at the front of the range.
+	front: usize,
+	/// The position one past the last address of the range.
+	back: usize,
+	/// The number of lines per page.
+	lines: Line,
+	/// The number of columns per line.
+	columns: Column,
+}
+
+
+impl AddressRange {
+	/// Creates a new `AddressRange` including `start` and excluding `end`, 
+	/// assuming the given wrapping parameters.
+	pub fn new(start: Address, end: Address, lines: Line, columns: Column) 
+		-> Self 
+	{
+		let limit = AddressRange::limit(lines, columns);
+		let front = start.index(lines, columns).min(limit);
+		AddressRange {
+			front: front,
+			back: end.index(lines, columns).max(front).min(limit),
+			lines: lines,
+			columns: columns,
+		}
+	}
+
+	/// Creates a new `AddressRange` covering `count` addresses beginning with
+	/// `start`, assuming the given wrapping parameters. The range is truncated
+	/// at the last valid address.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::Address;
+	/// use palette::address::AddressRange;
+	/// 
+	/// let start = Address::new(u16::max_value(), 9, 8);
+	/// let range = AddressRange::with_len(start, usize::max_value(), 10, 10);
+	///
+	/// assert_eq!(range.len(), 2);
+	/// assert_eq!(range.last(), Some(Address::new(u16::max_value(), 9, 9)));
+	/// ```
+	pub fn with_len(start: Address, count: usize, lines: Line, columns: Column)
+		-> Self
+	{
+		let limit = AddressRange::limit(lines, columns);
+		let front = start.index(lines, columns).min(limit);
+		AddressRange {
+			front: front,
+			back: front.checked_add(count).map_or(limit, |b| b.min(limit)),
+			lines: lines,
+			columns: columns,
+		}
+	}
+
+	/// Returns the position one past the last valid address, assuming the 
+	/// given wrapping parameters.
+	fn limit(lines: Line, columns: Column) -> usize {
+		(PAGE_MAX as usize + 1) * lines as usize * columns as usize
+	}
+
+	/// Returns the first `Address` remaining in the range, or None if the 
+	/// range is empty.
+	pub fn start(&self) -> Option<Address> {
+		if self.is_empty() { return None; }
+		Address::from_index(self.front, self.lines, self.columns)
+	}
+
+	/// Returns the number of lines per page used to wrap the range.
+	pub fn lines(&self) -> Line {
+		self.lines
+	}
+
+	/// Returns the number of columns per line used to wrap the range.
+	pub fn columns(&self) -> Column {
+		self.columns
+	}
+
+	/// Returns whether the range is empty.
+	pub fn is_empty(&self) -> bool {
+		self.front >= self.back
+	}
+
+	/// Returns whether the given `Address` lies within the range.
+	pub fn contains(&self, address: &Address) -> bool {
+		if address.line >= self.lines || address.column >= self.columns {
+			return false;
+		}
+		let index = address.index(self.lines, self.columns);
+		index >= self.front && index < self.back
+	}
+}
+
+
+impl Iterator for AddressRange {
+	type Item = Address;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.is_empty() { return None; }
+		let address = Address::from_index(self.front, self.lines, self.columns);
+		self.front += 1;
+		address
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let len = self.back.saturating_sub(self.front);
+		(len, Some(len))
+	}
+}
+
+
+impl DoubleEndedIterator for AddressRange {
+	fn next_back(&mut self) -> Option<Self::Item> {
+		if self.is_empty() { return None; }
+		self.back -= 1;
+		Address::from_index(self.back, self.lines, self.columns)
+	}
+}
+
+
+impl ExactSizeIterator for AddressRange {}
+
+
+
 ////////////////////////////////////////////////////////////////////////////////
 // Selection
 ////////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,268 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides statistical analysis of palette colors.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{Address, Page};
+use data::Data;
+use space::{relative_luminance, Hsl, Metric};
+use utilities::filter_pairs;
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+use std::collections::{BTreeMap, HashSet};
+use std::f32;
+use std::fmt;
+
+
+/// The number of bins in the hue histogram, each covering 30 degrees.
+pub const HUE_BINS: usize = 12;
+
+/// The saturation below which colors are treated as grays and excluded from 
+/// the hue histogram.
+const GRAY_SATURATION: f32 = 0.05;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Range
+////////////////////////////////////////////////////////////////////////////////
+/// The minimum, maximum, and mean of a set of values.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Range {
+	/// The smallest value.
+	pub min: f32,
+	/// The largest value.
+	pub max: f32,
+	/// The mean value.
+	pub mean: f32,
+}
+
+
+impl Range {
+	/// Returns the range of the given values, or the default range if there 
+	/// are none.
+	fn of<I>(values: I) -> Range where I: IntoIterator<Item=f32> {
+		let (mut min, mut max, mut sum, mut count) = 
+			(f32::INFINITY, f32::NEG_INFINITY, 0.0, 0);
+		for value in values {
+			min = min.min(value);
+			max = max.max(value);
+			sum += value;
+			count += 1;
+		}
+		if count == 0 { return Default::default(); }
+		Range {min: min, max: max, mean: sum / count as f32}
+	}
+
+	/// Returns the difference between the largest and smallest values.
+	pub fn spread(&self) -> f32 {
+		self.max - self.min
+	}
+}
+
+
+impl fmt::Display for Range {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{:.3} to {:.3} (mean {:.3})", self.min, self.max, self.mean)
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// PageSummary
+////////////////////////////////////////////////////////////////////////////////
+/// Statistics for a single page of the palette.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PageSummary {
+	/// The number of colors on the page.
+	pub color_count: usize,
+	/// The number of colors on the page which repeat an earlier color on the
+	/// same page.
+	pub duplicate_count: usize,
+	/// The relative luminance of the colors on the page.
+	pub luminance: Range,
+	/// The HSL saturation of the colors on the page.
+	pub saturation: Range,
+}
+
+
+impl PageSummary {
+	/// Computes the summary of the given colors.
+	fn of(colors: &[Color]) -> PageSummary {
+		PageSummary {
+			color_count: colors.len(),
+			duplicate_count: duplicates(colors),
+			luminance: Range::of(colors.iter()
+				.map(|&c| relative_luminance(c))),
+			saturation: Range::of(colors.iter().map(|&c| Hsl::from(c).s)),
+		}
+	}
+}
+
+
+/// Returns the pairs of addresses whose colors are closer than the given 
+/// CIE76 distance, along with the distance of each pair. Exact duplicates have
+/// a distance of zero. The pairs are ordered by address.
+///
+/// Every pair of colors is compared, so the scan is quadratic in the number of
+/// colors. The comparisons are run in parallel if the `parallel` feature is 
+/// enabled.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::analysis::near_duplicates;
+/// use palette::data::Data;
+/// use palette::Color;
+/// 
+/// let mut dat: Data = Default::default();
+/// let a = dat.add_color(Color::new(200, 40, 40)).unwrap();
+/// let b = dat.add_color(Color::new(202, 41, 40)).unwrap();
+/// dat.add_color(Color::new(40, 40, 200)).unwrap();
+///
+/// let pairs = near_duplicates(&dat, 2.0);
+/// assert_eq!(pairs.len(), 1);
+/// assert_eq!((pairs[0].0, pairs[0].1), (a, b));
+/// ```
+pub fn near_duplicates(data: &Data, threshold: f32) 
+	-> Vec<(Address, Address, f32)>
+{
+	let colors: Vec<(Address, Color)> = data.colors().collect();
+	filter_pairs(&colors, |&(a, ca), &(b, cb)| {
+		let distance = Metric::DeltaE.distance(ca, cb);
+		if distance < threshold { Some((a, b, distance)) } else { None }
+	})
+}
+
+
+/// Returns the number of colors which repeat an earlier color.
+fn duplicates(colors: &[Color]) -> usize {
+	let mut seen = HashSet::new();
+	colors.iter()
+		.filter(|&&c| !seen.insert((c.r(), c.g(), c.b())))
+		.count()
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Analysis
+////////////////////////////////////////////////////////////////////////////////
+/// Statistics describing the colors of a palette.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::data::Data;
+/// use palette::Color;
+/// 
+/// let mut dat: Data = Default::default();
+/// dat.add_color(Color::new(255, 0, 0)).unwrap();
+/// dat.add_color(Color::new(255, 0, 0)).unwrap();
+/// dat.add_color(Color::new(0, 0, 255)).unwrap();
+/// dat.add_color(Color::new(128, 128, 128)).unwrap();
+///
+/// let analysis = dat.analyze();
+/// assert_eq!(analysis.summary.color_count, 4);
+/// assert_eq!(analysis.summary.duplicate_count, 1);
+/// assert_eq!(analysis.hue_histogram[0], 2);
+/// assert_eq!(analysis.hue_histogram[8], 1);
+/// assert_eq!(analysis.hue_coverage(), 2.0 / 12.0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Analysis {
+	/// The statistics of the whole palette.
+	pub summary: PageSummary,
+	/// The number of saturated colors in each 30 degree hue bin, starting 
+	/// from red.
+	pub hue_histogram: [usize; HUE_BINS],
+	/// The statistics of each page.
+	pub pages: BTreeMap<Page, PageSummary>,
+}
+
+
+impl Analysis {
+	/// Analyzes the colors of the given palette data.
+	pub fn new(data: &Data) -> Analysis {
+		let mut all = Vec::new();
+		let mut pages: BTreeMap<Page, Vec<Color>> = BTreeMap::new();
+		let mut hue_histogram = [0; HUE_BINS];
+		for (address, color) in data.colors() {
+			all.push(color);
+			pages.entry(address.page).or_insert_with(Vec::new).push(color);
+
+			let hsl = Hsl::from(color);
+			if hsl.s >= GRAY_SATURATION {
+				let bin = (hsl.h / (360.0 / HUE_BINS as f32)) as usize;
+				hue_histogram[bin.min(HUE_BINS - 1)] += 1;
+			}
+		}
+
+		Analysis {
+			summary: PageSummary::of(&all),
+			hue_histogram: hue_histogram,
+			pages: pages.iter()
+				.map(|(&page, colors)| (page, PageSummary::of(colors)))
+				.collect(),
+		}
+	}
+
+	/// Returns the fraction of hue histogram bins containing any colors.
+	pub fn hue_coverage(&self) -> f32 {
+		self.hue_histogram.iter().filter(|&&n| n > 0).count() as f32 
+			/ HUE_BINS as f32
+	}
+}
+
+
+impl fmt::Display for Analysis {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(f, "Colors: {} ({} duplicates)", 
+			self.summary.color_count,
+			self.summary.duplicate_count)?;
+		writeln!(f, "Luminance: {}", self.summary.luminance)?;
+		writeln!(f, "Saturation: {}", self.summary.saturation)?;
+		write!(f, "Hues:")?;
+		for count in &self.hue_histogram {
+			write!(f, " {}", count)?;
+		}
+		writeln!(f, " ({:.0}% coverage)", self.hue_coverage() * 100.0)?;
+		for (page, summary) in &self.pages {
+			writeln!(f, "Page {}: {} colors, luminance {}", 
+				page,
+				summary.color_count,
+				summary.luminance)?;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,179 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides color cycling, in the style of Deluxe Paint. 
+//!
+//! A `Cycle` rotates the colors within a range of addresses over time. The 
+//! cycles of a palette are stored with its data, and the effective colors at 
+//! any point in time may be queried without modifying the palette.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::Address;
+use data::Data;
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Direction
+////////////////////////////////////////////////////////////////////////////////
+/// The direction colors move through a cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Direction {
+	/// Colors move toward higher addresses.
+	Forward,
+	/// Colors move toward lower addresses.
+	Reverse,
+	/// Colors move toward higher addresses, then back.
+	PingPong,
+}
+
+
+impl Default for Direction {
+	fn default() -> Self {
+		Direction::Forward
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Cycle
+////////////////////////////////////////////////////////////////////////////////
+/// Rotates the colors within an inclusive range of addresses over time. Only 
+/// addresses which resolve to a color take part in the cycle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Cycle {
+	/// The first address of the range.
+	pub start: Address,
+	/// The last address of the range.
+	pub end: Address,
+	/// The number of steps per second.
+	pub rate: f32,
+	/// The direction of the cycle.
+	pub direction: Direction,
+}
+
+
+impl Cycle {
+	/// Creates a new forward Cycle over the given range of addresses with the
+	/// given number of steps per second.
+	pub fn new(start: Address, end: Address, rate: f32) -> Cycle {
+		Cycle {
+			start: start,
+			end: end,
+			rate: rate,
+			direction: Direction::Forward,
+		}
+	}
+
+	/// Sets the direction of the cycle.
+	pub fn direction(mut self, direction: Direction) -> Cycle {
+		self.direction = direction;
+		self
+	}
+
+	/// Returns whether the given address is within the cycle's range.
+	pub fn contains(&self, address: Address) -> bool {
+		self.start <= address && address <= self.end
+	}
+
+	/// Returns the number of positions each color has moved forward at the 
+	/// given time, for a cycle over the given number of colors.
+	pub fn offset(&self, time: f32, len: usize) -> usize {
+		let steps = time * self.rate;
+		if len < 2 || self.rate <= 0.0 || time <= 0.0 || !steps.is_finite() {
+			return 0;
+		}
+		let steps = steps.floor() as u64;
+		let len = len as u64;
+		let offset = match self.direction {
+			Direction::Forward => steps % len,
+			Direction::Reverse => (len - steps % len) % len,
+			Direction::PingPong => {
+				let period = 2 * (len - 1);
+				let step = steps % period;
+				if step < len { step } else { period - step }
+			},
+		};
+		offset as usize
+	}
+}
+
+
+/// Returns the addresses and colors of the given palette data at the given 
+/// time in seconds, with all of its cycles applied. Cycles are applied in 
+/// order, so later cycles rotate the results of earlier ones.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::animation::{colors_at, Cycle};
+/// use palette::data::Data;
+/// use palette::{Address, Color};
+/// 
+/// let mut dat: Data = Default::default();
+/// let a = dat.add_color(Color::new(10, 10, 10)).unwrap();
+/// let b = dat.add_color(Color::new(20, 20, 20)).unwrap();
+/// let c = dat.add_color(Color::new(30, 30, 30)).unwrap();
+/// dat.add_cycle(Cycle::new(a, c, 2.0));
+///
+/// assert_eq!(colors_at(&dat, 0.5), vec![
+/// 	(a, Color::new(30, 30, 30)),
+/// 	(b, Color::new(10, 10, 10)),
+/// 	(c, Color::new(20, 20, 20)),
+/// ]);
+/// ```
+pub fn colors_at(data: &Data, time: f32) -> Vec<(Address, Color)> {
+	let mut colors: Vec<(Address, Color)> = data.colors().collect();
+
+	for cycle in data.cycles() {
+		let positions: Vec<usize> = colors.iter()
+			.enumerate()
+			.filter(|&(_, &(address, _))| cycle.contains(address))
+			.map(|(i, _)| i)
+			.collect();
+		let len = positions.len();
+		let offset = cycle.offset(time, len);
+		if offset == 0 { continue; }
+
+		let cycled: Vec<Color> = positions.iter()
+			.map(|&i| colors[i].1)
+			.collect();
+		for (n, &i) in positions.iter().enumerate() {
+			colors[i].1 = cycled[(n + len - offset) % len];
+		}
+	}
+	colors
+}
@@ -0,0 +1,271 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! A command line tool for inspecting, converting, and editing palette files.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+extern crate image;
+extern crate palette;
+
+// Local imports.
+use palette::*;
+use palette::diff::Change;
+use palette::extract::{from_pixels, Method};
+use palette::hex::{from_hex, Hex};
+use palette::operation::*;
+use palette::operation::script::Script;
+use palette::result::{Error, Result};
+
+// Standard imports.
+use std::env;
+use std::fs::File;
+use std::process;
+
+
+/// The command line usage message.
+const USAGE: &str = "\
+usage: rampeditor <command> [arguments]
+
+commands:
+    convert <input> <output>
+        Converts a palette to the format given by the output extension.
+    show <palette>
+        Prints the colors of a palette.
+    add <palette> <color>... [--at <address>]
+        Adds colors to a palette.
+    set <palette> <address> <color>
+        Sets the color at an address, replacing any existing color.
+    ramp <palette> <start> <end> <steps> [--at <address>]
+        Inserts a ramp between the colors at two addresses.
+    sort <palette> [hue|luminance] [--page <page>]
+        Sorts the colors of a palette.
+    script <palette> <script>
+        Applies the commands of a script file to a palette.
+    diff <palette> <palette>
+        Prints the differences between two palettes.
+    extract <image> <output> [--colors <count>] [--method median|kmeans]
+        Builds a palette from the colors of an image.
+
+Colors are given as hexadecimal values such as #1a2b3c. Addresses are given
+as page:line:column triples such as 0:0:15.";
+
+
+/// Returns an error result with the given message.
+fn fail<T>(message: &str) -> Result<T> {
+	Err(Error::Format {format: "rampeditor", reason: message.into()})
+}
+
+/// Parses a count or index.
+fn parse_number<T: std::str::FromStr>(text: &str) -> Result<T> {
+	text.parse().or_else(|_| fail(&format!("invalid number {:?}", text)))
+}
+
+/// Removes the value of the given option from the arguments, returning it.
+fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>> {
+	match args.iter().position(|arg| arg == name) {
+		Some(i) if i + 1 < args.len() => {
+			let value = args.remove(i + 1);
+			args.remove(i);
+			Ok(Some(value))
+		},
+		Some(_) => fail(&format!("missing value for {}", name)),
+		None => Ok(None),
+	}
+}
+
+/// Returns the positional arguments, or an error if there are not between the
+/// given number of them.
+fn positional(args: &[String], min: usize, max: usize) -> Result<&[String]> {
+	if args.len() < min || args.len() > max {
+		fail("wrong number of arguments")
+	} else {
+		Ok(args)
+	}
+}
+
+
+/// Prints the colors of the given palette data.
+fn show(palette: &Palette) {
+	let data = palette.data();
+	if let Some(name) = data.name(&Reference::all()) {
+		println!("{}", name);
+	}
+	for (address, cell) in data.iter() {
+		match cell.color() {
+			Some(color) => println!("{}  {}", address, Hex(color)),
+			None => println!("{}  (empty)", address),
+		}
+	}
+}
+
+/// Runs the given command with the given arguments.
+fn run(command: &str, mut args: Vec<String>) -> Result<()> {
+	match command {
+		"convert" => {
+			let args = positional(&args, 2, 2)?;
+			let palette = load(&args[0])?;
+			save(&args[1], &palette)
+		},
+
+		"show" => {
+			let args = positional(&args, 1, 1)?;
+			show(&load(&args[0])?);
+			Ok(())
+		},
+
+		"add" => {
+			let at = match take_option(&mut args, "--at")? {
+				Some(text) => Some(text.parse()?),
+				None => None,
+			};
+			let args = positional(&args, 2, usize::max_value())?;
+			let mut palette = load(&args[0])?;
+			for text in &args[1..] {
+				let mut op = InsertColor::new(from_hex(text)?);
+				if let Some(at) = at {
+					op = op.located_at(at);
+				}
+				palette.apply(Box::new(op))?;
+			}
+			save(&args[0], &palette)
+		},
+
+		"set" => {
+			let args = positional(&args, 3, 3)?;
+			let mut palette = load(&args[0])?;
+			let op = InsertColor::new(from_hex(&args[2])?)
+				.located_at(args[1].parse()?)
+				.overwrite(true);
+			palette.apply(Box::new(op))?;
+			save(&args[0], &palette)
+		},
+
+		"ramp" => {
+			let at = take_option(&mut args, "--at")?;
+			let args = positional(&args, 4, 4)?;
+			let mut palette = load(&args[0])?;
+			let mut op = CreateRamp::new(
+				args[1].parse()?,
+				args[2].parse()?,
+				parse_number(&args[3])?);
+			if let Some(at) = at {
+				op = op.located_at(at.parse()?);
+			}
+			palette.apply(Box::new(op))?;
+			save(&args[0], &palette)
+		},
+
+		"sort" => {
+			let page = take_option(&mut args, "--page")?;
+			let args = positional(&args, 1, 2)?;
+			let mut palette = load(&args[0])?;
+			let mut op = match args.get(1).map(|key| &key[..]) {
+				None | Some("hue") => SortCells::by_hue(),
+				Some("luminance") => SortCells::by_luminance(),
+				Some(key) => return fail(
+					&format!("unknown sort key {:?}", key)),
+			};
+			if let Some(page) = page {
+				op = op.page(parse_number(&page)?);
+			}
+			palette.apply(Box::new(op))?;
+			save(&args[0], &palette)
+		},
+
+		"script" => {
+			let args = positional(&args, 2, 2)?;
+			let mut palette = load(&args[0])?;
+			let script = Script::read(&mut File::open(&args[1])?)?;
+			let report = script.apply(&mut palette)?;
+			println!("applied {} operations", report.applied.len());
+			save(&args[0], &palette)
+		},
+
+		"diff" => {
+			let args = positional(&args, 2, 2)?;
+			let a = load(&args[0])?;
+			let b = load(&args[1])?;
+			for (address, change) in a.data().diff(b.data()).iter() {
+				match *change {
+					Change::Added(new) 
+						=> println!("{}  + {}", address, Hex(new)),
+					Change::Removed(old) 
+						=> println!("{}  - {}", address, Hex(old)),
+					Change::Changed {old, new} => println!(
+						"{}  {} -> {}",
+						address,
+						Hex(old),
+						Hex(new)),
+				}
+			}
+			Ok(())
+		},
+
+		"extract" => {
+			let colors = match take_option(&mut args, "--colors")? {
+				Some(text) => parse_number(&text)?,
+				None => 16,
+			};
+			let method = match take_option(&mut args, "--method")? {
+				None => Method::MedianCut,
+				Some(ref m) if m == "median" => Method::MedianCut,
+				Some(ref m) if m == "kmeans" => Method::KMeans,
+				Some(m) => return fail(&format!("unknown method {:?}", m)),
+			};
+			let args = positional(&args, 2, 2)?;
+			let image = match image::open(&args[0]) {
+				Ok(image) => image.to_rgb(),
+				Err(err) => return fail(&format!("{}", err)),
+			};
+			let pixels: Vec<Color> = image.pixels()
+				.map(|p| Color::new(p[0], p[1], p[2]))
+				.collect();
+			let data = from_pixels(&pixels, colors, method)?;
+
+			let mut palette = Palette::new("", Format::Default, false);
+			for (_, color) in data.colors() {
+				palette.apply(Box::new(InsertColor::new(color)))?;
+			}
+			save(&args[1], &palette)
+		},
+
+		_ => fail(&format!("unknown command {:?}", command)),
+	}
+}
+
+
+pub fn main() {
+	let mut args: Vec<String> = env::args().skip(1).collect();
+	if args.is_empty() || args[0] == "--help" || args[0] == "-h" {
+		println!("{}", USAGE);
+		return;
+	}
+	let command = args.remove(0);
+
+	if let Err(err) = run(&command, args) {
+		eprintln!("rampeditor: {}", err);
+		process::exit(1);
+	}
+}
@@ -28,28 +28,108 @@
 ////////////////////////////////////////////////////////////////////////////////
 
 // Local imports.
+use address::Address;
 use expression::Expression;
+use result::{Error, Result};
 
 // Non-local imports.
 use color::Color;
 
 // Standard imports.
-use std::cell::RefCell;
-use std::ops::{
-	Deref,
-	DerefMut,
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+#[cfg(not(feature = "sync"))]
+use std::cell::{
+	RefCell as Lock,
+	Ref as ReadGuard,
+	RefMut as WriteGuard,
 };
+#[cfg(feature = "sync")]
+use std::sync::{
+	PoisonError,
+	RwLock as Lock,
+	RwLockReadGuard as ReadGuard,
+	RwLockWriteGuard as WriteGuard,
+};
+use std::collections::BTreeMap;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Shared reference re-exports.
+/// The shared pointer type used to hold `Cell`s. This is `Rc` by default, or 
+/// `Arc` if the `sync` feature is enabled.
+#[cfg(not(feature = "sync"))]
+pub use std::rc::Rc as Shared;
+/// The shared pointer type used to hold `Cell`s. This is `Rc` by default, or 
+/// `Arc` if the `sync` feature is enabled.
+#[cfg(feature = "sync")]
+pub use std::sync::Arc as Shared;
+/// The weak pointer type used to reference `Cell`s.
+#[cfg(not(feature = "sync"))]
+pub use std::rc::Weak;
+/// The weak pointer type used to reference `Cell`s.
+#[cfg(feature = "sync")]
+pub use std::sync::Weak;
+
+
+/// The default maximum number of source cells followed in any chain when 
+/// resolving the color of a cell.
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+
+/// Immutably borrows the contents of the given lock.
+#[cfg(not(feature = "sync"))]
+fn read<T>(lock: &Lock<T>) -> ReadGuard<T> {
+	lock.borrow()
+}
+
+/// Immutably borrows the contents of the given lock.
+#[cfg(feature = "sync")]
+fn read<T>(lock: &Lock<T>) -> ReadGuard<T> {
+	lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Mutably borrows the contents of the given lock.
+#[cfg(not(feature = "sync"))]
+fn write<T>(lock: &Lock<T>) -> WriteGuard<T> {
+	lock.borrow_mut()
+}
+
+/// Mutably borrows the contents of the given lock.
+#[cfg(feature = "sync")]
+fn write<T>(lock: &Lock<T>) -> WriteGuard<T> {
+	lock.write().unwrap_or_else(PoisonError::into_inner)
+}
 
 
 
 ////////////////////////////////////////////////////////////////////////////////
 // Cell
 ////////////////////////////////////////////////////////////////////////////////
-/// A wrapper around a `Expression` for enabling interior mutability.
-#[derive(Debug, Clone)]
+/// A wrapper around a `Expression` for enabling interior mutability. The 
+/// `Expression` is held in a `RefCell` by default, or in an `RwLock` if the 
+/// `sync` feature is enabled.
+///
+/// The resolved color of the cell is cached, so repeatedly resolving derived
+/// colors does not walk their chains of source cells. When a cell is mutably
+/// borrowed or dropped, only its own cached color and those of the cells 
+/// derived from it are discarded.
+///
+/// Cells are shared with the checkpoints of the palette data holding them. 
+/// When a cell is mutably borrowed while a checkpoint which has not seen the
+/// change is alive, its previous expression is kept for that checkpoint.
+#[derive(Debug)]
 pub struct Cell {
 	/// The `Expression` being wrapped.
-	expr: RefCell<Expression>,
+	expr: Lock<Expression>,
+	/// The cache of the resolved color, shared with the caches of the cells
+	/// this cell's color was resolved from.
+	cache: Shared<Cache>,
+	/// The palette data holding the cell.
+	owner: Lock<Option<Owner>>,
+	/// The previous expressions of the cell, kept for live checkpoints.
+	versions: Lock<Vec<Version>>,
 }
 
 
@@ -57,29 +137,388 @@ impl Cell {
 	/// Creates a new `Cell` wrapping the given `Expression`.
 	pub fn new(element: Expression) -> Self {
 		Cell {
-			expr: RefCell::new(element),
+			expr: Lock::new(element),
+			cache: Shared::new(Cache::default()),
+			owner: Lock::new(None),
+			versions: Lock::new(Vec::new()),
+		}
+	}
+
+	/// Immutably borrows the wrapped `Expression`.
+	pub fn borrow(&self) -> ReadGuard<Expression> {
+		read(&self.expr)
+	}
+
+	/// Mutably borrows the wrapped `Expression`.
+	pub fn borrow_mut(&self) -> CellWriteGuard {
+		self.preserve();
+		self.cache.invalidate();
+		CellWriteGuard {
+			guard: Some(write(&self.expr)),
+			cell: self,
+		}
+	}
+
+	/// Sets the vacancy log to notify when the cell is emptied, along with the
+	/// cell's address, or clears it if the cell is no longer held. The current
+	/// expression is kept for the previous owner's live checkpoints.
+	pub(crate) fn set_owner(
+		&self, 
+		owner: Option<(&Shared<VacancyLog>, Address)>)
+	{
+		self.preserve();
+		*write(&self.owner) = owner.map(|(log, address)| Owner {
+			log: Shared::downgrade(log),
+			address: address,
+			epoch: log.next_epoch(),
+		});
+	}
+
+	/// Records the cell's address in its owner's vacancy log.
+	fn record_vacancy(&self) {
+		if let Some(ref owner) = *read(&self.owner) {
+			if let Some(log) = owner.log.upgrade() {
+				write(&log.addresses).push(owner.address);
+			}
+		}
+	}
+
+	/// Keeps the current expression for the owner's checkpoints taken since 
+	/// it was last kept, before the expression is changed. Expressions kept 
+	/// for checkpoints which have since been dropped are discarded.
+	fn preserve(&self) {
+		let mut guard = write(&self.owner);
+		let owner = match *guard {
+			Some(ref mut owner) => owner,
+			None => return,
+		};
+		let log = match owner.log.upgrade() {
+			Some(log) => log,
+			None => return,
+		};
+
+		let mut versions = write(&self.versions);
+		versions.retain(|version| version.log.upgrade()
+			.map_or(false, |l| l.has_checkpoints(version.from, version.to)));
+
+		let next = log.next_epoch();
+		if log.has_checkpoints(owner.epoch, next) {
+			versions.push(Version {
+				log: owner.log.clone(),
+				from: owner.epoch,
+				to: next,
+				expr: self.borrow().clone(),
+			});
+		}
+		owner.epoch = next;
+	}
+
+	/// Returns the expression of the cell as seen by the checkpoint taken at 
+	/// the given epoch of the given vacancy log.
+	pub(crate) fn expression_at(&self, log: &Shared<VacancyLog>, epoch: usize)
+		-> Expression
+	{
+		let versions = read(&self.versions);
+		let kept = versions.iter().find(|version| {
+			version.from <= epoch && epoch < version.to 
+				&& version.log.upgrade()
+					.map_or(false, |l| Shared::ptr_eq(&l, log))
+		});
+		match kept {
+			Some(version) => version.expr.clone(),
+			None => self.borrow().clone(),
 		}
 	}
 
 	/// Returns the `Color` of the internal `Expression`, or `None` if it is 
-	/// invalid.
+	/// invalid or if it depends on a chain of more than `DEFAULT_MAX_DEPTH`
+	/// source cells. Use `resolve` or `Data::resolve` to apply another limit
+	/// or to distinguish chains which are too long.
 	pub fn color(&self) -> Option<Color> {
-		self.expr.borrow().color()
+		self.resolve(DEFAULT_MAX_DEPTH).unwrap_or(None)
+	}
+
+	/// Returns the `Color` of the internal `Expression`, or `None` if it is 
+	/// invalid. Returns a `MaxOrderExceeded` error if the expression depends
+	/// on a chain of more than the given number of source cells.
+	pub fn resolve(&self, max_depth: usize) -> Result<Option<Color>> {
+		self.resolve_depth(max_depth).map(|(color, _)| color)
+	}
+
+	/// Returns the derivation order of the cell; the length of the longest 
+	/// chain of source cells its color depends on. Fixed colors have order 0,
+	/// and colors derived from them have order 1. The order is recomputed 
+	/// whenever any cell in the chain changes. Returns a `MaxOrderExceeded` 
+	/// error if the chain is longer than `DEFAULT_MAX_DEPTH`.
+	pub fn order(&self) -> Result<usize> {
+		self.resolve_depth(DEFAULT_MAX_DEPTH).map(|(_, depth)| depth)
+	}
+
+	/// Returns the `Color` of the internal `Expression` along with the length
+	/// of the longest chain of source cells it depends on. Returns a 
+	/// `MaxOrderExceeded` error if the chain is longer than the given depth.
+	pub(crate) fn resolve_depth(&self, max_depth: usize) 
+		-> Result<(Option<Color>, usize)>
+	{
+		// Read the generation first, so that changes made during resolution 
+		// will prevent the result from being cached.
+		let generation = self.cache.generation.load(Ordering::SeqCst);
+		if let Some(resolved) = *read(&self.cache.resolved) {
+			if resolved.depth > max_depth {
+				return Err(Error::MaxOrderExceeded);
+			}
+			return Ok((resolved.color, resolved.depth));
+		}
+
+		let expr = self.borrow();
+		// Register with the sources before resolving them, so that any 
+		// change to them will invalidate this cell.
+		for source in expr.sources() {
+			if let Some(source) = source.upgrade() {
+				source.cache.add_dependent(&self.cache);
+			}
+		}
+		let (color, depth) = expr.resolve_depth(max_depth)?;
+		self.cache.store(generation, Resolved {color: color, depth: depth});
+		Ok((color, depth))
+	}
+}
+
+
+impl Drop for Cell {
+	/// Invalidates the cached colors of the cells derived from this cell.
+	fn drop(&mut self) {
+		self.cache.invalidate();
 	}
 }
 
 
-impl Deref for Cell {
-	type Target = RefCell<Expression>;
-	fn deref(&self) -> &Self::Target {
-		&self.expr
+impl Clone for Cell {
+	fn clone(&self) -> Self {
+		Cell::new(self.borrow().clone())
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// CellWriteGuard
+////////////////////////////////////////////////////////////////////////////////
+/// A mutable borrow of a `Cell`'s `Expression`, returned by 
+/// `Cell::borrow_mut`.
+///
+/// The cached colors of the cell and of the cells derived from it are 
+/// invalidated both when the borrow is taken and after it is released, so 
+/// that a color resolved concurrently from the old expression is never 
+/// treated as current.
+#[derive(Debug)]
+pub struct CellWriteGuard<'a> {
+	/// The underlying guard, taken when the borrow is released.
+	guard: Option<WriteGuard<'a, Expression>>,
+	/// The borrowed cell.
+	cell: &'a Cell,
+}
+
+
+impl<'a> Deref for CellWriteGuard<'a> {
+	type Target = Expression;
+
+	fn deref(&self) -> &Expression {
+		self.guard.as_ref().expect("live cell write guard")
 	}
 }
 
 
-impl DerefMut for Cell {
-	fn deref_mut(&mut self) -> &mut Self::Target {
-		&mut self.expr
+impl<'a> DerefMut for CellWriteGuard<'a> {
+	fn deref_mut(&mut self) -> &mut Expression {
+		self.guard.as_mut().expect("live cell write guard")
 	}
 }
 
+
+impl<'a> Drop for CellWriteGuard<'a> {
+	/// Releases the borrow, then invalidates cached colors and records the
+	/// slot as free if the cell was emptied.
+	fn drop(&mut self) {
+		let emptied = self.guard.take().map_or(false, |guard| match *guard {
+			Expression::Empty => true,
+			_ => false,
+		});
+		self.cell.cache.invalidate();
+		if emptied { self.cell.record_vacancy(); }
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Cache
+////////////////////////////////////////////////////////////////////////////////
+/// The resolution cache of a `Cell`. 
+///
+/// Each cache records the caches of the cells whose colors were resolved from
+/// it. Invalidating a cache marks those dependents dirty in turn, so changes 
+/// are propagated only to the colors they affect. Dependents register again
+/// whenever they are next resolved.
+#[derive(Debug, Default)]
+struct Cache {
+	/// The most recently resolved color, or None if it must be resolved again.
+	resolved: Lock<Option<Resolved>>,
+	/// The caches of the cells whose cached colors depend on this cell.
+	dependents: Lock<Vec<Weak<Cache>>>,
+	/// Incremented whenever the cache is invalidated, so that resolutions 
+	/// begun before an invalidation are not stored.
+	generation: AtomicUsize,
+}
+
+
+impl Cache {
+	/// Stores the given resolution if the cache has not been invalidated 
+	/// since the given generation.
+	fn store(&self, generation: usize, resolved: Resolved) {
+		let mut current = write(&self.resolved);
+		if self.generation.load(Ordering::SeqCst) == generation {
+			*current = Some(resolved);
+		}
+	}
+
+	/// Records that the given cache was resolved from this cache.
+	fn add_dependent(&self, dependent: &Shared<Cache>) {
+		let mut dependents = write(&self.dependents);
+		let known = dependents.iter()
+			.filter_map(|d| d.upgrade())
+			.any(|d| Shared::ptr_eq(&d, dependent));
+		if !known {
+			dependents.retain(|d| d.upgrade().is_some());
+			dependents.push(Shared::downgrade(dependent));
+		}
+	}
+
+	/// Discards the cached colors of this cache and of every cache resolved 
+	/// from it, directly or indirectly.
+	fn invalidate(&self) {
+		self.clear();
+		let mut stack = self.take_dependents();
+		while let Some(cache) = stack.pop() {
+			cache.clear();
+			stack.extend(cache.take_dependents());
+		}
+	}
+
+	/// Discards the cached color.
+	fn clear(&self) {
+		self.generation.fetch_add(1, Ordering::SeqCst);
+		*write(&self.resolved) = None;
+	}
+
+	/// Removes and returns the live dependents of the cache.
+	fn take_dependents(&self) -> Vec<Shared<Cache>> {
+		mem::replace(&mut *write(&self.dependents), Vec::new())
+			.into_iter()
+			.filter_map(|dependent| dependent.upgrade())
+			.collect()
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Owner
+////////////////////////////////////////////////////////////////////////////////
+/// The palette data holding a `Cell`.
+#[derive(Debug)]
+struct Owner {
+	/// The vacancy log of the palette data.
+	log: Weak<VacancyLog>,
+	/// The cell's address within the palette data.
+	address: Address,
+	/// The first checkpoint epoch for which the cell's current expression has
+	/// not been kept.
+	epoch: usize,
+}
+
+
+/// A previous expression of a `Cell`, kept for the checkpoints taken while it
+/// was current.
+#[derive(Debug)]
+struct Version {
+	/// The vacancy log of the palette data the checkpoints were taken of.
+	log: Weak<VacancyLog>,
+	/// The first checkpoint epoch which saw the expression.
+	from: usize,
+	/// The first checkpoint epoch which did not see the expression.
+	to: usize,
+	/// The expression.
+	expr: Expression,
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// VacancyLog
+////////////////////////////////////////////////////////////////////////////////
+/// A record of the addresses of cells emptied through `Cell::borrow_mut`, so
+/// that the palette data holding them can find the freed slots. The log also
+/// counts the live checkpoints of the palette data, so that its cells know 
+/// which previous expressions to keep.
+#[derive(Debug, Default)]
+pub(crate) struct VacancyLog {
+	/// The addresses of the emptied cells.
+	addresses: Lock<Vec<Address>>,
+	/// The epoch of the next checkpoint.
+	epoch: AtomicUsize,
+	/// The number of live checkpoints taken at each epoch.
+	checkpoints: Lock<BTreeMap<usize, usize>>,
+}
+
+
+impl VacancyLog {
+	/// Registers a new checkpoint, returning its epoch.
+	pub(crate) fn begin_checkpoint(&self) -> usize {
+		let epoch = self.epoch.fetch_add(1, Ordering::SeqCst);
+		*write(&self.checkpoints).entry(epoch).or_insert(0) += 1;
+		epoch
+	}
+
+	/// Unregisters a checkpoint taken at the given epoch.
+	pub(crate) fn end_checkpoint(&self, epoch: usize) {
+		let mut checkpoints = write(&self.checkpoints);
+		let done = match checkpoints.get_mut(&epoch) {
+			Some(count) => { *count -= 1; *count == 0 },
+			None => false,
+		};
+		if done { checkpoints.remove(&epoch); }
+	}
+
+	/// Returns the epoch of the next checkpoint.
+	fn next_epoch(&self) -> usize {
+		self.epoch.load(Ordering::SeqCst)
+	}
+
+	/// Returns whether any checkpoint taken between the given epochs, 
+	/// including the first but not the last, is alive.
+	fn has_checkpoints(&self, from: usize, to: usize) -> bool {
+		from < to && read(&self.checkpoints).range(from..to).next().is_some()
+	}
+
+	/// Removes and returns the recorded addresses.
+	pub(crate) fn take(&self) -> Vec<Address> {
+		mem::replace(&mut *write(&self.addresses), Vec::new())
+	}
+
+	/// Returns the recorded addresses without removing them.
+	pub(crate) fn pending(&self) -> Vec<Address> {
+		read(&self.addresses).clone()
+	}
+}
+
+
+
+/// A cached resolution of a `Cell`'s color.
+#[derive(Debug, Clone, Copy)]
+struct Resolved {
+	/// The resolved color.
+	color: Option<Color>,
+	/// The length of the longest chain of source cells the color depends on.
+	depth: usize,
+}
@@ -0,0 +1,265 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides checkpoints for saving and restoring palette data.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{Address, Reference};
+use cell::{Cell, Shared, VacancyLog};
+use data::{Data, MetaData};
+use expression::Expression;
+use result::{Error, Result};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+use std::fmt;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Checkpoint
+////////////////////////////////////////////////////////////////////////////////
+/// A read-only view of palette data at a point in time. 
+///
+/// A checkpoint shares its cells with the palette data it was taken from, so
+/// taking one does not copy any cells. When a cell of the palette data is 
+/// later changed, replaced, or removed, the palette data copies its map of 
+/// cells and the cell keeps its previous expression for the checkpoint, so the
+/// checkpoint is unaffected by later edits, including those made through the
+/// handles returned by `Data::cell`. It may be restored with `Data::restore` 
+/// or copied out with `to_data`.
+///
+/// Clones of a checkpoint share the same view. Previous expressions kept for
+/// a checkpoint are discarded once it and its clones are dropped.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::data::Data;
+/// use palette::{Address, Color, Expression};
+/// 
+/// let mut dat: Data = Default::default();
+/// let a = dat.add_color(Color::new(12, 50, 78)).unwrap();
+/// let b = dat.add_color(Color::new(1, 2, 3)).unwrap();
+///
+/// let checkpoint = dat.checkpoint();
+/// dat.add_color(Color::new(60, 25, 17)).unwrap();
+/// dat.remove_cell(a).unwrap();
+/// *dat.cell(b).unwrap().borrow_mut() = 
+/// 	Expression::Color(Color::new(4, 5, 6));
+///
+/// assert_eq!(checkpoint.len(), 2);
+/// assert_eq!(checkpoint.color(a), Some(Color::new(12, 50, 78)));
+/// assert_eq!(checkpoint.color(b), Some(Color::new(1, 2, 3)));
+/// assert_eq!(dat.diff(&checkpoint.to_data()).len(), 3);
+///
+/// dat.restore(&checkpoint);
+/// assert_eq!(dat, checkpoint.to_data());
+/// ```
+#[derive(Clone)]
+pub struct Checkpoint {
+	/// The shared contents of the checkpoint.
+	inner: Shared<Inner>,
+}
+
+
+/// The contents of a `Checkpoint`, shared between its clones.
+struct Inner {
+	/// The palette data as of the checkpoint. Its cells are shared with the 
+	/// palette data the checkpoint was taken of, so their expressions must be
+	/// read through `Cell::expression_at`.
+	data: Data,
+	/// The vacancy log of the palette data the checkpoint was taken of.
+	log: Shared<VacancyLog>,
+	/// The epoch of the checkpoint within the vacancy log.
+	epoch: usize,
+	/// The addresses of cells which were emptied but not yet marked free.
+	vacancies: Vec<Address>,
+}
+
+
+impl Drop for Inner {
+	fn drop(&mut self) {
+		self.log.end_checkpoint(self.epoch);
+	}
+}
+
+
+impl Checkpoint {
+	/// Creates a new `Checkpoint` of the given palette data.
+	pub(crate) fn new(data: &Data) -> Self {
+		let log = data.vacancies.clone();
+		let epoch = log.begin_checkpoint();
+		Checkpoint {
+			inner: Shared::new(Inner {
+				data: Data {
+					cells: data.cells.clone(),
+					names: data.names.clone(),
+					metadata: data.metadata.clone(),
+					maximum_page_count: data.maximum_page_count,
+					default_line_count: data.default_line_count,
+					default_column_count: data.default_column_count,
+					max_depth: data.max_depth,
+					cycles: data.cycles.clone(),
+					ramps: data.ramps.clone(),
+					constraints: data.constraints.clone(),
+					schema: data.schema.clone(),
+					free_hint: data.free_hint,
+					occupancy: data.occupancy.clone(),
+					.. Default::default()
+				},
+				vacancies: log.pending(),
+				log: log,
+				epoch: epoch,
+			}),
+		}
+	}
+
+	/// Returns the number of cells in the checkpoint.
+	pub fn len(&self) -> usize {
+		self.inner.data.len()
+	}
+
+	/// Returns whether the checkpoint holds no cells.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Returns the occupied addresses of the checkpoint, in address order.
+	pub fn addresses(&self) -> Vec<Address> {
+		self.inner.data.cells.keys().collect()
+	}
+
+	/// Returns the expression of the cell at the given address, or None if 
+	/// the address is empty.
+	pub fn expression(&self, address: Address) -> Option<Expression> {
+		self.inner.data.cells.get(&address).map(|cell| self.expression_of(cell))
+	}
+
+	/// Returns the color of the cell at the given address, or None if the 
+	/// address is empty or the color is invalid. Returns a `MaxOrderExceeded`
+	/// error if the color depends on a chain of more source cells than the 
+	/// palette's maximum depth.
+	pub fn resolve(&self, address: Address) -> Result<Option<Color>> {
+		match self.inner.data.cells.get(&address) {
+			Some(cell) => self
+				.resolve_depth(cell, self.inner.data.max_depth)
+				.map(|(color, _)| color),
+			None => Ok(None),
+		}
+	}
+
+	/// Returns the color of the cell at the given address, or None if the 
+	/// address is empty, the color is invalid, or it depends on too long a 
+	/// chain of source cells.
+	pub fn color(&self, address: Address) -> Option<Color> {
+		self.resolve(address).unwrap_or(None)
+	}
+
+	/// Returns the metadata associated with the given group, or None if it had
+	/// none.
+	pub fn metadata(&self, group: &Reference) -> Option<&MetaData> {
+		self.inner.data.metadata(group)
+	}
+
+	/// Returns the name of the given group, or None if it had no name.
+	pub fn name(&self, group: &Reference) -> Option<&str> {
+		self.inner.data.name(group)
+	}
+
+	/// Returns a separate copy of the checkpoint's palette data.
+	pub fn to_data(&self) -> Data {
+		self.inner.data.copy_with(
+			|cell| self.expression_of(cell), 
+			&self.inner.vacancies)
+	}
+
+	/// Returns whether the given checkpoints share the same view of palette 
+	/// data.
+	pub fn ptr_eq(a: &Checkpoint, b: &Checkpoint) -> bool {
+		Shared::ptr_eq(&a.inner, &b.inner)
+	}
+
+	/// Returns the expression of the given cell as of the checkpoint.
+	fn expression_of(&self, cell: &Cell) -> Expression {
+		cell.expression_at(&self.inner.log, self.inner.epoch)
+	}
+
+	/// Returns the color of the given cell as of the checkpoint, along with 
+	/// the length of the longest chain of source cells it depends on. Returns
+	/// a `MaxOrderExceeded` error if the chain is longer than the given depth.
+	fn resolve_depth(&self, cell: &Cell, max_depth: usize) 
+		-> Result<(Option<Color>, usize)>
+	{
+		let expr = self.expression_of(cell);
+		if let Expression::Frozen {color, ..} = expr {
+			return Ok((Some(color), 0));
+		}
+		let sources = expr.sources();
+		if sources.is_empty() {
+			return Ok((expr.resolve_with(&[]), 0));
+		}
+		if max_depth == 0 {
+			return Err(Error::MaxOrderExceeded);
+		}
+
+		let mut depth = 0;
+		let mut colors = Vec::with_capacity(sources.len());
+		for source in sources {
+			match source.upgrade() {
+				Some(source) => {
+					let (color, d) = self
+						.resolve_depth(&source, max_depth - 1)?;
+					depth = depth.max(d + 1);
+					colors.push(color);
+				},
+				None => {
+					depth = depth.max(1);
+					colors.push(None);
+				},
+			}
+		}
+		Ok((expr.resolve_with(&colors), depth))
+	}
+}
+
+
+impl PartialEq for Checkpoint {
+	fn eq(&self, other: &Checkpoint) -> bool {
+		Checkpoint::ptr_eq(self, other) || self.to_data() == other.to_data()
+	}
+}
+
+
+impl fmt::Debug for Checkpoint {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_tuple("Checkpoint").field(&self.to_data()).finish()
+	}
+}
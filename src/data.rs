@@ -29,52 +29,211 @@
 
 // Local imports.
 use address::{
+	component_indices,
 	Address,
 	Reference,
+	ReferenceComponent,
 	Page, Line, Column, 
 	PAGE_MAX, LINE_MAX, COLUMN_MAX,
 };
-use cell::Cell;
+use analysis;
+use analysis::Analysis;
+use animation::Cycle;
+use cell::{Cell, Shared, VacancyLog, Weak, DEFAULT_MAX_DEPTH};
+use diff::PaletteDiff;
+use hex::Hex;
+use meta::{MetaKind, MetaSchema, MetaType, MetaValue};
+#[cfg(feature = "std")]
+use display::term::Preview;
 use expression::Expression;
+#[cfg(feature = "std")]
+use format::{native, ParseOptions};
+use operation::{MaybeSync, Transaction};
+use ramp::RampSpec;
+use checkpoint::Checkpoint;
+use space::{contrast_ratio, Easing, Metric};
+use storage::{Cells, Storage};
+use utilities::{filter_pairs, unix_time, wildcard_match, Fnv64};
+use view::{LineView, PageView};
+use vision;
+use vision::{Deficiency, DistinguishabilityReport};
 use result::{
 	Error,
 	Result,
 };
 
+// Non-local imports.
+use color::Color;
+
 // Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+use std::cmp::Ordering;
+#[cfg(feature = "std")]
+use std::io;
 use std::collections::{
 	BTreeMap,
 	BTreeSet,
 	HashMap,
+	HashSet,
 };
-use std::rc::Rc;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::mem;
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Prepare
+////////////////////////////////////////////////////////////////////////////////
+/// A callback which initializes the metadata of a newly created page or line.
+/// Implemented for all closures taking the palette data and the group being 
+/// initialized.
+pub trait Prepare: MaybeSync {
+	/// Initializes the given group in the given palette data.
+	fn prepare(&mut self, data: &mut Data, group: &Reference);
+}
+
+
+impl<F> Prepare for F where F: FnMut(&mut Data, &Reference) + MaybeSync {
+	fn prepare(&mut self, data: &mut Data, group: &Reference) {
+		self(data, group)
+	}
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Constraint
+////////////////////////////////////////////////////////////////////////////////
+/// A restriction on the changes which may be made to a palette slot.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Constraint {
+	/// The slot may not be changed or removed, and will not be filled when 
+	/// searching for free addresses.
+	Locked,
+	/// The slot may be changed, but may not be removed or moved.
+	Protected,
+	/// The slot is reserved by the palette's hardware or format, and may not
+	/// hold a color. It is otherwise treated as a locked slot.
+	Reserved,
+}
 
 
 
-/// Default function for `prepare_new_page` and `prepare_new_line` triggers.
-#[allow(unused_variables)]
-fn no_op(_: &mut Data, _: &Reference) {}
+////////////////////////////////////////////////////////////////////////////////
+// DependentPolicy
+////////////////////////////////////////////////////////////////////////////////
+/// The handling of the derived cells which reference a removed cell. Derived
+/// cells always follow changes to the expressions of the cells they 
+/// reference, so only removal needs a policy.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DependentPolicy {
+	/// The cell may not be removed while it is referenced. The error lists the
+	/// referencing cells.
+	Prevent,
+	/// The referencing cells are removed with the cell, along with any cells 
+	/// referencing them in turn.
+	Cascade,
+	/// The referencing cells are replaced by their last resolved colors.
+	Freeze,
+}
+
+
+impl Default for DependentPolicy {
+	fn default() -> Self {
+		DependentPolicy::Prevent
+	}
+}
+
 
 
 ////////////////////////////////////////////////////////////////////////////////
 // MetaData
 ////////////////////////////////////////////////////////////////////////////////
 /// Provides metadata about palette data.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MetaData {
 	/// A format-generated label for the item.
 	pub format_label: Option<String>,
 
 	/// A user-provided name for the item.
 	pub name: Option<String>,
+
+	/// User-provided tags for the item.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub tags: Vec<String>,
+
+	/// User-provided freeform notes for the item.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub notes: Option<String>,
 	
 	/// An override to the default line count for this group.
 	pub line_count: Line,
 	
 	/// An override to the default column count for this group.
 	pub column_count: Column,
+
+	/// The time the item was created, in seconds since the Unix epoch.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub created: Option<u64>,
+
+	/// The time the item was last modified, in seconds since the Unix epoch.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub modified: Option<u64>,
+
+	/// The author of the item.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub author: Option<String>,
+
+	/// The path of the file the item was loaded from.
+	#[cfg(feature = "std")]
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub source: Option<PathBuf>,
+
+	/// Application-defined properties of the item.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub properties: BTreeMap<String, String>,
+
+	/// Typed metadata values of the item, stored under keys registered in the
+	/// palette's `MetaSchema`.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub values: BTreeMap<String, MetaValue>,
+}
+
+
+impl PartialEq for MetaData {
+	/// Compares the metadata, ignoring its timestamps.
+	fn eq(&self, other: &MetaData) -> bool {
+		self.format_label == other.format_label &&
+		self.name == other.name &&
+		self.tags == other.tags &&
+		self.notes == other.notes &&
+		self.line_count == other.line_count &&
+		self.column_count == other.column_count &&
+		self.author == other.author &&
+		self.same_source(other) &&
+		self.properties == other.properties &&
+		self.values == other.values
+	}
+}
+
+impl MetaData {
+	/// Returns whether the metadata have the same source path.
+	#[cfg(feature = "std")]
+	fn same_source(&self, other: &MetaData) -> bool {
+		self.source == other.source
+	}
+
+	/// Returns true, as source paths are not recorded without `std`.
+	#[cfg(not(feature = "std"))]
+	fn same_source(&self, _: &MetaData) -> bool {
+		true
+	}
 }
 
 impl fmt::Display for MetaData {
@@ -86,6 +245,14 @@ impl fmt::Display for MetaData {
 			(Some(name), None)		  => write!(f, "\"{}\"", name),
 			_						  => Ok(())
 		}?;
+
+		if !self.tags.is_empty() {
+			write!(f, " [Tags: {}]", self.tags.join(", "))?;
+		}
+
+		if let Some(ref author) = self.author {
+			write!(f, " [Author: {}]", author)?;
+		}
 		
 		write!(f, " [Lines: {}] [Columns: {}]", 
 			self.line_count, 
@@ -96,102 +263,2301 @@ impl fmt::Display for MetaData {
 
 
 
+////////////////////////////////////////////////////////////////////////////////
+// Occupancy
+////////////////////////////////////////////////////////////////////////////////
+/// A secondary index over the cells of the palette, tracking the number of 
+/// cells on each page, which columns of each line hold a cell, and which of 
+/// those cells are known to hold a non-empty expression.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Occupancy {
+	/// The number of cells on each occupied page.
+	pages: HashMap<Page, usize>,
+	/// A bitset of the occupied columns of each occupied line.
+	lines: HashMap<(Page, Line), [u64; 4]>,
+	/// A bitset of the columns of each line holding a non-empty expression.
+	/// A cleared bit only means the slot may be free.
+	filled: HashMap<(Page, Line), [u64; 4]>,
+}
+
+impl Occupancy {
+	/// Records a cell at the given address. Returns false if the address was
+	/// already recorded.
+	fn insert(&mut self, address: Address) -> bool {
+		let (word, bit) = Occupancy::bit(address.column);
+		let bits = self.lines
+			.entry((address.page, address.line))
+			.or_insert([0; 4]);
+		if bits[word] & bit != 0 { return false; }
+		bits[word] |= bit;
+		*self.pages.entry(address.page).or_insert(0) += 1;
+		true
+	}
+
+	/// Removes the cell at the given address. Returns false if the address was
+	/// not recorded.
+	fn remove(&mut self, address: Address) -> bool {
+		let (word, bit) = Occupancy::bit(address.column);
+		let line = (address.page, address.line);
+		let line_empty = match self.lines.get_mut(&line) {
+			Some(bits) if bits[word] & bit != 0 => {
+				bits[word] &= !bit;
+				bits.iter().all(|&w| w == 0)
+			},
+			_ => return false,
+		};
+		if line_empty {
+			self.lines.remove(&line);
+		}
+		self.set_filled(address, false);
+		let page_empty = {
+			let count = self.pages.get_mut(&address.page)
+				.expect("occupied page count");
+			*count -= 1;
+			*count == 0
+		};
+		if page_empty {
+			self.pages.remove(&address.page);
+		}
+		true
+	}
+
+	/// Returns whether a cell is recorded at the given address.
+	fn contains(&self, address: Address) -> bool {
+		let (word, bit) = Occupancy::bit(address.column);
+		self.lines
+			.get(&(address.page, address.line))
+			.map_or(false, |bits| bits[word] & bit != 0)
+	}
+
+	/// Returns the number of cells on the given page.
+	fn page_len(&self, page: Page) -> usize {
+		self.pages.get(&page).cloned().unwrap_or(0)
+	}
+
+	/// Returns the number of cells on the given line.
+	fn line_len(&self, page: Page, line: Line) -> usize {
+		self.lines
+			.get(&(page, line))
+			.map_or(0, |bits| bits.iter()
+				.map(|w| w.count_ones() as usize)
+				.sum())
+	}
+
+	/// Records whether the slot at the given address holds a non-empty 
+	/// expression.
+	fn set_filled(&mut self, address: Address, filled: bool) {
+		let (word, bit) = Occupancy::bit(address.column);
+		let key = (address.page, address.line);
+		if filled {
+			self.filled.entry(key).or_insert([0; 4])[word] |= bit;
+			return;
+		}
+		let line_empty = match self.filled.get_mut(&key) {
+			Some(bits) => {
+				bits[word] &= !bit;
+				bits.iter().all(|&w| w == 0)
+			},
+			None => false,
+		};
+		if line_empty {
+			self.filled.remove(&key);
+		}
+	}
+
+	/// Returns the first column of the given line at or after the given 
+	/// column and before the given column count which is not known to hold a
+	/// non-empty expression.
+	fn first_unfilled(
+		&self, 
+		page: Page, 
+		line: Line, 
+		from: usize, 
+		columns: Column) 
+		-> Option<Column>
+	{
+		let columns = columns as usize;
+		if from >= columns { return None; }
+		let bits = match self.filled.get(&(page, line)) {
+			Some(bits) => bits,
+			None => return Some(from as Column),
+		};
+		let mut mask = !0u64 << (from % 64);
+		for word in from / 64..bits.len() {
+			let vacant = !bits[word] & mask;
+			if vacant != 0 {
+				let column = word * 64 + vacant.trailing_zeros() as usize;
+				if column >= columns { return None; }
+				return Some(column as Column);
+			}
+			mask = !0;
+		}
+		None
+	}
+
+	/// Returns the word index and bit mask for the given column.
+	fn bit(column: Column) -> (usize, u64) {
+		(column as usize / 64, 1 << (column % 64))
+	}
+}
+
+
+
+/// Returns the given reference with its page replaced by the given page.
+fn with_page(group: &Reference, page: Page) -> Reference {
+	Reference {
+		page: ReferenceComponent::Index(page),
+		.. group.clone()
+	}
+}
+
+
+/// Returns the addresses reachable from the given address along the given 
+/// edges, excluding the address itself, in address order.
+fn reachable<I>(edges: I, start: Address) -> Vec<Address>
+	where I: IntoIterator<Item=(Address, Address)>
+{
+	let mut adjacent: BTreeMap<Address, Vec<Address>> = BTreeMap::new();
+	for (from, to) in edges {
+		adjacent.entry(from).or_insert_with(Vec::new).push(to);
+	}
+
+	let mut visited = BTreeSet::new();
+	let mut stack = vec![start];
+	while let Some(address) = stack.pop() {
+		for &next in adjacent.get(&address).into_iter().flat_map(|v| v) {
+			if next != start && visited.insert(next) {
+				stack.push(next);
+			}
+		}
+	}
+	visited.into_iter().collect()
+}
+
+
+
 ////////////////////////////////////////////////////////////////////////////////
 // Data
 ////////////////////////////////////////////////////////////////////////////////
 /// Encapsulates a single palette's operation-relevant data.
 pub struct Data {
-	/// A map assigning addresses to `Palette` cells.
-	pub cells: BTreeMap<Address, Rc<Cell>>,
+	/// A map assigning addresses to `Palette` cells. The map is shared with
+	/// the palette's checkpoints, and is copied when it is changed while 
+	/// shared.
+	pub(crate) cells: Shared<Cells>,
 
 	/// A map assigning references to names.
-	pub names: HashMap<String, Reference>,
+	pub(crate) names: HashMap<String, Reference>,
 
 	/// A map assigning metadata to references.
-	pub metadata: HashMap<Reference, MetaData>,
+	pub(crate) metadata: HashMap<Reference, MetaData>,
 
 	/// The maximum number of pages in the `Palette`.
-	pub maximum_page_count: Page,
+	pub(crate) maximum_page_count: Page,
 
 	/// The default number of lines in each page.
-	pub default_line_count: Line,
+	pub(crate) default_line_count: Line,
 
 	/// The default number of columns in each line.
-	pub default_column_count: Column,
+	pub(crate) default_column_count: Column,
+
+	/// The maximum number of source cells followed in any chain when 
+	/// resolving colors.
+	pub(crate) max_depth: usize,
+
+	/// The color cycles of the palette.
+	pub(crate) cycles: Vec<Cycle>,
+
+	/// The ramp specs of the palette.
+	pub(crate) ramps: Vec<RampSpec>,
+
+	/// The constraints placed on slots of the palette.
+	pub(crate) constraints: BTreeMap<Address, Constraint>,
+
+	/// The registered metadata keys of the palette.
+	pub(crate) schema: MetaSchema,
+
+	/// A lower bound on the first free address of the palette. Every slot 
+	/// before this address is known to be occupied, so searches for free 
+	/// slots may begin here.
+	pub(crate) free_hint: Address,
+
+	/// An index of the occupied pages and lines of the palette. This must be
+	/// updated whenever a cell is added or removed.
+	pub(crate) occupancy: Occupancy,
+
+	/// The addresses of cells emptied directly through `Cell::borrow_mut`, 
+	/// which must be marked free before searching for free slots.
+	pub(crate) vacancies: Shared<VacancyLog>,
+
+	/// The addresses of the cells modified since the modification times of the
+	/// palette were last updated.
+	pub(crate) modified_cells: BTreeSet<Address>,
+
+	/// Called before a `Cell` is added to a new page in the palette. The 
+	/// expectation is that this will add the appropriate meta data to the 
+	/// palette. This will be called before the prepare_new_line function is 
+	/// called.
+	pub(crate) prepare_new_page: Option<Box<Prepare>>,
+	
+	/// Called before an expression is added to a new line in the palette. The 
+	/// expectation is that this will add the appropriate meta data to the 
+	/// palette.
+	pub(crate) prepare_new_line: Option<Box<Prepare>>,
+}
+
+
+impl Data {
+	/// Returns the maximum number of pages in the palette.
+	pub fn maximum_page_count(&self) -> Page {
+		self.maximum_page_count
+	}
+
+	/// Returns the default number of lines in each page.
+	pub fn default_line_count(&self) -> Line {
+		self.default_line_count
+	}
+
+	/// Returns the default number of columns in each line.
+	pub fn default_column_count(&self) -> Column {
+		self.default_column_count
+	}
+
+	/// Returns the maximum number of source cells followed in any chain when
+	/// resolving colors with `resolve`.
+	pub fn max_depth(&self) -> usize {
+		self.max_depth
+	}
+
+	/// Sets the maximum number of source cells followed in any chain when 
+	/// resolving colors with `resolve`.
+	pub fn set_max_depth(&mut self, max_depth: usize) {
+		self.max_depth = max_depth;
+	}
+
+	/// Returns the color of the cell at the given address, or None if the 
+	/// address is empty or the color is invalid. Returns a `MaxOrderExceeded`
+	/// error if the color depends on a chain of more source cells than the 
+	/// palette's maximum depth. Resolved colors are cached until the palette
+	/// is changed.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::DataBuilder;
+	/// use palette::expression::Expression;
+	/// use palette::cell::Shared;
+	/// use palette::{Address, Color};
+	/// 
+	/// let mut dat = DataBuilder::new().max_depth(1).build();
+	/// let base = dat.add_color(Color::new(0, 0, 100)).unwrap();
+	/// let (a, b) = (Address::new(0, 0, 1), Address::new(0, 0, 2));
+	/// let source = Shared::downgrade(&dat.cell(base).unwrap());
+	/// *dat.create_cell(a).unwrap().borrow_mut() = 
+	/// 	Expression::Lighten {a: source, amount: 0.5};
+	/// let source = Shared::downgrade(&dat.cell(a).unwrap());
+	/// *dat.create_cell(b).unwrap().borrow_mut() = 
+	/// 	Expression::Complement {a: source};
+	///
+	/// assert!(dat.resolve(a).unwrap().is_some());
+	/// assert!(dat.resolve(b).is_err());
+	///
+	/// dat.set_max_depth(2);
+	/// assert!(dat.resolve(b).unwrap().is_some());
+	/// ```
+	pub fn resolve(&self, address: Address) -> Result<Option<Color>> {
+		match self.cells.get(&address) {
+			Some(cell) => cell.resolve(self.max_depth),
+			None => Ok(None),
+		}
+	}
+
+	/// Returns the derivation order of the cell at the given address; the 
+	/// length of the longest chain of source cells its color depends on, or 0
+	/// if the address is empty. Returns a `MaxOrderExceeded` error if the 
+	/// chain is longer than the palette's maximum depth.
+	pub fn order(&self, address: Address) -> Result<usize> {
+		match self.cells.get(&address) {
+			Some(cell) => cell.resolve_depth(self.max_depth)
+				.map(|(_, depth)| depth),
+			None => Ok(0),
+		}
+	}
+
+	/// Returns the storage strategy used for the palette's cells.
+	pub fn storage(&self) -> Storage {
+		self.cells.storage()
+	}
+
+	/// Changes the storage strategy used for the palette's cells. Dense 
+	/// storage is laid out using the current default line and column counts.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::storage::Storage;
+	/// use palette::{Address, Color};
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// dat.add_color(Color::new(12, 50, 78)).unwrap();
+	/// dat.add_color(Color::new(60, 25, 17)).unwrap();
+	/// let before = dat.clone();
+	///
+	/// dat.set_storage(Storage::Dense);
+	///
+	/// assert_eq!(dat.storage(), Storage::Dense);
+	/// assert_eq!(dat, before);
+	/// ```
+	pub fn set_storage(&mut self, storage: Storage) {
+		let mut cells = Cells::new(
+			storage,
+			self.default_line_count,
+			self.default_column_count);
+		for (address, cell) in self.cells_mut().drain() {
+			cells.insert(address, cell);
+		}
+		self.cells = Shared::new(cells);
+	}
+
+	/// Returns the metadata associated with the given group, or None if it has
+	/// no metadata.
+	pub fn metadata(&self, group: &Reference) -> Option<&MetaData> {
+		self.metadata.get(group)
+	}
+
+	/// Returns the group associated with the given name, or None if there is
+	/// no such name.
+	pub fn named_group(&self, name: &str) -> Option<&Reference> {
+		self.names.get(name)
+	}
+
+	/// Returns the number of colors in the Data.
+	pub fn len(&self) -> usize {
+		self.cells.len()
+	}
+
+	/// Returns whether there are any `Cell`s in the `Data`.
+	pub fn is_empty(&self) -> bool {
+		self.cells.is_empty()
+	}
+
+	/// Returns a hash of the resolved contents of the palette data. The hash 
+	/// is stable between program runs, so it may be stored to detect unsaved
+	/// changes.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::Color;
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// dat.add_color(Color::new(12, 50, 78)).unwrap();
+	/// let saved = dat.clone();
+	///
+	/// assert_eq!(saved, dat);
+	/// assert_eq!(saved.content_hash(), dat.content_hash());
+	///
+	/// dat.add_color(Color::new(60, 25, 17)).unwrap();
+	/// assert!(saved != dat);
+	/// assert!(saved.content_hash() != dat.content_hash());
+	/// ```
+	pub fn content_hash(&self) -> u64 {
+		let mut hasher = Fnv64::default();
+		self.hash(&mut hasher);
+		hasher.finish()
+	}
+
+	/// Returns the number of occupied cells on the given page.
+	pub fn page_len(&self, page: Page) -> usize {
+		self.occupancy.page_len(page)
+	}
+
+	/// Returns the number of occupied cells on the given line.
+	pub fn line_len(&self, page: Page, line: Line) -> usize {
+		self.occupancy.line_len(page, line)
+	}
+
+	/// Inserts a cell at the given address, updating the occupancy index. 
+	/// Returns the cell previously at the address.
+	pub(crate) fn insert_cell(&mut self, address: Address, cell: Shared<Cell>)
+		-> Option<Shared<Cell>>
+	{
+		self.occupancy.insert(address);
+		self.occupancy.set_filled(address, match *cell.borrow() {
+			Expression::Empty => false,
+			_ => true,
+		});
+		self.modified_cells.insert(address);
+		cell.set_owner(Some((&self.vacancies, address)));
+		self.cells_mut().insert(address, cell)
+	}
+
+	/// Removes the cell at the given address, updating the occupancy index.
+	pub(crate) fn take_cell(&mut self, address: Address) 
+		-> Option<Shared<Cell>>
+	{
+		self.occupancy.remove(address);
+		self.modified_cells.insert(address);
+		let cell = self.cells_mut().remove(&address);
+		if let Some(ref cell) = cell { cell.set_owner(None); }
+		cell
+	}
+
+	/// Returns the palette's cells for modification, first copying the map of
+	/// cells if it is shared with a checkpoint. The cells themselves remain 
+	/// shared.
+	fn cells_mut(&mut self) -> &mut Cells {
+		Shared::make_mut(&mut self.cells)
+	}
+
+	/// Registers the palette data's vacancy log with each of its cells. This
+	/// must be called when palette data is built from cells directly.
+	fn adopt_cells(&self) {
+		for (address, cell) in self.cells.iter() {
+			cell.set_owner(Some((&self.vacancies, address)));
+		}
+	}
+
+	/// Notes that the cell at the given address has been modified, so that the
+	/// modification times of its groups will be updated and the occupancy 
+	/// index reflects its expression. This must be called whenever a cell's 
+	/// expression is replaced.
+	pub(crate) fn mark_modified(&mut self, address: Address) {
+		self.modified_cells.insert(address);
+		let filled = self.cells.get(&address)
+			.map_or(false, |cell| match *cell.borrow() {
+				Expression::Empty => false,
+				_ => true,
+			});
+		self.occupancy.set_filled(address, filled);
+	}
+
+	/// Forgets any modifications noted since the modification times were last
+	/// updated.
+	pub(crate) fn clear_modified(&mut self) {
+		self.modified_cells.clear();
+	}
+
+	/// Updates the modification times of the palette and of the pages and 
+	/// lines containing the cells modified since the last update. Groups 
+	/// without a creation time are given one. Returns the addresses of the 
+	/// modified cells.
+	pub(crate) fn stamp_modified(&mut self) -> BTreeSet<Address> {
+		if self.modified_cells.is_empty() { return BTreeSet::new(); }
+		let now = unix_time();
+		let modified = mem::replace(&mut self.modified_cells, BTreeSet::new());
+
+		let mut groups: HashSet<Reference> = HashSet::new();
+		groups.insert(Reference::all());
+		for address in &modified {
+			groups.insert(Reference::page_of(address));
+			groups.insert(Reference::line_of(address));
+		}
+		// Only existing metadata is updated, as new metadata would override 
+		// the default line and column counts.
+		for group in groups {
+			if let Some(meta) = self.metadata.get_mut(&group) {
+				meta.created.get_or_insert(now);
+				meta.modified = Some(now);
+			}
+		}
+		modified
+	}
+
+	/// Returns the number of slots available on the given page, according to
+	/// its line count and the column counts of its lines.
+	pub fn page_capacity(&self, page: Page) -> usize {
+		if page >= self.maximum_page_count { return 0; }
+		let lines = self.line_count(&Reference::page_of(
+			&Address::new(page, 0, 0)));
+		(0..lines)
+			.map(|line| self.column_count(&Reference::line_of(
+				&Address::new(page, line, 0))) as usize)
+			.sum()
+	}
+
+	/// Returns the total number of slots available in the palette.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::DataBuilder;
+	/// use palette::{Address, Color, Reference};
+	/// 
+	/// let mut dat = DataBuilder::new()
+	/// 	.page_count(2)
+	/// 	.line_count(2)
+	/// 	.column_count(4)
+	/// 	.build();
+	/// dat.set_line_count(Reference::page_of(&Address::new(1, 0, 0)), 1);
+	/// dat.add_color(Color::new(12, 50, 78)).unwrap();
+	///
+	/// assert_eq!(dat.capacity(), 12);
+	/// assert_eq!(dat.free_slots(), 11);
+	/// assert!(!dat.is_full());
+	/// assert_eq!(dat.page_len(0), 1);
+	/// ```
+	pub fn capacity(&self) -> usize {
+		// Only pages with metadata may differ from the defaults.
+		let custom: BTreeSet<Page> = self.metadata.keys()
+			.filter_map(|group| group.page().ok())
+			.filter(|&page| page < self.maximum_page_count)
+			.collect();
+		let default_capacity = self.default_line_count as usize 
+			* self.default_column_count as usize;
+
+		(self.maximum_page_count as usize - custom.len()) * default_capacity
+			+ custom.iter()
+				.map(|&page| self.page_capacity(page))
+				.sum::<usize>()
+	}
+
+	/// Returns the number of free slots in the palette.
+	pub fn free_slots(&self) -> usize {
+		let used: BTreeSet<Address> = self.cells.keys()
+			.chain(self.constraints.keys().cloned())
+			.filter(|&address| self.check_address(address) 
+				&& !self.is_free(address))
+			.collect();
+		self.capacity().saturating_sub(used.len())
+	}
+
+	/// Returns whether the palette has no free slots.
+	pub fn is_full(&self) -> bool {
+		self.free_slots() == 0
+	}
+
+	/// Returns whether the slot at the given address is free. A slot is free if
+	/// it holds no cell or an empty expression, and is not locked. Derived 
+	/// cells occupy their slot even if their color cannot be resolved.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::expression::Expression;
+	/// use palette::cell::Weak;
+	/// use palette::{Address, Color};
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// *dat.create_cell(Address::new(0, 0, 0)).unwrap().borrow_mut() = 
+	/// 	Expression::Lighten {a: Weak::new(), amount: 0.5};
+	///
+	/// assert!(!dat.is_free(Address::new(0, 0, 0)));
+	/// assert_eq!(dat.add_color(Color::new(12, 50, 78)).unwrap(), 
+	/// 	Address::new(0, 0, 1));
+	/// ```
+	pub fn is_free(&self, address: Address) -> bool {
+		let empty = self.cells.get(&address)
+			.map_or(true, |cell| match *cell.borrow() {
+				Expression::Empty => true,
+				_ => false,
+			});
+		empty && match self.constraint(address) {
+			Some(Constraint::Locked) | Some(Constraint::Reserved) => false,
+			_ => true,
+		}
+	}
+
+	/// Notes that the slot at the given address may have become free, so that
+	/// searches for free slots will not skip it. This must be called whenever
+	/// a cell is removed or emptied.
+	pub(crate) fn mark_free(&mut self, address: Address) {
+		self.occupancy.set_filled(address, false);
+		self.lower_free_hint(address);
+	}
+
+	/// Lowers the free hint to the given address, so that searches for free
+	/// slots will not skip it.
+	fn lower_free_hint(&mut self, address: Address) {
+		if address < self.free_hint {
+			self.free_hint = address;
+		}
+	}
+
+	/// Returns the first free address in the given group.
+	///
+	/// # Errors
+	///
+	/// Returns an `UnresolvedReferenceComponent` error if the group contains
+	/// named or indirect components, or a `MaxCellLimitExceeded` error if 
+	/// there are no free slots in the group.
+	pub fn first_free_address_in(&self, group: &Reference) -> Result<Address> {
+		let pages = component_indices(
+			&group.page, 
+			self.maximum_page_count as u32)?;
+		for page in pages {
+			let page = page as Page;
+			let lines = component_indices(
+				&group.line, 
+				self.line_count(&Reference::page_of(
+					&Address::new(page, 0, 0))) as u32)?;
+			for line in lines {
+				let line = line as Line;
+				let columns = component_indices(
+					&group.column, 
+					self.column_count(&Reference::line_of(
+						&Address::new(page, line, 0))) as u32)?;
+				for column in columns {
+					let address = Address::new(page, line, column as Column);
+					if self.check_address(address) && self.is_free(address) {
+						return Ok(address);
+					}
+				}
+			}
+		}
+		Err(Error::MaxCellLimitExceeded)
+	}
+
+	/// Returns the color cycles of the palette.
+	pub fn cycles(&self) -> &[Cycle] {
+		&self.cycles
+	}
+
+	/// Adds a color cycle to the palette.
+	pub fn add_cycle(&mut self, cycle: Cycle) {
+		self.cycles.push(cycle);
+	}
+
+	/// Removes and returns the color cycle at the given index, or None if 
+	/// there is no such cycle.
+	pub fn remove_cycle(&mut self, index: usize) -> Option<Cycle> {
+		if index < self.cycles.len() {
+			Some(self.cycles.remove(index))
+		} else {
+			None
+		}
+	}
+
+	/// Replaces the color cycles of the palette, returning the previous 
+	/// cycles.
+	pub fn set_cycles(&mut self, cycles: Vec<Cycle>) -> Vec<Cycle> {
+		mem::replace(&mut self.cycles, cycles)
+	}
+
+	/// Returns the ramp specs of the palette.
+	pub fn ramps(&self) -> &[RampSpec] {
+		&self.ramps
+	}
+
+	/// Replaces the ramp specs of the palette, returning the previous specs.
+	pub fn set_ramps(&mut self, ramps: Vec<RampSpec>) -> Vec<RampSpec> {
+		mem::replace(&mut self.ramps, ramps)
+	}
+
+	/// Returns the constraint placed on the given address, if any.
+	pub fn constraint(&self, address: Address) -> Option<Constraint> {
+		self.constraints.get(&address).cloned()
+	}
+
+	/// Places the given constraint on the given address, or removes its 
+	/// constraint if None is given. Returns the previous constraint.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::{Constraint, Data};
+	/// use palette::result::Error;
+	/// use palette::{Address, Color};
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// let addr = dat.add_color(Color::new(0, 0, 0)).unwrap();
+	/// dat.set_constraint(addr, Some(Constraint::Protected));
+	///
+	/// match dat.remove_cell(addr) {
+	/// 	Err(Error::SlotLocked(a)) => assert_eq!(a, addr),
+	/// 	_ => panic!("protected cell removed"),
+	/// }
+	///
+	/// // Locked addresses are skipped when adding colors.
+	/// dat.set_constraint(Address::new(0, 0, 1), Some(Constraint::Locked));
+	/// let next = dat.add_color(Color::new(10, 10, 10)).unwrap();
+	/// assert_eq!(next, Address::new(0, 0, 2));
+	/// ```
+	pub fn set_constraint(
+		&mut self, 
+		address: Address, 
+		constraint: Option<Constraint>) 
+		-> Option<Constraint>
+	{
+		// Removing a lock may free the slot.
+		if constraint.is_none() { self.lower_free_hint(address); }
+		match constraint {
+			Some(constraint) => self.constraints.insert(address, constraint),
+			None => self.constraints.remove(&address),
+		}
+	}
+
+	/// Returns an error if the slot at the given address is locked or 
+	/// reserved.
+	pub(crate) fn check_editable(&self, address: Address) -> Result<()> {
+		match self.constraint(address) {
+			Some(Constraint::Locked) | Some(Constraint::Reserved) 
+				=> Err(Error::SlotLocked(address)),
+			_ => Ok(()),
+		}
+	}
+
+	/// Returns an error if the slot at the given address has any constraint.
+	pub(crate) fn check_removable(&self, address: Address) -> Result<()> {
+		match self.constraint(address) {
+			Some(_) => Err(Error::SlotLocked(address)),
+			None => Ok(()),
+		}
+	}
+
+	/// Returns an iterator over the occupied addresses of the palette and their
+	/// `Cell`s, in address order.
+	pub fn iter<'a>(&'a self) -> impl Iterator<Item=(Address, &'a Cell)> + 'a {
+		self.cells.iter().map(|(address, cell)| (address, &**cell))
+	}
+
+	/// Returns an iterator over the occupied addresses of the given page and 
+	/// their `Cell`s, in address order.
+	pub fn iter_page<'a>(&'a self, page: Page) 
+		-> impl Iterator<Item=(Address, &'a Cell)> + 'a
+	{
+		self.cells
+			.range(Address::new(page, 0, 0), 
+				Address::new(page, LINE_MAX, COLUMN_MAX))
+			.map(|(address, cell)| (address, &**cell))
+	}
+
+	/// Returns an iterator over the occupied addresses of the given line and 
+	/// their `Cell`s, in address order.
+	pub fn iter_line<'a>(&'a self, page: Page, line: Line) 
+		-> impl Iterator<Item=(Address, &'a Cell)> + 'a
+	{
+		self.cells
+			.range(Address::new(page, line, 0), 
+				Address::new(page, line, COLUMN_MAX))
+			.map(|(address, cell)| (address, &**cell))
+	}
+
+	/// Prints a preview of the palette to standard output, using 24-bit color
+	/// escape codes. See `display::term::Preview` for more options.
+	#[cfg(feature = "std")]
+	pub fn print_term(&self) -> io::Result<()> {
+		let stdout = io::stdout();
+		let mut out = stdout.lock();
+		Preview::new().write(self, &mut out)
+	}
+
+	/// Returns a read-only view of the given page.
+	pub fn page_view(&self, page: Page) -> PageView {
+		PageView::new(self, page)
+	}
+
+	/// Returns a read-only view of the given line.
+	pub fn line_view(&self, page: Page, line: Line) -> LineView {
+		LineView::new(self, page, line)
+	}
+
+	/// Returns an iterator over the addresses and resolved `Color`s of the 
+	/// palette, in address order. Cells which do not resolve to a color are 
+	/// skipped.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::{Address, Color};
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// dat.add_color(Color::new(12, 50, 78)).unwrap();
+	/// dat.add_color(Color::new(60, 25, 17)).unwrap();
+	///
+	/// let colors: Vec<_> = dat.colors().collect();
+	/// assert_eq!(colors, vec![
+	/// 	(Address::new(0, 0, 0), Color::new(12, 50, 78)),
+	/// 	(Address::new(0, 0, 1), Color::new(60, 25, 17)),
+	/// ]);
+	/// ```
+	pub fn colors<'a>(&'a self) -> impl Iterator<Item=(Address, Color)> + 'a {
+		self.iter()
+			.filter_map(move |(address, cell)| self.color_of(cell)
+				.map(|c| (address, c)))
+	}
+
+	/// Returns a `MaxOrderExceeded` error if the color of any cell depends on
+	/// a chain of more source cells than the palette's maximum depth. Cells
+	/// exceeding the depth are otherwise treated as empty by `colors`.
+	pub fn check_depth(&self) -> Result<()> {
+		for (_, cell) in self.iter() {
+			cell.resolve(self.max_depth)?;
+		}
+		Ok(())
+	}
+
+	/// Returns the color of the given cell resolved within the palette's 
+	/// maximum depth, or None if it is invalid or exceeds the depth.
+	pub(crate) fn color_of(&self, cell: &Cell) -> Option<Color> {
+		cell.resolve(self.max_depth).unwrap_or(None)
+	}
+
+	/// Returns an iterator over the occupied addresses whose cells satisfy the
+	/// given predicate, in address order.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::{Address, Color};
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// dat.add_color(Color::new(12, 50, 78)).unwrap();
+	/// dat.add_color(Color::new(160, 25, 17)).unwrap();
+	///
+	/// let reds: Vec<_> = dat
+	/// 	.filter(|_, cell| cell.color().map_or(false, |c| c.r() > 128))
+	/// 	.collect();
+	/// assert_eq!(reds, vec![Address::new(0, 0, 1)]);
+	/// ```
+	pub fn filter<'a, F>(&'a self, mut predicate: F) 
+		-> impl Iterator<Item=Address> + 'a
+		where F: FnMut(Address, &Cell) -> bool + 'a
+	{
+		self.iter()
+			.filter(move |&(address, cell)| predicate(address, cell))
+			.map(|(address, _)| address)
+	}
+
+	/// Returns an iterator over the occupied addresses whose cell names match
+	/// the given wildcard pattern, in address order. A `*` in the pattern 
+	/// matches any sequence of characters, and a `?` matches any single 
+	/// character. Matching is case-insensitive.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::{Address, Color};
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// let a = dat.add_color(Color::new(230, 180, 150)).unwrap();
+	/// let b = dat.add_color(Color::new(150, 100, 80)).unwrap();
+	/// dat.set_slot_name(a, "Skin Light");
+	/// dat.set_slot_name(b, "Skin Shadow");
+	///
+	/// let found: Vec<_> = dat.find_by_name("skin s*").collect();
+	/// assert_eq!(found, vec![b]);
+	/// ```
+	pub fn find_by_name<'a>(&'a self, pattern: &'a str) 
+		-> impl Iterator<Item=Address> + 'a
+	{
+		self.cells
+			.keys()
+			.filter(move |&address| self.slot_name(address)
+				.map_or(false, |name| wildcard_match(pattern, name)))
+	}
+
+	/// Returns an iterator over the occupied addresses whose cells have the 
+	/// given tag, in address order.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::address::Reference;
+	/// use palette::{Address, Color};
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// let a = dat.add_color(Color::new(230, 180, 150)).unwrap();
+	/// dat.add_color(Color::new(20, 20, 20)).unwrap();
+	/// dat.add_tag(Reference::from(a), "skin");
+	///
+	/// let found: Vec<_> = dat.find_by_tag("skin").collect();
+	/// assert_eq!(found, vec![Address::new(0, 0, 0)]);
+	/// ```
+	pub fn find_by_tag<'a>(&'a self, tag: &'a str) 
+		-> impl Iterator<Item=Address> + 'a
+	{
+		self.cells
+			.keys()
+			.filter(move |&address| self.tags(&Reference::from(address))
+				.iter()
+				.any(|t| t == tag))
+	}
+
+	/// Returns the changes needed to turn this palette data into the given 
+	/// palette data, comparing the colors of each address.
+	pub fn diff(&self, other: &Data) -> PaletteDiff {
+		PaletteDiff::between(self, other)
+	}
+
+	/// Returns a checkpoint of the palette data, unaffected by later changes
+	/// to it. The checkpoint shares the palette's cells rather than copying 
+	/// them. See `Checkpoint` for details.
+	pub fn checkpoint(&self) -> Checkpoint {
+		Checkpoint::new(self)
+	}
+
+	/// Replaces the contents of the palette data with a copy of the given 
+	/// checkpoint, including its metadata. The page and line preparation 
+	/// functions are kept.
+	pub fn restore(&mut self, checkpoint: &Checkpoint) {
+		let mut restored = checkpoint.to_data();
+		restored.prepare_new_page = self.prepare_new_page.take();
+		restored.prepare_new_line = self.prepare_new_line.take();
+		*self = restored;
+	}
+
+	/// Returns statistics describing the colors of the palette.
+	pub fn analyze(&self) -> Analysis {
+		Analysis::new(self)
+	}
+
+	/// Returns a copy of the palette data with every color replaced by the 
+	/// result of the given function. Derived colors are resolved and stored as
+	/// fixed colors, while names, metadata, cycles, and ramp specs are copied 
+	/// unchanged.
+	pub(crate) fn map_colors<F>(&self, mut f: F) -> Data 
+		where F: FnMut(Color) -> Color
+	{
+		let mut cells = self.cells.empty_like();
+		let mut emptied = Vec::new();
+		for (address, cell) in self.cells.iter() {
+			let expr = match self.color_of(cell) {
+				Some(color) => Expression::Color(f(color)),
+				None => { emptied.push(address); Expression::Empty },
+			};
+			cells.insert(address, Shared::new(Cell::new(expr)));
+		}
+
+		let mut data = Data {
+			cells: Shared::new(cells),
+			names: self.names.clone(),
+			metadata: self.metadata.clone(),
+			maximum_page_count: self.maximum_page_count,
+			default_line_count: self.default_line_count,
+			default_column_count: self.default_column_count,
+			max_depth: self.max_depth,
+			cycles: self.cycles.clone(),
+			ramps: self.ramps.clone(),
+			constraints: self.constraints.clone(),
+			schema: self.schema.clone(),
+			occupancy: self.occupancy.clone(),
+			.. Default::default()
+		};
+		data.adopt_cells();
+		for address in emptied {
+			data.mark_free(address);
+		}
+		data
+	}
+
+	/// Returns a copy of the palette data with every color replaced by its 
+	/// appearance under the given color vision deficiency.
+	pub fn simulate(&self, deficiency: Deficiency) -> Data {
+		vision::simulate(self, deficiency)
+	}
+
+	/// Returns a report of the color pairs which become indistinguishable 
+	/// under each color vision deficiency.
+	pub fn distinguishability_report(&self) -> DistinguishabilityReport {
+		DistinguishabilityReport::new(self, vision::DEFAULT_THRESHOLD)
+	}
+
+	/// Returns the pairs of addresses whose colors have a WCAG contrast ratio 
+	/// of at least `min_ratio`, along with the ratio of each pair.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::space::{CONTRAST_AA, CONTRAST_AAA};
+	/// use palette::Color;
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// let black = dat.add_color(Color::new(0, 0, 0)).unwrap();
+	/// let gray = dat.add_color(Color::new(90, 90, 90)).unwrap();
+	/// let white = dat.add_color(Color::new(255, 255, 255)).unwrap();
+	///
+	/// let pairs = |min_ratio| dat.contrast_pairs(min_ratio)
+	/// 	.into_iter()
+	/// 	.map(|(a, b, _)| (a, b))
+	/// 	.collect::<Vec<_>>();
+	/// assert_eq!(pairs(CONTRAST_AA), vec![(black, white), (gray, white)]);
+	/// assert_eq!(pairs(CONTRAST_AAA), vec![(black, white)]);
+	/// ```
+	pub fn contrast_pairs(&self, min_ratio: f32) 
+		-> Vec<(Address, Address, f32)>
+	{
+		let colors: Vec<(Address, Color)> = self.colors().collect();
+		filter_pairs(&colors, |&(a, ca), &(b, cb)| {
+			let ratio = contrast_ratio(ca, cb);
+			if ratio >= min_ratio { Some((a, b, ratio)) } else { None }
+		})
+	}
+
+	/// Returns the pairs of addresses whose colors are closer than the given 
+	/// CIE76 distance, along with the distance of each pair. See 
+	/// `analysis::near_duplicates` for details.
+	pub fn near_duplicates(&self, threshold: f32) 
+		-> Vec<(Address, Address, f32)>
+	{
+		analysis::near_duplicates(self, threshold)
+	}
+
+	/// Returns the address and color in the palette nearest to the given color
+	/// using the given distance metric, or None if the palette has no colors.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::space::Metric;
+	/// use palette::{Address, Color};
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// dat.add_color(Color::new(0, 0, 0)).unwrap();
+	/// dat.add_color(Color::new(200, 200, 200)).unwrap();
+	///
+	/// assert_eq!(dat.nearest(Color::new(150, 160, 170), Metric::Rgb), 
+	/// 	Some((Address::new(0, 0, 1), Color::new(200, 200, 200))));
+	/// ```
+	pub fn nearest(&self, color: Color, metric: Metric) 
+		-> Option<(Address, Color)>
+	{
+		self.colors()
+			.map(|(address, c)| (metric.distance(color, c), address, c))
+			.fold(None, |best: Option<(f32, Address, Color)>, next| 
+				match best {
+					Some(best) if best.0 <= next.0 => Some(best),
+					_ => Some(next),
+				})
+			.map(|(_, address, c)| (address, c))
+	}
+
+	/// Returns up to `n` addresses and colors in the palette nearest to the 
+	/// given color using the given distance metric, ordered from nearest to 
+	/// farthest.
+	pub fn nearest_n(&self, color: Color, n: usize, metric: Metric) 
+		-> Vec<(Address, Color)>
+	{
+		let mut found: Vec<(f32, Address, Color)> = self.colors()
+			.map(|(address, c)| (metric.distance(color, c), address, c))
+			.collect();
+		let by_distance = |a: &(f32, Address, Color), b: &(f32, Address, Color)|
+			a.0.partial_cmp(&b.0)
+				.unwrap_or(Ordering::Equal)
+				.then(a.1.cmp(&b.1));
+		// Only the nearest `n` colors need to be sorted.
+		if n > 0 && n < found.len() {
+			found.select_nth_unstable_by(n - 1, by_distance);
+			found.truncate(n);
+		}
+		found.sort_by(by_distance);
+		found.into_iter()
+			.take(n)
+			.map(|(_, address, c)| (address, c))
+			.collect()
+	}
+
+	/// Returns a reference to the cell located at the given address, or None if
+	/// the address is invalid or empty.
+	pub fn cell(&self, address: Address) -> Option<Shared<Cell>> {
+		self.cells.get(&address).cloned()
+	}
+
+	/// Returns a reference to the cell located at the given address. If 
+	/// the address is empty, a new cell will be created an a weak reference 
+	/// will be returned. Returns None if the address is invalid.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::{Address, Color};
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// let cell = dat.create_cell(Address::new(1, 1, 1))
+	/// 	.ok()
+	/// 	.unwrap(); // Create empty `Cell` and unwrap it.
+	/// ```
+	#[cfg_attr(feature = "cargo-clippy", allow(map_entry))]
+	pub fn create_cell(&mut self, address: Address) -> Result<Shared<Cell>> {
+		if self.occupancy.contains(address) {
+			Err(Error::AddressInUse(address))
+		} else {
+			self.prepare_address(address)?;
+			let new_cell = Shared::new(Cell::new(Default::default()));
+			self.insert_cell(address, new_cell.clone());
+			Ok(new_cell)
+		}
+	}
+
+
+	/// Places the given `Color` in the first free address of the palette. 
+	/// Returns the address the color was placed at, or an error if there are no
+	/// free addresses.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::{Address, Color};
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// dat.add_color(Color::new(12, 50, 78)).unwrap();
+	/// let addr = dat.add_color(Color::new(60, 25, 17)).unwrap();
+	///
+	/// assert_eq!(addr, Address::new(0, 0, 1));
+	/// ```
+	pub fn add_color(&mut self, color: Color) -> Result<Address> {
+		let address = self.first_free_address_after(Default::default())?;
+		let cell = match self.cell(address) {
+			Some(cell) => cell,
+			None => self.create_cell(address)?,
+		};
+		*cell.borrow_mut() = Expression::Color(color);
+		self.mark_modified(address);
+		Ok(address)
+	}
+
+	/// Removes the cell at the given address from the palette. Returns the 
+	/// removed expression, or an error if the given address is empty or if the
+	/// cell is referenced by another cell.
+	pub fn remove_cell(&mut self, address: Address) -> Result<Expression> {
+		self.check_removable(address)?;
+		let dependents = self.direct_dependents(address);
+		if !dependents.is_empty() {
+			return Err(Error::CellInUse {
+				address: address, 
+				dependents: dependents,
+			});
+		}
+
+		// Remove cell from cells.
+		let cell = self.take_cell(address)
+			.ok_or_else(|| Error::EmptyAddress(address))?;
+		self.mark_free(address);
+
+		// Extract Expression and discard wrappers.
+		let expr = mem::replace(&mut *cell.borrow_mut(), Default::default());
+		Ok(expr)
+	}
+
+	/// Removes the cell at the given address from the palette, handling the 
+	/// cells which reference it according to the given policy. Returns the 
+	/// previous expressions of the removed cell and of every removed or frozen
+	/// dependent, beginning with the removed cell. No cells will be changed if
+	/// an error is returned.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::{Data, DependentPolicy};
+	/// use palette::expression::Expression;
+	/// use palette::cell::Shared;
+	/// use palette::{Address, Color};
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// let src = dat.add_color(Color::new(0, 0, 100)).unwrap();
+	/// let source = dat.cell(src).unwrap();
+	/// let derived = Address::new(0, 0, 1);
+	/// *dat.create_cell(derived).unwrap().borrow_mut() = 
+	/// 	Expression::Lighten {a: Shared::downgrade(&source), amount: 0.5};
+	/// let lightened = dat.cell(derived).unwrap().color();
+	///
+	/// assert!(dat.remove_cell(src).is_err());
+	///
+	/// let changed = dat.remove_cell_with(src, DependentPolicy::Freeze)
+	/// 	.unwrap();
+	/// assert_eq!(changed.len(), 2);
+	/// assert_eq!(dat.len(), 1);
+	/// assert_eq!(dat.cell(derived).unwrap().color(), lightened);
+	/// ```
+	pub fn remove_cell_with(
+		&mut self,
+		address: Address,
+		policy: DependentPolicy)
+		-> Result<Vec<(Address, Expression)>>
+	{
+		self.take_cell_with(address, policy).map(|changed| changed
+			.into_iter()
+			.map(|(address, _, expr)| (address, expr))
+			.collect())
+	}
+
+	/// Removes the cell at the given address from the palette, handling the
+	/// cells which reference it according to the given policy. Returns the 
+	/// previous expressions of the removed cell and of every removed or frozen
+	/// dependent, along with their cells, beginning with the removed cell. The
+	/// removed cells are emptied, but may be restored with `restore_cell`.
+	pub(crate) fn take_cell_with(
+		&mut self, 
+		address: Address, 
+		policy: DependentPolicy)
+		-> Result<Vec<(Address, Shared<Cell>, Expression)>>
+	{
+		self.check_removable(address)?;
+		if !self.cells.contains_key(&address) {
+			return Err(Error::EmptyAddress(address));
+		}
+		let dependents = self.direct_dependents(address);
+
+		let mut changed = Vec::with_capacity(dependents.len() + 1);
+		match policy {
+			DependentPolicy::Prevent => if !dependents.is_empty() {
+				return Err(Error::CellInUse {
+					address: address, 
+					dependents: dependents,
+				});
+			},
+
+			DependentPolicy::Cascade => {
+				let removed = if dependents.is_empty() {
+					dependents
+				} else {
+					self.dependents(address)
+				};
+				for &dependent in &removed {
+					self.check_removable(dependent)?;
+				}
+				for dependent in removed {
+					let cell = self.take_cell(dependent)
+						.expect("dependent cell");
+					self.mark_free(dependent);
+					let expr = mem::replace(
+						&mut *cell.borrow_mut(), 
+						Default::default());
+					changed.push((dependent, cell, expr));
+				}
+			},
+
+			DependentPolicy::Freeze => {
+				for &dependent in &dependents {
+					self.check_editable(dependent)?;
+				}
+				for dependent in dependents {
+					let cell = self.cells[&dependent].clone();
+					let frozen = match self.color_of(&cell) {
+						Some(color) => Expression::Color(color),
+						None => Expression::Empty,
+					};
+					let expr = mem::replace(&mut *cell.borrow_mut(), frozen);
+					if self.color_of(&cell).is_none() {
+						self.mark_free(dependent);
+					}
+					self.mark_modified(dependent);
+					changed.push((dependent, cell, expr));
+				}
+			},
+		}
+
+		let cell = self.take_cell(address).expect("removed cell");
+		self.mark_free(address);
+		let expr = mem::replace(&mut *cell.borrow_mut(), Default::default());
+		changed.insert(0, (address, cell, expr));
+		Ok(changed)
+	}
+
+	/// Places a previously removed cell back into the palette at the given 
+	/// address, so that any expressions referencing it become valid again. 
+	/// Returns an error if the address is occupied.
+	pub(crate) fn restore_cell(&mut self, address: Address, cell: Shared<Cell>)
+		-> Result<()>
+	{
+		if self.occupancy.contains(address) {
+			return Err(Error::AddressInUse(address));
+		}
+		self.prepare_address(address)?;
+		self.insert_cell(address, cell);
+		Ok(())
+	}
+
+	/// Returns the addresses of the cells whose expressions directly 
+	/// reference the cell at the given address, in address order.
+	pub(crate) fn direct_dependents(&self, address: Address) -> Vec<Address> {
+		let cell = match self.cells.get(&address) {
+			// Only referenced cells have weak references.
+			Some(cell) if Shared::weak_count(cell) > 0 => cell,
+			_ => return Vec::new(),
+		};
+		self.cells.iter()
+			.filter(|&(_, other)| other.borrow().sources()
+				.iter()
+				.filter_map(|source| source.upgrade())
+				.any(|source| Shared::ptr_eq(&source, cell)))
+			.map(|(address, _)| address)
+			.collect()
+	}
+
+	/// Removes the color from the cell at the given address, leaving an empty 
+	/// cell in its place. Any cells referencing the cell will remain valid.
+	/// Returns the removed expression, or an error if the given address is 
+	/// empty.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::{Address, Color};
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// let addr = dat.add_color(Color::new(12, 50, 78)).unwrap();
+	/// dat.add_color(Color::new(60, 25, 17)).unwrap();
+	/// dat.remove_color(addr).unwrap();
+	///
+	/// assert_eq!(dat.len(), 2);
+	/// assert_eq!(dat.cell(addr).unwrap().color(), None);
+	///
+	/// // The next color added fills the emptied slot.
+	/// assert_eq!(dat.add_color(Color::new(1, 2, 3)).unwrap(), addr);
+	/// ```
+	pub fn remove_color(&mut self, address: Address) -> Result<Expression> {
+		self.check_removable(address)?;
+		let expr = {
+			let cell = self.cells
+				.get(&address)
+				.ok_or_else(|| Error::EmptyAddress(address))?;
+			mem::replace(&mut *cell.borrow_mut(), Default::default())
+		};
+		self.mark_free(address);
+		self.mark_modified(address);
+		Ok(expr)
+	}
+
+	/// Removes the slot at the given address from the palette: its cell, and 
+	/// its name and other metadata. Returns the removed expression, or an 
+	/// error if the given address is empty or if the cell is referenced by 
+	/// another cell.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::{Address, Color};
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// let addr = dat.add_color(Color::new(12, 50, 78)).unwrap();
+	/// dat.add_color(Color::new(60, 25, 17)).unwrap();
+	/// dat.set_slot_name(addr, "Deep Blue");
+	/// dat.remove_slot(addr).unwrap();
+	///
+	/// assert_eq!(dat.len(), 1);
+	/// assert!(dat.cell(addr).is_none());
+	/// assert_eq!(dat.slot_name(addr), None);
+	/// assert!(dat.remove_slot(addr).is_err());
+	///
+	/// // The next color added fills the removed slot.
+	/// assert_eq!(dat.add_color(Color::new(1, 2, 3)).unwrap(), addr);
+	/// ```
+	pub fn remove_slot(&mut self, address: Address) -> Result<Expression> {
+		let expr = self.remove_cell(address)?;
+		let group = Reference::from(address);
+		self.metadata.remove(&group);
+		self.names.retain(|_, named| *named != group);
+		Ok(expr)
+	}
+
+	/// Freezes the derived color at the given address, replacing it with its 
+	/// current color while keeping its expression, so that the color may be 
+	/// edited as a fixed color and later linked to its sources again with 
+	/// `thaw`. Returns an error if the slot is locked, or if it does not hold
+	/// a derived color which can be resolved.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::expression::Expression;
+	/// use palette::cell::Shared;
+	/// use palette::{Address, Color};
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// let base = dat.add_color(Color::new(0, 0, 100)).unwrap();
+	/// let derived = Address::new(0, 0, 1);
+	/// let source = Shared::downgrade(&dat.cell(base).unwrap());
+	/// *dat.create_cell(derived).unwrap().borrow_mut() = 
+	/// 	Expression::Lighten {a: source, amount: 0.5};
+	/// let lightened = dat.cell(derived).unwrap().color();
+	///
+	/// dat.freeze(derived).unwrap();
+	/// *dat.cell(base).unwrap().borrow_mut() = 
+	/// 	Expression::Color(Color::new(100, 0, 0));
+	/// assert_eq!(dat.cell(derived).unwrap().color(), lightened);
+	///
+	/// dat.thaw(derived).unwrap();
+	/// assert_ne!(dat.cell(derived).unwrap().color(), lightened);
+	/// ```
+	pub fn freeze(&mut self, address: Address) -> Result<()> {
+		self.check_editable(address)?;
+		let cell = self.cell(address)
+			.ok_or_else(|| Error::EmptyAddress(address))?;
+		let derived = match *cell.borrow() {
+			Expression::Empty |
+			Expression::Color(..) |
+			Expression::Cmyk(..) |
+			Expression::Frozen {..} => false,
+			_ => true,
+		};
+		if !derived {
+			return Err(Error::NotDerived(address));
+		}
+		let color = cell.resolve(self.max_depth)?
+			.ok_or_else(|| Error::NotDerived(address))?;
+
+		{
+			let mut expr = cell.borrow_mut();
+			let recipe = mem::replace(&mut *expr, Expression::Empty);
+			*expr = Expression::Frozen {
+				color: color, 
+				recipe: Box::new(recipe),
+			};
+		}
+		self.mark_modified(address);
+		Ok(())
+	}
+
+	/// Thaws the frozen color at the given address, restoring the derived 
+	/// expression it was frozen from. Any changes made to the color while it 
+	/// was frozen are discarded. Returns an error if the slot is locked or if
+	/// it is not frozen.
+	pub fn thaw(&mut self, address: Address) -> Result<()> {
+		self.check_editable(address)?;
+		let cell = self.cell(address)
+			.ok_or_else(|| Error::EmptyAddress(address))?;
+
+		{
+			let mut expr = cell.borrow_mut();
+			match mem::replace(&mut *expr, Expression::Empty) {
+				Expression::Frozen {recipe, ..} => *expr = *recipe,
+				other => {
+					*expr = other;
+					return Err(Error::NotFrozen(address));
+				},
+			}
+		}
+		self.mark_modified(address);
+		Ok(())
+	}
+
+	/// Moves the cells at the given source addresses to the paired destination
+	/// addresses. The moves are performed simultaneously, so a destination may
+	/// be the source of another move; this allows cells to be swapped or 
+	/// shifted by overlapping amounts. The cells themselves are moved, so any 
+	/// cells referencing them will remain valid, and any names and metadata of
+	/// the cells will move with them. Empty sources are ignored.
+	///
+	/// Returns an error if a destination holds a cell or metadata and its cell
+	/// is not also moved, if any destination is used twice, or if a 
+	/// destination is outside of the palette. No cells will be moved and no 
+	/// pages or lines will be prepared if an error is returned.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::DataBuilder;
+	/// use palette::address::Reference;
+	/// use palette::{Address, Color};
+	/// 
+	/// let mut dat = DataBuilder::new()
+	/// 	.prepare_page_with(|data, group| 
+	/// 		data.set_label(group.clone(), "New"))
+	/// 	.build();
+	/// let a = dat.add_color(Color::new(255, 0, 0)).unwrap();
+	/// let b = dat.add_color(Color::new(0, 0, 255)).unwrap();
+	///
+	/// dat.move_cells(&[(a, b), (b, a)]).unwrap();
+	///
+	/// assert_eq!(dat.cell(a).unwrap().color(), Some(Color::new(0, 0, 255)));
+	/// assert_eq!(dat.cell(b).unwrap().color(), Some(Color::new(255, 0, 0)));
+	///
+	/// // A rejected move does not prepare any of its destinations.
+	/// let c = Address::new(3, 0, 0);
+	/// let d = Address::new(u16::max_value(), 0, 0);
+	/// assert!(dat.move_cells(&[(a, c), (b, d)]).is_err());
+	/// assert_eq!(dat.label(&Reference::page_of(&c)), None);
+	/// ```
+	pub fn move_cells(&mut self, moves: &[(Address, Address)]) -> Result<()> {
+		// Check constraints and destinations before moving anything.
+		let mut destinations = BTreeSet::new();
+		for &(from, to) in moves {
+			if self.cells.contains_key(&from) {
+				self.check_removable(from)?;
+				self.check_removable(to)?;
+			}
+			if !destinations.insert(to) {
+				return Err(Error::AddressInUse(to));
+			}
+			let occupied = self.cells.contains_key(&to) 
+				|| self.metadata.contains_key(&Reference::from(to));
+			let vacated = self.cells.contains_key(&to) 
+				&& moves.iter().any(|&(from, _)| from == to);
+			if occupied && !vacated {
+				return Err(Error::AddressInUse(to));
+			}
+			if !self.check_address(to) {
+				return Err(Error::InvalidAddress(to));
+			}
+		}
+		for &(_, to) in moves {
+			self.prepare_address(to)?;
+		}
+
+		// Remove all occupied sources, then place them at their destinations.
+		let mut removed = Vec::with_capacity(moves.len());
+		for &(from, to) in moves {
+			if let Some(cell) = self.take_cell(from) {
+				let meta = self.metadata.remove(&Reference::from(from));
+				self.mark_free(from);
+				removed.push((from, to, cell, meta));
+			}
+		}
+
+		let renamed: Vec<(Reference, Reference)> = removed.iter()
+			.map(|&(from, to, _, _)| 
+				(Reference::from(from), Reference::from(to)))
+			.collect();
+		for group in self.names.values_mut() {
+			if let Some(&(_, ref to)) = renamed.iter().find(|r| r.0 == *group) {
+				*group = to.clone();
+			}
+		}
+
+		for (_, to, cell, meta) in removed {
+			self.insert_cell(to, cell);
+			if let Some(meta) = meta {
+				self.metadata.insert(Reference::from(to), meta);
+			}
+		}
+		Ok(())
+	}
+
+	/// Moves the pages of the given palette data after the last used page of
+	/// this palette. Derived cells keep referencing the same cells, and the
+	/// names, metadata, cycles, ramp specs, and constraints of the pages are 
+	/// moved with them. Names which are already in use are dropped, as is any
+	/// metadata which does not refer to a single page. Returns the page the
+	/// first appended page was placed at.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::expression::Expression;
+	/// use palette::cell::Shared;
+	/// use palette::{Address, Color};
+	/// 
+	/// let mut a: Data = Default::default();
+	/// a.add_color(Color::new(255, 0, 0)).unwrap();
+	///
+	/// let mut b: Data = Default::default();
+	/// let src = b.add_color(Color::new(0, 0, 100)).unwrap();
+	/// let source = b.cell(src).unwrap();
+	/// *b.create_cell(Address::new(0, 0, 1)).unwrap().borrow_mut() = 
+	/// 	Expression::Lighten {a: Shared::downgrade(&source), amount: 0.5};
+	/// let lightened = b.cell(Address::new(0, 0, 1)).unwrap().color();
+	///
+	/// assert_eq!(a.append(b).unwrap(), 1);
+	/// assert_eq!(a.cell(Address::new(1, 0, 1)).unwrap().color(), lightened);
+	///
+	/// let page = a.split_page(1).unwrap();
+	/// assert_eq!(a.len(), 1);
+	/// assert_eq!(page.cell(Address::new(0, 0, 1)).unwrap().color(), 
+	/// 	lightened);
+	/// ```
+	pub fn append(&mut self, mut other: Data) -> Result<Page> {
+		let offset = match self.last_used_page() {
+			Some(page) => page.checked_add(1)
+				.ok_or(Error::MaxCellLimitExceeded)?,
+			None => 0,
+		};
+		if let Some(page) = other.last_used_page() {
+			match offset.checked_add(page) {
+				Some(last) if last < self.maximum_page_count => (),
+				_ => return Err(Error::MaxCellLimitExceeded),
+			}
+		}
+		let shift = |address: Address| 
+			Address::new(address.page + offset, address.line, address.column);
+
+		for (address, cell) in other.cells_mut().drain() {
+			self.insert_cell(shift(address), cell);
+		}
+		self.schema.merge(&other.schema);
+		let metadata = mem::replace(&mut other.metadata, HashMap::new());
+		for (group, meta) in metadata {
+			if let Ok(page) = group.page() {
+				self.metadata.insert(with_page(&group, page + offset), meta);
+			}
+		}
+		let names = mem::replace(&mut other.names, HashMap::new());
+		for (name, group) in names {
+			if let Ok(page) = group.page() {
+				self.names.entry(name)
+					.or_insert_with(|| with_page(&group, page + offset));
+			}
+		}
+		for mut cycle in other.cycles.drain(..) {
+			cycle.start = shift(cycle.start);
+			cycle.end = shift(cycle.end);
+			self.cycles.push(cycle);
+		}
+		for mut ramp in other.ramps.drain(..) {
+			for anchor in &mut ramp.anchors {
+				*anchor = shift(*anchor);
+			}
+			ramp.location = shift(ramp.location);
+			self.ramps.push(ramp);
+		}
+		for (address, constraint) in mem::replace(
+			&mut other.constraints, 
+			BTreeMap::new())
+		{
+			self.constraints.insert(shift(address), constraint);
+		}
+		Ok(offset)
+	}
+
+	/// Returns the last page holding a cell, or referred to by metadata, a 
+	/// name, a cycle, a ramp spec, or a constraint.
+	fn last_used_page(&self) -> Option<Page> {
+		let groups = self.metadata.keys().chain(self.names.values())
+			.filter_map(|group| group.page().ok());
+		let cycles = self.cycles.iter()
+			.flat_map(|cycle| vec![cycle.start.page, cycle.end.page]);
+		let ramps = self.ramps.iter()
+			.flat_map(|ramp| ramp.anchors.iter()
+				.chain(Some(&ramp.location))
+				.map(|address| address.page)
+				.collect::<Vec<_>>());
+		let constraints = self.constraints.keys()
+			.map(|address| address.page);
+		self.cells.last_address()
+			.map(|address| address.page)
+			.into_iter()
+			.chain(groups)
+			.chain(cycles)
+			.chain(ramps)
+			.chain(constraints)
+			.max()
+	}
+
+	/// Removes the given page from the palette and returns it as standalone 
+	/// palette data, placed at page 0. Derived cells on the page which 
+	/// reference cells on other pages are converted to fixed colors. Returns an
+	/// error if any cell on another page references a cell on the page, or if 
+	/// a slot on the page is locked or protected. No cells will be removed if 
+	/// an error is returned.
+	pub fn split_page(&mut self, page: Page) -> Result<Data> {
+		let on_page = |address: &Address| address.page == page;
+		let moved: HashMap<*const Cell, Address> = self.cells.iter()
+			.filter(|&(address, _)| on_page(&address))
+			.map(|(address, cell)| (&**cell as *const Cell, address))
+			.collect();
+
+		// Check constraints and references before removing anything.
+		for &address in moved.values() {
+			self.check_removable(address)?;
+		}
+		for (address, cell) in self.cells.iter() {
+			if on_page(&address) { continue; }
+			for source in cell.borrow().sources() {
+				if let Some(source) = source.upgrade() {
+					let ptr = &*source as *const Cell;
+					if let Some(&used) = moved.get(&ptr) {
+						return Err(Error::CellInUse {
+							address: used,
+							dependents: vec![address],
+						});
+					}
+				}
+			}
+		}
+
+		// Fix cells which reference cells left behind.
+		let moved_cells: Vec<(Address, Shared<Cell>)> = moved.values()
+			.map(|&address| (address, self.cells[&address].clone()))
+			.collect();
+		for &(_, ref cell) in &moved_cells {
+			let external = cell.borrow().sources().iter()
+				.filter_map(|source| source.upgrade())
+				.any(|source| !moved.contains_key(&(&*source as *const Cell)));
+			if external {
+				let expr = match self.color_of(cell) {
+					Some(color) => Expression::Color(color),
+					None => Expression::Empty,
+				};
+				*cell.borrow_mut() = expr;
+			}
+		}
+
+		let mut data = Data {
+			cells: Shared::new(self.cells.empty_like()),
+			maximum_page_count: self.maximum_page_count,
+			default_line_count: self.default_line_count,
+			default_column_count: self.default_column_count,
+			max_depth: self.max_depth,
+			schema: self.schema.clone(),
+			.. Default::default()
+		};
+		let shift = |address: Address| 
+			Address::new(0, address.line, address.column);
+
+		for (address, cell) in moved_cells {
+			self.take_cell(address);
+			data.insert_cell(shift(address), cell);
+		}
+		self.lower_free_hint(Address::new(page, 0, 0));
+		let groups: Vec<Reference> = self.metadata.keys()
+			.filter(|group| group.page().ok() == Some(page))
+			.cloned()
+			.collect();
+		for group in groups {
+			let meta = self.metadata.remove(&group).expect("page metadata");
+			data.metadata.insert(with_page(&group, 0), meta);
+		}
+		let names: Vec<String> = self.names.iter()
+			.filter(|&(_, group)| group.page().ok() == Some(page))
+			.map(|(name, _)| name.clone())
+			.collect();
+		for name in names {
+			let group = self.names.remove(&name).expect("page name");
+			data.names.insert(name, with_page(&group, 0));
+		}
+		let (cycles, kept): (Vec<Cycle>, Vec<Cycle>) = self.cycles.drain(..)
+			.partition(|cycle| on_page(&cycle.start) && on_page(&cycle.end));
+		self.cycles = kept;
+		data.cycles = cycles;
+		for cycle in &mut data.cycles {
+			cycle.start = shift(cycle.start);
+			cycle.end = shift(cycle.end);
+		}
+		let (ramps, kept): (Vec<RampSpec>, Vec<RampSpec>) = self.ramps.drain(..)
+			.partition(|ramp| on_page(&ramp.location) 
+				&& ramp.anchors.iter().all(|a| on_page(a)));
+		self.ramps = kept;
+		data.ramps = ramps;
+		for ramp in &mut data.ramps {
+			for anchor in &mut ramp.anchors {
+				*anchor = shift(*anchor);
+			}
+			ramp.location = shift(ramp.location);
+		}
+		let constrained: Vec<Address> = self.constraints.keys()
+			.filter(|address| address.page == page)
+			.cloned()
+			.collect();
+		for address in constrained {
+			let constraint = self.constraints.remove(&address)
+				.expect("page constraint");
+			data.constraints.insert(shift(address), constraint);
+		}
+		Ok(data)
+	}
+
+	/// Applies operations to the palette data as a single transaction. If the 
+	/// given function returns an error, every operation it applied is reverted.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::operation::*;
+	/// use palette::Color;
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// dat.transaction(|txn| {
+	/// 	txn.apply(Box::new(InsertColor::new(Color::new(10, 10, 10))))?;
+	/// 	txn.apply(Box::new(InsertColor::new(Color::new(20, 20, 20))))
+	/// }).unwrap();
+	///
+	/// assert_eq!(dat.len(), 2);
+	/// ```
+	pub fn transaction<F, T>(&mut self, f: F) -> Result<T>
+		where F: FnOnce(&mut Transaction) -> Result<T>
+	{
+		Transaction::run(self, f).map(|(value, _)| value)
+	}
+
+	/// Removes all cells on the given page from the palette. Returns the 
+	/// removed expressions and their addresses. No cells will be removed if
+	/// any cell on the page is locked, protected, or referenced by another 
+	/// cell.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::{Address, Color};
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// dat.add_color(Color::new(12, 50, 78)).unwrap();
+	/// dat.add_color(Color::new(60, 25, 17)).unwrap();
+	/// let removed = dat.clear_page(0).unwrap();
+	///
+	/// assert_eq!(removed.len(), 2);
+	/// assert!(dat.is_empty());
+	///
+	/// // Colors are added from the start of the cleared page again.
+	/// assert_eq!(dat.add_color(Color::new(1, 2, 3)).unwrap(), 
+	/// 	Address::new(0, 0, 0));
+	/// ```
+	pub fn clear_page(&mut self, page: Page) 
+		-> Result<Vec<(Address, Expression)>>
+	{
+		let addresses: Vec<Address> = self.iter_page(page)
+			.map(|(address, _)| address)
+			.collect();
+
+		// Check for constraints and references before removing anything.
+		for &address in &addresses {
+			self.check_removable(address)?;
+			let dependents = self.direct_dependents(address);
+			if !dependents.is_empty() {
+				return Err(Error::CellInUse {
+					address: address, 
+					dependents: dependents,
+				});
+			}
+		}
+
+		let mut removed = Vec::with_capacity(addresses.len());
+		for address in addresses {
+			removed.push((address, self.remove_cell(address)?));
+		}
+		Ok(removed)
+	}
+
+	/// Writes the palette data to the given buffer in the native `.rpal` 
+	/// format, preserving all expressions, metadata, and configuration.
+	#[cfg(feature = "std")]
+	pub fn write_native<W>(&self, out_buf: &mut W) -> Result<()>
+		where W: io::Write
+	{
+		native::write_native(self, out_buf)
+	}
+
+	/// Reads palette data in the native `.rpal` format from the given buffer.
+	/// The format's `prepare_new_page` and `prepare_new_line` functions are 
+	/// not stored, and must be restored if needed.
+	#[cfg(feature = "std")]
+	pub fn read_native<R>(in_buf: &mut R) -> Result<Data>
+		where R: io::Read
+	{
+		native::read_native(in_buf)
+	}
+
+	/// Reads palette data in the native `.rpal` format from the given buffer
+	/// using the given parse options. See `read_native`.
+	#[cfg(feature = "std")]
+	pub fn read_native_with<R>(in_buf: &mut R, options: &ParseOptions) 
+		-> Result<Data>
+		where R: io::Read
+	{
+		native::read_native_with(in_buf, options)
+	}
+
+	/// Checks the palette for reference cycles. Returns a `CircularReference`
+	/// error for the first cell found to depend upon itself.
+	pub fn validate(&self) -> Result<()> {
+		for (address, cell) in self.cells.iter() {
+			if cell.borrow().depends_on(cell) {
+				return Err(Error::CircularReference(address));
+			}
+		}
+		Ok(())
+	}
+
+	/// Returns the addresses of the cells whose expressions depend upon the 
+	/// cell at the given address, directly or through other derived cells, in
+	/// address order. These are the cells affected by a change to the cell.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::expression::Expression;
+	/// use palette::cell::Shared;
+	/// use palette::{Address, Color};
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// let base = dat.add_color(Color::new(0, 0, 100)).unwrap();
+	/// let (a, b) = (Address::new(0, 0, 1), Address::new(0, 0, 2));
+	/// let source = Shared::downgrade(&dat.cell(base).unwrap());
+	/// *dat.create_cell(a).unwrap().borrow_mut() = 
+	/// 	Expression::Lighten {a: source, amount: 0.5};
+	/// let source = Shared::downgrade(&dat.cell(a).unwrap());
+	/// *dat.create_cell(b).unwrap().borrow_mut() = 
+	/// 	Expression::Complement {a: source};
+	///
+	/// assert_eq!(dat.dependents(base), vec![a, b]);
+	/// assert_eq!(dat.dependencies(b), vec![base, a]);
+	/// assert!(dat.dependency_graph().contains("\"0:0:1\" -> \"0:0:2\""));
+	/// ```
+	pub fn dependents(&self, address: Address) -> Vec<Address> {
+		reachable(self.dependency_edges(), address)
+	}
+
+	/// Returns the addresses of the cells which the expression of the cell at
+	/// the given address depends upon, directly or through other derived 
+	/// cells, in address order.
+	pub fn dependencies(&self, address: Address) -> Vec<Address> {
+		let edges = self.dependency_edges()
+			.into_iter()
+			.map(|(source, dependent)| (dependent, source));
+		reachable(edges, address)
+	}
+
+	/// Returns the graph of derived cells in the DOT format used by Graphviz.
+	/// Each cell which references or is referenced by another cell is drawn 
+	/// in its color, with an edge from each source cell to the cells derived
+	/// from it.
+	pub fn dependency_graph(&self) -> String {
+		let edges = self.dependency_edges();
+		let nodes: BTreeSet<Address> = edges.iter()
+			.flat_map(|&(source, dependent)| vec![source, dependent])
+			.collect();
+
+		let mut dot = String::from("digraph palette {\n");
+		for address in nodes {
+			let color = self.cells.get(&address)
+				.and_then(|cell| self.color_of(cell));
+			match color {
+				Some(color) => dot.push_str(&format!(
+					"\t\"{}\" [label=\"{}\\n{}\", style=filled, \
+					fillcolor=\"{}\"];\n",
+					address, address, Hex(color), Hex(color))),
+				None => dot.push_str(&format!(
+					"\t\"{}\" [label=\"{}\"];\n", address, address)),
+			}
+		}
+		for (source, dependent) in edges {
+			dot.push_str(&format!(
+				"\t\"{}\" -> \"{}\";\n", source, dependent));
+		}
+		dot.push_str("}\n");
+		dot
+	}
+
+	/// Returns an iterator over the occupied addresses of the palette and their
+	/// `Cell`s in dependency order, so that every cell follows the cells it 
+	/// references. Cells which are ready at the same time are ordered by 
+	/// address, so the order is deterministic.
+	///
+	/// Every cell is yielded exactly once. Cells on a reference cycle, or 
+	/// depending on one, have no valid position; they are yielded last, in 
+	/// address order. Use `validate` to detect such cycles beforehand.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::expression::Expression;
+	/// use palette::cell::Shared;
+	/// use palette::{Address, Color};
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// let (a, b, c) = (
+	/// 	Address::new(0, 0, 0), 
+	/// 	Address::new(0, 0, 1), 
+	/// 	Address::new(0, 0, 2));
+	/// dat.create_cell(a).unwrap();
+	/// *dat.create_cell(c).unwrap().borrow_mut() = 
+	/// 	Expression::Color(Color::new(0, 0, 100));
+	/// let source = Shared::downgrade(&dat.cell(c).unwrap());
+	/// *dat.cell(a).unwrap().borrow_mut() = 
+	/// 	Expression::Lighten {a: source, amount: 0.5};
+	/// *dat.create_cell(b).unwrap().borrow_mut() = 
+	/// 	Expression::Color(Color::new(50, 0, 0));
+	///
+	/// let order: Vec<Address> = dat.iter_topological()
+	/// 	.map(|(address, _)| address)
+	/// 	.collect();
+	/// assert_eq!(order, vec![b, c, a]);
+	/// assert_eq!(dat.order(a).unwrap(), 1);
+	/// ```
+	pub fn iter_topological<'a>(&'a self) 
+		-> impl Iterator<Item=(Address, &'a Cell)> + 'a
+	{
+		let mut dependents: BTreeMap<Address, Vec<Address>> = BTreeMap::new();
+		let mut waiting: BTreeMap<Address, usize> = BTreeMap::new();
+		for (source, dependent) in self.dependency_edges() {
+			dependents.entry(source).or_insert_with(Vec::new).push(dependent);
+			*waiting.entry(dependent).or_insert(0) += 1;
+		}
+
+		let mut ready: BTreeSet<Address> = self.cells.keys()
+			.filter(|address| !waiting.contains_key(address))
+			.collect();
+		let mut sorted = Vec::with_capacity(self.cells.len());
+		while let Some(address) = ready.iter().next().cloned() {
+			ready.remove(&address);
+			sorted.push(address);
+			let next = dependents.get(&address).into_iter().flat_map(|v| v);
+			for &dependent in next {
+				let count = waiting.get_mut(&dependent).expect("waiting cell");
+				*count -= 1;
+				if *count == 0 { ready.insert(dependent); }
+			}
+		}
+
+		// Cells still waiting are on or behind a reference cycle.
+		if sorted.len() < self.cells.len() {
+			let placed: BTreeSet<Address> = sorted.iter().cloned().collect();
+			sorted.extend(self.cells.keys()
+				.filter(|address| !placed.contains(address)));
+		}
+
+		sorted.into_iter().map(move |address| (address, &*self.cells[&address]))
+	}
+
+	/// Returns the edges of the graph of derived cells as pairs of source and
+	/// dependent addresses.
+	pub(crate) fn dependency_edges(&self) -> Vec<(Address, Address)> {
+		let addresses: HashMap<*const Cell, Address> = self.cells.iter()
+			.map(|(address, cell)| (&**cell as *const Cell, address))
+			.collect();
+
+		let mut edges = Vec::new();
+		for (address, cell) in self.cells.iter() {
+			for source in cell.borrow().sources() {
+				let source = source.upgrade()
+					.and_then(|s| addresses.get(&(&*s as *const Cell)));
+				if let Some(&source) = source {
+					// A blend may use the same source twice.
+					if edges.last() != Some(&(source, address)) {
+						edges.push((source, address));
+					}
+				}
+			}
+		}
+		edges
+	}
+
+	/// Returns the label associated with the given group, or
+	/// None if it has no label.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::address::Reference;
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// dat.set_label(Reference::all(), "My Palette");
+	///
+	/// assert_eq!(dat.label(&Reference::all()), Some("My Palette"));
+	/// ```
+	pub fn label(&self, group: &Reference) -> Option<&str> {
+		self.metadata
+			.get(group)
+			.and_then(|slotmap| slotmap.format_label.as_ref())
+			.map(|label| &label[..])
+	}
+
+	/// Sets the label for the given group.
+	pub fn set_label<S>(
+		&mut self, 
+		group: Reference, 
+		format_label: S) 
+		where S: Into<String> 
+	{
+		self.metadata
+			.entry(group)
+			.or_insert_with(Default::default)
+			.format_label = Some(format_label.into());
+	}
+
+	/// Returns the name associated with the given group, or None if it has
+	/// no name.
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::address::Reference;
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// dat.set_name(Reference::all(), "My Palette");
+	///
+	/// assert_eq!(dat.name(&Reference::all()), Some("My Palette"));
+	/// ```
+	pub fn name(&self, group: &Reference) -> Option<&str> {
+		self.metadata
+			.get(group)
+			.and_then(|data| data.name.as_ref())
+			.map(|name| &name[..])
+	}
+
+	/// Sets the name for the given group.
+	pub fn set_name<S>(&mut self, group: Reference, name: S) 
+		where S: Into<String> 
+	{
+		self.metadata
+			.entry(group)
+			.or_insert_with(Default::default)
+			.name = Some(name.into());
+	}
+
+	/// Returns the name of the cell at the given address, or None if it has no
+	/// name.
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::Color;
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// let addr = dat.add_color(Color::new(200, 30, 30)).unwrap();
+	/// dat.set_slot_name(addr, "Blood Red");
+	///
+	/// assert_eq!(dat.slot_name(addr), Some("Blood Red"));
+	/// ```
+	pub fn slot_name(&self, address: Address) -> Option<&str> {
+		self.name(&Reference::from(address))
+	}
+
+	/// Sets the name of the cell at the given address.
+	pub fn set_slot_name<S>(&mut self, address: Address, name: S) 
+		where S: Into<String> 
+	{
+		self.set_name(Reference::from(address), name);
+	}
+
+	/// Returns the tags associated with the given group.
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::address::Reference;
+	/// use palette::Color;
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// let addr = dat.add_color(Color::new(200, 30, 30)).unwrap();
+	/// dat.add_tag(Reference::from(addr), "skin");
+	/// dat.add_tag(Reference::from(addr), "shadow");
+	/// dat.remove_tag(&Reference::from(addr), "skin");
+	///
+	/// assert_eq!(dat.tags(&Reference::from(addr)), &["shadow".to_string()]);
+	/// ```
+	pub fn tags(&self, group: &Reference) -> &[String] {
+		self.metadata
+			.get(group)
+			.map_or(&[], |data| &data.tags[..])
+	}
 
-	/// Called before a `Cell` is added to a new page in the palette. The 
-	/// expectation is that this will add the appropriate meta data to the 
-	/// palette. This will be called before the prepare_new_line function is 
-	/// called.
-	pub prepare_new_page: fn(&mut Data, &Reference),
-	
-	/// Called before an expression is added to a new line in the palette. The 
-	/// expectation is that this will add the appropriate meta data to the 
-	/// palette.
-	pub prepare_new_line: fn(&mut Data, &Reference),
-}
+	/// Adds a tag to the given group. Returns false if the group already had 
+	/// the tag.
+	pub fn add_tag<S>(&mut self, group: Reference, tag: S) -> bool
+		where S: Into<String> 
+	{
+		let tag = tag.into();
+		let tags = &mut self.metadata
+			.entry(group)
+			.or_insert_with(Default::default)
+			.tags;
+		if tags.contains(&tag) {
+			false
+		} else {
+			tags.push(tag);
+			true
+		}
+	}
 
+	/// Removes a tag from the given group. Returns false if the group did not
+	/// have the tag.
+	pub fn remove_tag(&mut self, group: &Reference, tag: &str) -> bool {
+		match self.metadata.get_mut(group) {
+			Some(data) => {
+				let len = data.tags.len();
+				data.tags.retain(|t| t != tag);
+				data.tags.len() != len
+			},
+			None => false,
+		}
+	}
 
-impl Data {
-	/// Returns the number of colors in the Data.
-	pub fn len(&self) -> usize {
-		self.cells.len()
+	/// Returns the notes associated with the given group, or None if it has no
+	/// notes.
+	pub fn notes(&self, group: &Reference) -> Option<&str> {
+		self.metadata
+			.get(group)
+			.and_then(|data| data.notes.as_ref())
+			.map(|notes| &notes[..])
 	}
 
-	/// Returns whether there are any `Cell`s in the `Data`.
-	pub fn is_empty(&self) -> bool {
-		self.cells.is_empty()
+	/// Sets the notes for the given group.
+	pub fn set_notes<S>(&mut self, group: Reference, notes: S) 
+		where S: Into<String> 
+	{
+		self.metadata
+			.entry(group)
+			.or_insert_with(Default::default)
+			.notes = Some(notes.into());
 	}
 
-	/// Returns a reference to the cell located at the given address, or None if
-	/// the address is invalid or empty.
-	pub fn cell(&self, address: Address) -> Option<Rc<Cell>> {
-		self.cells.get(&address).cloned()
+	/// Returns the time the given group was created, in seconds since the Unix
+	/// epoch, or None if it is unknown.
+	pub fn created(&self, group: &Reference) -> Option<u64> {
+		self.metadata.get(group).and_then(|data| data.created)
 	}
 
-	/// Returns a reference to the cell located at the given address. If 
-	/// the address is empty, a new cell will be created an a weak reference 
-	/// will be returned. Returns None if the address is invalid.
+	/// Returns the time the given group was last modified, in seconds since 
+	/// the Unix epoch, or None if it is unknown. Modification times are 
+	/// updated automatically when operations are applied to a `Palette`.
 	///
 	/// # Example
 	///
 	/// ```rust
-	/// use palette::data::Data;
-	/// use palette::{Address, Color};
-	/// 
-	/// let mut dat: Data = Default::default();
-	/// let cell = dat.create_cell(Address::new(1, 1, 1))
-	/// 	.ok()
-	/// 	.unwrap(); // Create empty `Cell` and unwrap it.
+	/// use palette::*;
+	/// use palette::operation::*;
+	///
+	/// let mut pal = Palette::new("Example", Format::Default, false);
+	/// assert!(pal.data().modified(&Reference::all()).is_none());
+	///
+	/// pal.apply(Box::new(InsertColor::new(Color::new(10, 20, 30)))).unwrap();
+	///
+	/// assert!(pal.data().modified(&Reference::all()).is_some());
+	/// let page = Reference::page_of(&Address::new(0, 0, 0));
+	/// assert!(pal.data().modified(&page).is_some());
 	/// ```
-	#[cfg_attr(feature = "cargo-clippy", allow(map_entry))]
-	pub fn create_cell(&mut self, address: Address) -> Result<Rc<Cell>> {
-		if self.cells.contains_key(&address) {
-			Err(Error::AddressInUse(address))
-		} else {
-			self.prepare_address(address)?;
-			let new_cell = Rc::new(Cell::new(Default::default()));
-			self.cells.insert(address, new_cell.clone());
-			Ok(new_cell)
-		}
+	pub fn modified(&self, group: &Reference) -> Option<u64> {
+		self.metadata.get(group).and_then(|data| data.modified)
 	}
 
+	/// Returns the author of the given group, or None if it has no author.
+	pub fn author(&self, group: &Reference) -> Option<&str> {
+		self.metadata
+			.get(group)
+			.and_then(|data| data.author.as_ref())
+			.map(|author| &author[..])
+	}
 
-	/// Removes the expression at the given address from the palette. Returns
-	/// the removed expression, or an error if the given address is empty.
-	pub fn remove_cell(&mut self, address: Address) -> Result<Expression> {
-		// Remove cell from cells.
-		let cell = self.cells
-			.remove(&address)
-			.ok_or_else(|| Error::EmptyAddress(address))?;
+	/// Sets the author of the given group.
+	pub fn set_author<S>(&mut self, group: Reference, author: S) 
+		where S: Into<String> 
+	{
+		self.metadata
+			.entry(group)
+			.or_insert_with(Default::default)
+			.author = Some(author.into());
+	}
 
-		// Extract Expression and discard wrappers.
-		let expr = mem::replace(&mut *cell.borrow_mut(), Default::default());
-		Ok(expr)
+	/// Returns the path of the file the given group was loaded from, or None 
+	/// if it has no source.
+	#[cfg(feature = "std")]
+	pub fn source(&self, group: &Reference) -> Option<&Path> {
+		self.metadata
+			.get(group)
+			.and_then(|data| data.source.as_ref())
+			.map(|source| source.as_path())
 	}
 
-	/// Returns the label associated with the given group, or
-	/// None if it has no label.
+	/// Sets the path of the file the given group was loaded from.
+	#[cfg(feature = "std")]
+	pub fn set_source<P>(&mut self, group: Reference, source: P) 
+		where P: Into<PathBuf> 
+	{
+		self.metadata
+			.entry(group)
+			.or_insert_with(Default::default)
+			.source = Some(source.into());
+	}
+
+	/// Returns the value of the given application-defined property of the 
+	/// given group, or None if it is not set.
 	///
 	/// # Example
 	///
@@ -200,57 +2566,145 @@ impl Data {
 	/// use palette::address::Reference;
 	/// 
 	/// let mut dat: Data = Default::default();
-	/// dat.set_label(Reference::all(), "My Palette");
+	/// dat.set_property(Reference::all(), "studio", "Northwind");
 	///
-	/// assert_eq!(dat.label(&Reference::all()), Some("My Palette"));
+	/// assert_eq!(dat.property(&Reference::all(), "studio"), 
+	/// 	Some("Northwind"));
+	/// assert_eq!(dat.property(&Reference::all(), "project"), None);
 	/// ```
-	pub fn label(&self, group: &Reference) -> Option<&str> {
+	pub fn property(&self, group: &Reference, key: &str) -> Option<&str> {
 		self.metadata
 			.get(group)
-			.and_then(|slotmap| slotmap.format_label.as_ref())
-			.map(|label| &label[..])
+			.and_then(|data| data.properties.get(key))
+			.map(|value| &value[..])
 	}
 
-	/// Sets the label for the given group.
-	pub fn set_label<S>(
-		&mut self, 
-		group: Reference, 
-		format_label: S) 
-		where S: Into<String> 
+	/// Sets an application-defined property of the given group. Returns the 
+	/// previous value of the property.
+	pub fn set_property<K, V>(&mut self, group: Reference, key: K, value: V)
+		-> Option<String>
+		where
+			K: Into<String>,
+			V: Into<String>,
 	{
 		self.metadata
 			.entry(group)
 			.or_insert_with(Default::default)
-			.format_label = Some(format_label.into());
+			.properties
+			.insert(key.into(), value.into())
 	}
 
-	/// Returns the name associated with the given group, or None if it has
-	/// no name.
+	/// Removes an application-defined property from the given group. Returns
+	/// the removed value of the property.
+	pub fn remove_property(&mut self, group: &Reference, key: &str) 
+		-> Option<String> 
+	{
+		self.metadata
+			.get_mut(group)
+			.and_then(|data| data.properties.remove(key))
+	}
+
+	/// Returns an iterator over the application-defined properties of the 
+	/// given group, in key order.
+	pub fn properties<'a>(&'a self, group: &Reference) 
+		-> impl Iterator<Item=(&'a str, &'a str)> + 'a
+	{
+		self.metadata
+			.get(group)
+			.into_iter()
+			.flat_map(|data| data.properties.iter())
+			.map(|(key, value)| (&key[..], &value[..]))
+	}
+
+	/// Returns the registered metadata keys of the palette.
+	pub fn schema(&self) -> &MetaSchema {
+		&self.schema
+	}
+
+	/// Registers a typed metadata key, allowing values of the given type to be
+	/// set under it on any group or slot.
+	///
+	/// # Errors
+	///
+	/// Returns a `MetaTypeMismatch` error if the key is already registered 
+	/// with a different type.
+	pub fn register_meta<S>(&mut self, key: S, meta_type: MetaType) 
+		-> Result<()>
+		where S: Into<String>
+	{
+		self.schema.register(key, meta_type)
+	}
+
+	/// Returns the typed metadata value stored under the given key for the 
+	/// given group, or None if it is not set or has a different type.
+	///
+	/// # Example
 	///
 	/// ```rust
 	/// use palette::data::Data;
 	/// use palette::address::Reference;
+	/// use palette::meta::MetaType;
+	/// use palette::Color;
 	/// 
 	/// let mut dat: Data = Default::default();
-	/// dat.set_name(Reference::all(), "My Palette");
+	/// dat.register_meta("level", MetaType::Int).unwrap();
+	/// dat.register_meta("backdrop", MetaType::Color).unwrap();
 	///
-	/// assert_eq!(dat.name(&Reference::all()), Some("My Palette"));
+	/// dat.set_meta(Reference::all(), "level", 3i64).unwrap();
+	/// dat.set_meta(Reference::all(), "backdrop", Color::new(0, 0, 32))
+	/// 	.unwrap();
+	///
+	/// assert_eq!(dat.get_meta::<i64>(&Reference::all(), "level"), Some(3));
+	/// assert_eq!(dat.get_meta::<Color>(&Reference::all(), "backdrop"), 
+	/// 	Some(Color::new(0, 0, 32)));
+	/// assert!(dat.set_meta(Reference::all(), "level", true).is_err());
+	/// assert!(dat.set_meta(Reference::all(), "hidden", true).is_err());
 	/// ```
-	pub fn name(&self, group: &Reference) -> Option<&str> {
+	pub fn get_meta<T>(&self, group: &Reference, key: &str) -> Option<T>
+		where T: MetaKind
+	{
+		self.meta_value(group, key).and_then(T::from_value)
+	}
+
+	/// Returns the metadata value stored under the given key for the given 
+	/// group, or None if it is not set.
+	pub fn meta_value(&self, group: &Reference, key: &str) 
+		-> Option<&MetaValue> 
+	{
 		self.metadata
 			.get(group)
-			.and_then(|data| data.name.as_ref())
-			.map(|name| &name[..])
+			.and_then(|data| data.values.get(key))
 	}
 
-	/// Sets the name for the given group.
-	pub fn set_name<S>(&mut self, group: Reference, name: S) 
-		where S: Into<String> 
+	/// Stores a typed metadata value under the given key for the given group.
+	/// Returns the previous value stored under the key.
+	///
+	/// # Errors
+	///
+	/// Returns an `UnregisteredMetaKey` error if the key is not registered, or
+	/// a `MetaTypeMismatch` error if the value does not have the registered 
+	/// type of the key.
+	pub fn set_meta<T>(&mut self, group: Reference, key: &str, value: T) 
+		-> Result<Option<MetaValue>>
+		where T: MetaKind
 	{
-		self.metadata
+		let value = value.into_value();
+		self.schema.validate(key, &value)?;
+		Ok(self.metadata
 			.entry(group)
 			.or_insert_with(Default::default)
-			.name = Some(name.into());
+			.values
+			.insert(key.to_owned(), value))
+	}
+
+	/// Removes the metadata value stored under the given key from the given 
+	/// group. Returns the removed value.
+	pub fn remove_meta(&mut self, group: &Reference, key: &str) 
+		-> Option<MetaValue> 
+	{
+		self.metadata
+			.get_mut(group)
+			.and_then(|data| data.values.remove(key))
 	}
 
 	/// Returns the next free address after the given address. And error will be
@@ -260,32 +2714,73 @@ impl Data {
 		starting_address: Address) 
 		-> Result<Address> 
 	{
-		let mut address = starting_address;
-		self.prepare_address(address)?;
+		// Slots emptied directly through their cells may precede the hint.
+		for address in self.vacancies.take() {
+			self.mark_free(address);
+		}
 
-		// Loop until we don't see a color.
-		while self.cells
-			.get(&address)
-			.and_then(|s| s.color())
-			.is_some() 
-		{
-			address = address.wrapping_step(
-				1,
-				self.maximum_page_count,
-				self.line_count(&Reference::page_of(&address)), 
-				self.column_count(&Reference::line_of(&address))
-			);
-			// Return an error if we've looped all the way around.
-			if address == starting_address {
+		// Every slot before the free hint is occupied, so the search can skip
+		// ahead to it.
+		let from_hint = starting_address <= self.free_hint;
+		let start = if from_hint { self.free_hint } else { starting_address };
+		self.prepare_address(start)?;
+
+		// Search a line at a time, using the occupancy index to skip directly
+		// to slots which may be free. Once the search wraps around to the 
+		// starting line, only the columns before the start remain.
+		let mut line_start = start;
+		let mut column = start.column as usize;
+		let mut wrapped = false;
+		loop {
+			let (lines, columns) = self.wrapping_counts(line_start);
+			let end = if wrapped { 
+				columns.min(start.column)
+			} else {
+				columns
+			};
+			while let Some(c) = self.occupancy.first_unfilled(
+				line_start.page,
+				line_start.line,
+				column,
+				end)
+			{
+				let address = Address::new(line_start.page, line_start.line, c);
+				if self.is_free(address) {
+					if from_hint && address >= self.free_hint {
+						self.free_hint = address;
+					}
+					return Ok(address);
+				}
+				column = c as usize + 1;
+			}
+			if wrapped {
 				return Err(Error::MaxCellLimitExceeded);
 			}
+
+			// Move to the start of the next line.
+			line_start = Address::new(line_start.page, line_start.line, 0)
+				.wrapping_step(
+					columns as usize, 
+					self.maximum_page_count, 
+					lines, 
+					columns);
+			column = 0;
+			wrapped = line_start.page == start.page 
+				&& line_start.line == start.line;
 		}
-		Ok(address)
 	}
 
-	/// Calls the prepare_new_page function and returns the current line count 
-	/// for the given group.
-	fn line_count(&mut self, group: &Reference) -> Line {
+	/// Returns the line count of the given address's page and the column 
+	/// count of its line.
+	fn wrapping_counts(&self, address: Address) -> (Line, Column) {
+		(
+			self.line_count(&Reference::page_of(&address)), 
+			self.column_count(&Reference::line_of(&address))
+		)
+	}
+
+	/// Returns the current line count for the given group.
+	pub fn line_count(&self, group: &Reference) -> Line {
 		self.metadata
 			.get(group)
 			.map_or(self.default_line_count, |meta| meta.line_count)
@@ -293,15 +2788,17 @@ impl Data {
 
 	/// Sets the line count for a group.
 	pub fn set_line_count(&mut self, group: Reference, line_count: Line) {
+		if self.line_count(&group) != line_count {
+			self.free_hint = Default::default();
+		}
 		self.metadata
 			.entry(group)
 			.or_insert_with(Default::default)
 			.line_count = line_count;
 	}
 
-	/// Calls the prepare_new_line function and returns the current column count 
-	/// for the given group.
-	fn column_count(&mut self, group: &Reference) -> Column {
+	/// Returns the current column count for the given group.
+	pub fn column_count(&self, group: &Reference) -> Column {
 		self.metadata
 			.get(group)
 			.map_or(self.default_column_count, |meta| meta.column_count)
@@ -313,6 +2810,9 @@ impl Data {
 		group: Reference, 
 		column_count: Column) 
 	{
+		if self.column_count(&group) != column_count {
+			self.free_hint = Default::default();
+		}
 		self.metadata
 			.entry(group)
 			.or_insert_with(Default::default)
@@ -321,7 +2821,7 @@ impl Data {
 
 	/// Returns whether the give address lies within the bounds defined by the 
 	/// wrapping and max page settings for the palette.
-	fn check_address(&mut self, address: Address) -> bool {
+	pub(crate) fn check_address(&self, address: Address) -> bool {
 		address.page < self.maximum_page_count &&
 		address.line < self.line_count(&Reference::page_of(&address)) &&
 		address.column < self.column_count(&Reference::line_of(&address))
@@ -338,12 +2838,24 @@ impl Data {
 
 		if !self.metadata.contains_key(&page_group) {
 			self.set_line_count(page_group.clone(), default_line_count);
-			(self.prepare_new_page)(self, &page_group);
+			// The callback is taken out of the data while it is running, so
+			// that it may be given mutable access to the data.
+			if let Some(mut prepare) = self.prepare_new_page.take() {
+				prepare.prepare(self, &page_group);
+				if self.prepare_new_page.is_none() {
+					self.prepare_new_page = Some(prepare);
+				}
+			}
 		}
 
 		if !self.metadata.contains_key(&line_group) {
 			self.set_column_count(line_group.clone(), default_column_count);
-			(self.prepare_new_line)(self, &line_group);
+			if let Some(mut prepare) = self.prepare_new_line.take() {
+				prepare.prepare(self, &line_group);
+				if self.prepare_new_line.is_none() {
+					self.prepare_new_line = Some(prepare);
+				}
+			}
 		}
 		
 		if self.check_address(address) {
@@ -353,7 +2865,7 @@ impl Data {
 		}
 	}
 
-	/// Retrieves n target addresses after starting_address from the palette. If 
+	/// Retrieves n target addresses after starting_address from the palette. If
 	/// overwrite is true, the addresses may potentially contain expressions. 
 	/// Otherwise, they will be empty. Addresses provided in the exclude list 
 	/// will be skipped. Returns an error if more targets are requested than are
@@ -389,9 +2901,9 @@ impl Data {
 		} else { // Find n free addresses.
 			self.prepare_address(next)?;
 
-			// Check if the starting address is empty.
+			// Check if the starting address is free.
 			if next == starting_address && 
-				self.cells.get(&next).and_then(|s| s.color()).is_none() &&
+				self.is_free(next) &&
 				!exclude.clone().map_or(false, |ex| ex.contains(&next))
 			{
 				targets.insert(next);
@@ -417,6 +2929,323 @@ impl Data {
 }
 
 
+////////////////////////////////////////////////////////////////////////////////
+// DataBuilder
+////////////////////////////////////////////////////////////////////////////////
+/// Provides configuration options for constructing palette `Data`.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::data::DataBuilder;
+/// 
+/// let dat = DataBuilder::new()
+/// 	.page_count(4)
+/// 	.line_count(16)
+/// 	.column_count(16)
+/// 	.build();
+///
+/// assert_eq!(dat.maximum_page_count(), 4);
+/// ```
+pub struct DataBuilder {
+	/// The maximum number of pages in the `Palette`.
+	page_count: Page,
+	/// The default number of lines in each page.
+	line_count: Line,
+	/// The default number of columns in each line.
+	column_count: Column,
+	/// Called before a `Cell` is added to a new page in the palette.
+	prepare_new_page: Option<Box<Prepare>>,
+	/// Called before a `Cell` is added to a new line in the palette.
+	prepare_new_line: Option<Box<Prepare>>,
+	/// The storage strategy for the palette's cells.
+	storage: Storage,
+	/// The maximum number of source cells followed when resolving colors.
+	max_depth: usize,
+}
+
+
+impl DataBuilder {
+	/// Creates a new `DataBuilder` with the default configuration.
+	pub fn new() -> Self {
+		DataBuilder {
+			page_count: PAGE_MAX,
+			line_count: LINE_MAX,
+			column_count: COLUMN_MAX,
+			prepare_new_page: None,
+			prepare_new_line: None,
+			storage: Storage::Sparse,
+			max_depth: DEFAULT_MAX_DEPTH,
+		}
+	}
+
+	/// Sets the maximum number of pages in the palette.
+	pub fn page_count(mut self, page_count: Page) -> Self {
+		self.page_count = page_count;
+		self
+	}
+
+	/// Sets the default number of lines in each page.
+	pub fn line_count(mut self, line_count: Line) -> Self {
+		self.line_count = line_count;
+		self
+	}
+
+	/// Sets the default number of columns in each line.
+	pub fn column_count(mut self, column_count: Column) -> Self {
+		self.column_count = column_count;
+		self
+	}
+
+	/// Sets the function to call before a `Cell` is added to a new page.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::DataBuilder;
+	/// use palette::address::Reference;
+	/// use palette::{Address, Color};
+	/// 
+	/// let prefix = String::from("Bank");
+	/// let mut dat = DataBuilder::new()
+	/// 	.prepare_page_with(move |data, group| {
+	/// 		let page = group.page().unwrap_or(0);
+	/// 		data.set_label(group.clone(), format!("{} {}", prefix, page));
+	/// 	})
+	/// 	.build();
+	/// dat.add_color(Color::new(10, 10, 10)).unwrap();
+	///
+	/// let page = Reference::page_of(&Address::new(0, 0, 0));
+	/// assert_eq!(dat.label(&page), Some("Bank 0"));
+	/// ```
+	pub fn prepare_page_with<F>(mut self, prepare_new_page: F) -> Self
+		where F: FnMut(&mut Data, &Reference) + MaybeSync + 'static
+	{
+		self.prepare_new_page = Some(Box::new(prepare_new_page));
+		self
+	}
+
+	/// Sets the function to call before a `Cell` is added to a new line.
+	pub fn prepare_line_with<F>(mut self, prepare_new_line: F) -> Self
+		where F: FnMut(&mut Data, &Reference) + MaybeSync + 'static
+	{
+		self.prepare_new_line = Some(Box::new(prepare_new_line));
+		self
+	}
+
+	/// Sets the storage strategy for the palette's cells.
+	pub fn storage(mut self, storage: Storage) -> Self {
+		self.storage = storage;
+		self
+	}
+
+	/// Sets the maximum number of source cells followed in any chain when 
+	/// resolving colors.
+	pub fn max_depth(mut self, max_depth: usize) -> Self {
+		self.max_depth = max_depth;
+		self
+	}
+
+	/// Constructs the configured `Data`.
+	pub fn build(self) -> Data {
+		Data {
+			cells: Shared::new(
+				Cells::new(self.storage, self.line_count, self.column_count)),
+			maximum_page_count: self.page_count,
+			default_line_count: self.line_count,
+			default_column_count: self.column_count,
+			max_depth: self.max_depth,
+			prepare_new_page: self.prepare_new_page,
+			prepare_new_line: self.prepare_new_line,
+			.. Default::default()
+		}
+	}
+}
+
+
+impl Default for DataBuilder {
+	fn default() -> Self {
+		DataBuilder::new()
+	}
+}
+
+
+
+impl Clone for Data {
+	/// Returns a deep copy of the palette data. Every cell is duplicated, and
+	/// derived cells reference the duplicated cells rather than the originals.
+	/// The page and line preparation functions are not cloned.
+	fn clone(&self) -> Self {
+		self.copy_with(|cell| cell.borrow().clone(), &self.vacancies.pending())
+	}
+}
+
+
+impl Data {
+	/// Returns a deep copy of the palette data, giving each copied cell the 
+	/// expression returned by the given function for the original. The given
+	/// addresses of emptied cells are marked free in the copy.
+	pub(crate) fn copy_with<F>(&self, expr_of: F, vacancies: &[Address]) 
+		-> Data
+		where F: Fn(&Cell) -> Expression
+	{
+		let addresses: HashMap<*const Cell, Address> = self.cells.iter()
+			.map(|(address, cell)| (&**cell as *const Cell, address))
+			.collect();
+		let mut cells = self.cells.empty_like();
+		for (address, cell) in self.cells.iter() {
+			cells.insert(address, Shared::new(Cell::new(expr_of(cell))));
+		}
+
+		// Redirect the sources of the copies to the copied cells.
+		for cell in cells.values() {
+			for source in cell.borrow_mut().sources_mut() {
+				*source = source.upgrade()
+					.and_then(|s| addresses.get(&(&*s as *const Cell)))
+					.map_or_else(Weak::new, |a| Shared::downgrade(&cells[a]));
+			}
+		}
+
+		let mut data = Data {
+			cells: Shared::new(cells),
+			names: self.names.clone(),
+			metadata: self.metadata.clone(),
+			maximum_page_count: self.maximum_page_count,
+			default_line_count: self.default_line_count,
+			default_column_count: self.default_column_count,
+			max_depth: self.max_depth,
+			cycles: self.cycles.clone(),
+			ramps: self.ramps.clone(),
+			constraints: self.constraints.clone(),
+			schema: self.schema.clone(),
+			free_hint: self.free_hint,
+			occupancy: self.occupancy.clone(),
+			vacancies: Default::default(),
+			modified_cells: BTreeSet::new(),
+			prepare_new_page: None,
+			prepare_new_line: None,
+		};
+		data.adopt_cells();
+		for &address in vacancies {
+			data.mark_free(address);
+		}
+		data
+	}
+}
+
+
+impl PartialEq for Data {
+	/// Compares the resolved contents of the palette data. Cells are equal if
+	/// they occupy the same addresses and resolve to the same colors, even if 
+	/// they are derived differently.
+	fn eq(&self, other: &Data) -> bool {
+		self.maximum_page_count == other.maximum_page_count &&
+		self.default_line_count == other.default_line_count &&
+		self.default_column_count == other.default_column_count &&
+		self.cells.len() == other.cells.len() &&
+		self.cells.iter()
+			.zip(other.cells.iter())
+			.all(|((a, a_cell), (b, b_cell))| 
+				a == b && a_cell.color() == b_cell.color()) &&
+		self.names == other.names &&
+		self.metadata == other.metadata &&
+		self.cycles == other.cycles &&
+		self.ramps == other.ramps &&
+		self.constraints == other.constraints &&
+		self.schema == other.schema
+	}
+}
+
+
+impl Hash for Data {
+	/// Hashes the resolved contents of the palette data, consistent with its
+	/// `PartialEq` implementation.
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.maximum_page_count.hash(state);
+		self.default_line_count.hash(state);
+		self.default_column_count.hash(state);
+
+		self.cells.len().hash(state);
+		for (address, cell) in self.cells.iter() {
+			address.hash(state);
+			cell.color().map(|c| (c.r(), c.g(), c.b())).hash(state);
+		}
+
+		// Map entries are combined without regard to order.
+		let names = self.names.iter()
+			.map(|(name, group)| {
+				let mut entry = Fnv64::default();
+				name.hash(&mut entry);
+				group.hash(&mut entry);
+				entry.finish()
+			})
+			.fold(0u64, u64::wrapping_add);
+		names.hash(state);
+		let metadata = self.metadata.iter()
+			.map(|(group, meta)| {
+				let mut entry = Fnv64::default();
+				group.hash(&mut entry);
+				meta.format_label.hash(&mut entry);
+				meta.name.hash(&mut entry);
+				meta.tags.hash(&mut entry);
+				meta.notes.hash(&mut entry);
+				meta.line_count.hash(&mut entry);
+				meta.column_count.hash(&mut entry);
+				meta.author.hash(&mut entry);
+				#[cfg(feature = "std")]
+				meta.source.hash(&mut entry);
+				meta.properties.hash(&mut entry);
+				meta.values.hash(&mut entry);
+				entry.finish()
+			})
+			.fold(0u64, u64::wrapping_add);
+		metadata.hash(state);
+
+		self.cycles.len().hash(state);
+		for cycle in &self.cycles {
+			cycle.start.hash(state);
+			cycle.end.hash(state);
+			cycle.rate.to_bits().hash(state);
+			cycle.direction.hash(state);
+		}
+		self.ramps.len().hash(state);
+		for ramp in &self.ramps {
+			ramp.anchors.hash(state);
+			ramp.steps.hash(state);
+			ramp.interpolation.hash(state);
+			hash_easing(ramp.easing, state);
+			ramp.location.hash(state);
+		}
+		self.constraints.len().hash(state);
+		for (address, constraint) in &self.constraints {
+			address.hash(state);
+			constraint.hash(state);
+		}
+	}
+}
+
+
+/// Feeds the given easing into the given hasher.
+fn hash_easing<H: Hasher>(easing: Easing, state: &mut H) {
+	match easing {
+		Easing::Linear => 0u8.hash(state),
+		Easing::EaseIn => 1u8.hash(state),
+		Easing::EaseOut => 2u8.hash(state),
+		Easing::EaseInOut => 3u8.hash(state),
+		Easing::Gamma(gamma) => {
+			4u8.hash(state);
+			gamma.to_bits().hash(state);
+		},
+		Easing::CubicBezier(x1, y1, x2, y2) => {
+			5u8.hash(state);
+			for value in &[x1, y1, x2, y2] {
+				value.to_bits().hash(state);
+			}
+		},
+	}
+}
+
+
 impl fmt::Debug for Data {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		write!(f, "Data {{ \
@@ -436,47 +3265,96 @@ impl fmt::Debug for Data {
 
 
 impl fmt::Display for Data {
+	/// Writes the palette as a table of addresses, colors, names, and tags, 
+	/// with a header for each page and line. The alternate form (`{:#}`) 
+	/// writes each line of the palette as a single row of colors instead.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::Data;
+	/// use palette::Color;
+	/// 
+	/// let mut dat: Data = Default::default();
+	/// let addr = dat.add_color(Color::new(200, 30, 30)).unwrap();
+	/// dat.set_slot_name(addr, "Blood Red");
+	/// dat.add_color(Color::new(10, 20, 30)).unwrap();
+	///
+	/// let table = format!("{}", dat);
+	/// assert!(table.contains("00:00:00  #c81e1e  Blood Red"));
+	///
+	/// let compact = format!("{:#}", dat);
+	/// assert!(compact.contains("00:00  #c81e1e #0a141e"));
+	/// ```
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		if let Some(data) = self.metadata.get(&Reference::all()) {
 			write!(f, "{} ", data)?;
 		}
-		write!(f, 
-			"[{} pages] [{} expression(s)] [default wrap {}:{}]\n",
+		writeln!(f, 
+			"[{} pages] [{} expression(s)] [default wrap {}:{}]",
 			self.maximum_page_count,
 			self.len(),
 			self.default_line_count,
 			self.default_column_count
 		)?;
 
-		let mut cur_page_group = Reference::all();
-		let mut cur_line_group = Reference::all();
-		for (&address, cell) in &self.cells {
-
-			if cur_page_group != Reference::page_of(&address) {
-				match self.metadata.get(&Reference::page_of(&address)) {
-					Some(meta) => writeln!(f, "Page {} - {}", 
-						Reference::page_of(&address), 
-						meta)?
-					,
-					None => writeln!(f, "Page {}", Reference::page_of(&address))?,
+		let color_text = |cell: &Cell| cell.color()
+			.map_or_else(|| "-".to_owned(), |c| Hex(c).to_string());
+
+		// Compute the width of the name column.
+		let name_width = self.cells.keys()
+			.filter_map(|address| self.slot_name(address))
+			.map(|name| name.chars().count())
+			.max()
+			.unwrap_or(0)
+			.max("Name".len());
+
+		let mut cur_page = None;
+		let mut cur_line = None;
+		for (address, cell) in self.cells.iter() {
+			if cur_page != Some(address.page) {
+				let group = Reference::page_of(&address);
+				match self.metadata.get(&group) {
+					Some(meta) => writeln!(f, "== Page {} {} ==", 
+						address.page, 
+						meta.to_string().trim())?,
+					None => writeln!(f, "== Page {} ==", address.page)?,
 				}
-			};
+				cur_page = Some(address.page);
+				cur_line = None;
+			}
 
-			cur_page_group = Reference::page_of(&address);
-			if cur_line_group != Reference::line_of(&address) {
-				if let Some(meta) = self.metadata.get(&Reference::line_of(&address)) {
-					write!(f, "\t{}\n", meta)?;
+			if cur_line != Some(address.line) {
+				if f.alternate() {
+					if cur_line.is_some() { writeln!(f)?; }
+					write!(f, "{:02X}:{:02X} ", address.page, address.line)?;
+				} else {
+					let group = Reference::line_of(&address);
+					if let Some(meta) = self.metadata.get(&group) {
+						writeln!(f, "-- Line {} {}", 
+							address.line, 
+							meta.to_string().trim())?;
+					}
+					writeln!(f, "{:<10}{:<9}{:<w$}  Tags", 
+						"Address", "Color", "Name", w = name_width)?;
 				}
-				cur_line_group = Reference::line_of(&address);
-				write!(f, "\tAddress   Color\n")?;
+				cur_line = Some(address.line);
 			}
 
-			writeln!(f, "\t{:X}  {}",
-				address,
-				cell.borrow()
-					.color()
-					.map(|c| c.to_string())
-					.unwrap_or("-".to_string()))?;
+			if f.alternate() {
+				write!(f, " {}", color_text(cell))?;
+			} else {
+				let slot = Reference::from(address);
+				writeln!(f, "{:X}  {:<7}  {:<w$}  {}",
+					address,
+					color_text(cell),
+					self.slot_name(address).unwrap_or(""),
+					self.tags(&slot).join(", "),
+					w = name_width)?;
+			}
+		}
+		if f.alternate() && cur_line.is_some() {
+			writeln!(f)?;
 		}
 		Ok(())
 	}
@@ -486,14 +3364,24 @@ impl fmt::Display for Data {
 impl Default for Data {
 	fn default() -> Self {
 		Data {
-			cells: BTreeMap::new(),
+			cells: Shared::new(
+				Cells::new(Storage::Sparse, LINE_MAX, COLUMN_MAX)),
 			names: HashMap::new(),
 			metadata: HashMap::new(),
 			maximum_page_count: PAGE_MAX,
 			default_line_count: LINE_MAX,
 			default_column_count: COLUMN_MAX,
-			prepare_new_page: no_op,
-			prepare_new_line: no_op,
+			max_depth: DEFAULT_MAX_DEPTH,
+			cycles: Vec::new(),
+			ramps: Vec::new(),
+			constraints: BTreeMap::new(),
+			schema: MetaSchema::new(),
+			free_hint: Default::default(),
+			occupancy: Default::default(),
+			vacancies: Default::default(),
+			modified_cells: BTreeSet::new(),
+			prepare_new_page: None,
+			prepare_new_line: None,
 		}
 	}
 }
\ No newline at end of file
@@ -0,0 +1,301 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides color representations with alternative channel depths, along with
+//! functions for scaling channels to and from the reduced bit depths used by
+//! hardware palettes and file formats.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use result::{Error, Result};
+use utilities::clamped;
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
+
+/// Reduces an 8-bit channel value to the given number of bits by truncation.
+///
+/// # Examples
+///
+/// ```rust
+/// # use palette::depth::reduce;
+/// assert_eq!(reduce(255, 6), 63);
+/// assert_eq!(reduce(128, 5), 16);
+/// ```
+#[inline]
+pub fn reduce(channel: u8, bits: u8) -> u8 {
+	assert!(bits >= 1 && bits <= 8);
+	channel >> (8 - bits)
+}
+
+/// Expands a channel value with the given number of bits to 8 bits. The high
+/// bits are replicated into the low bits so that the full range is covered.
+/// Any bits of the value above the given number of bits are ignored.
+///
+/// # Examples
+///
+/// ```rust
+/// # use palette::depth::expand;
+/// assert_eq!(expand(63, 6), 255);
+/// assert_eq!(expand(0, 6), 0);
+/// assert_eq!(expand(1, 1), 255);
+/// assert_eq!(expand(0xE1, 5), expand(0x01, 5));
+/// ```
+#[inline]
+pub fn expand(value: u8, bits: u8) -> u8 {
+	assert!(bits >= 1 && bits <= 8);
+	let value = value as u16 & ((1 << bits) - 1);
+	let mut result: u16 = 0;
+	let mut filled = 0;
+	while filled < 8 {
+		result = (result << bits) | value;
+		filled += bits;
+	}
+	(result >> (filled - 8)) as u8
+}
+
+
+/// Packs the color into a 15-bit BGR value, as used by the SNES and GBA.
+///
+/// # Examples
+///
+/// ```rust
+/// # use palette::Color;
+/// # use palette::depth::{pack_bgr555, unpack_bgr555};
+/// let c = Color::new(255, 0, 255);
+///
+/// assert_eq!(pack_bgr555(c), 0x7C1F);
+/// assert_eq!(unpack_bgr555(0x7C1F), c);
+/// ```
+pub fn pack_bgr555(color: Color) -> u16 {
+	(reduce(color.b(), 5) as u16) << 10
+		| (reduce(color.g(), 5) as u16) << 5
+		| reduce(color.r(), 5) as u16
+}
+
+/// Unpacks a color from a 15-bit BGR value, as used by the SNES and GBA.
+pub fn unpack_bgr555(value: u16) -> Color {
+	Color::new(
+		expand((value & 0x1F) as u8, 5),
+		expand(((value >> 5) & 0x1F) as u8, 5),
+		expand(((value >> 10) & 0x1F) as u8, 5))
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Grid
+////////////////////////////////////////////////////////////////////////////////
+/// A coarse grid of colors to which colors may be snapped, reducing their 
+/// effective color depth.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Color;
+/// use palette::depth::{Grid, RGB332};
+/// 
+/// let c = Color::new(255, 128, 64);
+///
+/// assert_eq!(RGB332.snap(c), Color::new(255, 146, 85));
+/// assert_eq!(Grid::Levels(4).snap(c), Color::new(255, 170, 85));
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Grid {
+	/// Red, green, and blue channels reduced to the given numbers of bits by 
+	/// truncation, as stored by hardware palettes and file formats.
+	Bits(u8, u8, u8),
+	/// Channels rounded to the nearest of the given number of evenly spaced
+	/// levels, between 2 and 256.
+	Levels(u16),
+}
+
+
+/// The 8-bit 3-3-2 grid.
+pub const RGB332: Grid = Grid::Bits(3, 3, 2);
+
+/// The 16-bit 5-6-5 grid.
+pub const RGB565: Grid = Grid::Bits(5, 6, 5);
+
+/// The 18-bit grid of 6 bits per channel, as used by ZPL palettes and VGA 
+/// hardware.
+pub const RGB666: Grid = Grid::Bits(6, 6, 6);
+
+
+impl Grid {
+	/// Returns an `InvalidGrid` error if a channel depth is not between 1 and 
+	/// 8 bits, or if the number of levels is not between 2 and 256.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::depth::{Grid, RGB565};
+	/// 
+	/// assert!(RGB565.validate().is_ok());
+	/// assert!(Grid::Bits(0, 6, 5).validate().is_err());
+	/// assert!(Grid::Levels(1).validate().is_err());
+	/// ```
+	pub fn validate(self) -> Result<()> {
+		let valid = match self {
+			Grid::Bits(r, g, b) => [r, g, b].iter()
+				.all(|&bits| bits >= 1 && bits <= 8),
+			Grid::Levels(levels) => levels >= 2 && levels <= 256,
+		};
+		if valid { Ok(()) } else { Err(Error::InvalidGrid(self)) }
+	}
+
+	/// Returns the nearest color on the grid. Colors on `Bits` grids are 
+	/// snapped as they would be stored and reloaded by a format of that 
+	/// depth.
+	///
+	/// # Panics
+	///
+	/// Panics if the grid is not valid. See `Grid::validate`.
+	pub fn snap(self, color: Color) -> Color {
+		match self {
+			Grid::Bits(r, g, b) => Color::new(
+				expand(reduce(color.r(), r), r),
+				expand(reduce(color.g(), g), g),
+				expand(reduce(color.b(), b), b)),
+
+			Grid::Levels(levels) => {
+				assert!(levels >= 2 && levels <= 256);
+				let steps = (levels - 1) as u32;
+				let snap = |c: u8| {
+					let level = (c as u32 * steps + 127) / 255;
+					((level * 255 + steps / 2) / steps) as u8
+				};
+				Color::new(snap(color.r()), snap(color.g()), snap(color.b()))
+			},
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Color16
+////////////////////////////////////////////////////////////////////////////////
+/// A color with 16-bit channels.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Color;
+/// use palette::depth::Color16;
+/// 
+/// let c = Color16::from(Color::new(255, 128, 0));
+///
+/// assert_eq!(c, Color16::new(0xFFFF, 0x8080, 0));
+/// assert_eq!(Color::from(c), Color::new(255, 128, 0));
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub struct Color16 {
+	/// The red channel.
+	pub r: u16,
+	/// The green channel.
+	pub g: u16,
+	/// The blue channel.
+	pub b: u16,
+}
+
+
+impl Color16 {
+	/// Creates a new `Color16`.
+	pub fn new(r: u16, g: u16, b: u16) -> Self {
+		Color16 {r: r, g: g, b: b}
+	}
+}
+
+
+impl From<Color> for Color16 {
+	fn from(color: Color) -> Self {
+		// Multiplying by 257 maps 0xFF to 0xFFFF exactly.
+		Color16 {
+			r: color.r() as u16 * 257,
+			g: color.g() as u16 * 257,
+			b: color.b() as u16 * 257,
+		}
+	}
+}
+
+
+impl From<Color16> for Color {
+	fn from(color: Color16) -> Self {
+		let scale = |c: u16| ((c as u32 * 255 + 32_767) / 65_535) as u8;
+		Color::new(scale(color.r), scale(color.g), scale(color.b))
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ColorF32
+////////////////////////////////////////////////////////////////////////////////
+/// A color with floating point channels. Channels are nominally between 0 and 
+/// 1, but may exceed that range to represent high dynamic range colors.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct ColorF32 {
+	/// The red channel.
+	pub r: f32,
+	/// The green channel.
+	pub g: f32,
+	/// The blue channel.
+	pub b: f32,
+}
+
+
+impl ColorF32 {
+	/// Creates a new `ColorF32`.
+	pub fn new(r: f32, g: f32, b: f32) -> Self {
+		ColorF32 {r: r, g: g, b: b}
+	}
+}
+
+
+impl From<Color> for ColorF32 {
+	fn from(color: Color) -> Self {
+		ColorF32 {
+			r: color.r() as f32 / 255.0,
+			g: color.g() as f32 / 255.0,
+			b: color.b() as f32 / 255.0,
+		}
+	}
+}
+
+
+impl From<ColorF32> for Color {
+	/// Converts the color to 8-bit channels, clamping channels outside of the 
+	/// nominal range.
+	fn from(color: ColorF32) -> Self {
+		let scale = |c: f32| (clamped(c, 0.0, 1.0) * 255.0).round() as u8;
+		Color::new(scale(color.r), scale(color.g), scale(color.b))
+	}
+}
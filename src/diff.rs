@@ -0,0 +1,333 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides functions for comparing and merging palettes.
+//!
+//! Palettes are compared by the colors their cells resolve to, so a derived
+//! cell is considered changed only if its color changes. Cells which do not 
+//! resolve to a color are treated as absent.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::Address;
+use data::Data;
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+
+/// Returns the resolved colors of the given palette data.
+fn resolved_colors(data: &Data) -> BTreeMap<Address, Color> {
+	data.colors().collect()
+}
+
+/// Returns the change from the given old color to the given new color, or None
+/// if they are the same.
+fn change(old: Option<Color>, new: Option<Color>) -> Option<Change> {
+	match (old, new) {
+		(None, Some(new)) => Some(Change::Added(new)),
+		(Some(old), None) => Some(Change::Removed(old)),
+		(Some(old), Some(new)) if old != new 
+			=> Some(Change::Changed {old: old, new: new}),
+		_ => None,
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Change
+////////////////////////////////////////////////////////////////////////////////
+/// A change to the color at a single address.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Change {
+	/// A color was added to an empty address.
+	Added(Color),
+	/// A color was removed.
+	Removed(Color),
+	/// A color was replaced.
+	Changed {
+		/// The original color.
+		old: Color,
+		/// The replacement color.
+		new: Color,
+	},
+}
+
+
+impl Change {
+	/// Returns the color before the change, or None if the address was empty.
+	pub fn before(&self) -> Option<Color> {
+		match *self {
+			Change::Added(_) => None,
+			Change::Removed(old) | Change::Changed {old, ..} => Some(old),
+		}
+	}
+
+	/// Returns the color after the change, or None if the address is empty.
+	pub fn after(&self) -> Option<Color> {
+		match *self {
+			Change::Removed(_) => None,
+			Change::Added(new) | Change::Changed {new, ..} => Some(new),
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// PaletteDiff
+////////////////////////////////////////////////////////////////////////////////
+/// The set of changes between two palettes, by address.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::data::Data;
+/// use palette::diff::Change;
+/// use palette::{Address, Color};
+/// 
+/// let mut a: Data = Default::default();
+/// a.add_color(Color::new(10, 10, 10)).unwrap();
+/// a.add_color(Color::new(20, 20, 20)).unwrap();
+///
+/// let mut b: Data = Default::default();
+/// b.add_color(Color::new(10, 10, 10)).unwrap();
+/// b.add_color(Color::new(25, 25, 25)).unwrap();
+/// b.add_color(Color::new(30, 30, 30)).unwrap();
+///
+/// let diff = a.diff(&b);
+/// assert_eq!(diff.len(), 2);
+/// assert_eq!(diff.get(Address::new(0, 0, 1)), Some(&Change::Changed {
+/// 	old: Color::new(20, 20, 20), 
+/// 	new: Color::new(25, 25, 25),
+/// }));
+/// assert_eq!(diff.get(Address::new(0, 0, 2)), 
+/// 	Some(&Change::Added(Color::new(30, 30, 30))));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PaletteDiff {
+	/// The changes, by address.
+	changes: BTreeMap<Address, Change>,
+}
+
+
+impl PaletteDiff {
+	/// Creates a new, empty PaletteDiff.
+	pub fn new() -> PaletteDiff {
+		Default::default()
+	}
+
+	/// Returns the changes needed to turn the palette data `a` into `b`.
+	pub fn between(a: &Data, b: &Data) -> PaletteDiff {
+		let a = resolved_colors(a);
+		let b = resolved_colors(b);
+		let addresses: BTreeSet<Address> = a.keys()
+			.chain(b.keys())
+			.cloned()
+			.collect();
+
+		let mut diff = PaletteDiff::new();
+		for address in addresses {
+			let old = a.get(&address).cloned();
+			let new = b.get(&address).cloned();
+			if let Some(change) = change(old, new) {
+				diff.changes.insert(address, change);
+			}
+		}
+		diff
+	}
+
+	/// Returns the number of changed addresses.
+	pub fn len(&self) -> usize {
+		self.changes.len()
+	}
+
+	/// Returns true if there are no changes.
+	pub fn is_empty(&self) -> bool {
+		self.changes.is_empty()
+	}
+
+	/// Returns the change at the given address, or None if it is unchanged.
+	pub fn get(&self, address: Address) -> Option<&Change> {
+		self.changes.get(&address)
+	}
+
+	/// Records a change at the given address, replacing any previous change.
+	pub fn insert(&mut self, address: Address, change: Change) {
+		self.changes.insert(address, change);
+	}
+
+	/// Returns an iterator over the changed addresses and their changes, in 
+	/// address order.
+	pub fn iter<'a>(&'a self) 
+		-> impl Iterator<Item=(Address, &'a Change)> + 'a 
+	{
+		self.changes.iter().map(|(&address, change)| (address, change))
+	}
+
+	/// Returns an iterator over the addresses of added colors.
+	pub fn added<'a>(&'a self) -> impl Iterator<Item=Address> + 'a {
+		self.iter()
+			.filter(|&(_, change)| change.before().is_none())
+			.map(|(address, _)| address)
+	}
+
+	/// Returns an iterator over the addresses of removed colors.
+	pub fn removed<'a>(&'a self) -> impl Iterator<Item=Address> + 'a {
+		self.iter()
+			.filter(|&(_, change)| change.after().is_none())
+			.map(|(address, _)| address)
+	}
+
+	/// Returns an iterator over the addresses of replaced colors.
+	pub fn changed<'a>(&'a self) -> impl Iterator<Item=Address> + 'a {
+		self.iter()
+			.filter(|&(_, change)| match *change {
+				Change::Changed {..} => true,
+				_ => false,
+			})
+			.map(|(address, _)| address)
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Conflict
+////////////////////////////////////////////////////////////////////////////////
+/// An address changed differently by both sides of a merge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Conflict {
+	/// The conflicting address.
+	pub address: Address,
+	/// The color in the common ancestor.
+	pub base: Option<Color>,
+	/// The color on our side.
+	pub ours: Option<Color>,
+	/// The color on their side.
+	pub theirs: Option<Color>,
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Merge
+////////////////////////////////////////////////////////////////////////////////
+/// The result of a three-way merge.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Merge {
+	/// The changes to apply to our side to incorporate their changes.
+	pub diff: PaletteDiff,
+	/// The addresses changed differently by both sides. Our colors are kept 
+	/// for these addresses.
+	pub conflicts: Vec<Conflict>,
+}
+
+
+impl Merge {
+	/// Returns true if the merge had no conflicts.
+	pub fn is_clean(&self) -> bool {
+		self.conflicts.is_empty()
+	}
+}
+
+
+/// Performs a three-way merge of the palettes `ours` and `theirs`, which share
+/// the common ancestor `base`. 
+///
+/// Changes made only on their side are collected into a diff which may be 
+/// applied to our side using the `ApplyDiff` operation. Addresses changed on 
+/// both sides to different colors are reported as conflicts.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::diff::merge;
+/// use palette::operation::*;
+/// 
+/// let mut base = Palette::new("Base", Format::Default, false);
+/// base.apply(Box::new(InsertColor::new(Color::new(10, 10, 10)))).unwrap();
+/// base.apply(Box::new(InsertColor::new(Color::new(20, 20, 20)))).unwrap();
+///
+/// let mut ours = Palette::new("Ours", Format::Default, true);
+/// ours.apply(Box::new(InsertColor::new(Color::new(10, 10, 10)))).unwrap();
+/// ours.apply(Box::new(InsertColor::new(Color::new(21, 21, 21)))).unwrap();
+///
+/// let mut theirs = Palette::new("Theirs", Format::Default, false);
+/// theirs.apply(Box::new(InsertColor::new(Color::new(15, 15, 15)))).unwrap();
+/// theirs.apply(Box::new(InsertColor::new(Color::new(22, 22, 22)))).unwrap();
+///
+/// let merged = merge(base.data(), ours.data(), theirs.data());
+/// assert_eq!(merged.conflicts.len(), 1);
+/// assert_eq!(merged.conflicts[0].address, Address::new(0, 0, 1));
+///
+/// ours.apply(Box::new(ApplyDiff::new(merged.diff))).unwrap();
+/// assert_eq!(ours.color(Address::new(0, 0, 0)), 
+/// 	Some(Color::new(15, 15, 15)));
+/// assert_eq!(ours.color(Address::new(0, 0, 1)), 
+/// 	Some(Color::new(21, 21, 21)));
+/// ```
+pub fn merge(base: &Data, ours: &Data, theirs: &Data) -> Merge {
+	let base = resolved_colors(base);
+	let ours = resolved_colors(ours);
+	let theirs = resolved_colors(theirs);
+	let addresses: BTreeSet<Address> = base.keys()
+		.chain(ours.keys())
+		.chain(theirs.keys())
+		.cloned()
+		.collect();
+
+	let mut merged = Merge::default();
+	for address in addresses {
+		let b = base.get(&address).cloned();
+		let o = ours.get(&address).cloned();
+		let t = theirs.get(&address).cloned();
+
+		if o == t || b == t {
+			// Both sides agree, or only our side changed.
+			continue;
+		} else if b == o {
+			// Only their side changed.
+			if let Some(change) = change(o, t) {
+				merged.diff.insert(address, change);
+			}
+		} else {
+			merged.conflicts.push(Conflict {
+				address: address,
+				base: b,
+				ours: o,
+				theirs: t,
+			});
+		}
+	}
+	merged
+}
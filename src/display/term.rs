@@ -0,0 +1,196 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides previews of palettes for terminals supporting 24-bit color.
+//!
+//! Each occupied page is drawn as a grid of cells with its line and column 
+//! indices, using ANSI escape codes to set the background color of each cell.
+//! Only the lines and columns up to the last occupied cell of each page are
+//! drawn. Empty slots are drawn as dots.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{Address, Page, Reference};
+use data::Data;
+use space::relative_luminance;
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::collections::BTreeSet;
+use std::io;
+
+
+/// The escape sequence which resets the terminal colors.
+const RESET: &str = "\x1b[0m";
+
+
+/// Returns the escape sequence which sets the background to the given color.
+fn background(color: Color) -> String {
+	format!("\x1b[48;2;{};{};{}m", color.r(), color.g(), color.b())
+}
+
+/// Returns the escape sequence which sets the foreground to a color readable
+/// against the given color.
+fn ink(color: Color) -> &'static str {
+	if relative_luminance(color) > 0.18 {
+		"\x1b[38;2;0;0;0m"
+	} else {
+		"\x1b[38;2;255;255;255m"
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Preview
+////////////////////////////////////////////////////////////////////////////////
+/// Renders palette data as colored text for a terminal.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::data::Data;
+/// use palette::display::term::Preview;
+/// use palette::Color;
+/// 
+/// let mut dat: Data = Default::default();
+/// dat.add_color(Color::new(255, 0, 0)).unwrap();
+///
+/// let text = Preview::new().cell_width(2).render(&dat);
+/// assert!(text.starts_with("Page 0\n"));
+/// assert!(text.contains("\x1b[48;2;255;0;0m"));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Preview {
+	/// The width of each cell in characters.
+	cell_width: usize,
+	/// Whether to print the hexadecimal value of each color in its cell.
+	hex: bool,
+}
+
+
+impl Preview {
+	/// Creates a new `Preview` with the default settings.
+	pub fn new() -> Self {
+		Preview {
+			cell_width: 4,
+			hex: false,
+		}
+	}
+
+	/// Sets the width of each cell in characters. Cells are at least two 
+	/// characters wide.
+	pub fn cell_width(mut self, cell_width: usize) -> Self {
+		self.cell_width = cell_width.max(2);
+		self
+	}
+
+	/// Configures whether to print the hexadecimal value of each color in its
+	/// cell. This widens the cells to fit the values.
+	pub fn hex(mut self, hex: bool) -> Self {
+		self.hex = hex;
+		self
+	}
+
+	/// Returns the preview of the given palette data.
+	pub fn render(&self, data: &Data) -> String {
+		let mut out = Vec::new();
+		self.write(data, &mut out).expect("write to vec");
+		String::from_utf8(out).expect("preview is utf-8")
+	}
+
+	/// Writes the preview of the given palette data to the given buffer.
+	pub fn write<W>(&self, data: &Data, out_buf: &mut W) -> io::Result<()>
+		where W: io::Write
+	{
+		let width = if self.hex { 
+			self.cell_width.max(7) 
+		} else { 
+			self.cell_width 
+		};
+		let pages: BTreeSet<Page> = data.iter()
+			.map(|(address, _)| address.page)
+			.collect();
+
+		for page in pages {
+			let group = Reference::page_of(&Address::new(page, 0, 0));
+			match data.name(&group).or_else(|| data.label(&group)) {
+				Some(name) => writeln!(out_buf, "Page {} - {}", page, name)?,
+				None => writeln!(out_buf, "Page {}", page)?,
+			}
+
+			let (lines, columns) = data.iter_page(page)
+				.fold((0, 0), |(l, c), (address, _)| (
+					l.max(address.line as usize + 1), 
+					c.max(address.column as usize + 1)));
+
+			// Column header.
+			write!(out_buf, "   ")?;
+			for column in 0..columns {
+				write!(out_buf, "{:<w$X}", column, w = width)?;
+			}
+			writeln!(out_buf)?;
+
+			for line in 0..lines {
+				let line_group = Reference::line_of(
+					&Address::new(page, line as u8, 0));
+				let line_columns = columns
+					.min(data.column_count(&line_group) as usize);
+				write!(out_buf, "{:02X} ", line)?;
+				for column in 0..line_columns {
+					let address = Address::new(page, line as u8, column as u8);
+					match data.cell(address).and_then(|cell| cell.color()) {
+						Some(color) => {
+							let text = if self.hex {
+								format!("{:02x}{:02x}{:02x}", 
+									color.r(), color.g(), color.b())
+							} else {
+								String::new()
+							};
+							write!(out_buf, "{}{}{:^w$}{}", 
+								background(color), 
+								ink(color),
+								text, 
+								RESET,
+								w = width)?;
+						},
+						None => write!(out_buf, "{:^w$}", "·", w = width)?,
+					}
+				}
+				writeln!(out_buf)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+
+impl Default for Preview {
+	fn default() -> Self {
+		Preview::new()
+	}
+}
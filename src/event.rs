@@ -0,0 +1,311 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides change notifications for palettes.
+//!
+//! Listeners registered with a `Palette` are notified of the changes made by 
+//! each applied operation, undo, redo, or transaction. The cells recorded as
+//! changed by the operation, and the cells derived from them, are compared 
+//! with their last reported state, so changes are reported the same way 
+//! regardless of which operation made them. Events are sent in address order.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{Address, Reference};
+use cell::Shared;
+use data::{Data, MetaData};
+use operation::MaybeSync;
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::sync::Mutex;
+use std::sync::mpsc::Sender;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Event
+////////////////////////////////////////////////////////////////////////////////
+/// A change to a palette.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::event::Event;
+/// use palette::expression::Expression;
+/// use palette::operation::*;
+/// use std::sync::mpsc::channel;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// let (a, b) = (Address::new(0, 0, 0), Address::new(0, 0, 1));
+/// pal.apply(Box::new(InsertColor::new(Color::new(100, 50, 0)))).unwrap();
+/// pal.apply(Box::new(InsertDerived::complement(a))).unwrap();
+///
+/// let (sender, receiver) = channel();
+/// pal.add_sender(sender);
+/// let blue = Expression::Color(Color::new(0, 50, 100));
+/// pal.apply(Box::new(SetExpression::new(a, blue))).unwrap();
+/// pal.undo().unwrap();
+///
+/// // Derived colors are reported along with their sources, in address order.
+/// let (orange, blue) = (Color::new(100, 50, 0), Color::new(0, 50, 100));
+/// let colors: Vec<Event> = receiver.try_iter()
+/// 	.filter(|event| match *event {
+/// 		Event::MetadataChanged(_) => false,
+/// 		_ => true,
+/// 	})
+/// 	.collect();
+/// assert_eq!(colors, vec![
+/// 	Event::ColorChanged(a, orange, blue),
+/// 	Event::ColorChanged(b, blue, orange),
+/// 	Event::ColorChanged(a, blue, orange),
+/// 	Event::ColorChanged(b, orange, blue),
+/// ]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+	/// A color was added to an empty address.
+	ColorAdded(Address),
+	/// The color at an address changed from the first color to the second.
+	ColorChanged(Address, Color, Color),
+	/// The color at an address was removed.
+	ColorRemoved(Address),
+	/// The metadata of a group was added, changed, or removed.
+	MetadataChanged(Reference),
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Listener
+////////////////////////////////////////////////////////////////////////////////
+/// Receives palette change events. Implemented for all closures taking an
+/// `&Event`.
+pub trait Listener: MaybeSync {
+	/// Handles the given event.
+	fn notify(&mut self, event: &Event);
+}
+
+
+impl<F> Listener for F where F: FnMut(&Event) + MaybeSync {
+	fn notify(&mut self, event: &Event) {
+		self(event)
+	}
+}
+
+
+/// Forwards events to a channel.
+struct ChannelListener {
+	/// The sending half of the channel. Held in a `Mutex` so that the listener
+	/// may be shared between threads.
+	sender: Mutex<Sender<Event>>,
+}
+
+
+impl Listener for ChannelListener {
+	fn notify(&mut self, event: &Event) {
+		if let Ok(sender) = self.sender.get_mut() {
+			// A closed channel is not an error for the palette.
+			let _ = sender.send(event.clone());
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ListenerId
+////////////////////////////////////////////////////////////////////////////////
+/// Identifies a registered listener, allowing it to be removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ListenerId(usize);
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Listeners
+////////////////////////////////////////////////////////////////////////////////
+/// The listeners registered with a palette.
+#[derive(Default)]
+pub(crate) struct Listeners {
+	/// The id to assign to the next registered listener.
+	next_id: usize,
+	/// The registered listeners.
+	listeners: Vec<(ListenerId, Box<Listener>)>,
+	/// The palette state last reported to the listeners, or None if there are
+	/// no listeners.
+	reported: Option<Reported>,
+}
+
+
+impl Listeners {
+	/// Registers a listener for the given palette data, returning its id.
+	pub(crate) fn add(&mut self, listener: Box<Listener>, data: &Data) 
+		-> ListenerId
+	{
+		if self.reported.is_none() {
+			self.reported = Some(Reported::capture(data));
+		}
+		let id = ListenerId(self.next_id);
+		self.next_id += 1;
+		self.listeners.push((id, listener));
+		id
+	}
+
+	/// Registers a channel sender as a listener for the given palette data, 
+	/// returning its id.
+	pub(crate) fn add_sender(&mut self, sender: Sender<Event>, data: &Data) 
+		-> ListenerId
+	{
+		let listener = ChannelListener {sender: Mutex::new(sender)};
+		self.add(Box::new(listener), data)
+	}
+
+	/// Removes the listener with the given id. Returns false if no such 
+	/// listener is registered.
+	pub(crate) fn remove(&mut self, id: ListenerId) -> bool {
+		let len = self.listeners.len();
+		self.listeners.retain(|&(listener_id, _)| listener_id != id);
+		if self.listeners.is_empty() { self.reported = None; }
+		self.listeners.len() != len
+	}
+
+	/// Notifies all listeners of the changes made to the cells at the given 
+	/// addresses, to the cells derived from them, and to their groups, since 
+	/// the last notification.
+	pub(crate) fn notify_changes(
+		&mut self, 
+		changed: &BTreeSet<Address>, 
+		data: &Data)
+	{
+		let events = match self.reported {
+			Some(ref mut reported) => reported.update(changed, data),
+			None => return,
+		};
+
+		for event in &events {
+			for &mut (_, ref mut listener) in &mut self.listeners {
+				listener.notify(event);
+			}
+		}
+	}
+}
+
+
+impl fmt::Debug for Listeners {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "Listeners {{ count: {} }}", self.listeners.len())
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Reported
+////////////////////////////////////////////////////////////////////////////////
+/// The state of palette data last reported to the listeners. Only the parts of
+/// the state touched by a change are compared, so that reporting a change does
+/// not scan the whole palette.
+struct Reported {
+	/// The resolved colors of the palette.
+	colors: BTreeMap<Address, Color>,
+	/// The metadata of the palette.
+	metadata: HashMap<Reference, MetaData>,
+}
+
+
+impl Reported {
+	/// Captures the state of the given palette data.
+	fn capture(data: &Data) -> Reported {
+		Reported {
+			colors: data.colors().collect(),
+			metadata: data.metadata.clone(),
+		}
+	}
+
+	/// Updates the state of the cells at the given addresses, the cells 
+	/// derived from them, and their groups. Returns an event for each 
+	/// difference found, in address order.
+	fn update(&mut self, changed: &BTreeSet<Address>, data: &Data) 
+		-> Vec<Event>
+	{
+		let mut events = Vec::new();
+
+		// Only referenced cells have weak references, so the dependency graph
+		// is only needed if a changed cell is a source.
+		let mut addresses = changed.clone();
+		let referenced = changed.iter().any(|&address| data.cell(address)
+			.map_or(false, |cell| Shared::weak_count(&cell) > 0));
+		if referenced {
+			let edges = data.dependency_edges();
+			let mut queue: Vec<Address> = changed.iter().cloned().collect();
+			while let Some(source) = queue.pop() {
+				for &(_, dependent) in edges.iter()
+					.filter(|&&(edge_source, _)| edge_source == source)
+				{
+					if addresses.insert(dependent) { queue.push(dependent); }
+				}
+			}
+		}
+
+		for address in addresses {
+			let new = data.resolve(address).unwrap_or(None);
+			let old = match new {
+				Some(color) => self.colors.insert(address, color),
+				None => self.colors.remove(&address),
+			};
+			match (old, new) {
+				(None, Some(_)) => events.push(Event::ColorAdded(address)),
+				(Some(_), None) => events.push(Event::ColorRemoved(address)),
+				(Some(old), Some(new)) if old != new 
+					=> events.push(Event::ColorChanged(address, old, new)),
+				_ => (),
+			}
+		}
+
+		let mut groups = BTreeSet::new();
+		groups.insert(Reference::all());
+		for address in changed {
+			groups.insert(Reference::from(*address));
+			groups.insert(Reference::line_of(address));
+			groups.insert(Reference::page_of(address));
+		}
+		for group in groups {
+			let new = data.metadata.get(&group);
+			if self.metadata.get(&group) == new { continue; }
+			match new {
+				Some(meta) => self.metadata.insert(group.clone(), meta.clone()),
+				None => self.metadata.remove(&group),
+			};
+			events.push(Event::MetadataChanged(group));
+		}
+		events
+	}
+}
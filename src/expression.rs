@@ -28,10 +28,19 @@
 //!
 ////////////////////////////////////////////////////////////////////////////////
 
+// Local imports.
+use cell::{Cell, Shared, Weak, DEFAULT_MAX_DEPTH};
+use result::{Error, Result};
+use space::{Adjustment, Cmyk, Easing, Interpolation};
+use utilities::lerp_u8;
+
 // Non-local imports.
 use color::Color;
 
 // Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+use std::collections::HashSet;
 use std::fmt;
 
 
@@ -40,31 +49,288 @@ use std::fmt;
 // Expression
 ////////////////////////////////////////////////////////////////////////////////
 /// An AST in the color-expression grammar.
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub enum Expression {
 	/// An empty expression.
 	Empty,
 	/// A pure color.
-	Color(Color)
+	Color(Color),
+	/// A pure color specified in CMYK.
+	Cmyk(Cmyk),
+	/// A linear interpolation between the colors of two cells.
+	Blend {
+		/// The cell providing the starting color.
+		a: Weak<Cell>,
+		/// The cell providing the ending color.
+		b: Weak<Cell>,
+		/// The interpolation ratio, between 0 and 1.
+		ratio: f32,
+		/// The color space to interpolate in.
+		interpolation: Interpolation,
+		/// The easing curve applied to the ratio.
+		easing: Easing,
+	},
+	/// A color lightened toward white.
+	Lighten {
+		/// The cell providing the source color.
+		a: Weak<Cell>,
+		/// The amount to lighten by, between 0 and 1.
+		amount: f32,
+	},
+	/// A color darkened toward black.
+	Darken {
+		/// The cell providing the source color.
+		a: Weak<Cell>,
+		/// The amount to darken by, between 0 and 1.
+		amount: f32,
+	},
+	/// The complement of a color; the color with the opposite hue and the same
+	/// lightness.
+	Complement {
+		/// The cell providing the source color.
+		a: Weak<Cell>,
+	},
+	/// A color with an adjustment applied.
+	Adjust {
+		/// The cell providing the source color.
+		a: Weak<Cell>,
+		/// The adjustment to apply.
+		adjustment: Adjustment,
+	},
+	/// A fixed color detached from a derived expression. The expression is 
+	/// kept so that the link to its sources may be restored.
+	Frozen {
+		/// The fixed color.
+		color: Color,
+		/// The derived expression the color was detached from.
+		recipe: Box<Expression>,
+	},
 }
 
 
+
+
 impl Expression {
-	/// Returns the `Color` generated by the expression.
+	/// Returns the order of the expression; the number of source cells it
+	/// depends upon directly. See `Cell::order` for the derivation order.
+	pub fn order(&self) -> usize {
+		self.sources().len()
+	}
+
+	/// Returns references to the source cells the expression depends upon.
+	/// The sources of a frozen expression's recipe are included, as they will
+	/// be needed when it is thawed.
+	pub fn sources(&self) -> Vec<Weak<Cell>> {
+		match *self {
+			Expression::Empty				=> vec![],
+			Expression::Color(..)			=> vec![],
+			Expression::Cmyk(..)			=> vec![],
+			Expression::Blend {ref a, ref b, ..} => vec![a.clone(), b.clone()],
+			Expression::Lighten {ref a, ..}	|
+			Expression::Darken {ref a, ..}	|
+			Expression::Complement {ref a}	|
+			Expression::Adjust {ref a, ..}	=> vec![a.clone()],
+			Expression::Frozen {ref recipe, ..} => recipe.sources(),
+		}
+	}
+
+	/// Returns mutable references to the source cells the expression depends
+	/// upon, allowing them to be retargeted.
+	pub fn sources_mut(&mut self) -> Vec<&mut Weak<Cell>> {
+		match *self {
+			Expression::Empty				=> vec![],
+			Expression::Color(..)			=> vec![],
+			Expression::Cmyk(..)			=> vec![],
+			Expression::Blend {ref mut a, ref mut b, ..} => vec![a, b],
+			Expression::Lighten {ref mut a, ..}	|
+			Expression::Darken {ref mut a, ..}	|
+			Expression::Complement {ref mut a}	|
+			Expression::Adjust {ref mut a, ..}	=> vec![a],
+			Expression::Frozen {ref mut recipe, ..} => recipe.sources_mut(),
+		}
+	}
+
+	/// Returns whether the expression depends upon the given cell, either 
+	/// directly or through the sources of its sources.
+	pub fn depends_on(&self, cell: &Shared<Cell>) -> bool {
+		let mut visited = HashSet::new();
+		let mut stack = self.sources();
+		while let Some(source) = stack.pop() {
+			if let Some(source) = source.upgrade() {
+				if Shared::ptr_eq(&source, cell) { return true; }
+				if visited.insert(&*source as *const Cell) {
+					stack.extend(source.borrow().sources());
+				}
+			}
+		}
+		false
+	}
+
+	/// Returns the `Color` generated by the expression, or `None` if it is 
+	/// invalid or if it depends on a chain of more than `DEFAULT_MAX_DEPTH`
+	/// source cells.
 	pub fn color(&self) -> Option<Color> {
-		None
+		self.resolve(DEFAULT_MAX_DEPTH).unwrap_or(None)
+	}
+
+	/// Returns the `Color` generated by the expression, or `None` if it is 
+	/// invalid. Returns a `MaxOrderExceeded` error if the expression depends 
+	/// on a chain of more than the given number of source cells.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::cell::{Cell, Shared};
+	/// use palette::expression::Expression;
+	/// use palette::Color;
+	///
+	/// let black = Expression::Color(Color::new(0, 0, 0));
+	/// let base = Shared::new(Cell::new(black));
+	/// let mid = Shared::new(Cell::new(Expression::Lighten {
+	/// 	a: Shared::downgrade(&base), 
+	/// 	amount: 0.5,
+	/// }));
+	/// let top = Expression::Complement {a: Shared::downgrade(&mid)};
+	///
+	/// assert!(top.resolve(2).unwrap().is_some());
+	/// assert!(top.resolve(1).is_err());
+	/// ```
+	pub fn resolve(&self, max_depth: usize) -> Result<Option<Color>> {
+		self.resolve_depth(max_depth).map(|(color, _)| color)
+	}
+
+	/// Returns the `Color` generated by the expression along with the length 
+	/// of the longest chain of source cells it depends on. Returns a 
+	/// `MaxOrderExceeded` error if the chain is longer than the given depth.
+	pub(crate) fn resolve_depth(&self, max_depth: usize) 
+		-> Result<(Option<Color>, usize)>
+	{
+		if let Expression::Frozen {color, ..} = *self {
+			return Ok((Some(color), 0));
+		}
+		let sources = self.sources();
+		if sources.is_empty() {
+			return Ok((self.resolve_with(&[]), 0));
+		}
+		if max_depth == 0 {
+			return Err(Error::MaxOrderExceeded);
+		}
+
+		let mut depth = 0;
+		let mut colors = Vec::with_capacity(sources.len());
+		for source in sources {
+			match source.upgrade() {
+				Some(cell) => {
+					let (color, d) = cell.resolve_depth(max_depth - 1)?;
+					depth = depth.max(d + 1);
+					colors.push(color);
+				},
+				None => {
+					depth = depth.max(1);
+					colors.push(None);
+				},
+			}
+		}
+		Ok((self.resolve_with(&colors), depth))
+	}
+
+	/// Returns the `Color` generated by the expression from the given colors
+	/// of its sources, in the order returned by `sources`.
+	pub(crate) fn resolve_with(&self, sources: &[Option<Color>]) 
+		-> Option<Color>
+	{
+		let source = |i: usize| sources.get(i).cloned().and_then(|c| c);
+		match *self {
+			Expression::Empty			=> None,
+			Expression::Color(color)	=> Some(color),
+			Expression::Cmyk(cmyk)		=> Some(Color::from(cmyk)),
+			Expression::Blend {ratio, interpolation, easing, ..} => {
+				let a = source(0)?;
+				let b = source(1)?;
+				Some(interpolation.interpolate(a, b, easing.apply(ratio)))
+			},
+
+			Expression::Lighten {amount, ..} => {
+				let a = source(0)?;
+				Some(Color::new(
+					lerp_u8(a.r(), 255, amount),
+					lerp_u8(a.g(), 255, amount),
+					lerp_u8(a.b(), 255, amount),
+				))
+			},
+
+			Expression::Darken {amount, ..} => {
+				let a = source(0)?;
+				Some(Color::new(
+					lerp_u8(a.r(), 0, amount),
+					lerp_u8(a.g(), 0, amount),
+					lerp_u8(a.b(), 0, amount),
+				))
+			},
+
+			Expression::Complement {..} => {
+				// Reflecting each channel across the channel range midpoint
+				// rotates the hue by 180 degrees and preserves lightness.
+				let a = source(0)?;
+				let max = a.r().max(a.g()).max(a.b()) as u16;
+				let min = a.r().min(a.g()).min(a.b()) as u16;
+				Some(Color::new(
+					(max + min - a.r() as u16) as u8,
+					(max + min - a.g() as u16) as u8,
+					(max + min - a.b() as u16) as u8,
+				))
+			},
+
+			Expression::Adjust {adjustment, ..} 
+				=> Some(adjustment.apply(source(0)?)),
+
+			Expression::Frozen {color, ..} => Some(color),
+		}
 	}
 }
 
 
 impl fmt::Debug for Expression {
-	fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match *self {
 			Expression::Empty
 				=> write!(f, "Expression::Empty"),
 
 			Expression::Color(ref color)
 				=> write!(f, "Expression::Color({:?})", color),
+
+			Expression::Cmyk(ref cmyk)
+				=> write!(f, "Expression::Cmyk({:?})", cmyk),
+
+			Expression::Blend {ratio, interpolation, easing, ..}
+				=> write!(f, 
+					"Expression::Blend {{ \
+						ratio: {}, \
+						interpolation: {:?}, \
+						easing: {:?} }}", 
+					ratio,
+					interpolation,
+					easing),
+
+			Expression::Lighten {amount, ..}
+				=> write!(f, "Expression::Lighten {{ amount: {} }}", amount),
+
+			Expression::Darken {amount, ..}
+				=> write!(f, "Expression::Darken {{ amount: {} }}", amount),
+
+			Expression::Complement {..}
+				=> write!(f, "Expression::Complement"),
+
+			Expression::Adjust {ref adjustment, ..}
+				=> write!(f, 
+					"Expression::Adjust {{ adjustment: {:?} }}", 
+					adjustment),
+
+			Expression::Frozen {ref color, ref recipe}
+				=> write!(f, 
+					"Expression::Frozen {{ color: {:?}, recipe: {:?} }}", 
+					color,
+					recipe),
 		}
 	}
 }
@@ -0,0 +1,189 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides functions for building palettes from existing image data by color
+//! quantization.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use data::Data;
+use result::Result;
+use space::Metric;
+use utilities::map_items;
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+use std::f32;
+
+
+/// The maximum number of refinement passes made by the k-means method.
+const KMEANS_MAX_ITERATIONS: usize = 16;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Method
+////////////////////////////////////////////////////////////////////////////////
+/// A color quantization method.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Method {
+	/// Recursively splits the color space at the median of its widest channel.
+	/// Fast and deterministic.
+	MedianCut,
+	/// Refines the median cut colors by k-means clustering. Slower, but 
+	/// usually produces more representative colors.
+	KMeans,
+}
+
+
+/// Returns the channels of the given color as an array.
+#[inline]
+fn channels(color: Color) -> [u8; 3] {
+	[color.r(), color.g(), color.b()]
+}
+
+/// Returns the average of the given colors.
+fn average(colors: &[Color]) -> Color {
+	let mut sum = [0u64; 3];
+	for &color in colors {
+		for (s, c) in sum.iter_mut().zip(channels(color).iter()) {
+			*s += *c as u64;
+		}
+	}
+	let n = colors.len().max(1) as u64;
+	Color::new(
+		((sum[0] + n / 2) / n) as u8,
+		((sum[1] + n / 2) / n) as u8,
+		((sum[2] + n / 2) / n) as u8)
+}
+
+/// Returns the widest channel of the given colors and its range.
+fn widest_channel(colors: &[Color]) -> (usize, u8) {
+	let mut min = [255u8; 3];
+	let mut max = [0u8; 3];
+	for &color in colors {
+		for (i, &c) in channels(color).iter().enumerate() {
+			min[i] = min[i].min(c);
+			max[i] = max[i].max(c);
+		}
+	}
+	(0..3)
+		.map(|i| (i, max[i].saturating_sub(min[i])))
+		.max_by_key(|&(_, range)| range)
+		.unwrap_or((0, 0))
+}
+
+/// Returns the representative colors of the given pixels found by median cut.
+fn median_cut(pixels: &[Color], max_colors: usize) -> Vec<Color> {
+	if pixels.is_empty() || max_colors == 0 { return Vec::new(); }
+
+	let mut boxes: Vec<Vec<Color>> = vec![pixels.to_vec()];
+	while boxes.len() < max_colors {
+		// Find the box with the widest channel range.
+		let (index, channel, range) = boxes.iter()
+			.enumerate()
+			.map(|(i, b)| {
+				let (channel, range) = widest_channel(b);
+				(i, channel, range)
+			})
+			.max_by_key(|&(_, _, range)| range)
+			.expect("median cut box");
+		if range == 0 { break; }
+
+		// Split it at the median.
+		let mut b = boxes.swap_remove(index);
+		b.sort_by_key(|&color| channels(color)[channel]);
+		let upper = b.split_off(b.len() / 2);
+		boxes.push(b);
+		boxes.push(upper);
+	}
+
+	boxes.iter().map(|b| average(b)).collect()
+}
+
+/// Refines the given centroids by k-means clustering of the given pixels.
+fn kmeans(pixels: &[Color], mut centroids: Vec<Color>) -> Vec<Color> {
+	for _ in 0..KMEANS_MAX_ITERATIONS {
+		let nearest = map_items(pixels, |&pixel| centroids.iter()
+			.enumerate()
+			.map(|(i, &c)| (i, Metric::Rgb.distance(pixel, c)))
+			.fold((0, f32::MAX), |best, next| 
+				if next.1 < best.1 { next } else { best })
+			.0);
+
+		let mut clusters: Vec<Vec<Color>> = vec![Vec::new(); centroids.len()];
+		for (&pixel, &i) in pixels.iter().zip(nearest.iter()) {
+			clusters[i].push(pixel);
+		}
+
+		let next: Vec<Color> = clusters.iter()
+			.zip(centroids.iter())
+			.map(|(cluster, &c)| if cluster.is_empty() { 
+				c 
+			} else { 
+				average(cluster)
+			})
+			.collect();
+		if next == centroids { break; }
+		centroids = next;
+	}
+	centroids
+}
+
+
+/// Returns palette `Data` containing up to `max_colors` colors representative 
+/// of the given pixels, chosen using the given quantization method.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Color;
+/// use palette::extract::{from_pixels, Method};
+/// 
+/// let pixels = vec![
+/// 	Color::new(250, 0, 0), Color::new(255, 0, 0), 
+/// 	Color::new(0, 0, 250), Color::new(0, 0, 255),
+/// ];
+/// let dat = from_pixels(&pixels, 2, Method::MedianCut).unwrap();
+///
+/// assert_eq!(dat.len(), 2);
+/// ```
+pub fn from_pixels(pixels: &[Color], max_colors: usize, method: Method) 
+	-> Result<Data>
+{
+	let mut colors = median_cut(pixels, max_colors);
+	if method == Method::KMeans {
+		colors = kmeans(pixels, colors);
+	}
+
+	let mut data: Data = Default::default();
+	for color in colors {
+		data.add_color(color)?;
+	}
+	Ok(data)
+}
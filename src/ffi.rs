@@ -0,0 +1,389 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides a C ABI for palettes, allowing the crate to be linked from C and 
+//! C++ tools. The declarations are given in `include/palette.h`.
+//!
+//! Palettes are accessed through opaque `PaletteHandle` pointers, created by
+//! `palette_create` or `palette_load` and released by `palette_destroy`. 
+//! Fallible functions return a `PaletteStatus` code, and a description of the
+//! most recent error on the calling thread is available from 
+//! `palette_last_error`.
+//!
+//! Panics never unwind across the C ABI. A panic inside any of these 
+//! functions is caught and reported as `PaletteStatus::Other`, or as a null or
+//! zero result where no status is returned.
+//!
+//! This module requires the `ffi` feature. Only an `rlib` is built by 
+//! default; build a C library with `cargo rustc --lib --features ffi 
+//! --crate-type cdylib`, or `staticlib` for static linking.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::{load, save, Palette};
+use address::Address;
+use format::Format;
+use result::Error;
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+
+thread_local! {
+	/// The description of the most recent error on this thread.
+	static LAST_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// PaletteStatus
+////////////////////////////////////////////////////////////////////////////////
+/// The result of a fallible FFI call.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PaletteStatus {
+	/// The call succeeded.
+	Ok = 0,
+	/// A required pointer argument was null.
+	NullPointer = 1,
+	/// A string argument was not valid UTF-8.
+	InvalidString = 2,
+	/// An address lies outside of the palette.
+	InvalidAddress = 3,
+	/// An address holds no color.
+	EmptyAddress = 4,
+	/// An address is already occupied.
+	AddressInUse = 5,
+	/// The palette has no free slots.
+	PaletteFull = 6,
+	/// A file could not be read or written.
+	Io = 7,
+	/// A palette could not be read or written in a file format.
+	Format = 8,
+	/// A palette file could not be parsed.
+	Parse = 9,
+	/// Any other error.
+	Other = 10,
+}
+
+
+impl<'a> From<&'a Error> for PaletteStatus {
+	fn from(error: &'a Error) -> Self {
+		match *error {
+			Error::InvalidAddress(..) => PaletteStatus::InvalidAddress,
+			Error::EmptyAddress(..) => PaletteStatus::EmptyAddress,
+			Error::AddressInUse(..) => PaletteStatus::AddressInUse,
+			Error::MaxCellLimitExceeded => PaletteStatus::PaletteFull,
+			Error::Io(..) => PaletteStatus::Io,
+			Error::Format {..} => PaletteStatus::Format,
+			Error::Parse {..} => PaletteStatus::Parse,
+			_ => PaletteStatus::Other,
+		}
+	}
+}
+
+
+/// Records the given error as the most recent error, returning its status.
+fn fail(error: Error) -> PaletteStatus {
+	let status = PaletteStatus::from(&error);
+	set_last_error(&error.to_string());
+	status
+}
+
+/// Records the given message as the most recent error.
+fn set_last_error(message: &str) {
+	// Interior NULs can not be represented, so they are removed.
+	let message = CString::new(message.replace('\0', ""))
+		.expect("NULs removed from error message");
+	LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Returns the given C string as a `&str`, recording an error and returning
+/// a status on failure.
+unsafe fn to_str<'a>(text: *const c_char) 
+	-> Result<&'a str, PaletteStatus>
+{
+	if text.is_null() {
+		set_last_error("null string argument");
+		return Err(PaletteStatus::NullPointer);
+	}
+	CStr::from_ptr(text).to_str().map_err(|_| {
+		set_last_error("string argument is not valid UTF-8");
+		PaletteStatus::InvalidString
+	})
+}
+
+/// Returns the palette of the given handle, recording an error and returning 
+/// a status if it is null.
+unsafe fn palette<'a>(handle: *mut PaletteHandle) 
+	-> Result<&'a mut Palette, PaletteStatus>
+{
+	if handle.is_null() {
+		set_last_error("null palette handle");
+		Err(PaletteStatus::NullPointer)
+	} else {
+		Ok(&mut (*handle).palette)
+	}
+}
+
+/// Runs the given function, catching any panic so that it does not unwind 
+/// across the C ABI. A panic is recorded as the most recent error, and the 
+/// given value is returned in place of the function's result.
+fn guard<F, T>(on_panic: T, f: F) -> T where F: FnOnce() -> T {
+	panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+		let message = payload.downcast_ref::<&str>()
+			.map(|message| message.to_string())
+			.or_else(|| payload.downcast_ref::<String>().cloned())
+			.unwrap_or_else(|| "unknown panic".to_string());
+		set_last_error(&format!("internal error: {}", message));
+		on_panic
+	})
+}
+
+/// Returns the status of the given result.
+fn status(result: Result<(), PaletteStatus>) -> PaletteStatus {
+	match result {
+		Ok(()) => PaletteStatus::Ok,
+		Err(status) => status,
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// FFI types
+////////////////////////////////////////////////////////////////////////////////
+/// An opaque handle to a palette.
+#[derive(Debug)]
+pub struct PaletteHandle {
+	palette: Palette,
+}
+
+/// The address of a palette slot.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PaletteAddress {
+	/// The page of the address.
+	pub page: u16,
+	/// The line of the address.
+	pub line: u8,
+	/// The column of the address.
+	pub column: u8,
+}
+
+impl From<Address> for PaletteAddress {
+	fn from(address: Address) -> Self {
+		PaletteAddress {
+			page: address.page,
+			line: address.line,
+			column: address.column,
+		}
+	}
+}
+
+impl From<PaletteAddress> for Address {
+	fn from(address: PaletteAddress) -> Self {
+		Address::new(address.page, address.line, address.column)
+	}
+}
+
+/// An RGB color.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PaletteColor {
+	/// The red channel.
+	pub r: u8,
+	/// The green channel.
+	pub g: u8,
+	/// The blue channel.
+	pub b: u8,
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Functions
+////////////////////////////////////////////////////////////////////////////////
+/// Creates a new empty palette with the given name, returning its handle. A 
+/// null name gives an unnamed palette. Returns null if the name is not valid
+/// UTF-8.
+///
+/// # Safety
+///
+/// `name` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn palette_create(name: *const c_char) 
+	-> *mut PaletteHandle
+{
+	guard(ptr::null_mut(), || {
+		let name = if name.is_null() {
+			""
+		} else {
+			match to_str(name) {
+				Ok(name) => name,
+				Err(_) => return ptr::null_mut(),
+			}
+		};
+		Box::into_raw(Box::new(PaletteHandle {
+			palette: Palette::new(name, Format::Default, true),
+		}))
+	})
+}
+
+/// Releases the given palette. Null handles are ignored.
+///
+/// # Safety
+///
+/// `handle` must be null or a handle returned by this library which has not
+/// already been destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn palette_destroy(handle: *mut PaletteHandle) {
+	guard((), || if !handle.is_null() {
+		drop(Box::from_raw(handle));
+	})
+}
+
+/// Returns the number of cells in the given palette, or 0 if the handle is 
+/// null.
+///
+/// # Safety
+///
+/// `handle` must be null or a valid palette handle.
+#[no_mangle]
+pub unsafe extern "C" fn palette_len(handle: *mut PaletteHandle) -> usize {
+	guard(0, || palette(handle).map_or(0, |palette| palette.len()))
+}
+
+/// Adds a color to the first free slot of the given palette, storing its 
+/// address in `address` if it is not null.
+///
+/// # Safety
+///
+/// `handle` must be a valid palette handle, and `address` must be null or 
+/// point to writable memory.
+#[no_mangle]
+pub unsafe extern "C" fn palette_add_color(
+	handle: *mut PaletteHandle,
+	color: PaletteColor,
+	address: *mut PaletteAddress)
+	-> PaletteStatus
+{
+	guard(PaletteStatus::Other, || status(palette(handle).and_then(|palette| {
+		let added = palette.data_mut()
+			.add_color(Color::new(color.r, color.g, color.b))
+			.map_err(fail)?;
+		if !address.is_null() {
+			*address = PaletteAddress::from(added);
+		}
+		Ok(())
+	})))
+}
+
+/// Stores the color at the given address of the palette in `color`.
+///
+/// # Safety
+///
+/// `handle` must be a valid palette handle, and `color` must point to 
+/// writable memory.
+#[no_mangle]
+pub unsafe extern "C" fn palette_get_color(
+	handle: *mut PaletteHandle,
+	address: PaletteAddress,
+	color: *mut PaletteColor)
+	-> PaletteStatus
+{
+	guard(PaletteStatus::Other, || status(palette(handle).and_then(|palette| {
+		if color.is_null() {
+			set_last_error("null color argument");
+			return Err(PaletteStatus::NullPointer);
+		}
+		let address = Address::from(address);
+		let found = palette.color(address)
+			.ok_or_else(|| fail(Error::EmptyAddress(address)))?;
+		*color = PaletteColor {r: found.r(), g: found.g(), b: found.b()};
+		Ok(())
+	})))
+}
+
+/// Loads a palette from the file at the given path, detecting its format, 
+/// and stores its handle in `handle`.
+///
+/// # Safety
+///
+/// `path` must point to a NUL-terminated string, and `handle` must point to 
+/// writable memory.
+#[no_mangle]
+pub unsafe extern "C" fn palette_load(
+	path: *const c_char,
+	handle: *mut *mut PaletteHandle)
+	-> PaletteStatus
+{
+	guard(PaletteStatus::Other, || status(to_str(path).and_then(|path| {
+		if handle.is_null() {
+			set_last_error("null handle argument");
+			return Err(PaletteStatus::NullPointer);
+		}
+		let palette = load(path).map_err(fail)?;
+		*handle = Box::into_raw(Box::new(PaletteHandle {palette: palette}));
+		Ok(())
+	})))
+}
+
+/// Saves the palette to the file at the given path, selecting the format by
+/// the file extension.
+///
+/// # Safety
+///
+/// `handle` must be a valid palette handle, and `path` must point to a 
+/// NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn palette_save(
+	handle: *mut PaletteHandle,
+	path: *const c_char)
+	-> PaletteStatus
+{
+	guard(PaletteStatus::Other, || status(palette(handle).and_then(|palette| {
+		let path = to_str(path)?;
+		save(path, palette).map_err(fail)
+	})))
+}
+
+/// Returns a description of the most recent error on the calling thread, or 
+/// null if there has been none. The string remains valid until the next 
+/// failing call on the thread.
+#[no_mangle]
+pub extern "C" fn palette_last_error() -> *const c_char {
+	guard(ptr::null(), || LAST_ERROR.with(|last| match *last.borrow() {
+		Some(ref message) => message.as_ptr(),
+		None => ptr::null(),
+	}))
+}
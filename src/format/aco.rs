@@ -0,0 +1,344 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides components for interacting with the Adobe Photoshop color swatch 
+//! format.
+//!
+//! ACO files are big-endian, and contain a version 1 section of unnamed colors,
+//! optionally followed by a version 2 section repeating the same colors with 
+//! names. RGB, HSB, CMYK, Lab, and grayscale colors are read; colors are always
+//! written as RGB, in both sections.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::Palette;
+use address::Reference;
+use format::{Format, OffsetReader, ParseOptions, parse_error};
+use format::report::{Loss, WriteReport};
+use result::{Error, Result};
+use space::{Hsv, Lab};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::io;
+
+
+/// The color space identifier for RGB colors.
+const ACO_SPACE_RGB: u16 = 0;
+
+/// The color space identifier for HSB colors.
+const ACO_SPACE_HSB: u16 = 1;
+
+/// The color space identifier for CMYK colors.
+const ACO_SPACE_CMYK: u16 = 2;
+
+/// The color space identifier for Lab colors.
+const ACO_SPACE_LAB: u16 = 7;
+
+/// The color space identifier for grayscale colors.
+const ACO_SPACE_GRAY: u16 = 8;
+
+/// The maximum length of a color name, in UTF-16 code units. Longer lengths
+/// are rejected rather than allocated.
+const ACO_NAME_LIMIT: usize = 0xFFFF;
+
+
+/// Returns a `Format` error with the given reason.
+fn format_error(reason: &str) -> Error {
+	Error::Format {format: Format::Aco.name(), reason: reason.into()}
+}
+
+/// Reads a big-endian u16 from the buffer.
+fn read_u16<R>(in_buf: &mut R) -> io::Result<u16> where R: io::Read {
+	let mut buf = [0u8; 2];
+	in_buf.read_exact(&mut buf)?;
+	Ok((buf[0] as u16) << 8 | buf[1] as u16)
+}
+
+/// Writes a big-endian u16 to the buffer.
+fn write_u16<W>(out_buf: &mut W, value: u16) -> io::Result<()> 
+	where W: io::Write
+{
+	out_buf.write_all(&[(value >> 8) as u8, value as u8])
+}
+
+/// Converts a 16-bit channel value to an 8-bit channel value.
+fn channel(value: u16) -> u8 {
+	(value >> 8) as u8
+}
+
+/// Reads a color entry from the buffer. Returns None if the entry was skipped
+/// because its color space is unsupported.
+fn read_color<R>(in_buf: &mut OffsetReader<R>, options: &ParseOptions) 
+	-> Result<Option<Color>> 
+	where R: io::Read
+{
+	let offset = in_buf.offset();
+	let space = read_u16(in_buf)?;
+	let mut w = [0u16; 4];
+	for value in &mut w {
+		*value = read_u16(in_buf)?;
+	}
+
+	let color = match space {
+		ACO_SPACE_RGB => Color::new(
+			channel(w[0]), 
+			channel(w[1]), 
+			channel(w[2])),
+
+		ACO_SPACE_HSB => Color::from(Hsv::new(
+			w[0] as f32 / 65535.0 * 360.0,
+			w[1] as f32 / 65535.0,
+			w[2] as f32 / 65535.0)),
+
+		// CMYK values are stored inverted; 0 is full ink.
+		ACO_SPACE_CMYK => {
+			let k = w[3] as f32 / 65535.0;
+			let ink = |v: u16| (v as f32 / 65535.0 * k * 255.0).round() as u8;
+			Color::new(ink(w[0]), ink(w[1]), ink(w[2]))
+		},
+
+		ACO_SPACE_LAB => Color::from(Lab::new(
+			w[0] as f32 / 100.0,
+			w[1] as i16 as f32 / 100.0,
+			w[2] as i16 as f32 / 100.0)),
+
+		ACO_SPACE_GRAY => {
+			let v = 255 - (w[0].min(10_000) as u32 * 255 / 10_000) as u8;
+			Color::new(v, v, v)
+		},
+
+		_ => {
+			options.recover(parse_error(
+				offset, 
+				"a supported ACO color space", 
+				format!("color space {}", space)))?;
+			return Ok(None);
+		},
+	};
+	Ok(Some(color))
+}
+
+/// Writes a color entry to the buffer.
+fn write_color<W>(out_buf: &mut W, color: Color) -> io::Result<()>
+	where W: io::Write
+{
+	write_u16(out_buf, ACO_SPACE_RGB)?;
+	write_u16(out_buf, color.r() as u16 * 257)?;
+	write_u16(out_buf, color.g() as u16 * 257)?;
+	write_u16(out_buf, color.b() as u16 * 257)?;
+	write_u16(out_buf, 0)
+}
+
+
+/// Returns the UTF-16 code units of the given name, truncated so that its 
+/// length with the terminator is within `ACO_NAME_LIMIT`. Returns whether the
+/// name was truncated.
+fn name_units(name: &str) -> (Vec<u16>, bool) {
+	let mut units: Vec<u16> = name.encode_utf16().collect();
+	let truncated = units.len() >= ACO_NAME_LIMIT;
+	if truncated {
+		units.truncate(ACO_NAME_LIMIT - 1);
+		// Don't split a surrogate pair.
+		if units.last().map_or(false, |&u| u >= 0xD800 && u < 0xDC00) {
+			units.pop();
+		}
+	}
+	(units, truncated)
+}
+
+
+/// Writes the palette to the given buffer.
+pub fn write_palette<W>(palette: &Palette, out_buf: &mut W) -> Result<()>
+	where W: io::Write
+{
+	write_palette_reported(palette, out_buf, &mut WriteReport::new(Format::Aco))
+}
+
+
+/// Writes the palette to the given buffer, recording the names truncated to 
+/// fit in the given report.
+pub fn write_palette_reported<W>(
+	palette: &Palette, 
+	out_buf: &mut W,
+	report: &mut WriteReport)
+	-> Result<()>
+	where W: io::Write
+{
+	let colors: Vec<_> = palette.data.colors().collect();
+	if colors.len() > u16::max_value() as usize {
+		return Err(format_error("too many colors for ACO palette"));
+	}
+
+	// Write version 1 section.
+	write_u16(out_buf, 1)?;
+	write_u16(out_buf, colors.len() as u16)?;
+	for &(_, color) in &colors {
+		write_color(out_buf, color)?;
+	}
+
+	// Write version 2 section.
+	write_u16(out_buf, 2)?;
+	write_u16(out_buf, colors.len() as u16)?;
+	for &(address, color) in &colors {
+		write_color(out_buf, color)?;
+		let group = Reference::from(address);
+		let (name, truncated) = name_units(
+			palette.data.name(&group).unwrap_or(""));
+		if truncated {
+			report.push(Loss::NameTruncated(group));
+		}
+		let len = name.len() as u32 + 1;
+		out_buf.write_all(&[
+			(len >> 24) as u8,
+			(len >> 16) as u8,
+			(len >> 8) as u8,
+			len as u8,
+		])?;
+		for unit in name {
+			write_u16(out_buf, unit)?;
+		}
+		write_u16(out_buf, 0)?;
+	}
+	Ok(())
+}
+
+
+/// Reads a palette from the given buffer.
+pub fn read_palette<R>(in_buf: &mut R, options: &ParseOptions) 
+	-> Result<Palette>
+	where R: io::Read
+{
+	let mut in_buf = OffsetReader::new(in_buf);
+	let mut palette = Palette::new("", Format::Aco, true);
+	let result = read_colors(&mut in_buf, &mut palette, options);
+	options.finish(in_buf.offset(), palette, result)
+}
+
+/// Reads the colors from the given buffer into the palette.
+fn read_colors<R>(
+	in_buf: &mut OffsetReader<R>,
+	palette: &mut Palette,
+	options: &ParseOptions)
+	-> Result<()>
+	where R: io::Read
+{
+	// In lenient mode, the colors read before an error are kept.
+	let mut colors = Vec::new();
+	let result = read_sections(in_buf, &mut colors, options);
+
+	for (offset, color, name) in colors {
+		if !options.admits(palette.data.len(), offset)? {
+			break;
+		}
+		let address = palette.data
+			.add_color(color)?;
+		if let Some(name) = name {
+			if !name.is_empty() {
+				palette.data.set_name(Reference::from(address), name);
+			}
+		}
+	}
+	result
+}
+
+/// Reads the color sections from the given buffer, collecting each color with
+/// its offset and name. The version 2 section replaces the colors of the 
+/// version 1 section only if it is read completely.
+fn read_sections<R>(
+	in_buf: &mut OffsetReader<R>,
+	colors: &mut Vec<(u64, Color, Option<String>)>,
+	options: &ParseOptions)
+	-> Result<()>
+	where R: io::Read
+{
+	// Read version 1 section.
+	let version = read_u16(in_buf)?;
+	if version != 1 {
+		return Err(parse_error(
+			0, 
+			"ACO version 1", 
+			format!("version {}", version)));
+	}
+	let count = read_u16(in_buf)?;
+	for _ in 0..count {
+		let offset = in_buf.offset();
+		if let Some(color) = read_color(in_buf, options)? {
+			colors.push((offset, color, None));
+		}
+	}
+
+	// Read version 2 section, if present.
+	let offset = in_buf.offset();
+	match read_u16(in_buf) {
+		Ok(2) => (),
+		Ok(version) => return options.recover(parse_error(
+			offset, 
+			"ACO version 2", 
+			format!("version {}", version))),
+		Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+			return Ok(());
+		},
+		Err(e) => return Err(e.into()),
+	}
+
+	let mut named = Vec::new();
+	let count = read_u16(in_buf)?;
+	for _ in 0..count {
+		let offset = in_buf.offset();
+		let color = read_color(in_buf, options)?;
+		let name_offset = in_buf.offset();
+		let len = (read_u16(in_buf)? as usize) << 16 
+			| read_u16(in_buf)? as usize;
+		if len > ACO_NAME_LIMIT {
+			return Err(parse_error(
+				name_offset, 
+				"a color name length of at most 65535", 
+				format!("length {}", len)));
+		}
+		let mut units = Vec::with_capacity(len);
+		for _ in 0..len {
+			units.push(read_u16(in_buf)?);
+		}
+		while units.last() == Some(&0) { units.pop(); }
+		let name = match String::from_utf16(&units) {
+			Ok(name) => Some(name),
+			Err(_) => {
+				options.recover(parse_error(
+					name_offset, 
+					"a UTF-16 color name", 
+					"invalid UTF-16"))?;
+				None
+			},
+		};
+		if let Some(color) = color {
+			named.push((offset, color, name));
+		}
+	}
+	*colors = named;
+	Ok(())
+}
@@ -0,0 +1,180 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides components for interacting with the Adobe color table format.
+//!
+//! ACT files are a raw table of 256 RGB colors, optionally followed by a 
+//! big-endian color count and transparent color index. The transparent color
+//! is read with an alpha of 0 under the `meta::ALPHA_KEY` key, and the first 
+//! color with an alpha of 0 is written as the transparent color. Other 
+//! translucent colors are written as opaque.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::Palette;
+use address::{Address, Reference};
+use format::{Format, OffsetReader, ParseOptions, parse_error};
+use format::report::{Loss, WriteReport};
+use meta::{MetaType, ALPHA_KEY};
+use result::{Error, Result};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::io;
+use std::io::Read;
+
+
+/// The number of entries in an ACT color table.
+const ACT_TABLE_SIZE: usize = 256;
+
+/// The transparent index value indicating no transparent color.
+const ACT_NO_TRANSPARENCY: u16 = 0xFFFF;
+
+
+/// Returns a `Format` error with the given reason.
+fn format_error(reason: &str) -> Error {
+	Error::Format {format: Format::Act.name(), reason: reason.into()}
+}
+
+
+/// Writes the palette to the given buffer.
+pub fn write_palette<W>(palette: &Palette, out_buf: &mut W) -> Result<()>
+	where W: io::Write
+{
+	write_palette_reported(palette, out_buf, &mut WriteReport::new(Format::Act))
+}
+
+
+/// Writes the palette to the given buffer, recording the translucent colors 
+/// written as opaque in the given report.
+pub fn write_palette_reported<W>(
+	palette: &Palette, 
+	out_buf: &mut W,
+	report: &mut WriteReport)
+	-> Result<()>
+	where W: io::Write
+{
+	let colors: Vec<(Address, Color)> = palette.data.colors().collect();
+	if colors.len() > ACT_TABLE_SIZE {
+		return Err(format_error("too many colors for ACT palette"));
+	}
+
+	let mut table = [0u8; ACT_TABLE_SIZE * 3];
+	let mut transparent = ACT_NO_TRANSPARENCY;
+	for (index, (entry, &(address, color))) in table
+		.chunks_mut(3)
+		.zip(colors.iter())
+		.enumerate()
+	{
+		entry.copy_from_slice(&[color.r(), color.g(), color.b()]);
+
+		let alpha = palette.data
+			.get_meta::<i64>(&Reference::from(address), ALPHA_KEY)
+			.unwrap_or(0xFF);
+		if alpha <= 0 && transparent == ACT_NO_TRANSPARENCY {
+			transparent = index as u16;
+		} else if alpha < 0xFF {
+			report.push(Loss::Alpha(address));
+		}
+	}
+	out_buf.write_all(&table)?;
+
+	let count = colors.len() as u16;
+	out_buf.write_all(&[
+		(count >> 8) as u8, count as u8,
+		(transparent >> 8) as u8, transparent as u8])?;
+	Ok(())
+}
+
+
+/// Reads a palette from the given buffer.
+pub fn read_palette<R>(in_buf: &mut R, options: &ParseOptions) 
+	-> Result<Palette>
+	where R: io::Read
+{
+	let mut in_buf = OffsetReader::new(in_buf);
+	let mut palette = Palette::new("", Format::Act, true);
+	let result = read_colors(&mut in_buf, &mut palette, options);
+	options.finish(in_buf.offset(), palette, result)
+}
+
+/// Reads the color table from the given buffer into the palette.
+fn read_colors<R>(
+	in_buf: &mut OffsetReader<R>,
+	palette: &mut Palette,
+	options: &ParseOptions)
+	-> Result<()>
+	where R: io::Read
+{
+	// In lenient mode, the entries of a truncated table are kept.
+	let mut table = Vec::with_capacity(ACT_TABLE_SIZE * 3);
+	in_buf.by_ref()
+		.take((ACT_TABLE_SIZE * 3) as u64)
+		.read_to_end(&mut table)?;
+	if table.len() < ACT_TABLE_SIZE * 3 {
+		options.recover(parse_error(
+			in_buf.offset(), 
+			format!("{} bytes of color table", ACT_TABLE_SIZE * 3), 
+			format!("{} bytes", table.len())))?;
+	}
+
+	// Read the optional color count and transparent index.
+	let offset = in_buf.offset();
+	let mut suffix = Vec::with_capacity(4);
+	in_buf.by_ref().take(4).read_to_end(&mut suffix)?;
+	let mut count = ACT_TABLE_SIZE;
+	if suffix.len() >= 2 {
+		count = (suffix[0] as usize) << 8 | suffix[1] as usize;
+		if count > ACT_TABLE_SIZE {
+			options.recover(parse_error(
+				offset, 
+				format!("at most {} colors", ACT_TABLE_SIZE), 
+				format!("{} colors", count)))?;
+			count = ACT_TABLE_SIZE;
+		}
+	}
+	let transparent = if suffix.len() >= 4 {
+		(suffix[2] as u16) << 8 | suffix[3] as u16
+	} else {
+		ACT_NO_TRANSPARENCY
+	};
+
+	for (index, entry) in table.chunks(3).take(count).enumerate() {
+		if entry.len() < 3 { break; }
+		let offset = (index * 3) as u64;
+		if !options.admits(palette.data.len(), offset)? {
+			break;
+		}
+		let address = palette.data
+			.add_color(Color::new(entry[0], entry[1], entry[2]))?;
+		if index as u16 == transparent {
+			palette.data.register_meta(ALPHA_KEY, MetaType::Int)?;
+			palette.data.set_meta(Reference::from(address), ALPHA_KEY, 0i64)?;
+		}
+	}
+	Ok(())
+}
@@ -0,0 +1,433 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides components for interacting with the Adobe Swatch Exchange format.
+//!
+//! ASE files are a big-endian sequence of blocks. Color entry blocks are 
+//! mapped to palette cells, and group blocks are mapped to pages: each group 
+//! is placed on its own page, and the group name is stored as the page name.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::Palette;
+use address::{Address, Page, Reference};
+use expression::Expression;
+use format::{
+	Format,
+	OffsetReader,
+	ParseOptions,
+	describe_bytes,
+	parse_error,
+};
+use format::report::{Loss, WriteReport};
+use result::Result;
+use space::Cmyk;
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::io;
+use std::io::Read;
+
+
+/// The ASE file signature.
+const ASE_SIGNATURE: [u8;4] = [0x41, 0x53, 0x45, 0x46];
+
+/// The ASE file version.
+const ASE_VERSION: [u8;4] = [0x00, 0x01, 0x00, 0x00];
+
+/// The maximum length of a name, in UTF-16 code units, excluding the 
+/// terminator.
+const ASE_NAME_LIMIT: usize = 0xFFFE;
+
+/// The block type for the start of a group.
+const ASE_GROUP_START: u16 = 0xC001;
+
+/// The block type for the end of a group.
+const ASE_GROUP_END: u16 = 0xC002;
+
+/// The block type for a color entry.
+const ASE_COLOR_ENTRY: u16 = 0x0001;
+
+/// The color type written for color entries. (Normal color.)
+const ASE_COLOR_TYPE_NORMAL: u16 = 2;
+
+
+/// Reads a big-endian u16 from the buffer.
+fn read_u16<R>(in_buf: &mut R) -> io::Result<u16> where R: io::Read {
+	let mut buf = [0u8; 2];
+	in_buf.read_exact(&mut buf)?;
+	Ok((buf[0] as u16) << 8 | buf[1] as u16)
+}
+
+/// Reads a big-endian u32 from the buffer.
+fn read_u32<R>(in_buf: &mut R) -> io::Result<u32> where R: io::Read {
+	let mut buf = [0u8; 4];
+	in_buf.read_exact(&mut buf)?;
+	Ok((buf[0] as u32) << 24 
+		| (buf[1] as u32) << 16 
+		| (buf[2] as u32) << 8 
+		| buf[3] as u32)
+}
+
+/// Reads a big-endian f32 from the buffer.
+fn read_f32<R>(in_buf: &mut R) -> io::Result<f32> where R: io::Read {
+	Ok(f32::from_bits(read_u32(in_buf)?))
+}
+
+/// Reads a length-prefixed, null-terminated UTF-16 string from the buffer. In
+/// lenient mode, invalid characters are replaced.
+fn read_name<R>(in_buf: &mut OffsetReader<R>, options: &ParseOptions) 
+	-> Result<String> 
+	where R: io::Read
+{
+	let offset = in_buf.offset();
+	let len = read_u16(in_buf)?;
+	let mut units = Vec::with_capacity(len as usize);
+	for _ in 0..len {
+		units.push(read_u16(in_buf)?);
+	}
+	// Strip null terminator.
+	while units.last() == Some(&0) {
+		units.pop();
+	}
+	match String::from_utf16(&units) {
+		Ok(name) => Ok(name),
+		Err(_) => {
+			options.recover(parse_error(
+				offset, 
+				"a UTF-16 name", 
+				"invalid UTF-16"))?;
+			Ok(String::from_utf16_lossy(&units))
+		},
+	}
+}
+
+/// Appends a big-endian u16 to the buffer.
+fn push_u16(buf: &mut Vec<u8>, value: u16) {
+	buf.push((value >> 8) as u8);
+	buf.push(value as u8);
+}
+
+/// Appends a big-endian u32 to the buffer.
+fn push_u32(buf: &mut Vec<u8>, value: u32) {
+	push_u16(buf, (value >> 16) as u16);
+	push_u16(buf, value as u16);
+}
+
+/// Appends a length-prefixed, null-terminated UTF-16 string to the buffer,
+/// truncated to `ASE_NAME_LIMIT` code units. Returns whether the name was 
+/// truncated.
+fn push_name(buf: &mut Vec<u8>, name: &str) -> bool {
+	let mut units: Vec<u16> = name.encode_utf16().collect();
+	let truncated = units.len() > ASE_NAME_LIMIT;
+	if truncated {
+		units.truncate(ASE_NAME_LIMIT);
+		// Don't split a surrogate pair.
+		if units.last().map_or(false, |&u| u >= 0xD800 && u < 0xDC00) {
+			units.pop();
+		}
+	}
+	push_u16(buf, units.len() as u16 + 1);
+	for unit in units {
+		push_u16(buf, unit);
+	}
+	push_u16(buf, 0);
+	truncated
+}
+
+/// Writes a block with the given type and body to the buffer.
+fn write_block<W>(out_buf: &mut W, block_type: u16, body: &[u8]) 
+	-> io::Result<()>
+	where W: io::Write
+{
+	let mut header = Vec::with_capacity(6);
+	push_u16(&mut header, block_type);
+	push_u32(&mut header, body.len() as u32);
+	out_buf.write_all(&header)?;
+	out_buf.write_all(body)
+}
+
+/// Converts a color channel to the ASE float range.
+fn channel_to_f32(channel: u8) -> f32 {
+	channel as f32 / 255.0
+}
+
+/// Converts an ASE float channel to a color channel.
+fn channel_from_f32(channel: f32) -> u8 {
+	(channel.max(0.0).min(1.0) * 255.0).round() as u8
+}
+
+
+/// Writes the blocks of the palette to the given buffer, returning the number
+/// of blocks written. Truncated names are recorded in the given report.
+fn write_blocks<W>(
+	palette: &Palette, 
+	out_buf: &mut W, 
+	report: &mut WriteReport)
+	-> io::Result<u32>
+	where W: io::Write
+{
+	let data = &palette.data;
+	let mut block_count: u32 = 0;
+	let mut cur_page: Option<Page> = None;
+	let mut in_group = false;
+
+	for (address, cell) in data.cells.iter() {
+		let color = match data.color_of(cell) {
+			Some(color) => color,
+			None => continue,
+		};
+		let cmyk = match *cell.borrow() {
+			Expression::Cmyk(cmyk) => Some(cmyk),
+			_ => None,
+		};
+
+		// Start a new group for each named page.
+		if cur_page != Some(address.page) {
+			if in_group {
+				write_block(out_buf, ASE_GROUP_END, &[])?;
+				block_count += 1;
+			}
+			cur_page = Some(address.page);
+			in_group = false;
+			let group = Reference::page_of(&address);
+			if let Some(name) = data.name(&group) {
+				let mut body = Vec::new();
+				if push_name(&mut body, name) {
+					report.push(Loss::NameTruncated(group));
+				}
+				write_block(out_buf, ASE_GROUP_START, &body)?;
+				block_count += 1;
+				in_group = true;
+			}
+		}
+
+		let mut body = Vec::new();
+		let group = Reference::from(address);
+		if push_name(&mut body, data.name(&group).unwrap_or("")) {
+			report.push(Loss::NameTruncated(group));
+		}
+		if let Some(cmyk) = cmyk {
+			body.extend_from_slice(b"CMYK");
+			for &value in &[cmyk.c, cmyk.m, cmyk.y, cmyk.k] {
+				push_u32(&mut body, value.to_bits());
+			}
+		} else {
+			body.extend_from_slice(b"RGB ");
+			for &channel in &[color.r(), color.g(), color.b()] {
+				push_u32(&mut body, channel_to_f32(channel).to_bits());
+			}
+		}
+		push_u16(&mut body, ASE_COLOR_TYPE_NORMAL);
+		write_block(out_buf, ASE_COLOR_ENTRY, &body)?;
+		block_count += 1;
+	}
+
+	if in_group {
+		write_block(out_buf, ASE_GROUP_END, &[])?;
+		block_count += 1;
+	}
+	Ok(block_count)
+}
+
+
+/// Writes the palette to the given buffer.
+pub fn write_palette<W>(palette: &Palette, out_buf: &mut W) -> Result<()>
+	where W: io::Write
+{
+	write_palette_reported(palette, out_buf, &mut WriteReport::new(Format::Ase))
+}
+
+
+/// Writes the palette to the given buffer, recording the names truncated to 
+/// fit in the given report.
+pub fn write_palette_reported<W>(
+	palette: &Palette, 
+	out_buf: &mut W,
+	report: &mut WriteReport)
+	-> Result<()>
+	where W: io::Write
+{
+	// Count blocks without writing them.
+	let block_count = write_blocks(palette, &mut io::sink(), report)?;
+
+	// Write header.
+	out_buf.write_all(&ASE_SIGNATURE)?;
+	out_buf.write_all(&ASE_VERSION)?;
+	let mut count = Vec::with_capacity(4);
+	push_u32(&mut count, block_count);
+	out_buf.write_all(&count)?;
+
+	// Write blocks.
+	write_blocks(palette, out_buf, report)?;
+	Ok(())
+}
+
+
+/// Reads a palette from the given buffer.
+pub fn read_palette<R>(in_buf: &mut R, options: &ParseOptions) 
+	-> Result<Palette>
+	where R: io::Read
+{
+	let mut in_buf = OffsetReader::new(in_buf);
+	let mut palette = Palette::new("", Format::Ase, true);
+	let result = read_blocks(&mut in_buf, &mut palette, options);
+	options.finish(in_buf.offset(), palette, result)
+}
+
+/// Reads the blocks of the given buffer into the palette.
+fn read_blocks<R>(
+	in_buf: &mut OffsetReader<R>,
+	palette: &mut Palette,
+	options: &ParseOptions)
+	-> Result<()>
+	where R: io::Read
+{
+	// Read header.
+	let mut signature = [0u8; 4];
+	in_buf.read_exact(&mut signature)?;
+	if signature != ASE_SIGNATURE {
+		return Err(parse_error(
+			0, 
+			"ASEF signature", 
+			describe_bytes(&signature)));
+	}
+	let _major = read_u16(in_buf)?;
+	let _minor = read_u16(in_buf)?;
+	let block_count = read_u32(in_buf)?;
+
+	let mut page: Page = 0;
+	let mut index: usize = 0;
+	let mut page_used = false;
+	let mut group_name: Option<String> = None;
+
+	for _ in 0..block_count {
+		let offset = in_buf.offset();
+		let block_type = read_u16(in_buf)?;
+		let block_len = read_u32(in_buf)?;
+		let block_end = in_buf.offset() + block_len as u64;
+
+		match block_type {
+			ASE_GROUP_START => {
+				if page_used {
+					let max = palette.data.maximum_page_count;
+					match next_page(page, max, offset) {
+						Ok(next) => page = next,
+						Err(e) => { options.recover(e)?; break; },
+					}
+				}
+				index = 0;
+				page_used = true;
+				group_name = Some(read_name(in_buf, options)?);
+			},
+
+			ASE_GROUP_END => {
+				let max = palette.data.maximum_page_count;
+				match next_page(page, max, offset) {
+					Ok(next) => page = next,
+					Err(e) => { options.recover(e)?; break; },
+				}
+				index = 0;
+				page_used = false;
+				group_name = None;
+			},
+
+			ASE_COLOR_ENTRY => {
+				if !options.admits(palette.data.len(), offset)? {
+					break;
+				}
+				let name = read_name(in_buf, options)?;
+				let model_offset = in_buf.offset();
+				let mut model = [0u8; 4];
+				in_buf.read_exact(&mut model)?;
+				let expr = match &model {
+					b"RGB " => Expression::Color(Color::new(
+						channel_from_f32(read_f32(in_buf)?),
+						channel_from_f32(read_f32(in_buf)?),
+						channel_from_f32(read_f32(in_buf)?))),
+
+					b"Gray" => {
+						let v = channel_from_f32(read_f32(in_buf)?);
+						Expression::Color(Color::new(v, v, v))
+					},
+
+					// CMYK colors are kept as CMYK so they can be written 
+					// back unchanged.
+					b"CMYK" => Expression::Cmyk(Cmyk::new(
+						read_f32(in_buf)?,
+						read_f32(in_buf)?,
+						read_f32(in_buf)?,
+						read_f32(in_buf)?)),
+
+					_ => {
+						options.recover(parse_error(
+							model_offset, 
+							"a supported ASE color model", 
+							describe_bytes(&model)))?;
+						in_buf.skip_to(block_end)?;
+						continue;
+					},
+				};
+				let _color_type = read_u16(in_buf)?;
+
+				// Place the color on the current page.
+				let address = Address::new(page, 0, 0).wrapping_step(
+					index,
+					palette.data.maximum_page_count,
+					palette.data.default_line_count,
+					palette.data.default_column_count);
+				let cell = palette.data.create_cell(address)?;
+				*cell.borrow_mut() = expr;
+				index += 1;
+				page_used = true;
+
+				// Page metadata must be set after the page is prepared.
+				if let Some(name) = group_name.take() {
+					palette.data.set_name(Reference::page_of(&address), name);
+				}
+				if !name.is_empty() {
+					palette.data.set_name(Reference::from(address), name);
+				}
+			},
+
+			_ => in_buf.skip_to(block_end)?, // Skip unknown blocks.
+		}
+	}
+	Ok(())
+}
+
+/// Returns the page following the given page, or an error at the given offset
+/// if it would exceed the given page count.
+fn next_page(page: Page, page_count: Page, offset: u64) -> Result<Page> {
+	match page.checked_add(1) {
+		Some(next) if next < page_count => Ok(next),
+		_ => Err(parse_error(
+			offset, 
+			format!("at most {} groups", page_count), 
+			"another group")),
+	}
+}
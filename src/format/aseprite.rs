@@ -0,0 +1,449 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides components for extracting palettes from Aseprite sprite files.
+//!
+//! Aseprite files are little-endian, beginning with a 128 byte header followed
+//! by a sequence of frames, each containing a sequence of chunks. The palette 
+//! is read from the first frame, using the palette chunk (`0x2019`) if present,
+//! and the legacy palette chunks (`0x0004` and `0x0011`) otherwise.
+//!
+//! Color names are preserved. As palette colors have no alpha channel, the 
+//! alpha of each non-opaque entry is preserved as slot metadata under the 
+//! `meta::ALPHA_KEY` key.
+//!
+//! Written files contain a single 1x1 indexed frame with no layers, holding 
+//! only the palette.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::Palette;
+use address::{Address, Reference};
+use format::{
+	ByteCounter,
+	Format,
+	OffsetReader,
+	ParseOptions,
+	parse_error,
+};
+use meta::{MetaType, ALPHA_KEY};
+use result::{Error, Result};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::io;
+use std::io::Read;
+
+
+/// The magic number of the Aseprite file header.
+const ASEPRITE_MAGIC: u16 = 0xA5E0;
+
+/// The magic number of an Aseprite frame header.
+const ASEPRITE_FRAME_MAGIC: u16 = 0xF1FA;
+
+/// The size of the Aseprite file header.
+const ASEPRITE_HEADER_SIZE: usize = 128;
+
+/// The size of an Aseprite frame header.
+const ASEPRITE_FRAME_HEADER_SIZE: usize = 16;
+
+/// The chunk type of the palette chunk.
+const ASEPRITE_PALETTE_CHUNK: u16 = 0x2019;
+
+/// The chunk type of the legacy 8-bit palette chunk.
+const ASEPRITE_OLD_PALETTE_CHUNK: u16 = 0x0004;
+
+/// The chunk type of the legacy 6-bit palette chunk.
+const ASEPRITE_OLD_PALETTE_6BIT_CHUNK: u16 = 0x0011;
+
+/// The palette entry flag indicating the entry has a name.
+const ASEPRITE_ENTRY_HAS_NAME: u16 = 1;
+
+/// The number of entries in the palette of a legacy palette chunk.
+const ASEPRITE_OLD_PALETTE_SIZE: usize = 256;
+
+
+/// Returns a `Format` error with the given reason.
+fn format_error(reason: &str) -> Error {
+	Error::Format {format: Format::Aseprite.name(), reason: reason.into()}
+}
+
+/// Returns an error for a chunk ending at the given offset of the buffer read 
+/// from the given base offset.
+fn truncated(base: u64, offset: usize) -> Error {
+	parse_error(base + offset as u64, "more chunk data", "end of chunk")
+}
+
+/// Returns the little-endian u16 at the given offset of the buffer read from 
+/// the given base offset.
+fn u16_at(buf: &[u8], base: u64, offset: usize) -> Result<u16> {
+	buf.get(offset..offset + 2)
+		.map(|b| b[0] as u16 | (b[1] as u16) << 8)
+		.ok_or_else(|| truncated(base, offset))
+}
+
+/// Returns the little-endian u32 at the given offset of the buffer read from 
+/// the given base offset.
+fn u32_at(buf: &[u8], base: u64, offset: usize) -> Result<u32> {
+	Ok(u16_at(buf, base, offset)? as u32 
+		| (u16_at(buf, base, offset + 2)? as u32) << 16)
+}
+
+/// Appends a little-endian u16 to the buffer.
+fn push_u16(buf: &mut Vec<u8>, value: u16) {
+	buf.extend_from_slice(&[value as u8, (value >> 8) as u8]);
+}
+
+/// Appends a little-endian u32 to the buffer.
+fn push_u32(buf: &mut Vec<u8>, value: u32) {
+	push_u16(buf, value as u16);
+	push_u16(buf, (value >> 16) as u16);
+}
+
+
+/// An entry read from an Aseprite palette chunk.
+struct Entry {
+	offset: u64,
+	color: Color,
+	alpha: u8,
+	name: Option<String>,
+}
+
+
+/// Parses the entries of a palette chunk body read from the given offset, 
+/// appending them to the given entries.
+fn parse_palette_chunk(body: &[u8], base: u64, entries: &mut Vec<Entry>) 
+	-> Result<()>
+{
+	let first = u32_at(body, base, 4)?;
+	let last = u32_at(body, base, 8)?;
+	let mut offset = 20;
+	for _ in first..last.saturating_add(1) {
+		let flags = u16_at(body, base, offset)?;
+		let rgba = body.get(offset + 2..offset + 6)
+			.ok_or_else(|| truncated(base, offset))?;
+		let mut entry = Entry {
+			offset: base + offset as u64,
+			color: Color::new(rgba[0], rgba[1], rgba[2]),
+			alpha: rgba[3],
+			name: None,
+		};
+		offset += 6;
+
+		if flags & ASEPRITE_ENTRY_HAS_NAME != 0 {
+			let len = u16_at(body, base, offset)? as usize;
+			let bytes = body.get(offset + 2..offset + 2 + len)
+				.ok_or_else(|| truncated(base, offset))?;
+			entry.name = Some(String::from_utf8(bytes.to_vec())
+				.map_err(|_| parse_error(
+					base + offset as u64, 
+					"a UTF-8 color name", 
+					"invalid UTF-8"))?);
+			offset += 2 + len;
+		}
+		entries.push(entry);
+	}
+	Ok(())
+}
+
+/// Parses the entries of a legacy palette chunk body read from the given 
+/// offset, appending them to the given entries. If `six_bit` is true, channels
+/// are scaled from the range 0-63.
+fn parse_old_palette_chunk(
+	body: &[u8],
+	base: u64,
+	six_bit: bool,
+	entries: &mut Vec<Entry>) 
+	-> Result<()> 
+{
+	let scale = |c: u8| if six_bit { 
+		(c & 0x3F) << 2 | (c & 0x3F) >> 4 
+	} else { 
+		c 
+	};
+	let packets = u16_at(body, base, 0)?;
+	let mut offset = 2;
+	let mut index = 0;
+	for _ in 0..packets {
+		let header = body.get(offset..offset + 2)
+			.ok_or_else(|| truncated(base, offset))?;
+		let count = if header[1] == 0 { 256 } else { header[1] as usize };
+		index += header[0] as usize;
+		if index + count > ASEPRITE_OLD_PALETTE_SIZE {
+			return Err(parse_error(
+				base + offset as u64, 
+				format!("at most {} palette entries", 
+					ASEPRITE_OLD_PALETTE_SIZE), 
+				format!("{} entries", index + count)));
+		}
+		
+		// Skipped entries keep their previous value, which is black for a new
+		// palette.
+		while entries.len() < index {
+			entries.push(Entry {
+				offset: base + offset as u64,
+				color: Color::new(0, 0, 0), 
+				alpha: 0xFF, 
+				name: None,
+			});
+		}
+		offset += 2;
+
+		let colors = body.get(offset..offset + count * 3)
+			.ok_or_else(|| truncated(base, offset))?;
+		for (index, rgb) in colors.chunks(3).enumerate() {
+			entries.push(Entry {
+				offset: base + (offset + index * 3) as u64,
+				color: Color::new(scale(rgb[0]), scale(rgb[1]), scale(rgb[2])),
+				alpha: 0xFF,
+				name: None,
+			});
+		}
+		offset += count * 3;
+		index += count;
+	}
+	Ok(())
+}
+
+
+/// Writes the palette chunk entries for the given colors to the buffer.
+fn write_entries<W>(
+	palette: &Palette, 
+	colors: &[(Address, Color)], 
+	out_buf: &mut W) 
+	-> io::Result<()>
+	where W: io::Write
+{
+	for &(address, color) in colors {
+		let reference = Reference::from(address);
+		let name = palette.data.name(&reference);
+		let alpha = palette.data.get_meta::<i64>(&reference, ALPHA_KEY)
+			.map_or(0xFF, |alpha| alpha.max(0).min(0xFF) as u8);
+
+		let mut entry = Vec::with_capacity(6);
+		let flags = if name.is_some() { ASEPRITE_ENTRY_HAS_NAME } else { 0 };
+		push_u16(&mut entry, flags);
+		entry.extend_from_slice(&[color.r(), color.g(), color.b(), alpha]);
+		if let Some(name) = name {
+			let name = &name.as_bytes()[..name.len().min(0xFFFF)];
+			push_u16(&mut entry, name.len() as u16);
+			entry.extend_from_slice(name);
+		}
+		out_buf.write_all(&entry)?;
+	}
+	Ok(())
+}
+
+
+/// Writes the palette to the given buffer.
+pub fn write_palette<W>(palette: &Palette, out_buf: &mut W) -> Result<()>
+	where W: io::Write
+{
+	let colors: Vec<(Address, Color)> = palette.data.colors().collect();
+	if colors.is_empty() || colors.len() > 256 {
+		return Err(format_error(
+			"Aseprite palettes must have between 1 and 256 colors"));
+	}
+
+	// Measure palette entries.
+	let entries_size = {
+		let mut counter = ByteCounter::default();
+		write_entries(palette, &colors, &mut counter)?;
+		counter.count() as u32
+	};
+	let chunk_size = 6 + 20 + entries_size;
+
+	// Build frame header.
+	let frame_size = ASEPRITE_FRAME_HEADER_SIZE as u32 + chunk_size;
+	let mut frame = Vec::with_capacity(ASEPRITE_FRAME_HEADER_SIZE);
+	push_u32(&mut frame, frame_size);
+	push_u16(&mut frame, ASEPRITE_FRAME_MAGIC);
+	push_u16(&mut frame, 1); // Old chunk count.
+	push_u16(&mut frame, 100); // Frame duration.
+	frame.extend_from_slice(&[0u8; 2]);
+	push_u32(&mut frame, 1); // New chunk count.
+
+	// Build file header.
+	let mut header = Vec::with_capacity(ASEPRITE_HEADER_SIZE);
+	push_u32(&mut header, ASEPRITE_HEADER_SIZE as u32 + frame_size);
+	push_u16(&mut header, ASEPRITE_MAGIC);
+	push_u16(&mut header, 1); // Frames.
+	push_u16(&mut header, 1); // Width.
+	push_u16(&mut header, 1); // Height.
+	push_u16(&mut header, 8); // Color depth (indexed.)
+	push_u32(&mut header, 0); // Flags.
+	push_u16(&mut header, 100); // Speed.
+	push_u32(&mut header, 0);
+	push_u32(&mut header, 0);
+	header.extend_from_slice(&[0u8; 4]); // Transparent index and padding.
+	push_u16(&mut header, colors.len() as u16 % 256);
+	header.extend_from_slice(&[1, 1]); // Pixel ratio.
+	header.resize(ASEPRITE_HEADER_SIZE, 0);
+
+	// Build palette chunk header.
+	let mut chunk = Vec::with_capacity(26);
+	push_u32(&mut chunk, chunk_size);
+	push_u16(&mut chunk, ASEPRITE_PALETTE_CHUNK);
+	push_u32(&mut chunk, colors.len() as u32);
+	push_u32(&mut chunk, 0);
+	push_u32(&mut chunk, colors.len() as u32 - 1);
+	chunk.extend_from_slice(&[0u8; 8]);
+
+	out_buf.write_all(&header)?;
+	out_buf.write_all(&frame)?;
+	out_buf.write_all(&chunk)?;
+	write_entries(palette, &colors, out_buf)?;
+	Ok(())
+}
+
+
+/// Reads a palette from the first frame of the given buffer.
+pub fn read_palette<R>(in_buf: &mut R, options: &ParseOptions) 
+	-> Result<Palette>
+	where R: io::Read
+{
+	let mut in_buf = OffsetReader::new(in_buf);
+	let mut palette = Palette::new("", Format::Aseprite, true);
+	let result = read_frame(&mut in_buf, &mut palette, options);
+	options.finish(in_buf.offset(), palette, result)
+}
+
+/// Reads the palette of the first frame of the given buffer into the palette.
+fn read_frame<R>(
+	in_buf: &mut OffsetReader<R>,
+	palette: &mut Palette,
+	options: &ParseOptions)
+	-> Result<()>
+	where R: io::Read
+{
+	let mut header = [0u8; ASEPRITE_HEADER_SIZE];
+	in_buf.read_exact(&mut header)?;
+	let magic = u16_at(&header, 0, 4)?;
+	if magic != ASEPRITE_MAGIC {
+		return Err(parse_error(
+			4, 
+			format!("magic number {:04X}", ASEPRITE_MAGIC), 
+			format!("{:04X}", magic)));
+	}
+	if u16_at(&header, 0, 6)? == 0 {
+		return Err(parse_error(6, "at least one frame", "0 frames"));
+	}
+
+	let base = in_buf.offset();
+	let mut frame_header = [0u8; ASEPRITE_FRAME_HEADER_SIZE];
+	in_buf.read_exact(&mut frame_header)?;
+	let magic = u16_at(&frame_header, base, 4)?;
+	if magic != ASEPRITE_FRAME_MAGIC {
+		return Err(parse_error(
+			base + 4, 
+			format!("frame magic number {:04X}", ASEPRITE_FRAME_MAGIC), 
+			format!("{:04X}", magic)));
+	}
+	let chunk_count = match u32_at(&frame_header, base, 12)? {
+		0 => u16_at(&frame_header, base, 6)? as u32,
+		n => n,
+	};
+
+	// Read chunks, preferring the new palette chunk. In lenient mode, the 
+	// entries of a malformed chunk before the error are kept.
+	let mut entries = None;
+	let mut old_entries = None;
+	for _ in 0..chunk_count {
+		let base = in_buf.offset();
+		let mut chunk_header = [0u8; 6];
+		in_buf.read_exact(&mut chunk_header)?;
+		let size = u32_at(&chunk_header, base, 0)? as u64;
+		let chunk_type = u16_at(&chunk_header, base, 4)?;
+		if size < 6 {
+			options.recover(parse_error(
+				base, 
+				"a chunk size of at least 6 bytes", 
+				format!("{} bytes", size)))?;
+			break;
+		}
+		let mut body = Vec::new();
+		in_buf.by_ref().take(size - 6).read_to_end(&mut body)?;
+		if (body.len() as u64) < size - 6 {
+			options.recover(parse_error(
+				in_buf.offset(), 
+				format!("{} bytes of chunk data", size - 6), 
+				format!("{} bytes", body.len())))?;
+		}
+
+		let base = base + 6;
+		let mut parsed = Vec::new();
+		let result = match chunk_type {
+			ASEPRITE_PALETTE_CHUNK => {
+				let result = parse_palette_chunk(&body, base, &mut parsed);
+				entries = Some(parsed);
+				result
+			},
+			ASEPRITE_OLD_PALETTE_CHUNK if old_entries.is_none() => {
+				let result = parse_old_palette_chunk(
+					&body, 
+					base, 
+					false, 
+					&mut parsed);
+				old_entries = Some(parsed);
+				result
+			},
+			ASEPRITE_OLD_PALETTE_6BIT_CHUNK if old_entries.is_none() => {
+				let result = parse_old_palette_chunk(
+					&body, 
+					base, 
+					true, 
+					&mut parsed);
+				old_entries = Some(parsed);
+				result
+			},
+			_ => Ok(()),
+		};
+		if let Err(e) = result { options.recover(e)?; }
+	}
+
+	let entries = entries.or(old_entries)
+		.ok_or_else(|| parse_error(
+			in_buf.offset(), 
+			"a palette chunk", 
+			"end of frame"))?;
+	for entry in entries {
+		if !options.admits(palette.data.len(), entry.offset)? {
+			break;
+		}
+		let address = palette.data
+			.add_color(entry.color)?;
+		let reference = Reference::from(address);
+		if let Some(name) = entry.name {
+			palette.data.set_name(reference.clone(), name);
+		}
+		if entry.alpha != 0xFF {
+			palette.data.register_meta(ALPHA_KEY, MetaType::Int)?;
+			palette.data.set_meta(reference, ALPHA_KEY, entry.alpha as i64)?;
+		}
+	}
+	Ok(())
+}
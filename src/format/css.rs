@@ -0,0 +1,173 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides components for exporting palettes as CSS custom properties or SCSS
+//! variables.
+//!
+//! Each color is written as a variable whose name is built from its page, 
+//! line, and column. The name or label of each group is used for that part of
+//! the variable name when present, and the index otherwise, giving names such 
+//! as `page0-line1-col2` or `greens-line1-leaf`. Names are lowercased, and 
+//! characters other than letters and digits are replaced by `-`. Duplicate 
+//! names are given a numeric suffix.
+//!
+//! These formats are export only.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::Palette;
+use address::Reference;
+use data::Data;
+use format::Format;
+use format::report::{Loss, WriteReport};
+use hex::Hex;
+use result::Result;
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::collections::HashSet;
+use std::io;
+
+
+/// Returns the given text converted to a valid variable name component, or 
+/// None if nothing would remain.
+fn slug(text: &str) -> Option<String> {
+	let mut slug = String::with_capacity(text.len());
+	for c in text.chars().flat_map(|c| c.to_lowercase()) {
+		if c.is_ascii_alphanumeric() {
+			slug.push(c);
+		} else if !slug.is_empty() && !slug.ends_with('-') {
+			slug.push('-');
+		}
+	}
+	while slug.ends_with('-') { slug.pop(); }
+	if slug.is_empty() { None } else { Some(slug) }
+}
+
+/// Returns the variable name component for the given group. Names which are
+/// rewritten or dropped to form the component are recorded in the given 
+/// report.
+fn component(
+	data: &Data, 
+	group: &Reference, 
+	prefix: &str, 
+	index: u32,
+	report: &mut WriteReport)
+	-> String 
+{
+	if let Some(name) = data.name(group) {
+		match slug(name) {
+			Some(ref slug) if slug == name => (),
+			Some(_) => report.push(Loss::NameRewritten(group.clone())),
+			None => report.push(Loss::Name(group.clone())),
+		}
+	}
+	data.name(group)
+		.and_then(slug)
+		.or_else(|| data.label(group).and_then(slug))
+		.unwrap_or_else(|| format!("{}{}", prefix, index))
+}
+
+/// Returns the variable names and colors of the palette, in address order.
+/// Names which are rewritten or dropped to form the variable names are 
+/// recorded in the given report.
+fn variables(data: &Data, report: &mut WriteReport) -> Vec<(String, Color)> {
+	let mut used = HashSet::new();
+	let mut variables = Vec::new();
+	for (address, color) in data.colors() {
+		let cell = Reference::from(address);
+		let base = format!("{}-{}-{}",
+			component(data, &Reference::page_of(&address), 
+				"page", address.page as u32, report),
+			component(data, &Reference::line_of(&address), 
+				"line", address.line as u32, report),
+			component(data, &cell, 
+				"col", address.column as u32, report));
+		let mut name = base.clone();
+		let mut n = 2;
+		while !used.insert(name.clone()) {
+			name = format!("{}-{}", base, n);
+			n += 1;
+		}
+		if name != base && data.name(&cell).is_some() {
+			report.push(Loss::NameRewritten(cell));
+		}
+		variables.push((name, color));
+	}
+	variables
+}
+
+/// Writes the palette to the given buffer as CSS custom properties on the 
+/// `:root` selector.
+pub fn write_palette<W>(palette: &Palette, out_buf: &mut W) -> Result<()>
+	where W: io::Write
+{
+	write_palette_reported(palette, out_buf, &mut WriteReport::new(Format::Css))
+}
+
+
+/// Writes the palette to the given buffer as CSS custom properties, 
+/// recording the names rewritten to form the property names in the given 
+/// report.
+pub fn write_palette_reported<W>(
+	palette: &Palette, 
+	out_buf: &mut W,
+	report: &mut WriteReport)
+	-> Result<()>
+	where W: io::Write
+{
+	writeln!(out_buf, ":root {{")?;
+	for (name, color) in variables(&palette.data, report) {
+		writeln!(out_buf, "\t--{}: {};", name, Hex(color))?;
+	}
+	writeln!(out_buf, "}}")?;
+	Ok(())
+}
+
+
+/// Writes the palette to the given buffer as SCSS variables.
+pub fn write_scss<W>(palette: &Palette, out_buf: &mut W) -> Result<()>
+	where W: io::Write
+{
+	write_scss_reported(palette, out_buf, &mut WriteReport::new(Format::Scss))
+}
+
+
+/// Writes the palette to the given buffer as SCSS variables, recording the 
+/// names rewritten to form the variable names in the given report.
+pub fn write_scss_reported<W>(
+	palette: &Palette, 
+	out_buf: &mut W,
+	report: &mut WriteReport)
+	-> Result<()>
+	where W: io::Write
+{
+	for (name, color) in variables(&palette.data, report) {
+		writeln!(out_buf, "${}: {};", name, Hex(color))?;
+	}
+	Ok(())
+}
@@ -0,0 +1,451 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides components for interacting with palettes stored as CSV or TSV 
+//! tables.
+//!
+//! Each color is written as a record holding the columns chosen in the 
+//! `CsvOptions`: its address, hex value, channel values, slot name, or tags.
+//! Tags are separated by `;` within their field. Fields containing the 
+//! delimiter or quotes are quoted, with quotes doubled, but fields may not 
+//! span lines.
+//!
+//! When reading, a header record names the columns if present, and unknown 
+//! columns are ignored. A color is read from the hex column, or from the 
+//! channel columns if there is no hex column. Colors are placed at their 
+//! addresses when there is an address column, and in the first free slot 
+//! otherwise.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::Palette;
+use address::{Address, Reference};
+use format::{Format, Lines, ParseOptions, parse_error};
+use hex::{from_hex, Hex};
+use expression::Expression;
+use result::Result;
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::io;
+
+
+/// The separator between tags within a field.
+const TAG_SEPARATOR: &str = ";";
+
+
+////////////////////////////////////////////////////////////////////////////////
+// CsvColumn
+////////////////////////////////////////////////////////////////////////////////
+/// A column of a CSV palette table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CsvColumn {
+	/// The address of the color, in `P:L:C` form.
+	Address,
+	/// The color as a `#RRGGBB` hexadecimal value.
+	Hex,
+	/// The red channel of the color, in decimal.
+	R,
+	/// The green channel of the color, in decimal.
+	G,
+	/// The blue channel of the color, in decimal.
+	B,
+	/// The slot name of the color.
+	Name,
+	/// The tags of the color's slot.
+	Tags,
+}
+
+
+impl CsvColumn {
+	/// Returns the name of the column used in header records.
+	pub fn name(self) -> &'static str {
+		match self {
+			CsvColumn::Address => "address",
+			CsvColumn::Hex => "hex",
+			CsvColumn::R => "r",
+			CsvColumn::G => "g",
+			CsvColumn::B => "b",
+			CsvColumn::Name => "name",
+			CsvColumn::Tags => "tags",
+		}
+	}
+
+	/// Returns the column with the given header name, or None if the name is
+	/// not recognized. Names are matched case-insensitively.
+	pub fn from_name(name: &str) -> Option<CsvColumn> {
+		match &name.trim().to_lowercase()[..] {
+			"address" => Some(CsvColumn::Address),
+			"hex" => Some(CsvColumn::Hex),
+			"r" | "red" => Some(CsvColumn::R),
+			"g" | "green" => Some(CsvColumn::G),
+			"b" | "blue" => Some(CsvColumn::B),
+			"name" => Some(CsvColumn::Name),
+			"tags" => Some(CsvColumn::Tags),
+			_ => None,
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// CsvOptions
+////////////////////////////////////////////////////////////////////////////////
+/// Options controlling the layout of a CSV palette table.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::{Palette, Format, Color};
+/// use palette::format::ParseOptions;
+/// use palette::format::csv::{self, CsvColumn, CsvOptions};
+///
+/// let mut pal = Palette::new("Example", Format::Default, false);
+/// let red = pal.data_mut().add_color(Color::new(255, 0, 0)).unwrap();
+/// pal.data_mut().set_slot_name(red, "Red, bright");
+///
+/// let options = CsvOptions::tsv()
+///     .columns(vec![
+///         CsvColumn::Name, 
+///         CsvColumn::R, 
+///         CsvColumn::G, 
+///         CsvColumn::B,
+///     ]);
+/// let mut out = Vec::new();
+/// csv::write_palette_with(&pal, &mut out, &options).unwrap();
+/// assert_eq!(String::from_utf8(out.clone()).unwrap(), 
+///     "name\tr\tg\tb\nRed, bright\t255\t0\t0\n");
+///
+/// let read = csv::read_palette_with(
+///     &mut &out[..], 
+///     &ParseOptions::strict(), 
+///     &options).unwrap();
+/// assert_eq!(read.data().slot_name(red), Some("Red, bright"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CsvOptions {
+	/// The character separating the fields of a record.
+	pub delimiter: char,
+	/// The columns to write, in order. When reading, these columns are used if
+	/// there is no header record.
+	pub columns: Vec<CsvColumn>,
+	/// Whether the table begins with a header record naming its columns.
+	pub header: bool,
+}
+
+
+impl CsvOptions {
+	/// Returns options for a comma separated table with a header, holding the
+	/// address, hex value, and name of each color.
+	pub fn csv() -> CsvOptions {
+		CsvOptions {
+			delimiter: ',',
+			columns: vec![CsvColumn::Address, CsvColumn::Hex, CsvColumn::Name],
+			header: true,
+		}
+	}
+
+	/// Returns options for a tab separated table with a header, holding the
+	/// address, hex value, and name of each color.
+	pub fn tsv() -> CsvOptions {
+		CsvOptions {delimiter: '\t', .. CsvOptions::csv()}
+	}
+
+	/// Sets the character separating the fields of a record.
+	pub fn delimiter(mut self, delimiter: char) -> Self {
+		self.delimiter = delimiter;
+		self
+	}
+
+	/// Sets the columns of the table.
+	pub fn columns(mut self, columns: Vec<CsvColumn>) -> Self {
+		self.columns = columns;
+		self
+	}
+
+	/// Sets whether the table begins with a header record.
+	pub fn header(mut self, header: bool) -> Self {
+		self.header = header;
+		self
+	}
+}
+
+
+impl Default for CsvOptions {
+	fn default() -> Self {
+		CsvOptions::csv()
+	}
+}
+
+
+
+/// Returns the given field, quoted if it contains the delimiter or quotes.
+fn quote(field: &str, delimiter: char) -> String {
+	if field.contains(|c: char| c == delimiter || c == '"') {
+		format!("\"{}\"", field.replace('"', "\"\""))
+	} else {
+		field.to_owned()
+	}
+}
+
+/// Splits the given record into its fields, removing any quotes. Returns None
+/// if a quoted field is not closed.
+fn split_record(record: &str, delimiter: char) -> Option<Vec<String>> {
+	let mut fields = Vec::new();
+	let mut field = String::new();
+	let mut chars = record.chars().peekable();
+	let mut quoted = false;
+	while let Some(c) = chars.next() {
+		if quoted {
+			if c != '"' {
+				field.push(c);
+			} else if chars.peek() == Some(&'"') {
+				field.push('"');
+				chars.next();
+			} else {
+				quoted = false;
+			}
+		} else if c == '"' {
+			quoted = true;
+		} else if c == delimiter {
+			fields.push(field);
+			field = String::new();
+		} else {
+			field.push(c);
+		}
+	}
+	if quoted { return None; }
+	fields.push(field);
+	Some(fields)
+}
+
+/// Returns the value of the given column in the given record, if present.
+fn field<'a>(
+	columns: &[Option<CsvColumn>],
+	record: &'a [String],
+	column: CsvColumn)
+	-> Option<&'a str>
+{
+	columns.iter()
+		.position(|&c| c == Some(column))
+		.and_then(|index| record.get(index))
+		.map(|field| &field[..])
+}
+
+/// Returns the color of the given record, or None if it can not be parsed.
+fn record_color(columns: &[Option<CsvColumn>], record: &[String]) 
+	-> Option<Color>
+{
+	if let Some(hex) = field(columns, record, CsvColumn::Hex) {
+		return from_hex(hex).ok();
+	}
+	let channel = |column| field(columns, record, column)
+		.and_then(|text| text.trim().parse::<u8>().ok());
+	match (channel(CsvColumn::R), channel(CsvColumn::G), channel(CsvColumn::B))
+	{
+		(Some(r), Some(g), Some(b)) => Some(Color::new(r, g, b)),
+		_ => None,
+	}
+}
+
+
+/// Writes the palette to the given buffer as a comma separated table using 
+/// the default options.
+pub fn write_palette<W>(palette: &Palette, out_buf: &mut W) -> Result<()>
+	where W: io::Write
+{
+	write_palette_with(palette, out_buf, &CsvOptions::csv())
+}
+
+/// Writes the palette to the given buffer as a tab separated table using the
+/// default options.
+pub fn write_tsv<W>(palette: &Palette, out_buf: &mut W) -> Result<()>
+	where W: io::Write
+{
+	write_palette_with(palette, out_buf, &CsvOptions::tsv())
+}
+
+/// Writes the palette to the given buffer using the given table options.
+pub fn write_palette_with<W>(
+	palette: &Palette,
+	out_buf: &mut W,
+	csv: &CsvOptions)
+	-> Result<()>
+	where W: io::Write
+{
+	let separator = csv.delimiter.to_string();
+	if csv.header {
+		let names: Vec<&str> = csv.columns.iter().map(|c| c.name()).collect();
+		writeln!(out_buf, "{}", names.join(&separator[..]))?;
+	}
+
+	let data = &palette.data;
+	for (address, color) in data.colors() {
+		let fields: Vec<String> = csv.columns.iter()
+			.map(|&column| match column {
+				CsvColumn::Address => address.to_string(),
+				CsvColumn::Hex => Hex(color).to_string(),
+				CsvColumn::R => color.r().to_string(),
+				CsvColumn::G => color.g().to_string(),
+				CsvColumn::B => color.b().to_string(),
+				CsvColumn::Name => data.slot_name(address)
+					.unwrap_or("")
+					.to_owned(),
+				CsvColumn::Tags => data.tags(&Reference::from(address))
+					.join(TAG_SEPARATOR),
+			})
+			.map(|field| quote(&field, csv.delimiter))
+			.collect();
+		writeln!(out_buf, "{}", fields.join(&separator[..]))?;
+	}
+	Ok(())
+}
+
+
+/// Reads a comma separated table from the given buffer using the default 
+/// options.
+pub fn read_palette<R>(in_buf: &mut R, options: &ParseOptions) 
+	-> Result<Palette>
+	where R: io::Read
+{
+	read_table(in_buf, options, &CsvOptions::csv(), Format::Csv)
+}
+
+/// Reads a tab separated table from the given buffer using the default 
+/// options.
+pub fn read_tsv<R>(in_buf: &mut R, options: &ParseOptions) 
+	-> Result<Palette>
+	where R: io::Read
+{
+	read_table(in_buf, options, &CsvOptions::tsv(), Format::Tsv)
+}
+
+/// Reads a palette from the given buffer using the given table options.
+pub fn read_palette_with<R>(
+	in_buf: &mut R,
+	options: &ParseOptions,
+	csv: &CsvOptions)
+	-> Result<Palette>
+	where R: io::Read
+{
+	let format = if csv.delimiter == '\t' { Format::Tsv } else { Format::Csv };
+	read_table(in_buf, options, csv, format)
+}
+
+/// Reads a table from the given buffer into a new palette with the given 
+/// format.
+fn read_table<R>(
+	in_buf: &mut R,
+	options: &ParseOptions,
+	csv: &CsvOptions,
+	format: Format)
+	-> Result<Palette>
+	where R: io::Read
+{
+	let mut palette = Palette::new("", format, true);
+	let mut header: Option<Vec<Option<CsvColumn>>> = if csv.header {
+		None
+	} else {
+		Some(csv.columns.iter().map(|&c| Some(c)).collect())
+	};
+
+	for line in Lines::new(in_buf) {
+		let (offset, line) = line?;
+		if line.trim().is_empty() { continue; }
+		let record = match split_record(&line, csv.delimiter) {
+			Some(record) => record,
+			None => {
+				options.recover(parse_error(
+					offset, 
+					"a closing quote", 
+					format!("{:?}", line)))?;
+				continue;
+			},
+		};
+
+		// The first record names the columns if there is a header.
+		let columns = match header {
+			Some(ref columns) => columns,
+			None => {
+				header = Some(record.iter()
+					.map(|name| CsvColumn::from_name(name))
+					.collect());
+				continue;
+			},
+		};
+
+		let color = match record_color(columns, &record) {
+			Some(color) => color,
+			None => {
+				options.recover(parse_error(
+					offset, 
+					"a color record", 
+					format!("{:?}", line)))?;
+				continue;
+			},
+		};
+		if !options.admits(palette.data.len(), offset)? {
+			break;
+		}
+
+		let address = match field(columns, &record, CsvColumn::Address) {
+			Some(text) => {
+				let cell = text.parse::<Address>()
+					.and_then(|address| palette.data
+						.create_cell(address)
+						.map(|cell| (address, cell)));
+				match cell {
+					Ok((address, cell)) => {
+						*cell.borrow_mut() = Expression::Color(color);
+						address
+					},
+					Err(_) => {
+						options.recover(parse_error(
+							offset, 
+							"a free address", 
+							format!("{:?}", text)))?;
+						continue;
+					},
+				}
+			},
+			None => palette.data.add_color(color)?,
+		};
+
+		if let Some(name) = field(columns, &record, CsvColumn::Name) {
+			if !name.is_empty() {
+				palette.data.set_slot_name(address, name);
+			}
+		}
+		if let Some(tags) = field(columns, &record, CsvColumn::Tags) {
+			for tag in tags.split(TAG_SEPARATOR).filter(|t| !t.is_empty()) {
+				palette.data.add_tag(Reference::from(address), tag);
+			}
+		}
+	}
+	Ok(palette)
+}
@@ -29,7 +29,7 @@
 // Local imports.
 use ::Palette;
 use operation::PaletteOperation;
-use result::Result;
+use result::{Error, Result};
 
 
 
@@ -65,7 +65,7 @@ pub fn undo(palette: &mut Palette) -> Result<()> {
 		}
 		Ok(())
 	} else {
-		panic!("undo not supported")
+		Err(Error::HistoryDisabled)
 	}
 }
 
@@ -83,6 +83,6 @@ pub fn redo(palette: &mut Palette) -> Result<()> {
 		}
 		Ok(())
 	} else {
-		panic!("undo not supported")
+		Err(Error::HistoryDisabled)
 	}
 }
\ No newline at end of file
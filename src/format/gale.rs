@@ -0,0 +1,151 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides components for interacting with the GraphicsGale palette format.
+//!
+//! GraphicsGale palette files are plain text, beginning with a 
+//! `GraphicsGale Palette` line and a color count line. Each color is given on
+//! its own line as three decimal channel values. GraphicsGale palettes hold at
+//! most 256 colors.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::Palette;
+use format::{Format, Lines, ParseOptions, parse_error};
+use result::{Error, Result};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::io;
+
+
+/// The required first line of a GraphicsGale palette file.
+const GALE_HEADER: &'static str = "GraphicsGale Palette";
+
+/// The maximum number of colors in a GraphicsGale palette.
+const GALE_COLOR_LIMIT: usize = 256;
+
+
+/// Returns a `Format` error with the given reason.
+fn format_error(reason: &str) -> Error {
+	Error::Format {format: Format::Gale.name(), reason: reason.into()}
+}
+
+/// Returns the next line and its offset from the given lines, or an error 
+/// describing the expected line if there are no more lines.
+fn next_line<R>(lines: &mut Lines<R>, expected: &str) 
+	-> Result<(u64, String)>
+	where R: io::Read
+{
+	match lines.next() {
+		Some(line) => Ok(line?),
+		None => Err(parse_error(lines.offset(), expected, "end of file")),
+	}
+}
+
+
+/// Writes the palette to the given buffer.
+pub fn write_palette<W>(palette: &Palette, out_buf: &mut W) -> Result<()>
+	where W: io::Write
+{
+	let colors: Vec<Color> = palette.data.colors().map(|(_, c)| c).collect();
+	if colors.len() > GALE_COLOR_LIMIT {
+		return Err(format_error("too many colors for GraphicsGale palette"));
+	}
+
+	write!(out_buf, "{}\r\n{}\r\n", GALE_HEADER, colors.len())?;
+	for color in colors {
+		write!(out_buf, "{} {} {}\r\n", color.r(), color.g(), color.b())?;
+	}
+	Ok(())
+}
+
+
+/// Reads a palette from the given buffer.
+pub fn read_palette<R>(in_buf: &mut R, options: &ParseOptions) 
+	-> Result<Palette>
+	where R: io::Read
+{
+	let mut palette = Palette::new("", Format::Gale, true);
+	let mut lines = Lines::new(in_buf);
+
+	// Read header.
+	let (offset, line) = next_line(&mut lines, "GraphicsGale header")?;
+	if line.trim() != GALE_HEADER {
+		return Err(parse_error(
+			offset, 
+			"GraphicsGale header", 
+			format!("{:?}", line)));
+	}
+
+	// In lenient mode, an invalid color count reads colors until the end of
+	// the file, and an excessive one is clamped to the color limit.
+	let (offset, line) = next_line(&mut lines, "a color count")?;
+	let count = match line.trim().parse() {
+		Ok(count) if count <= GALE_COLOR_LIMIT => count,
+		_ => {
+			options.recover(parse_error(
+				offset, 
+				format!("a color count of at most {}", GALE_COLOR_LIMIT), 
+				format!("{:?}", line)))?;
+			GALE_COLOR_LIMIT
+		},
+	};
+
+	// Read colors.
+	let mut read = 0;
+	while read < count {
+		let (offset, line) = match lines.next() {
+			Some(line) => line?,
+			None if options.strict => return Err(parse_error(
+				lines.offset(), 
+				format!("{} colors", count), 
+				format!("{} colors", read))),
+			None => break,
+		};
+		read += 1;
+
+		let channels: Vec<u8> = line.split_whitespace()
+			.take(3)
+			.filter_map(|part| part.parse().ok())
+			.collect();
+		if channels.len() < 3 {
+			options.recover(parse_error(
+				offset, 
+				"a color entry", 
+				format!("{:?}", line)))?;
+			continue;
+		}
+		if !options.admits(palette.data.len(), offset)? {
+			break;
+		}
+
+		palette.data
+			.add_color(Color::new(channels[0], channels[1], channels[2]))?;
+	}
+	Ok(palette)
+}
@@ -0,0 +1,156 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides components for interacting with the GIMP palette format. 
+//!
+//! GPL files are plain text, beginning with a `GIMP Palette` line and followed
+//! by optional `Name:` and `Columns:` headers. Each color is given on its own
+//! line as three decimal channel values, followed by an optional color name.
+//! Lines beginning with `#` are comments.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::Palette;
+use address::Reference;
+use format::{Format, Lines, ParseOptions, parse_error};
+use result::Result;
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::io;
+
+
+/// The required first line of a GPL file.
+const GPL_HEADER: &'static str = "GIMP Palette";
+
+/// The name written for colors with no name.
+const GPL_DEFAULT_COLOR_NAME: &'static str = "Untitled";
+
+
+
+/// Writes the palette to the given buffer.
+pub fn write_palette<W>(palette: &Palette, out_buf: &mut W) -> Result<()>
+	where W: io::Write
+{
+	let data = &palette.data;
+
+	writeln!(out_buf, "{}", GPL_HEADER)?;
+	if let Some(name) = data.name(&Reference::all()) {
+		writeln!(out_buf, "Name: {}", name)?;
+	}
+	writeln!(out_buf, "Columns: {}", data.default_column_count)?;
+	writeln!(out_buf, "#")?;
+
+	for (address, cell) in data.cells.iter() {
+		if let Some(color) = data.color_of(cell) {
+			writeln!(out_buf, "{:3} {:3} {:3}\t{}",
+				color.r(),
+				color.g(),
+				color.b(),
+				data.name(&Reference::from(address))
+					.unwrap_or(GPL_DEFAULT_COLOR_NAME)
+			)?;
+		}
+	}
+	Ok(())
+}
+
+
+/// Reads a palette from the given buffer.
+pub fn read_palette<R>(in_buf: &mut R, options: &ParseOptions) 
+	-> Result<Palette>
+	where R: io::Read
+{
+	let mut palette = Palette::new("", Format::Gpl, true);
+	let mut lines = Lines::new(in_buf);
+
+	// Check header.
+	match lines.next() {
+		Some(line) => {
+			let (_, line) = line?;
+			if line.trim() != GPL_HEADER {
+				return Err(parse_error(
+					0, 
+					"GIMP Palette header", 
+					format!("{:?}", line)));
+			}
+		},
+		None => return Err(parse_error(
+			0, 
+			"GIMP Palette header", 
+			"end of file")),
+	}
+
+	for line in lines {
+		let (offset, line) = line?;
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') { continue; }
+
+		// Parse header fields.
+		if line.starts_with("Name:") {
+			palette.data.set_name(Reference::all(), line[5..].trim());
+			continue;
+		}
+		if line.starts_with("Columns:") {
+			match line[8..].trim().parse() {
+				Ok(0) => (),
+				Ok(columns) => palette.data.default_column_count = columns,
+				Err(_) => options.recover(parse_error(
+					offset, 
+					"a column count", 
+					format!("{:?}", line)))?,
+			}
+			continue;
+		}
+
+		// Parse color entry.
+		let mut parts = line.split_whitespace();
+		let channels: Vec<u8> = parts.by_ref()
+			.take(3)
+			.filter_map(|part| part.parse().ok())
+			.collect();
+		if channels.len() < 3 {
+			options.recover(parse_error(
+				offset, 
+				"a color entry", 
+				format!("{:?}", line)))?;
+			continue;
+		}
+		if !options.admits(palette.data.len(), offset)? {
+			break;
+		}
+
+		let address = palette.data
+			.add_color(Color::new(channels[0], channels[1], channels[2]))?;
+
+		let name = parts.collect::<Vec<_>>().join(" ");
+		if !name.is_empty() && name != GPL_DEFAULT_COLOR_NAME {
+			palette.data.set_name(Reference::from(address), name);
+		}
+	}
+	Ok(palette)
+}
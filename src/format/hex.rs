@@ -0,0 +1,112 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides components for interacting with plain hexadecimal color lists, such
+//! as those downloaded from Lospec.
+//!
+//! Each color is given on its own line as a `#RRGGBB` hexadecimal value. When
+//! reading, the `#` prefix is optional, letter case is ignored, blank lines are
+//! skipped, and anything following a `;` is treated as a comment.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::Palette;
+use format::{Format, Lines, ParseOptions, parse_error};
+use hex::{from_hex, Hex};
+use result::Result;
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::io;
+
+
+/// Parses a color from the given hexadecimal digits. Six digits are read as 
+/// `RRGGBB`, and eight digits are read as `AARRGGBB` with the alpha discarded.
+/// A leading `#` and surrounding whitespace are ignored.
+pub(crate) fn parse_hex(text: &str) -> Option<Color> {
+	from_hex(text).ok()
+}
+
+/// Returns the given line with any comment removed, or None if nothing 
+/// remains.
+pub(crate) fn content(line: &str) -> Option<&str> {
+	let line = line.split(';').next().unwrap_or("").trim();
+	if line.is_empty() { None } else { Some(line) }
+}
+
+/// Reads colors from the lines of the given buffer into a new palette with 
+/// the given format.
+pub(crate) fn read_lines<R>(
+	in_buf: &mut R,
+	format: Format,
+	options: &ParseOptions)
+	-> Result<Palette>
+	where R: io::Read
+{
+	let mut palette = Palette::new("", format, true);
+	for line in Lines::new(in_buf) {
+		let (offset, line) = line?;
+		if let Some(text) = content(&line) {
+			let color = match parse_hex(text) {
+				Some(color) => color,
+				None => {
+					options.recover(parse_error(
+						offset, 
+						"a hexadecimal color", 
+						format!("{:?}", text)))?;
+					continue;
+				},
+			};
+			if !options.admits(palette.data.len(), offset)? {
+				break;
+			}
+			palette.data
+				.add_color(color)?;
+		}
+	}
+	Ok(palette)
+}
+
+
+/// Writes the palette to the given buffer.
+pub fn write_palette<W>(palette: &Palette, out_buf: &mut W) -> Result<()>
+	where W: io::Write
+{
+	for (_, color) in palette.data.colors() {
+		writeln!(out_buf, "{}", Hex(color))?;
+	}
+	Ok(())
+}
+
+
+/// Reads a palette from the given buffer.
+pub fn read_palette<R>(in_buf: &mut R, options: &ParseOptions) 
+	-> Result<Palette>
+	where R: io::Read
+{
+	read_lines(in_buf, Format::Hex, options)
+}
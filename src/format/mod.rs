@@ -31,24 +31,436 @@
 #[warn(missing_docs)]
 pub mod zpl;
 #[warn(missing_docs)]
+pub mod aco;
+#[warn(missing_docs)]
+pub mod act;
+#[warn(missing_docs)]
+pub mod ase;
+#[warn(missing_docs)]
+pub mod aseprite;
+#[warn(missing_docs)]
+pub mod css;
+#[warn(missing_docs)]
+pub mod csv;
+#[warn(missing_docs)]
 pub mod default;
+#[warn(missing_docs)]
+pub mod gale;
+#[warn(missing_docs)]
+pub mod gpl;
+#[warn(missing_docs)]
+pub mod hex;
+#[warn(missing_docs)]
+pub mod jasc;
+#[warn(missing_docs)]
+pub mod native;
+#[warn(missing_docs)]
+pub mod paintnet;
+#[warn(missing_docs)]
+pub mod png;
+#[warn(missing_docs)]
+pub mod promotion;
+#[warn(missing_docs)]
+pub mod qst;
+#[warn(missing_docs)]
+pub mod registry;
+#[warn(missing_docs)]
+pub mod report;
+#[warn(missing_docs)]
+pub mod riff;
+#[warn(missing_docs)]
+pub mod svg;
+#[cfg(feature = "toml-format")]
+#[warn(missing_docs)]
+pub mod toml;
 
 // Module imports.
 use Palette;
 use address::Reference;
 use data::Data;
+use format::report::{Violation, WriteReport};
 use operation::PaletteOperation;
-use result::Result;
+use result::{Error, Result};
 
 // Standard imports.
 use std::io;
 
 
+////////////////////////////////////////////////////////////////////////////////
+// ByteCounter
+////////////////////////////////////////////////////////////////////////////////
+/// A writer which discards its input, counting the bytes written. Used by 
+/// format writers to measure a section before streaming it to the output.
+#[derive(Debug, Default)]
+pub(crate) struct ByteCounter {
+	count: u64,
+}
+
+impl ByteCounter {
+	/// Returns the number of bytes written.
+	pub(crate) fn count(&self) -> u64 {
+		self.count
+	}
+}
+
+impl io::Write for ByteCounter {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.count += buf.len() as u64;
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// OffsetReader
+////////////////////////////////////////////////////////////////////////////////
+/// A reader which counts the bytes read from the wrapped reader. Used by 
+/// format readers to report the location of malformed input.
+#[derive(Debug)]
+pub(crate) struct OffsetReader<R> {
+	inner: R,
+	offset: u64,
+}
+
+impl<R> OffsetReader<R> where R: io::Read {
+	/// Constructs a new `OffsetReader` wrapping the given reader.
+	pub(crate) fn new(inner: R) -> Self {
+		OffsetReader::starting_at(inner, 0)
+	}
+
+	/// Constructs a new `OffsetReader` wrapping the given reader, whose first
+	/// byte lies at the given offset.
+	pub(crate) fn starting_at(inner: R, offset: u64) -> Self {
+		OffsetReader {inner: inner, offset: offset}
+	}
+
+	/// Returns the number of bytes read.
+	pub(crate) fn offset(&self) -> u64 {
+		self.offset
+	}
+
+	/// Discards bytes until the given offset is reached.
+	pub(crate) fn skip_to(&mut self, offset: u64) -> io::Result<()> {
+		use std::io::Read;
+		if offset > self.offset {
+			let len = offset - self.offset;
+			let skipped = io::copy(
+				&mut self.by_ref().take(len), 
+				&mut io::sink())?;
+			if skipped < len {
+				return Err(io::ErrorKind::UnexpectedEof.into());
+			}
+		}
+		Ok(())
+	}
+}
+
+impl<R> io::Read for OffsetReader<R> where R: io::Read {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let count = self.inner.read(buf)?;
+		self.offset += count as u64;
+		Ok(count)
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Lines
+////////////////////////////////////////////////////////////////////////////////
+/// An iterator over the lines of a buffer, yielding each line along with the 
+/// byte offset of its start. Line endings are removed.
+#[derive(Debug)]
+pub(crate) struct Lines<R> {
+	inner: io::BufReader<R>,
+	offset: u64,
+}
+
+impl<R> Lines<R> where R: io::Read {
+	/// Constructs a new `Lines` iterator over the given reader.
+	pub(crate) fn new(inner: R) -> Self {
+		Lines {inner: io::BufReader::new(inner), offset: 0}
+	}
+
+	/// Returns the byte offset of the next line.
+	pub(crate) fn offset(&self) -> u64 {
+		self.offset
+	}
+}
+
+impl<R> Iterator for Lines<R> where R: io::Read {
+	type Item = io::Result<(u64, String)>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		use std::io::BufRead;
+		let start = self.offset;
+		let mut line = String::new();
+		match self.inner.read_line(&mut line) {
+			Ok(0) => None,
+			Ok(count) => {
+				self.offset += count as u64;
+				if line.ends_with('\n') { line.pop(); }
+				if line.ends_with('\r') { line.pop(); }
+				Some(Ok((start, line)))
+			},
+			Err(e) => Some(Err(e)),
+		}
+	}
+}
+
+
+/// Returns a `Parse` error for malformed input at the given byte offset.
+pub(crate) fn parse_error<E, F>(offset: u64, expected: E, found: F) -> Error
+	where E: Into<String>, F: Into<String>
+{
+	Error::Parse {
+		offset: offset,
+		expected: expected.into(),
+		found: found.into(),
+	}
+}
+
+/// Returns a description of the given bytes for use in a `Parse` error.
+pub(crate) fn describe_bytes(bytes: &[u8]) -> String {
+	let mut text = String::from("bytes");
+	for byte in bytes.iter().take(16) {
+		text.push_str(&format!(" {:02X}", byte));
+	}
+	if bytes.len() > 16 { text.push_str(" ..."); }
+	text
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Capabilities
+////////////////////////////////////////////////////////////////////////////////
+/// Describes which palette features a format is able to store. Writing a 
+/// palette using features the format lacks will lose information.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Format;
+///
+/// let caps = Format::Act.capabilities();
+/// assert_eq!(caps.max_colors, Some(256));
+/// assert!(!caps.names);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Capabilities {
+	/// The maximum number of colors that can be stored, or None if there is no
+	/// practical limit.
+	pub max_colors: Option<usize>,
+	/// Whether per-color transparency can be stored. Formats which store 
+	/// only some transparency report the rest as lost when written.
+	pub alpha: bool,
+	/// Whether color names can be stored.
+	pub names: bool,
+	/// Whether the page and line structure and group names can be stored.
+	pub groups: bool,
+	/// Whether derived colors can be stored without being reduced to fixed 
+	/// colors.
+	pub derived: bool,
+	/// The minimum number of colors that can be stored.
+	pub min_colors: usize,
+	/// The number of bits stored for each color channel.
+	pub depth: u8,
+	/// The maximum length of a stored name, or None if there is no practical
+	/// limit. Names are measured in UTF-16 code units for the Adobe formats,
+	/// which store them in UTF-16, and in UTF-8 bytes otherwise.
+	pub max_name_length: Option<usize>,
+}
+
+
+impl Capabilities {
+	/// Returns capabilities which can store only colors, with no limit on 
+	/// their number.
+	pub fn colors_only() -> Capabilities {
+		Capabilities {
+			max_colors: None,
+			alpha: false,
+			names: false,
+			groups: false,
+			derived: false,
+			min_colors: 0,
+			depth: 8,
+			max_name_length: None,
+		}
+	}
+
+	/// Returns capabilities which can store every palette feature.
+	pub fn lossless() -> Capabilities {
+		Capabilities {
+			max_colors: None,
+			alpha: true,
+			names: true,
+			groups: true,
+			derived: true,
+			min_colors: 0,
+			depth: 8,
+			max_name_length: Some(u16::max_value() as usize),
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ParseOptions
+////////////////////////////////////////////////////////////////////////////////
+/// Options controlling how format readers handle malformed input.
+///
+/// In strict mode, any malformed or unexpected input is an error. In lenient 
+/// mode, readers recover as much of the palette as they can: malformed 
+/// entries are skipped and truncated input ends the palette. Errors in either
+/// mode report the byte offset of the malformed input.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Format;
+/// use palette::format::ParseOptions;
+///
+/// let text = "FF0000\nnot a color\n00FF00\n";
+///
+/// let strict = ParseOptions::strict();
+/// assert!(Format::Hex.read_palette_with(&mut text.as_bytes(), &strict)
+///     .is_err());
+///
+/// let lenient = ParseOptions::lenient().max_colors(1);
+/// let pal = Format::Hex.read_palette_with(&mut text.as_bytes(), &lenient)
+///     .unwrap();
+/// assert_eq!(pal.len(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParseOptions {
+	/// Whether malformed input is an error. If false, readers skip malformed 
+	/// entries and stop at truncated input.
+	pub strict: bool,
+	/// The maximum number of colors to read, or None if there is no limit. In
+	/// strict mode, exceeding the limit is an error; in lenient mode, 
+	/// further colors are ignored.
+	pub max_colors: Option<usize>,
+}
+
+
+impl ParseOptions {
+	/// Returns options which treat malformed input as an error.
+	pub fn strict() -> ParseOptions {
+		ParseOptions {
+			strict: true,
+			max_colors: None,
+		}
+	}
+
+	/// Returns options which recover as much of a malformed palette as 
+	/// possible.
+	pub fn lenient() -> ParseOptions {
+		ParseOptions {
+			strict: false,
+			max_colors: None,
+		}
+	}
+
+	/// Sets the maximum number of colors to read.
+	pub fn max_colors(mut self, max_colors: usize) -> Self {
+		self.max_colors = Some(max_colors);
+		self
+	}
+
+	/// Returns the given error in strict mode, or Ok if the malformed input 
+	/// should be skipped.
+	pub(crate) fn recover(&self, error: Error) -> Result<()> {
+		if self.strict { Err(error) } else { Ok(()) }
+	}
+
+	/// Returns whether another color may be read into a palette holding the 
+	/// given number of colors. In strict mode, exceeding the color limit is 
+	/// reported as an error at the given offset.
+	pub(crate) fn admits(&self, count: usize, offset: u64) -> Result<bool> {
+		match self.max_colors {
+			Some(max) if count >= max => if self.strict {
+				Err(parse_error(
+					offset, 
+					format!("at most {} colors", max), 
+					"another color"))
+			} else {
+				Ok(false)
+			},
+			_ => Ok(true),
+		}
+	}
+
+	/// Completes the reading of a palette, given the result of reading its 
+	/// contents and the byte offset reached. Unexpected ends of input are 
+	/// reported as errors in strict mode; in lenient mode, the colors read 
+	/// before the end are kept.
+	pub(crate) fn finish(
+		&self,
+		offset: u64,
+		palette: Palette,
+		result: Result<()>)
+		-> Result<Palette>
+	{
+		if let Err(Error::Io(ref e)) = result {
+			if e.kind() == io::ErrorKind::UnexpectedEof {
+				return if self.strict || palette.data.is_empty() {
+					Err(parse_error(offset, "more data", "end of file"))
+				} else {
+					Ok(palette)
+				};
+			}
+		}
+		result.map(|_| palette)
+	}
+}
+
+
+impl Default for ParseOptions {
+	fn default() -> Self {
+		ParseOptions::strict()
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// PaletteReader
+////////////////////////////////////////////////////////////////////////////////
+/// Provides reading of palettes from a byte stream.
+pub trait PaletteReader {
+	/// Reads a palette from the given buffer.
+	fn read_from(&self, in_buf: &mut io::Read) -> Result<Palette>;
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// PaletteWriter
+////////////////////////////////////////////////////////////////////////////////
+/// Provides writing of palettes to a byte stream.
+pub trait PaletteWriter {
+	/// Returns the palette features the writer is able to store.
+	fn capabilities(&self) -> Capabilities;
+
+	/// Writes the palette to the given buffer.
+	fn write_to(&self, palette: &Palette, out_buf: &mut io::Write) 
+		-> Result<()>;
+}
+
+
+
 ////////////////////////////////////////////////////////////////////////////////
 // Format
 ////////////////////////////////////////////////////////////////////////////////
 /// An enum of the supported palette formats.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Format {
 	/// The default palette format; provides no special behaviors or 
 	/// restrictions.
@@ -58,6 +470,68 @@ pub enum Format {
 	/// lines per page, for 211 pages. The names of lines and pages are 
 	/// auto-generated.
 	Zpl,
+
+	/// The GIMP palette format. Colors are stored as plain text, and the
+	/// palette name, column count, and color names are preserved.
+	Gpl,
+
+	/// The Adobe Swatch Exchange format. Groups are stored as named pages.
+	Ase,
+
+	/// The JASC-PAL format used by Paint Shop Pro. Only colors are preserved.
+	Jasc,
+
+	/// An indexed PNG image. Colors are stored in the image palette, and up to
+	/// 256 colors are supported.
+	Png,
+
+	/// The Microsoft RIFF palette format. Entry flags are preserved as slot
+	/// metadata.
+	Riff,
+
+	/// The Adobe Photoshop color swatch format. Color names are preserved.
+	Aco,
+
+	/// The Adobe color table format. Up to 256 colors are supported.
+	Act,
+
+	/// The Paint.NET palette format. Only colors are preserved.
+	PaintNet,
+
+	/// A plain list of hexadecimal colors. Only colors are preserved.
+	Hex,
+
+	/// The Aseprite sprite format. Only the palette is read or written; color
+	/// names are preserved.
+	Aseprite,
+
+	/// The GraphicsGale palette format. Up to 256 colors are supported.
+	Gale,
+
+	/// The Pro Motion NG palette format, a raw table of 256 colors.
+	ProMotion,
+
+	/// A comma separated table. Addresses and color names are preserved.
+	Csv,
+
+	/// A tab separated table. Addresses and color names are preserved.
+	Tsv,
+
+	/// A human-editable TOML document. Colors, names, the page and line 
+	/// structure, and ramp specs are preserved. Requires the `toml-format` 
+	/// feature.
+	Toml,
+
+	/// CSS custom properties. Variable names are derived from group names.
+	/// Export only.
+	Css,
+
+	/// SCSS variables. Variable names are derived from group names. Export 
+	/// only.
+	Scss,
+
+	/// An SVG swatch grid. Slot names may be written as labels. Export only.
+	Svg,
 }
 
 #[cfg_attr(feature = "cargo-clippy", allow(single_match))]
@@ -114,21 +588,224 @@ impl Format {
 		default::redo(palette)
 	}
 
+	/// Returns the name of the format.
+	pub fn name(self) -> &'static str {
+		match self {
+			Format::Default => "Default",
+			Format::Zpl => "ZPL",
+			Format::Gpl => "GPL",
+			Format::Ase => "ASE",
+			Format::Jasc => "JASC-PAL",
+			Format::Png => "PNG",
+			Format::Riff => "RIFF",
+			Format::Aco => "ACO",
+			Format::Act => "ACT",
+			Format::PaintNet => "Paint.NET",
+			Format::Hex => "Hex",
+			Format::Aseprite => "Aseprite",
+			Format::Gale => "GraphicsGale",
+			Format::ProMotion => "Pro Motion",
+			Format::Csv => "CSV",
+			Format::Tsv => "TSV",
+			Format::Toml => "TOML",
+			Format::Css => "CSS",
+			Format::Scss => "SCSS",
+			Format::Svg => "SVG",
+		}
+	}
+
+	/// Returns the palette features the format is able to store.
+	pub fn capabilities(self) -> Capabilities {
+		let caps = Capabilities::colors_only();
+		match self {
+			Format::Default => Capabilities::lossless(),
+			Format::Zpl => Capabilities {
+				max_colors: Some(zpl::ZPL_COLOR_LIMIT),
+				groups: true,
+				depth: zpl::ZPL_COLOR_DEPTH,
+				.. caps
+			},
+			Format::Gpl | Format::Css | Format::Scss | Format::Svg 
+				| Format::Csv | Format::Tsv
+				=> Capabilities {names: true, .. caps},
+			Format::Aco => Capabilities {
+				max_colors: Some(0xFFFF), 
+				names: true, 
+				max_name_length: Some(0xFFFE),
+				.. caps
+			},
+			Format::Ase => Capabilities {
+				names: true, 
+				groups: true, 
+				max_name_length: Some(0xFFFE),
+				.. caps
+			},
+			Format::Toml => Capabilities {
+				names: true, 
+				groups: true, 
+				.. caps
+			},
+			Format::Act => Capabilities {
+				max_colors: Some(256), 
+				alpha: true, 
+				.. caps
+			},
+			Format::Gale | Format::ProMotion 
+				=> Capabilities {max_colors: Some(256), .. caps},
+			Format::Png => Capabilities {
+				max_colors: Some(256), 
+				min_colors: 1, 
+				alpha: true,
+				.. caps
+			},
+			Format::Riff => Capabilities {max_colors: Some(0xFFFF), .. caps},
+			Format::Aseprite => Capabilities {
+				max_colors: Some(256),
+				min_colors: 1,
+				alpha: true, 
+				names: true, 
+				.. caps
+			},
+			Format::Jasc | Format::PaintNet | Format::Hex => caps,
+		}
+	}
+
+	/// Returns a report of the palette data which would be dropped or altered
+	/// by writing the given palette in the format.
+	pub fn check(self, palette: &Palette) -> WriteReport {
+		let mut report = report::check(self, palette);
+		// Writers record the losses they cause, so write to a sink to find
+		// them. Failures to write are reported by `validate`.
+		let _ = self.write_with_report(palette, &mut io::sink(), &mut report);
+		report
+	}
+
+	/// Returns the problems which would prevent the given palette data from 
+	/// being written in the format, or which would corrupt the written file.
+	/// See `report::validate_for`.
+	pub fn validate(self, data: &Data) -> Vec<Violation> {
+		report::validate_for(self, data)
+	}
+
+	/// Writes the palette to the given buffer, returning a report of the 
+	/// palette data which was dropped or altered.
+	pub fn write_palette_reported<W>(self, palette: &Palette, out_buf: &mut W)
+		-> Result<WriteReport>
+		where W: io::Write
+	{
+		let mut report = report::check(self, palette);
+		self.write_with_report(palette, out_buf, &mut report)?;
+		Ok(report)
+	}
+
 	/// Writes the palette to the given buffer.
-	#[allow(unused_variables)]
-	pub fn write_palette<W>(self, palette: &Palette, out_buf: &mut W) -> io::Result<()> 
+	pub fn write_palette<W>(self, palette: &Palette, out_buf: &mut W) 
+		-> Result<()> 
 		where W: io::Write
 	{
-		unimplemented!()
+		self.write_with_report(palette, out_buf, &mut WriteReport::new(self))
 	}
 
-	/// Reads a palette from the given buffer.
-	#[allow(unused_variables)]
-	pub fn read_palette<R>(self, in_buf: &mut R) -> io::Result<()> 
+	/// Writes the palette to the given buffer, recording the losses caused by
+	/// the format's writer in the given report.
+	fn write_with_report<W>(
+		self, 
+		palette: &Palette, 
+		out_buf: &mut W, 
+		report: &mut WriteReport)
+		-> Result<()> 
+		where W: io::Write
+	{
+		// Writers resolve colors within the palette's maximum depth, so 
+		// deeper chains must be reported rather than written as empty.
+		palette.data.check_depth()?;
+		match self {
+			Format::Zpl => zpl::write_palette(palette, out_buf),
+			Format::Gpl => gpl::write_palette(palette, out_buf),
+			Format::Ase 
+				=> ase::write_palette_reported(palette, out_buf, report),
+			Format::Jasc => jasc::write_palette(palette, out_buf),
+			Format::Png => png::write_palette(palette, out_buf),
+			Format::Riff => riff::write_palette(palette, out_buf),
+			Format::Aco 
+				=> aco::write_palette_reported(palette, out_buf, report),
+			Format::Act 
+				=> act::write_palette_reported(palette, out_buf, report),
+			Format::PaintNet => paintnet::write_palette(palette, out_buf),
+			Format::Hex => hex::write_palette(palette, out_buf),
+			Format::Aseprite => aseprite::write_palette(palette, out_buf),
+			Format::Gale => gale::write_palette(palette, out_buf),
+			Format::ProMotion => promotion::write_palette(palette, out_buf),
+			Format::Csv => csv::write_palette(palette, out_buf),
+			Format::Tsv => csv::write_tsv(palette, out_buf),
+			#[cfg(feature = "toml-format")]
+			Format::Toml => toml::write_palette(palette, out_buf),
+			Format::Css 
+				=> css::write_palette_reported(palette, out_buf, report),
+			Format::Scss => css::write_scss_reported(palette, out_buf, report),
+			Format::Svg => svg::write_palette(palette, out_buf),
+			_ => Err(Error::Format {
+				format: self.name(),
+				reason: "writing is not supported".into(),
+			}),
+		}
+	}
+
+	/// Reads a palette from the given buffer, treating malformed input as an
+	/// error.
+	pub fn read_palette<R>(self, in_buf: &mut R) -> Result<Palette> 
+		where R: io::Read
+	{
+		self.read_palette_with(in_buf, &ParseOptions::default())
+	}
+
+	/// Reads a palette from the given buffer using the given parse options.
+	pub fn read_palette_with<R>(self, in_buf: &mut R, options: &ParseOptions)
+		-> Result<Palette> 
 		where R: io::Read
 	{
-		unimplemented!()
+		match self {
+			Format::Gpl => gpl::read_palette(in_buf, options),
+			Format::Ase => ase::read_palette(in_buf, options),
+			Format::Jasc => jasc::read_palette(in_buf, options),
+			Format::Png => png::read_palette(in_buf, options),
+			Format::Riff => riff::read_palette(in_buf, options),
+			Format::Aco => aco::read_palette(in_buf, options),
+			Format::Act => act::read_palette(in_buf, options),
+			Format::PaintNet => paintnet::read_palette(in_buf, options),
+			Format::Hex => hex::read_palette(in_buf, options),
+			Format::Aseprite => aseprite::read_palette(in_buf, options),
+			Format::Gale => gale::read_palette(in_buf, options),
+			Format::ProMotion => promotion::read_palette(in_buf, options),
+			Format::Csv => csv::read_palette(in_buf, options),
+			Format::Tsv => csv::read_tsv(in_buf, options),
+			#[cfg(feature = "toml-format")]
+			Format::Toml => toml::read_palette(in_buf, options),
+			Format::Zpl => zpl::read_palette(in_buf, options),
+			_ => Err(Error::Format {
+				format: self.name(),
+				reason: "reading is not supported".into(),
+			}),
+		}
+	}
+}
+
+
+impl PaletteReader for Format {
+	fn read_from(&self, mut in_buf: &mut io::Read) -> Result<Palette> {
+		self.read_palette(&mut in_buf)
 	}
 }
 
 
+impl PaletteWriter for Format {
+	fn capabilities(&self) -> Capabilities {
+		Format::capabilities(*self)
+	}
+
+	fn write_to(&self, palette: &Palette, mut out_buf: &mut io::Write) 
+		-> Result<()>
+	{
+		self.write_palette(palette, &mut out_buf)
+	}
+}
@@ -0,0 +1,1151 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides components for reading and writing the native `.rpal` palette 
+//! format.
+//!
+//! Unlike the external formats, the native format preserves all palette data:
+//! derived expressions and their source references, group metadata, names, 
+//! color cycles, ramp specs, and the page, line, and column configuration. 
+//!
+//! A native file begins with the `RPAL` magic number and a version number, 
+//! followed by a sequence of chunks. Each chunk has a four byte identifier and
+//! a length, so readers may skip unknown chunks. All values are little-endian.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{
+	Address,
+	Reference,
+	ReferenceComponent,
+	DirectReferenceComponent,
+};
+use animation::{Cycle, Direction};
+use cell::{Cell, Shared, Weak};
+use data::{Constraint, Data, MetaData};
+use expression::Expression;
+use meta::{MetaType, MetaValue};
+use format::{
+	ByteCounter,
+	OffsetReader,
+	ParseOptions,
+	describe_bytes,
+	parse_error,
+};
+use ramp::RampSpec;
+use result::{Error, Result};
+use space::{Adjustment, Channel, Cmyk, Easing, Interpolation};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io;
+use std::io::Read;
+
+
+/// The native file magic number.
+const NATIVE_MAGIC: [u8;4] = *b"RPAL";
+
+/// The native file version.
+const NATIVE_VERSION: u16 = 8;
+
+/// The name of the native format, used in error messages.
+const NATIVE_FORMAT_NAME: &'static str = "RPAL";
+
+/// The configuration chunk identifier.
+const CHUNK_CONFIG: [u8;4] = *b"CONF";
+
+/// The cell chunk identifier.
+const CHUNK_CELLS: [u8;4] = *b"CELL";
+
+/// The metadata chunk identifier.
+const CHUNK_METADATA: [u8;4] = *b"META";
+
+/// The names chunk identifier.
+const CHUNK_NAMES: [u8;4] = *b"NAME";
+
+/// The color cycles chunk identifier.
+const CHUNK_CYCLES: [u8;4] = *b"CYCL";
+
+/// The ramp specs chunk identifier.
+const CHUNK_RAMPS: [u8;4] = *b"RAMP";
+
+/// The metadata schema chunk identifier.
+const CHUNK_SCHEMA: [u8;4] = *b"MKEY";
+
+/// The slot constraints chunk identifier.
+const CHUNK_CONSTRAINTS: [u8;4] = *b"LOCK";
+
+// Expression tags.
+const EXPR_EMPTY: u8 = 0;
+const EXPR_COLOR: u8 = 1;
+const EXPR_BLEND: u8 = 2;
+const EXPR_LIGHTEN: u8 = 3;
+const EXPR_DARKEN: u8 = 4;
+const EXPR_COMPLEMENT: u8 = 5;
+const EXPR_CMYK: u8 = 6;
+const EXPR_FROZEN: u8 = 7;
+const EXPR_ADJUST: u8 = 8;
+
+// Interpolation tags.
+const INTERPOLATION_RGB: u8 = 0;
+const INTERPOLATION_LAB: u8 = 1;
+const INTERPOLATION_LINEAR_RGB: u8 = 2;
+
+// Easing tags.
+const EASING_LINEAR: u8 = 0;
+const EASING_IN: u8 = 1;
+const EASING_OUT: u8 = 2;
+const EASING_IN_OUT: u8 = 3;
+const EASING_GAMMA: u8 = 4;
+const EASING_CUBIC_BEZIER: u8 = 5;
+
+// Adjustment tags.
+const ADJUST_BRIGHTNESS: u8 = 0;
+const ADJUST_CONTRAST: u8 = 1;
+const ADJUST_HUE_ROTATE: u8 = 2;
+const ADJUST_SATURATE: u8 = 3;
+const ADJUST_GAMMA: u8 = 4;
+const ADJUST_LEVELS: u8 = 5;
+const ADJUST_SWAP_CHANNELS: u8 = 6;
+
+// Channel tags.
+const CHANNEL_RED: u8 = 0;
+const CHANNEL_GREEN: u8 = 1;
+const CHANNEL_BLUE: u8 = 2;
+
+// Metadata type tags.
+const META_STRING: u8 = 0;
+const META_INT: u8 = 1;
+const META_COLOR: u8 = 2;
+const META_BOOL: u8 = 3;
+
+// Constraint tags.
+const CONSTRAINT_LOCKED: u8 = 0;
+const CONSTRAINT_PROTECTED: u8 = 1;
+const CONSTRAINT_RESERVED: u8 = 2;
+
+// Cycle direction tags.
+const DIRECTION_FORWARD: u8 = 0;
+const DIRECTION_REVERSE: u8 = 1;
+const DIRECTION_PING_PONG: u8 = 2;
+
+// Reference component tags.
+const COMPONENT_ALL: u8 = 0;
+const COMPONENT_ANY: u8 = 1;
+const COMPONENT_INDEX: u8 = 2;
+const COMPONENT_NAMED: u8 = 3;
+const COMPONENT_INDIRECT: u8 = 4;
+
+
+/// Returns a `Format` error with the given reason.
+fn format_error(reason: &str) -> Error {
+	Error::Format {format: NATIVE_FORMAT_NAME, reason: reason.into()}
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Encoding
+////////////////////////////////////////////////////////////////////////////////
+fn write_u8<W>(out_buf: &mut W, value: u8) -> io::Result<()> 
+	where W: io::Write + ?Sized
+{
+	out_buf.write_all(&[value])
+}
+
+fn write_u16<W>(out_buf: &mut W, value: u16) -> io::Result<()> 
+	where W: io::Write + ?Sized
+{
+	out_buf.write_all(&[value as u8, (value >> 8) as u8])
+}
+
+fn write_u32<W>(out_buf: &mut W, value: u32) -> io::Result<()> 
+	where W: io::Write + ?Sized
+{
+	write_u16(out_buf, value as u16)?;
+	write_u16(out_buf, (value >> 16) as u16)
+}
+
+fn write_u64<W>(out_buf: &mut W, value: u64) -> io::Result<()> 
+	where W: io::Write + ?Sized
+{
+	write_u32(out_buf, value as u32)?;
+	write_u32(out_buf, (value >> 32) as u32)
+}
+
+fn write_opt_u64<W>(out_buf: &mut W, value: Option<u64>) -> io::Result<()> 
+	where W: io::Write + ?Sized
+{
+	match value {
+		Some(value) => { write_u8(out_buf, 1)?; write_u64(out_buf, value) },
+		None => write_u8(out_buf, 0),
+	}
+}
+
+fn write_str<W>(out_buf: &mut W, value: &str) -> io::Result<()> 
+	where W: io::Write + ?Sized
+{
+	// Lengths are stored in 16 bits, so longer strings cannot be written.
+	if value.len() > u16::MAX as usize {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidInput, 
+			"string longer than 65535 bytes"));
+	}
+	write_u16(out_buf, value.len() as u16)?;
+	out_buf.write_all(value.as_bytes())
+}
+
+fn write_opt_str<W>(out_buf: &mut W, value: Option<&str>) -> io::Result<()> 
+	where W: io::Write + ?Sized
+{
+	match value {
+		Some(value) => { write_u8(out_buf, 1)?; write_str(out_buf, value) },
+		None => write_u8(out_buf, 0),
+	}
+}
+
+fn write_meta_type<W>(out_buf: &mut W, meta_type: MetaType) 
+	-> io::Result<()> 
+	where W: io::Write + ?Sized
+{
+	write_u8(out_buf, match meta_type {
+		MetaType::String => META_STRING,
+		MetaType::Int => META_INT,
+		MetaType::Color => META_COLOR,
+		MetaType::Bool => META_BOOL,
+	})
+}
+
+fn write_meta_value<W>(out_buf: &mut W, value: &MetaValue) -> io::Result<()> 
+	where W: io::Write + ?Sized
+{
+	write_meta_type(out_buf, value.meta_type())?;
+	match *value {
+		MetaValue::String(ref value) => write_str(out_buf, value),
+		MetaValue::Int(value) => write_u64(out_buf, value as u64),
+		MetaValue::Color(color) 
+			=> out_buf.write_all(&[color.r(), color.g(), color.b()]),
+		MetaValue::Bool(value) => write_u8(out_buf, value as u8),
+	}
+}
+
+fn write_easing<W>(out_buf: &mut W, easing: Easing) -> io::Result<()> 
+	where W: io::Write + ?Sized
+{
+	match easing {
+		Easing::Linear => write_u8(out_buf, EASING_LINEAR),
+		Easing::EaseIn => write_u8(out_buf, EASING_IN),
+		Easing::EaseOut => write_u8(out_buf, EASING_OUT),
+		Easing::EaseInOut => write_u8(out_buf, EASING_IN_OUT),
+		Easing::Gamma(gamma) => {
+			write_u8(out_buf, EASING_GAMMA)?;
+			write_u32(out_buf, gamma.to_bits())
+		},
+		Easing::CubicBezier(x1, y1, x2, y2) => {
+			write_u8(out_buf, EASING_CUBIC_BEZIER)?;
+			for &value in &[x1, y1, x2, y2] {
+				write_u32(out_buf, value.to_bits())?;
+			}
+			Ok(())
+		},
+	}
+}
+
+fn write_channel<W>(out_buf: &mut W, channel: Channel) -> io::Result<()> 
+	where W: io::Write + ?Sized
+{
+	write_u8(out_buf, match channel {
+		Channel::Red => CHANNEL_RED,
+		Channel::Green => CHANNEL_GREEN,
+		Channel::Blue => CHANNEL_BLUE,
+	})
+}
+
+fn write_adjustment<W>(out_buf: &mut W, adjustment: Adjustment) 
+	-> io::Result<()> 
+	where W: io::Write + ?Sized
+{
+	match adjustment {
+		Adjustment::Brightness(amount) => {
+			write_u8(out_buf, ADJUST_BRIGHTNESS)?;
+			write_u32(out_buf, amount.to_bits())
+		},
+		Adjustment::Contrast(amount) => {
+			write_u8(out_buf, ADJUST_CONTRAST)?;
+			write_u32(out_buf, amount.to_bits())
+		},
+		Adjustment::HueRotate(degrees) => {
+			write_u8(out_buf, ADJUST_HUE_ROTATE)?;
+			write_u32(out_buf, degrees.to_bits())
+		},
+		Adjustment::Saturate(amount) => {
+			write_u8(out_buf, ADJUST_SATURATE)?;
+			write_u32(out_buf, amount.to_bits())
+		},
+		Adjustment::Gamma(gamma) => {
+			write_u8(out_buf, ADJUST_GAMMA)?;
+			write_u32(out_buf, gamma.to_bits())
+		},
+		Adjustment::Levels {black, white, gamma} => {
+			write_u8(out_buf, ADJUST_LEVELS)?;
+			out_buf.write_all(&[black, white])?;
+			write_u32(out_buf, gamma.to_bits())
+		},
+		Adjustment::SwapChannels(a, b) => {
+			write_u8(out_buf, ADJUST_SWAP_CHANNELS)?;
+			write_channel(out_buf, a)?;
+			write_channel(out_buf, b)
+		},
+	}
+}
+
+fn write_address<W>(out_buf: &mut W, address: Address) -> io::Result<()> 
+	where W: io::Write + ?Sized
+{
+	write_u16(out_buf, address.page)?;
+	out_buf.write_all(&[address.line, address.column])
+}
+
+fn write_direct_component<W, T>(
+	out_buf: &mut W, 
+	drc: &DirectReferenceComponent<T>) 
+	-> io::Result<()>
+	where W: io::Write + ?Sized, T: Copy + Into<u32>
+{
+	match *drc {
+		DirectReferenceComponent::Any => write_u8(out_buf, COMPONENT_ANY),
+		DirectReferenceComponent::Index(i) => {
+			write_u8(out_buf, COMPONENT_INDEX)?;
+			write_u32(out_buf, i.into())
+		},
+		DirectReferenceComponent::Named(ref name) => {
+			write_u8(out_buf, COMPONENT_NAMED)?;
+			write_str(out_buf, name)
+		},
+	}
+}
+
+fn write_component<W, T, O>(
+	out_buf: &mut W, 
+	rc: &ReferenceComponent<T, O>) 
+	-> io::Result<()>
+	where W: io::Write + ?Sized, T: Copy + Into<u32>, O: Copy + Into<i32>
+{
+	match *rc {
+		ReferenceComponent::All => write_u8(out_buf, COMPONENT_ALL),
+		ReferenceComponent::Any => write_u8(out_buf, COMPONENT_ANY),
+		ReferenceComponent::Index(i) => {
+			write_u8(out_buf, COMPONENT_INDEX)?;
+			write_u32(out_buf, i.into())
+		},
+		ReferenceComponent::Named(ref name) => {
+			write_u8(out_buf, COMPONENT_NAMED)?;
+			write_str(out_buf, name)
+		},
+		ReferenceComponent::Indirect(ref drc, offset) => {
+			write_u8(out_buf, COMPONENT_INDIRECT)?;
+			write_direct_component(out_buf, drc)?;
+			let offset: i32 = offset.into();
+			write_u32(out_buf, offset as u32)
+		},
+	}
+}
+
+fn write_reference<W>(out_buf: &mut W, reference: &Reference) 
+	-> io::Result<()>
+	where W: io::Write + ?Sized
+{
+	write_component(out_buf, &reference.page)?;
+	write_component(out_buf, &reference.line)?;
+	write_component(out_buf, &reference.column)
+}
+
+/// Writes a chunk with the given identifier, whose body is written by the 
+/// given function. The function is called twice: once to measure the body
+/// length, and once to write it.
+fn write_chunk<W, F>(out_buf: &mut W, id: [u8;4], mut body: F) 
+	-> Result<()>
+	where 
+		W: io::Write, 
+		F: FnMut(&mut io::Write) -> Result<()>
+{
+	let mut counter = ByteCounter::default();
+	body(&mut counter)?;
+
+	out_buf.write_all(&id)?;
+	write_u32(out_buf, counter.count() as u32)?;
+	body(out_buf)
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Decoding
+////////////////////////////////////////////////////////////////////////////////
+fn read_u8<R>(in_buf: &mut R) -> io::Result<u8> where R: io::Read {
+	let mut buf = [0u8; 1];
+	in_buf.read_exact(&mut buf)?;
+	Ok(buf[0])
+}
+
+fn read_u16<R>(in_buf: &mut R) -> io::Result<u16> where R: io::Read {
+	let mut buf = [0u8; 2];
+	in_buf.read_exact(&mut buf)?;
+	Ok(buf[0] as u16 | (buf[1] as u16) << 8)
+}
+
+fn read_u32<R>(in_buf: &mut R) -> io::Result<u32> where R: io::Read {
+	Ok(read_u16(in_buf)? as u32 | (read_u16(in_buf)? as u32) << 16)
+}
+
+fn read_u64<R>(in_buf: &mut R) -> io::Result<u64> where R: io::Read {
+	Ok(read_u32(in_buf)? as u64 | (read_u32(in_buf)? as u64) << 32)
+}
+
+fn read_opt_u64<R>(in_buf: &mut R) -> io::Result<Option<u64>> 
+	where R: io::Read
+{
+	match read_u8(in_buf)? {
+		0 => Ok(None),
+		_ => Ok(Some(read_u64(in_buf)?)),
+	}
+}
+
+fn read_f32<R>(in_buf: &mut R) -> io::Result<f32> where R: io::Read {
+	Ok(f32::from_bits(read_u32(in_buf)?))
+}
+
+/// Returns a `Parse` error for the tag byte just read from the buffer.
+fn invalid_tag<R>(in_buf: &OffsetReader<R>, expected: &str, tag: u8) -> Error
+	where R: io::Read
+{
+	parse_error(in_buf.offset() - 1, expected, format!("tag {}", tag))
+}
+
+fn read_str<R>(in_buf: &mut OffsetReader<R>) -> Result<String> 
+	where R: io::Read
+{
+	let offset = in_buf.offset();
+	let len = read_u16(in_buf)?;
+	let mut buf = vec![0u8; len as usize];
+	in_buf.read_exact(&mut buf)?;
+	String::from_utf8(buf)
+		.map_err(|_| parse_error(offset, "a UTF-8 string", "invalid UTF-8"))
+}
+
+fn read_opt_str<R>(in_buf: &mut OffsetReader<R>) -> Result<Option<String>> 
+	where R: io::Read
+{
+	match read_u8(in_buf)? {
+		0 => Ok(None),
+		_ => Ok(Some(read_str(in_buf)?)),
+	}
+}
+
+fn read_meta_type<R>(in_buf: &mut OffsetReader<R>) -> Result<MetaType> 
+	where R: io::Read
+{
+	Ok(match read_u8(in_buf)? {
+		META_STRING => MetaType::String,
+		META_INT => MetaType::Int,
+		META_COLOR => MetaType::Color,
+		META_BOOL => MetaType::Bool,
+		tag => return Err(invalid_tag(in_buf, "a metadata type", tag)),
+	})
+}
+
+fn read_meta_value<R>(in_buf: &mut OffsetReader<R>) -> Result<MetaValue> 
+	where R: io::Read
+{
+	Ok(match read_meta_type(in_buf)? {
+		MetaType::String => MetaValue::String(read_str(in_buf)?),
+		MetaType::Int => MetaValue::Int(read_u64(in_buf)? as i64),
+		MetaType::Color => MetaValue::Color(Color::new(
+			read_u8(in_buf)?,
+			read_u8(in_buf)?,
+			read_u8(in_buf)?)),
+		MetaType::Bool => MetaValue::Bool(read_u8(in_buf)? != 0),
+	})
+}
+
+fn read_easing<R>(in_buf: &mut OffsetReader<R>) -> Result<Easing> 
+	where R: io::Read
+{
+	Ok(match read_u8(in_buf)? {
+		EASING_LINEAR => Easing::Linear,
+		EASING_IN => Easing::EaseIn,
+		EASING_OUT => Easing::EaseOut,
+		EASING_IN_OUT => Easing::EaseInOut,
+		EASING_GAMMA => Easing::Gamma(read_f32(in_buf)?),
+		EASING_CUBIC_BEZIER => Easing::CubicBezier(
+			read_f32(in_buf)?,
+			read_f32(in_buf)?,
+			read_f32(in_buf)?,
+			read_f32(in_buf)?),
+		tag => return Err(invalid_tag(in_buf, "an easing", tag)),
+	})
+}
+
+fn read_channel<R>(in_buf: &mut OffsetReader<R>) -> Result<Channel> 
+	where R: io::Read
+{
+	Ok(match read_u8(in_buf)? {
+		CHANNEL_RED => Channel::Red,
+		CHANNEL_GREEN => Channel::Green,
+		CHANNEL_BLUE => Channel::Blue,
+		tag => return Err(invalid_tag(in_buf, "a channel", tag)),
+	})
+}
+
+fn read_adjustment<R>(in_buf: &mut OffsetReader<R>) -> Result<Adjustment> 
+	where R: io::Read
+{
+	Ok(match read_u8(in_buf)? {
+		ADJUST_BRIGHTNESS => Adjustment::Brightness(read_f32(in_buf)?),
+		ADJUST_CONTRAST => Adjustment::Contrast(read_f32(in_buf)?),
+		ADJUST_HUE_ROTATE => Adjustment::HueRotate(read_f32(in_buf)?),
+		ADJUST_SATURATE => Adjustment::Saturate(read_f32(in_buf)?),
+		ADJUST_GAMMA => Adjustment::Gamma(read_f32(in_buf)?),
+		ADJUST_LEVELS => Adjustment::Levels {
+			black: read_u8(in_buf)?,
+			white: read_u8(in_buf)?,
+			gamma: read_f32(in_buf)?,
+		},
+		ADJUST_SWAP_CHANNELS => Adjustment::SwapChannels(
+			read_channel(in_buf)?,
+			read_channel(in_buf)?),
+		tag => return Err(invalid_tag(in_buf, "an adjustment", tag)),
+	})
+}
+
+fn read_address<R>(in_buf: &mut R) -> io::Result<Address> where R: io::Read {
+	Ok(Address::new(read_u16(in_buf)?, read_u8(in_buf)?, read_u8(in_buf)?))
+}
+
+fn read_index<R, T>(in_buf: &mut OffsetReader<R>) -> Result<T> 
+	where R: io::Read, T: TryFrom<u32>
+{
+	let offset = in_buf.offset();
+	let index = read_u32(in_buf)?;
+	T::try_from(index)
+		.map_err(|_| parse_error(
+			offset, 
+			"a reference index in range", 
+			format!("{}", index)))
+}
+
+fn read_direct_component<R, T>(in_buf: &mut OffsetReader<R>) 
+	-> Result<DirectReferenceComponent<T>>
+	where R: io::Read, T: TryFrom<u32>
+{
+	match read_u8(in_buf)? {
+		COMPONENT_ANY => Ok(DirectReferenceComponent::Any),
+		COMPONENT_INDEX 
+			=> Ok(DirectReferenceComponent::Index(read_index(in_buf)?)),
+		COMPONENT_NAMED 
+			=> Ok(DirectReferenceComponent::Named(read_str(in_buf)?)),
+		tag => Err(invalid_tag(in_buf, "a reference component", tag)),
+	}
+}
+
+fn read_component<R, T, O>(in_buf: &mut OffsetReader<R>) 
+	-> Result<ReferenceComponent<T, O>>
+	where R: io::Read, T: TryFrom<u32>, O: TryFrom<i32>
+{
+	match read_u8(in_buf)? {
+		COMPONENT_ALL => Ok(ReferenceComponent::All),
+		COMPONENT_ANY => Ok(ReferenceComponent::Any),
+		COMPONENT_INDEX => Ok(ReferenceComponent::Index(read_index(in_buf)?)),
+		COMPONENT_NAMED => Ok(ReferenceComponent::Named(read_str(in_buf)?)),
+		COMPONENT_INDIRECT => {
+			let drc = read_direct_component(in_buf)?;
+			let position = in_buf.offset();
+			let value = read_u32(in_buf)? as i32;
+			let offset = O::try_from(value)
+				.map_err(|_| parse_error(
+					position, 
+					"a reference offset in range", 
+					format!("{}", value)))?;
+			Ok(ReferenceComponent::Indirect(drc, offset))
+		},
+		tag => Err(invalid_tag(in_buf, "a reference component", tag)),
+	}
+}
+
+fn read_reference<R>(in_buf: &mut OffsetReader<R>) -> Result<Reference> 
+	where R: io::Read
+{
+	Ok(Reference {
+		page: read_component(in_buf)?,
+		line: read_component(in_buf)?,
+		column: read_component(in_buf)?,
+	})
+}
+
+/// Returns the cell at the given address, creating an empty cell if there is
+/// none. Cells are inserted directly so that no format preparation is applied.
+fn cell_at(data: &mut Data, address: Address) -> Shared<Cell> {
+	if let Some(cell) = data.cell(address) {
+		return cell;
+	}
+	let cell = Shared::new(Cell::new(Expression::Empty));
+	data.insert_cell(address, cell.clone());
+	cell
+}
+
+fn read_source<R>(in_buf: &mut R, data: &mut Data) -> Result<Weak<Cell>>
+	where R: io::Read
+{
+	let address = read_address(in_buf)?;
+	Ok(Shared::downgrade(&cell_at(data, address)))
+}
+
+fn write_expression(
+	out_buf: &mut io::Write, 
+	expr: &Expression,
+	address_of: &Fn(&Weak<Cell>) -> Result<Address>)
+	-> Result<()>
+{
+	match *expr {
+		Expression::Empty => write_u8(out_buf, EXPR_EMPTY)?,
+
+		Expression::Color(color) => {
+			write_u8(out_buf, EXPR_COLOR)?;
+			out_buf.write_all(&[color.r(), color.g(), color.b()])?;
+		},
+
+		Expression::Blend {ref a, ref b, ratio, interpolation, easing} => {
+			write_u8(out_buf, EXPR_BLEND)?;
+			write_address(out_buf, address_of(a)?)?;
+			write_address(out_buf, address_of(b)?)?;
+			write_u32(out_buf, ratio.to_bits())?;
+			write_u8(out_buf, match interpolation {
+				Interpolation::Rgb => INTERPOLATION_RGB,
+				Interpolation::Lab => INTERPOLATION_LAB,
+				Interpolation::LinearRgb => INTERPOLATION_LINEAR_RGB,
+			})?;
+			write_easing(out_buf, easing)?;
+		},
+
+		Expression::Lighten {ref a, amount} => {
+			write_u8(out_buf, EXPR_LIGHTEN)?;
+			write_address(out_buf, address_of(a)?)?;
+			write_u32(out_buf, amount.to_bits())?;
+		},
+
+		Expression::Darken {ref a, amount} => {
+			write_u8(out_buf, EXPR_DARKEN)?;
+			write_address(out_buf, address_of(a)?)?;
+			write_u32(out_buf, amount.to_bits())?;
+		},
+
+		Expression::Complement {ref a} => {
+			write_u8(out_buf, EXPR_COMPLEMENT)?;
+			write_address(out_buf, address_of(a)?)?;
+		},
+
+		Expression::Cmyk(cmyk) => {
+			write_u8(out_buf, EXPR_CMYK)?;
+			for &value in &[cmyk.c, cmyk.m, cmyk.y, cmyk.k] {
+				write_u32(out_buf, value.to_bits())?;
+			}
+		},
+
+		Expression::Adjust {ref a, adjustment} => {
+			write_u8(out_buf, EXPR_ADJUST)?;
+			write_address(out_buf, address_of(a)?)?;
+			write_adjustment(out_buf, adjustment)?;
+		},
+
+		Expression::Frozen {color, ref recipe} => {
+			write_u8(out_buf, EXPR_FROZEN)?;
+			out_buf.write_all(&[color.r(), color.g(), color.b()])?;
+			write_expression(out_buf, recipe, address_of)?;
+		},
+	}
+	Ok(())
+}
+
+fn read_expression<R>(
+	chunk: &mut OffsetReader<R>, 
+	data: &mut Data, 
+	version: u16)
+	-> Result<Expression>
+	where R: io::Read
+{
+	let tag = read_u8(chunk)?;
+	read_tagged_expression(chunk, data, version, tag)
+}
+
+/// Reads the body of an expression whose tag has already been read.
+fn read_tagged_expression<R>(
+	chunk: &mut OffsetReader<R>, 
+	data: &mut Data, 
+	version: u16,
+	tag: u8)
+	-> Result<Expression>
+	where R: io::Read
+{
+	Ok(match tag {
+		EXPR_EMPTY => Expression::Empty,
+		EXPR_COLOR => Expression::Color(Color::new(
+			read_u8(chunk)?,
+			read_u8(chunk)?,
+			read_u8(chunk)?)),
+		EXPR_BLEND => Expression::Blend {
+			a: read_source(chunk, data)?,
+			b: read_source(chunk, data)?,
+			ratio: read_f32(chunk)?,
+			// Version 1 files only support RGB interpolation.
+			interpolation: if version < 2 {
+				Interpolation::Rgb
+			} else {
+				match read_u8(chunk)? {
+					INTERPOLATION_RGB => Interpolation::Rgb,
+					INTERPOLATION_LAB => Interpolation::Lab,
+					INTERPOLATION_LINEAR_RGB 
+						=> Interpolation::LinearRgb,
+					tag => return Err(invalid_tag(
+						chunk, 
+						"an interpolation", 
+						tag)),
+				}
+			},
+			// Version 4 added easing curves.
+			easing: if version < 4 {
+				Easing::Linear
+			} else {
+				read_easing(chunk)?
+			},
+		},
+		EXPR_LIGHTEN => Expression::Lighten {
+			a: read_source(chunk, data)?,
+			amount: read_f32(chunk)?,
+		},
+		EXPR_DARKEN => Expression::Darken {
+			a: read_source(chunk, data)?,
+			amount: read_f32(chunk)?,
+		},
+		EXPR_COMPLEMENT => Expression::Complement {
+			a: read_source(chunk, data)?,
+		},
+		EXPR_CMYK => Expression::Cmyk(Cmyk::new(
+			read_f32(chunk)?,
+			read_f32(chunk)?,
+			read_f32(chunk)?,
+			read_f32(chunk)?)),
+		EXPR_FROZEN => {
+			let color = Color::new(
+				read_u8(chunk)?,
+				read_u8(chunk)?,
+				read_u8(chunk)?);
+			// Frozen cells cannot be frozen again, so the recipe is never 
+			// itself frozen. Rejecting it also bounds the recursion.
+			let recipe_tag = read_u8(chunk)?;
+			if recipe_tag == EXPR_FROZEN {
+				return Err(invalid_tag(
+					chunk, 
+					"an unfrozen expression", 
+					recipe_tag));
+			}
+			Expression::Frozen {
+				color: color,
+				recipe: Box::new(
+					read_tagged_expression(chunk, data, version, recipe_tag)?),
+			}
+		},
+		EXPR_ADJUST => Expression::Adjust {
+			a: read_source(chunk, data)?,
+			adjustment: read_adjustment(chunk)?,
+		},
+		tag => return Err(
+			invalid_tag(chunk, "an expression", tag)),
+	})
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Native format
+////////////////////////////////////////////////////////////////////////////////
+/// Writes the palette data to the given buffer in the native format.
+pub fn write_native<W>(data: &Data, out_buf: &mut W) -> Result<()>
+	where W: io::Write
+{
+	// Write header.
+	out_buf.write_all(&NATIVE_MAGIC)?;
+	write_u16(out_buf, NATIVE_VERSION)?;
+
+	// Write configuration.
+	write_chunk(out_buf, CHUNK_CONFIG, |out| {
+		write_u16(out, data.maximum_page_count)?;
+		out.write_all(&[data.default_line_count, data.default_column_count])?;
+		Ok(())
+	})?;
+
+	// Write cells.
+	let addresses: HashMap<*const Cell, Address> = data.cells
+		.iter()
+		.map(|(address, cell)| (&**cell as *const Cell, address))
+		.collect();
+	let address_of = |source: &Weak<Cell>| -> Result<Address> {
+		source.upgrade()
+			.and_then(|cell| addresses.get(&(&*cell as *const Cell)).cloned())
+			.ok_or_else(|| format_error("unresolved source reference"))
+	};
+
+	write_chunk(out_buf, CHUNK_CELLS, |out| {
+		write_u32(out, data.cells.len() as u32)?;
+		for (address, cell) in data.cells.iter() {
+			write_address(out, address)?;
+			write_expression(out, &*cell.borrow(), &address_of)?;
+		}
+		Ok(())
+	})?;
+
+	// Write metadata schema.
+	write_chunk(out_buf, CHUNK_SCHEMA, |out| {
+		write_u32(out, data.schema.len() as u32)?;
+		for (key, meta_type) in data.schema.iter() {
+			write_str(out, key)?;
+			write_meta_type(out, meta_type)?;
+		}
+		Ok(())
+	})?;
+
+	// Write metadata.
+	write_chunk(out_buf, CHUNK_METADATA, |out| {
+		write_u32(out, data.metadata.len() as u32)?;
+		for (group, meta) in &data.metadata {
+			write_reference(out, group)?;
+			write_opt_str(out, meta.format_label.as_ref().map(|s| &s[..]))?;
+			write_opt_str(out, meta.name.as_ref().map(|s| &s[..]))?;
+			out.write_all(&[meta.line_count, meta.column_count])?;
+			write_u32(out, meta.tags.len() as u32)?;
+			for tag in &meta.tags {
+				write_str(out, tag)?;
+			}
+			write_opt_str(out, meta.notes.as_ref().map(|s| &s[..]))?;
+			write_opt_u64(out, meta.created)?;
+			write_opt_u64(out, meta.modified)?;
+			write_opt_str(out, meta.author.as_ref().map(|s| &s[..]))?;
+			write_opt_str(out, meta.source.as_ref()
+				.map(|path| path.to_string_lossy())
+				.as_ref()
+				.map(|s| &s[..]))?;
+			write_u32(out, meta.properties.len() as u32)?;
+			for (key, value) in &meta.properties {
+				write_str(out, key)?;
+				write_str(out, value)?;
+			}
+			write_u32(out, meta.values.len() as u32)?;
+			for (key, value) in &meta.values {
+				write_str(out, key)?;
+				write_meta_value(out, value)?;
+			}
+		}
+		Ok(())
+	})?;
+
+	// Write names.
+	write_chunk(out_buf, CHUNK_NAMES, |out| {
+		write_u32(out, data.names.len() as u32)?;
+		for (name, group) in &data.names {
+			write_str(out, name)?;
+			write_reference(out, group)?;
+		}
+		Ok(())
+	})?;
+
+	// Write ramp specs.
+	if !data.ramps.is_empty() {
+		write_chunk(out_buf, CHUNK_RAMPS, |out| {
+			write_u32(out, data.ramps.len() as u32)?;
+			for ramp in &data.ramps {
+				write_u32(out, ramp.anchors.len() as u32)?;
+				for &anchor in &ramp.anchors {
+					write_address(out, anchor)?;
+				}
+				write_u32(out, ramp.steps as u32)?;
+				write_u8(out, match ramp.interpolation {
+					Interpolation::Rgb => INTERPOLATION_RGB,
+					Interpolation::Lab => INTERPOLATION_LAB,
+					Interpolation::LinearRgb => INTERPOLATION_LINEAR_RGB,
+				})?;
+				write_address(out, ramp.location)?;
+				write_easing(out, ramp.easing)?;
+			}
+			Ok(())
+		})?;
+	}
+
+	// Write color cycles.
+	if !data.cycles.is_empty() {
+		write_chunk(out_buf, CHUNK_CYCLES, |out| {
+			write_u32(out, data.cycles.len() as u32)?;
+			for cycle in &data.cycles {
+				write_address(out, cycle.start)?;
+				write_address(out, cycle.end)?;
+				write_u32(out, cycle.rate.to_bits())?;
+				write_u8(out, match cycle.direction {
+					Direction::Forward => DIRECTION_FORWARD,
+					Direction::Reverse => DIRECTION_REVERSE,
+					Direction::PingPong => DIRECTION_PING_PONG,
+				})?;
+			}
+			Ok(())
+		})?;
+	}
+
+	// Write slot constraints.
+	if data.constraints.is_empty() { return Ok(()); }
+	write_chunk(out_buf, CHUNK_CONSTRAINTS, |out| {
+		write_u32(out, data.constraints.len() as u32)?;
+		for (&address, &constraint) in &data.constraints {
+			write_address(out, address)?;
+			write_u8(out, match constraint {
+				Constraint::Locked => CONSTRAINT_LOCKED,
+				Constraint::Protected => CONSTRAINT_PROTECTED,
+				Constraint::Reserved => CONSTRAINT_RESERVED,
+			})?;
+		}
+		Ok(())
+	})
+}
+
+
+/// Reads palette data in the native format from the given buffer, treating 
+/// malformed input as an error.
+pub fn read_native<R>(in_buf: &mut R) -> Result<Data>
+	where R: io::Read
+{
+	read_native_with(in_buf, &ParseOptions::default())
+}
+
+/// Reads palette data in the native format from the given buffer using the 
+/// given parse options. In lenient mode, the remainder of a malformed chunk is
+/// skipped, and truncated input ends the palette.
+pub fn read_native_with<R>(in_buf: &mut R, options: &ParseOptions) 
+	-> Result<Data>
+	where R: io::Read
+{
+	let mut in_buf = OffsetReader::new(in_buf);
+	let mut data: Data = Default::default();
+	read_chunks(&mut in_buf, &mut data, options).map_err(|e| match e {
+		Error::Io(ref e) if e.kind() == io::ErrorKind::UnexpectedEof
+			=> parse_error(in_buf.offset(), "more data", "end of file"),
+		e => e,
+	})?;
+	data.validate()?;
+	Ok(data)
+}
+
+/// Reads the header and chunks of a native file from the given buffer into 
+/// the given palette data.
+fn read_chunks<R>(
+	in_buf: &mut OffsetReader<R>, 
+	data: &mut Data, 
+	options: &ParseOptions) 
+	-> Result<()>
+	where R: io::Read
+{
+	// Read header.
+	let mut magic = [0u8; 4];
+	in_buf.read_exact(&mut magic)?;
+	if magic != NATIVE_MAGIC {
+		return Err(parse_error(
+			0, 
+			"RPAL magic number", 
+			describe_bytes(&magic)));
+	}
+	let version = read_u16(in_buf)?;
+	if version > NATIVE_VERSION {
+		return Err(parse_error(
+			4, 
+			format!("version {} or earlier", NATIVE_VERSION), 
+			format!("version {}", version)));
+	}
+
+	// Read chunks.
+	loop {
+		let mut id = [0u8; 4];
+		match in_buf.read_exact(&mut id) {
+			Ok(()) => (),
+			Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+			Err(e) => return Err(e.into()),
+		}
+		let len = read_u32(in_buf)?;
+		let start = in_buf.offset();
+		let chunk = &mut OffsetReader::starting_at(
+			in_buf.by_ref().take(len as u64), 
+			start);
+
+		if let Err(e) = read_chunk(chunk, id, data, version, options) {
+			options.recover(e)?;
+		}
+
+		// Skip any unread chunk data.
+		io::copy(chunk, &mut io::sink())?;
+	}
+	Ok(())
+}
+
+/// Reads the contents of the chunk with the given identifier into the given
+/// palette data. Unknown chunks are ignored.
+fn read_chunk<R>(
+	chunk: &mut OffsetReader<R>, 
+	id: [u8; 4], 
+	data: &mut Data, 
+	version: u16, 
+	options: &ParseOptions)
+	-> Result<()>
+	where R: io::Read
+{
+	match id {
+		CHUNK_CONFIG => {
+			data.maximum_page_count = read_u16(chunk)?;
+			data.default_line_count = read_u8(chunk)?;
+			data.default_column_count = read_u8(chunk)?;
+		},
+
+		CHUNK_CELLS => for _ in 0..read_u32(chunk)? {
+			if !options.admits(data.len(), chunk.offset())? { break; }
+			let address = read_address(chunk)?;
+			let expr = read_expression(chunk, data, version)?;
+			*cell_at(data, address).borrow_mut() = expr;
+		},
+
+		CHUNK_METADATA => for _ in 0..read_u32(chunk)? {
+			let group = read_reference(chunk)?;
+			let mut meta = MetaData {
+				format_label: read_opt_str(chunk)?,
+				name: read_opt_str(chunk)?,
+				line_count: read_u8(chunk)?,
+				column_count: read_u8(chunk)?,
+				.. Default::default()
+			};
+			// Version 3 added tags and notes.
+			if version >= 3 {
+				for _ in 0..read_u32(chunk)? {
+					meta.tags.push(read_str(chunk)?);
+				}
+				meta.notes = read_opt_str(chunk)?;
+			}
+			// Version 5 added timestamps and provenance.
+			if version >= 5 {
+				meta.created = read_opt_u64(chunk)?;
+				meta.modified = read_opt_u64(chunk)?;
+				meta.author = read_opt_str(chunk)?;
+				meta.source = read_opt_str(chunk)?.map(Into::into);
+				for _ in 0..read_u32(chunk)? {
+					let key = read_str(chunk)?;
+					let value = read_str(chunk)?;
+					meta.properties.insert(key, value);
+				}
+			}
+			// Version 6 added typed values.
+			if version >= 6 {
+				for _ in 0..read_u32(chunk)? {
+					let key = read_str(chunk)?;
+					let value = read_meta_value(chunk)?;
+					meta.values.insert(key, value);
+				}
+			}
+			data.metadata.insert(group, meta);
+		},
+
+		CHUNK_SCHEMA => for _ in 0..read_u32(chunk)? {
+			let offset = chunk.offset();
+			let key = read_str(chunk)?;
+			let meta_type = read_meta_type(chunk)?;
+			data.register_meta(key.clone(), meta_type)
+				.map_err(|_| parse_error(
+					offset, 
+					"a unique metadata key", 
+					format!("{:?}", key)))?;
+		},
+
+		CHUNK_NAMES => for _ in 0..read_u32(chunk)? {
+			let name = read_str(chunk)?;
+			let group = read_reference(chunk)?;
+			data.names.insert(name, group);
+		},
+
+		CHUNK_RAMPS => for _ in 0..read_u32(chunk)? {
+			let mut anchors = Vec::new();
+			for _ in 0..read_u32(chunk)? {
+				anchors.push(read_address(chunk)?);
+			}
+			let steps = read_u32(chunk)? as usize;
+			let interpolation = match read_u8(chunk)? {
+				INTERPOLATION_RGB => Interpolation::Rgb,
+				INTERPOLATION_LAB => Interpolation::Lab,
+				INTERPOLATION_LINEAR_RGB => Interpolation::LinearRgb,
+				tag => return Err(
+					invalid_tag(chunk, "an interpolation", tag)),
+			};
+			let location = read_address(chunk)?;
+			let easing = if version < 4 {
+				Easing::Linear
+			} else {
+				read_easing(chunk)?
+			};
+			data.ramps.push(RampSpec::new(anchors, steps, location)
+				.interpolation(interpolation)
+				.easing(easing));
+		},
+
+		CHUNK_CYCLES => for _ in 0..read_u32(chunk)? {
+			let start = read_address(chunk)?;
+			let end = read_address(chunk)?;
+			let rate = read_f32(chunk)?;
+			let direction = match read_u8(chunk)? {
+				DIRECTION_FORWARD => Direction::Forward,
+				DIRECTION_REVERSE => Direction::Reverse,
+				DIRECTION_PING_PONG => Direction::PingPong,
+				tag => return Err(
+					invalid_tag(chunk, "a cycle direction", tag)),
+			};
+			data.cycles.push(Cycle::new(start, end, rate)
+				.direction(direction));
+		},
+
+		CHUNK_CONSTRAINTS => for _ in 0..read_u32(chunk)? {
+			let address = read_address(chunk)?;
+			let constraint = match read_u8(chunk)? {
+				CONSTRAINT_LOCKED => Constraint::Locked,
+				CONSTRAINT_PROTECTED => Constraint::Protected,
+				CONSTRAINT_RESERVED => Constraint::Reserved,
+				tag => return Err(
+					invalid_tag(chunk, "a constraint", tag)),
+			};
+			data.constraints.insert(address, constraint);
+		},
+
+		_ => (),
+	}
+	Ok(())
+}
@@ -0,0 +1,68 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides components for interacting with the Paint.NET palette format.
+//!
+//! Paint.NET palettes are plain text, with one `AARRGGBB` hexadecimal color per
+//! line and `;` comments. The alpha channel is discarded when reading and 
+//! written as opaque. Paint.NET itself only loads the first 96 colors.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::Palette;
+use address::Reference;
+use format::{Format, ParseOptions};
+use format::hex::read_lines;
+use result::Result;
+
+// Standard imports.
+use std::io;
+
+
+/// Writes the palette to the given buffer.
+pub fn write_palette<W>(palette: &Palette, out_buf: &mut W) -> Result<()>
+	where W: io::Write
+{
+	write!(out_buf, "; paint.net Palette File\r\n")?;
+	if let Some(name) = palette.data.name(&Reference::all()) {
+		write!(out_buf, "; {}\r\n", name)?;
+	}
+	for (_, color) in palette.data.colors() {
+		write!(out_buf, "FF{:02X}{:02X}{:02X}\r\n", 
+			color.r(), 
+			color.g(), 
+			color.b())?;
+	}
+	Ok(())
+}
+
+
+/// Reads a palette from the given buffer.
+pub fn read_palette<R>(in_buf: &mut R, options: &ParseOptions) 
+	-> Result<Palette>
+	where R: io::Read
+{
+	read_lines(in_buf, Format::PaintNet, options)
+}
@@ -0,0 +1,361 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides components for reading and writing palettes as indexed PNG images.
+//!
+//! Palettes are read from the `PLTE` chunk of an indexed PNG. Palettes are 
+//! written as an indexed image one pixel high, with one pixel for each color. 
+//! Image data is stored uncompressed, so no compression library is required.
+//! Transparency from the `tRNS` chunk is stored with each color under the 
+//! `meta::ALPHA_KEY` key, and is written back when any color is translucent.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::Palette;
+use address::{Address, Reference};
+use format::{
+	Format,
+	OffsetReader,
+	ParseOptions,
+	describe_bytes,
+	parse_error,
+};
+use meta::{MetaType, ALPHA_KEY};
+use result::{Error, Result};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::io;
+use std::io::Read;
+
+
+/// The PNG file signature.
+pub(crate) const PNG_SIGNATURE: [u8;8] = [
+	0x89, 0x50, 0x4E, 0x47, 
+	0x0D, 0x0A, 0x1A, 0x0A
+];
+
+/// The maximum number of entries in a PNG palette.
+const PNG_PALETTE_LIMIT: usize = 256;
+
+/// The maximum length of a stored deflate block.
+const DEFLATE_BLOCK_LIMIT: usize = 0xFFFF;
+
+
+/// Returns a `Format` error with the given reason.
+fn format_error(reason: &str) -> Error {
+	Error::Format {format: Format::Png.name(), reason: reason.into()}
+}
+
+/// Returns the CRC-32 of the given bytes.
+fn crc32(bytes: &[u8]) -> u32 {
+	let mut crc = 0xFFFF_FFFFu32;
+	for &byte in bytes {
+		crc ^= byte as u32;
+		for _ in 0..8 {
+			crc = if crc & 1 == 1 { 
+				(crc >> 1) ^ 0xEDB8_8320 
+			} else { 
+				crc >> 1 
+			};
+		}
+	}
+	!crc
+}
+
+/// Returns the Adler-32 checksum of the given bytes.
+fn adler32(bytes: &[u8]) -> u32 {
+	let (mut a, mut b) = (1u32, 0u32);
+	for &byte in bytes {
+		a = (a + byte as u32) % 65_521;
+		b = (b + a) % 65_521;
+	}
+	b << 16 | a
+}
+
+/// Returns the given bytes wrapped in a zlib stream of stored deflate blocks.
+pub(crate) fn zlib_stored(bytes: &[u8]) -> Vec<u8> {
+	let mut out = vec![0x78, 0x01];
+	let mut blocks = bytes.chunks(DEFLATE_BLOCK_LIMIT).peekable();
+	if blocks.peek().is_none() {
+		out.extend_from_slice(&[0x01, 0x00, 0x00, 0xFF, 0xFF]);
+	}
+	while let Some(block) = blocks.next() {
+		out.push(if blocks.peek().is_none() { 0x01 } else { 0x00 });
+		let len = block.len() as u16;
+		out.extend_from_slice(&[len as u8, (len >> 8) as u8]);
+		out.extend_from_slice(&[!len as u8, (!len >> 8) as u8]);
+		out.extend_from_slice(block);
+	}
+	let checksum = adler32(bytes);
+	out.extend_from_slice(&[
+		(checksum >> 24) as u8, 
+		(checksum >> 16) as u8, 
+		(checksum >> 8) as u8, 
+		checksum as u8]);
+	out
+}
+
+/// Writes a PNG chunk with the given type and body.
+pub(crate) fn write_chunk<W>(out_buf: &mut W, chunk_type: &[u8;4], body: &[u8]) 
+	-> io::Result<()>
+	where W: io::Write
+{
+	let len = body.len() as u32;
+	out_buf.write_all(&[
+		(len >> 24) as u8, (len >> 16) as u8, (len >> 8) as u8, len as u8])?;
+
+	let mut crc_data = chunk_type.to_vec();
+	crc_data.extend_from_slice(body);
+	out_buf.write_all(&crc_data)?;
+
+	let crc = crc32(&crc_data);
+	out_buf.write_all(&[
+		(crc >> 24) as u8, (crc >> 16) as u8, (crc >> 8) as u8, crc as u8])
+}
+
+/// Returns the colors of the palette, or an error if there are too many to 
+/// store in a PNG palette.
+fn palette_colors(palette: &Palette) -> Result<Vec<Color>> {
+	let colors: Vec<Color> = palette.data.colors().map(|(_, c)| c).collect();
+	if colors.len() > PNG_PALETTE_LIMIT {
+		return Err(format_error("too many colors for PNG palette"));
+	}
+	Ok(colors)
+}
+
+
+/// Writes the palette's colors to the given buffer as a `PLTE` chunk, 
+/// suitable for embedding in a PNG file.
+pub fn write_plte<W>(palette: &Palette, out_buf: &mut W) -> Result<()>
+	where W: io::Write
+{
+	let mut body = Vec::new();
+	for color in palette_colors(palette)? {
+		body.extend_from_slice(&[color.r(), color.g(), color.b()]);
+	}
+	write_chunk(out_buf, b"PLTE", &body)?;
+	Ok(())
+}
+
+
+/// Writes the palette to the given buffer as an indexed PNG image with one 
+/// pixel for each color.
+pub fn write_palette<W>(palette: &Palette, out_buf: &mut W) -> Result<()>
+	where W: io::Write
+{
+	let count = palette_colors(palette)?.len();
+	if count == 0 {
+		return Err(format_error("cannot write empty palette as PNG"));
+	}
+
+	out_buf.write_all(&PNG_SIGNATURE)?;
+
+	// Write header: width, height, bit depth 8, color type 3 (indexed).
+	let width = count as u32;
+	write_chunk(out_buf, b"IHDR", &[
+		(width >> 24) as u8, (width >> 16) as u8, (width >> 8) as u8, 
+		width as u8,
+		0, 0, 0, 1,
+		8, 3, 0, 0, 0,
+	])?;
+
+	write_plte(palette, out_buf)?;
+
+	// Write transparency, omitting the trailing opaque entries.
+	let mut alphas: Vec<u8> = palette.data.colors()
+		.map(|(address, _)| palette.data
+			.get_meta::<i64>(&Reference::from(address), ALPHA_KEY)
+			.map_or(0xFF, |alpha| alpha.max(0).min(0xFF) as u8))
+		.collect();
+	while alphas.last() == Some(&0xFF) { alphas.pop(); }
+	if !alphas.is_empty() {
+		write_chunk(out_buf, b"tRNS", &alphas)?;
+	}
+
+	// Write image data: a single scanline with no filter.
+	let mut scanline = vec![0u8];
+	scanline.extend((0..count).map(|i| i as u8));
+	write_chunk(out_buf, b"IDAT", &zlib_stored(&scanline))?;
+
+	write_chunk(out_buf, b"IEND", &[])?;
+	Ok(())
+}
+
+
+/// Reads a palette from the `PLTE` chunk of the PNG image in the given buffer.
+pub fn read_palette<R>(in_buf: &mut R, options: &ParseOptions) 
+	-> Result<Palette>
+	where R: io::Read
+{
+	let mut in_buf = OffsetReader::new(in_buf);
+	let mut palette = Palette::new("", Format::Png, true);
+	let result = read_colors(&mut in_buf, &mut palette, options);
+	options.finish(in_buf.offset(), palette, result)
+}
+
+/// Reads a chunk header from the given buffer, returning the offset of the 
+/// chunk, its type, and the length of its body.
+fn read_chunk_header<R>(in_buf: &mut OffsetReader<R>) 
+	-> Result<(u64, [u8;4], u64)>
+	where R: io::Read
+{
+	let offset = in_buf.offset();
+	let mut header = [0u8; 8];
+	in_buf.read_exact(&mut header)?;
+	let len = (header[0] as u64) << 24 
+		| (header[1] as u64) << 16 
+		| (header[2] as u64) << 8 
+		| header[3] as u64;
+	let mut chunk_type = [0u8; 4];
+	chunk_type.copy_from_slice(&header[4..]);
+	Ok((offset, chunk_type, len))
+}
+
+/// Reads the body of a chunk with the given type and length from the given 
+/// buffer, checking the checksum which follows it. In lenient mode, the 
+/// bytes of a truncated body are kept.
+fn read_chunk_body<R>(
+	in_buf: &mut OffsetReader<R>,
+	chunk_type: &[u8;4],
+	len: u64,
+	description: &str,
+	options: &ParseOptions)
+	-> Result<Vec<u8>>
+	where R: io::Read
+{
+	let mut body = Vec::new();
+	in_buf.by_ref().take(len + 4).read_to_end(&mut body)?;
+	if body.len() < len as usize + 4 {
+		options.recover(parse_error(
+			in_buf.offset(), 
+			format!("{} bytes of {} chunk", len + 4, description), 
+			format!("{} bytes", body.len())))?;
+		body.truncate(len as usize);
+	} else {
+		let stored = body.split_off(len as usize);
+		let stored = (stored[0] as u32) << 24 
+			| (stored[1] as u32) << 16 
+			| (stored[2] as u32) << 8 
+			| stored[3] as u32;
+		let mut crc_data = chunk_type.to_vec();
+		crc_data.extend_from_slice(&body);
+		let crc = crc32(&crc_data);
+		if stored != crc {
+			options.recover(parse_error(
+				in_buf.offset() - 4, 
+				format!("checksum {:08X}", crc), 
+				format!("checksum {:08X}", stored)))?;
+		}
+	}
+	Ok(body)
+}
+
+/// Reads the `PLTE` chunk from the given buffer into the palette, along with
+/// the `tRNS` chunk if one follows it.
+fn read_colors<R>(
+	in_buf: &mut OffsetReader<R>,
+	palette: &mut Palette,
+	options: &ParseOptions)
+	-> Result<()>
+	where R: io::Read
+{
+	let mut signature = [0u8; 8];
+	in_buf.read_exact(&mut signature)?;
+	if signature != PNG_SIGNATURE {
+		return Err(parse_error(
+			0, 
+			"PNG signature", 
+			describe_bytes(&signature)));
+	}
+
+	let len = loop {
+		let (offset, chunk_type, len) = read_chunk_header(in_buf)?;
+		match &chunk_type {
+			b"PLTE" => break len,
+			b"IEND" => return Err(parse_error(
+				offset, 
+				"PLTE chunk", 
+				"IEND chunk")),
+			_ => {
+				let end = in_buf.offset() + len + 4; // Include CRC.
+				in_buf.skip_to(end)?;
+			},
+		}
+	};
+
+	// In lenient mode, the entries of a truncated chunk are kept.
+	let offset = in_buf.offset();
+	let body = read_chunk_body(in_buf, b"PLTE", len, "palette", options)?;
+	if body.len() % 3 != 0 {
+		options.recover(parse_error(
+			offset, 
+			"a multiple of 3 bytes of palette data", 
+			format!("{} bytes", body.len())))?;
+	}
+
+	let mut addresses: Vec<Address> = Vec::new();
+	for (index, entry) in body.chunks(3).enumerate() {
+		if entry.len() < 3 { break; }
+		let offset = offset + (index * 3) as u64;
+		if !options.admits(palette.data.len(), offset)? {
+			break;
+		}
+		addresses.push(palette.data
+			.add_color(Color::new(entry[0], entry[1], entry[2]))?);
+	}
+
+	// Transparency follows the palette and precedes the image data.
+	loop {
+		let (_, chunk_type, len) = read_chunk_header(in_buf)?;
+		match &chunk_type {
+			b"tRNS" => {
+				let body = read_chunk_body(
+					in_buf, 
+					b"tRNS", 
+					len, 
+					"transparency", 
+					options)?;
+				for (&address, &alpha) in addresses.iter().zip(&body) {
+					if alpha == 0xFF { continue; }
+					palette.data.register_meta(ALPHA_KEY, MetaType::Int)?;
+					palette.data.set_meta(
+						Reference::from(address), 
+						ALPHA_KEY, 
+						alpha as i64)?;
+				}
+				break;
+			},
+			b"IDAT" | b"IEND" => break,
+			_ => {
+				let end = in_buf.offset() + len + 4; // Include CRC.
+				in_buf.skip_to(end)?;
+			},
+		}
+	}
+	Ok(())
+}
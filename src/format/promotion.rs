@@ -0,0 +1,125 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides components for interacting with the Pro Motion NG palette format.
+//!
+//! Pro Motion palette files are a raw table of 256 RGB colors, with no header
+//! or color count. As the files share the `.pal` extension with the RIFF and
+//! JASC-PAL formats, they are recognized by their size.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::Palette;
+use format::{Format, OffsetReader, ParseOptions, parse_error};
+use result::{Error, Result};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::io;
+use std::io::Read;
+
+
+/// The number of entries in a Pro Motion color table.
+const PRO_MOTION_TABLE_SIZE: usize = 256;
+
+/// The size of a Pro Motion palette file in bytes.
+pub const PRO_MOTION_FILE_SIZE: usize = PRO_MOTION_TABLE_SIZE * 3;
+
+
+/// Returns a `Format` error with the given reason.
+fn format_error(reason: &str) -> Error {
+	Error::Format {format: Format::ProMotion.name(), reason: reason.into()}
+}
+
+
+/// Writes the palette to the given buffer. Unused entries are written as 
+/// black.
+pub fn write_palette<W>(palette: &Palette, out_buf: &mut W) -> Result<()>
+	where W: io::Write
+{
+	let colors: Vec<Color> = palette.data.colors().map(|(_, c)| c).collect();
+	if colors.len() > PRO_MOTION_TABLE_SIZE {
+		return Err(format_error("too many colors for Pro Motion palette"));
+	}
+
+	let mut table = [0u8; PRO_MOTION_FILE_SIZE];
+	for (entry, color) in table.chunks_mut(3).zip(colors.iter()) {
+		entry.copy_from_slice(&[color.r(), color.g(), color.b()]);
+	}
+	out_buf.write_all(&table)?;
+	Ok(())
+}
+
+
+/// Reads a palette from the given buffer.
+pub fn read_palette<R>(in_buf: &mut R, options: &ParseOptions) 
+	-> Result<Palette>
+	where R: io::Read
+{
+	let mut in_buf = OffsetReader::new(in_buf);
+	let mut palette = Palette::new("", Format::ProMotion, true);
+	let result = read_colors(&mut in_buf, &mut palette, options);
+	options.finish(in_buf.offset(), palette, result)
+}
+
+/// Reads the color table from the given buffer into the palette.
+fn read_colors<R>(
+	in_buf: &mut OffsetReader<R>,
+	palette: &mut Palette,
+	options: &ParseOptions)
+	-> Result<()>
+	where R: io::Read
+{
+	// In lenient mode, the entries of a truncated table are kept and trailing
+	// data is ignored.
+	let mut table = Vec::with_capacity(PRO_MOTION_FILE_SIZE);
+	in_buf.by_ref()
+		.take(PRO_MOTION_FILE_SIZE as u64)
+		.read_to_end(&mut table)?;
+	if table.len() < PRO_MOTION_FILE_SIZE {
+		options.recover(parse_error(
+			in_buf.offset(), 
+			format!("{} bytes of color table", PRO_MOTION_FILE_SIZE), 
+			format!("{} bytes", table.len())))?;
+	} else if in_buf.by_ref().take(1).read_to_end(&mut Vec::new())? > 0 {
+		options.recover(parse_error(
+			in_buf.offset() - 1, 
+			"end of file", 
+			"trailing data"))?;
+	}
+
+	for (index, entry) in table.chunks(3).enumerate() {
+		if entry.len() < 3 { break; }
+		let offset = (index * 3) as u64;
+		if !options.admits(palette.data.len(), offset)? {
+			break;
+		}
+		palette.data
+			.add_color(Color::new(entry[0], entry[1], entry[2]))?;
+	}
+	Ok(())
+}
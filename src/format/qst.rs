@@ -0,0 +1,667 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//!
+//! Provides components for extracting palettes from Zelda Classic quest files.
+//!
+//! Most Zelda Classic palettes live inside `.qst` quest files rather than 
+//! standalone ZPL files. A quest begins with an identifier string, followed by
+//! a sequence of sections. Each section has a four byte identifier, a version,
+//! a compatibility version, and a length, all little-endian except the 
+//! identifier. The palette is held in the `CSET` section, whose contents are 
+//! the same as a ZPL file.
+//!
+//! Quests are stored as Allegro packfiles: the data may be compressed with 
+//! LZSS and encrypted with a password. Quests saved by Zelda Classic are also
+//! wrapped in Zelda Classic's own encoding, a keyed byte stream with a 
+//! trailing checksum, and encrypted with Zelda Classic's packfile password. 
+//! Both are removed when reading and restored when writing.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::Palette;
+use format::{ParseOptions, describe_bytes, parse_error};
+use format::zpl;
+use result::{Error, Result};
+
+// Standard imports.
+use std::io;
+
+
+/// The Allegro packfile magic number of compressed data.
+const PACK_MAGIC: u32 = 0x736C_6821; // "slh!"
+
+/// The Allegro packfile magic number of uncompressed data.
+const NOPACK_MAGIC: u32 = 0x736C_682E; // "slh."
+
+/// The packfile password of quests saved by Zelda Classic.
+pub const QUEST_PASSWORD: &'static [u8] = b"longtan";
+
+/// The identifier string of quests wrapped in the Zelda Classic encoding.
+const ENCODING_ID: &'static [u8] = b"Zelda Classic Quest File";
+
+/// The key masks of the Zelda Classic encoding methods, oldest first.
+const ENCODING_MASKS: [u32;5] = [
+	0x4C35_8938, 0x91B2_A2D1, 0x4A7C_1B87, 0xF939_41E6, 0xFD09_5E94
+];
+
+/// The low word increments of the Zelda Classic encoding generator.
+const ENCODING_P: [u16;5] = [0x62E9, 0x7D14, 0x1A82, 0x02BB, 0xE09C];
+
+/// The high word increments of the Zelda Classic encoding generator.
+const ENCODING_Q: [u16;5] = [0x3619, 0xA26B, 0xF03C, 0x7B12, 0x4E8F];
+
+/// The identifier string of quests with a section table.
+const QUEST_ID: &'static [u8] = b"AG ZC Enhanced Quest File\n";
+
+/// The identifier string of quests which predate the section table.
+const OLD_QUEST_ID: &'static [u8] = b"AG Zelda Classic Quest File\n";
+
+/// The size of the quest identifier string field.
+const QUEST_ID_SIZE: usize = 31;
+
+/// The size of a section header.
+const SECTION_HEADER_SIZE: usize = 12;
+
+/// The identifier of the palette section.
+pub const CSET_SECTION: [u8;4] = [0x43, 0x53, 0x45, 0x54]; // "CSET"
+
+/// The palette section version written by the ZPL format.
+const CSET_VERSION: (u16, u16) = (4, 1);
+
+/// The size of the LZSS window.
+const LZSS_WINDOW: usize = 4096;
+
+/// The maximum length of an LZSS match.
+const LZSS_MAX_MATCH: usize = 18;
+
+/// The minimum length of an LZSS match, less one.
+const LZSS_THRESHOLD: usize = 2;
+
+
+/// Returns a `Format` error with the given reason.
+fn format_error(reason: &str) -> Error {
+	Error::Format {format: "QST", reason: reason.into()}
+}
+
+/// Returns the little-endian u16 at the start of the given bytes.
+fn u16_le(bytes: &[u8]) -> u16 {
+	bytes[0] as u16 | (bytes[1] as u16) << 8
+}
+
+/// Returns the little-endian u32 at the start of the given bytes.
+fn u32_le(bytes: &[u8]) -> u32 {
+	u16_le(bytes) as u32 | (u16_le(&bytes[2..]) as u32) << 16
+}
+
+/// Returns the big-endian u32 at the start of the given bytes.
+fn u32_be(bytes: &[u8]) -> u32 {
+	(bytes[0] as u32) << 24 
+		| (bytes[1] as u32) << 16 
+		| (bytes[2] as u32) << 8 
+		| bytes[3] as u32
+}
+
+/// Returns the big-endian bytes of the given u32.
+fn u32_be_bytes(value: u32) -> [u8;4] {
+	[(value >> 24) as u8, (value >> 16) as u8, (value >> 8) as u8, value as u8]
+}
+
+/// Returns the packfile magic number as stored under the given password. 
+/// Allegro masks the magic number with a value derived from the password, 
+/// rather than encrypting it with the data.
+fn encrypt_id(id: u32, password: &[u8]) -> u32 {
+	if password.is_empty() { return id; }
+	// Allegro holds passwords as (signed) `char`s.
+	let key = |byte: u8| byte as i8 as i32 as u32;
+	let mut mask = 0u32;
+	for (i, &byte) in password.iter().enumerate() {
+		mask ^= key(byte) << ((i & 3) * 8);
+	}
+	for (i, &byte) in password.iter().cycle().take(4).enumerate() {
+		mask ^= key(byte) << (24 - i * 8);
+	}
+	id ^ mask ^ 42
+}
+
+/// Applies the given Allegro packfile password to the bytes following the 
+/// packfile magic number. As the password is applied with exclusive-or, this 
+/// both encrypts and decrypts.
+fn apply_password(bytes: &mut [u8], password: &[u8]) {
+	if password.is_empty() { return; }
+	for (byte, key) in bytes.iter_mut().zip(password.iter().cycle()) {
+		*byte ^= *key;
+	}
+}
+
+/// Decompresses Allegro LZSS data.
+fn unpack(packed: &[u8]) -> Vec<u8> {
+	let mut window = [0u8; LZSS_WINDOW];
+	let mut position = LZSS_WINDOW - LZSS_MAX_MATCH;
+	let mut unpacked = Vec::with_capacity(packed.len() * 2);
+	let mut bytes = packed.iter().cloned();
+
+	'decode: while let Some(flags) = bytes.next() {
+		for bit in 0..8 {
+			// Set flag bits mark literal bytes. Clear flag bits mark matches 
+			// within the window.
+			let (start, len) = if flags & (1 << bit) != 0 {
+				match bytes.next() {
+					Some(byte) => {
+						window[position] = byte;
+						(position, 1)
+					},
+					None => break 'decode,
+				}
+			} else {
+				match (bytes.next(), bytes.next()) {
+					(Some(low), Some(high)) => (
+						low as usize | (high as usize & 0xF0) << 4,
+						(high as usize & 0x0F) + LZSS_THRESHOLD + 1),
+					_ => break 'decode,
+				}
+			};
+
+			for i in 0..len {
+				let byte = window[(start + i) % LZSS_WINDOW];
+				unpacked.push(byte);
+				window[position] = byte;
+				position = (position + 1) % LZSS_WINDOW;
+			}
+		}
+	}
+	unpacked
+}
+
+/// Compresses data as Allegro LZSS. Every byte is stored as a literal, which 
+/// any LZSS reader accepts, at the cost of a slightly larger output.
+fn pack(data: &[u8]) -> Vec<u8> {
+	let mut packed = Vec::with_capacity(data.len() + data.len() / 8 + 1);
+	for group in data.chunks(8) {
+		packed.push(0xFF);
+		packed.extend_from_slice(group);
+	}
+	packed
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Encoding
+////////////////////////////////////////////////////////////////////////////////
+/// The Zelda Classic encoding wrapped around a quest packfile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Encoding {
+	/// The encoding method, counting from zero for the oldest.
+	pub method: usize,
+	/// The key seeding the encoding.
+	pub key: u32,
+}
+
+
+impl Encoding {
+	/// Returns the encoding used by current versions of Zelda Classic, seeded
+	/// with the given key.
+	pub fn new(key: u32) -> Encoding {
+		Encoding {method: ENCODING_MASKS.len() - 1, key: key}
+	}
+
+	/// Encodes or decodes the given data. Returns the checksum bytes of the 
+	/// decoded data.
+	fn apply(&self, data: &mut [u8], decode: bool) -> [u8;4] {
+		let mut rng = EncodingRng {seed: self.key as i32, method: self.method};
+		let mut sum: i16 = 0;
+		let mut mix: i16 = 0;
+		let mut r = 0;
+		for (i, byte) in data.iter_mut().enumerate() {
+			if !decode { 
+				sum = sum.wrapping_add(*byte as i16);
+				mix = checksum_mix(mix, *byte);
+			}
+			// Even bytes are masked with a new random value, and odd bytes 
+			// are offset by the same value.
+			*byte = match (i % 2 == 0, decode) {
+				(true, _)      => { r = rng.next(); *byte ^ r as u8 },
+				(false, false) => byte.wrapping_add(r as u8),
+				(false, true)  => byte.wrapping_sub(r as u8),
+			};
+			if decode { 
+				sum = sum.wrapping_add(*byte as i16);
+				mix = checksum_mix(mix, *byte);
+			}
+		}
+
+		let r = rng.next();
+		let sum = sum as u16 ^ r as u16;
+		let mix = (mix as u16).wrapping_add(r as u16);
+		[(sum >> 8) as u8, sum as u8, (mix >> 8) as u8, mix as u8]
+	}
+
+	/// Wraps the given packfile in the encoding.
+	fn encode(&self, mut packfile: Vec<u8>) -> Vec<u8> {
+		let checksum = self.apply(&mut packfile, false);
+		let mut bytes = ENCODING_ID.to_vec();
+		bytes.extend_from_slice(
+			&u32_be_bytes(self.key ^ ENCODING_MASKS[self.method]));
+		bytes.extend_from_slice(&packfile);
+		bytes.extend_from_slice(&checksum);
+		bytes
+	}
+
+	/// Removes the encoding from the given bytes, which must start with the
+	/// encoding identifier. Like Zelda Classic, each method is tried, newest 
+	/// first, until one produces a matching checksum.
+	fn decode(bytes: &[u8]) -> Result<(Encoding, Vec<u8>)> {
+		let body = &bytes[ENCODING_ID.len()..];
+		if body.len() < 8 {
+			return Err(parse_error(
+				bytes.len() as u64, 
+				"an encoding key and checksum", 
+				"end of file"));
+		}
+		let stored_key = u32_be(body);
+		let (data, checksum) = body[4..].split_at(body.len() - 8);
+
+		for method in (0..ENCODING_MASKS.len()).rev() {
+			let encoding = Encoding {
+				method: method,
+				key: stored_key ^ ENCODING_MASKS[method],
+			};
+			let mut decoded = data.to_vec();
+			if encoding.apply(&mut decoded, true)[..] == checksum[..] {
+				return Ok((encoding, decoded));
+			}
+		}
+		Err(parse_error(
+			(bytes.len() - checksum.len()) as u64,
+			"a quest encoding checksum", 
+			describe_bytes(checksum)))
+	}
+}
+
+/// Returns the rotating checksum of the Zelda Classic encoding, updated with
+/// the given byte. The shifts mirror Zelda Classic's use of a signed `short`.
+fn checksum_mix(mix: i16, byte: u8) -> i16 {
+	let mix = mix as i32;
+	((mix << 4) + (mix >> 12) + byte as i32) as i16
+}
+
+
+/// The pseudorandom generator of the Zelda Classic encoding. The arithmetic
+/// mirrors Zelda Classic's use of signed 8 and 16 bit registers.
+struct EncodingRng {
+	/// The generator state.
+	seed: i32,
+	/// The encoding method.
+	method: usize,
+}
+
+impl EncodingRng {
+	/// Advances the generator, returning the next value.
+	fn next(&mut self) -> i32 {
+		let seed = self.seed;
+		let bx = (seed >> 8) as i16;
+		let cx = ((seed & 0xFF) << 8) as i16;
+		let c = ((seed >> 24) as i8 >> 7) as i32;
+		let d = (bx >> 15) as i32;
+		let bx = ((bx as i32) << 1 | c) as i16;
+		let cx = ((cx as i32) << 1 | d) as i16;
+		let cx = cx
+			.wrapping_add((seed & 0xFFFF) as i16)
+			.wrapping_add(ENCODING_P[self.method] as i16);
+		let bx = bx
+			.wrapping_add(((seed >> 16) + c) as i16)
+			.wrapping_add((ENCODING_Q[self.method] as i32 + d) as i16);
+		self.seed = ((bx as i32) << 16).wrapping_add(cx as i32);
+		((cx as i32) << 16).wrapping_add(bx as i32)
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Packing
+////////////////////////////////////////////////////////////////////////////////
+/// The packfile encoding of a quest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Packing {
+	/// The sections are stored without a packfile header.
+	None,
+	/// The sections are stored in an uncompressed packfile.
+	Stored,
+	/// The sections are stored in an LZSS compressed packfile.
+	Compressed,
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Section
+////////////////////////////////////////////////////////////////////////////////
+/// A section of a quest file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Section {
+	/// The four byte section identifier.
+	pub id: [u8;4],
+	/// The section version.
+	pub version: u16,
+	/// The section compatibility version.
+	pub cversion: u16,
+	/// The offset of the section header within the unpacked quest data.
+	pub offset: u64,
+	/// The section contents, excluding the header.
+	pub data: Vec<u8>,
+}
+
+
+impl Section {
+	/// Writes the section header and contents to the given buffer.
+	fn write_to(&self, out_buf: &mut Vec<u8>) {
+		let size = self.data.len() as u32;
+		out_buf.extend_from_slice(&self.id);
+		out_buf.extend_from_slice(&[
+			self.version as u8, (self.version >> 8) as u8,
+			self.cversion as u8, (self.cversion >> 8) as u8,
+			size as u8, (size >> 8) as u8, 
+			(size >> 16) as u8, (size >> 24) as u8]);
+		out_buf.extend_from_slice(&self.data);
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Quest
+////////////////////////////////////////////////////////////////////////////////
+/// The section table of a Zelda Classic quest file, with the packing needed to
+/// write it back in place.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::{Color, Format, Palette};
+/// use palette::format::qst::{CSET_SECTION, Packing, Quest};
+///
+/// let mut pal = Palette::new("Example", Format::Zpl, false);
+/// pal.data_mut().add_color(Color::new(255, 0, 0)).unwrap();
+/// let mut zpl = Vec::new();
+/// Format::Zpl.write_palette(&pal, &mut zpl).unwrap();
+///
+/// // Build an uncompressed quest holding only the palette section.
+/// let size = zpl.len() as u32 - 12;
+/// let mut buf = b"slh.".to_vec();
+/// buf.extend_from_slice(&zpl[..8]);
+/// buf.extend_from_slice(&[
+///     size as u8, (size >> 8) as u8, (size >> 16) as u8, (size >> 24) as u8]);
+/// buf.extend_from_slice(&zpl[12..]);
+///
+/// let mut quest = Quest::read(&mut &buf[..]).unwrap();
+/// assert_eq!(quest.packing(), Packing::Stored);
+/// assert!(quest.section(CSET_SECTION).is_some());
+///
+/// let read = quest.palette().unwrap();
+/// assert_eq!(read.data().colors().next().unwrap().1, Color::new(255, 0, 0));
+///
+/// // Replace the palette and write the quest back.
+/// pal.data_mut().add_color(Color::new(0, 0, 255)).unwrap();
+/// quest.set_palette(&pal).unwrap();
+/// let mut out = Vec::new();
+/// quest.write(&mut out).unwrap();
+/// assert_eq!(&out[..4], b"slh.");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quest {
+	/// The Zelda Classic encoding of the quest, if any.
+	encoding: Option<Encoding>,
+	/// The packfile encoding of the quest.
+	packing: Packing,
+	/// The packfile password, if any.
+	password: Option<Vec<u8>>,
+	/// The identifier string preceding the sections, if any.
+	prefix: Vec<u8>,
+	/// The sections of the quest.
+	sections: Vec<Section>,
+}
+
+
+impl Quest {
+	/// Reads a quest from the given buffer. Quests wrapped in the Zelda 
+	/// Classic encoding are decrypted with Zelda Classic's packfile password.
+	pub fn read<R>(in_buf: &mut R) -> Result<Quest>
+		where R: io::Read
+	{
+		Quest::read_packfile(in_buf, None)
+	}
+
+	/// Reads a quest encrypted with the given packfile password from the given
+	/// buffer.
+	pub fn read_with_password<R>(in_buf: &mut R, password: &[u8]) 
+		-> Result<Quest>
+		where R: io::Read
+	{
+		Quest::read_packfile(in_buf, Some(password))
+	}
+
+	/// Reads a quest from the given buffer, decrypting it with the given 
+	/// password.
+	fn read_packfile<R>(in_buf: &mut R, password: Option<&[u8]>) 
+		-> Result<Quest>
+		where R: io::Read
+	{
+		let mut bytes = Vec::new();
+		in_buf.read_to_end(&mut bytes)?;
+
+		// Remove the Zelda Classic encoding.
+		let encoded = bytes.starts_with(ENCODING_ID);
+		let (encoding, mut bytes, password) = if encoded {
+			let (encoding, bytes) = Encoding::decode(&bytes)?;
+			(Some(encoding), bytes, password.unwrap_or(QUEST_PASSWORD))
+		} else {
+			(None, bytes, password.unwrap_or(&[]))
+		};
+
+		// Remove the packfile encoding. The password applies to the stored 
+		// bytes following the magic number. Offsets in errors are within the 
+		// unpacked data.
+		let magic = if bytes.len() >= 4 { Some(u32_be(&bytes)) } else { None };
+		let packing = if magic == Some(encrypt_id(PACK_MAGIC, password)) {
+			Packing::Compressed
+		} else if magic == Some(encrypt_id(NOPACK_MAGIC, password)) {
+			Packing::Stored
+		} else {
+			Packing::None
+		};
+		let data = match packing {
+			Packing::None => bytes,
+			Packing::Stored => {
+				let mut data = bytes.split_off(4);
+				apply_password(&mut data, password);
+				data
+			},
+			Packing::Compressed => {
+				apply_password(&mut bytes[4..], password);
+				unpack(&bytes[4..])
+			},
+		};
+
+		// Read the identifier string.
+		let start = if data.starts_with(QUEST_ID) {
+			QUEST_ID_SIZE
+		} else if data.starts_with(OLD_QUEST_ID) {
+			return Err(parse_error(
+				0, 
+				"a quest with a section table", 
+				"a quest from before version 2.10"));
+		} else {
+			0
+		};
+		if data.len() < start {
+			return Err(parse_error(
+				data.len() as u64, 
+				"a quest identifier", 
+				"end of file"));
+		}
+
+		// Read the section table.
+		let mut sections = Vec::new();
+		let mut offset = start;
+		while offset < data.len() {
+			let header = data.get(offset..offset + SECTION_HEADER_SIZE)
+				.ok_or_else(|| parse_error(
+					data.len() as u64, 
+					"a section header", 
+					"end of file"))?;
+			if !header[..4].iter().all(|&b| b == b' ' || b.is_ascii_graphic())
+			{
+				return Err(parse_error(
+					offset as u64, 
+					"a section identifier", 
+					describe_bytes(&header[..4])));
+			}
+
+			let size = u32_le(&header[8..]) as usize;
+			let body_start = offset + SECTION_HEADER_SIZE;
+			let body = data.get(body_start..body_start.saturating_add(size))
+				.ok_or_else(|| parse_error(
+					data.len() as u64, 
+					format!("{} bytes of section data", size), 
+					format!("{} bytes", data.len() - body_start)))?;
+
+			sections.push(Section {
+				id: [header[0], header[1], header[2], header[3]],
+				version: u16_le(&header[4..]),
+				cversion: u16_le(&header[6..]),
+				offset: offset as u64,
+				data: body.to_vec(),
+			});
+			offset = body_start + size;
+		}
+
+		Ok(Quest {
+			encoding: encoding,
+			packing: packing,
+			password: if password.is_empty() || packing == Packing::None {
+				None
+			} else {
+				Some(password.to_vec())
+			},
+			prefix: data[..start].to_vec(),
+			sections: sections,
+		})
+	}
+
+	/// Writes the quest to the given buffer, using the encoding, packing, and
+	/// password it was read with.
+	pub fn write<W>(&self, out_buf: &mut W) -> Result<()>
+		where W: io::Write
+	{
+		let mut data = self.prefix.clone();
+		for section in &self.sections {
+			section.write_to(&mut data);
+		}
+
+		let password = self.password.as_ref().map_or(&[][..], |p| &p[..]);
+		let (magic, mut stored) = match self.packing {
+			Packing::None       => (None, data),
+			Packing::Stored     => (Some(NOPACK_MAGIC), data),
+			Packing::Compressed => (Some(PACK_MAGIC), pack(&data)),
+		};
+		let mut bytes = Vec::with_capacity(stored.len() + 4);
+		if let Some(magic) = magic {
+			bytes.extend_from_slice(&u32_be_bytes(encrypt_id(magic, password)));
+			apply_password(&mut stored, password);
+		}
+		bytes.extend_from_slice(&stored);
+
+		if let Some(encoding) = self.encoding {
+			bytes = encoding.encode(bytes);
+		}
+		out_buf.write_all(&bytes)?;
+		Ok(())
+	}
+
+	/// Returns the Zelda Classic encoding of the quest, or None if the quest 
+	/// is a bare packfile.
+	pub fn encoding(&self) -> Option<Encoding> {
+		self.encoding
+	}
+
+	/// Returns the packfile encoding of the quest.
+	pub fn packing(&self) -> Packing {
+		self.packing
+	}
+
+	/// Returns the sections of the quest, in file order.
+	pub fn sections(&self) -> &[Section] {
+		&self.sections
+	}
+
+	/// Returns the first section with the given identifier, or None if there
+	/// is no such section.
+	pub fn section(&self, id: [u8;4]) -> Option<&Section> {
+		self.sections.iter().find(|section| section.id == id)
+	}
+
+	/// Returns the palette stored in the quest's `CSET` section.
+	pub fn palette(&self) -> Result<Palette> {
+		self.palette_with(&ParseOptions::default())
+	}
+
+	/// Returns the palette stored in the quest's `CSET` section, read using 
+	/// the given parse options. Offsets in errors are within the unpacked 
+	/// quest data.
+	pub fn palette_with(&self, options: &ParseOptions) -> Result<Palette> {
+		let section = self.section(CSET_SECTION)
+			.ok_or_else(|| format_error("quest has no CSET section"))?;
+
+		let mut bytes = Vec::with_capacity(
+			SECTION_HEADER_SIZE + section.data.len());
+		section.write_to(&mut bytes);
+		zpl::read_palette(&mut &bytes[..], options).map_err(|e| match e {
+			Error::Parse {offset, expected, found} => Error::Parse {
+				offset: section.offset + offset,
+				expected: expected,
+				found: found,
+			},
+			e => e,
+		})
+	}
+
+	/// Replaces the palette stored in the quest's `CSET` section. The section
+	/// keeps its place in the section table, so the rest of the quest is 
+	/// written back unchanged.
+	pub fn set_palette(&mut self, palette: &Palette) -> Result<()> {
+		let section = self.sections.iter_mut()
+			.find(|section| section.id == CSET_SECTION)
+			.ok_or_else(|| format_error("quest has no CSET section"))?;
+		if (section.version, section.cversion) != CSET_VERSION {
+			return Err(format_error("unsupported CSET section version"));
+		}
+
+		let mut bytes = Vec::new();
+		zpl::write_palette(palette, &mut bytes)?;
+		section.data = bytes.split_off(SECTION_HEADER_SIZE);
+		Ok(())
+	}
+}
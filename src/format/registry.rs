@@ -0,0 +1,346 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides a registry of palette formats for selecting a format by file 
+//! extension or file contents.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use format::Format;
+use format::promotion::PRO_MOTION_FILE_SIZE;
+
+// Standard imports.
+use std::path::Path;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Registration
+////////////////////////////////////////////////////////////////////////////////
+/// Describes a registered palette format.
+#[derive(Debug, Clone, Copy)]
+pub struct Registration {
+	/// The registered format.
+	pub format: Format,
+	/// The file extensions associated with the format, in lowercase and 
+	/// without the leading `.`.
+	pub extensions: &'static [&'static str],
+	/// The offset and bytes identifying files of the format, if any.
+	pub magic: Option<(usize, &'static [u8])>,
+	/// The exact size in bytes of files of the format, if the format has a 
+	/// fixed size. Used to recognize files which lack identifying bytes.
+	pub size: Option<usize>,
+	/// Whether palettes can be read in the format.
+	pub readable: bool,
+	/// Whether palettes can be written in the format.
+	pub writable: bool,
+}
+
+
+/// The registered formats. Where several formats share an extension, the first
+/// registration is preferred.
+pub const REGISTRY: &'static [Registration] = &[
+	Registration {
+		format: Format::Gpl, 
+		extensions: &["gpl"], 
+		magic: Some((0, b"GIMP Palette")),
+		size: None,
+		readable: true, 
+		writable: true,
+	},
+	Registration {
+		format: Format::Ase, 
+		extensions: &["ase"], 
+		magic: Some((0, b"ASEF")),
+		size: None,
+		readable: true, 
+		writable: true,
+	},
+	Registration {
+		format: Format::Aseprite, 
+		extensions: &["aseprite", "ase"], 
+		magic: Some((4, &[0xE0, 0xA5])),
+		size: None,
+		readable: true, 
+		writable: true,
+	},
+	Registration {
+		format: Format::Jasc, 
+		extensions: &["pal"], 
+		magic: Some((0, b"JASC-PAL")),
+		size: None,
+		readable: true, 
+		writable: true,
+	},
+	Registration {
+		format: Format::Riff, 
+		extensions: &["pal"], 
+		magic: Some((8, b"PAL ")),
+		size: None,
+		readable: true, 
+		writable: true,
+	},
+	Registration {
+		format: Format::Png, 
+		extensions: &["png"], 
+		magic: Some((0, &[0x89, 0x50, 0x4E, 0x47])),
+		size: None,
+		readable: true, 
+		writable: true,
+	},
+	Registration {
+		format: Format::Aco, 
+		extensions: &["aco"], 
+		magic: None,
+		size: None,
+		readable: true, 
+		writable: true,
+	},
+	Registration {
+		format: Format::Act, 
+		extensions: &["act"], 
+		magic: None,
+		size: None,
+		readable: true, 
+		writable: true,
+	},
+	Registration {
+		format: Format::PaintNet, 
+		extensions: &["txt"], 
+		magic: None,
+		size: None,
+		readable: true, 
+		writable: true,
+	},
+	Registration {
+		format: Format::Hex, 
+		extensions: &["hex"], 
+		magic: None,
+		size: None,
+		readable: true, 
+		writable: true,
+	},
+	Registration {
+		format: Format::Gale, 
+		extensions: &["gpl"], 
+		magic: Some((0, b"GraphicsGale Palette")),
+		size: None,
+		readable: true, 
+		writable: true,
+	},
+	Registration {
+		format: Format::ProMotion, 
+		extensions: &["pal"], 
+		magic: None,
+		size: Some(PRO_MOTION_FILE_SIZE),
+		readable: true, 
+		writable: true,
+	},
+	Registration {
+		format: Format::Zpl, 
+		extensions: &["zpl"], 
+		magic: Some((0, b"CSET")),
+		size: None,
+		readable: true, 
+		writable: true,
+	},
+	Registration {
+		format: Format::Csv, 
+		extensions: &["csv"], 
+		magic: None,
+		size: None,
+		readable: true, 
+		writable: true,
+	},
+	Registration {
+		format: Format::Tsv, 
+		extensions: &["tsv", "tab"], 
+		magic: None,
+		size: None,
+		readable: true, 
+		writable: true,
+	},
+	Registration {
+		format: Format::Toml, 
+		extensions: &["toml"], 
+		magic: None,
+		size: None,
+		readable: cfg!(feature = "toml-format"), 
+		writable: cfg!(feature = "toml-format"),
+	},
+	Registration {
+		format: Format::Css, 
+		extensions: &["css"], 
+		magic: None,
+		size: None,
+		readable: false, 
+		writable: true,
+	},
+	Registration {
+		format: Format::Scss, 
+		extensions: &["scss"], 
+		magic: None,
+		size: None,
+		readable: false, 
+		writable: true,
+	},
+	Registration {
+		format: Format::Svg, 
+		extensions: &["svg"], 
+		magic: None,
+		size: None,
+		readable: false, 
+		writable: true,
+	},
+];
+
+
+/// Returns the registration for the given format, or None if the format is not
+/// registered.
+pub fn registration(format: Format) -> Option<&'static Registration> {
+	REGISTRY.iter().find(|r| r.format == format)
+}
+
+/// Returns the registered format with the given name, or None if there is no
+/// such format. The name is matched case-insensitively.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Format;
+/// use palette::format::registry;
+///
+/// assert_eq!(registry::from_name("jasc-pal"), Some(Format::Jasc));
+/// assert_eq!(registry::from_name("doc"), None);
+/// ```
+pub fn from_name(name: &str) -> Option<Format> {
+	REGISTRY.iter()
+		.map(|r| r.format)
+		.find(|format| format.name().eq_ignore_ascii_case(name))
+}
+
+/// Returns the preferred format for the given file extension, or None if no 
+/// format is registered for it. The extension is matched case-insensitively.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Format;
+/// use palette::format::registry;
+///
+/// assert_eq!(registry::from_extension("GPL"), Some(Format::Gpl));
+/// assert_eq!(registry::from_extension("doc"), None);
+/// ```
+pub fn from_extension(extension: &str) -> Option<Format> {
+	let extension = extension.to_lowercase();
+	REGISTRY.iter()
+		.find(|r| r.extensions.contains(&&extension[..]))
+		.map(|r| r.format)
+}
+
+/// Returns the format identified by the leading bytes of a file, or None if 
+/// the bytes do not match any registered format.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Format;
+/// use palette::format::registry;
+///
+/// assert_eq!(registry::from_magic(b"JASC-PAL\r\n0100"), Some(Format::Jasc));
+/// ```
+pub fn from_magic(bytes: &[u8]) -> Option<Format> {
+	REGISTRY.iter()
+		.find(|r| match r.magic {
+			Some((offset, magic)) => bytes
+				.get(offset..offset + magic.len())
+				.map_or(false, |b| b == magic),
+			None => false,
+		})
+		.map(|r| r.format)
+}
+
+/// Returns the preferred format for the given path's extension, or None if no
+/// format is registered for it.
+pub fn from_path<P>(path: P) -> Option<Format> where P: AsRef<Path> {
+	path.as_ref()
+		.extension()
+		.and_then(|ext| ext.to_str())
+		.and_then(from_extension)
+}
+
+/// Returns the format of a fixed size with the given path's extension and 
+/// file size, or None if no such format is registered.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Format;
+/// use palette::format::registry;
+///
+/// assert_eq!(registry::from_size("a.pal", 768), Some(Format::ProMotion));
+/// assert_eq!(registry::from_size("a.pal", 800), None);
+/// ```
+pub fn from_size<P>(path: P, size: usize) -> Option<Format> 
+	where P: AsRef<Path>
+{
+	let extension = path.as_ref()
+		.extension()
+		.and_then(|ext| ext.to_str())
+		.map(|ext| ext.to_lowercase());
+	let extension = match extension {
+		Some(extension) => extension,
+		None => return None,
+	};
+	REGISTRY.iter()
+		.find(|r| r.size == Some(size) 
+			&& r.extensions.contains(&&extension[..]))
+		.map(|r| r.format)
+}
+
+/// Returns the format for a file with the given path and leading bytes. The
+/// file contents are checked first, then formats of a fixed size matching 
+/// the number of bytes given, and the extension is used if neither is 
+/// recognized. Small files should be given whole, so that their size can be 
+/// checked.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Format;
+/// use palette::format::registry;
+///
+/// let table = [0u8; 768];
+/// assert_eq!(registry::detect("a.pal", &table), Some(Format::ProMotion));
+/// assert_eq!(registry::detect("a.pal", b"JASC-PAL"), Some(Format::Jasc));
+/// assert_eq!(registry::detect("a.gpl", b"GraphicsGale Palette"), 
+///     Some(Format::Gale));
+/// ```
+pub fn detect<P>(path: P, bytes: &[u8]) -> Option<Format> 
+	where P: AsRef<Path>
+{
+	from_magic(bytes)
+		.or_else(|| from_size(&path, bytes.len()))
+		.or_else(|| from_path(path))
+}
@@ -0,0 +1,198 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides standalone HTML reports of palettes.
+//!
+//! A report is a single HTML page with no external resources, listing each 
+//! color with its swatch, address, name, and hex, RGB, and HSL values. It is
+//! followed by a matrix of the contrast ratios between the colors, marked by 
+//! their WCAG level, and a row of swatches for each of the palette's ramps.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::Palette;
+use address::{Address, Reference};
+use hex::Hex;
+use result::Result;
+use space::{CONTRAST_AA, CONTRAST_AAA, Hsl, contrast_ratio};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::io;
+
+
+/// The maximum number of colors for which the contrast matrix is written. 
+/// Larger palettes would give an unreadably large matrix.
+pub const CONTRAST_MATRIX_LIMIT: usize = 64;
+
+/// The style sheet embedded in the report.
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.5em; text-align: left; }
+.swatch { display: inline-block; width: 2em; height: 2em; \
+border: 1px solid #888; vertical-align: middle; }
+.matrix td { text-align: center; font-size: 0.8em; }
+.aaa { background: #c8f0c8; }
+.aa { background: #f0f0c0; }
+.fail { color: #999; }
+.ramp { margin-bottom: 0.5em; }";
+
+
+/// Returns the given text with the HTML special characters escaped.
+fn escape(text: &str) -> String {
+	let mut escaped = String::with_capacity(text.len());
+	for c in text.chars() {
+		match c {
+			'&' => escaped.push_str("&amp;"),
+			'<' => escaped.push_str("&lt;"),
+			'>' => escaped.push_str("&gt;"),
+			'"' => escaped.push_str("&quot;"),
+			'\'' => escaped.push_str("&#39;"),
+			_ => escaped.push(c),
+		}
+	}
+	escaped
+}
+
+/// Returns the HTML for a swatch of the given color.
+fn swatch(color: Color) -> String {
+	format!("<span class=\"swatch\" style=\"background: {0}\" \
+		title=\"{0}\"></span>", 
+		Hex(color))
+}
+
+/// Returns the style class for the given contrast ratio.
+fn contrast_class(ratio: f32) -> &'static str {
+	if ratio >= CONTRAST_AAA {
+		"aaa"
+	} else if ratio >= CONTRAST_AA {
+		"aa"
+	} else {
+		"fail"
+	}
+}
+
+
+/// Writes a standalone HTML report of the palette to the given buffer.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::{Palette, Format, Color};
+/// use palette::format::report::html;
+///
+/// let mut pal = Palette::new("Example", Format::Default, false);
+/// let black = pal.data_mut().add_color(Color::new(0, 0, 0)).unwrap();
+/// pal.data_mut().add_color(Color::new(255, 255, 255)).unwrap();
+/// pal.data_mut().set_slot_name(black, "Ink");
+///
+/// let mut out = Vec::new();
+/// html::write_report(&pal, &mut out).unwrap();
+///
+/// let text = String::from_utf8(out).unwrap();
+/// assert!(text.contains("<title>Example</title>"));
+/// assert!(text.contains("<td>Ink</td>"));
+/// assert!(text.contains("rgb(255, 255, 255)"));
+/// assert!(text.contains("<td class=\"aaa\">21.00</td>"));
+/// ```
+pub fn write_report<W>(palette: &Palette, out_buf: &mut W) -> Result<()>
+	where W: io::Write
+{
+	let data = &palette.data;
+	let title = escape(data.name(&Reference::all()).unwrap_or("Palette"));
+	let colors: Vec<(Address, Color)> = data.colors().collect();
+
+	writeln!(out_buf, "<!DOCTYPE html>")?;
+	writeln!(out_buf, "<html>\n<head>\n<meta charset=\"utf-8\">")?;
+	writeln!(out_buf, "<title>{}</title>", title)?;
+	writeln!(out_buf, "<style>\n{}\n</style>", STYLE)?;
+	writeln!(out_buf, "</head>\n<body>\n<h1>{}</h1>", title)?;
+
+	// Write the color table.
+	writeln!(out_buf, "<h2>Colors</h2>\n<table>")?;
+	writeln!(out_buf, "<tr><th></th><th>Address</th><th>Name</th>\
+		<th>Hex</th><th>RGB</th><th>HSL</th></tr>")?;
+	for &(address, color) in &colors {
+		let hsl = Hsl::from(color);
+		writeln!(out_buf, 
+			"<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td>\
+			<td>rgb({}, {}, {})</td><td>hsl({:.0}, {:.0}%, {:.0}%)</td></tr>",
+			swatch(color),
+			address,
+			escape(data.slot_name(address).unwrap_or("")),
+			Hex(color),
+			color.r(), color.g(), color.b(),
+			hsl.h, hsl.s * 100.0, hsl.l * 100.0)?;
+	}
+	writeln!(out_buf, "</table>")?;
+
+	// Write the contrast matrix.
+	writeln!(out_buf, "<h2>Contrast</h2>")?;
+	if colors.len() > CONTRAST_MATRIX_LIMIT {
+		writeln!(out_buf, 
+			"<p>The contrast matrix is omitted for palettes of more than {} \
+			colors.</p>",
+			CONTRAST_MATRIX_LIMIT)?;
+	} else {
+		writeln!(out_buf, "<table class=\"matrix\">")?;
+		write!(out_buf, "<tr><th></th>")?;
+		for &(_, color) in &colors {
+			write!(out_buf, "<th>{}</th>", swatch(color))?;
+		}
+		writeln!(out_buf, "</tr>")?;
+		for &(_, row) in &colors {
+			write!(out_buf, "<tr><th>{}</th>", swatch(row))?;
+			for &(_, column) in &colors {
+				let ratio = contrast_ratio(row, column);
+				write!(out_buf, "<td class=\"{}\">{:.2}</td>", 
+					contrast_class(ratio), 
+					ratio)?;
+			}
+			writeln!(out_buf, "</tr>")?;
+		}
+		writeln!(out_buf, "</table>")?;
+	}
+
+	// Write the ramps.
+	if !data.ramps().is_empty() {
+		writeln!(out_buf, "<h2>Ramps</h2>")?;
+		for (index, ramp) in data.ramps().iter().enumerate() {
+			write!(out_buf, "<div class=\"ramp\">Ramp {}: ", index)?;
+			match ramp.evaluate(data) {
+				Ok(colors) => for color in colors {
+					write!(out_buf, "{}", swatch(color))?;
+				},
+				Err(e) => write!(out_buf, "{}", escape(&e.to_string()))?,
+			}
+			writeln!(out_buf, "</div>")?;
+		}
+	}
+
+	writeln!(out_buf, "</body>\n</html>")?;
+	Ok(())
+}
@@ -0,0 +1,439 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides reports of the palette data lost when writing to a format, and of
+//! the problems which prevent writing to a format. The `html` submodule 
+//! provides shareable reports of the palette itself.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Submodules.
+#[warn(missing_docs)]
+pub mod html;
+
+// Local imports.
+use ::Palette;
+use address::{Address, Reference};
+use data::{Constraint, Data};
+use depth::{expand, reduce};
+use expression::Expression;
+use format::{Format, zpl};
+use meta::ALPHA_KEY;
+
+// Standard imports.
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Loss
+////////////////////////////////////////////////////////////////////////////////
+/// A single item of palette data which a format can not represent.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Loss {
+	/// A derived color will be written as a fixed color.
+	Derived(Address),
+	/// A color will not be written.
+	Dropped(Address),
+	/// The palette has more colors than the format can store, so writing will
+	/// fail.
+	TooManyColors {
+		/// The number of colors in the palette.
+		count: usize,
+		/// The maximum number of colors in the format.
+		limit: usize,
+	},
+	/// Color channels will be reduced to the given bit depth.
+	DepthReduced(u8),
+	/// The name of a cell or group will be dropped.
+	Name(Reference),
+	/// The name of a cell or group will be shortened.
+	NameTruncated(Reference),
+	/// The name of a cell or group will be rewritten to suit the format.
+	NameRewritten(Reference),
+	/// The transparency of a color will be dropped.
+	Alpha(Address),
+	/// The tags or notes of a cell or group will be dropped.
+	Metadata(Reference),
+	/// Colors will not be read back at the same addresses, as the format does
+	/// not store the page and line structure.
+	Layout,
+}
+
+
+impl fmt::Display for Loss {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Loss::Derived(address) 
+				=> write!(f, "derived color at {} written as fixed", address),
+			Loss::Dropped(address) 
+				=> write!(f, "color at {} dropped", address),
+			Loss::TooManyColors {count, limit} 
+				=> write!(f, "{} colors exceeds the limit of {}", count, limit),
+			Loss::DepthReduced(depth) 
+				=> write!(f, "channels reduced to {} bits", depth),
+			Loss::Name(ref group) 
+				=> write!(f, "name of {} dropped", group),
+			Loss::NameTruncated(ref group) 
+				=> write!(f, "name of {} truncated", group),
+			Loss::NameRewritten(ref group) 
+				=> write!(f, "name of {} rewritten", group),
+			Loss::Alpha(address) 
+				=> write!(f, "transparency of color at {} dropped", address),
+			Loss::Metadata(ref group) 
+				=> write!(f, "tags and notes of {} dropped", group),
+			Loss::Layout 
+				=> write!(f, "page and line layout not preserved"),
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Violation
+////////////////////////////////////////////////////////////////////////////////
+/// A problem which prevents palette data from being written in a format, or 
+/// which would alter it beyond the format's usual losses.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Violation {
+	/// The palette has more colors than the format can store.
+	TooManyColors {
+		/// The number of colors in the palette.
+		count: usize,
+		/// The maximum number of colors in the format.
+		limit: usize,
+	},
+	/// The palette has fewer colors than the format requires.
+	TooFewColors {
+		/// The number of colors in the palette.
+		count: usize,
+		/// The minimum number of colors in the format.
+		minimum: usize,
+	},
+	/// A color can not be represented exactly at the format's bit depth.
+	DepthExceeded {
+		/// The address of the color.
+		address: Address,
+		/// The number of bits stored for each channel.
+		depth: u8,
+	},
+	/// A color lies in a slot which the format reserves or can not store.
+	Reserved(Address),
+	/// A name is longer than the format can store. Lengths are measured as 
+	/// described by `Capabilities::max_name_length`.
+	NameTooLong {
+		/// The named group.
+		group: Reference,
+		/// The length of the name.
+		length: usize,
+		/// The maximum length of a name.
+		limit: usize,
+	},
+}
+
+
+impl fmt::Display for Violation {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Violation::TooManyColors {count, limit} 
+				=> write!(f, "{} colors exceeds the limit of {}", count, limit),
+			Violation::TooFewColors {count, minimum} 
+				=> write!(f, "{} colors is below the minimum of {}", 
+					count, 
+					minimum),
+			Violation::DepthExceeded {address, depth} 
+				=> write!(f, "color at {} exceeds {} bits per channel", 
+					address, 
+					depth),
+			Violation::Reserved(address) 
+				=> write!(f, "color at {} lies in a reserved slot", address),
+			Violation::NameTooLong {ref group, length, limit} 
+				=> write!(f, "name of {} has length {}, over the limit of {}",
+					group, 
+					length, 
+					limit),
+		}
+	}
+}
+
+
+/// Returns the problems which would prevent the given palette data from being
+/// written in the given format, or which would corrupt the written file. This
+/// allows problems to be reported before writing begins. Colors in slots 
+/// reserved by the palette's template or outside of its geometry are reported
+/// for every format.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::data::Data;
+/// use palette::format::report::{validate_for, Violation};
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Zpl, false);
+/// pal.apply(Box::new(InsertColor::new(Color::new(0, 0, 0)))).unwrap();
+/// pal.apply(Box::new(InsertColor::new(Color::new(1, 2, 3)))).unwrap();
+///
+/// assert!(validate_for(Format::Default, pal.data()).is_empty());
+/// assert_eq!(validate_for(Format::Zpl, pal.data()), vec![
+/// 	Violation::DepthExceeded {address: Address::new(0, 0, 1), depth: 6},
+/// ]);
+/// assert_eq!(validate_for(Format::Png, &Default::default()), vec![
+/// 	Violation::TooFewColors {count: 0, minimum: 1},
+/// ]);
+///
+/// // ASE names are measured in UTF-16 code units rather than bytes.
+/// let mut named = pal.data().clone();
+/// named.set_slot_name(Address::new(0, 0, 0), "é".repeat(40000));
+/// assert!(validate_for(Format::Ase, &named).is_empty());
+/// assert_eq!(validate_for(Format::Default, &named).len(), 1);
+///
+/// // Reserved slots may not hold colors in any format.
+/// let mut nes = Data::nes();
+/// let mirror = Address::new(0, 1, 0);
+/// *nes.create_cell(mirror).unwrap().borrow_mut() = 
+/// 	Expression::Color(Color::new(0, 0, 0));
+/// assert_eq!(validate_for(Format::Act, &nes), vec![
+/// 	Violation::Reserved(mirror),
+/// ]);
+/// ```
+pub fn validate_for(format: Format, data: &Data) -> Vec<Violation> {
+	let mut violations = Vec::new();
+	let caps = format.capabilities();
+
+	// Colors.
+	let count = data.colors().count();
+	if let Some(limit) = caps.max_colors {
+		if count > limit {
+			violations.push(Violation::TooManyColors {
+				count: count, 
+				limit: limit,
+			});
+		}
+	}
+	if count < caps.min_colors {
+		violations.push(Violation::TooFewColors {
+			count: count, 
+			minimum: caps.min_colors,
+		});
+	}
+	if caps.depth < 8 {
+		let exact = |channel: u8| 
+			expand(reduce(channel, caps.depth), caps.depth) == channel;
+		for (address, color) in data.colors() {
+			if !(exact(color.r()) && exact(color.g()) && exact(color.b())) {
+				violations.push(Violation::DepthExceeded {
+					address: address, 
+					depth: caps.depth,
+				});
+			}
+		}
+	}
+
+	// Reserved slots.
+	for (address, _) in data.colors() {
+		if data.constraint(address) == Some(Constraint::Reserved) 
+			|| !data.check_address(address)
+		{
+			violations.push(Violation::Reserved(address));
+		}
+	}
+
+	// Names.
+	if let Some(limit) = caps.max_name_length {
+		let names: BTreeMap<&Reference, &str> = data.metadata.iter()
+			.filter_map(|(group, meta)| meta.name.as_ref()
+				.map(|name| (group, &name[..])))
+			.collect();
+		for (group, name) in names {
+			let length = match format {
+				Format::Ase | Format::Aco => name.encode_utf16().count(),
+				_ => name.len(),
+			};
+			if length > limit {
+				violations.push(Violation::NameTooLong {
+					group: group.clone(), 
+					length: length, 
+					limit: limit,
+				});
+			}
+		}
+	}
+
+	// Format-specific violations.
+	if format == Format::Zpl {
+		zpl::validate(data, &mut violations);
+	}
+	violations
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// WriteReport
+////////////////////////////////////////////////////////////////////////////////
+/// Lists the palette data which is dropped or altered when writing a palette 
+/// to a format. Losses common to all formats are found by comparing the 
+/// palette against the format's `Capabilities`; format writers record the 
+/// losses they cause themselves.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::format::report::Loss;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertColor::new(Color::new(0, 0, 0)))).unwrap();
+/// pal.apply(Box::new(InsertDerived::lighten(Address::new(0, 0, 0), 0.5)))
+/// 	.unwrap();
+///
+/// let report = Format::Gpl.check(&pal);
+/// assert_eq!(report.losses(), &[Loss::Derived(Address::new(0, 0, 1))]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteReport {
+	/// The format being written.
+	format: Format,
+	/// The recorded losses, in the order they were recorded.
+	losses: Vec<Loss>,
+	/// The set of recorded losses, used to skip duplicates.
+	recorded: BTreeSet<Loss>,
+}
+
+
+impl WriteReport {
+	/// Creates a new, empty WriteReport for the given format.
+	pub fn new(format: Format) -> WriteReport {
+		WriteReport {
+			format: format,
+			losses: Vec::new(),
+			recorded: BTreeSet::new(),
+		}
+	}
+
+	/// Returns the format being written.
+	pub fn format(&self) -> Format {
+		self.format
+	}
+
+	/// Returns the recorded losses.
+	pub fn losses(&self) -> &[Loss] {
+		&self.losses
+	}
+
+	/// Returns true if no data is lost.
+	pub fn is_lossless(&self) -> bool {
+		self.losses.is_empty()
+	}
+
+	/// Records a loss. Losses which have already been recorded are ignored.
+	pub fn push(&mut self, loss: Loss) {
+		if self.recorded.insert(loss.clone()) {
+			self.losses.push(loss);
+		}
+	}
+}
+
+
+impl fmt::Display for WriteReport {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(f, "{} export:", self.format.name())?;
+		for loss in &self.losses {
+			writeln!(f, "\t{}", loss)?;
+		}
+		Ok(())
+	}
+}
+
+
+/// Returns a report of the data lost when writing the given palette in the 
+/// given format, excluding the losses recorded by the format's writer.
+pub(crate) fn check(format: Format, palette: &Palette) -> WriteReport {
+	let mut report = WriteReport::new(format);
+	if format == Format::Default { return report; }
+
+	let caps = format.capabilities();
+	let data = &palette.data;
+
+	// Colors.
+	let count = data.colors().count();
+	if let Some(limit) = caps.max_colors {
+		if count > limit {
+			report.push(Loss::TooManyColors {count: count, limit: limit});
+		}
+	}
+	if !caps.derived {
+		for (address, cell) in data.iter() {
+			match *cell.borrow() {
+				Expression::Empty 
+					| Expression::Color(..) 
+					| Expression::Cmyk(..) => (),
+				_ => report.push(Loss::Derived(address)),
+			}
+		}
+	}
+	if !caps.groups {
+		let lines = data.default_line_count;
+		let columns = data.default_column_count;
+		let moved = data.colors()
+			.enumerate()
+			.any(|(i, (address, _))| 
+				Address::from_index(i, lines, columns) != Some(address));
+		if moved {
+			report.push(Loss::Layout);
+		}
+	}
+	if !caps.alpha {
+		for (address, _) in data.colors() {
+			let alpha = data
+				.get_meta::<i64>(&Reference::from(address), ALPHA_KEY);
+			if alpha.map_or(false, |alpha| alpha < 255) {
+				report.push(Loss::Alpha(address));
+			}
+		}
+	}
+
+	// Metadata.
+	for (group, meta) in &data.metadata {
+		if *group == Reference::all() { continue; }
+		let stored = if group.column().is_ok() {caps.names} else {caps.groups};
+		if meta.name.is_some() && !stored {
+			report.push(Loss::Name(group.clone()));
+		}
+		if !meta.tags.is_empty() || meta.notes.is_some() {
+			report.push(Loss::Metadata(group.clone()));
+		}
+	}
+
+	// Format-specific losses.
+	if format == Format::Zpl {
+		zpl::check(palette, &mut report);
+	}
+	report
+}
@@ -0,0 +1,193 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides components for interacting with the Microsoft RIFF palette format.
+//!
+//! RIFF palette files contain a `PAL ` form with a single `data` chunk holding
+//! a `LOGPALETTE` structure: a version, a color count, and a sequence of 
+//! red, green, blue, and flags bytes. All values are little-endian.
+//!
+//! The flags byte of each entry is preserved as slot metadata under the 
+//! `meta::RIFF_FLAGS_KEY` key. Entries with no flags set have no metadata.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::Palette;
+use address::Reference;
+use format::{
+	Format,
+	OffsetReader,
+	ParseOptions,
+	describe_bytes,
+	parse_error,
+};
+use meta::{MetaType, RIFF_FLAGS_KEY};
+use result::{Error, Result};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::io;
+use std::io::Read;
+
+
+/// The `LOGPALETTE` version number.
+const RIFF_PALETTE_VERSION: u16 = 0x0300;
+
+/// The maximum number of entries in a `LOGPALETTE`.
+const RIFF_PALETTE_LIMIT: usize = 0xFFFF;
+
+
+/// Returns a `Format` error with the given reason.
+fn format_error(reason: &str) -> Error {
+	Error::Format {format: Format::Riff.name(), reason: reason.into()}
+}
+
+/// Reads a little-endian u16 from the given buffer.
+fn read_u16<R>(in_buf: &mut R) -> io::Result<u16> where R: io::Read {
+	let mut bytes = [0u8; 2];
+	in_buf.read_exact(&mut bytes)?;
+	Ok(bytes[0] as u16 | (bytes[1] as u16) << 8)
+}
+
+/// Reads a little-endian u32 from the given buffer.
+fn read_u32<R>(in_buf: &mut R) -> io::Result<u32> where R: io::Read {
+	let mut bytes = [0u8; 4];
+	in_buf.read_exact(&mut bytes)?;
+	Ok(bytes[0] as u32 
+		| (bytes[1] as u32) << 8 
+		| (bytes[2] as u32) << 16 
+		| (bytes[3] as u32) << 24)
+}
+
+/// Returns the bytes of the given u32 in little-endian order.
+fn u32_bytes(value: u32) -> [u8;4] {
+	[value as u8, (value >> 8) as u8, (value >> 16) as u8, (value >> 24) as u8]
+}
+
+
+/// Writes the palette to the given buffer.
+pub fn write_palette<W>(palette: &Palette, out_buf: &mut W) -> Result<()>
+	where W: io::Write
+{
+	let entries: Vec<(Color, u8)> = palette.data.colors()
+		.map(|(address, color)| {
+			let flags = palette.data
+				.get_meta::<i64>(&Reference::from(address), RIFF_FLAGS_KEY)
+				.map_or(0, |flags| flags.max(0).min(0xFF) as u8);
+			(color, flags)
+		})
+		.collect();
+	if entries.len() > RIFF_PALETTE_LIMIT {
+		return Err(format_error("too many colors for RIFF palette"));
+	}
+
+	let data_size = 4 + 4 * entries.len() as u32;
+	out_buf.write_all(b"RIFF")?;
+	out_buf.write_all(&u32_bytes(12 + data_size))?;
+	out_buf.write_all(b"PAL data")?;
+	out_buf.write_all(&u32_bytes(data_size))?;
+
+	// Write LOGPALETTE.
+	let count = entries.len() as u16;
+	out_buf.write_all(&[
+		RIFF_PALETTE_VERSION as u8, (RIFF_PALETTE_VERSION >> 8) as u8,
+		count as u8, (count >> 8) as u8])?;
+	for (color, flags) in entries {
+		out_buf.write_all(&[color.r(), color.g(), color.b(), flags])?;
+	}
+	Ok(())
+}
+
+
+/// Reads a palette from the given buffer.
+pub fn read_palette<R>(in_buf: &mut R, options: &ParseOptions) 
+	-> Result<Palette>
+	where R: io::Read
+{
+	let mut in_buf = OffsetReader::new(in_buf);
+	let mut palette = Palette::new("", Format::Riff, true);
+	let result = read_colors(&mut in_buf, &mut palette, options);
+	options.finish(in_buf.offset(), palette, result)
+}
+
+/// Reads the palette entries from the given buffer into the palette.
+fn read_colors<R>(
+	in_buf: &mut OffsetReader<R>,
+	palette: &mut Palette,
+	options: &ParseOptions)
+	-> Result<()>
+	where R: io::Read
+{
+	let mut form = [0u8; 12];
+	in_buf.read_exact(&mut form)?;
+	if &form[0..4] != b"RIFF" || &form[8..12] != b"PAL " {
+		return Err(parse_error(
+			0, 
+			"RIFF palette header", 
+			describe_bytes(&form)));
+	}
+
+	// Skip chunks until the data chunk is found.
+	loop {
+		let mut chunk_id = [0u8; 4];
+		in_buf.read_exact(&mut chunk_id)?;
+		let size = read_u32(in_buf)? as u64;
+		if &chunk_id == b"data" { break; }
+		// Chunks are padded to an even length.
+		let end = in_buf.offset() + size + (size & 1);
+		in_buf.skip_to(end)?;
+	}
+
+	// Read LOGPALETTE.
+	let offset = in_buf.offset();
+	let version = read_u16(in_buf)?;
+	if version != RIFF_PALETTE_VERSION {
+		options.recover(parse_error(
+			offset, 
+			format!("version {:04X}", RIFF_PALETTE_VERSION), 
+			format!("version {:04X}", version)))?;
+	}
+	let count = read_u16(in_buf)?;
+	for _ in 0..count {
+		let offset = in_buf.offset();
+		let mut entry = [0u8; 4];
+		in_buf.read_exact(&mut entry)?;
+		if !options.admits(palette.data.len(), offset)? {
+			break;
+		}
+		let address = palette.data
+			.add_color(Color::new(entry[0], entry[1], entry[2]))?;
+		if entry[3] != 0 {
+			palette.data.register_meta(RIFF_FLAGS_KEY, MetaType::Int)?;
+			palette.data.set_meta(
+				Reference::from(address), 
+				RIFF_FLAGS_KEY,
+				entry[3] as i64)?;
+		}
+	}
+	Ok(())
+}
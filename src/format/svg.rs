@@ -0,0 +1,249 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides components for exporting palettes as SVG swatch grids.
+//!
+//! Each color is drawn as a square `rect` filled with the color, arranged in
+//! rows according to the chosen `SvgLayout`. Swatches may optionally be 
+//! labelled with their slot names.
+//!
+//! This format is export only.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::Palette;
+use address::{Address, Line, Page};
+use hex::Hex;
+use result::Result;
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::io;
+
+
+/// The height of the label area below each swatch, in pixels.
+const LABEL_HEIGHT: u32 = 14;
+
+/// The font size of swatch labels, in pixels.
+const LABEL_FONT_SIZE: u32 = 10;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// SvgLayout
+////////////////////////////////////////////////////////////////////////////////
+/// The arrangement of swatches in an SVG swatch grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SvgLayout {
+	/// Colors are written in address order, wrapping after the given number 
+	/// of columns.
+	Columns(usize),
+	/// Each line of the palette is written as a row.
+	Lines,
+	/// Each page of the palette is written as a row.
+	Pages,
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// SvgOptions
+////////////////////////////////////////////////////////////////////////////////
+/// Options controlling the appearance of an SVG swatch grid.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::{Palette, Format, Color};
+/// use palette::format::svg::{self, SvgLayout, SvgOptions};
+///
+/// let mut pal = Palette::new("Example", Format::Default, false);
+/// let address = pal.data_mut().add_color(Color::new(255, 0, 0)).unwrap();
+/// pal.data_mut().set_slot_name(address, "Red & Hot");
+///
+/// let options = SvgOptions::default()
+///     .swatch_size(16)
+///     .labels(true)
+///     .layout(SvgLayout::Pages);
+/// let mut out = Vec::new();
+/// svg::write_palette_with(&pal, &mut out, &options).unwrap();
+///
+/// let text = String::from_utf8(out).unwrap();
+/// assert!(text.contains("fill=\"#ff0000\""));
+/// assert!(text.contains(">Red &amp; Hot</text>"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SvgOptions {
+	/// The width and height of each swatch, in pixels.
+	pub swatch_size: u32,
+	/// Whether swatches are labelled with their slot names.
+	pub labels: bool,
+	/// The arrangement of the swatches.
+	pub layout: SvgLayout,
+}
+
+
+impl SvgOptions {
+	/// Sets the width and height of each swatch, in pixels.
+	pub fn swatch_size(mut self, swatch_size: u32) -> Self {
+		self.swatch_size = swatch_size;
+		self
+	}
+
+	/// Sets whether swatches are labelled with their slot names.
+	pub fn labels(mut self, labels: bool) -> Self {
+		self.labels = labels;
+		self
+	}
+
+	/// Sets the arrangement of the swatches.
+	pub fn layout(mut self, layout: SvgLayout) -> Self {
+		self.layout = layout;
+		self
+	}
+}
+
+
+impl Default for SvgOptions {
+	fn default() -> Self {
+		SvgOptions {
+			swatch_size: 32,
+			labels: false,
+			layout: SvgLayout::Columns(16),
+		}
+	}
+}
+
+
+
+/// Returns the given text with the XML special characters escaped.
+fn escape(text: &str) -> String {
+	let mut escaped = String::with_capacity(text.len());
+	for c in text.chars() {
+		match c {
+			'&' => escaped.push_str("&amp;"),
+			'<' => escaped.push_str("&lt;"),
+			'>' => escaped.push_str("&gt;"),
+			'"' => escaped.push_str("&quot;"),
+			'\'' => escaped.push_str("&apos;"),
+			_ => escaped.push(c),
+		}
+	}
+	escaped
+}
+
+/// Returns the colors of the palette arranged in rows by the given layout.
+fn rows(palette: &Palette, layout: SvgLayout) -> Vec<Vec<(Address, Color)>> {
+	let colors: Vec<(Address, Color)> = palette.data.colors().collect();
+	match layout {
+		SvgLayout::Columns(columns) => colors
+			.chunks(columns.max(1))
+			.map(|row| row.to_vec())
+			.collect(),
+		SvgLayout::Lines => group_rows(colors, |a| (a.page, a.line)),
+		SvgLayout::Pages => group_rows(colors, |a| (a.page, 0)),
+	}
+}
+
+/// Splits the given colors into rows wherever the key of their address 
+/// changes.
+fn group_rows<F>(colors: Vec<(Address, Color)>, key: F) 
+	-> Vec<Vec<(Address, Color)>>
+	where F: Fn(&Address) -> (Page, Line)
+{
+	let mut rows: Vec<Vec<(Address, Color)>> = Vec::new();
+	let mut current = None;
+	for (address, color) in colors {
+		let k = key(&address);
+		if current != Some(k) {
+			rows.push(Vec::new());
+			current = Some(k);
+		}
+		rows.last_mut()
+			.expect("row pushed for first color")
+			.push((address, color));
+	}
+	rows
+}
+
+
+/// Writes the palette to the given buffer as an SVG swatch grid using the 
+/// default options.
+pub fn write_palette<W>(palette: &Palette, out_buf: &mut W) -> Result<()>
+	where W: io::Write
+{
+	write_palette_with(palette, out_buf, &SvgOptions::default())
+}
+
+/// Writes the palette to the given buffer as an SVG swatch grid using the 
+/// given options.
+pub fn write_palette_with<W>(
+	palette: &Palette, 
+	out_buf: &mut W,
+	options: &SvgOptions) 
+	-> Result<()>
+	where W: io::Write
+{
+	let rows = rows(palette, options.layout);
+	let cell_width = options.swatch_size;
+	let cell_height = options.swatch_size 
+		+ if options.labels { LABEL_HEIGHT } else { 0 };
+	let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+	let width = columns as u32 * cell_width;
+	let height = rows.len() as u32 * cell_height;
+
+	writeln!(out_buf, 
+		"<svg xmlns=\"http://www.w3.org/2000/svg\" \
+		width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+		w = width, 
+		h = height)?;
+	for (y, row) in rows.iter().enumerate() {
+		let top = y as u32 * cell_height;
+		for (x, &(address, color)) in row.iter().enumerate() {
+			let left = x as u32 * cell_width;
+			writeln!(out_buf, 
+				"\t<rect x=\"{}\" y=\"{}\" width=\"{s}\" height=\"{s}\" \
+				fill=\"{}\"/>",
+				left, 
+				top, 
+				Hex(color), 
+				s = options.swatch_size)?;
+
+			if !options.labels { continue; }
+			if let Some(name) = palette.data.slot_name(address) {
+				writeln!(out_buf, 
+					"\t<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" \
+					font-size=\"{}\">{}</text>",
+					left, 
+					top + options.swatch_size + LABEL_FONT_SIZE, 
+					LABEL_FONT_SIZE, 
+					escape(name))?;
+			}
+		}
+	}
+	writeln!(out_buf, "</svg>")?;
+	Ok(())
+}
@@ -0,0 +1,322 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides components for interacting with palettes stored as TOML, a 
+//! human-editable format suited to hand editing and version control.
+//!
+//! Each occupied page is written as an entry of the `pages` array, holding 
+//! its index, its name, its lines as arrays of `#RRGGBB` strings, and tables
+//! of line names and slot names. Empty slots within a line are written as 
+//! empty strings. Ramps are written as declarative specs in the `ramps` 
+//! array.
+//!
+//! ```toml
+//! name = "Example"
+//!
+//! [[pages]]
+//! page = 0
+//! name = "Greens"
+//! lines = [
+//!     ["#000000", "#336633", "#ffffff"],
+//!     ["", "#88cc88"],
+//! ]
+//!
+//! [pages.names]
+//! "0:1" = "Leaf"
+//!
+//! [[ramps]]
+//! anchors = ["0:0:0", "0:0:2"]
+//! steps = 1
+//! interpolation = "Rgb"
+//! easing = "Linear"
+//! location = "0:0:1"
+//! ```
+//!
+//! Derived colors are written as fixed colors. The ramp specs are preserved,
+//! so ramps can be rebuilt after editing their anchors.
+//!
+//! This format requires the `toml-format` feature.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::Palette;
+use address::{Address, Column, Line, Page, Reference};
+use expression::Expression;
+use format::{Format, ParseOptions, parse_error};
+use hex::{from_hex, Hex};
+use ramp::RampSpec;
+use result::{Error, Result};
+use space::{Easing, Interpolation};
+
+// Standard imports.
+use std::collections::BTreeMap;
+use std::io;
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Serialized forms
+////////////////////////////////////////////////////////////////////////////////
+/// The serialized form of a palette.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PaletteRepr {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	name: Option<String>,
+	#[serde(default)]
+	pages: Vec<PageRepr>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	ramps: Vec<RampRepr>,
+}
+
+/// The serialized form of a page.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PageRepr {
+	page: Page,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	name: Option<String>,
+	#[serde(default)]
+	lines: Vec<Vec<String>>,
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	line_names: BTreeMap<String, String>,
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	names: BTreeMap<String, String>,
+}
+
+/// The serialized form of a `RampSpec`.
+#[derive(Debug, Serialize, Deserialize)]
+struct RampRepr {
+	anchors: Vec<String>,
+	steps: usize,
+	#[serde(default)]
+	interpolation: Interpolation,
+	#[serde(default)]
+	easing: Easing,
+	location: String,
+}
+
+
+/// Returns a `Format` error with the given reason.
+fn format_error<S>(reason: S) -> Error where S: Into<String> {
+	Error::Format {format: Format::Toml.name(), reason: reason.into()}
+}
+
+/// Returns the byte offset of the given value as a quoted string in the given
+/// text, or 0 if it is not found. Used to locate malformed values after 
+/// deserialization.
+fn locate(text: &str, value: &str) -> u64 {
+	text.find(&format!("\"{}\"", value)).unwrap_or(0) as u64
+}
+
+/// Returns the byte offset of the given line and column in the given text.
+fn offset_of(text: &str, line: usize, column: usize) -> u64 {
+	let start: usize = text.split('\n')
+		.take(line)
+		.map(|line| line.len() + 1)
+		.sum();
+	(start + column).min(text.len()) as u64
+}
+
+
+/// Writes the palette to the given buffer.
+pub fn write_palette<W>(palette: &Palette, out_buf: &mut W) -> Result<()>
+	where W: io::Write
+{
+	let data = &palette.data;
+	let mut pages: BTreeMap<Page, PageRepr> = BTreeMap::new();
+	for (address, color) in data.colors() {
+		let page = pages.entry(address.page).or_insert_with(|| PageRepr {
+			page: address.page,
+			name: data.name(&Reference::page_of(&address))
+				.map(String::from),
+			.. Default::default()
+		});
+
+		let line = address.line as usize;
+		if page.lines.len() <= line {
+			page.lines.resize(line + 1, Vec::new());
+		}
+		if let Some(name) = data.name(&Reference::line_of(&address)) {
+			page.line_names.insert(line.to_string(), name.into());
+		}
+		let column = address.column as usize;
+		if page.lines[line].len() <= column {
+			page.lines[line].resize(column + 1, String::new());
+		}
+		page.lines[line][column] = Hex(color).to_string();
+
+		if let Some(name) = data.slot_name(address) {
+			page.names.insert(
+				format!("{}:{}", address.line, address.column), 
+				name.into());
+		}
+	}
+
+	let repr = PaletteRepr {
+		name: data.name(&Reference::all()).map(String::from),
+		pages: pages.into_iter().map(|(_, page)| page).collect(),
+		ramps: data.ramps().iter()
+			.map(|ramp| RampRepr {
+				anchors: ramp.anchors.iter().map(|a| a.to_string()).collect(),
+				steps: ramp.steps,
+				interpolation: ramp.interpolation,
+				easing: ramp.easing,
+				location: ramp.location.to_string(),
+			})
+			.collect(),
+	};
+	let text = ::toml::to_string_pretty(&repr)
+		.map_err(|e| format_error(e.to_string()))?;
+	out_buf.write_all(text.as_bytes())?;
+	Ok(())
+}
+
+
+/// Reads a palette from the given buffer.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::{Address, Color, Format};
+///
+/// let text = r##"
+/// name = "Example"
+///
+/// [[pages]]
+/// page = 0
+/// lines = [["#000000", "", "#ffffff"]]
+///
+/// [pages.names]
+/// "0:2" = "Paper"
+/// "##;
+///
+/// let pal = Format::Toml.read_palette(&mut text.as_bytes()).unwrap();
+/// let paper = Address::new(0, 0, 2);
+/// assert_eq!(pal.color(paper), Some(Color::new(255, 255, 255)));
+/// assert_eq!(pal.data().slot_name(paper), Some("Paper"));
+/// assert_eq!(pal.color(Address::new(0, 0, 1)), None);
+/// ```
+pub fn read_palette<R>(in_buf: &mut R, options: &ParseOptions) 
+	-> Result<Palette>
+	where R: io::Read
+{
+	let mut text = String::new();
+	in_buf.read_to_string(&mut text)?;
+	let repr: PaletteRepr = ::toml::from_str(&text)
+		.map_err(|e| {
+			let offset = e.line_col()
+				.map_or(0, |(line, column)| offset_of(&text, line, column));
+			parse_error(offset, "a TOML palette", e.to_string())
+		})?;
+
+	let mut palette = Palette::new(
+		repr.name.unwrap_or_default(), 
+		Format::Toml, 
+		true);
+	for page in repr.pages {
+		let base = Address::new(page.page, 0, 0);
+		if let Some(name) = page.name {
+			palette.data.set_name(Reference::page_of(&base), name);
+		}
+
+		// Read colors.
+		for (line, colors) in page.lines.iter().enumerate() {
+			for (column, value) in colors.iter().enumerate() {
+				if value.is_empty() { continue; }
+				let offset = locate(&text, value);
+				let color = match from_hex(value) {
+					Ok(color) => color,
+					Err(_) => {
+						options.recover(parse_error(
+							offset, 
+							"a hexadecimal color", 
+							format!("{:?}", value)))?;
+						continue;
+					},
+				};
+				if line > Line::max_value() as usize 
+					|| column > Column::max_value() as usize
+				{
+					options.recover(parse_error(
+						offset, 
+						"a color within the page bounds", 
+						format!("line {} column {}", line, column)))?;
+					continue;
+				}
+				if !options.admits(palette.data.len(), offset)? {
+					break;
+				}
+
+				let address = Address::new(
+					page.page, 
+					line as Line, 
+					column as Column);
+				let cell = palette.data.create_cell(address)?;
+				*cell.borrow_mut() = Expression::Color(color);
+			}
+		}
+
+		// Read names.
+		for (line, name) in page.line_names {
+			match line.parse::<Line>() {
+				Ok(line) => palette.data.set_name(
+					Reference::line_of(&Address::new(page.page, line, 0)), 
+					name),
+				Err(_) => options.recover(parse_error(
+					locate(&text, &line), 
+					"a line index", 
+					format!("{:?}", line)))?,
+			}
+		}
+		for (slot, name) in page.names {
+			match format!("{}:{}", page.page, slot).parse::<Address>() {
+				Ok(address) => palette.data.set_slot_name(address, name),
+				Err(_) => options.recover(parse_error(
+					locate(&text, &slot), 
+					"a slot in `line:column` form", 
+					format!("{:?}", slot)))?,
+			}
+		}
+	}
+
+	// Read ramps.
+	let mut ramps = Vec::with_capacity(repr.ramps.len());
+	for ramp in repr.ramps {
+		let anchors = ramp.anchors.iter()
+			.map(|anchor| anchor.parse::<Address>())
+			.collect::<Result<Vec<_>>>();
+		let location = ramp.location.parse::<Address>();
+		match (anchors, location) {
+			(Ok(anchors), Ok(location)) => ramps.push(
+				RampSpec::new(anchors, ramp.steps, location)
+					.interpolation(ramp.interpolation)
+					.easing(ramp.easing)),
+			_ => options.recover(parse_error(
+				locate(&text, &ramp.location), 
+				"a ramp with addresses in `page:line:column` form", 
+				format!("{:?}", ramp.anchors)))?,
+		}
+	}
+	palette.data.set_ramps(ramps);
+	Ok(palette)
+}
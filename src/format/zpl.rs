@@ -28,18 +28,38 @@
 //!
 ////////////////////////////////////////////////////////////////////////////////
 
+use ::Palette;
 use address::{
+	Address,
 	Reference,
 	Page, Line, Column};
 use data::Data;
+use depth::{expand, reduce};
+use expression::Expression;
+use templates::Template;
+use format::{
+	Format,
+	OffsetReader,
+	ParseOptions,
+	describe_bytes,
+	parse_error,
+};
+use format::report::{Loss, Violation, WriteReport};
+use result::Result;
 
+// Non-local imports.
+use color::Color;
 
-const ZPL_COLOR_DEPTH_SCALE: f32 = 0.25;
+// Standard imports.
+use std::io;
+use std::io::Read;
 
-const ZPL_HEADER : [u8;12] = [
+
+pub(crate) const ZPL_COLOR_DEPTH: u8 = 6;
+
+const ZPL_HEADER : [u8;8] = [
 	0x43, 0x53, 0x45, 0x54, 
 	0x04, 0x00, 0x01, 0x00, 
-	0x9c, 0x0d, 0x05, 0x00
 ];
 
 const ZPL_FOOTER_A : [u8;4] = [
@@ -74,9 +94,24 @@ const ZPL_FOOTER_E : [u8;36] = [
 	0x3f, 0x07, 0x07, 0x07
 ];
 
-const ZPL_PAGE_LIMIT: Page =  0x203;
-const ZPL_DEFAULT_LINE_LIMIT: Line =  16;
-const ZPL_DEFAULT_COLUMN_LIMIT: Column =  16;
+const ZPL_PALETTE_NAME_SIZE: usize = 17;
+
+pub(crate) const ZPL_PAGE_LIMIT: Page =  0x203;
+pub(crate) const ZPL_DEFAULT_LINE_LIMIT: Line =  16;
+pub(crate) const ZPL_DEFAULT_COLUMN_LIMIT: Column =  16;
+
+const MAIN_LINE_LIMIT: Line = 14;
+const LEVEL_LINE_LIMIT: Line = 13;
+
+/// The maximum number of colors in a ZPL palette.
+pub(crate) const ZPL_COLOR_LIMIT: usize = (MAIN_LINE_LIMIT as usize 
+	+ LEVEL_PAGE_LIMIT as usize * LEVEL_LINE_LIMIT as usize
+	+ (ZPL_PAGE_LIMIT - LEVEL_PAGE_LIMIT - 1) as usize 
+	* ZPL_DEFAULT_LINE_LIMIT as usize)
+	* ZPL_DEFAULT_COLUMN_LIMIT as usize;
+
+/// The metadata key holding the Zelda Classic name of a level page.
+pub const ZPL_LEVEL_NAME_KEY: &'static str = "zpl.level_name";
 
 const MAIN_PAGE_LIMIT: Page = 0;
 const LEVEL_PAGE_LIMIT: Page = 512;
@@ -101,12 +136,7 @@ fn level_label(line: Line) -> String {
 
 /// Called when a new palette is created. Initializes the palette data.
 pub fn initialize(data: &mut Data) {
-	data.set_label(Reference::all(), "ZplPalette 1.0.0");
-	data.maximum_page_count = ZPL_PAGE_LIMIT;
-	data.default_line_count = ZPL_DEFAULT_LINE_LIMIT;
-	data.default_column_count = ZPL_DEFAULT_COLUMN_LIMIT;
-	data.prepare_new_page = prepare_new_page;
-	data.prepare_new_line = prepare_new_line;
+	Template::zelda_classic().initialize(data);
 }
 
 	
@@ -117,9 +147,10 @@ pub fn prepare_new_page(data: &mut Data, group: &Reference) {
 	if page <= MAIN_PAGE_LIMIT {
 		data.set_name(group.clone(), "Main");
 		data.set_label(group.clone(), "Level 0");
-		data.set_line_count(group.clone(), 14);
+		data.set_line_count(group.clone(), MAIN_LINE_LIMIT);
 	} else if page <= LEVEL_PAGE_LIMIT {
 		data.set_label(group.clone(), format!("Level {}", page));
+		data.set_line_count(group.clone(), LEVEL_LINE_LIMIT);
 	} else {
 		data.set_label(group.clone(), format!("Sprite Page {}", page));
 	}
@@ -150,26 +181,200 @@ pub fn prepare_new_line(data: &mut Data, group: &Reference) {
 
 
 
-	// fn write_palette<W>(&self, out_buf: &mut W) -> io::Result<()> 
-	// 	where W: io::Write
-	// {
-	// 	// Write header.
-	// 	out_buf.write(&ZPL_HEADER)?;
+/// Returns the number of lines on the given page.
+#[cfg_attr(feature = "cargo-clippy", allow(absurd_extreme_comparisons))]
+fn page_line_count(data: &Data, page: Page) -> Line {
+	let default = if page <= MAIN_PAGE_LIMIT {
+		MAIN_LINE_LIMIT
+	} else if page <= LEVEL_PAGE_LIMIT {
+		LEVEL_LINE_LIMIT
+	} else {
+		ZPL_DEFAULT_LINE_LIMIT
+	};
+	data.metadata
+		.get(&Reference::page_of(&Address::new(page, 0, 0)))
+		.map_or(default, |meta| meta.line_count)
+}
+
+
+/// Returns whether the given address lies outside of the ZPL layout.
+fn is_reserved(data: &Data, address: Address) -> bool {
+	address.page >= ZPL_PAGE_LIMIT 
+		|| address.line >= page_line_count(data, address.page)
+		|| address.column >= ZPL_DEFAULT_COLUMN_LIMIT
+}
+
+
+/// Records the ZPL-specific problems which prevent writing the palette data.
+pub(crate) fn validate(data: &Data, violations: &mut Vec<Violation>) {
+	for (address, _) in data.colors() {
+		let violation = Violation::Reserved(address);
+		if is_reserved(data, address) && !violations.contains(&violation) {
+			violations.push(violation);
+		}
+	}
+}
+
+
+/// Records the ZPL-specific data lost when writing the palette.
+pub(crate) fn check(palette: &Palette, report: &mut WriteReport) {
+	for (address, _) in palette.data.colors() {
+		if is_reserved(&palette.data, address) {
+			report.push(Loss::Dropped(address));
+		} else {
+			report.push(Loss::DepthReduced(ZPL_COLOR_DEPTH));
+		}
+	}
+
+	for page in 1..=LEVEL_PAGE_LIMIT {
+		let group = Reference::page_of(&Address::new(page, 0, 0));
+		if palette.data.name(&group)
+			.map_or(false, |name| name.len() >= ZPL_PALETTE_NAME_SIZE)
+		{
+			report.push(Loss::NameTruncated(group));
+		}
+	}
+}
+
+
+/// Writes the palette to the given buffer.
+pub fn write_palette<W>(palette: &Palette, out_buf: &mut W) -> Result<()> 
+	where W: io::Write
+{
+	// Write the section body first, so that its size can be written in the
+	// header.
+	let mut section = Vec::new();
+
+	// Write all pages in sequence.
+	for page in 0..ZPL_PAGE_LIMIT {
+		for line in 0..page_line_count(&palette.data, page) {
+			for column in 0..ZPL_DEFAULT_COLUMN_LIMIT {
+				let address = Address::new(page, line, column);
+				let rgb = palette.color(address)
+					.map_or([0, 0, 0], |c| [c.r(), c.g(), c.b()]);
+				for &channel in &rgb {
+					section.push(reduce(channel, ZPL_COLOR_DEPTH));
+				}
+			}
+		}
+	}
 
-	// 	// Write all pages in sequence.
+	// Write level names. Level names are stored for the level pages only.
+	for page in 1..=LEVEL_PAGE_LIMIT {
+		let mut name_buf = [0u8; ZPL_PALETTE_NAME_SIZE];
+		if let Some(name) = palette.data
+			.name(&Reference::page_of(&Address::new(page, 0, 0))) 
+		{
+			// Leave room for the null terminator.
+			let bytes = name.as_bytes();
+			let len = bytes.len().min(ZPL_PALETTE_NAME_SIZE - 1);
+			name_buf[..len].copy_from_slice(&bytes[..len]);
+		}
+		section.extend_from_slice(&name_buf);
+	}
+
+	// Write footer.
+	section.extend_from_slice(&ZPL_FOOTER_A);
+	for _ in 1..109 {
+		section.extend_from_slice(&ZPL_FOOTER_B);
+	}
+	section.extend_from_slice(&ZPL_FOOTER_C);
+	for _ in 1..79 {
+		section.extend_from_slice(&ZPL_FOOTER_D);
+	}
+	section.extend_from_slice(&ZPL_FOOTER_E);
 
-	// 	// Write level names.
+	// Write header.
+	let size = section.len() as u32;
+	out_buf.write_all(&ZPL_HEADER)?;
+	out_buf.write_all(&[
+		size as u8,
+		(size >> 8) as u8,
+		(size >> 16) as u8,
+		(size >> 24) as u8,
+	])?;
+	out_buf.write_all(&section)?;
+	Ok(())
+}
 
-	// 	// Write footer.
-	// 	out_buf.write(&ZPL_FOOTER_A)?;
-	// 	for _ in 1..109 {
-	// 		out_buf.write(&ZPL_FOOTER_B)?;
-	// 	}
-	// 	out_buf.write(&ZPL_FOOTER_C)?;
-	// 	for _ in 1..79 {
-	// 		out_buf.write(&ZPL_FOOTER_D)?;
-	// 	}
-	// 	out_buf.write(&ZPL_FOOTER_E)?;
-	// 	Ok(())
-	// }
 
+/// Reads a palette from the given buffer, which may hold a ZPL file or the 
+/// `CSET` section of a quest file.
+pub fn read_palette<R>(in_buf: &mut R, options: &ParseOptions) 
+	-> Result<Palette>
+	where R: io::Read
+{
+	let mut in_buf = OffsetReader::new(in_buf);
+	let mut palette = Palette::new("", Format::Zpl, true);
+	let result = read_colors(&mut in_buf, &mut palette, options);
+	options.finish(in_buf.offset(), palette, result)
+}
+
+/// Reads the colors and level names from the given buffer into the palette.
+fn read_colors<R>(
+	in_buf: &mut OffsetReader<R>,
+	palette: &mut Palette,
+	options: &ParseOptions)
+	-> Result<()>
+	where R: io::Read
+{
+	// Read header. The section size is not checked, as the footer is not 
+	// read.
+	let mut header = [0u8; 12];
+	in_buf.read_exact(&mut header)?;
+	if header[..4] != ZPL_HEADER[..4] {
+		return Err(parse_error(
+			0, 
+			"CSET section identifier", 
+			describe_bytes(&header[..4])));
+	}
+	if header[4..8] != ZPL_HEADER[4..8] {
+		options.recover(parse_error(
+			4, 
+			"CSET section version 4", 
+			describe_bytes(&header[4..8])))?;
+	}
+
+	// Read all pages in sequence.
+	let mut rgb = [0u8; 3];
+	for page in 0..ZPL_PAGE_LIMIT {
+		for line in 0..page_line_count(&palette.data, page) {
+			for column in 0..ZPL_DEFAULT_COLUMN_LIMIT {
+				let offset = in_buf.offset();
+				in_buf.read_exact(&mut rgb)?;
+				if rgb.iter().any(|&channel| channel > 0x3F) {
+					options.recover(parse_error(
+						offset, 
+						"6-bit color channels", 
+						describe_bytes(&rgb)))?;
+				}
+				if !options.admits(palette.data.len(), offset)? {
+					return Ok(());
+				}
+
+				let color = Color::new(
+					expand(rgb[0] & 0x3F, ZPL_COLOR_DEPTH),
+					expand(rgb[1] & 0x3F, ZPL_COLOR_DEPTH),
+					expand(rgb[2] & 0x3F, ZPL_COLOR_DEPTH));
+				let address = Address::new(page, line, column);
+				let cell = palette.data.create_cell(address)?;
+				*cell.borrow_mut() = Expression::Color(color);
+			}
+		}
+	}
+
+	// Read level names.
+	for page in 1..=LEVEL_PAGE_LIMIT {
+		let mut name_buf = [0u8; ZPL_PALETTE_NAME_SIZE];
+		in_buf.read_exact(&mut name_buf)?;
+		let len = name_buf.iter()
+			.position(|&byte| byte == 0)
+			.unwrap_or(ZPL_PALETTE_NAME_SIZE);
+		if len > 0 {
+			palette.data.set_name(
+				Reference::page_of(&Address::new(page, 0, 0)), 
+				String::from_utf8_lossy(&name_buf[..len]).into_owned());
+		}
+	}
+	Ok(())
+}
@@ -0,0 +1,397 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides the fixed palettes of classic hardware, for use as quantization 
+//! targets and as starting points for new palettes.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use data::Data;
+use space::Metric;
+use templates::Template;
+
+// Non-local imports.
+use color::Color;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Color tables
+////////////////////////////////////////////////////////////////////////////////
+/// The 64 colors of the NES NTSC picture processing unit (2C02), in hardware
+/// index order.
+pub const NES_NTSC_COLORS: &[(u8, u8, u8)] = &[
+	(0x7C, 0x7C, 0x7C), (0x00, 0x00, 0xFC), (0x00, 0x00, 0xBC),
+	(0x44, 0x28, 0xBC), (0x94, 0x00, 0x84), (0xA8, 0x00, 0x20),
+	(0xA8, 0x10, 0x00), (0x88, 0x14, 0x00), (0x50, 0x30, 0x00),
+	(0x00, 0x78, 0x00), (0x00, 0x68, 0x00), (0x00, 0x58, 0x00),
+	(0x00, 0x40, 0x58), (0x00, 0x00, 0x00), (0x00, 0x00, 0x00),
+	(0x00, 0x00, 0x00), (0xBC, 0xBC, 0xBC), (0x00, 0x78, 0xF8),
+	(0x00, 0x58, 0xF8), (0x68, 0x44, 0xFC), (0xD8, 0x00, 0xCC),
+	(0xE4, 0x00, 0x58), (0xF8, 0x38, 0x00), (0xE4, 0x5C, 0x10),
+	(0xAC, 0x7C, 0x00), (0x00, 0xB8, 0x00), (0x00, 0xA8, 0x00),
+	(0x00, 0xA8, 0x44), (0x00, 0x88, 0x88), (0x00, 0x00, 0x00),
+	(0x00, 0x00, 0x00), (0x00, 0x00, 0x00), (0xF8, 0xF8, 0xF8),
+	(0x3C, 0xBC, 0xFC), (0x68, 0x88, 0xFC), (0x98, 0x78, 0xF8),
+	(0xF8, 0x78, 0xF8), (0xF8, 0x58, 0x98), (0xF8, 0x78, 0x58),
+	(0xFC, 0xA0, 0x44), (0xF8, 0xB8, 0x00), (0xB8, 0xF8, 0x18),
+	(0x58, 0xD8, 0x54), (0x58, 0xF8, 0x98), (0x00, 0xE8, 0xD8),
+	(0x78, 0x78, 0x78), (0x00, 0x00, 0x00), (0x00, 0x00, 0x00),
+	(0xFC, 0xFC, 0xFC), (0xA4, 0xE4, 0xFC), (0xB8, 0xB8, 0xF8),
+	(0xD8, 0xB8, 0xF8), (0xF8, 0xB8, 0xF8), (0xF8, 0xA4, 0xC0),
+	(0xF0, 0xD0, 0xB0), (0xFC, 0xE0, 0xA8), (0xF8, 0xD8, 0x78),
+	(0xD8, 0xF8, 0x78), (0xB8, 0xF8, 0xB8), (0xB8, 0xF8, 0xD8),
+	(0x00, 0xFC, 0xFC), (0xF8, 0xD8, 0xF8), (0x00, 0x00, 0x00),
+	(0x00, 0x00, 0x00),
+];
+
+/// The 64 colors of the NES PAL picture processing unit (2C07), in hardware
+/// index order. These are decoded from the signal levels of the 2C07, whose 
+/// hues are rotated by half a hue step (15 degrees) from those of the 2C02.
+pub const NES_PAL_COLORS: &[(u8, u8, u8)] = &[
+	(0x6C, 0x6C, 0x6C), (0x00, 0x41, 0x7E), (0x00, 0x25, 0xAF),
+	(0x31, 0x08, 0xBE), (0x5B, 0x00, 0xA8), (0x77, 0x00, 0x71),
+	(0x7F, 0x00, 0x23), (0x72, 0x15, 0x00), (0x52, 0x32, 0x00),
+	(0x26, 0x4B, 0x00), (0x00, 0x5C, 0x00), (0x00, 0x60, 0x00),
+	(0x00, 0x56, 0x34), (0x00, 0x00, 0x00), (0x00, 0x00, 0x00),
+	(0x00, 0x00, 0x00), (0xB2, 0xB2, 0xB2), (0x00, 0x7A, 0xC8),
+	(0x2B, 0x57, 0xFF), (0x66, 0x35, 0xFF), (0x9B, 0x1D, 0xFF),
+	(0xC0, 0x17, 0xB8), (0xCB, 0x26, 0x55), (0xBA, 0x43, 0x00),
+	(0x91, 0x67, 0x00), (0x59, 0x88, 0x00), (0x1E, 0x9D, 0x00),
+	(0x00, 0xA2, 0x00), (0x00, 0x95, 0x6A), (0x00, 0x00, 0x00),
+	(0x00, 0x00, 0x00), (0x00, 0x00, 0x00), (0xFF, 0xFF, 0xFF),
+	(0x4F, 0xC8, 0xFF), (0x7C, 0xA6, 0xFF), (0xB4, 0x85, 0xFF),
+	(0xE9, 0x6F, 0xFF), (0xFF, 0x69, 0xFF), (0xFF, 0x77, 0xA3),
+	(0xFF, 0x93, 0x3D), (0xDE, 0xB5, 0x00), (0xA7, 0xD5, 0x00),
+	(0x70, 0xEA, 0x00), (0x48, 0xEF, 0x51), (0x3B, 0xE2, 0xB8),
+	(0x57, 0x57, 0x57), (0x00, 0x00, 0x00), (0x00, 0x00, 0x00),
+	(0xFF, 0xFF, 0xFF), (0xBB, 0xE9, 0xFF), (0xCB, 0xDC, 0xFF),
+	(0xE1, 0xCF, 0xFF), (0xF6, 0xC7, 0xFF), (0xFF, 0xC5, 0xFF),
+	(0xFF, 0xCA, 0xDB), (0xFF, 0xD4, 0xB4), (0xF2, 0xE2, 0x99),
+	(0xDC, 0xEE, 0x90), (0xC7, 0xF7, 0x9D), (0xB8, 0xF8, 0xBC),
+	(0xB4, 0xF4, 0xE3), (0xBE, 0xBE, 0xBE), (0x00, 0x00, 0x00),
+	(0x00, 0x00, 0x00),
+];
+
+/// The 16 colors of the CGA, in hardware index order. These are also the 
+/// default colors of the EGA and the first 16 default colors of the VGA.
+pub const CGA_COLORS: &[(&str, u8, u8, u8)] = &[
+	("Black", 0x00, 0x00, 0x00),
+	("Blue", 0x00, 0x00, 0xAA),
+	("Green", 0x00, 0xAA, 0x00),
+	("Cyan", 0x00, 0xAA, 0xAA),
+	("Red", 0xAA, 0x00, 0x00),
+	("Magenta", 0xAA, 0x00, 0xAA),
+	("Brown", 0xAA, 0x55, 0x00),
+	("Light Gray", 0xAA, 0xAA, 0xAA),
+	("Dark Gray", 0x55, 0x55, 0x55),
+	("Light Blue", 0x55, 0x55, 0xFF),
+	("Light Green", 0x55, 0xFF, 0x55),
+	("Light Cyan", 0x55, 0xFF, 0xFF),
+	("Light Red", 0xFF, 0x55, 0x55),
+	("Light Magenta", 0xFF, 0x55, 0xFF),
+	("Yellow", 0xFF, 0xFF, 0x55),
+	("White", 0xFF, 0xFF, 0xFF),
+];
+
+/// The 16 colors of the Commodore 64 VIC-II, in hardware index order, as 
+/// measured by Philip "Pepto" Timmermann.
+pub const C64_COLORS: &[(&str, u8, u8, u8)] = &[
+	("Black", 0x00, 0x00, 0x00),
+	("White", 0xFF, 0xFF, 0xFF),
+	("Red", 0x68, 0x37, 0x2B),
+	("Cyan", 0x70, 0xA4, 0xB2),
+	("Purple", 0x6F, 0x3D, 0x86),
+	("Green", 0x58, 0x8D, 0x43),
+	("Blue", 0x35, 0x28, 0x79),
+	("Yellow", 0xB8, 0xC7, 0x6F),
+	("Orange", 0x6F, 0x4F, 0x25),
+	("Brown", 0x43, 0x39, 0x00),
+	("Light Red", 0x9A, 0x67, 0x59),
+	("Dark Grey", 0x44, 0x44, 0x44),
+	("Grey", 0x6C, 0x6C, 0x6C),
+	("Light Green", 0x9A, 0xD2, 0x84),
+	("Light Blue", 0x6C, 0x5E, 0xB5),
+	("Light Grey", 0x95, 0x95, 0x95),
+];
+
+/// The 4 shades of the original Game Boy display, from lightest to darkest.
+pub const GAMEBOY_COLORS: &[(&str, u8, u8, u8)] = &[
+	("White", 0x9B, 0xBC, 0x0F),
+	("Light", 0x8B, 0xAC, 0x0F),
+	("Dark", 0x30, 0x62, 0x30),
+	("Black", 0x0F, 0x38, 0x0F),
+];
+
+/// The 6-bit channel levels of the hue rings of the default VGA palette. Each
+/// entry gives the maximum, minimum, and intermediate levels of a ring, with 
+/// rings ordered by decreasing intensity and then by decreasing saturation.
+const VGA_RINGS: [(u8, u8, [u8; 3]); 9] = [
+	(63, 0, [16, 31, 47]), (63, 31, [39, 47, 55]), (63, 45, [49, 54, 58]),
+	(28, 0, [7, 14, 21]), (28, 14, [17, 21, 24]), (28, 20, [22, 24, 26]),
+	(16, 0, [4, 8, 12]), (16, 8, [10, 12, 14]), (16, 11, [12, 13, 15]),
+];
+
+/// The 6-bit levels of the gray ramp of the default VGA palette.
+const VGA_GRAYS: [u8; 16] = [
+	0, 5, 8, 11, 14, 17, 20, 24, 28, 32, 36, 40, 45, 50, 56, 63,
+];
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// HardwarePalette
+////////////////////////////////////////////////////////////////////////////////
+/// A read-only palette of fixed hardware colors.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::hardware;
+/// use palette::space::Metric;
+/// use palette::Color;
+///
+/// let c64 = hardware::c64();
+/// let (index, color) = c64.nearest(Color::new(250, 250, 240), Metric::Rgb)
+/// 	.unwrap();
+///
+/// assert_eq!(c64.len(), 16);
+/// assert_eq!(index, 1);
+/// assert_eq!(color, Color::new(255, 255, 255));
+/// assert_eq!(c64.color_name(index), Some("White"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct HardwarePalette {
+	/// The name of the hardware.
+	name: &'static str,
+	/// The colors of the palette, in hardware index order.
+	colors: Vec<Color>,
+	/// The names of the colors, if they have names.
+	color_names: Vec<&'static str>,
+	/// The number of colors in each line of the palette's data.
+	columns: u8,
+}
+
+
+impl HardwarePalette {
+	/// Creates a new HardwarePalette from the given unnamed colors.
+	fn from_table(name: &'static str, table: &[(u8, u8, u8)], columns: u8)
+		-> Self
+	{
+		HardwarePalette {
+			name: name,
+			colors: table.iter()
+				.map(|&(r, g, b)| Color::new(r, g, b))
+				.collect(),
+			color_names: Vec::new(),
+			columns: columns,
+		}
+	}
+
+	/// Creates a new HardwarePalette from the given named colors.
+	fn from_named_table(
+		name: &'static str, 
+		table: &[(&'static str, u8, u8, u8)],
+		columns: u8)
+		-> Self
+	{
+		HardwarePalette {
+			name: name,
+			colors: table.iter()
+				.map(|&(_, r, g, b)| Color::new(r, g, b))
+				.collect(),
+			color_names: table.iter().map(|&(name, _, _, _)| name).collect(),
+			columns: columns,
+		}
+	}
+
+	/// Returns the name of the hardware.
+	pub fn name(&self) -> &'static str {
+		self.name
+	}
+
+	/// Returns the number of colors in the palette.
+	pub fn len(&self) -> usize {
+		self.colors.len()
+	}
+
+	/// Returns whether the palette has no colors.
+	pub fn is_empty(&self) -> bool {
+		self.colors.is_empty()
+	}
+
+	/// Returns the color with the given hardware index.
+	pub fn get(&self, index: usize) -> Option<Color> {
+		self.colors.get(index).cloned()
+	}
+
+	/// Returns the name of the color with the given hardware index, or None 
+	/// if the hardware does not name its colors.
+	pub fn color_name(&self, index: usize) -> Option<&'static str> {
+		self.color_names.get(index).cloned()
+	}
+
+	/// Returns the colors of the palette, in hardware index order.
+	pub fn colors(&self) -> &[Color] {
+		&self.colors
+	}
+
+	/// Returns the hardware index and color of the entry nearest to the given
+	/// color, using the given metric, or None if the palette is empty. Ties 
+	/// are resolved in favor of the lowest index.
+	pub fn nearest(&self, color: Color, metric: Metric) 
+		-> Option<(usize, Color)> 
+	{
+		let mut best: Option<(usize, Color, f32)> = None;
+		for (index, &entry) in self.colors.iter().enumerate() {
+			let distance = metric.distance(color, entry);
+			if best.map_or(true, |(_, _, d)| distance < d) {
+				best = Some((index, entry, distance));
+			}
+		}
+		best.map(|(index, entry, _)| (index, entry))
+	}
+
+	/// Returns new palette data holding the colors of the palette in hardware
+	/// index order, on a single page. The data may be used as a quantization 
+	/// target or as the starting point of a new palette.
+	pub fn data(&self) -> Data {
+		let columns = self.columns as usize;
+		let lines = (self.colors.len() + columns - 1) / columns;
+		let mut data = Template::new(1, lines as u8, self.columns)
+			.label(self.name)
+			.data();
+		for (index, &color) in self.colors.iter().enumerate() {
+			let address = data.add_color(color)
+				.expect("add hardware color");
+			if let Some(name) = self.color_name(index) {
+				data.set_slot_name(address, name);
+			}
+		}
+		data
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Hardware palettes
+////////////////////////////////////////////////////////////////////////////////
+/// Returns the palette of the NES NTSC picture processing unit (2C02).
+pub fn nes() -> HardwarePalette {
+	HardwarePalette::from_table("NES (NTSC)", NES_NTSC_COLORS, 16)
+}
+
+/// Returns the palette of the NES PAL picture processing unit (2C07). The 
+/// 2C07 also swaps the red and green color emphasis bits of the 2C02; 
+/// emphasized colors are not represented here.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::hardware;
+///
+/// let (ntsc, pal) = (hardware::nes(), hardware::nes_pal());
+/// assert_eq!(pal.len(), ntsc.len());
+/// assert!(pal.colors() != ntsc.colors());
+/// ```
+pub fn nes_pal() -> HardwarePalette {
+	HardwarePalette::from_table("NES (PAL)", NES_PAL_COLORS, 16)
+}
+
+/// Returns the 16 color palette of the CGA.
+pub fn cga() -> HardwarePalette {
+	HardwarePalette::from_named_table("CGA", CGA_COLORS, 16)
+}
+
+/// Returns the default 16 color palette of the EGA.
+pub fn ega() -> HardwarePalette {
+	HardwarePalette::from_named_table("EGA", CGA_COLORS, 16)
+}
+
+/// Returns the default 256 color palette of the VGA: the 16 CGA colors, a 
+/// ramp of 16 grays, nine rings of 24 hues, and 8 unused blacks.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::hardware;
+/// use palette::Color;
+///
+/// let vga = hardware::vga();
+///
+/// assert_eq!(vga.len(), 256);
+/// assert_eq!(vga.get(31), Some(Color::new(255, 255, 255)));
+/// assert_eq!(vga.get(32), Some(Color::new(0, 0, 255)));
+/// assert_eq!(vga.get(40), Some(Color::new(255, 0, 0)));
+/// ```
+pub fn vga() -> HardwarePalette {
+	// Expands a 6-bit DAC level to 8 bits.
+	let expand = |level: u8| (level << 2) | (level >> 4);
+
+	let mut colors: Vec<Color> = CGA_COLORS.iter()
+		.map(|&(_, r, g, b)| Color::new(r, g, b))
+		.collect();
+	colors.extend(VGA_GRAYS.iter()
+		.map(|&level| Color::new(expand(level), expand(level), expand(level))));
+
+	for &(max, min, [q1, q2, q3]) in &VGA_RINGS {
+		// Each ring runs from blue through magenta, red, yellow, green, and 
+		// cyan, back toward blue.
+		let up = [min, q1, q2, q3];
+		let down = [max, q3, q2, q1];
+		for i in 0..4 { colors.push(Color::new(up[i], min, max)); }
+		for i in 0..4 { colors.push(Color::new(max, min, down[i])); }
+		for i in 0..4 { colors.push(Color::new(max, up[i], min)); }
+		for i in 0..4 { colors.push(Color::new(down[i], max, min)); }
+		for i in 0..4 { colors.push(Color::new(min, max, up[i])); }
+		for i in 0..4 { colors.push(Color::new(min, down[i], max)); }
+	}
+	let ring_start = CGA_COLORS.len() + VGA_GRAYS.len();
+	for color in &mut colors[ring_start..] {
+		*color = Color::new(
+			expand(color.r()), 
+			expand(color.g()), 
+			expand(color.b()));
+	}
+	colors.extend((0..8).map(|_| Color::new(0, 0, 0)));
+
+	HardwarePalette {
+		name: "VGA",
+		colors: colors,
+		color_names: Vec::new(),
+		columns: 16,
+	}
+}
+
+/// Returns the palette of the Commodore 64.
+pub fn c64() -> HardwarePalette {
+	HardwarePalette::from_named_table("C64", C64_COLORS, 16)
+}
+
+/// Returns the palette of the original Game Boy (DMG).
+pub fn gameboy() -> HardwarePalette {
+	HardwarePalette::from_named_table("Game Boy", GAMEBOY_COLORS, 4)
+}
@@ -0,0 +1,253 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides parsing and formatting of hexadecimal colors, such as `#1a2b3c`
+//! and `#abc`.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use result::{Error, Result};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+
+/// Returns the hexadecimal digits of the given text, stripping surrounding 
+/// whitespace and a leading `#`, or an error if any other characters remain 
+/// or the number of digits is not one of the given lengths.
+fn digits<'t>(text: &'t str, lengths: &[usize]) -> Result<&'t str> {
+	let trimmed = text.trim();
+	let digits = if trimmed.starts_with('#') { 
+		&trimmed[1..] 
+	} else { 
+		trimmed 
+	};
+	if digits.chars().all(|c| c.is_digit(16)) 
+		&& lengths.contains(&digits.len())
+	{
+		Ok(digits)
+	} else {
+		Err(Error::InvalidHexColor(text.to_owned()))
+	}
+}
+
+/// Parses a color from six hexadecimal digits in `RRGGBB` order, or from 
+/// eight digits in `AARRGGBB` order with the alpha discarded. A leading `#` 
+/// and surrounding whitespace are ignored.
+///
+/// # Errors
+///
+/// Returns an `InvalidHexColor` error if the text is not a valid hexadecimal
+/// color.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::hex::from_hex;
+/// use palette::Color;
+///
+/// assert_eq!(from_hex("#1a2b3c").unwrap(), Color::new(0x1A, 0x2B, 0x3C));
+/// assert_eq!(from_hex("FF1A2B3C").unwrap(), Color::new(0x1A, 0x2B, 0x3C));
+/// assert!(from_hex("#1a2b3").is_err());
+/// ```
+pub fn from_hex(text: &str) -> Result<Color> {
+	let digits = digits(text, &[6, 8])?;
+	let value = u32::from_str_radix(digits, 16)
+		.map_err(|_| Error::InvalidHexColor(text.to_owned()))?;
+	Ok(Color::new((value >> 16) as u8, (value >> 8) as u8, value as u8))
+}
+
+/// Parses a color from three hexadecimal digits in `RGB` order, where each 
+/// digit is repeated to form the channel. A leading `#` and surrounding 
+/// whitespace are ignored.
+///
+/// # Errors
+///
+/// Returns an `InvalidHexColor` error if the text is not a valid short 
+/// hexadecimal color.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::hex::from_hex_short;
+/// use palette::Color;
+///
+/// assert_eq!(from_hex_short("#abc").unwrap(), Color::new(0xAA, 0xBB, 0xCC));
+/// ```
+pub fn from_hex_short(text: &str) -> Result<Color> {
+	let digits = digits(text, &[3])?;
+	let value = u16::from_str_radix(digits, 16)
+		.map_err(|_| Error::InvalidHexColor(text.to_owned()))?;
+	let expand = |nibble: u16| (nibble & 0xF) as u8 * 0x11;
+	Ok(Color::new(expand(value >> 8), expand(value >> 4), expand(value)))
+}
+
+/// Returns the given color as a lowercase `#rrggbb` hexadecimal string.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::hex::to_hex_string;
+/// use palette::Color;
+///
+/// assert_eq!(to_hex_string(Color::new(0x1A, 0x2B, 0x3C)), "#1a2b3c");
+/// ```
+pub fn to_hex_string(color: Color) -> String {
+	Hex(color).to_string()
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Hex
+////////////////////////////////////////////////////////////////////////////////
+/// A wrapper around a `Color` which is parsed from and displayed as a 
+/// hexadecimal color. Parsing accepts the three, six, and eight digit forms
+/// accepted by `from_hex_short` and `from_hex`.
+///
+/// Because `Color` is defined in the `color` crate, conversions between it and
+/// other foreign types cannot be implemented here. `Hex` provides them 
+/// instead: it converts to and from `[u8; 3]`, `(u8, u8, u8)`, and `0xRRGGBB`
+/// `u32` values, and from `&str` using the hexadecimal parser.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::hex::Hex;
+/// use palette::Color;
+///
+/// let Hex(color) = "#abc".parse().unwrap();
+/// assert_eq!(color, Color::new(0xAA, 0xBB, 0xCC));
+/// assert_eq!(format!("{}", Hex(color)), "#aabbcc");
+/// assert_eq!(format!("{:X}", Hex(color)), "#AABBCC");
+///
+/// let Hex(color) = Hex::from(0x1A2B3C);
+/// assert_eq!(color, Color::new(0x1A, 0x2B, 0x3C));
+/// assert_eq!(<[u8; 3]>::from(Hex(color)), [0x1A, 0x2B, 0x3C]);
+/// assert_eq!(u32::from(Hex::from((0xAA, 0xBB, 0xCC))), 0xAABBCC);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hex(pub Color);
+
+
+impl FromStr for Hex {
+	type Err = Error;
+
+	fn from_str(text: &str) -> Result<Self> {
+		from_hex(text)
+			.or_else(|_| from_hex_short(text))
+			.map(Hex)
+	}
+}
+
+
+impl fmt::Display for Hex {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "#{:02x}{:02x}{:02x}", self.0.r(), self.0.g(), self.0.b())
+	}
+}
+
+
+impl fmt::UpperHex for Hex {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "#{:02X}{:02X}{:02X}", self.0.r(), self.0.g(), self.0.b())
+	}
+}
+
+
+impl From<Color> for Hex {
+	fn from(color: Color) -> Hex {
+		Hex(color)
+	}
+}
+
+
+impl From<Hex> for Color {
+	fn from(hex: Hex) -> Color {
+		hex.0
+	}
+}
+
+
+impl From<[u8; 3]> for Hex {
+	fn from(rgb: [u8; 3]) -> Hex {
+		Hex(Color::new(rgb[0], rgb[1], rgb[2]))
+	}
+}
+
+
+impl From<Hex> for [u8; 3] {
+	fn from(hex: Hex) -> [u8; 3] {
+		[hex.0.r(), hex.0.g(), hex.0.b()]
+	}
+}
+
+
+impl From<(u8, u8, u8)> for Hex {
+	fn from((r, g, b): (u8, u8, u8)) -> Hex {
+		Hex(Color::new(r, g, b))
+	}
+}
+
+
+impl From<Hex> for (u8, u8, u8) {
+	fn from(hex: Hex) -> (u8, u8, u8) {
+		(hex.0.r(), hex.0.g(), hex.0.b())
+	}
+}
+
+
+impl From<u32> for Hex {
+	/// Converts a `0xRRGGBB` value. Any higher bits are ignored.
+	fn from(value: u32) -> Hex {
+		Hex(Color::new((value >> 16) as u8, (value >> 8) as u8, value as u8))
+	}
+}
+
+
+impl From<Hex> for u32 {
+	/// Converts to a `0xRRGGBB` value.
+	fn from(hex: Hex) -> u32 {
+		u32::from(hex.0.r()) << 16 
+			| u32::from(hex.0.g()) << 8 
+			| u32::from(hex.0.b())
+	}
+}
+
+
+impl<'a> TryFrom<&'a str> for Hex {
+	type Error = Error;
+
+	fn try_from(text: &'a str) -> Result<Hex> {
+		text.parse()
+	}
+}
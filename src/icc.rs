@@ -0,0 +1,650 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides ICC-profile-aware color conversions.
+//!
+//! Palette colors are assumed to be sRGB. This module loads RGB matrix/TRC 
+//! display profiles (the kind embedded in most images and used by most 
+//! monitors) and converts colors between them, so that a palette can be 
+//! exported for a wider gamut space such as Display P3. CMYK output profiles
+//! are supported through their `A2B0` lookup table when it is stored as a 
+//! `lut8` or `lut16` tag; other LUT-based profiles are not supported.
+//!
+//! This module requires the `icc` feature.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use data::Data;
+use result::{Error, Result};
+use space::{lab_f_inv, linear_to_srgb, srgb_to_linear, Cmyk};
+use utilities::clamped;
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::io;
+
+
+/// The name of the ICC format, used in error messages.
+const ICC_FORMAT_NAME: &'static str = "ICC";
+
+/// The number of bisection steps used to invert tone curves.
+const CURVE_ITERATIONS: usize = 24;
+
+/// The size of the ICC profile header.
+const HEADER_SIZE: usize = 128;
+
+/// The D50 reference white point of the profile connection space.
+const D50_WHITE: [f32; 3] = [0.964_2, 1.0, 0.824_9];
+
+/// The D50-adapted colorants of the sRGB color space.
+const SRGB_COLORANTS: [[f32; 3]; 3] = [
+	[0.436_074_7, 0.385_064_9, 0.143_080_4],
+	[0.222_504_5, 0.716_878_6, 0.060_616_9],
+	[0.013_932_2, 0.097_104_5, 0.714_173_3],
+];
+
+/// The D50-adapted colorants of the Display P3 color space.
+const DISPLAY_P3_COLORANTS: [[f32; 3]; 3] = [
+	[0.515_102, 0.291_965, 0.157_153],
+	[0.241_182, 0.692_236, 0.066_582],
+	[-0.001_050, 0.041_881, 0.784_378],
+];
+
+
+/// Returns a `Format` error with the given reason.
+fn format_error(reason: &str) -> Error {
+	Error::Format {format: ICC_FORMAT_NAME, reason: reason.into()}
+}
+
+/// Returns the big-endian u16 at the given offset.
+fn u16_at(bytes: &[u8], offset: usize) -> Result<u16> {
+	bytes.get(offset..offset + 2)
+		.map(|b| (b[0] as u16) << 8 | b[1] as u16)
+		.ok_or_else(|| format_error("unexpected end of profile"))
+}
+
+/// Returns the big-endian u32 at the given offset.
+fn u32_at(bytes: &[u8], offset: usize) -> Result<u32> {
+	Ok((u16_at(bytes, offset)? as u32) << 16 
+		| u16_at(bytes, offset + 2)? as u32)
+}
+
+/// Returns the s15Fixed16 number at the given offset.
+fn fixed_at(bytes: &[u8], offset: usize) -> Result<f32> {
+	Ok(u32_at(bytes, offset)? as i32 as f32 / 65536.0)
+}
+
+/// Returns the given number of unsigned samples of the given byte width, 
+/// normalized to between 0 and 1, and advances the offset past them.
+fn samples_at(bytes: &[u8], offset: &mut usize, count: usize, width: usize)
+	-> Result<Vec<f32>>
+{
+	let data = count.checked_mul(width)
+		.and_then(|len| offset.checked_add(len))
+		.and_then(|end| bytes.get(*offset..end))
+		.ok_or_else(|| format_error("lookup table out of bounds"))?;
+	*offset += data.len();
+	Ok(match width {
+		1 => data.iter().map(|&v| v as f32 / 255.0).collect(),
+		_ => data.chunks(2)
+			.map(|b| ((b[0] as u16) << 8 | b[1] as u16) as f32 / 65535.0)
+			.collect(),
+	})
+}
+
+/// Returns the data of the tag with the given signature.
+fn find_tag<'a>(bytes: &'a [u8], signature: &[u8]) -> Result<&'a [u8]> {
+	let count = u32_at(bytes, HEADER_SIZE)? as usize;
+	let table = bytes.len().saturating_sub(HEADER_SIZE + 4);
+	if count > table / 12 {
+		return Err(format_error("tag table out of bounds"));
+	}
+	for i in 0..count {
+		let entry = HEADER_SIZE + 4 + 12 * i;
+		if bytes.get(entry..entry + 4) == Some(signature) {
+			let offset = u32_at(bytes, entry + 4)? as usize;
+			let size = u32_at(bytes, entry + 8)? as usize;
+			return offset.checked_add(size)
+				.and_then(|end| bytes.get(offset..end))
+				.ok_or_else(|| format_error("tag out of bounds"));
+		}
+	}
+	Err(format_error("missing matrix/TRC tag"))
+}
+
+/// Returns the colorant of the `XYZ ` tag with the given signature.
+fn find_xyz(bytes: &[u8], signature: &[u8]) -> Result<[f32; 3]> {
+	let data = find_tag(bytes, signature)?;
+	if data.get(0..4) != Some(&b"XYZ "[..]) {
+		return Err(format_error("invalid colorant tag"));
+	}
+	Ok([fixed_at(data, 8)?, fixed_at(data, 12)?, fixed_at(data, 16)?])
+}
+
+/// Returns the product of the given matrix and vector.
+fn mul(m: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+	[
+		m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+		m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+		m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+	]
+}
+
+/// Returns the inverse of the given matrix, or None if it is singular.
+fn invert(m: &[[f32; 3]; 3]) -> Option<[[f32; 3]; 3]> {
+	let c = |r0: usize, c0: usize, r1: usize, c1: usize| 
+		m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+	let det = m[0][0] * c(1, 1, 2, 2) 
+		- m[0][1] * c(1, 0, 2, 2) 
+		+ m[0][2] * c(1, 0, 2, 1);
+	if det.abs() < 1e-9 { return None; }
+	Some([
+		[c(1, 1, 2, 2) / det, -c(0, 1, 2, 2) / det, c(0, 1, 1, 2) / det],
+		[-c(1, 0, 2, 2) / det, c(0, 0, 2, 2) / det, -c(0, 0, 1, 2) / det],
+		[c(1, 0, 2, 1) / det, -c(0, 0, 2, 1) / det, c(0, 0, 1, 1) / det],
+	])
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Curve
+////////////////////////////////////////////////////////////////////////////////
+/// A tone reproduction curve, mapping encoded channel values to linear light.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Curve {
+	/// The channel values are already linear.
+	Identity,
+	/// A pure power function with the given exponent.
+	Gamma(f32),
+	/// The sRGB transfer function.
+	Srgb,
+	/// An ICC parametric curve. The parameters are `g, a, b, c, d, e, f` of
+	/// the piecewise function `(a x + b)^g + e` for `x >= d`, and `c x + f` 
+	/// otherwise.
+	Parametric([f32; 7]),
+	/// Evenly spaced samples of the curve, linearly interpolated.
+	Table(Vec<f32>),
+}
+
+
+impl Curve {
+	/// Returns the linear value of the given encoded value.
+	pub fn to_linear(&self, x: f32) -> f32 {
+		let x = clamped(x, 0.0, 1.0);
+		match *self {
+			Curve::Identity => x,
+			Curve::Gamma(g) => x.powf(g),
+			Curve::Srgb => srgb_to_linear(x),
+			Curve::Parametric([g, a, b, c, d, e, f]) => if x >= d {
+				(a * x + b).max(0.0).powf(g) + e
+			} else {
+				c * x + f
+			},
+			Curve::Table(ref table) => {
+				if table.is_empty() { return x; }
+				let pos = x * (table.len() - 1) as f32;
+				let i = (pos.floor() as usize).min(table.len() - 1);
+				let j = (i + 1).min(table.len() - 1);
+				let t = pos - i as f32;
+				table[i] + (table[j] - table[i]) * t
+			},
+		}
+	}
+
+	/// Returns the encoded value of the given linear value.
+	pub fn to_encoded(&self, y: f32) -> f32 {
+		let y = clamped(y, 0.0, 1.0);
+		match *self {
+			Curve::Identity => y,
+			Curve::Gamma(g) => y.powf(1.0 / g),
+			Curve::Srgb => linear_to_srgb(y),
+			_ => {
+				// Tone curves are monotonic, so bisect to invert them.
+				let (mut lo, mut hi) = (0.0, 1.0);
+				for _ in 0..CURVE_ITERATIONS {
+					let mid = (lo + hi) / 2.0;
+					if self.to_linear(mid) < y { lo = mid; } else { hi = mid; }
+				}
+				(lo + hi) / 2.0
+			},
+		}
+	}
+
+	/// Parses a `curv` or `para` tag from the given bytes.
+	fn parse(bytes: &[u8]) -> Result<Curve> {
+		match bytes.get(0..4) {
+			Some(b"curv") => {
+				let count = u32_at(bytes, 8)? as usize;
+				match count {
+					0 => Ok(Curve::Identity),
+					1 => Ok(Curve::Gamma(u16_at(bytes, 12)? as f32 / 256.0)),
+					_ => (0..count)
+						.map(|i| u16_at(bytes, 12 + 2 * i)
+							.map(|v| v as f32 / 65535.0))
+						.collect::<Result<Vec<_>>>()
+						.map(Curve::Table),
+				}
+			},
+			Some(b"para") => {
+				let mut p = [1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+				let read = |i: usize| fixed_at(bytes, 12 + 4 * i);
+				match u16_at(bytes, 8)? {
+					0 => { p[0] = read(0)?; },
+					1 => {
+						p[0] = read(0)?; p[1] = read(1)?; p[2] = read(2)?;
+						p[4] = -p[2] / p[1];
+					},
+					2 => {
+						p[0] = read(0)?; p[1] = read(1)?; p[2] = read(2)?;
+						p[4] = -p[2] / p[1];
+						p[5] = read(3)?; p[6] = read(3)?;
+					},
+					n @ 3..=4 => {
+						let len = if n == 3 { 5 } else { 7 };
+						for (i, value) in p.iter_mut().take(len).enumerate() {
+							*value = read(i)?;
+						}
+					},
+					_ => return Err(format_error(
+						"unsupported parametric curve")),
+				}
+				Ok(Curve::Parametric(p))
+			},
+			_ => Err(format_error("unsupported tone curve type")),
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Profile
+////////////////////////////////////////////////////////////////////////////////
+/// An RGB matrix/TRC color profile.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Color;
+/// use palette::icc::{Profile, Transform};
+/// 
+/// let transform = Transform::new(&Profile::srgb(), &Profile::display_p3())
+/// 	.unwrap();
+/// let red = transform.convert(Color::new(255, 0, 0));
+///
+/// // Pure sRGB red lies inside the wider P3 gamut.
+/// assert!(red.r() < 255 && red.g() > 0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+	/// The matrix converting linear RGB to D50 XYZ.
+	pub to_xyz: [[f32; 3]; 3],
+	/// The red, green, and blue tone curves.
+	pub curves: [Curve; 3],
+}
+
+
+impl Profile {
+	/// Returns the sRGB profile.
+	pub fn srgb() -> Profile {
+		Profile {
+			to_xyz: SRGB_COLORANTS,
+			curves: [Curve::Srgb, Curve::Srgb, Curve::Srgb],
+		}
+	}
+
+	/// Returns the Display P3 profile.
+	pub fn display_p3() -> Profile {
+		Profile {
+			to_xyz: DISPLAY_P3_COLORANTS,
+			curves: [Curve::Srgb, Curve::Srgb, Curve::Srgb],
+		}
+	}
+
+	/// Parses an ICC profile from the given bytes.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::icc::Profile;
+	/// 
+	/// // A header whose tag table claims more tags than the profile holds.
+	/// let mut bytes = vec![0u8; 132];
+	/// bytes[16..20].copy_from_slice(b"RGB ");
+	/// bytes[36..40].copy_from_slice(b"acsp");
+	/// bytes[128..132].copy_from_slice(&[0xFF; 4]);
+	///
+	/// assert!(Profile::from_bytes(&bytes).is_err());
+	/// ```
+	pub fn from_bytes(bytes: &[u8]) -> Result<Profile> {
+		if bytes.len() < HEADER_SIZE + 4 || &bytes[36..40] != b"acsp" {
+			return Err(format_error("missing acsp signature"));
+		}
+		if &bytes[16..20] != b"RGB " {
+			return Err(format_error("profile is not an RGB profile"));
+		}
+
+		let r = find_xyz(bytes, b"rXYZ")?;
+		let g = find_xyz(bytes, b"gXYZ")?;
+		let b = find_xyz(bytes, b"bXYZ")?;
+		Ok(Profile {
+			to_xyz: [
+				[r[0], g[0], b[0]],
+				[r[1], g[1], b[1]],
+				[r[2], g[2], b[2]],
+			],
+			curves: [
+				Curve::parse(find_tag(bytes, b"rTRC")?)?,
+				Curve::parse(find_tag(bytes, b"gTRC")?)?,
+				Curve::parse(find_tag(bytes, b"bTRC")?)?,
+			],
+		})
+	}
+
+	/// Reads an ICC profile from the given buffer.
+	pub fn read<R>(in_buf: &mut R) -> Result<Profile> where R: io::Read {
+		let mut bytes = Vec::new();
+		in_buf.read_to_end(&mut bytes)?;
+		Profile::from_bytes(&bytes)
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// CmykProfile
+////////////////////////////////////////////////////////////////////////////////
+/// A CMYK output profile, which maps ink coverages to the profile connection
+/// space through the `lut8` or `lut16` lookup table of its `A2B0` tag.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Color;
+/// use palette::icc::{CmykProfile, CmykTransform, Profile};
+/// use palette::space::Cmyk;
+/// 
+/// // A lut8 table whose lightness falls with the black ink only.
+/// let mut lut = b"mft1\0\0\0\0\x04\x03\x02\0".to_vec();
+/// lut.extend(vec![0u8; 36]);
+/// for _ in 0..4 { lut.extend(0..=255u8); }
+/// for corner in 0..16 {
+/// 	let l = if corner & 1 == 0 { 255 } else { 0 };
+/// 	lut.extend(&[l, 128, 128]);
+/// }
+/// for _ in 0..3 { lut.extend(0..=255u8); }
+///
+/// let mut bytes = vec![0u8; 144];
+/// bytes[16..20].copy_from_slice(b"CMYK");
+/// bytes[20..24].copy_from_slice(b"Lab ");
+/// bytes[36..40].copy_from_slice(b"acsp");
+/// bytes[128..132].copy_from_slice(&[0, 0, 0, 1]);
+/// bytes[132..136].copy_from_slice(b"A2B0");
+/// bytes[136..140].copy_from_slice(&[0, 0, 0, 144]);
+/// bytes[140..144].copy_from_slice(&[0, 0, 
+/// 	(lut.len() >> 8) as u8, lut.len() as u8]);
+/// bytes.extend(lut);
+///
+/// let profile = CmykProfile::from_bytes(&bytes).unwrap();
+/// let transform = CmykTransform::new(&profile, &Profile::srgb()).unwrap();
+///
+/// assert_eq!(transform.convert(Cmyk::new(0.0, 0.0, 0.0, 0.0)), 
+/// 	Color::new(255, 255, 255));
+/// assert_eq!(transform.convert(Cmyk::new(0.0, 0.0, 0.0, 1.0)), 
+/// 	Color::new(0, 0, 0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CmykProfile {
+	/// The cyan, magenta, yellow, and black input curves.
+	input: [Curve; 4],
+	/// The number of grid points along each axis of the CLUT.
+	grid: usize,
+	/// The CLUT samples, three per grid point, with black varying fastest.
+	clut: Vec<f32>,
+	/// The output curves of the three connection space channels.
+	output: [Curve; 3],
+	/// Whether the connection space is CIELAB rather than XYZ.
+	lab: bool,
+	/// The factor scaling the normalized output to the encoded connection 
+	/// space range.
+	scale: f32,
+}
+
+
+impl CmykProfile {
+	/// Parses a CMYK ICC profile from the given bytes.
+	pub fn from_bytes(bytes: &[u8]) -> Result<CmykProfile> {
+		if bytes.len() < HEADER_SIZE + 4 || &bytes[36..40] != b"acsp" {
+			return Err(format_error("missing acsp signature"));
+		}
+		if &bytes[16..20] != b"CMYK" {
+			return Err(format_error("profile is not a CMYK profile"));
+		}
+		let lab = match &bytes[20..24] {
+			b"Lab " => true,
+			b"XYZ " => false,
+			_ => return Err(format_error("unsupported connection space")),
+		};
+
+		let lut = find_tag(bytes, b"A2B0")?;
+		let (width, inputs, outputs, mut offset) = match lut.get(0..4) {
+			Some(b"mft1") => (1, 256, 256, 48),
+			Some(b"mft2") => (
+				2, 
+				u16_at(lut, 48)? as usize, 
+				u16_at(lut, 50)? as usize, 
+				52),
+			_ => return Err(format_error("unsupported lookup table type")),
+		};
+		if lut.get(8..10) != Some(&[4, 3][..]) {
+			return Err(format_error("lookup table is not CMYK to PCS"));
+		}
+		let grid = lut[10] as usize;
+		if grid < 2 || inputs < 2 || outputs < 2 {
+			return Err(format_error("invalid lookup table"));
+		}
+		let points = grid.checked_pow(4)
+			.and_then(|n| n.checked_mul(3))
+			.ok_or_else(|| format_error("lookup table out of bounds"))?;
+
+		let mut table = |count| samples_at(lut, &mut offset, count, width);
+		let input = [
+			Curve::Table(table(inputs)?), 
+			Curve::Table(table(inputs)?), 
+			Curve::Table(table(inputs)?), 
+			Curve::Table(table(inputs)?),
+		];
+		let clut = table(points)?;
+		let output = [
+			Curve::Table(table(outputs)?), 
+			Curve::Table(table(outputs)?), 
+			Curve::Table(table(outputs)?),
+		];
+
+		// lut16 tags use the legacy 16-bit CIELAB encoding, and XYZ values 
+		// are u1Fixed15 numbers.
+		let scale = match (lab, width) {
+			(true, 1) => 1.0,
+			(true, _) => 65535.0 / 65280.0,
+			(false, _) => 65535.0 / 32768.0,
+		};
+		Ok(CmykProfile {
+			input: input,
+			grid: grid,
+			clut: clut,
+			output: output,
+			lab: lab,
+			scale: scale,
+		})
+	}
+
+	/// Reads a CMYK ICC profile from the given buffer.
+	pub fn read<R>(in_buf: &mut R) -> Result<CmykProfile> where R: io::Read {
+		let mut bytes = Vec::new();
+		in_buf.read_to_end(&mut bytes)?;
+		CmykProfile::from_bytes(&bytes)
+	}
+
+	/// Returns the D50 XYZ value of the given CMYK color.
+	pub fn to_xyz(&self, cmyk: Cmyk) -> [f32; 3] {
+		let ink = [cmyk.c, cmyk.m, cmyk.y, cmyk.k];
+		let mut base = [0; 4];
+		let mut frac = [0.0; 4];
+		for i in 0..4 {
+			let pos = self.input[i].to_linear(ink[i]) * (self.grid - 1) as f32;
+			base[i] = (pos.floor() as usize).min(self.grid - 2);
+			frac[i] = pos - base[i] as f32;
+		}
+
+		// Interpolate between the 16 corners of the enclosing grid cell.
+		let mut pcs = [0.0; 3];
+		for corner in 0..16 {
+			let mut weight = 1.0;
+			let mut index = 0;
+			for i in 0..4 {
+				let bit = (corner >> (3 - i)) & 1;
+				weight *= if bit == 1 { frac[i] } else { 1.0 - frac[i] };
+				index = index * self.grid + base[i] + bit;
+			}
+			for (c, value) in pcs.iter_mut().enumerate() {
+				*value += weight * self.clut[index * 3 + c];
+			}
+		}
+		for (c, value) in pcs.iter_mut().enumerate() {
+			*value = self.output[c].to_linear(*value) * self.scale;
+		}
+
+		if !self.lab { return pcs; }
+		let fy = (pcs[0] * 100.0 + 16.0) / 116.0;
+		let fx = fy + (pcs[1] * 255.0 - 128.0) / 500.0;
+		let fz = fy - (pcs[2] * 255.0 - 128.0) / 200.0;
+		[
+			D50_WHITE[0] * lab_f_inv(fx),
+			D50_WHITE[1] * lab_f_inv(fy),
+			D50_WHITE[2] * lab_f_inv(fz),
+		]
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Transform
+////////////////////////////////////////////////////////////////////////////////
+/// Converts colors from one profile to another. Colors outside of the 
+/// destination gamut are clipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transform {
+	/// The source profile curves.
+	source: [Curve; 3],
+	/// The matrix converting source linear RGB to destination linear RGB.
+	matrix: [[f32; 3]; 3],
+	/// The destination profile curves.
+	destination: [Curve; 3],
+}
+
+
+impl Transform {
+	/// Creates a new `Transform` between the given profiles.
+	pub fn new(source: &Profile, destination: &Profile) -> Result<Transform> {
+		let from_xyz = invert(&destination.to_xyz)
+			.ok_or_else(|| format_error("singular colorant matrix"))?;
+		let mut matrix = [[0.0; 3]; 3];
+		for (r, row) in matrix.iter_mut().enumerate() {
+			for (c, value) in row.iter_mut().enumerate() {
+				*value = (0..3)
+					.map(|k| from_xyz[r][k] * source.to_xyz[k][c])
+					.sum();
+			}
+		}
+		Ok(Transform {
+			source: source.curves.clone(),
+			matrix: matrix,
+			destination: destination.curves.clone(),
+		})
+	}
+
+	/// Converts the given color.
+	pub fn convert(&self, color: Color) -> Color {
+		let linear = [
+			self.source[0].to_linear(color.r() as f32 / 255.0),
+			self.source[1].to_linear(color.g() as f32 / 255.0),
+			self.source[2].to_linear(color.b() as f32 / 255.0),
+		];
+		let out = mul(&self.matrix, linear);
+		let channel = |i: usize| 
+			(self.destination[i].to_encoded(out[i]) * 255.0).round() as u8;
+		Color::new(channel(0), channel(1), channel(2))
+	}
+
+	/// Returns a copy of the given palette data with every color converted. 
+	/// Derived colors are resolved and stored as fixed colors.
+	pub fn convert_data(&self, data: &Data) -> Data {
+		data.map_colors(|color| self.convert(color))
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// CmykTransform
+////////////////////////////////////////////////////////////////////////////////
+/// Converts CMYK colors from a CMYK profile to an RGB profile. Colors outside
+/// of the destination gamut are clipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CmykTransform {
+	/// The source CMYK profile.
+	source: CmykProfile,
+	/// The matrix converting D50 XYZ to destination linear RGB.
+	from_xyz: [[f32; 3]; 3],
+	/// The destination profile curves.
+	destination: [Curve; 3],
+}
+
+
+impl CmykTransform {
+	/// Creates a new `CmykTransform` between the given profiles.
+	pub fn new(source: &CmykProfile, destination: &Profile) 
+		-> Result<CmykTransform>
+	{
+		Ok(CmykTransform {
+			source: source.clone(),
+			from_xyz: invert(&destination.to_xyz)
+				.ok_or_else(|| format_error("singular colorant matrix"))?,
+			destination: destination.curves.clone(),
+		})
+	}
+
+	/// Converts the given CMYK color.
+	pub fn convert(&self, cmyk: Cmyk) -> Color {
+		let out = mul(&self.from_xyz, self.source.to_xyz(cmyk));
+		let channel = |i: usize| 
+			(self.destination[i].to_encoded(out[i]) * 255.0).round() as u8;
+		Color::new(channel(0), channel(1), channel(2))
+	}
+}
@@ -38,28 +38,144 @@
 //! other colors, while a zeroth order color element is simply a color. These
 //! dependencies are expressed through references to other cells in the palette.
 //!
+//! By default, cells are held in `Rc` and `RefCell`. If the `sync` feature is 
+//! enabled, they are held in `Arc` and `RwLock` instead, and palettes may be 
+//! sent and shared between threads.
+//!
+//! If the `parallel` feature is enabled, bulk operations such as color 
+//! quantization, remapping, and pairwise color scans are run in parallel.
+//!
+//! The crate builds for `wasm32` targets, where the file loading and saving 
+//! functions are unavailable; palettes are instead read and written through 
+//! byte buffers. The `wasm` feature provides `wasm-bindgen` bindings.
+//!
+//! Without the default `std` feature, the crate builds as `no_std` with 
+//! `alloc`. Only the in-memory data structures are then available: `Color`,
+//! `Address`, `Expression`, `Data`, and the operations upon them. File I/O, 
+//! formats, and the `Palette` type require `std`. Such builds must enable 
+//! the `libm` feature:
+//!
+//! ```text
+//! cargo build --no-default-features --features libm
+//! ```
+//!
 ////////////////////////////////////////////////////////////////////////////////
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("building without the `std` feature requires `libm`");
 
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+#[cfg(all(not(feature = "std"), feature = "libm"))]
+extern crate libm;
 extern crate color;
 extern crate interval;
+#[cfg(feature = "arbitrary")]
+extern crate arbitrary;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(feature = "toml-format")]
+extern crate toml;
+#[cfg(feature = "python")]
+extern crate pyo3;
+#[cfg(feature = "wasm")]
+extern crate js_sys;
+#[cfg(feature = "wasm")]
+extern crate wasm_bindgen;
+#[cfg(feature = "parallel")]
+extern crate rayon;
 
 // Submodules.
 #[warn(missing_docs)]
 pub mod address;
 #[warn(missing_docs)]
+pub mod analysis;
+#[warn(missing_docs)]
+pub mod animation;
+#[warn(missing_docs)]
 pub mod cell;
 #[warn(missing_docs)]
+pub mod checkpoint;
+#[warn(missing_docs)]
 pub mod data;
 #[warn(missing_docs)]
+pub mod depth;
+#[warn(missing_docs)]
+pub mod diff;
+#[cfg(feature = "std")]
+#[warn(missing_docs)]
+pub mod display;
+#[cfg(feature = "std")]
+#[warn(missing_docs)]
+pub mod event;
+#[warn(missing_docs)]
 pub mod expression;
 #[warn(missing_docs)]
+pub mod extract;
+#[cfg(all(feature = "ffi", not(target_arch = "wasm32")))]
+#[warn(missing_docs)]
+pub mod ffi;
+#[cfg(feature = "std")]
+#[warn(missing_docs)]
 pub mod format;
+#[cfg(feature = "std")]
+#[warn(missing_docs)]
+pub mod hardware;
+#[warn(missing_docs)]
+pub mod hex;
+#[cfg(feature = "icc")]
+#[warn(missing_docs)]
+pub mod icc;
+#[warn(missing_docs)]
+pub mod meta;
+#[warn(missing_docs)]
+pub mod names;
 #[warn(missing_docs)]
 pub mod operation;
+#[cfg(feature = "python")]
+#[warn(missing_docs)]
+pub mod python;
+#[warn(missing_docs)]
+pub mod ramp;
+#[cfg(all(feature = "arbitrary", feature = "std"))]
+#[warn(missing_docs)]
+pub mod random;
+#[warn(missing_docs)]
+pub mod remap;
+#[cfg(feature = "std")]
+#[warn(missing_docs)]
+pub mod render;
 #[warn(missing_docs)]
 pub mod result;
 #[warn(missing_docs)]
+pub mod select;
+#[cfg(feature = "serde")]
+#[warn(missing_docs)]
+pub mod serialize;
+#[warn(missing_docs)]
+pub mod shade;
+#[warn(missing_docs)]
+pub mod space;
+#[warn(missing_docs)]
+pub mod storage;
+#[cfg(feature = "std")]
+#[warn(missing_docs)]
+pub mod templates;
+#[warn(missing_docs)]
 pub mod utilities;
+#[warn(missing_docs)]
+pub mod view;
+#[warn(missing_docs)]
+pub mod vision;
+#[cfg(feature = "wasm")]
+#[warn(missing_docs)]
+pub mod wasm;
+
+#[cfg(not(feature = "std"))]
+mod std;
 
 
 
@@ -72,16 +188,45 @@ pub use address::{
 	Reference,
 };
 pub use expression::Expression;
+#[cfg(feature = "std")]
 pub use format::Format;
 
 
 // Local imports.
+#[cfg(feature = "std")]
 use data::Data;
-use operation::{PaletteOperation, OperationHistory};
-use result::Result;
+#[cfg(feature = "std")]
+use event::{Event, Listener, ListenerId, Listeners};
+#[cfg(feature = "std")]
+use operation::{
+	History,
+	HistoryEntry,
+	OperationHistory,
+	OperationInfo,
+	PaletteOperation,
+	SetExpression,
+	Transaction,
+};
+#[cfg(feature = "std")]
+use result::{Error, Result};
+#[cfg(feature = "std")]
+use select::{ImportReport, Region, Select};
+#[cfg(feature = "std")]
+use utilities::unix_time;
 
 // Standard imports.
+#[cfg(feature = "std")]
 use std::fmt;
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
+#[cfg(feature = "std")]
+use std::sync::mpsc::Sender;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+use std::fs::File;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+use std::io::{BufRead, BufReader, BufWriter};
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+use std::path::Path;
 
 
 
@@ -89,6 +234,7 @@ use std::fmt;
 // Palette
 ////////////////////////////////////////////////////////////////////////////////
 /// Encapsulates a single color palette.
+#[cfg(feature = "std")]
 #[derive(Debug)]
 pub struct Palette {
 	/// The `Palette`'s operation-relevant data.
@@ -99,9 +245,13 @@ pub struct Palette {
 	
 	/// The palette format.
 	format: Format,
+
+	/// The registered change listeners.
+	listeners: Listeners,
 }
 
 
+#[cfg(feature = "std")]
 impl Palette {
 	/// Creates a new `Palette` with the given name.
 	pub fn new<S>(name: S, format: Format, history: bool) 
@@ -115,9 +265,13 @@ impl Palette {
 				    None
 				},
 			format: format,
+			listeners: Default::default(),
 		};
 		
 		pal.data.set_name(Reference::all(), name.into());
+		if let Some(meta) = pal.data.metadata.get_mut(&Reference::all()) {
+			meta.created = Some(unix_time());
+		}
 		format.initialize(&mut pal.data);
 		pal
 	}
@@ -141,6 +295,12 @@ impl Palette {
 		}
 	}
 
+	/// Returns the `Palette`'s operation history, or None if history is not 
+	/// enabled.
+	pub fn history(&self) -> Option<&History> {
+		self.operation_history.as_ref()
+	}
+
 	/// Returns whether the `Palette` contains any history entries.
 	pub fn history_is_empty(&self) -> bool {
 		if let Some(ref history) = self.operation_history {
@@ -150,12 +310,30 @@ impl Palette {
 		}
 	}
 
-	/// Returns the color at the given address, or None if the cell is empty.
+	/// Returns the `Palette`'s data.
+	pub fn data(&self) -> &Data {
+		&self.data
+	}
+
+	/// Returns the `Palette`'s data for direct modification. Changes made this
+	/// way bypass the operation history and are not reported to listeners.
+	pub fn data_mut(&mut self) -> &mut Data {
+		&mut self.data
+	}
+
+	/// Returns the color at the given address, or None if the cell is empty
+	/// or its color cannot be resolved within the palette's maximum depth.
 	pub fn color(&self, address: Address) -> Option<Color> {
-		self.data.cell(address).and_then(|cell| cell.color())
+		self.data.resolve(address).unwrap_or(None)
 	}
 
 
+	/// Returns the addresses and colors of the `Palette` at the given time in
+	/// seconds, with all of its color cycles applied.
+	pub fn at_time(&self, time: f32) -> Vec<(Address, Color)> {
+		animation::colors_at(&self.data, time)
+	}
+
 	/// Applies the given operation to the `Palette`. Usually, this will just 
 	/// defer to the `PaletteOperation`'s apply method, but this could also 
 	/// provide extra functionality such as undo/redo and format-specific 
@@ -166,31 +344,288 @@ impl Palette {
 		operation: Box<PaletteOperation>)
 		-> Result<()> 
 	{
-		self.format.apply_operation(self, operation)
+		self.data.clear_modified();
+		let result = self.format.apply_operation(self, operation);
+		self.finish_change();
+		result
+	}
+
+	/// Applies operations to the `Palette` as a single transaction. If the 
+	/// given function returns an error, every operation it applied is reverted.
+	/// Otherwise, the transaction is recorded as a single history entry.
+	pub fn transaction<F, T>(&mut self, f: F) -> Result<T>
+		where F: FnOnce(&mut Transaction) -> Result<T>
+	{
+		self.data.clear_modified();
+		let result = Transaction::run(&mut self.data, f);
+		self.finish_change();
+		let (value, undo) = result?;
+		if let Some(ref mut history) = self.operation_history {
+			history.undo_entries.push(HistoryEntry {
+				info: OperationInfo {
+					name: "Transaction",
+					details: None,
+				},
+				undo: undo,
+			});
+			history.redo_entries.clear();
+		}
+		Ok(value)
 	}
 
 	/// Reverses the most recently applied operation.
 	#[allow(unused_variables)]
 	pub fn undo(&mut self) -> Result<()> {
-		self.format.undo(self)
+		self.data.clear_modified();
+		let result = self.format.undo(self);
+		self.finish_change();
+		result
 	}
 
 	/// Reverses the most recently applied undo operation.
 	#[allow(unused_variables)]
 	pub fn redo(&mut self) -> Result<()> {
-		self.format.redo(self)
+		self.data.clear_modified();
+		let result = self.format.redo(self);
+		self.finish_change();
+		result
+	}
+
+	/// Registers a listener to be notified of changes to the `Palette`. 
+	/// Returns an id which may be used to remove the listener.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::*;
+	/// use palette::event::Event;
+	/// use palette::operation::*;
+	/// use std::sync::mpsc::channel;
+	/// 
+	/// let mut pal = Palette::new("Example", Format::Default, true);
+	/// let (sender, receiver) = channel();
+	/// pal.add_sender(sender);
+	///
+	/// pal.apply(Box::new(InsertColor::new(Color::new(10, 10, 10)))).unwrap();
+	///
+	/// assert_eq!(receiver.try_recv(), 
+	/// 	Ok(Event::ColorAdded(Address::new(0, 0, 0))));
+	/// ```
+	pub fn add_listener<L>(&mut self, listener: L) -> ListenerId
+		where L: Listener + 'static
+	{
+		self.listeners.add(Box::new(listener), &self.data)
+	}
+
+	/// Registers a channel to be sent the changes to the `Palette`. Returns an
+	/// id which may be used to remove the channel.
+	pub fn add_sender(&mut self, sender: Sender<Event>) -> ListenerId {
+		self.listeners.add_sender(sender, &self.data)
+	}
+
+	/// Removes the listener with the given id. Returns false if no such 
+	/// listener is registered.
+	pub fn remove_listener(&mut self, id: ListenerId) -> bool {
+		self.listeners.remove(id)
+	}
+
+	/// Updates the modification times of the groups containing the cells 
+	/// changed by an operation, and notifies the listeners of the changes.
+	fn finish_change(&mut self) {
+		let changed = self.data.stamp_modified();
+		self.listeners.notify_changes(&changed, &self.data);
+	}
+
+	/// Writes the `Palette` to the file at the given path in the given format.
+	/// The file is created if it does not exist, and truncated if it does.
+	#[cfg(not(target_arch = "wasm32"))]
+	pub fn write_to_path<P>(&self, path: P, format: Format) -> Result<()>
+		where P: AsRef<Path>
+	{
+		let mut out_buf = BufWriter::new(File::create(path)?);
+		format.write_palette(self, &mut out_buf)?;
+		out_buf.flush()?;
+		Ok(())
+	}
+
+	/// Writes the selected colors of the `Palette` to the given buffer in the 
+	/// given format. The colors are packed densely in address order, and keep
+	/// their slot names. Selected cells which do not resolve to a color are 
+	/// skipped.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::*;
+	/// use palette::operation::*;
+	/// 
+	/// let mut pal = Palette::new("Example", Format::Default, false);
+	/// for i in 0..4 {
+	/// 	let color = Color::new(i * 10, i * 10, i * 10);
+	/// 	pal.apply(Box::new(InsertColor::new(color))).unwrap();
+	/// }
+	///
+	/// let mut out = Vec::new();
+	/// let select = "0:0:1-0:0:2".parse().unwrap();
+	/// pal.export_selection(&select, Format::Hex, &mut out).unwrap();
+	///
+	/// assert_eq!(String::from_utf8(out).unwrap(), "#0a0a0a\n#141414\n");
+	/// ```
+	pub fn export_selection<W>(
+		&self, 
+		selection: &Select, 
+		format: Format, 
+		out_buf: &mut W) 
+		-> Result<()>
+		where W: Write
+	{
+		let name = self.data.name(&Reference::all()).unwrap_or("");
+		let mut subset = Palette::new(name, format, false);
+		for address in selection.addresses(&self.data) {
+			if let Some(color) = self.color(address) {
+				let packed = subset.data.add_color(color)?;
+				if let Some(slot_name) = self.data.slot_name(address) {
+					subset.data.set_slot_name(packed, slot_name);
+				}
+			}
+		}
+		format.write_palette(&subset, out_buf)
+	}
+
+	/// Reads a palette in the given format from the given buffer and places its
+	/// colors in the given region of the `Palette`, replacing any existing 
+	/// cells. If the colors do not fit in the region, the colors which do not
+	/// fit are dropped if `truncate` is true, and an error is returned 
+	/// otherwise. The import is recorded as a single history entry.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::*;
+	/// use palette::select::Select;
+	/// 
+	/// let mut pal = Palette::new("Example", Format::Default, true);
+	/// let mut file = "#102030\n#405060\n#708090\n".as_bytes();
+	///
+	/// let select: Select = "1:0:0-1:0:1".parse().unwrap();
+	/// let report = pal.import_into(select, &mut file, Format::Hex, true)
+	/// 	.unwrap();
+	///
+	/// assert_eq!(report.placed, 
+	/// 	vec![Address::new(1, 0, 0), Address::new(1, 0, 1)]);
+	/// assert_eq!(report.dropped, 1);
+	/// assert_eq!(pal.color(Address::new(1, 0, 1)), 
+	/// 	Some(Color::new(0x40, 0x50, 0x60)));
+	/// ```
+	pub fn import_into<G, R>(
+		&mut self, 
+		region: G, 
+		in_buf: &mut R,
+		format: Format,
+		truncate: bool) 
+		-> Result<ImportReport>
+		where 
+			G: Into<Region>,
+			R: Read,
+	{
+		let imported = format.read_palette(in_buf)?;
+		let colors: Vec<Color> = imported.data.colors()
+			.map(|(_, color)| color)
+			.collect();
+		let slots = region.into().slots(&self.data, colors.len());
+		if slots.len() < colors.len() && !truncate {
+			return Err(Error::MaxCellLimitExceeded);
+		}
+
+		let report = ImportReport {
+			dropped: colors.len() - slots.len(),
+			placed: slots,
+		};
+		let placed = report.placed.clone();
+		self.transaction(move |txn| {
+			for (address, color) in placed.into_iter().zip(colors) {
+				txn.apply(Box::new(SetExpression::new(
+					address, 
+					Expression::Color(color))))?;
+			}
+			Ok(())
+		})?;
+		Ok(report)
+	}
+
+	/// Reads a `Palette` in the given format from the file at the given path.
+	#[cfg(not(target_arch = "wasm32"))]
+	pub fn read_from_path<P>(path: P, format: Format) -> Result<Palette>
+		where P: AsRef<Path>
+	{
+		let mut in_buf = BufReader::new(File::open(path.as_ref())?);
+		let mut palette = format.read_palette(&mut in_buf)?;
+		palette.data.set_source(Reference::all(), path.as_ref());
+		Ok(palette)
+	}
+}
+
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// File loading and saving
+////////////////////////////////////////////////////////////////////////////////
+/// Loads a palette from the file at the given path. The format is detected 
+/// from the file contents, or from the file extension if the contents are not
+/// recognized.
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub fn load<P>(path: P) -> Result<Palette> where P: AsRef<Path> {
+	let path = path.as_ref();
+	let mut in_buf = BufReader::new(File::open(path)?);
+
+	let format = format::registry::detect(path, in_buf.fill_buf()?)
+		.ok_or_else(|| Error::Format {
+			format: "unknown",
+			reason: "unrecognized palette format".into(),
+		})?;
+	if !format::registry::registration(format).map_or(false, |r| r.readable) {
+		return Err(Error::Format {
+			format: format.name(),
+			reason: "format cannot be read".into(),
+		});
+	}
+	let mut palette = format.read_palette(&mut in_buf)?;
+	palette.data.set_source(Reference::all(), path);
+	Ok(palette)
+}
+
+/// Saves the palette to the file at the given path. The format is selected by
+/// the file extension.
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub fn save<P>(path: P, palette: &Palette) -> Result<()> 
+	where P: AsRef<Path> 
+{
+	let format = format::registry::from_path(&path)
+		.ok_or_else(|| Error::Format {
+			format: "unknown",
+			reason: "unrecognized palette file extension".into(),
+		})?;
+	if !format::registry::registration(format).map_or(false, |r| r.writable) {
+		return Err(Error::Format {
+			format: format.name(),
+			reason: "format cannot be written".into(),
+		});
 	}
+	palette.write_to_path(path, format)
 }
 
 
 
 // Default is empty `Palette` with default format.
+#[cfg(feature = "std")]
 impl Default for Palette {
 	fn default() -> Self {
 		Palette {
 			data: Default::default(),
 			operation_history: None,
 			format: Format::Default,
+			listeners: Default::default(),
 		}
 	}
 }
@@ -198,6 +633,7 @@ impl Default for Palette {
 
 
 // Display `Palette` in readable format.
+#[cfg(feature = "std")]
 impl fmt::Display for Palette {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		write!(f, "Format: {:?}, History: {:?}\n{}",
@@ -0,0 +1,305 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides typed metadata values, and the schemas which declare the keys 
+//! they may be stored under.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use hex::Hex;
+use result::{Error, Result};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+
+/// The metadata key under which formats with per-color alpha store the alpha
+/// of a slot, as an `Int` between 0 and 255. Slots without it are opaque.
+pub const ALPHA_KEY: &'static str = "alpha";
+
+/// The metadata key under which RIFF palettes store the entry flags of a 
+/// slot, as an `Int` between 0 and 255.
+pub const RIFF_FLAGS_KEY: &'static str = "riff-flags";
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// MetaType
+////////////////////////////////////////////////////////////////////////////////
+/// The type of a metadata value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MetaType {
+	/// A text value.
+	String,
+	/// An integer value.
+	Int,
+	/// A color value.
+	Color,
+	/// A boolean value.
+	Bool,
+}
+
+
+impl fmt::Display for MetaType {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", match *self {
+			MetaType::String => "string",
+			MetaType::Int => "int",
+			MetaType::Color => "color",
+			MetaType::Bool => "bool",
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// MetaValue
+////////////////////////////////////////////////////////////////////////////////
+/// A typed metadata value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetaValue {
+	/// A text value.
+	String(String),
+	/// An integer value.
+	Int(i64),
+	/// A color value.
+	Color(Color),
+	/// A boolean value.
+	Bool(bool),
+}
+
+
+impl MetaValue {
+	/// Returns the type of the value.
+	pub fn meta_type(&self) -> MetaType {
+		match *self {
+			MetaValue::String(..) => MetaType::String,
+			MetaValue::Int(..) => MetaType::Int,
+			MetaValue::Color(..) => MetaType::Color,
+			MetaValue::Bool(..) => MetaType::Bool,
+		}
+	}
+}
+
+
+impl Hash for MetaValue {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.meta_type().hash(state);
+		match *self {
+			MetaValue::String(ref value) => value.hash(state),
+			MetaValue::Int(value) => value.hash(state),
+			MetaValue::Color(color) 
+				=> (color.r(), color.g(), color.b()).hash(state),
+			MetaValue::Bool(value) => value.hash(state),
+		}
+	}
+}
+
+
+impl fmt::Display for MetaValue {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			MetaValue::String(ref value) => write!(f, "{:?}", value),
+			MetaValue::Int(value) => write!(f, "{}", value),
+			MetaValue::Color(color) => write!(f, "{}", Hex(color)),
+			MetaValue::Bool(value) => write!(f, "{}", value),
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// MetaKind
+////////////////////////////////////////////////////////////////////////////////
+/// Provides conversions between Rust types and typed metadata values.
+pub trait MetaKind: Sized {
+	/// Returns the metadata type of the Rust type.
+	fn meta_type() -> MetaType;
+
+	/// Converts the given metadata value into the Rust type, or returns None 
+	/// if it has a different type.
+	fn from_value(value: &MetaValue) -> Option<Self>;
+
+	/// Converts the Rust value into a metadata value.
+	fn into_value(self) -> MetaValue;
+}
+
+
+impl MetaKind for String {
+	fn meta_type() -> MetaType { MetaType::String }
+
+	fn from_value(value: &MetaValue) -> Option<Self> {
+		match *value {
+			MetaValue::String(ref value) => Some(value.clone()),
+			_ => None,
+		}
+	}
+
+	fn into_value(self) -> MetaValue { MetaValue::String(self) }
+}
+
+
+impl MetaKind for i64 {
+	fn meta_type() -> MetaType { MetaType::Int }
+
+	fn from_value(value: &MetaValue) -> Option<Self> {
+		match *value {
+			MetaValue::Int(value) => Some(value),
+			_ => None,
+		}
+	}
+
+	fn into_value(self) -> MetaValue { MetaValue::Int(self) }
+}
+
+
+impl MetaKind for Color {
+	fn meta_type() -> MetaType { MetaType::Color }
+
+	fn from_value(value: &MetaValue) -> Option<Self> {
+		match *value {
+			MetaValue::Color(value) => Some(value),
+			_ => None,
+		}
+	}
+
+	fn into_value(self) -> MetaValue { MetaValue::Color(self) }
+}
+
+
+impl MetaKind for bool {
+	fn meta_type() -> MetaType { MetaType::Bool }
+
+	fn from_value(value: &MetaValue) -> Option<Self> {
+		match *value {
+			MetaValue::Bool(value) => Some(value),
+			_ => None,
+		}
+	}
+
+	fn into_value(self) -> MetaValue { MetaValue::Bool(self) }
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// MetaSchema
+////////////////////////////////////////////////////////////////////////////////
+/// The registered metadata keys of a palette and the types of their values.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MetaSchema {
+	/// The types of the registered keys.
+	keys: BTreeMap<String, MetaType>,
+}
+
+
+impl MetaSchema {
+	/// Creates a new, empty MetaSchema.
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	/// Registers the given key with the given type. Registering a key again 
+	/// with the same type has no effect.
+	///
+	/// # Errors
+	///
+	/// Returns a `MetaTypeMismatch` error if the key is already registered 
+	/// with a different type.
+	pub fn register<S>(&mut self, key: S, meta_type: MetaType) -> Result<()>
+		where S: Into<String>
+	{
+		let key = key.into();
+		match self.keys.get(&key) {
+			Some(&existing) if existing != meta_type 
+				=> return Err(Error::MetaTypeMismatch(key)),
+			_ => (),
+		}
+		self.keys.insert(key, meta_type);
+		Ok(())
+	}
+
+	/// Returns the type registered for the given key, or None if it is not 
+	/// registered.
+	pub fn get(&self, key: &str) -> Option<MetaType> {
+		self.keys.get(key).cloned()
+	}
+
+	/// Returns whether the given key is registered.
+	pub fn contains(&self, key: &str) -> bool {
+		self.keys.contains_key(key)
+	}
+
+	/// Checks that the given value may be stored under the given key.
+	///
+	/// # Errors
+	///
+	/// Returns an `UnregisteredMetaKey` error if the key is not registered, or
+	/// a `MetaTypeMismatch` error if the value has a different type than the 
+	/// key.
+	pub fn validate(&self, key: &str, value: &MetaValue) -> Result<()> {
+		match self.get(key) {
+			None => Err(Error::UnregisteredMetaKey(key.to_owned())),
+			Some(meta_type) if meta_type != value.meta_type() 
+				=> Err(Error::MetaTypeMismatch(key.to_owned())),
+			_ => Ok(()),
+		}
+	}
+
+	/// Registers every key of the given schema which is not already 
+	/// registered.
+	pub fn merge(&mut self, other: &MetaSchema) {
+		for (key, &meta_type) in &other.keys {
+			self.keys.entry(key.clone()).or_insert(meta_type);
+		}
+	}
+
+	/// Returns the number of registered keys.
+	pub fn len(&self) -> usize {
+		self.keys.len()
+	}
+
+	/// Returns whether no keys are registered.
+	pub fn is_empty(&self) -> bool {
+		self.keys.is_empty()
+	}
+
+	/// Returns an iterator over the registered keys and their types, in key 
+	/// order.
+	pub fn iter<'a>(&'a self) -> impl Iterator<Item=(&'a str, MetaType)> + 'a {
+		self.keys.iter().map(|(key, &meta_type)| (&key[..], meta_type))
+	}
+}
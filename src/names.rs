@@ -0,0 +1,355 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides lookups between color names and colors, using the CSS named colors
+//! or a custom dictionary.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use space::Metric;
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+use std::collections::BTreeMap;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// CSS names
+////////////////////////////////////////////////////////////////////////////////
+/// The CSS named colors, in alphabetical order. These are also the X11 colors,
+/// excepting a handful of X11 grays and greens which CSS redefined.
+pub const CSS_COLORS: &[(&str, u8, u8, u8)] = &[
+	("aliceblue", 240, 248, 255),
+	("antiquewhite", 250, 235, 215),
+	("aqua", 0, 255, 255),
+	("aquamarine", 127, 255, 212),
+	("azure", 240, 255, 255),
+	("beige", 245, 245, 220),
+	("bisque", 255, 228, 196),
+	("black", 0, 0, 0),
+	("blanchedalmond", 255, 235, 205),
+	("blue", 0, 0, 255),
+	("blueviolet", 138, 43, 226),
+	("brown", 165, 42, 42),
+	("burlywood", 222, 184, 135),
+	("cadetblue", 95, 158, 160),
+	("chartreuse", 127, 255, 0),
+	("chocolate", 210, 105, 30),
+	("coral", 255, 127, 80),
+	("cornflowerblue", 100, 149, 237),
+	("cornsilk", 255, 248, 220),
+	("crimson", 220, 20, 60),
+	("cyan", 0, 255, 255),
+	("darkblue", 0, 0, 139),
+	("darkcyan", 0, 139, 139),
+	("darkgoldenrod", 184, 134, 11),
+	("darkgray", 169, 169, 169),
+	("darkgreen", 0, 100, 0),
+	("darkgrey", 169, 169, 169),
+	("darkkhaki", 189, 183, 107),
+	("darkmagenta", 139, 0, 139),
+	("darkolivegreen", 85, 107, 47),
+	("darkorange", 255, 140, 0),
+	("darkorchid", 153, 50, 204),
+	("darkred", 139, 0, 0),
+	("darksalmon", 233, 150, 122),
+	("darkseagreen", 143, 188, 143),
+	("darkslateblue", 72, 61, 139),
+	("darkslategray", 47, 79, 79),
+	("darkslategrey", 47, 79, 79),
+	("darkturquoise", 0, 206, 209),
+	("darkviolet", 148, 0, 211),
+	("deeppink", 255, 20, 147),
+	("deepskyblue", 0, 191, 255),
+	("dimgray", 105, 105, 105),
+	("dimgrey", 105, 105, 105),
+	("dodgerblue", 30, 144, 255),
+	("firebrick", 178, 34, 34),
+	("floralwhite", 255, 250, 240),
+	("forestgreen", 34, 139, 34),
+	("fuchsia", 255, 0, 255),
+	("gainsboro", 220, 220, 220),
+	("ghostwhite", 248, 248, 255),
+	("gold", 255, 215, 0),
+	("goldenrod", 218, 165, 32),
+	("gray", 128, 128, 128),
+	("green", 0, 128, 0),
+	("greenyellow", 173, 255, 47),
+	("grey", 128, 128, 128),
+	("honeydew", 240, 255, 240),
+	("hotpink", 255, 105, 180),
+	("indianred", 205, 92, 92),
+	("indigo", 75, 0, 130),
+	("ivory", 255, 255, 240),
+	("khaki", 240, 230, 140),
+	("lavender", 230, 230, 250),
+	("lavenderblush", 255, 240, 245),
+	("lawngreen", 124, 252, 0),
+	("lemonchiffon", 255, 250, 205),
+	("lightblue", 173, 216, 230),
+	("lightcoral", 240, 128, 128),
+	("lightcyan", 224, 255, 255),
+	("lightgoldenrodyellow", 250, 250, 210),
+	("lightgray", 211, 211, 211),
+	("lightgreen", 144, 238, 144),
+	("lightgrey", 211, 211, 211),
+	("lightpink", 255, 182, 193),
+	("lightsalmon", 255, 160, 122),
+	("lightseagreen", 32, 178, 170),
+	("lightskyblue", 135, 206, 250),
+	("lightslategray", 119, 136, 153),
+	("lightslategrey", 119, 136, 153),
+	("lightsteelblue", 176, 196, 222),
+	("lightyellow", 255, 255, 224),
+	("lime", 0, 255, 0),
+	("limegreen", 50, 205, 50),
+	("linen", 250, 240, 230),
+	("magenta", 255, 0, 255),
+	("maroon", 128, 0, 0),
+	("mediumaquamarine", 102, 205, 170),
+	("mediumblue", 0, 0, 205),
+	("mediumorchid", 186, 85, 211),
+	("mediumpurple", 147, 112, 219),
+	("mediumseagreen", 60, 179, 113),
+	("mediumslateblue", 123, 104, 238),
+	("mediumspringgreen", 0, 250, 154),
+	("mediumturquoise", 72, 209, 204),
+	("mediumvioletred", 199, 21, 133),
+	("midnightblue", 25, 25, 112),
+	("mintcream", 245, 255, 250),
+	("mistyrose", 255, 228, 225),
+	("moccasin", 255, 228, 181),
+	("navajowhite", 255, 222, 173),
+	("navy", 0, 0, 128),
+	("oldlace", 253, 245, 230),
+	("olive", 128, 128, 0),
+	("olivedrab", 107, 142, 35),
+	("orange", 255, 165, 0),
+	("orangered", 255, 69, 0),
+	("orchid", 218, 112, 214),
+	("palegoldenrod", 238, 232, 170),
+	("palegreen", 152, 251, 152),
+	("paleturquoise", 175, 238, 238),
+	("palevioletred", 219, 112, 147),
+	("papayawhip", 255, 239, 213),
+	("peachpuff", 255, 218, 185),
+	("peru", 205, 133, 63),
+	("pink", 255, 192, 203),
+	("plum", 221, 160, 221),
+	("powderblue", 176, 224, 230),
+	("purple", 128, 0, 128),
+	("rebeccapurple", 102, 51, 153),
+	("red", 255, 0, 0),
+	("rosybrown", 188, 143, 143),
+	("royalblue", 65, 105, 225),
+	("saddlebrown", 139, 69, 19),
+	("salmon", 250, 128, 114),
+	("sandybrown", 244, 164, 96),
+	("seagreen", 46, 139, 87),
+	("seashell", 255, 245, 238),
+	("sienna", 160, 82, 45),
+	("silver", 192, 192, 192),
+	("skyblue", 135, 206, 235),
+	("slateblue", 106, 90, 205),
+	("slategray", 112, 128, 144),
+	("slategrey", 112, 128, 144),
+	("snow", 255, 250, 250),
+	("springgreen", 0, 255, 127),
+	("steelblue", 70, 130, 180),
+	("tan", 210, 180, 140),
+	("teal", 0, 128, 128),
+	("thistle", 216, 191, 216),
+	("tomato", 255, 99, 71),
+	("turquoise", 64, 224, 208),
+	("violet", 238, 130, 238),
+	("wheat", 245, 222, 179),
+	("white", 255, 255, 255),
+	("whitesmoke", 245, 245, 245),
+	("yellow", 255, 255, 0),
+	("yellowgreen", 154, 205, 50),
+];
+
+
+/// Returns the CSS color with the given name. Names are matched ignoring case,
+/// spaces, underscores, and hyphens.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::names::from_name;
+/// use palette::Color;
+///
+/// assert_eq!(from_name("rebeccapurple"), Some(Color::new(102, 51, 153)));
+/// assert_eq!(from_name("Light Sky Blue"), Some(Color::new(135, 206, 250)));
+/// assert_eq!(from_name("nonesuch"), None);
+/// ```
+pub fn from_name(name: &str) -> Option<Color> {
+	let key = normalize(name);
+	CSS_COLORS
+		.binary_search_by(|&(n, ..)| n.cmp(&&key[..]))
+		.ok()
+		.map(|i| {
+			let (_, r, g, b) = CSS_COLORS[i];
+			Color::new(r, g, b)
+		})
+}
+
+/// Returns the name of the CSS color closest to the given color. Where several
+/// names share a color, the first in alphabetical order is returned.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::names::closest_name;
+/// use palette::Color;
+///
+/// assert_eq!(closest_name(Color::new(102, 51, 153)), "rebeccapurple");
+/// assert_eq!(closest_name(Color::new(250, 2, 3)), "red");
+/// ```
+pub fn closest_name(color: Color) -> &'static str {
+	let mut best = ("", ::std::f32::INFINITY);
+	for &(name, r, g, b) in CSS_COLORS {
+		let distance = Metric::DeltaE.distance(color, Color::new(r, g, b));
+		if distance < best.1 {
+			best = (name, distance);
+		}
+	}
+	best.0
+}
+
+/// Returns the given name in the form used for lookups.
+fn normalize(name: &str) -> String {
+	name.chars()
+		.filter(|&c| c != ' ' && c != '_' && c != '-')
+		.flat_map(|c| c.to_lowercase())
+		.collect()
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Dictionary
+////////////////////////////////////////////////////////////////////////////////
+/// A custom mapping between color names and colors, such as a vendor swatch
+/// book. Names are matched ignoring case, spaces, underscores, and hyphens, 
+/// but are returned as they were inserted.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::names::Dictionary;
+/// use palette::space::Metric;
+/// use palette::Color;
+///
+/// let mut dict = Dictionary::new();
+/// dict.insert("Studio Red 01", Color::new(200, 16, 46));
+/// dict.insert("Studio Blue 02", Color::new(0, 51, 160));
+///
+/// assert_eq!(dict.get("studio-red-01"), Some(Color::new(200, 16, 46)));
+/// assert_eq!(
+/// 	dict.closest(Color::new(10, 40, 150), Metric::DeltaE), 
+/// 	Some("Studio Blue 02"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Dictionary {
+	/// The entries of the dictionary, keyed by their normalized name.
+	entries: BTreeMap<String, (String, Color)>,
+}
+
+
+impl Dictionary {
+	/// Creates a new, empty `Dictionary`.
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	/// Creates a new `Dictionary` containing the CSS named colors.
+	pub fn css() -> Self {
+		let mut dict = Dictionary::new();
+		for &(name, r, g, b) in CSS_COLORS {
+			dict.insert(name, Color::new(r, g, b));
+		}
+		dict
+	}
+
+	/// Adds a named color to the dictionary. Returns the color previously 
+	/// associated with the name, if any.
+	pub fn insert<S>(&mut self, name: S, color: Color) -> Option<Color>
+		where S: Into<String>
+	{
+		let name = name.into();
+		self.entries
+			.insert(normalize(&name), (name, color))
+			.map(|(_, color)| color)
+	}
+
+	/// Removes a named color from the dictionary, returning its color.
+	pub fn remove(&mut self, name: &str) -> Option<Color> {
+		self.entries
+			.remove(&normalize(name))
+			.map(|(_, color)| color)
+	}
+
+	/// Returns the color with the given name.
+	pub fn get(&self, name: &str) -> Option<Color> {
+		self.entries
+			.get(&normalize(name))
+			.map(|&(_, color)| color)
+	}
+
+	/// Returns the name of the color closest to the given color, using the 
+	/// given metric, or None if the dictionary is empty.
+	pub fn closest(&self, color: Color, metric: Metric) -> Option<&str> {
+		let mut best: Option<(&str, f32)> = None;
+		for &(ref name, entry) in self.entries.values() {
+			let distance = metric.distance(color, entry);
+			if best.map_or(true, |(_, d)| distance < d) {
+				best = Some((name, distance));
+			}
+		}
+		best.map(|(name, _)| name)
+	}
+
+	/// Returns the number of names in the dictionary.
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	/// Returns whether the dictionary is empty.
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Returns an iterator over the names and colors of the dictionary, in 
+	/// order of their normalized names.
+	pub fn iter<'a>(&'a self) -> impl Iterator<Item=(&'a str, Color)> + 'a {
+		self.entries.values().map(|&(ref name, color)| (&name[..], color))
+	}
+}
@@ -0,0 +1,483 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Defines color adjustment operations.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{Address, AddressRange, Reference};
+use data::Data;
+use depth::Grid;
+use expression::Expression;
+use operation::{
+	set_target,
+	source,
+	HistoryEntry,
+	OperationInfo,
+	PaletteOperation,
+	Undo,
+};
+use result::{Error, Result};
+use select::Select;
+use space::{delta_e, Adjustment, Hsl};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
+
+/// Returns the color stored at the given address. Returns an error if the 
+/// address is empty or if it contains a derived color.
+fn fixed_color(data: &Data, address: Address) -> Result<Color> {
+	let cell = data.cell(address).ok_or(Error::EmptyAddress(address))?;
+	let expr = cell.borrow();
+	match *expr {
+		Expression::Color(color) => Ok(color),
+		Expression::Cmyk(cmyk) => Ok(Color::from(cmyk)),
+		Expression::Frozen {color, ..} => Ok(color),
+		Expression::Empty => Err(Error::EmptyAddress(address)),
+		_ => Err(Error::CannotSetDerivedColor),
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ShiftHue
+////////////////////////////////////////////////////////////////////////////////
+/// Rotates the hue of the color at the given address.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertColor::new(Color::new(255, 0, 0)))).unwrap();
+///
+/// pal.apply(Box::new(ShiftHue::new(Address::new(0, 0, 0), 120.0))).unwrap();
+///
+/// assert_eq!(pal.color(Address::new(0, 0, 0)), Some(Color::new(0, 255, 0)));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ShiftHue {
+	/// The address of the color to adjust.
+	address: Address,
+	/// The number of degrees to rotate the hue by.
+	degrees: f32,
+}
+
+
+impl ShiftHue {
+	/// Creates a new ShiftHue operation.
+	#[inline]
+	pub fn new(address: Address, degrees: f32) -> ShiftHue {
+		ShiftHue {
+			address: address,
+			degrees: degrees,
+		}
+	}
+}
+
+
+impl PaletteOperation for ShiftHue {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Shift Hue",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let hsl = Hsl::from(fixed_color(data, self.address)?);
+		let color = Color::from(Hsl::new(hsl.h + self.degrees, hsl.s, hsl.l));
+
+		let mut undo = Undo::new_for(self);
+		set_target(data, self.address, Expression::Color(color), &mut undo)?;
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(undo),
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// SetSaturation
+////////////////////////////////////////////////////////////////////////////////
+/// Sets the HSL saturation of the color at the given address.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertColor::new(Color::new(200, 100, 100)))).unwrap();
+///
+/// pal.apply(Box::new(SetSaturation::new(Address::new(0, 0, 0), 0.0)))
+/// 	.unwrap();
+///
+/// assert_eq!(pal.color(Address::new(0, 0, 0)), 
+/// 	Some(Color::new(150, 150, 150)));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SetSaturation {
+	/// The address of the color to adjust.
+	address: Address,
+	/// The new saturation, between 0 and 1.
+	saturation: f32,
+}
+
+
+impl SetSaturation {
+	/// Creates a new SetSaturation operation.
+	#[inline]
+	pub fn new(address: Address, saturation: f32) -> SetSaturation {
+		SetSaturation {
+			address: address,
+			saturation: saturation,
+		}
+	}
+}
+
+
+impl PaletteOperation for SetSaturation {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Set Saturation",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let hsl = Hsl::from(fixed_color(data, self.address)?);
+		let color = Color::from(Hsl::new(hsl.h, self.saturation, hsl.l));
+
+		let mut undo = Undo::new_for(self);
+		set_target(data, self.address, Expression::Color(color), &mut undo)?;
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(undo),
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// AdjustSelection
+////////////////////////////////////////////////////////////////////////////////
+/// Applies a color adjustment to the selected cells. 
+///
+/// By default, the fixed colors of the selected cells are replaced with their
+/// adjusted colors, while derived cells are left to follow their sources. If 
+/// a layer destination is given, the selected cells are left unchanged and 
+/// derived cells adjusting each of them are placed at the destination 
+/// instead, packed densely in address order.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// use palette::select::Select;
+/// use palette::space::Adjustment;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertColor::new(Color::new(100, 100, 100)))).unwrap();
+/// pal.apply(Box::new(InsertColor::new(Color::new(0, 0, 0)))).unwrap();
+///
+/// let select: Select = "0:0:0-0:0:1".parse().unwrap();
+/// let brighten = Adjustment::Brightness(0.2);
+/// pal.apply(Box::new(AdjustSelection::new(select.clone(), brighten)
+/// 	.as_layer(Address::new(1, 0, 0)))).unwrap();
+/// assert_eq!(pal.color(Address::new(1, 0, 1)), Some(Color::new(51, 51, 51)));
+///
+/// pal.apply(Box::new(AdjustSelection::new(select, brighten))).unwrap();
+/// assert_eq!(pal.color(Address::new(0, 0, 1)), Some(Color::new(51, 51, 51)));
+/// assert_eq!(pal.color(Address::new(1, 0, 1)), 
+/// 	Some(Color::new(102, 102, 102)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct AdjustSelection {
+	/// The selection to adjust.
+	selection: Select,
+	/// The adjustment to apply.
+	adjustment: Adjustment,
+	/// The address to place the first adjustment layer cell, if any.
+	layer: Option<Address>,
+	/// Whether to overwrite existing cells at the layer destination.
+	overwrite: bool,
+}
+
+
+impl AdjustSelection {
+	/// Creates a new AdjustSelection operation.
+	#[inline]
+	pub fn new(selection: Select, adjustment: Adjustment) -> AdjustSelection {
+		AdjustSelection {
+			selection: selection,
+			adjustment: adjustment,
+			layer: None,
+			overwrite: false,
+		}
+	}
+
+	/// Configures the operation to place derived cells applying the 
+	/// adjustment at the given destination, leaving the selected cells 
+	/// unchanged.
+	pub fn as_layer(mut self, destination: Address) -> AdjustSelection {
+		self.layer = Some(destination);
+		self
+	}
+
+	/// Configures the operation to overwrite existing cells at the layer 
+	/// destination.
+	pub fn overwrite(mut self, overwrite: bool) -> AdjustSelection {
+		self.overwrite = overwrite;
+		self
+	}
+
+	/// Replaces the fixed colors of the selected cells with their adjusted 
+	/// colors.
+	fn adjust_colors(&self, data: &mut Data, undo: &mut Undo) -> Result<()> {
+		for address in self.selection.addresses(data) {
+			let color = match fixed_color(data, address) {
+				Ok(color) => color,
+				Err(Error::CannotSetDerivedColor) |
+				Err(Error::EmptyAddress(..)) => continue,
+				Err(e) => return Err(e),
+			};
+			let color = self.adjustment.apply(color);
+			set_target(data, address, Expression::Color(color), undo)?;
+		}
+		Ok(())
+	}
+
+	/// Places derived cells adjusting each of the selected cells at the given
+	/// destination.
+	fn insert_layer(
+		&self,
+		data: &mut Data,
+		destination: Address,
+		undo: &mut Undo)
+		-> Result<()>
+	{
+		let lines = data.line_count(&Reference::page_of(&destination));
+		let columns = data.column_count(&Reference::line_of(&destination));
+		let sources = self.selection.addresses(data);
+		let count = sources.len();
+		let pairs: Vec<(Address, Address)> = sources.into_iter()
+			.zip(AddressRange::with_len(destination, count, lines, columns))
+			.collect();
+		if pairs.len() < count {
+			return Err(Error::InvalidAddress(destination));
+		}
+
+		if !self.overwrite {
+			for &(_, to) in &pairs {
+				if data.cell(to).is_some() {
+					return Err(Error::AddressInUse(to));
+				}
+			}
+		}
+
+		for (from, to) in pairs {
+			let expr = Expression::Adjust {
+				a: source(data, from, false, undo)?,
+				adjustment: self.adjustment,
+			};
+			set_target(data, to, expr, undo)?;
+		}
+		Ok(())
+	}
+}
+
+
+impl PaletteOperation for AdjustSelection {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Adjust Selection",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let mut undo = Undo::new_for(self);
+		match self.layer {
+			Some(destination) 
+				=> self.insert_layer(data, destination, &mut undo)?,
+			None => self.adjust_colors(data, &mut undo)?,
+		}
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(undo),
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ColorShift
+////////////////////////////////////////////////////////////////////////////////
+/// A record of how far a color moved when it was adjusted.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ColorShift {
+	/// The address of the adjusted color.
+	pub address: Address,
+	/// The color before adjustment.
+	pub before: Color,
+	/// The color after adjustment.
+	pub after: Color,
+	/// The CIE76 perceptual difference between the colors.
+	pub distance: f32,
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Posterize
+////////////////////////////////////////////////////////////////////////////////
+/// Snaps the fixed colors of the selected cells to a coarser color grid. This
+/// ensures they will be stored exactly by formats with reduced color depth.
+///
+/// Derived cells are not changed, and may generate colors off of the grid. 
+/// Freeze them first in order to posterize them.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// use palette::select::Select;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertColor::new(Color::new(255, 128, 64)))).unwrap();
+/// pal.apply(Box::new(InsertColor::new(Color::new(0, 0, 0)))).unwrap();
+///
+/// let posterize = Posterize::quantize_channels(Select::all(), [3, 3, 2]);
+/// let shifts = posterize.preview(pal.data());
+/// assert_eq!(shifts.len(), 1);
+/// assert_eq!(shifts[0].after, Color::new(255, 146, 85));
+///
+/// pal.apply(Box::new(posterize)).unwrap();
+/// assert_eq!(pal.color(Address::new(0, 0, 0)), 
+/// 	Some(Color::new(255, 146, 85)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Posterize {
+	/// The selection to posterize.
+	selection: Select,
+	/// The grid to snap colors to.
+	grid: Grid,
+}
+
+
+impl Posterize {
+	/// Creates a new Posterize operation snapping colors to the given grid.
+	#[inline]
+	pub fn new(selection: Select, grid: Grid) -> Posterize {
+		Posterize {
+			selection: selection,
+			grid: grid,
+		}
+	}
+
+	/// Creates a new Posterize operation which reduces the red, green, and 
+	/// blue channels to the given numbers of bits.
+	#[inline]
+	pub fn quantize_channels(selection: Select, bits_per_channel: [u8; 3]) 
+		-> Posterize
+	{
+		let bits = bits_per_channel;
+		Posterize::new(selection, Grid::Bits(bits[0], bits[1], bits[2]))
+	}
+
+	/// Creates a new Posterize operation which rounds each channel to the 
+	/// nearest of the given number of evenly spaced levels.
+	#[inline]
+	pub fn snap_to_grid(selection: Select, levels: u16) -> Posterize {
+		Posterize::new(selection, Grid::Levels(levels))
+	}
+
+	/// Returns the colors which would be moved by the operation, along with 
+	/// how far they would move, without changing the palette. No colors are
+	/// moved if the grid is invalid.
+	pub fn preview(&self, data: &Data) -> Vec<ColorShift> {
+		if self.grid.validate().is_err() { return Vec::new(); }
+		self.selection.addresses(data)
+			.into_iter()
+			.filter_map(|address| {
+				let before = fixed_color(data, address).ok()?;
+				let after = self.grid.snap(before);
+				if before == after { return None; }
+				Some(ColorShift {
+					address: address,
+					before: before,
+					after: after,
+					distance: delta_e(before, after),
+				})
+			})
+			.collect()
+	}
+}
+
+
+impl PaletteOperation for Posterize {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Posterize",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		self.grid.validate()?;
+		let mut undo = Undo::new_for(self);
+		for shift in self.preview(data) {
+			set_target(
+				data,
+				shift.address,
+				Expression::Color(shift.after),
+				&mut undo)?;
+		}
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(undo),
+		})
+	}
+}
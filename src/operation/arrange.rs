@@ -0,0 +1,495 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Defines operations for rearranging cells in the palette.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{Address, AddressRange, Reference};
+use cell::{Cell, Shared, Weak};
+use data::Data;
+use expression::Expression;
+use operation::{
+	set_target,
+	target,
+	HistoryEntry,
+	OperationInfo,
+	PaletteOperation,
+	Undo,
+};
+use result::{Error, Result};
+use select::Select;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
+
+/// Returns the source and destination address pairs for moving or copying the
+/// given range to the given destination.
+fn range_pairs(source: &AddressRange, destination: Address) 
+	-> Result<Vec<(Address, Address)>>
+{
+	let (lines, columns) = (source.lines(), source.columns());
+	let base = destination.index(lines, columns);
+	source.clone()
+		.enumerate()
+		.map(|(i, from)| Address::from_index(base + i, lines, columns)
+			.map(|to| (from, to))
+			.ok_or_else(|| Error::InvalidAddress(destination)))
+		.collect()
+}
+
+/// Copies the cells at the source addresses of the given pairs to their 
+/// destinations, recording the changes in the given `Undo`. Derived cells 
+/// which reference other copied cells will reference the corresponding copies.
+fn copy_cells(
+	data: &mut Data, 
+	pairs: Vec<(Address, Address)>,
+	overwrite: bool,
+	undo: &mut Undo)
+	-> Result<()>
+{
+	// Snapshot the source cells before anything is written.
+	let copies: Vec<(Shared<Cell>, Address, Expression)> = pairs
+		.into_iter()
+		.filter_map(|(from, to)| data.cell(from).map(|cell| {
+			let expr = cell.borrow().clone();
+			(cell, to, expr)
+		}))
+		.collect();
+
+	if !overwrite {
+		for &(_, to, _) in &copies {
+			if data.cell(to).is_some() {
+				return Err(Error::AddressInUse(to));
+			}
+		}
+	}
+
+	// Create the destination cells, so that copies may reference each other.
+	let mut retargets: Vec<(*const Cell, Weak<Cell>)> = Vec::new();
+	for &(ref cell, to, _) in &copies {
+		let copy = target(data, to, undo)?;
+		retargets.push((&**cell as *const Cell, Shared::downgrade(&copy)));
+	}
+
+	// Set the copied expressions.
+	for (_, to, mut expr) in copies {
+		for source in expr.sources_mut() {
+			let retarget = source.upgrade()
+				.and_then(|cell| retargets.iter()
+					.find(|&&(ptr, _)| ptr == &*cell as *const Cell)
+					.map(|&(_, ref copy)| copy.clone()));
+			if let Some(copy) = retarget {
+				*source = copy;
+			}
+		}
+		set_target(data, to, expr, undo)?;
+	}
+	Ok(())
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// CopyRange
+////////////////////////////////////////////////////////////////////////////////
+/// Copies the cells in a range of addresses to another location in the 
+/// palette. 
+///
+/// Derived cells which reference other cells within the copied range will 
+/// reference the corresponding copies, while references to cells outside of 
+/// the range are unchanged. Empty addresses in the range are skipped.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// use palette::address::AddressRange;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertColor::new(Color::new(0, 0, 0)))).unwrap();
+/// pal.apply(Box::new(InsertDerived::lighten(Address::new(0, 0, 0), 0.5)))
+/// 	.unwrap();
+///
+/// let range = AddressRange::with_len(Address::new(0, 0, 0), 2, 16, 16);
+/// pal.apply(Box::new(CopyRange::new(range, Address::new(1, 0, 0)))).unwrap();
+///
+/// // The copy of the derived color references the copied source.
+/// pal.apply(Box::new(SetExpression::new(
+/// 	Address::new(1, 0, 0), 
+/// 	Expression::Color(Color::new(100, 100, 100))))).unwrap();
+/// let (original, copy) = (Address::new(0, 0, 1), Address::new(1, 0, 1));
+/// assert_eq!(pal.color(original), Some(Color::new(127, 127, 127)));
+/// assert_eq!(pal.color(copy), Some(Color::new(177, 177, 177)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CopyRange {
+	/// The range of addresses to copy.
+	source: AddressRange,
+	/// The address to place the first copied cell.
+	destination: Address,
+	/// Whether to overwrite existing cells at the destination.
+	overwrite: bool,
+}
+
+
+impl CopyRange {
+	/// Creates a new CopyRange operation.
+	#[inline]
+	pub fn new(source: AddressRange, destination: Address) -> CopyRange {
+		CopyRange {
+			source: source,
+			destination: destination,
+			overwrite: false,
+		}
+	}
+
+	/// Configures the operation to overwrite existing cells at the 
+	/// destination.
+	pub fn overwrite(mut self, overwrite: bool) -> CopyRange {
+		self.overwrite = overwrite;
+		self
+	}
+}
+
+
+impl PaletteOperation for CopyRange {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Copy Range",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let pairs = range_pairs(&self.source, self.destination)?;
+		let mut undo = Undo::new_for(self);
+		copy_cells(data, pairs, self.overwrite, &mut undo)?;
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(undo),
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// CopySelection
+////////////////////////////////////////////////////////////////////////////////
+/// Copies the selected cells to another location in the palette, packed 
+/// densely in address order. 
+///
+/// Derived cells which reference other selected cells will reference the 
+/// corresponding copies, while references to other cells are unchanged.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertColor::new(Color::new(0, 0, 0)))).unwrap();
+/// pal.apply(Box::new(InsertColor::new(Color::new(50, 50, 50)))).unwrap();
+/// pal.apply(Box::new(InsertColor::new(Color::new(100, 100, 100)))).unwrap();
+///
+/// let select = "0:0:0, 0:0:2".parse().unwrap();
+/// pal.apply(Box::new(CopySelection::new(select, Address::new(1, 0, 0))))
+/// 	.unwrap();
+///
+/// assert_eq!(pal.color(Address::new(1, 0, 0)), Some(Color::new(0, 0, 0)));
+/// assert_eq!(pal.color(Address::new(1, 0, 1)), 
+/// 	Some(Color::new(100, 100, 100)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CopySelection {
+	/// The selection to copy.
+	source: Select,
+	/// The address to place the first copied cell.
+	destination: Address,
+	/// Whether to overwrite existing cells at the destination.
+	overwrite: bool,
+}
+
+
+impl CopySelection {
+	/// Creates a new CopySelection operation.
+	#[inline]
+	pub fn new(source: Select, destination: Address) -> CopySelection {
+		CopySelection {
+			source: source,
+			destination: destination,
+			overwrite: false,
+		}
+	}
+
+	/// Configures the operation to overwrite existing cells at the 
+	/// destination.
+	pub fn overwrite(mut self, overwrite: bool) -> CopySelection {
+		self.overwrite = overwrite;
+		self
+	}
+}
+
+
+impl PaletteOperation for CopySelection {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Copy Selection",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let lines = data.line_count(&Reference::page_of(&self.destination));
+		let columns = data.column_count(
+			&Reference::line_of(&self.destination));
+		let sources = self.source.addresses(data);
+		let count = sources.len();
+		let range = AddressRange::with_len(
+			self.destination, 
+			count, 
+			lines, 
+			columns);
+		let pairs: Vec<(Address, Address)> = sources.into_iter()
+			.zip(range)
+			.collect();
+		if pairs.len() < count {
+			return Err(Error::InvalidAddress(self.destination));
+		}
+
+		let mut undo = Undo::new_for(self);
+		copy_cells(data, pairs, self.overwrite, &mut undo)?;
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(undo),
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// MoveRange
+////////////////////////////////////////////////////////////////////////////////
+/// Moves the cells in a range of addresses to another location in the palette.
+///
+/// The cells themselves are moved, so any cells referencing them will remain
+/// valid, and their names and metadata move with them. The destination may 
+/// overlap the source range, but must otherwise be empty.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// use palette::address::AddressRange;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertColor::new(Color::new(0, 0, 0)))).unwrap();
+/// pal.apply(Box::new(InsertDerived::lighten(Address::new(0, 0, 0), 0.5)))
+/// 	.unwrap();
+///
+/// let range = AddressRange::with_len(Address::new(0, 0, 0), 1, 16, 16);
+/// pal.apply(Box::new(MoveRange::new(range, Address::new(0, 0, 5)))).unwrap();
+///
+/// assert_eq!(pal.color(Address::new(0, 0, 0)), None);
+/// assert_eq!(pal.color(Address::new(0, 0, 5)), Some(Color::new(0, 0, 0)));
+/// let derived = Address::new(0, 0, 1);
+/// assert_eq!(pal.color(derived), Some(Color::new(127, 127, 127)));
+///
+/// pal.undo().unwrap();
+/// assert_eq!(pal.color(Address::new(0, 0, 0)), Some(Color::new(0, 0, 0)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MoveRange {
+	/// The range of addresses to move.
+	source: AddressRange,
+	/// The address to place the first moved cell.
+	destination: Address,
+}
+
+
+impl MoveRange {
+	/// Creates a new MoveRange operation.
+	#[inline]
+	pub fn new(source: AddressRange, destination: Address) -> MoveRange {
+		MoveRange {
+			source: source,
+			destination: destination,
+		}
+	}
+}
+
+
+impl PaletteOperation for MoveRange {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Move Range",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let pairs = range_pairs(&self.source, self.destination)?;
+		data.move_cells(&pairs)?;
+
+		// The move is undone by moving the cells back.
+		let start = match self.source.start() {
+			Some(start) => start,
+			None => self.destination,
+		};
+		let undo = MoveRange::new(
+			AddressRange::with_len(
+				self.destination, 
+				pairs.len(), 
+				self.source.lines(), 
+				self.source.columns()),
+			start);
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(undo),
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// SwapCells
+////////////////////////////////////////////////////////////////////////////////
+/// Exchanges the cells at two addresses in the palette.
+///
+/// The cells themselves are exchanged, so any cells referencing them will 
+/// remain valid, and their names and metadata move with them. Either address 
+/// may be empty.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertColor::new(Color::new(255, 0, 0)))).unwrap();
+/// pal.apply(Box::new(InsertColor::new(Color::new(0, 0, 255)))).unwrap();
+///
+/// let (a, b) = (Address::new(0, 0, 0), Address::new(0, 0, 1));
+/// pal.apply(Box::new(SwapCells::new(a, b))).unwrap();
+///
+/// assert_eq!(pal.color(a), Some(Color::new(0, 0, 255)));
+/// assert_eq!(pal.color(b), Some(Color::new(255, 0, 0)));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SwapCells {
+	/// The first address.
+	a: Address,
+	/// The second address.
+	b: Address,
+}
+
+
+impl SwapCells {
+	/// Creates a new SwapCells operation.
+	#[inline]
+	pub fn new(a: Address, b: Address) -> SwapCells {
+		SwapCells {a: a, b: b}
+	}
+}
+
+
+impl PaletteOperation for SwapCells {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Swap Cells",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		if self.a != self.b {
+			data.move_cells(&[(self.a, self.b), (self.b, self.a)])?;
+		}
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(*self),
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// MoveCells
+////////////////////////////////////////////////////////////////////////////////
+/// Moves cells between arbitrary pairs of addresses simultaneously.
+///
+/// Every cell is removed before any is placed, so the moves may form cycles.
+/// Each destination must be empty or be the source of another move.
+#[derive(Debug, Clone)]
+pub struct MoveCells {
+	/// The source and destination address pairs.
+	moves: Vec<(Address, Address)>,
+}
+
+
+impl MoveCells {
+	/// Creates a new MoveCells operation.
+	#[inline]
+	pub fn new(moves: Vec<(Address, Address)>) -> MoveCells {
+		MoveCells {moves: moves}
+	}
+}
+
+
+impl PaletteOperation for MoveCells {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Move Cells",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		data.move_cells(&self.moves)?;
+
+		let undo = MoveCells::new(self.moves.iter()
+			.map(|&(from, to)| (to, from))
+			.collect());
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(undo),
+		})
+	}
+}
@@ -27,8 +27,10 @@
 ////////////////////////////////////////////////////////////////////////////////
 
 // Local imports.
-use address::Address;
-use data::Data;
+use address::{Address, Page};
+use animation::Cycle;
+use data::{Constraint, Data, DependentPolicy};
+use expression::Expression;
 use operation::{
 	set_target,
 	HistoryEntry,
@@ -36,8 +38,16 @@ use operation::{
 	PaletteOperation,
 	Undo,
 };
+use ramp::RampSpec;
 use result::Result;
 
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
 
 
 
@@ -130,26 +140,197 @@ impl PaletteOperation for InsertCell {
 
 
 ////////////////////////////////////////////////////////////////////////////////
-// DeleteCell
+// InsertColor
+////////////////////////////////////////////////////////////////////////////////
+/// Inserts a new `Color` into the palette.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+///
+/// pal.apply(Box::new(InsertColor::new(Color::new(12, 50, 78)))).unwrap();
+///
+/// assert_eq!(pal.color(Address::new(0, 0, 0)), Some(Color::new(12, 50, 78)));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct InsertColor {
+	/// The color to insert.
+	color: Color,
+	/// The location to place the color.
+	location: Option<Address>,
+	/// Whether to overwrite existing cells when generating new ones.
+	overwrite: bool,
+}
+
+
+impl InsertColor {
+	/// Creates a new InsertColor operation.
+	#[inline]
+	pub fn new(color: Color) -> InsertColor {
+		InsertColor {
+			color: color,
+			location: None,
+			overwrite: false,
+		}
+	}
+
+	/// Sets the location to place the color.
+	pub fn located_at(mut self, location: Address) -> InsertColor {
+		self.location = Some(location);
+		self
+	}
+
+	/// Configures the operation to overwrite existing cells when inserted.
+	pub fn overwrite(mut self, overwrite: bool) -> InsertColor {
+		self.overwrite = overwrite;
+		self
+	}
+}
+
+
+impl PaletteOperation for InsertColor {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Insert Color",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		// Get starting address.
+		let starting_address = if let Some(address) = self.location {
+			address
+		} else {
+			data.first_free_address_after(Default::default())?
+		};
+
+		// Get targets.
+		let target = data.find_targets(
+			1, 
+			starting_address,
+			self.overwrite,
+			None
+		)?[0];
+
+		// Set target.
+		let mut undo = Undo::new_for(self);
+		set_target(data, target, Expression::Color(self.color), &mut undo)?;
+		
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(undo),
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// SetExpression
 ////////////////////////////////////////////////////////////////////////////////
-/// Removes an cell from the palette.
+/// Replaces the `Expression` at the given address. The cell will be created if
+/// it is empty.
 /// 
 /// # Example
 ///
 /// ```rust
 /// use palette::*;
+/// use palette::operation::*;
 /// 
 /// let mut pal = Palette::new("Example", Format::Default, true);
+/// let address = Address::new(0, 0, 0);
+///
+/// pal.apply(Box::new(SetExpression::new(
+/// 	address, 
+/// 	Expression::Color(Color::new(12, 50, 78))
+/// ))).unwrap();
+/// 
+/// assert_eq!(pal.color(address), Some(Color::new(12, 50, 78)));
+///
+/// pal.undo().unwrap();
+/// assert_eq!(pal.color(address), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SetExpression {
+	/// The address of the cell to set.
+	address: Address,
+	/// The expression to place in the cell.
+	expression: Expression,
+}
+
+
+impl SetExpression {
+	/// Creates a new SetExpression operation.
+	#[inline]
+	pub fn new(address: Address, expression: Expression) -> SetExpression {
+		SetExpression {
+			address: address,
+			expression: expression,
+		}
+	}
+}
+
+
+impl PaletteOperation for SetExpression {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Set Expression",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let mut undo = Undo::new_for(self);
+		set_target(data, self.address, self.expression.clone(), &mut undo)?;
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(undo),
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// DeleteCell
+////////////////////////////////////////////////////////////////////////////////
+/// Removes an cell from the palette. By default, cells referenced by other
+/// cells can not be removed; see `DependentPolicy` for the alternatives.
+/// 
+/// # Example
 ///
-/// pal.apply(Box::new(InsertCell::new(Color::new(12, 50, 78)))).unwrap();
-/// pal.apply(Box::new(DeleteCell::new(Address::new(0, 0, 0)))).unwrap();
+/// ```rust
+/// use palette::*;
+/// use palette::data::DependentPolicy;
+/// use palette::operation::*;
 /// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+///
+/// pal.apply(Box::new(InsertColor::new(Color::new(12, 50, 78)))).unwrap();
+/// pal.apply(Box::new(InsertDerived::complement(Address::new(0, 0, 0))))
+/// 	.unwrap();
+/// let complement = pal.color(Address::new(0, 0, 1));
+/// assert!(pal.apply(Box::new(DeleteCell::new(Address::new(0, 0, 0))))
+/// 	.is_err());
+///
+/// pal.apply(Box::new(DeleteCell::new(Address::new(0, 0, 0))
+/// 	.with_policy(DependentPolicy::Cascade))).unwrap();
 /// assert_eq!(pal.len(), 0);
+///
+/// pal.undo().unwrap();
+/// assert_eq!(pal.color(Address::new(0, 0, 1)), complement);
 /// ```
 #[derive(Debug, Clone, Copy, Default)]
 pub struct DeleteCell {
 	/// The addres of the cell to remove.
 	address: Address,
+	/// The handling of cells referencing the removed cell.
+	policy: DependentPolicy,
 }
 
 
@@ -157,7 +338,16 @@ impl DeleteCell {
 	/// Creates a new DeleteCell operation targetting the given address.
 	#[inline]
 	pub fn new(address: Address) -> DeleteCell {
-		DeleteCell {address: address}
+		DeleteCell {
+			address: address,
+			policy: DependentPolicy::Prevent,
+		}
+	}
+
+	/// Sets the handling of cells referencing the removed cell.
+	pub fn with_policy(mut self, policy: DependentPolicy) -> Self {
+		self.policy = policy;
+		self
 	}
 }
 
@@ -173,11 +363,318 @@ impl PaletteOperation for DeleteCell {
 	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
 
 		let mut undo = Undo::new_for(self);
-		undo.record(self.address, Some(data.remove_cell(self.address)?));
+		for (address, cell, expr) in data
+			.take_cell_with(self.address, self.policy)? 
+		{
+			if data.cell(address).is_some() {
+				undo.record(address, Some(expr));
+			} else {
+				undo.record_removed(address, cell, expr);
+			}
+		}
 		
 		Ok(HistoryEntry {
 			info: self.info(),
 			undo: Box::new(undo),
 		})
 	}
-}
\ No newline at end of file
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// RemoveColor
+////////////////////////////////////////////////////////////////////////////////
+/// Removes the color from a cell, leaving an empty cell in its place. Cells 
+/// referencing the cell remain valid.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+///
+/// pal.apply(Box::new(InsertColor::new(Color::new(12, 50, 78)))).unwrap();
+/// pal.apply(Box::new(RemoveColor::new(Address::new(0, 0, 0)))).unwrap();
+/// assert_eq!(pal.len(), 1);
+/// assert_eq!(pal.color(Address::new(0, 0, 0)), None);
+///
+/// pal.undo().unwrap();
+/// assert_eq!(pal.color(Address::new(0, 0, 0)), Some(Color::new(12, 50, 78)));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RemoveColor {
+	/// The address of the cell to empty.
+	address: Address,
+}
+
+
+impl RemoveColor {
+	/// Creates a new RemoveColor operation targetting the given address.
+	#[inline]
+	pub fn new(address: Address) -> RemoveColor {
+		RemoveColor {
+			address: address,
+		}
+	}
+}
+
+
+impl PaletteOperation for RemoveColor {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Remove Color",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let mut undo = Undo::new_for(self);
+		let expr = data.remove_color(self.address)?;
+		undo.record(self.address, Some(expr));
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(undo),
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ClearPage
+////////////////////////////////////////////////////////////////////////////////
+/// Removes every cell on a page. No cells are removed if any cell on the page
+/// is locked, protected, or referenced by another cell.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+///
+/// pal.apply(Box::new(InsertColor::new(Color::new(12, 50, 78)))).unwrap();
+/// pal.apply(Box::new(InsertColor::new(Color::new(60, 25, 17)))).unwrap();
+/// pal.apply(Box::new(ClearPage::new(0))).unwrap();
+/// assert_eq!(pal.len(), 0);
+///
+/// pal.undo().unwrap();
+/// assert_eq!(pal.len(), 2);
+/// assert_eq!(pal.color(Address::new(0, 0, 1)), Some(Color::new(60, 25, 17)));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ClearPage {
+	/// The page to clear.
+	page: Page,
+}
+
+
+impl ClearPage {
+	/// Creates a new ClearPage operation targetting the given page.
+	#[inline]
+	pub fn new(page: Page) -> ClearPage {
+		ClearPage {
+			page: page,
+		}
+	}
+}
+
+
+impl PaletteOperation for ClearPage {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Clear Page",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		// Keep the removed cells, so that they may be restored by the undo.
+		let cells: Vec<_> = data.iter_page(self.page)
+			.filter_map(|(address, _)| data.cell(address))
+			.collect();
+
+		let mut undo = Undo::new_for(self);
+		for ((address, expr), cell) in data.clear_page(self.page)?
+			.into_iter()
+			.zip(cells)
+		{
+			undo.record_removed(address, cell, expr);
+		}
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(undo),
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// SetConstraint
+////////////////////////////////////////////////////////////////////////////////
+/// Places a constraint on a palette slot, or removes its constraint.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::data::Constraint;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertColor::new(Color::new(0, 0, 0)))).unwrap();
+///
+/// let address = Address::new(0, 0, 0);
+/// pal.apply(Box::new(SetConstraint::new(address, Some(Constraint::Locked))))
+/// 	.unwrap();
+/// assert!(pal.apply(Box::new(SetExpression::new(
+/// 	address, 
+/// 	Expression::Color(Color::new(10, 10, 10))))).is_err());
+///
+/// pal.undo().unwrap();
+/// assert_eq!(pal.data().constraint(address), None);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SetConstraint {
+	/// The address of the slot.
+	address: Address,
+	/// The constraint to place on the slot.
+	constraint: Option<Constraint>,
+}
+
+
+impl SetConstraint {
+	/// Creates a new SetConstraint operation.
+	#[inline]
+	pub fn new(address: Address, constraint: Option<Constraint>) 
+		-> SetConstraint
+	{
+		SetConstraint {address: address, constraint: constraint}
+	}
+}
+
+
+impl PaletteOperation for SetConstraint {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Set Constraint",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let cur = data.set_constraint(self.address, self.constraint);
+		
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(SetConstraint::new(self.address, cur)),
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// SetCycles
+////////////////////////////////////////////////////////////////////////////////
+/// Replaces the color cycles of the palette.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::animation::Cycle;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertColor::new(Color::new(10, 10, 10)))).unwrap();
+/// pal.apply(Box::new(InsertColor::new(Color::new(20, 20, 20)))).unwrap();
+///
+/// let (a, b) = (Address::new(0, 0, 0), Address::new(0, 0, 1));
+/// pal.apply(Box::new(SetCycles::new(vec![Cycle::new(a, b, 1.0)]))).unwrap();
+///
+/// assert_eq!(pal.at_time(1.0), vec![
+/// 	(a, Color::new(20, 20, 20)),
+/// 	(b, Color::new(10, 10, 10)),
+/// ]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SetCycles {
+	/// The cycles to place in the palette.
+	cycles: Vec<Cycle>,
+}
+
+
+impl SetCycles {
+	/// Creates a new SetCycles operation.
+	#[inline]
+	pub fn new(cycles: Vec<Cycle>) -> SetCycles {
+		SetCycles {cycles: cycles}
+	}
+}
+
+
+impl PaletteOperation for SetCycles {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Set Cycles",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let cur = data.set_cycles(self.cycles.clone());
+		
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(SetCycles::new(cur)),
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// SetRamps
+////////////////////////////////////////////////////////////////////////////////
+/// Replaces the ramp specs stored in the palette. The cells of the ramps are 
+/// not changed.
+#[derive(Debug, Clone)]
+pub struct SetRamps {
+	/// The ramp specs to place in the palette.
+	ramps: Vec<RampSpec>,
+}
+
+
+impl SetRamps {
+	/// Creates a new SetRamps operation.
+	#[inline]
+	pub fn new(ramps: Vec<RampSpec>) -> SetRamps {
+		SetRamps {ramps: ramps}
+	}
+}
+
+
+impl PaletteOperation for SetRamps {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Set Ramps",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let cur = data.set_ramps(self.ramps.clone());
+		
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(SetRamps::new(cur)),
+		})
+	}
+}
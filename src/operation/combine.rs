@@ -33,9 +33,12 @@ use operation::{
 	OperationInfo,
 	PaletteOperation,
 };
+use operation::transaction::rollback;
 use result::Result;
 
 // Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
 use std::mem;
 
 
@@ -43,7 +46,8 @@ use std::mem;
 ////////////////////////////////////////////////////////////////////////////////
 // Sequence
 ////////////////////////////////////////////////////////////////////////////////
-/// Applies a sequence of operations to the palette.
+/// Applies a sequence of operations to the palette. If any operation fails, 
+/// the preceding operations are reverted.
 ///
 /// # Example
 ///
@@ -90,10 +94,17 @@ impl PaletteOperation for Sequence {
 
 		let operations = mem::replace(&mut self.operations, Vec::new());
 		for mut operation in operations {
-			let entry = operation.apply(data)?;
-			undo_sequence.push(entry.undo);
+			match operation.apply(data) {
+				Ok(entry) => undo_sequence.push(entry.undo),
+				Err(e) => {
+					rollback(data, undo_sequence);
+					return Err(e);
+				},
+			}
 		}
 
+		// Undo the operations in reverse order.
+		undo_sequence.reverse();
 		Ok(HistoryEntry {
 			info: self.info(),
 			undo: Box::new(Sequence::new(undo_sequence)),
@@ -162,10 +173,17 @@ impl PaletteOperation for Repeat {
 		let mut undo_sequence: Vec<Box<PaletteOperation>> = Vec::new();
 
 		for _ in 0..self.repeat_count {
-			let entry = self.operation.apply(data)?;
-			undo_sequence.push(entry.undo);
+			match self.operation.apply(data) {
+				Ok(entry) => undo_sequence.push(entry.undo),
+				Err(e) => {
+					rollback(data, undo_sequence);
+					return Err(e);
+				},
+			}
 		}
 
+		// Undo the operations in reverse order.
+		undo_sequence.reverse();
 		Ok(HistoryEntry {
 			info: self.info(),
 			undo: Box::new(Sequence::new(undo_sequence)),
@@ -0,0 +1,375 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Defines operations for creating derived colors.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::Address;
+use data::Data;
+use expression::Expression;
+use operation::{
+	set_target,
+	source,
+	HistoryEntry,
+	OperationInfo,
+	PaletteOperation,
+	Undo,
+};
+use result::Result;
+use space::{Adjustment, Easing, Interpolation};
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Derivation
+////////////////////////////////////////////////////////////////////////////////
+/// Describes how a derived color is generated from its source addresses.
+#[derive(Debug, Clone, Copy)]
+enum Derivation {
+	Blend(Address, Address, f32),
+	Lighten(Address, f32),
+	Darken(Address, f32),
+	Complement(Address),
+	Adjust(Address, Adjustment),
+}
+
+
+impl Derivation {
+	/// Returns the source addresses of the derivation.
+	fn sources(&self) -> Vec<Address> {
+		match *self {
+			Derivation::Blend(a, b, _)	=> vec![a, b],
+			Derivation::Lighten(a, _)	|
+			Derivation::Darken(a, _)	|
+			Derivation::Complement(a)	|
+			Derivation::Adjust(a, _)	=> vec![a],
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// InsertDerived
+////////////////////////////////////////////////////////////////////////////////
+/// Inserts a new derived `Expression` into the palette. The generated color 
+/// will update whenever its source cells are changed.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertColor::new(Color::new(100, 50, 0)))).unwrap();
+///
+/// pal.apply(Box::new(InsertDerived::complement(Address::new(0, 0, 0))))
+/// 	.unwrap();
+///
+/// assert_eq!(pal.color(Address::new(0, 0, 1)), Some(Color::new(0, 50, 100)));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct InsertDerived {
+	/// The derivation of the color.
+	derivation: Derivation,
+	/// The location to place the color.
+	location: Option<Address>,
+	/// Whether to overwrite existing cells when generating new ones.
+	overwrite: bool,
+	/// Whether to create empty source cells if they do not exist.
+	make_sources: bool,
+	/// The color space to interpolate blended colors in.
+	interpolation: Interpolation,
+	/// The easing curve applied to blend ratios.
+	easing: Easing,
+}
+
+
+impl InsertDerived {
+	/// Creates a new InsertDerived operation with the given derivation.
+	fn new(derivation: Derivation) -> InsertDerived {
+		InsertDerived {
+			derivation: derivation,
+			location: None,
+			overwrite: false,
+			make_sources: false,
+			interpolation: Interpolation::Rgb,
+			easing: Easing::Linear,
+		}
+	}
+
+	/// Creates a new InsertDerived operation which blends the colors at the 
+	/// given addresses with the given ratio.
+	#[inline]
+	pub fn blend(a: Address, b: Address, ratio: f32) -> InsertDerived {
+		InsertDerived::new(Derivation::Blend(a, b, ratio))
+	}
+
+	/// Creates a new InsertDerived operation which lightens the color at the 
+	/// given address by the given amount.
+	#[inline]
+	pub fn lighten(a: Address, amount: f32) -> InsertDerived {
+		InsertDerived::new(Derivation::Lighten(a, amount))
+	}
+
+	/// Creates a new InsertDerived operation which darkens the color at the 
+	/// given address by the given amount.
+	#[inline]
+	pub fn darken(a: Address, amount: f32) -> InsertDerived {
+		InsertDerived::new(Derivation::Darken(a, amount))
+	}
+
+	/// Creates a new InsertDerived operation which complements the color at 
+	/// the given address.
+	#[inline]
+	pub fn complement(a: Address) -> InsertDerived {
+		InsertDerived::new(Derivation::Complement(a))
+	}
+
+	/// Creates a new InsertDerived operation which applies the given 
+	/// adjustment to the color at the given address.
+	#[inline]
+	pub fn adjust(a: Address, adjustment: Adjustment) -> InsertDerived {
+		InsertDerived::new(Derivation::Adjust(a, adjustment))
+	}
+
+	/// Sets the location to place the color.
+	pub fn located_at(mut self, location: Address) -> InsertDerived {
+		self.location = Some(location);
+		self
+	}
+
+	/// Configures the operation to overwrite existing cells when inserted.
+	pub fn overwrite(mut self, overwrite: bool) -> InsertDerived {
+		self.overwrite = overwrite;
+		self
+	}
+
+	/// Configures the operation to create empty source cells if they do not 
+	/// exist.
+	pub fn make_sources(mut self, make_sources: bool) -> InsertDerived {
+		self.make_sources = make_sources;
+		self
+	}
+
+	/// Sets the color space to interpolate blended colors in.
+	pub fn interpolation(mut self, interpolation: Interpolation) 
+		-> InsertDerived
+	{
+		self.interpolation = interpolation;
+		self
+	}
+
+	/// Sets the easing curve applied to blend ratios.
+	pub fn easing(mut self, easing: Easing) -> InsertDerived {
+		self.easing = easing;
+		self
+	}
+}
+
+
+impl PaletteOperation for InsertDerived {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Insert Derived",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let mut undo = Undo::new_for(self);
+
+		// Build expression.
+		let make_sources = self.make_sources;
+		let expr = match self.derivation {
+			Derivation::Blend(a, b, ratio) => Expression::Blend {
+				a: source(data, a, make_sources, &mut undo)?,
+				b: source(data, b, make_sources, &mut undo)?,
+				ratio: ratio,
+				interpolation: self.interpolation,
+				easing: self.easing,
+			},
+
+			Derivation::Lighten(a, amount) => Expression::Lighten {
+				a: source(data, a, make_sources, &mut undo)?,
+				amount: amount,
+			},
+
+			Derivation::Darken(a, amount) => Expression::Darken {
+				a: source(data, a, make_sources, &mut undo)?,
+				amount: amount,
+			},
+
+			Derivation::Complement(a) => Expression::Complement {
+				a: source(data, a, make_sources, &mut undo)?,
+			},
+
+			Derivation::Adjust(a, adjustment) => Expression::Adjust {
+				a: source(data, a, make_sources, &mut undo)?,
+				adjustment: adjustment,
+			},
+		};
+
+		// Get starting address.
+		let starting_address = if let Some(address) = self.location {
+			address
+		} else {
+			data.first_free_address_after(Default::default())?
+		};
+
+		// Get target, excluding the sources.
+		let target = data.find_targets(
+			1,
+			starting_address,
+			self.overwrite,
+			Some(self.derivation.sources())
+		)?[0];
+
+		// Set target.
+		set_target(data, target, expr, &mut undo)?;
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(undo),
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// FreezeCell
+////////////////////////////////////////////////////////////////////////////////
+/// Detaches the derived color at the given address from its sources, keeping
+/// its current color and remembering how it was derived. See `Data::freeze`.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertColor::new(Color::new(100, 50, 0)))).unwrap();
+/// pal.apply(Box::new(InsertDerived::complement(Address::new(0, 0, 0))))
+/// 	.unwrap();
+///
+/// pal.apply(Box::new(FreezeCell::new(Address::new(0, 0, 1)))).unwrap();
+/// pal.apply(Box::new(SetExpression::new(
+/// 	Address::new(0, 0, 0), 
+/// 	Expression::Color(Color::new(0, 0, 0))))).unwrap();
+/// assert_eq!(pal.color(Address::new(0, 0, 1)), Some(Color::new(0, 50, 100)));
+///
+/// pal.apply(Box::new(ThawCell::new(Address::new(0, 0, 1)))).unwrap();
+/// assert_eq!(pal.color(Address::new(0, 0, 1)), Some(Color::new(0, 0, 0)));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct FreezeCell {
+	/// The address of the cell to freeze.
+	address: Address,
+}
+
+
+impl FreezeCell {
+	/// Creates a new FreezeCell operation targetting the given address.
+	#[inline]
+	pub fn new(address: Address) -> FreezeCell {
+		FreezeCell {address: address}
+	}
+}
+
+
+impl PaletteOperation for FreezeCell {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Freeze Cell",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let mut undo = Undo::new_for(self);
+		let expr = data.cell(self.address)
+			.map(|cell| cell.borrow().clone());
+		data.freeze(self.address)?;
+		undo.record(self.address, expr);
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(undo),
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ThawCell
+////////////////////////////////////////////////////////////////////////////////
+/// Restores the derived color a frozen cell was detached from. See 
+/// `Data::thaw`.
+#[derive(Debug, Clone, Copy)]
+pub struct ThawCell {
+	/// The address of the cell to thaw.
+	address: Address,
+}
+
+
+impl ThawCell {
+	/// Creates a new ThawCell operation targetting the given address.
+	#[inline]
+	pub fn new(address: Address) -> ThawCell {
+		ThawCell {address: address}
+	}
+}
+
+
+impl PaletteOperation for ThawCell {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Thaw Cell",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let mut undo = Undo::new_for(self);
+		let expr = data.cell(self.address)
+			.map(|cell| cell.borrow().clone());
+		data.thaw(self.address)?;
+		undo.record(self.address, expr);
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(undo),
+		})
+	}
+}
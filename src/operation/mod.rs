@@ -30,33 +30,90 @@
 
 // Sumbodules.
 #[warn(missing_docs)]
+mod adjust;
+#[warn(missing_docs)]
+mod arrange;
+#[warn(missing_docs)]
 mod basic;
 #[warn(missing_docs)]
 mod combine;
 #[warn(missing_docs)]
+mod derive;
+#[warn(missing_docs)]
+mod patch;
+#[warn(missing_docs)]
+mod ramp;
+#[cfg(feature = "std")]
+#[warn(missing_docs)]
+pub mod script;
+#[warn(missing_docs)]
+mod sort;
+#[warn(missing_docs)]
+mod transaction;
+#[warn(missing_docs)]
 mod undo;
 
 // Submodule re-exports.
+pub use self::adjust::{
+	AdjustSelection,
+	ColorShift,
+	Posterize,
+	SetSaturation,
+	ShiftHue,
+};
+pub use self::arrange::{
+	CopyRange,
+	CopySelection,
+	MoveCells,
+	MoveRange,
+	SwapCells,
+};
 pub use self::basic::{
+	ClearPage,
 	InsertCell,
+	InsertColor,
 	DeleteCell,
+	RemoveColor,
+	SetConstraint,
+	SetCycles,
+	SetExpression,
+	SetRamps,
 };
 pub use self::combine::{
 	Repeat,
 	Sequence,
 };
+pub use self::derive::{
+	FreezeCell,
+	InsertDerived,
+	ThawCell,
+};
+pub use self::patch::ApplyDiff;
+pub use self::ramp::{
+	AutoRamp,
+	BuildRamp,
+	CreateRamp,
+	InsertShades,
+};
+pub use self::sort::{
+	Compare,
+	SortCells,
+	SortKey,
+};
+pub use self::transaction::Transaction;
 pub use self::undo::Undo;
 
 // Local imports.
 use address::Address;
-use cell::Cell;
+use cell::{Cell, Shared, Weak};
 use data::Data;
 use expression::Expression;
 use result::{Error, Result};
 
 // Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
 use std::fmt;
-use std::rc::{Rc, Weak};
 use std::mem;
 
 
@@ -72,9 +129,9 @@ pub(crate) fn source(
 	-> Result<Weak<Cell>>
 {
 	if let Some(cell) = data.cell(address) {
-		Ok(Rc::downgrade(&cell))
+		Ok(Shared::downgrade(&cell))
 	} else if make_sources {
-		let cell = Rc::downgrade(&data.create_cell(address)?);
+		let cell = Shared::downgrade(&data.create_cell(address)?);
 		undo.record(address, None);
 		Ok(cell)
 	} else {
@@ -88,7 +145,7 @@ pub(crate) fn target(
 	data: &mut Data, 
 	address: Address, 
 	undo: &mut Undo)
-	-> Result<Rc<Cell>>
+	-> Result<Shared<Cell>>
 {
 	if let Some(cell) = data.cell(address) {
 		Ok(cell)
@@ -100,7 +157,8 @@ pub(crate) fn target(
 }
 
 /// Stores the given Expression in the cell at the given address in the given 
-/// palette. If the cell is empty, it will be created.
+/// palette. If the cell is empty, it will be created. Fixed colors stored in a
+/// frozen cell keep its recipe, so that hand-tweaked colors may be thawed.
 pub(crate) fn set_target(
 	data: &mut Data,
 	address: Address,
@@ -109,10 +167,31 @@ pub(crate) fn set_target(
 	-> Result<()>
 {
 	// Get the target cell.
+	data.check_editable(address)?;
 	let target = target(data, address, undo)?;
 
+	// Keep the recipe of a frozen cell.
+	let new_element = match (&*target.borrow(), new_element) {
+		(&Expression::Frozen {ref recipe, ..}, Expression::Color(color)) 
+			=> Expression::Frozen {color: color, recipe: recipe.clone()},
+		(_, new_element) => new_element,
+	};
+
+	// Ensure the new element won't create a reference cycle.
+	if new_element.depends_on(&target) {
+		return Err(Error::CircularReference(address));
+	}
+
 	// Insert new element into palette.
+	let empty = match new_element {
+		Expression::Empty => true,
+		_ => false,
+	};
 	let cur = mem::replace(&mut *target.borrow_mut(), new_element);
+	if empty {
+		data.mark_free(address);
+	}
+	data.mark_modified(address);
 	undo.record(address, Some(cur));
 	Ok(())
 }
@@ -123,7 +202,7 @@ pub(crate) fn set_target(
 // PaletteOperation
 ////////////////////////////////////////////////////////////////////////////////
 /// Provides the methods for modifying palettes.
-pub trait PaletteOperation: fmt::Debug {
+pub trait PaletteOperation: fmt::Debug + MaybeSync {
 	/// Returns information about the operation.
 	fn info(&self) -> OperationInfo;
 
@@ -134,6 +213,24 @@ pub trait PaletteOperation: fmt::Debug {
 
 
 
+/// A marker trait for types which can be shared between threads when the `sync`
+/// feature is enabled. Without the feature, all types implement it.
+#[cfg(feature = "sync")]
+pub trait MaybeSync: Send + Sync {}
+
+#[cfg(feature = "sync")]
+impl<T> MaybeSync for T where T: Send + Sync {}
+
+/// A marker trait for types which can be shared between threads when the `sync`
+/// feature is enabled. Without the feature, all types implement it.
+#[cfg(not(feature = "sync"))]
+pub trait MaybeSync {}
+
+#[cfg(not(feature = "sync"))]
+impl<T> MaybeSync for T {}
+
+
+
 ////////////////////////////////////////////////////////////////////////////////
 // OperationHistory
 ////////////////////////////////////////////////////////////////////////////////
@@ -148,6 +245,14 @@ pub struct OperationHistory {
 }
 
 
+/// The history of a palette's operations, undone and redone with 
+/// `Palette::undo` and `Palette::redo`. Every change to palette data made 
+/// through `Palette::apply` is recorded, including the `InsertColor`, 
+/// `SetExpression`, `DeleteCell`, `RemoveColor`, and `ClearPage` operations 
+/// which mirror the `Data` methods for adding, setting, and removing colors.
+pub type History = OperationHistory;
+
+
 
 
 ////////////////////////////////////////////////////////////////////////////////
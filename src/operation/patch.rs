@@ -0,0 +1,131 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Defines operations for applying palette diffs.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use data::Data;
+use diff::PaletteDiff;
+use expression::Expression;
+use operation::{
+	set_target,
+	HistoryEntry,
+	OperationInfo,
+	PaletteOperation,
+	Undo,
+};
+use result::{Error, Result};
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ApplyDiff
+////////////////////////////////////////////////////////////////////////////////
+/// Applies the changes in a `PaletteDiff` to the palette.
+///
+/// Added and changed addresses are set to their new colors, replacing any 
+/// derived expressions. Removed addresses are deleted, unless the cell is 
+/// referenced by another cell, in which case its color is removed and the 
+/// empty cell is left in place.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// 
+/// let mut a = Palette::new("A", Format::Default, true);
+/// a.apply(Box::new(InsertColor::new(Color::new(10, 10, 10)))).unwrap();
+///
+/// let mut b = Palette::new("B", Format::Default, true);
+/// b.apply(Box::new(InsertColor::new(Color::new(40, 40, 40)))).unwrap();
+/// b.apply(Box::new(InsertColor::new(Color::new(50, 50, 50)))).unwrap();
+///
+/// let diff = a.data().diff(b.data());
+/// a.apply(Box::new(ApplyDiff::new(diff))).unwrap();
+/// assert!(a.data().diff(b.data()).is_empty());
+///
+/// a.undo().unwrap();
+/// assert_eq!(a.color(Address::new(0, 0, 0)), Some(Color::new(10, 10, 10)));
+/// assert_eq!(a.color(Address::new(0, 0, 1)), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ApplyDiff {
+	/// The changes to apply.
+	diff: PaletteDiff,
+}
+
+
+impl ApplyDiff {
+	/// Creates a new ApplyDiff operation.
+	#[inline]
+	pub fn new(diff: PaletteDiff) -> ApplyDiff {
+		ApplyDiff {diff: diff}
+	}
+}
+
+
+impl PaletteOperation for ApplyDiff {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Apply Diff",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let mut undo = Undo::new_for(self);
+
+		for (address, change) in self.diff.iter() {
+			match change.after() {
+				Some(color) => set_target(
+					data, 
+					address, 
+					Expression::Color(color), 
+					&mut undo)?,
+
+				None => match data.remove_cell(address) {
+					Ok(expr) => undo.record(address, Some(expr)),
+					Err(Error::CellInUse {..}) => {
+						let expr = data.remove_color(address)?;
+						undo.record(address, Some(expr));
+					},
+					Err(Error::EmptyAddress(_)) => (),
+					Err(e) => return Err(e),
+				},
+			}
+		}
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(undo),
+		})
+	}
+}
@@ -0,0 +1,575 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Defines color ramp generation operations.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{Address, Column, Line, Page, Reference};
+use data::Data;
+use expression::Expression;
+use operation::{
+	set_target,
+	source,
+	HistoryEntry,
+	MoveCells,
+	OperationInfo,
+	PaletteOperation,
+	Sequence,
+	SetRamps,
+	Undo,
+};
+use ramp::{auto_ramp_with, AutoRampOptions, RampSpec};
+use result::{Error, Result};
+use shade::{generate_shades, ShadeOptions};
+use space::{Easing, Interpolation};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// CreateRamp
+////////////////////////////////////////////////////////////////////////////////
+/// Inserts a sequence of `Expression`s which linearly interpolate between the 
+/// colors of two existing cells. The generated cells will update whenever 
+/// either source cell is changed.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertColor::new(Color::new(0, 0, 0)))).unwrap();
+/// pal.apply(Box::new(InsertColor::new(Color::new(100, 100, 100)))).unwrap();
+///
+/// pal.apply(Box::new(CreateRamp::new(
+/// 	Address::new(0, 0, 0),
+/// 	Address::new(0, 0, 1),
+/// 	3
+/// ))).unwrap();
+///
+/// assert_eq!(pal.color(Address::new(0, 0, 2)), Some(Color::new(25, 25, 25)));
+/// assert_eq!(pal.color(Address::new(0, 0, 3)), Some(Color::new(50, 50, 50)));
+/// assert_eq!(pal.color(Address::new(0, 0, 4)), Some(Color::new(75, 75, 75)));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct CreateRamp {
+	/// The address of the color to start the ramp from.
+	start: Address,
+	/// The address of the color to end the ramp at.
+	end: Address,
+	/// The number of colors to generate.
+	steps: usize,
+	/// The location to start placing the colors.
+	location: Option<Address>,
+	/// Whether to overwrite existing cells when generating new ones.
+	overwrite: bool,
+	/// The color space to interpolate in.
+	interpolation: Interpolation,
+	/// The easing curve applied to the ramp.
+	easing: Easing,
+}
+
+
+impl CreateRamp {
+	/// Creates a new CreateRamp operation generating the given number of steps
+	/// between the given addresses.
+	#[inline]
+	pub fn new(start: Address, end: Address, steps: usize) -> CreateRamp {
+		CreateRamp {
+			start: start,
+			end: end,
+			steps: steps,
+			location: None,
+			overwrite: false,
+			interpolation: Interpolation::Rgb,
+			easing: Easing::Linear,
+		}
+	}
+
+	/// Sets the location to start placing the generated colors.
+	pub fn located_at(mut self, location: Address) -> CreateRamp {
+		self.location = Some(location);
+		self
+	}
+
+	/// Configures the operation to overwrite existing cells when inserted.
+	pub fn overwrite(mut self, overwrite: bool) -> CreateRamp {
+		self.overwrite = overwrite;
+		self
+	}
+
+	/// Sets the color space to interpolate the ramp in.
+	pub fn interpolation(mut self, interpolation: Interpolation) -> CreateRamp {
+		self.interpolation = interpolation;
+		self
+	}
+
+	/// Sets the easing curve applied to the ramp.
+	pub fn easing(mut self, easing: Easing) -> CreateRamp {
+		self.easing = easing;
+		self
+	}
+}
+
+
+impl PaletteOperation for CreateRamp {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Create Ramp",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let mut undo = Undo::new_for(self);
+
+		// Get sources.
+		let a = source(data, self.start, false, &mut undo)?;
+		let b = source(data, self.end, false, &mut undo)?;
+
+		// Get starting address.
+		let starting_address = if let Some(address) = self.location {
+			address
+		} else {
+			data.first_free_address_after(Default::default())?
+		};
+
+		// Get targets, excluding the sources.
+		let targets = data.find_targets(
+			self.steps,
+			starting_address,
+			self.overwrite,
+			Some(vec![self.start, self.end])
+		)?;
+
+		// Set targets.
+		let denominator = (self.steps + 1) as f32;
+		for (i, &target) in targets.iter().enumerate() {
+			let expr = Expression::Blend {
+				a: a.clone(),
+				b: b.clone(),
+				ratio: (i + 1) as f32 / denominator,
+				interpolation: self.interpolation,
+				easing: self.easing,
+			};
+			set_target(data, target, expr, &mut undo)?;
+		}
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(undo),
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// BuildRamp
+////////////////////////////////////////////////////////////////////////////////
+/// Inserts the colors of a ramp described by a `RampSpec`, and stores the 
+/// spec in the palette. The generated cells will update whenever any anchor 
+/// cell is changed.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// use palette::ramp::RampSpec;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertColor::new(Color::new(0, 0, 0)))).unwrap();
+/// pal.apply(Box::new(InsertColor::new(Color::new(100, 100, 100)))).unwrap();
+/// pal.apply(Box::new(InsertColor::new(Color::new(100, 0, 0)))).unwrap();
+///
+/// let anchors = vec![
+/// 	Address::new(0, 0, 0), 
+/// 	Address::new(0, 0, 1), 
+/// 	Address::new(0, 0, 2),
+/// ];
+/// let spec = RampSpec::new(anchors, 1, Address::new(0, 1, 0));
+/// pal.apply(Box::new(BuildRamp::new(spec))).unwrap();
+///
+/// assert_eq!(pal.color(Address::new(0, 1, 0)), Some(Color::new(50, 50, 50)));
+/// assert_eq!(pal.color(Address::new(0, 1, 1)), Some(Color::new(100, 50, 50)));
+/// assert_eq!(pal.data().ramps().len(), 1);
+///
+/// pal.undo().unwrap();
+/// assert_eq!(pal.color(Address::new(0, 1, 0)), None);
+/// assert_eq!(pal.data().ramps().len(), 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BuildRamp {
+	/// The ramp to build.
+	spec: RampSpec,
+	/// Whether to overwrite existing cells when generating new ones.
+	overwrite: bool,
+}
+
+
+impl BuildRamp {
+	/// Creates a new BuildRamp operation for the given ramp.
+	#[inline]
+	pub fn new(spec: RampSpec) -> BuildRamp {
+		BuildRamp {
+			spec: spec,
+			overwrite: false,
+		}
+	}
+
+	/// Configures the operation to overwrite existing cells when inserted.
+	pub fn overwrite(mut self, overwrite: bool) -> BuildRamp {
+		self.overwrite = overwrite;
+		self
+	}
+}
+
+
+impl PaletteOperation for BuildRamp {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Build Ramp",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let mut undo = Undo::new_for(self);
+
+		// Get sources.
+		let mut sources = Vec::with_capacity(self.spec.anchors.len());
+		for &anchor in &self.spec.anchors {
+			sources.push((anchor, source(data, anchor, false, &mut undo)?));
+		}
+		let source_of = |address: Address| sources.iter()
+			.find(|&&(anchor, _)| anchor == address)
+			.map(|&(_, ref source)| source.clone())
+			.expect("ramp anchor source");
+
+		// Get targets, excluding the anchors.
+		let targets = data.find_targets(
+			self.spec.len(),
+			self.spec.location,
+			self.overwrite,
+			Some(self.spec.anchors.clone())
+		)?;
+
+		// Set targets.
+		for ((a, b, ratio), target) in self.spec.segments()
+			.into_iter()
+			.zip(targets)
+		{
+			let expr = Expression::Blend {
+				a: source_of(a),
+				b: source_of(b),
+				ratio: ratio,
+				interpolation: self.spec.interpolation,
+				easing: self.spec.easing,
+			};
+			set_target(data, target, expr, &mut undo)?;
+		}
+
+		// Store the spec.
+		let mut ramps = data.ramps().to_vec();
+		ramps.push(self.spec.clone());
+		let prev = data.set_ramps(ramps);
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(Sequence::new(vec![
+				Box::new(SetRamps::new(prev)),
+				Box::new(undo),
+			])),
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// InsertShades
+////////////////////////////////////////////////////////////////////////////////
+/// Inserts a shading ramp generated from a base color, ordered from darkest 
+/// to lightest. The generated colors are independent of one another.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// use palette::shade::ShadeOptions;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// let base = Color::new(60, 140, 70);
+///
+/// pal.apply(Box::new(InsertShades::new(base, 5)
+/// 	.options(ShadeOptions::new().hue_shift(30.0)))).unwrap();
+///
+/// assert_eq!(pal.data().len(), 5);
+/// assert_eq!(pal.color(Address::new(0, 0, 2)), Some(base));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct InsertShades {
+	/// The base color of the ramp.
+	base: Color,
+	/// The number of colors to generate.
+	count: usize,
+	/// The options controlling the shape of the ramp.
+	options: ShadeOptions,
+	/// The location to start placing the colors.
+	location: Option<Address>,
+	/// Whether to overwrite existing cells when generating new ones.
+	overwrite: bool,
+}
+
+
+impl InsertShades {
+	/// Creates a new InsertShades operation generating the given number of 
+	/// colors from the given base color.
+	#[inline]
+	pub fn new(base: Color, count: usize) -> InsertShades {
+		InsertShades {
+			base: base,
+			count: count,
+			options: Default::default(),
+			location: None,
+			overwrite: false,
+		}
+	}
+
+	/// Sets the options controlling the shape of the ramp.
+	pub fn options(mut self, options: ShadeOptions) -> InsertShades {
+		self.options = options;
+		self
+	}
+
+	/// Sets the location to start placing the generated colors.
+	pub fn located_at(mut self, location: Address) -> InsertShades {
+		self.location = Some(location);
+		self
+	}
+
+	/// Configures the operation to overwrite existing cells when inserted.
+	pub fn overwrite(mut self, overwrite: bool) -> InsertShades {
+		self.overwrite = overwrite;
+		self
+	}
+}
+
+
+impl PaletteOperation for InsertShades {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Insert Shades",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let mut undo = Undo::new_for(self);
+
+		// Get starting address.
+		let starting_address = if let Some(address) = self.location {
+			address
+		} else {
+			data.first_free_address_after(Default::default())?
+		};
+
+		// Get targets.
+		let targets = data.find_targets(
+			self.count,
+			starting_address,
+			self.overwrite,
+			None
+		)?;
+
+		// Set targets.
+		let shades = generate_shades(self.base, self.count, &self.options);
+		for (color, target) in shades.into_iter().zip(targets) {
+			set_target(data, target, Expression::Color(color), &mut undo)?;
+		}
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(undo),
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// AutoRamp
+////////////////////////////////////////////////////////////////////////////////
+/// Groups the colors of the palette into ramps, moves each ramp onto its own
+/// line of a page, and stores a `RampSpec` recording each ramp. See 
+/// `ramp::auto_ramp` for how ramps are detected.
+///
+/// Ramps are placed on consecutive lines starting from the first line of the
+/// page, ordered from darkest to lightest. Ramps longer than the first line 
+/// are split, and an `InvalidRampLength` error is returned if a ramp does not
+/// fit on a shorter later line, or if the maximum ramp length is zero. The 
+/// cells themselves are moved, so any cells referencing them will remain 
+/// valid, and their names and metadata move with them. The stored specs 
+/// have no steps; they record the anchors of each ramp so that the ramp may 
+/// later be rebuilt with generated colors.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertColor::new(Color::new(0, 0, 255)))).unwrap();
+/// pal.apply(Box::new(InsertColor::new(Color::new(255, 0, 0)))).unwrap();
+/// pal.apply(Box::new(InsertColor::new(Color::new(0, 0, 0)))).unwrap();
+/// pal.apply(Box::new(InsertColor::new(Color::new(100, 0, 0)))).unwrap();
+/// pal.apply(Box::new(InsertColor::new(Color::new(255, 255, 255)))).unwrap();
+///
+/// pal.apply(Box::new(AutoRamp::new())).unwrap();
+///
+/// assert_eq!(pal.color(Address::new(0, 0, 1)), 
+/// 	Some(Color::new(255, 255, 255)));
+/// assert_eq!(pal.color(Address::new(0, 1, 0)), Some(Color::new(0, 0, 255)));
+/// assert_eq!(pal.color(Address::new(0, 2, 0)), Some(Color::new(100, 0, 0)));
+/// assert_eq!(pal.color(Address::new(0, 2, 1)), Some(Color::new(255, 0, 0)));
+/// assert_eq!(pal.data().ramps().len(), 3);
+///
+/// pal.undo().unwrap();
+/// assert_eq!(pal.color(Address::new(0, 0, 0)), Some(Color::new(0, 0, 255)));
+/// assert_eq!(pal.data().ramps().len(), 0);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AutoRamp {
+	/// The page to place the ramps on.
+	page: Page,
+	/// The options controlling the detection of ramps.
+	options: AutoRampOptions,
+}
+
+
+impl AutoRamp {
+	/// Creates a new AutoRamp operation placing the ramps on the first page.
+	#[inline]
+	pub fn new() -> AutoRamp {
+		AutoRamp {
+			page: 0,
+			options: Default::default(),
+		}
+	}
+
+	/// Sets the page to place the ramps on.
+	pub fn page(mut self, page: Page) -> AutoRamp {
+		self.page = page;
+		self
+	}
+
+	/// Sets the options controlling the detection of ramps.
+	pub fn options(mut self, options: AutoRampOptions) -> AutoRamp {
+		self.options = options;
+		self
+	}
+}
+
+
+impl PaletteOperation for AutoRamp {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Auto Ramp",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		if self.options.max_len == Some(0) {
+			return Err(Error::InvalidRampLength(0));
+		}
+		let first = Address::new(self.page, 0, 0);
+		let lines = data.line_count(&Reference::page_of(&first)) as usize;
+		let columns = data.column_count(&Reference::line_of(&first)) as usize;
+		if columns == 0 {
+			return Err(Error::InvalidAddress(first));
+		}
+		let max_len = self.options.max_len
+			.map_or(columns, |len| len.min(columns));
+		let ramps = auto_ramp_with(data, &self.options.max_len(Some(max_len)));
+		if ramps.len() > lines {
+			return Err(Error::InvalidAddress(
+				Address::new(self.page, lines as Line, 0)));
+		}
+
+		// Place each ramp at the start of its line.
+		let mut moves = Vec::new();
+		let mut specs = Vec::with_capacity(ramps.len());
+		for (line, ramp) in ramps.into_iter().enumerate() {
+			// Lines may be shorter than the first line of the page.
+			let start = Address::new(self.page, line as Line, 0);
+			let columns = data.column_count(&Reference::line_of(&start));
+			if ramp.len() > columns as usize {
+				return Err(Error::InvalidRampLength(ramp.len()));
+			}
+			let anchors: Vec<Address> = (0..ramp.len())
+				.map(|column| Address::new(
+					self.page, 
+					line as Line, 
+					column as Column))
+				.collect();
+			moves.extend(ramp.into_iter()
+				.zip(anchors.iter().cloned())
+				.filter(|&(from, to)| from != to));
+			specs.push(RampSpec::new(anchors, 0, start));
+		}
+		data.move_cells(&moves)?;
+
+		// Store the specs.
+		let mut ramps = data.ramps().to_vec();
+		ramps.extend(specs);
+		let prev = data.set_ramps(ramps);
+
+		// The arrangement is undone by moving the cells back.
+		let undo = MoveCells::new(moves.into_iter()
+			.map(|(from, to)| (to, from))
+			.collect());
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(Sequence::new(vec![
+				Box::new(SetRamps::new(prev)),
+				Box::new(undo),
+			])),
+		})
+	}
+}
@@ -0,0 +1,392 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//! Provides line-based scripts of palette operations, allowing palettes to be
+//! built reproducibly.
+//!
+//! Each line of a script holds one command. Blank lines are skipped, and 
+//! anything following a `;` is treated as a comment. Colors are given as 
+//! hexadecimal values such as `#1a2b3c`, and addresses as `page:line:column`
+//! triples such as `0:0:15`. The commands are:
+//!
+//! ```text
+//! add <color>... [at <address>]
+//! set <address> <color>
+//! delete <address>
+//! ramp <start> <end> <steps> [at <address>]
+//! sort [page <page>] [by] hue|luminance
+//! ```
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::Palette;
+use address::{Address, Page};
+use format::{Lines, parse_error};
+use format::hex::content;
+use hex::from_hex;
+use operation::{
+	CreateRamp,
+	DeleteCell,
+	InsertColor,
+	PaletteOperation,
+	SortCells,
+	SortKey,
+};
+use result::{Error, Result};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::io;
+use std::str::FromStr;
+
+
+/// Parses the given token, returning a `Parse` error at the given offset 
+/// describing the expected value if it is invalid.
+fn parse_token<T>(offset: u64, token: Option<&str>, expected: &str) 
+	-> Result<T>
+	where T: FromStr
+{
+	match token {
+		Some(token) => token.parse().map_err(|_| parse_error(
+			offset, 
+			expected, 
+			format!("{:?}", token))),
+		None => Err(parse_error(offset, expected, "end of line")),
+	}
+}
+
+/// Parses the given token as a hexadecimal color.
+fn parse_color(offset: u64, token: Option<&str>) -> Result<Color> {
+	match token {
+		Some(token) => from_hex(token).map_err(|_| parse_error(
+			offset, 
+			"a hexadecimal color", 
+			format!("{:?}", token))),
+		None => Err(parse_error(offset, "a hexadecimal color", "end of line")),
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// SortOrder
+////////////////////////////////////////////////////////////////////////////////
+/// An ordering named by a `Command::Sort`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SortOrder {
+	/// Orders colors by hue, then by lightness.
+	Hue,
+	/// Orders colors from darkest to lightest.
+	Luminance,
+}
+
+
+impl SortOrder {
+	/// Returns the `SortKey` which applies the ordering.
+	pub fn key(&self) -> SortKey {
+		match *self {
+			SortOrder::Hue => SortKey::Hue,
+			SortOrder::Luminance => SortKey::Luminance,
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Command
+////////////////////////////////////////////////////////////////////////////////
+/// A single command of a `Script`.
+#[derive(Debug, Clone)]
+pub enum Command {
+	/// Inserts colors in the first free slots, starting from the given 
+	/// address if any.
+	Add {
+		/// The colors to insert.
+		colors: Vec<Color>,
+		/// The address to start searching for free slots from.
+		at: Option<Address>,
+	},
+	/// Sets the color at an address, replacing any existing cell.
+	Set {
+		/// The address to set.
+		address: Address,
+		/// The color to set.
+		color: Color,
+	},
+	/// Deletes the cell at an address.
+	Delete(Address),
+	/// Inserts a ramp between the colors at two addresses.
+	Ramp {
+		/// The address of the start color.
+		start: Address,
+		/// The address of the end color.
+		end: Address,
+		/// The number of colors generated between them.
+		steps: usize,
+		/// The address to place the generated colors from.
+		at: Option<Address>,
+	},
+	/// Sorts the colors of a page, or of the whole palette.
+	Sort {
+		/// The ordering to apply.
+		order: SortOrder,
+		/// The page to sort, or None to sort the whole palette.
+		page: Option<Page>,
+	},
+}
+
+
+impl Command {
+	/// Returns the operations which apply the command.
+	pub fn operations(&self) -> Vec<Box<PaletteOperation>> {
+		match *self {
+			Command::Add {ref colors, at} => colors.iter()
+				.map(|&color| {
+					let mut op = InsertColor::new(color);
+					if let Some(at) = at { op = op.located_at(at); }
+					Box::new(op) as Box<PaletteOperation>
+				})
+				.collect(),
+
+			Command::Set {address, color} => vec![
+				Box::new(InsertColor::new(color)
+					.located_at(address)
+					.overwrite(true)),
+			],
+
+			Command::Delete(address) => vec![
+				Box::new(DeleteCell::new(address)),
+			],
+
+			Command::Ramp {start, end, steps, at} => {
+				let mut op = CreateRamp::new(start, end, steps);
+				if let Some(at) = at { op = op.located_at(at); }
+				vec![Box::new(op)]
+			},
+
+			Command::Sort {order, page} => {
+				let mut op = SortCells::new(order.key());
+				if let Some(page) = page { op = op.page(page); }
+				vec![Box::new(op)]
+			},
+		}
+	}
+
+	/// Parses a command from the given line, reporting errors at the given 
+	/// byte offset.
+	fn parse(offset: u64, line: &str) -> Result<Command> {
+		let mut tokens = line.split_whitespace();
+		let name = tokens.next().unwrap_or("");
+
+		// Removes a trailing `at <address>` clause from the tokens.
+		let mut rest: Vec<&str> = tokens.collect();
+		let mut at = None;
+		let len = rest.len();
+		if len >= 2 && rest[len - 2] == "at" {
+			at = Some(parse_token(offset, Some(rest[len - 1]), "an address")?);
+			rest.truncate(len - 2);
+		}
+		let mut rest = rest.into_iter();
+
+		let command = match name {
+			"add" => {
+				let colors = rest
+					.map(|token| parse_color(offset, Some(token)))
+					.collect::<Result<Vec<_>>>()?;
+				if colors.is_empty() {
+					return Err(parse_error(
+						offset, 
+						"a hexadecimal color", 
+						"end of line"));
+				}
+				return Ok(Command::Add {colors: colors, at: at});
+			},
+
+			"set" => Command::Set {
+				address: parse_token(offset, rest.next(), "an address")?,
+				color: parse_color(offset, rest.next())?,
+			},
+
+			"delete" => Command::Delete(
+				parse_token(offset, rest.next(), "an address")?),
+
+			"ramp" => Command::Ramp {
+				start: parse_token(offset, rest.next(), "an address")?,
+				end: parse_token(offset, rest.next(), "an address")?,
+				steps: parse_token(offset, rest.next(), "a step count")?,
+				at: at.take(),
+			},
+
+			"sort" => {
+				let mut token = rest.next();
+				let mut page = None;
+				if token == Some("page") {
+					page = Some(parse_token(
+						offset, 
+						rest.next(), 
+						"a page index")?);
+					token = rest.next();
+				}
+				if token == Some("by") { token = rest.next(); }
+				let order = match token {
+					Some("hue") => SortOrder::Hue,
+					Some("luminance") => SortOrder::Luminance,
+					Some(token) => return Err(parse_error(
+						offset, 
+						"a sort key", 
+						format!("{:?}", token))),
+					None => return Err(parse_error(
+						offset, 
+						"a sort key", 
+						"end of line")),
+				};
+				Command::Sort {order: order, page: page}
+			},
+
+			_ => return Err(parse_error(
+				offset, 
+				"a command", 
+				format!("{:?}", name))),
+		};
+
+		// Reject unused arguments.
+		if at.is_some() {
+			return Err(parse_error(offset, "end of line", "\"at\""));
+		}
+		match rest.next() {
+			Some(token) => Err(parse_error(
+				offset, 
+				"end of line", 
+				format!("{:?}", token))),
+			None => Ok(command),
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ScriptReport
+////////////////////////////////////////////////////////////////////////////////
+/// A report of the operations applied by a `Script`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ScriptReport {
+	/// The line number and operation name of each operation applied, in 
+	/// order.
+	pub applied: Vec<(usize, &'static str)>,
+	/// The number of cells in the palette before the script was applied.
+	pub cells_before: usize,
+	/// The number of cells in the palette after the script was applied.
+	pub cells_after: usize,
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Script
+////////////////////////////////////////////////////////////////////////////////
+/// A sequence of commands to apply to a palette.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::script::Script;
+///
+/// let script = Script::parse("\
+///     ; Two anchors and a ramp between them.
+///     add #000000 #ff0000
+///     ramp 0:0:0 0:0:1 3 at 0:1:0
+///     sort page 0 by luminance
+/// ").unwrap();
+///
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// let report = script.apply(&mut pal).unwrap();
+/// assert_eq!(report.applied.len(), 4);
+/// assert_eq!(report.cells_after, 5);
+///
+/// // The script is applied as a single transaction.
+/// pal.undo().unwrap();
+/// assert_eq!(pal.len(), 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Script {
+	/// The line number and command of each line holding a command.
+	commands: Vec<(usize, Command)>,
+}
+
+
+impl Script {
+	/// Parses a script from the given text.
+	pub fn parse(text: &str) -> Result<Script> {
+		Script::read(&mut text.as_bytes())
+	}
+
+	/// Reads a script from the given buffer.
+	pub fn read<R>(in_buf: &mut R) -> Result<Script> where R: io::Read {
+		let mut commands = Vec::new();
+		for (index, line) in Lines::new(in_buf).enumerate() {
+			let (offset, line) = line?;
+			if let Some(text) = content(&line) {
+				commands.push((index + 1, Command::parse(offset, text)?));
+			}
+		}
+		Ok(Script {commands: commands})
+	}
+
+	/// Returns the line number and command of each command in the script.
+	pub fn commands(&self) -> &[(usize, Command)] {
+		&self.commands
+	}
+
+	/// Applies the script to the given palette as a single transaction. If 
+	/// any operation fails, the palette is left unchanged and a `Script` error
+	/// giving the line of the failed command is returned.
+	pub fn apply(&self, palette: &mut Palette) -> Result<ScriptReport> {
+		let cells_before = palette.data.len();
+		let applied = palette.transaction(|txn| {
+			let mut applied = Vec::new();
+			for &(line, ref command) in &self.commands {
+				for operation in command.operations() {
+					let name = operation.info().name;
+					txn.apply(operation).map_err(|e| Error::Script {
+						line: line,
+						error: Box::new(e),
+					})?;
+					applied.push((line, name));
+				}
+			}
+			Ok(applied)
+		})?;
+
+		Ok(ScriptReport {
+			applied: applied,
+			cells_before: cells_before,
+			cells_after: palette.data.len(),
+		})
+	}
+}
@@ -0,0 +1,310 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Defines operations for sorting the cells of the palette.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{Address, Page};
+use data::Data;
+use operation::{
+	HistoryEntry,
+	MaybeSync,
+	MoveCells,
+	OperationInfo,
+	PaletteOperation,
+};
+use result::Result;
+use select::Select;
+use space::{Hsl, Lab};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Compare
+////////////////////////////////////////////////////////////////////////////////
+/// A comparison used to order colors when sorting cells. Implemented for all 
+/// closures taking two colors and returning their ordering.
+pub trait Compare: MaybeSync {
+	/// Returns the ordering of the given colors.
+	fn compare(&self, a: Color, b: Color) -> Ordering;
+}
+
+
+impl<F> Compare for F where F: Fn(Color, Color) -> Ordering + MaybeSync {
+	fn compare(&self, a: Color, b: Color) -> Ordering {
+		self(a, b)
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// SortKey
+////////////////////////////////////////////////////////////////////////////////
+/// The ordering to apply when sorting cells.
+pub enum SortKey {
+	/// Orders colors by hue, then by lightness. Grays are placed before all
+	/// saturated colors.
+	Hue,
+	/// Orders colors from darkest to lightest by perceptual lightness.
+	Luminance,
+	/// Orders cells from most to least frequently used, given a usage count 
+	/// for each address. Addresses without a count are treated as unused.
+	Frequency(BTreeMap<Address, usize>),
+	/// Orders colors using the given comparison closure.
+	By(Box<Compare>),
+}
+
+
+impl SortKey {
+	/// Compares the given cells using the sort key.
+	fn compare(&self, a: (Address, Color), b: (Address, Color)) -> Ordering {
+		match *self {
+			SortKey::Hue => {
+				let (ha, hb) = (Hsl::from(a.1), Hsl::from(b.1));
+				// Grays have no meaningful hue, so they are ordered by
+				// lightness alone.
+				let (gray_a, gray_b) = (ha.s <= 0.0, hb.s <= 0.0);
+				let hue = if gray_a || gray_b {
+					gray_b.cmp(&gray_a)
+				} else {
+					ha.h.partial_cmp(&hb.h).unwrap_or(Ordering::Equal)
+				};
+				hue.then(ha.l.partial_cmp(&hb.l).unwrap_or(Ordering::Equal))
+			},
+
+			SortKey::Luminance => Lab::from(a.1).l
+				.partial_cmp(&Lab::from(b.1).l)
+				.unwrap_or(Ordering::Equal),
+
+			SortKey::Frequency(ref counts) => {
+				let count = |address| counts.get(&address).cloned()
+					.unwrap_or(0);
+				count(b.0).cmp(&count(a.0))
+			},
+
+			SortKey::By(ref compare) => compare.compare(a.1, b.1),
+		}
+	}
+}
+
+
+impl fmt::Debug for SortKey {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			SortKey::Hue => write!(f, "Hue"),
+			SortKey::Luminance => write!(f, "Luminance"),
+			SortKey::Frequency(ref counts) => f.debug_tuple("Frequency")
+				.field(counts)
+				.finish(),
+			SortKey::By(_) => write!(f, "By(..)"),
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// SortCells
+////////////////////////////////////////////////////////////////////////////////
+/// Sorts the cells of a page or of the whole palette.
+///
+/// The occupied addresses are reused in address order, so the sorted cells 
+/// fill the same slots they occupied before. The cells themselves are moved, 
+/// so any cells referencing them will remain valid, and their names and 
+/// metadata move with them. The sort is stable, and cells which do not 
+/// resolve to a color are placed after all other cells.
+/// 
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+/// pal.apply(Box::new(InsertColor::new(Color::new(200, 200, 200)))).unwrap();
+/// pal.apply(Box::new(InsertColor::new(Color::new(0, 0, 0)))).unwrap();
+/// pal.apply(Box::new(InsertDerived::lighten(Address::new(0, 0, 1), 0.5)))
+/// 	.unwrap();
+///
+/// pal.apply(Box::new(SortCells::by_luminance())).unwrap();
+///
+/// assert_eq!(pal.color(Address::new(0, 0, 0)), Some(Color::new(0, 0, 0)));
+/// assert_eq!(pal.color(Address::new(0, 0, 1)), 
+/// 	Some(Color::new(127, 127, 127)));
+/// assert_eq!(pal.color(Address::new(0, 0, 2)), 
+/// 	Some(Color::new(200, 200, 200)));
+///
+/// pal.undo().unwrap();
+/// assert_eq!(pal.color(Address::new(0, 0, 0)), 
+/// 	Some(Color::new(200, 200, 200)));
+/// ```
+#[derive(Debug)]
+pub struct SortCells {
+	/// The ordering to apply.
+	key: SortKey,
+	/// The page to sort, or None to sort the whole palette.
+	page: Option<Page>,
+	/// The selection to sort, or None to sort the whole palette.
+	selection: Option<Select>,
+}
+
+
+impl SortCells {
+	/// Creates a new SortCells operation using the given sort key.
+	#[inline]
+	pub fn new(key: SortKey) -> SortCells {
+		SortCells {
+			key: key,
+			page: None,
+			selection: None,
+		}
+	}
+
+	/// Creates a new SortCells operation which sorts by hue.
+	#[inline]
+	pub fn by_hue() -> SortCells {
+		SortCells::new(SortKey::Hue)
+	}
+
+	/// Creates a new SortCells operation which sorts by luminance.
+	#[inline]
+	pub fn by_luminance() -> SortCells {
+		SortCells::new(SortKey::Luminance)
+	}
+
+	/// Creates a new SortCells operation which sorts by the given usage 
+	/// counts.
+	#[inline]
+	pub fn by_frequency(counts: BTreeMap<Address, usize>) -> SortCells {
+		SortCells::new(SortKey::Frequency(counts))
+	}
+
+	/// Creates a new SortCells operation which sorts using the given 
+	/// comparison closure.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::*;
+	/// use palette::operation::*;
+	///
+	/// let mut pal = Palette::new("Example", Format::Default, true);
+	/// pal.apply(Box::new(InsertColor::new(Color::new(10, 0, 0)))).unwrap();
+	/// pal.apply(Box::new(InsertColor::new(Color::new(200, 0, 0)))).unwrap();
+	///
+	/// // Order the colors by their distance from a target color.
+	/// let target = Color::new(255, 0, 0);
+	/// let distance = move |c: Color| (target.r() as i32 - c.r() as i32).abs();
+	/// pal.apply(Box::new(SortCells::by(move |a: Color, b: Color| {
+	/// 	distance(a).cmp(&distance(b))
+	/// }))).unwrap();
+	///
+	/// assert_eq!(pal.color(Address::new(0, 0, 0)),
+	/// 	Some(Color::new(200, 0, 0)));
+	/// ```
+	#[inline]
+	pub fn by<F>(compare: F) -> SortCells where F: Compare + 'static {
+		SortCells::new(SortKey::By(Box::new(compare)))
+	}
+
+	/// Restricts the operation to the given page.
+	pub fn page(mut self, page: Page) -> SortCells {
+		self.page = Some(page);
+		self
+	}
+
+	/// Restricts the operation to the selected cells. The selected cells are
+	/// sorted among their own addresses.
+	pub fn selection(mut self, selection: Select) -> SortCells {
+		self.selection = Some(selection);
+		self
+	}
+}
+
+
+impl PaletteOperation for SortCells {
+	fn info(&self) -> OperationInfo {
+		OperationInfo {
+			name: "Sort Cells",
+			details: Some(format!("{:?}", self))
+		}
+	}
+
+	fn apply(&mut self, data: &mut Data) -> Result<HistoryEntry> {
+		let cells: Vec<(Address, Option<Color>)> = match self.page {
+			Some(page) => data.iter_page(page)
+				.map(|(address, cell)| (address, cell.color()))
+				.collect(),
+			None => data.iter()
+				.map(|(address, cell)| (address, cell.color()))
+				.collect(),
+		};
+		let cells: Vec<(Address, Option<Color>)> = match self.selection {
+			Some(ref selection) => cells.into_iter()
+				.filter(|&(address, _)| selection.contains(address))
+				.collect(),
+			None => cells,
+		};
+
+		let mut sorted = cells.clone();
+		let key = &self.key;
+		sorted.sort_by(|&(a, ca), &(b, cb)| match (ca, cb) {
+			(Some(ca), Some(cb)) => key.compare((a, ca), (b, cb)),
+			(Some(_), None) => Ordering::Less,
+			(None, Some(_)) => Ordering::Greater,
+			(None, None) => Ordering::Equal,
+		});
+
+		let moves: Vec<(Address, Address)> = sorted.into_iter()
+			.zip(cells.into_iter())
+			.map(|((from, _), (to, _))| (from, to))
+			.filter(|&(from, to)| from != to)
+			.collect();
+		data.move_cells(&moves)?;
+
+		// The sort is undone by moving the cells back.
+		let undo = MoveCells::new(moves.into_iter()
+			.map(|(from, to)| (to, from))
+			.collect());
+
+		Ok(HistoryEntry {
+			info: self.info(),
+			undo: Box::new(undo),
+		})
+	}
+}
+
@@ -0,0 +1,127 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides transactional application of multiple operations.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use data::Data;
+use operation::{PaletteOperation, Sequence};
+use result::Result;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
+
+/// Reverts the given applied operations by applying their undo operations in 
+/// reverse order. An undo operation which fails is skipped so that the rest 
+/// are still applied, reverting as much as possible; callers report the error
+/// which caused the rollback instead.
+pub(crate) fn rollback(data: &mut Data, undos: Vec<Box<PaletteOperation>>) {
+	for mut undo in undos.into_iter().rev() {
+		let _ = undo.apply(data);
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Transaction
+////////////////////////////////////////////////////////////////////////////////
+/// A set of operations applied to palette data which are either all kept or
+/// all reverted.
+///
+/// Operations applied through a transaction take effect immediately, so later
+/// steps may observe the results of earlier ones. If the transaction fails, 
+/// every applied operation is reverted.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::*;
+/// use palette::operation::*;
+/// 
+/// let mut pal = Palette::new("Example", Format::Default, true);
+///
+/// let result = pal.transaction(|txn| {
+/// 	txn.apply(Box::new(InsertColor::new(Color::new(10, 10, 10))))?;
+/// 	txn.apply(Box::new(ShiftHue::new(Address::new(0, 0, 9), 30.0)))?;
+/// 	Ok(())
+/// });
+///
+/// // The second step fails, so the first is reverted.
+/// assert!(result.is_err());
+/// assert_eq!(pal.color(Address::new(0, 0, 0)), None);
+/// ```
+pub struct Transaction<'a> {
+	/// The palette data being modified.
+	data: &'a mut Data,
+	/// The undo operations for the applied operations, in application order.
+	undos: Vec<Box<PaletteOperation>>,
+}
+
+
+impl<'a> Transaction<'a> {
+	/// Runs the given function as a transaction on the given palette data. If
+	/// the function succeeds, its result is returned with an operation which 
+	/// undoes the whole transaction. Otherwise, all applied operations are 
+	/// reverted and the error is returned.
+	pub fn run<F, T>(data: &'a mut Data, f: F) 
+		-> Result<(T, Box<PaletteOperation>)>
+		where F: FnOnce(&mut Transaction) -> Result<T>
+	{
+		let mut txn = Transaction {
+			data: data,
+			undos: Vec::new(),
+		};
+
+		match f(&mut txn) {
+			Ok(value) => {
+				let mut undos = txn.undos;
+				undos.reverse();
+				Ok((value, Box::new(Sequence::new(undos))))
+			},
+			Err(e) => {
+				rollback(txn.data, txn.undos);
+				Err(e)
+			},
+		}
+	}
+
+	/// Applies the given operation to the palette data.
+	pub fn apply(&mut self, mut operation: Box<PaletteOperation>) 
+		-> Result<()>
+	{
+		let entry = operation.apply(self.data)?;
+		self.undos.push(entry.undo);
+		Ok(())
+	}
+
+	/// Returns the palette data in its current, uncommitted state.
+	pub fn data(&self) -> &Data {
+		self.data
+	}
+}
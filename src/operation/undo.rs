@@ -28,6 +28,7 @@
 
 // Local imports.
 use address::Address;
+use cell::{Cell, Shared};
 use data::Data;
 use expression::Expression;
 use operation::{
@@ -35,9 +36,11 @@ use operation::{
 	OperationInfo,
 	PaletteOperation,
 };
-use result::Result;
+use result::{Error, Result};
 
 // Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
 use std::collections::HashMap;
 use std::mem;
 
@@ -60,6 +63,10 @@ pub struct Undo {
 
 	/// The `Expression`s to restore when applying the Undo.
 	saved: HashMap<Address, Option<Expression>>,
+
+	/// The removed cells to restore when applying the Undo. These are kept so
+	/// that restored expressions referencing them remain valid.
+	removed: HashMap<Address, Shared<Cell>>,
 }
 
 
@@ -73,6 +80,7 @@ impl Undo {
 				details: None,
 			},
 			saved: Default::default(),
+			removed: Default::default(),
 		}
 	}
 
@@ -84,6 +92,7 @@ impl Undo {
 		Undo {
 			undoing: operation.info(),
 			saved: Default::default(),
+			removed: Default::default(),
 		}
 	}
 
@@ -95,6 +104,18 @@ impl Undo {
 		}
 	}
 
+	/// Records the removal of a cell to be replayed by the Undo operation. The
+	/// cell itself will be restored, so that any recorded expressions 
+	/// referencing it remain valid.
+	pub(crate) fn record_removed(
+		&mut self, 
+		address: Address, 
+		cell: Shared<Cell>, 
+		element: Expression)
+	{
+		self.record(address, Some(element));
+		self.removed.insert(address, cell);
+	}
 }
 
 
@@ -110,6 +131,7 @@ impl PaletteOperation for Undo {
 		let mut redo = Undo::new();
 
 		let saved = mem::replace(&mut self.saved, HashMap::new());
+		let mut added = Vec::new();
 
 		for (address, item) in saved {
 			match (item.is_some(), data.cell(address).is_some()) {
@@ -118,21 +140,30 @@ impl PaletteOperation for Undo {
 					let elem = item.unwrap();
 					let cell = data.cell(address).unwrap();
 					let cur = mem::replace(&mut *cell.borrow_mut(), elem);
+					if let Expression::Empty = *cell.borrow() {
+						data.mark_free(address);
+					}
+					data.mark_modified(address);
 					redo.record(address, Some(cur));
 					continue;
 				},
 
 				(true, false) => { // The cell was deleted.
 					let elem = item.unwrap();
-					let cell = data.create_cell(address).unwrap();
-					mem::replace(&mut *cell.borrow_mut(), elem);
+					let cell = match self.removed.remove(&address) {
+						Some(cell) => {
+							data.restore_cell(address, cell.clone())?;
+							cell
+						},
+						None => data.create_cell(address)?,
+					};
+					*cell.borrow_mut() = elem;
 					redo.record(address, None);
 					continue;
 				},
 
 				(false, true) => { // The cell was added.
-					let cur = data.remove_cell(address)?;
-					redo.record(address, Some(cur));
+					added.push(address);
 					continue;
 				},
 
@@ -140,6 +171,27 @@ impl PaletteOperation for Undo {
 			}
 		}
 
+		// Remove the added cells once every expression has been restored. 
+		// Cells referenced by other added cells are removed after them.
+		while !added.is_empty() {
+			let count = added.len();
+			let mut in_use = None;
+			for address in mem::replace(&mut added, Vec::new()) {
+				let cell = data.cell(address).expect("added cell");
+				match data.remove_cell(address) {
+					Ok(cur) => redo.record_removed(address, cell, cur),
+					Err(e @ Error::CellInUse {..}) => {
+						added.push(address);
+						in_use = Some(e);
+					},
+					Err(e) => return Err(e),
+				}
+			}
+			if added.len() == count {
+				return Err(in_use.expect("cell in use"));
+			}
+		}
+
 		Ok(HistoryEntry {
 			info: self.info(),
 			undo: Box::new(redo),
@@ -0,0 +1,372 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides Python bindings for palettes using PyO3.
+//!
+//! The bindings are built as a Python extension module named `palette`, 
+//! exposing the `PaletteData`, `Color`, and `Address` classes and the format 
+//! registry. Palettes can be iterated as `(Address, Color)` pairs, and their
+//! colors exported in bulk as packed RGB bytes for use with numpy:
+//!
+//! ```python
+//! import numpy, palette
+//!
+//! pal = palette.PaletteData.load("example.gpl")
+//! rgb = numpy.frombuffer(pal.color_bytes(), dtype=numpy.uint8).reshape(-1, 3)
+//! ```
+//!
+//! This module requires the `python` feature. Only an `rlib` is built by 
+//! default; build the extension module with `cargo rustc --lib --features 
+//! python --crate-type cdylib`.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::{load, save, Palette};
+use address::{Address, Reference};
+use format::Format;
+use format::registry::{self, REGISTRY};
+use hex::{from_hex, Hex};
+use result::Error;
+
+// Non-local imports.
+use color::Color;
+use pyo3::basic::CompareOp;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use pyo3::wrap_pyfunction;
+
+// Standard imports.
+use std::vec;
+
+
+impl From<Error> for PyErr {
+	fn from(error: Error) -> PyErr {
+		match error {
+			Error::Io(e) => PyIOError::new_err(e.to_string()),
+			e => PyValueError::new_err(e.to_string()),
+		}
+	}
+}
+
+/// Returns the registered format with the given name, matched 
+/// case-insensitively.
+fn format_named(name: &str) -> PyResult<Format> {
+	registry::from_name(name)
+		.ok_or_else(|| PyValueError::new_err(
+			format!("unknown palette format {:?}", name)))
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// PyColor
+////////////////////////////////////////////////////////////////////////////////
+/// An RGB color.
+#[pyclass(name = "Color")]
+#[derive(Debug, Clone, Copy)]
+pub struct PyColor {
+	color: Color,
+}
+
+
+#[pymethods]
+impl PyColor {
+	#[new]
+	fn new(r: u8, g: u8, b: u8) -> Self {
+		PyColor {color: Color::new(r, g, b)}
+	}
+
+	/// Parses a color from a hexadecimal string such as `#1a2b3c`.
+	#[staticmethod]
+	fn from_hex(text: &str) -> PyResult<Self> {
+		Ok(PyColor {color: from_hex(text)?})
+	}
+
+	#[getter]
+	fn r(&self) -> u8 { self.color.r() }
+
+	#[getter]
+	fn g(&self) -> u8 { self.color.g() }
+
+	#[getter]
+	fn b(&self) -> u8 { self.color.b() }
+
+	/// Returns the color as a hexadecimal string.
+	fn hex(&self) -> String {
+		Hex(self.color).to_string()
+	}
+
+	/// Returns the color channels as a tuple.
+	fn rgb(&self) -> (u8, u8, u8) {
+		(self.color.r(), self.color.g(), self.color.b())
+	}
+
+	fn __repr__(&self) -> String {
+		format!("Color({}, {}, {})", 
+			self.color.r(), 
+			self.color.g(), 
+			self.color.b())
+	}
+
+	fn __richcmp__(&self, other: &PyColor, op: CompareOp, py: Python) 
+		-> PyObject
+	{
+		match op {
+			CompareOp::Eq => (self.color == other.color).into_py(py),
+			CompareOp::Ne => (self.color != other.color).into_py(py),
+			_ => py.NotImplemented(),
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// PyAddress
+////////////////////////////////////////////////////////////////////////////////
+/// The address of a palette slot.
+#[pyclass(name = "Address")]
+#[derive(Debug, Clone, Copy)]
+pub struct PyAddress {
+	address: Address,
+}
+
+
+#[pymethods]
+impl PyAddress {
+	#[new]
+	fn new(page: u16, line: u8, column: u8) -> Self {
+		PyAddress {address: Address::new(page, line, column)}
+	}
+
+	/// Parses an address in `page:line:column` form.
+	#[staticmethod]
+	fn parse(text: &str) -> PyResult<Self> {
+		Ok(PyAddress {address: text.parse()?})
+	}
+
+	#[getter]
+	fn page(&self) -> u16 { self.address.page }
+
+	#[getter]
+	fn line(&self) -> u8 { self.address.line }
+
+	#[getter]
+	fn column(&self) -> u8 { self.address.column }
+
+	fn __str__(&self) -> String {
+		self.address.to_string()
+	}
+
+	fn __repr__(&self) -> String {
+		format!("Address({}, {}, {})", 
+			self.address.page, 
+			self.address.line, 
+			self.address.column)
+	}
+
+	fn __richcmp__(&self, other: &PyAddress, op: CompareOp) -> bool {
+		let (a, b) = (self.address, other.address);
+		match op {
+			CompareOp::Lt => a < b,
+			CompareOp::Le => a <= b,
+			CompareOp::Eq => a == b,
+			CompareOp::Ne => a != b,
+			CompareOp::Gt => a > b,
+			CompareOp::Ge => a >= b,
+		}
+	}
+
+	fn __hash__(&self) -> u64 {
+		(self.address.page as u64) << 16 
+			| (self.address.line as u64) << 8 
+			| self.address.column as u64
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// PyPaletteIter
+////////////////////////////////////////////////////////////////////////////////
+/// An iterator over the addresses and colors of a palette.
+#[pyclass(name = "PaletteIter")]
+pub struct PyPaletteIter {
+	colors: vec::IntoIter<(Address, Color)>,
+}
+
+
+#[pymethods]
+impl PyPaletteIter {
+	fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+		slf
+	}
+
+	fn __next__(mut slf: PyRefMut<Self>) -> Option<(PyAddress, PyColor)> {
+		slf.colors.next().map(|(address, color)| 
+			(PyAddress {address: address}, PyColor {color: color}))
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// PyPalette
+////////////////////////////////////////////////////////////////////////////////
+/// A palette of colors. Palettes hold their cells in `Rc`s unless the `sync`
+/// feature is enabled, so they may not be shared between Python threads.
+#[pyclass(name = "PaletteData", unsendable)]
+pub struct PyPalette {
+	palette: Palette,
+}
+
+
+#[pymethods]
+impl PyPalette {
+	#[new]
+	fn new(name: &str) -> Self {
+		PyPalette {palette: Palette::new(name, Format::Default, true)}
+	}
+
+	/// Loads a palette from a file, detecting its format.
+	#[staticmethod]
+	fn load(path: &str) -> PyResult<Self> {
+		Ok(PyPalette {palette: load(path)?})
+	}
+
+	/// Reads a palette from a file in the named format.
+	#[staticmethod]
+	fn read(path: &str, format: &str) -> PyResult<Self> {
+		let format = format_named(format)?;
+		Ok(PyPalette {palette: Palette::read_from_path(path, format)?})
+	}
+
+	/// Saves the palette to a file, selecting the format by its extension.
+	fn save(&self, path: &str) -> PyResult<()> {
+		Ok(save(path, &self.palette)?)
+	}
+
+	/// Writes the palette to a file in the named format.
+	fn write(&self, path: &str, format: &str) -> PyResult<()> {
+		let format = format_named(format)?;
+		Ok(self.palette.write_to_path(path, format)?)
+	}
+
+	/// Adds a color in the first free slot, returning its address.
+	fn add_color(&mut self, color: &PyColor) -> PyResult<PyAddress> {
+		let address = self.palette.data_mut().add_color(color.color)?;
+		Ok(PyAddress {address: address})
+	}
+
+	/// Returns the color at the given address, or None if the slot is empty.
+	fn color(&self, address: &PyAddress) -> Option<PyColor> {
+		self.palette.color(address.address).map(|c| PyColor {color: c})
+	}
+
+	/// Returns the name of the slot at the given address, if any.
+	fn slot_name(&self, address: &PyAddress) -> Option<String> {
+		self.palette.data().slot_name(address.address).map(String::from)
+	}
+
+	/// Sets the name of the slot at the given address.
+	fn set_slot_name(&mut self, address: &PyAddress, name: &str) {
+		self.palette.data_mut().set_slot_name(address.address, name);
+	}
+
+	/// Returns the colors of the palette in address order as packed RGB 
+	/// bytes, three per color.
+	fn color_bytes<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+		let bytes: Vec<u8> = self.palette.data()
+			.colors()
+			.flat_map(|(_, c)| vec![c.r(), c.g(), c.b()])
+			.collect();
+		PyBytes::new(py, &bytes)
+	}
+
+	/// Returns the addresses of the palette's colors in address order.
+	fn addresses(&self) -> Vec<PyAddress> {
+		self.palette.data()
+			.colors()
+			.map(|(address, _)| PyAddress {address: address})
+			.collect()
+	}
+
+	fn __len__(&self) -> usize {
+		self.palette.len()
+	}
+
+	fn __iter__(&self) -> PyPaletteIter {
+		let colors: Vec<(Address, Color)> = self.palette.data()
+			.colors()
+			.collect();
+		PyPaletteIter {colors: colors.into_iter()}
+	}
+
+	fn __repr__(&self) -> String {
+		format!("PaletteData({:?}, {} cells)", 
+			self.palette.data()
+				.name(&Reference::all())
+				.unwrap_or(""),
+			self.palette.len())
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Module
+////////////////////////////////////////////////////////////////////////////////
+/// Returns the registered formats as `(name, extensions, readable, writable)`
+/// tuples.
+#[pyfunction]
+fn formats() -> Vec<(&'static str, Vec<&'static str>, bool, bool)> {
+	REGISTRY.iter()
+		.map(|r| (
+			r.format.name(), 
+			r.extensions.to_vec(), 
+			r.readable, 
+			r.writable))
+		.collect()
+}
+
+/// Returns the name of the format registered for the given path's extension,
+/// or None if there is none.
+#[pyfunction]
+fn format_for_path(path: &str) -> Option<&'static str> {
+	registry::from_path(path).map(|format| format.name())
+}
+
+/// The `palette` Python extension module.
+#[pymodule]
+fn palette(_py: Python, module: &PyModule) -> PyResult<()> {
+	module.add_class::<PyColor>()?;
+	module.add_class::<PyAddress>()?;
+	module.add_class::<PyPalette>()?;
+	module.add_class::<PyPaletteIter>()?;
+	module.add_function(wrap_pyfunction!(formats, module)?)?;
+	module.add_function(wrap_pyfunction!(format_for_path, module)?)?;
+	Ok(())
+}
@@ -0,0 +1,314 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides declarative descriptions of color ramps.
+//!
+//! A `RampSpec` records how a ramp was built: the anchor colors it passes 
+//! through, the color space it is interpolated in, and the number of colors 
+//! generated between each pair of anchors. Ramps built from a spec are made of
+//! derived cells, so they update whenever an anchor changes, and the spec is 
+//! stored with the palette so the ramp can be rebuilt or edited later.
+//!
+//! Ramps may also be detected in palettes which were not built from specs; 
+//! `auto_ramp` groups colors into ramps of similar hue, ordered by lightness.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::Address;
+use data::Data;
+use result::{Error, Result};
+use space::{normalize_hue, Easing, Interpolation, Lch};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+use std::cmp::Ordering;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// RampSpec
+////////////////////////////////////////////////////////////////////////////////
+/// Describes a ramp passing through a sequence of anchor colors.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::data::Data;
+/// use palette::ramp::RampSpec;
+/// use palette::{Address, Color};
+/// 
+/// let mut dat: Data = Default::default();
+/// let a = dat.add_color(Color::new(0, 0, 0)).unwrap();
+/// let b = dat.add_color(Color::new(100, 100, 100)).unwrap();
+/// let c = dat.add_color(Color::new(100, 0, 0)).unwrap();
+///
+/// let spec = RampSpec::new(vec![a, b, c], 1, Address::new(0, 1, 0));
+/// assert_eq!(spec.evaluate(&dat).unwrap(), vec![
+/// 	Color::new(0, 0, 0),
+/// 	Color::new(50, 50, 50),
+/// 	Color::new(100, 100, 100),
+/// 	Color::new(100, 50, 50),
+/// 	Color::new(100, 0, 0),
+/// ]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RampSpec {
+	/// The addresses of the anchor colors, in ramp order.
+	pub anchors: Vec<Address>,
+	/// The number of colors generated between each pair of anchors.
+	pub steps: usize,
+	/// The color space to interpolate in.
+	pub interpolation: Interpolation,
+	/// The easing curve applied between each pair of anchors.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub easing: Easing,
+	/// The address to place the first generated color.
+	pub location: Address,
+}
+
+
+impl RampSpec {
+	/// Creates a new RampSpec through the given anchors, generating the given
+	/// number of colors between each pair and placing them starting at the
+	/// given location.
+	pub fn new(anchors: Vec<Address>, steps: usize, location: Address) 
+		-> RampSpec
+	{
+		RampSpec {
+			anchors: anchors,
+			steps: steps,
+			interpolation: Interpolation::Rgb,
+			easing: Easing::Linear,
+			location: location,
+		}
+	}
+
+	/// Sets the color space to interpolate in.
+	pub fn interpolation(mut self, interpolation: Interpolation) -> RampSpec {
+		self.interpolation = interpolation;
+		self
+	}
+
+	/// Sets the easing curve applied between each pair of anchors.
+	pub fn easing(mut self, easing: Easing) -> RampSpec {
+		self.easing = easing;
+		self
+	}
+
+	/// Returns the number of colors generated by the ramp, not including the
+	/// anchors.
+	pub fn len(&self) -> usize {
+		self.anchors.len().saturating_sub(1) * self.steps
+	}
+
+	/// Returns true if the ramp generates no colors.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Returns the anchor address pairs and interpolation ratios of each 
+	/// generated color, in ramp order.
+	pub fn segments(&self) -> Vec<(Address, Address, f32)> {
+		let denominator = (self.steps + 1) as f32;
+		self.anchors
+			.windows(2)
+			.flat_map(|pair| (1..self.steps + 1)
+				.map(move |i| (pair[0], pair[1], i as f32 / denominator)))
+			.collect()
+	}
+
+	/// Returns the colors of the ramp in the given palette data, including 
+	/// the anchors. Returns an error if any anchor has no color.
+	pub fn evaluate(&self, data: &Data) -> Result<Vec<Color>> {
+		let anchors: Vec<Color> = self.anchors
+			.iter()
+			.map(|&address| data.cell(address)
+				.and_then(|cell| cell.color())
+				.ok_or(Error::EmptyAddress(address)))
+			.collect::<Result<_>>()?;
+
+		let mut colors = Vec::with_capacity(anchors.len() + self.len());
+		for (i, pair) in anchors.windows(2).enumerate() {
+			if i == 0 { colors.push(pair[0]); }
+			let denominator = (self.steps + 1) as f32;
+			for step in 1..self.steps + 1 {
+				let ratio = self.easing.apply(step as f32 / denominator);
+				colors.push(self.interpolation
+					.interpolate(pair[0], pair[1], ratio));
+			}
+			colors.push(pair[1]);
+		}
+		if anchors.len() == 1 { colors.push(anchors[0]); }
+		Ok(colors)
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// AutoRampOptions
+////////////////////////////////////////////////////////////////////////////////
+/// Options controlling the detection of ramps by `auto_ramp_with`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoRampOptions {
+	/// The largest difference in LCh hue, in degrees, between the first color
+	/// of a ramp and the other colors in it.
+	pub hue_tolerance: f32,
+	/// The LCh chroma below which colors are treated as grays. All grays are
+	/// placed in a single ramp.
+	pub gray_chroma: f32,
+	/// The maximum number of colors in a ramp, or None if ramps may be any 
+	/// length. Longer ramps are split into consecutive ramps by lightness.
+	pub max_len: Option<usize>,
+}
+
+
+impl AutoRampOptions {
+	/// Creates a new `AutoRampOptions` with the default settings.
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	/// Sets the largest hue difference within a ramp, in degrees.
+	pub fn hue_tolerance(mut self, hue_tolerance: f32) -> Self {
+		self.hue_tolerance = hue_tolerance;
+		self
+	}
+
+	/// Sets the chroma below which colors are treated as grays.
+	pub fn gray_chroma(mut self, gray_chroma: f32) -> Self {
+		self.gray_chroma = gray_chroma;
+		self
+	}
+
+	/// Sets the maximum number of colors in a ramp.
+	pub fn max_len(mut self, max_len: Option<usize>) -> Self {
+		self.max_len = max_len;
+		self
+	}
+}
+
+
+impl Default for AutoRampOptions {
+	fn default() -> Self {
+		AutoRampOptions {
+			hue_tolerance: 30.0,
+			gray_chroma: 8.0,
+			max_len: None,
+		}
+	}
+}
+
+
+/// Returns the addresses of the palette's colors grouped into ramps, using 
+/// the default options. Each ramp is ordered from darkest to lightest. The 
+/// ramp of grays is returned first, followed by the ramps of other colors in 
+/// order of hue, starting after the widest gap between hues.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::data::Data;
+/// use palette::ramp::auto_ramp;
+/// use palette::Color;
+/// 
+/// let mut dat: Data = Default::default();
+/// let blue = dat.add_color(Color::new(0, 0, 255)).unwrap();
+/// let red = dat.add_color(Color::new(255, 0, 0)).unwrap();
+/// let black = dat.add_color(Color::new(0, 0, 0)).unwrap();
+/// let dark_red = dat.add_color(Color::new(100, 0, 0)).unwrap();
+/// let dark_blue = dat.add_color(Color::new(0, 0, 100)).unwrap();
+/// let white = dat.add_color(Color::new(255, 255, 255)).unwrap();
+///
+/// assert_eq!(auto_ramp(&dat), vec![
+/// 	vec![black, white],
+/// 	vec![dark_blue, blue],
+/// 	vec![dark_red, red],
+/// ]);
+/// ```
+pub fn auto_ramp(data: &Data) -> Vec<Vec<Address>> {
+	auto_ramp_with(data, &AutoRampOptions::new())
+}
+
+
+/// Returns the addresses of the palette's colors grouped into ramps, using 
+/// the given options. See `auto_ramp` for the ordering of the ramps.
+pub fn auto_ramp_with(data: &Data, options: &AutoRampOptions) 
+	-> Vec<Vec<Address>>
+{
+	let (grays, mut colors): (Vec<(Address, Lch)>, Vec<(Address, Lch)>) = data
+		.colors()
+		.map(|(address, color)| (address, Lch::from(color)))
+		.partition(|&(_, lch)| lch.c < options.gray_chroma);
+	colors.sort_by(|a, b| a.1.h.partial_cmp(&b.1.h)
+		.unwrap_or(Ordering::Equal));
+
+	// Start grouping after the widest gap between hues, so that hues on 
+	// either side of 0 degrees may share a ramp.
+	let start = {
+		let gap = |i: usize| {
+			let prev = (i + colors.len() - 1) % colors.len();
+			normalize_hue(colors[i].1.h - colors[prev].1.h)
+		};
+		(0..colors.len())
+			.max_by(|&a, &b| gap(a).partial_cmp(&gap(b))
+				.unwrap_or(Ordering::Equal))
+			.unwrap_or(0)
+	};
+	colors.rotate_left(start);
+
+	let mut groups: Vec<Vec<(Address, Lch)>> = Vec::new();
+	for (address, lch) in colors {
+		let split = groups.last()
+			.map(|group| normalize_hue(lch.h - group[0].1.h) 
+				> options.hue_tolerance)
+			.unwrap_or(true);
+		if split { groups.push(Vec::new()); }
+		groups.last_mut().expect("ramp group").push((address, lch));
+	}
+	if !grays.is_empty() {
+		groups.insert(0, grays);
+	}
+
+	let mut ramps = Vec::with_capacity(groups.len());
+	for mut group in groups {
+		group.sort_by(|a, b| a.1.l.partial_cmp(&b.1.l)
+			.unwrap_or(Ordering::Equal));
+		let addresses: Vec<Address> = group.into_iter()
+			.map(|(address, _)| address)
+			.collect();
+		match options.max_len {
+			Some(len) if len > 0 => ramps
+				.extend(addresses.chunks(len).map(|chunk| chunk.to_vec())),
+			_ => ramps.push(addresses),
+		}
+	}
+	ramps
+}
@@ -0,0 +1,73 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides `Arbitrary` implementations for generating random palettes from 
+//! unstructured data, as used by the fuzz targets.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::Palette;
+use address::Address;
+use format::Format;
+
+// Non-local imports.
+use arbitrary::{Arbitrary, Error, Result, Unstructured};
+use color::Color;
+
+
+/// The maximum number of colors in a generated palette.
+const MAX_COLORS: usize = 256;
+
+
+impl<'a> Arbitrary<'a> for Address {
+	fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+		Ok(Address::new(
+			u16::arbitrary(u)?, 
+			u8::arbitrary(u)?, 
+			u8::arbitrary(u)?))
+	}
+}
+
+
+impl<'a> Arbitrary<'a> for Palette {
+	/// Generates a palette of up to 256 fixed colors, some of which are given
+	/// slot names.
+	fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+		let mut palette = Palette::new("Arbitrary", Format::Default, false);
+		let count = u.int_in_range(0..=MAX_COLORS)?;
+		for index in 0..count {
+			let color = Color::new(
+				u8::arbitrary(u)?, 
+				u8::arbitrary(u)?, 
+				u8::arbitrary(u)?);
+			let address = palette.data.add_color(color)
+				.map_err(|_| Error::IncorrectFormat)?;
+			if bool::arbitrary(u)? {
+				palette.data.set_slot_name(address, format!("Color {}", index));
+			}
+		}
+		Ok(palette)
+	}
+}
@@ -0,0 +1,506 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides recoloring of images through a palette.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::Address;
+use data::Data;
+use space::Metric;
+use utilities::map_items;
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+
+
+/// The 4x4 Bayer threshold matrix used for ordered dithering.
+const BAYER_4X4: [[u8; 4]; 4] = [
+	[ 0,  8,  2, 10],
+	[12,  4, 14,  6],
+	[ 3, 11,  1,  9],
+	[15,  7, 13,  5],
+];
+
+/// The channel offset range of ordered dithering.
+const ORDERED_SPREAD: f32 = 32.0;
+
+/// The number of nearest destination colors kept for each source color while
+/// building a one-to-one remap table. More are found once they are all used.
+const REMAP_CANDIDATES: usize = 8;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Dither
+////////////////////////////////////////////////////////////////////////////////
+/// The dithering method used when remapping an image.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Dither {
+	/// Each pixel is mapped to its nearest color.
+	None,
+	/// The error of each pixel is diffused to its unmapped neighbors.
+	FloydSteinberg,
+	/// Each pixel is offset by a 4x4 Bayer threshold pattern before mapping.
+	Ordered,
+}
+
+
+impl Default for Dither {
+	fn default() -> Self {
+		Dither::None
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Remapped
+////////////////////////////////////////////////////////////////////////////////
+/// An image remapped through a palette.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Remapped {
+	/// The palette index of each pixel.
+	pub indices: Vec<usize>,
+	/// The address of the palette color for each index.
+	pub addresses: Vec<Address>,
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Remapper
+////////////////////////////////////////////////////////////////////////////////
+/// Maps the pixels of an image to the nearest colors of a palette.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::data::Data;
+/// use palette::remap::{Dither, Remapper};
+/// use palette::{Address, Color};
+/// 
+/// let mut dat: Data = Default::default();
+/// dat.add_color(Color::new(0, 0, 0)).unwrap();
+/// dat.add_color(Color::new(255, 255, 255)).unwrap();
+///
+/// let pixels = vec![Color::new(10, 10, 10), Color::new(240, 240, 240)];
+/// let remapped = Remapper::new().remap(&dat, &pixels, 2).unwrap();
+/// assert_eq!(remapped.indices, vec![0, 1]);
+/// assert_eq!(remapped.addresses[1], Address::new(0, 0, 1));
+///
+/// // A mid-gray dithers to a mix of black and white.
+/// let gray = vec![Color::new(128, 128, 128); 16];
+/// let remapped = Remapper::new()
+/// 	.dither(Dither::FloydSteinberg)
+/// 	.remap(&dat, &gray, 4)
+/// 	.unwrap();
+/// assert!(remapped.indices.contains(&0) && remapped.indices.contains(&1));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Remapper {
+	/// The dithering method.
+	dither: Dither,
+	/// The distance metric used to find the nearest colors.
+	metric: Metric,
+}
+
+
+impl Remapper {
+	/// Creates a new `Remapper` with the default settings.
+	pub fn new() -> Self {
+		Remapper {
+			dither: Dither::None,
+			metric: Metric::Rgb,
+		}
+	}
+
+	/// Sets the dithering method.
+	pub fn dither(mut self, dither: Dither) -> Self {
+		self.dither = dither;
+		self
+	}
+
+	/// Sets the distance metric used to find the nearest colors.
+	pub fn metric(mut self, metric: Metric) -> Self {
+		self.metric = metric;
+		self
+	}
+
+	/// Remaps the given pixels, which are stored in rows of the given width. 
+	/// Returns None if the palette has no colors.
+	pub fn remap(&self, data: &Data, pixels: &[Color], width: usize) 
+		-> Option<Remapped>
+	{
+		let (addresses, colors): (Vec<Address>, Vec<Color>) = data.colors()
+			.unzip();
+		if colors.is_empty() { return None; }
+		let width = if width == 0 { pixels.len().max(1) } else { width };
+
+		let metric = self.metric;
+		let nearest = |color: Color| colors.iter()
+			.enumerate()
+			.map(|(i, &c)| (metric.distance(color, c), i))
+			.fold((::std::f32::INFINITY, 0), |best, next| 
+				if next.0 < best.0 { next } else { best })
+			.1;
+
+		let indices = match self.dither {
+			Dither::None => nearest_each(pixels, &nearest),
+
+			Dither::Ordered => {
+				let adjusted: Vec<Color> = pixels.iter()
+					.enumerate()
+					.map(|(i, &p)| {
+						let (x, y) = (i % width, i / width);
+						let offset = ((BAYER_4X4[y % 4][x % 4] as f32 + 0.5) 
+							/ 16.0 - 0.5) * ORDERED_SPREAD;
+						offset_color(p, [offset; 3])
+					})
+					.collect();
+				nearest_each(&adjusted, &nearest)
+			},
+
+			// Each pixel's error depends upon the previous pixels, so they 
+			// are remapped in order.
+			Dither::FloydSteinberg => {
+				let mut cache: HashMap<(u8, u8, u8), usize> = HashMap::new();
+				let mut error = vec![[0.0f32; 3]; pixels.len()];
+				let mut indices = Vec::with_capacity(pixels.len());
+				for (i, &p) in pixels.iter().enumerate() {
+					let adjusted = offset_color(p, error[i]);
+					let index = *cache
+						.entry(key(adjusted))
+						.or_insert_with(|| nearest(adjusted));
+					indices.push(index);
+
+					let chosen = colors[index];
+					let diff = [
+						adjusted.r() as f32 - chosen.r() as f32,
+						adjusted.g() as f32 - chosen.g() as f32,
+						adjusted.b() as f32 - chosen.b() as f32,
+					];
+					let x = i % width;
+					let mut spread = |j: usize, weight: f32| {
+						if let Some(e) = error.get_mut(j) {
+							for (e, d) in e.iter_mut().zip(diff.iter()) {
+								*e += d * weight;
+							}
+						}
+					};
+					if x + 1 < width { spread(i + 1, 7.0 / 16.0); }
+					if x > 0 { spread(i + width - 1, 3.0 / 16.0); }
+					spread(i + width, 5.0 / 16.0);
+					if x + 1 < width { spread(i + width + 1, 1.0 / 16.0); }
+				}
+				indices
+			},
+		};
+
+		Some(Remapped {indices: indices, addresses: addresses})
+	}
+}
+
+
+impl Default for Remapper {
+	fn default() -> Self {
+		Remapper::new()
+	}
+}
+
+
+/// Returns the channels of the given color as a map key.
+#[inline]
+fn key(color: Color) -> (u8, u8, u8) {
+	(color.r(), color.g(), color.b())
+}
+
+
+/// Returns the result of the given nearest color function for each of the 
+/// given pixels. Each distinct color is looked up only once.
+fn nearest_each<F>(pixels: &[Color], nearest: F) -> Vec<usize>
+	where F: Fn(Color) -> usize + Sync + Send
+{
+	let mut distinct: Vec<(u8, u8, u8)> = pixels.iter()
+		.map(|&p| key(p))
+		.collect();
+	distinct.sort();
+	distinct.dedup();
+	let found = map_items(&distinct, |&(r, g, b)| nearest(Color::new(r, g, b)));
+
+	let table: HashMap<(u8, u8, u8), usize> = distinct.into_iter()
+		.zip(found.into_iter())
+		.collect();
+	pixels.iter().map(|&p| table[&key(p)]).collect()
+}
+
+
+/// Returns the given color with the given channel offsets added.
+fn offset_color(color: Color, offset: [f32; 3]) -> Color {
+	let channel = |c: u8, o: f32| (c as f32 + o).round().max(0.0).min(255.0) 
+		as u8;
+	Color::new(
+		channel(color.r(), offset[0]),
+		channel(color.g(), offset[1]),
+		channel(color.b(), offset[2]))
+}
+
+
+/// Remaps the given pixels, which are stored in rows of the given width, to 
+/// the nearest colors of the given palette data by RGB distance. Returns None
+/// if the palette has no colors.
+pub fn remap(data: &Data, pixels: &[Color], width: usize, dither: Dither) 
+	-> Option<Remapped>
+{
+	Remapper::new().dither(dither).remap(data, pixels, width)
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// TableOptions
+////////////////////////////////////////////////////////////////////////////////
+/// Options controlling the generation of palette remapping tables.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TableOptions {
+	/// The distance metric used to compare colors.
+	pub metric: Metric,
+	/// Whether each destination color may be used by at most one source 
+	/// color.
+	pub one_to_one: bool,
+}
+
+
+impl TableOptions {
+	/// Creates a new `TableOptions` with the default settings.
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	/// Sets the distance metric used to compare colors.
+	pub fn metric(mut self, metric: Metric) -> Self {
+		self.metric = metric;
+		self
+	}
+
+	/// Configures whether each destination color may be used by at most one
+	/// source color.
+	pub fn one_to_one(mut self, one_to_one: bool) -> Self {
+		self.one_to_one = one_to_one;
+		self
+	}
+}
+
+
+impl Default for TableOptions {
+	fn default() -> Self {
+		TableOptions {
+			metric: Metric::DeltaE,
+			one_to_one: false,
+		}
+	}
+}
+
+
+/// Returns the best mapping from the colors of one palette to the colors of 
+/// another by perceptual distance. Each source address is paired with the 
+/// address of its nearest destination color.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::data::Data;
+/// use palette::remap::build_remap_table;
+/// use palette::{Address, Color};
+/// 
+/// let mut old: Data = Default::default();
+/// old.add_color(Color::new(250, 10, 10)).unwrap();
+/// old.add_color(Color::new(10, 10, 250)).unwrap();
+///
+/// let mut new: Data = Default::default();
+/// new.add_color(Color::new(0, 0, 255)).unwrap();
+/// new.add_color(Color::new(255, 0, 0)).unwrap();
+///
+/// assert_eq!(build_remap_table(&old, &new), vec![
+/// 	(Address::new(0, 0, 0), Address::new(0, 0, 1)),
+/// 	(Address::new(0, 0, 1), Address::new(0, 0, 0)),
+/// ]);
+/// ```
+pub fn build_remap_table(from: &Data, to: &Data) -> Vec<(Address, Address)> {
+	build_remap_table_with(from, to, &TableOptions::new())
+}
+
+
+/// Returns the best mapping from the colors of one palette to the colors of 
+/// another using the given options. 
+///
+/// If the mapping is one-to-one, the closest pairs are assigned first, and 
+/// source colors left over once every destination color is used are omitted
+/// from the table. Only the nearest few destination colors of each source 
+/// color are held at once, so memory use grows linearly with the palettes. 
+/// The table is ordered by source address.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::data::Data;
+/// use palette::remap::{build_remap_table_with, TableOptions};
+/// use palette::{Address, Color};
+/// 
+/// let mut old: Data = Default::default();
+/// old.add_color(Color::new(250, 10, 10)).unwrap();
+/// old.add_color(Color::new(128, 0, 0)).unwrap();
+/// old.add_color(Color::new(10, 10, 250)).unwrap();
+///
+/// let mut new: Data = Default::default();
+/// new.add_color(Color::new(0, 0, 255)).unwrap();
+/// new.add_color(Color::new(255, 0, 0)).unwrap();
+///
+/// let options = TableOptions::new().one_to_one(true);
+/// assert_eq!(build_remap_table_with(&old, &new, &options), vec![
+/// 	(Address::new(0, 0, 0), Address::new(0, 0, 1)),
+/// 	(Address::new(0, 0, 2), Address::new(0, 0, 0)),
+/// ]);
+/// ```
+pub fn build_remap_table_with(from: &Data, to: &Data, options: &TableOptions) 
+	-> Vec<(Address, Address)>
+{
+	let targets: Vec<(Address, Color)> = to.colors().collect();
+	if targets.is_empty() { return Vec::new(); }
+	let metric = options.metric;
+
+	let sources: Vec<(Address, Color)> = from.colors().collect();
+	if !options.one_to_one {
+		return map_items(&sources, |&(address, color)| {
+			let nearest = targets.iter()
+				.map(|&(target, c)| (metric.distance(color, c), target))
+				.fold((::std::f32::INFINITY, targets[0].0), |best, next|
+					if next.0 < best.0 { next } else { best });
+			(address, nearest.1)
+		});
+	}
+
+	// Assign the globally closest pairs first. Each source color keeps only
+	// its nearest unused destination colors, and the queue holds the nearest
+	// candidate of each unassigned source color.
+	let indices: Vec<usize> = (0..sources.len()).collect();
+	let mut target_used = vec![false; targets.len()];
+	let mut candidates = map_items(&indices, |&i| 
+		nearest_candidates(i, sources[i].1, &targets, &target_used, metric));
+	let mut queue: BinaryHeap<Reverse<Candidate>> = candidates.iter_mut()
+		.filter_map(|list| list.pop())
+		.map(Reverse)
+		.collect();
+
+	let mut table = Vec::new();
+	while let Some(Reverse(best)) = queue.pop() {
+		if target_used[best.target] {
+			// Replace the candidate with the source's next nearest.
+			let list = &mut candidates[best.source];
+			while list.last().map_or(false, |c| target_used[c.target]) {
+				list.pop();
+			}
+			if list.is_empty() {
+				*list = nearest_candidates(
+					best.source, 
+					sources[best.source].1, 
+					&targets, 
+					&target_used, 
+					metric);
+			}
+			if let Some(next) = list.pop() {
+				queue.push(Reverse(next));
+			}
+			continue;
+		}
+		target_used[best.target] = true;
+		table.push((sources[best.source].0, targets[best.target].0));
+	}
+	table.sort();
+	table
+}
+
+
+/// A destination color considered for a source color in a one-to-one remap 
+/// table, ordered by distance, then source index, then destination index.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Candidate {
+	/// The distance between the colors.
+	distance: f32,
+	/// The index of the source color.
+	source: usize,
+	/// The index of the destination color.
+	target: usize,
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+	fn partial_cmp(&self, other: &Candidate) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Candidate {
+	fn cmp(&self, other: &Candidate) -> Ordering {
+		self.distance.partial_cmp(&other.distance)
+			.unwrap_or(Ordering::Equal)
+			.then(self.source.cmp(&other.source))
+			.then(self.target.cmp(&other.target))
+	}
+}
+
+/// Returns up to `REMAP_CANDIDATES` of the unused destination colors nearest 
+/// to the given source color, with the nearest last.
+fn nearest_candidates(
+	source: usize,
+	color: Color,
+	targets: &[(Address, Color)],
+	used: &[bool],
+	metric: Metric)
+	-> Vec<Candidate>
+{
+	let mut nearest = BinaryHeap::with_capacity(REMAP_CANDIDATES + 1);
+	for (target, &(_, c)) in targets.iter().enumerate() {
+		if used[target] { continue; }
+		nearest.push(Candidate {
+			distance: metric.distance(color, c),
+			source: source,
+			target: target,
+		});
+		if nearest.len() > REMAP_CANDIDATES {
+			nearest.pop();
+		}
+	}
+	let mut nearest = nearest.into_sorted_vec();
+	nearest.reverse();
+	nearest
+}
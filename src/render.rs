@@ -0,0 +1,381 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides rendering of palettes to swatch sheet images.
+//!
+//! Each occupied page of the palette is drawn as a grid of cells, with pages 
+//! stacked vertically. Cells may optionally be labelled with their line and 
+//! column, drawn in a small built-in hexadecimal font.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{Address, Page, Reference};
+use data::Data;
+use format::png::{PNG_SIGNATURE, write_chunk, zlib_stored};
+use result::{Error, Result};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+use std::collections::BTreeSet;
+use std::io;
+
+
+/// The width of a label glyph in pixels.
+const GLYPH_WIDTH: u32 = 3;
+
+/// The height of a label glyph in pixels.
+const GLYPH_HEIGHT: u32 = 5;
+
+/// Glyph bitmaps for the label font, `0-9`, `A-F`, and `:`. Each row is stored
+/// in the low three bits of a byte.
+const GLYPHS: [[u8;5];17] = [
+	[0b111, 0b101, 0b101, 0b101, 0b111], // 0
+	[0b010, 0b110, 0b010, 0b010, 0b111], // 1
+	[0b111, 0b001, 0b111, 0b100, 0b111], // 2
+	[0b111, 0b001, 0b111, 0b001, 0b111], // 3
+	[0b101, 0b101, 0b111, 0b001, 0b001], // 4
+	[0b111, 0b100, 0b111, 0b001, 0b111], // 5
+	[0b111, 0b100, 0b111, 0b101, 0b111], // 6
+	[0b111, 0b001, 0b001, 0b001, 0b001], // 7
+	[0b111, 0b101, 0b111, 0b101, 0b111], // 8
+	[0b111, 0b101, 0b111, 0b001, 0b111], // 9
+	[0b010, 0b101, 0b111, 0b101, 0b101], // A
+	[0b110, 0b101, 0b110, 0b101, 0b110], // B
+	[0b011, 0b100, 0b100, 0b100, 0b011], // C
+	[0b110, 0b101, 0b101, 0b101, 0b110], // D
+	[0b111, 0b100, 0b111, 0b100, 0b111], // E
+	[0b111, 0b100, 0b111, 0b100, 0b100], // F
+	[0b000, 0b010, 0b000, 0b010, 0b000], // :
+];
+
+
+/// Returns the glyph for the given character, or None if it is not in the 
+/// label font.
+fn glyph(c: char) -> Option<&'static [u8;5]> {
+	match c {
+		'0'..='9' => Some(&GLYPHS[c as usize - '0' as usize]),
+		'A'..='F' => Some(&GLYPHS[c as usize - 'A' as usize + 10]),
+		':' => Some(&GLYPHS[16]),
+		_ => None,
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Image
+////////////////////////////////////////////////////////////////////////////////
+/// A rendered RGBA image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+	/// The width of the image in pixels.
+	pub width: u32,
+	/// The height of the image in pixels.
+	pub height: u32,
+	/// The pixel data, stored as rows of RGBA bytes from top to bottom.
+	pub pixels: Vec<u8>,
+}
+
+
+impl Image {
+	/// Creates a new `Image` filled with the given color. Returns an 
+	/// `ImageTooLarge` error if the pixel data can not be addressed.
+	pub fn new(width: u32, height: u32, fill: Color) -> Result<Self> {
+		let len = (width as usize)
+			.checked_mul(height as usize)
+			.and_then(|pixels| pixels.checked_mul(4))
+			.ok_or(Error::ImageTooLarge)?;
+		let mut pixels = Vec::with_capacity(len);
+		for _ in 0..len / 4 {
+			pixels.extend_from_slice(&[fill.r(), fill.g(), fill.b(), 0xFF]);
+		}
+		Ok(Image {width: width, height: height, pixels: pixels})
+	}
+
+	/// Fills the given rectangle with the given color. The rectangle is 
+	/// clipped to the image bounds.
+	pub fn fill_rect(&mut self, x: u32, y: u32, w: u32, h: u32, color: Color) {
+		for py in y..y.saturating_add(h).min(self.height) {
+			for px in x..x.saturating_add(w).min(self.width) {
+				let i = (py as usize * self.width as usize + px as usize) 
+					* 4;
+				self.pixels[i..i + 4]
+					.copy_from_slice(&[color.r(), color.g(), color.b(), 0xFF]);
+			}
+		}
+	}
+
+	/// Draws the given text at the given location using the built-in label 
+	/// font. Unsupported characters are skipped.
+	fn draw_text(&mut self, x: u32, y: u32, text: &str, color: Color) {
+		for (n, c) in text.chars().enumerate() {
+			let gx = x + n as u32 * (GLYPH_WIDTH + 1);
+			if let Some(rows) = glyph(c) {
+				for (gy, row) in rows.iter().enumerate() {
+					for bit in 0..GLYPH_WIDTH {
+						if row & (0b100 >> bit) != 0 {
+							let py = y + gy as u32;
+							self.fill_rect(gx + bit, py, 1, 1, color);
+						}
+					}
+				}
+			}
+		}
+	}
+
+	/// Writes the image to the given buffer as a PNG file.
+	pub fn write_png<W>(&self, out_buf: &mut W) -> io::Result<()>
+		where W: io::Write
+	{
+		out_buf.write_all(&PNG_SIGNATURE)?;
+
+		// Write header: bit depth 8, color type 6 (RGBA).
+		let mut header = Vec::new();
+		for &v in &[self.width, self.height] {
+			header.extend_from_slice(&[
+				(v >> 24) as u8, (v >> 16) as u8, (v >> 8) as u8, v as u8]);
+		}
+		header.extend_from_slice(&[8, 6, 0, 0, 0]);
+		write_chunk(out_buf, b"IHDR", &header)?;
+
+		// Write image data, with no filter on each scanline.
+		let stride = self.width as usize * 4;
+		let mut raw = Vec::with_capacity((stride + 1) * self.height as usize);
+		for row in self.pixels.chunks(stride.max(1)) {
+			raw.push(0);
+			raw.extend_from_slice(row);
+		}
+		write_chunk(out_buf, b"IDAT", &zlib_stored(&raw))?;
+
+		write_chunk(out_buf, b"IEND", &[])
+	}
+
+	/// Writes the image to the given buffer as a 24-bit BMP file.
+	pub fn write_bmp<W>(&self, out_buf: &mut W) -> io::Result<()>
+		where W: io::Write
+	{
+		let too_large = || io::Error::new(
+			io::ErrorKind::InvalidInput, 
+			"image too large for BMP");
+		let stride = self.width.checked_mul(3)
+			.and_then(|row| row.checked_add(3))
+			.ok_or_else(too_large)? & !3;
+		let image_size = stride.checked_mul(self.height)
+			.and_then(|size| size.checked_add(54))
+			.ok_or_else(too_large)? - 54;
+		let le = |v: u32| [
+			v as u8,
+			(v >> 8) as u8,
+			(v >> 16) as u8,
+			(v >> 24) as u8,
+		];
+
+		// Write file header.
+		out_buf.write_all(b"BM")?;
+		out_buf.write_all(&le(54 + image_size))?;
+		out_buf.write_all(&[0, 0, 0, 0])?;
+		out_buf.write_all(&le(54))?;
+
+		// Write info header.
+		out_buf.write_all(&le(40))?;
+		out_buf.write_all(&le(self.width))?;
+		out_buf.write_all(&le(self.height))?;
+		out_buf.write_all(&[1, 0, 24, 0])?;
+		out_buf.write_all(&le(0))?;
+		out_buf.write_all(&le(image_size))?;
+		out_buf.write_all(&le(2835))?;
+		out_buf.write_all(&le(2835))?;
+		out_buf.write_all(&le(0))?;
+		out_buf.write_all(&le(0))?;
+
+		// Write rows from bottom to top, in BGR order.
+		let padding = vec![0u8; (stride - self.width * 3) as usize];
+		for y in (0..self.height).rev() {
+			for x in 0..self.width {
+				let i = (y as usize * self.width as usize + x as usize) * 4;
+				out_buf.write_all(&[
+					self.pixels[i + 2], 
+					self.pixels[i + 1], 
+					self.pixels[i]])?;
+			}
+			out_buf.write_all(&padding)?;
+		}
+		Ok(())
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Renderer
+////////////////////////////////////////////////////////////////////////////////
+/// Renders palette `Data` to swatch sheet `Image`s.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Color;
+/// use palette::data::DataBuilder;
+/// use palette::render::{Image, Renderer};
+/// use std::u32;
+/// 
+/// let mut dat = DataBuilder::new().column_count(4).build();
+/// dat.add_color(Color::new(255, 0, 0)).unwrap();
+///
+/// let image = Renderer::new().cell_size(8).spacing(0).render(&dat).unwrap();
+///
+/// assert_eq!(image.width, 32);
+/// assert_eq!(&image.pixels[0..4], &[255, 0, 0, 255]);
+///
+/// assert!(Renderer::new().cell_size(u32::MAX).render(&dat).is_err());
+/// assert!(Image::new(u32::MAX, u32::MAX, Color::new(0, 0, 0)).is_err());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Renderer {
+	/// The width and height of each cell in pixels.
+	cell_size: u32,
+	/// The space between cells in pixels.
+	spacing: u32,
+	/// Whether to label cells with their line and column.
+	labels: bool,
+	/// The background color.
+	background: Color,
+}
+
+
+impl Renderer {
+	/// Creates a new `Renderer` with the default settings.
+	pub fn new() -> Self {
+		Renderer {
+			cell_size: 16,
+			spacing: 1,
+			labels: false,
+			background: Color::new(0x40, 0x40, 0x40),
+		}
+	}
+
+	/// Sets the width and height of each cell in pixels.
+	pub fn cell_size(mut self, cell_size: u32) -> Self {
+		self.cell_size = cell_size;
+		self
+	}
+
+	/// Sets the space between cells in pixels.
+	pub fn spacing(mut self, spacing: u32) -> Self {
+		self.spacing = spacing;
+		self
+	}
+
+	/// Configures whether to label cells with their line and column.
+	pub fn labels(mut self, labels: bool) -> Self {
+		self.labels = labels;
+		self
+	}
+
+	/// Sets the background color.
+	pub fn background(mut self, background: Color) -> Self {
+		self.background = background;
+		self
+	}
+
+	/// Renders the given palette data to an `Image`. Returns an 
+	/// `ImageTooLarge` error if the image dimensions overflow.
+	pub fn render(&self, data: &Data) -> Result<Image> {
+		let pages: BTreeSet<Page> = data.iter()
+			.map(|(address, _)| address.page)
+			.collect();
+		let pitch = self.cell_size.checked_add(self.spacing)
+			.ok_or(Error::ImageTooLarge)?;
+
+		// Compute the layout of each page.
+		let layout: Vec<(Page, u32, u32)> = pages.iter()
+			.map(|&page| {
+				let group = Reference::page_of(&Address::new(page, 0, 0));
+				let lines = data.line_count(&group) as u32;
+				let columns = data.iter_page(page)
+					.map(|(address, _)| data.column_count(
+						&Reference::line_of(&address)) as u32)
+					.max()
+					.unwrap_or(0);
+				(page, lines, columns)
+			})
+			.collect();
+
+		// Returns the extent of the given number of cells with spacing.
+		let extent = |cells: u32| cells.checked_mul(pitch)
+			.and_then(|extent| extent.checked_add(self.spacing))
+			.ok_or(Error::ImageTooLarge);
+		let mut width = 0;
+		let mut height: u32 = 0;
+		for &(_, lines, columns) in &layout {
+			width = width.max(extent(columns)?);
+			height = height.checked_add(extent(lines)?)
+				.ok_or(Error::ImageTooLarge)?;
+		}
+		let mut image = Image::new(width, height, self.background)?;
+
+		// Draw cells.
+		let mut page_y = 0;
+		for &(page, lines, _) in &layout {
+			for (address, cell) in data.iter_page(page) {
+				if let Some(color) = data.color_of(cell) {
+					let x = self.spacing + address.column as u32 * pitch;
+					let y = page_y + self.spacing + address.line as u32 * pitch;
+					let size = self.cell_size;
+					image.fill_rect(x, y, size, size, color);
+
+					if self.labels && self.cell_size > GLYPH_HEIGHT + 2 {
+						let luma = 0.299 * color.r() as f32 
+							+ 0.587 * color.g() as f32 
+							+ 0.114 * color.b() as f32;
+						let ink = if luma > 127.0 {
+							Color::new(0, 0, 0)
+						} else {
+							Color::new(255, 255, 255)
+						};
+						let label = format!("{:X}:{:X}", 
+							address.line, 
+							address.column);
+						let fit = ((self.cell_size - 2) / (GLYPH_WIDTH + 1)) 
+							as usize;
+						let label: String = label.chars().take(fit).collect();
+						image.draw_text(x + 1, y + 1, &label, ink);
+					}
+				}
+			}
+			page_y += lines * pitch + self.spacing;
+		}
+		Ok(image)
+	}
+}
+
+
+impl Default for Renderer {
+	fn default() -> Self {
+		Renderer::new()
+	}
+}
@@ -31,11 +31,17 @@ use address::{
 	Address,
 	Reference,
 };
+use depth::Grid;
 
 // Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
 use std::fmt;
 use std::result;
+#[cfg(feature = "std")]
 use std::error;
+#[cfg(feature = "std")]
+use std::io;
 
 /// An alias for `std::result::Result<T, palette::Error>`.
 pub type Result<T> = result::Result<T, Error>;
@@ -53,7 +59,7 @@ pub enum Error {
 	/// Attempted to set a color to a non-zeroth-order slot.
 	CannotSetDerivedColor,
 	
-	/// An `Address` was provided that lies outside of the range defined for the 
+	/// An `Address` was provided that lies outside of the range defined for the
 	/// palette.
 	InvalidAddress(Address),
 	
@@ -71,26 +77,158 @@ pub enum Error {
 	
 	/// An element could not be created because the address was occupied.
 	AddressInUse(Address),
+
+	/// A cell could not be removed because it is referenced by other cells.
+	CellInUse {
+		/// The address of the cell.
+		address: Address,
+		/// The addresses of the cells referencing it.
+		dependents: Vec<Address>,
+	},
+
+	/// An expression could not be set because it would depend upon itself.
+	CircularReference(Address),
+
+	/// A cell could not be frozen because it does not hold a derived color.
+	NotDerived(Address),
+
+	/// A cell could not be thawed because it is not frozen.
+	NotFrozen(Address),
+
+	/// A derived color could not be resolved because it depends on a chain of
+	/// source cells longer than the maximum resolution depth.
+	MaxOrderExceeded,
+
+	/// A slot could not be changed or removed because it is constrained.
+	SlotLocked(Address),
+
+	/// Text could not be parsed as a hexadecimal color.
+	InvalidHexColor(String),
+
+	/// Text could not be parsed as an `Address` or `Reference`.
+	InvalidAddressSyntax(String),
+
+	/// A metadata value was set for a key which is not registered.
+	UnregisteredMetaKey(String),
+
+	/// A metadata value or key type did not match the registered type of its
+	/// key.
+	MetaTypeMismatch(String),
+
+	/// An undo or redo was requested from a palette without history enabled.
+	HistoryDisabled,
+
+	/// A `Grid` has a channel depth or number of levels outside of its 
+	/// allowed range.
+	InvalidGrid(Grid),
+
+	/// A ramp length was zero, or too long to fit on a line.
+	InvalidRampLength(usize),
+
+	/// An image was too large to render or store in memory.
+	ImageTooLarge,
+
+	/// An I/O error occurred while reading or writing a palette.
+	#[cfg(feature = "std")]
+	Io(io::Error),
+
+	/// Palette data could not be read or written in a file format.
+	Format {
+		/// The name of the file format.
+		format: &'static str,
+		/// A description of the problem.
+		reason: String,
+	},
+
+	/// A palette file could not be parsed.
+	Parse {
+		/// The byte offset in the file at which parsing failed.
+		offset: u64,
+		/// A description of the expected input.
+		expected: String,
+		/// A description of the input found.
+		found: String,
+	},
+
+	/// A command of an operation script failed.
+	Script {
+		/// The line number of the failed command.
+		line: usize,
+		/// The error produced by the command.
+		error: Box<Error>,
+	},
 }
 
 
 impl fmt::Display for Error {
 	fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
 		match *self {
-			Error::EmptyAddress(address)
+			Error::EmptyAddress(address) |
+			Error::CircularReference(address) |
+			Error::NotDerived(address) |
+			Error::NotFrozen(address) |
+			Error::SlotLocked(address)
 				=> write!(f, "{}: {}", 
-					error::Error::description(self), 
+					self.summary(), 
 					address
 				),
 
-			_	=> write!(f, "{}", error::Error::description(self))
+			Error::CellInUse {address, ref dependents} => {
+				write!(f, "{}: {} (referenced by ", self.summary(), address)?;
+				for (i, dependent) in dependents.iter().enumerate() {
+					if i > 0 { write!(f, ", ")?; }
+					write!(f, "{}", dependent)?;
+				}
+				write!(f, ")")
+			},
+
+			Error::InvalidHexColor(ref text) |
+			Error::InvalidAddressSyntax(ref text) |
+			Error::UnregisteredMetaKey(ref text) |
+			Error::MetaTypeMismatch(ref text)
+				=> write!(f, "{}: {:?}", self.summary(), text),
+
+			Error::InvalidGrid(grid)
+				=> write!(f, "{}: {:?}", self.summary(), grid),
+
+			Error::InvalidRampLength(len)
+				=> write!(f, "{}: {}", self.summary(), len),
+
+			#[cfg(feature = "std")]
+			Error::Io(ref err)
+				=> write!(f, "{}: {}", self.summary(), err),
+
+			Error::Format {format, ref reason}
+				=> write!(f, "{} {}: {}", 
+					format, 
+					self.summary(), 
+					reason
+				),
+
+			Error::Parse {offset, ref expected, ref found}
+				=> write!(f, "{} at byte {}: expected {}, found {}", 
+					self.summary(), 
+					offset,
+					expected,
+					found
+				),
+
+			Error::Script {line, ref error}
+				=> write!(f, "{} on line {}: {}", 
+					self.summary(), 
+					line,
+					error
+				),
+
+			_	=> write!(f, "{}", self.summary())
 		}
 	}
 }
 
 
-impl error::Error for Error {
-	fn description(&self) -> &str {
+impl Error {
+	/// Returns a short description of the error.
+	fn summary(&self) -> &'static str {
 		match *self {
 			Error::MaxCellLimitExceeded
 				=> "maximum number of color slots for palette exceeded",
@@ -116,6 +254,85 @@ impl error::Error for Error {
 
 			Error::AddressInUse(..)
 				=> "the address is in use",
+
+			Error::CellInUse {..}
+				=> "the cell is referenced by other cells",
+
+			Error::CircularReference(..)
+				=> "the expression would create a circular reference",
+
+			Error::NotDerived(..)
+				=> "the cell does not hold a derived color",
+
+			Error::NotFrozen(..)
+				=> "the cell is not frozen",
+
+			Error::MaxOrderExceeded
+				=> "the derived color exceeds the maximum resolution depth",
+
+			Error::SlotLocked(..)
+				=> "the slot is locked or protected",
+
+			Error::InvalidHexColor(..)
+				=> "invalid hexadecimal color",
+
+			Error::InvalidAddressSyntax(..)
+				=> "invalid address or reference syntax",
+
+			Error::UnregisteredMetaKey(..)
+				=> "the metadata key is not registered",
+
+			Error::MetaTypeMismatch(..)
+				=> "the metadata type does not match the registered type",
+
+			Error::HistoryDisabled
+				=> "operation history is not enabled for the palette",
+
+			Error::InvalidGrid(..)
+				=> "the grid depth is out of range",
+
+			Error::InvalidRampLength(..)
+				=> "the ramp length is zero or exceeds the line length",
+
+			Error::ImageTooLarge
+				=> "the image is too large to render",
+
+			#[cfg(feature = "std")]
+			Error::Io(..)
+				=> "palette I/O error",
+
+			Error::Format {..}
+				=> "format error",
+
+			Error::Parse {..}
+				=> "parse error",
+
+			Error::Script {..}
+				=> "script error",
 		}
 	}
 }
+
+
+#[cfg(feature = "std")]
+impl error::Error for Error {
+	fn description(&self) -> &str {
+		self.summary()
+	}
+
+	fn cause(&self) -> Option<&error::Error> {
+		match *self {
+			Error::Io(ref err) => Some(err),
+			Error::Script {ref error, ..} => Some(&**error),
+			_ => None,
+		}
+	}
+}
+
+
+#[cfg(feature = "std")]
+impl From<io::Error> for Error {
+	fn from(err: io::Error) -> Self {
+		Error::Io(err)
+	}
+}
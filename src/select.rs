@@ -0,0 +1,391 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides a textual language for selecting sets of addresses.
+//!
+//! A selection is a comma-separated union of terms. Each term is either a 
+//! reference, such as `0:1:*` or `page 3`, or an inclusive range of addresses
+//! in address order, such as `0:0:0-0:0:7`.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{
+	component_indices,
+	Address,
+	Reference,
+	ReferenceComponent,
+};
+use data::Data;
+use result::{Error, Result};
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+
+/// Returns whether the given reference component matches the given index.
+/// Named and indirect components match nothing.
+fn component_matches<T, O>(component: &ReferenceComponent<T, O>, index: T) 
+	-> bool
+	where T: PartialEq
+{
+	use address::ReferenceComponent::*;
+
+	match *component {
+		Any | All => true,
+		Index(ref i) => *i == index,
+		_ => false,
+	}
+}
+
+
+
+/// Returns the slot following the given address in the given palette data, 
+/// according to its page and line sizes, or None if it is the last slot.
+fn next_slot(data: &Data, address: Address) -> Option<Address> {
+	let Address {page, line, column} = address;
+	if (column as u32 + 1) < data.column_count(&Reference::line_of(&address)) 
+		as u32
+	{
+		Some(Address::new(page, line, column + 1))
+	} else if (line as u32 + 1) < data.line_count(&Reference::page_of(&address))
+		as u32
+	{
+		Some(Address::new(page, line + 1, 0))
+	} else if (page as u32 + 1) < data.maximum_page_count() as u32 {
+		Some(Address::new(page + 1, 0, 0))
+	} else {
+		None
+	}
+}
+
+/// Returns whether the given address is a valid slot in the given palette
+/// data.
+fn is_slot(data: &Data, address: Address) -> bool {
+	address.page < data.maximum_page_count() &&
+	address.line < data.line_count(&Reference::page_of(&address)) &&
+	address.column < data.column_count(&Reference::line_of(&address))
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Term
+////////////////////////////////////////////////////////////////////////////////
+/// A single term of a `Select`.
+#[derive(Debug, Clone, PartialEq)]
+enum Term {
+	/// The addresses covered by a reference.
+	Group(Reference),
+	/// The addresses between two addresses, inclusive.
+	Range(Address, Address),
+}
+
+
+impl Term {
+	/// Returns whether the term contains the given address.
+	fn contains(&self, address: Address) -> bool {
+		match *self {
+			Term::Group(ref group) => 
+				component_matches(&group.page, address.page) &&
+				component_matches(&group.line, address.line) &&
+				component_matches(&group.column, address.column),
+			Term::Range(start, end) => start <= address && address <= end,
+		}
+	}
+
+	/// Adds up to the given number of the first valid slots of the given 
+	/// palette data covered by the term to the given set.
+	fn slots(&self, data: &Data, limit: usize, slots: &mut BTreeSet<Address>) {
+		let mut remaining = limit;
+		match *self {
+			Term::Group(ref group) => {
+				let page_count = data.maximum_page_count() as u32;
+				let pages = match component_indices(&group.page, page_count) {
+					Ok(pages) => pages,
+					Err(_) => return,
+				};
+				for page in pages {
+					let first = Address::new(page as u16, 0, 0);
+					let line_count = data
+						.line_count(&Reference::page_of(&first)) as u32;
+					let lines = component_indices(&group.line, line_count)
+						.unwrap_or(0..0);
+					for line in lines {
+						let first = Address::new(page as u16, line as u8, 0);
+						let column_count = data.column_count(
+							&Reference::line_of(&first));
+						let columns = component_indices(
+								&group.column, 
+								column_count as u32)
+							.unwrap_or(0..0);
+						for column in columns {
+							if remaining == 0 { return; }
+							let address = Address::new(
+								first.page, 
+								first.line, 
+								column as u8);
+							if is_slot(data, address) {
+								slots.insert(address);
+								remaining -= 1;
+							}
+						}
+					}
+				}
+			},
+			Term::Range(start, end) => {
+				let mut address = start;
+				loop {
+					if address > end || remaining == 0 { break; }
+					if is_slot(data, address) {
+						slots.insert(address);
+						remaining -= 1;
+					}
+					address = match next_slot(data, address) {
+						Some(next) if next > address => next,
+						_ => break,
+					};
+				}
+			},
+		}
+	}
+}
+
+
+impl fmt::Display for Term {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Term::Group(ref group) => write!(f, "{}", group),
+			Term::Range(start, end) => write!(f, "{}-{}", start, end),
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Select
+////////////////////////////////////////////////////////////////////////////////
+/// A set of addresses, described as a union of references and address ranges.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::select::Select;
+/// use palette::Address;
+///
+/// let select: Select = "0:1:*, 0:0:0-0:0:7, page 3".parse().unwrap();
+///
+/// assert!(select.contains(Address::new(0, 1, 200)));
+/// assert!(select.contains(Address::new(0, 0, 7)));
+/// assert!(!select.contains(Address::new(0, 0, 8)));
+/// assert!(select.contains(Address::new(3, 4, 5)));
+/// assert_eq!(select.to_string().parse::<Select>().unwrap(), select);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Select {
+	/// The terms of the selection.
+	terms: Vec<Term>,
+}
+
+
+impl Select {
+	/// Creates a new, empty `Select`.
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	/// Creates a new `Select` covering every address.
+	pub fn all() -> Self {
+		Select::new().group(Reference::all())
+	}
+
+	/// Adds the addresses covered by the given reference to the selection.
+	pub fn group(mut self, group: Reference) -> Self {
+		self.terms.push(Term::Group(group));
+		self
+	}
+
+	/// Adds the addresses between the given addresses, inclusive, to the 
+	/// selection.
+	pub fn range(mut self, start: Address, end: Address) -> Self {
+		self.terms.push(Term::Range(start, end));
+		self
+	}
+
+	/// Returns whether the selection contains the given address.
+	pub fn contains(&self, address: Address) -> bool {
+		self.terms.iter().any(|term| term.contains(address))
+	}
+
+	/// Returns whether the selection is empty.
+	pub fn is_empty(&self) -> bool {
+		self.terms.is_empty()
+	}
+
+	/// Returns the selected slots of the given palette data, whether occupied 
+	/// or not, in address order. Pages which are selected without giving their
+	/// index cover every page up to the maximum page count, so broad 
+	/// selections may be very large; see `slots_up_to`.
+	pub fn slots(&self, data: &Data) -> Vec<Address> {
+		self.slots_up_to(data, usize::MAX)
+	}
+
+	/// Returns up to the given number of the first selected slots of the 
+	/// given palette data, whether occupied or not, in address order.
+	pub fn slots_up_to(&self, data: &Data, count: usize) -> Vec<Address> {
+		// Each term yields its slots in address order, so the first slots of
+		// the selection are among the first slots of each term.
+		let mut slots = BTreeSet::new();
+		for term in &self.terms {
+			term.slots(data, count, &mut slots);
+		}
+		slots.into_iter().take(count).collect()
+	}
+
+	/// Returns the selected occupied addresses of the given palette data, in 
+	/// address order.
+	pub fn addresses(&self, data: &Data) -> Vec<Address> {
+		data.iter()
+			.map(|(address, _)| address)
+			.filter(|&address| self.contains(address))
+			.collect()
+	}
+}
+
+
+impl FromStr for Select {
+	type Err = Error;
+
+	fn from_str(text: &str) -> Result<Self> {
+		let mut select = Select::new();
+		for term in text.split(',') {
+			let term = term.trim();
+			let range = term.find('-').map(|i| (&term[..i], &term[i + 1..]));
+			select = match range {
+				Some((start, end)) 
+					=> select.range(start.parse()?, end.parse()?),
+				None => select.group(term.parse()?),
+			};
+		}
+		Ok(select)
+	}
+}
+
+
+impl fmt::Display for Select {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for (i, term) in self.terms.iter().enumerate() {
+			if i > 0 { write!(f, ", ")?; }
+			write!(f, "{}", term)?;
+		}
+		Ok(())
+	}
+}
+
+
+impl From<Reference> for Select {
+	fn from(group: Reference) -> Select {
+		Select::new().group(group)
+	}
+}
+
+
+impl From<Address> for Select {
+	fn from(address: Address) -> Select {
+		Select::new().range(address, address)
+	}
+}
+
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Region
+////////////////////////////////////////////////////////////////////////////////
+/// A region of the palette to place colors into.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Region {
+	/// The consecutive slots beginning at the given address.
+	At(Address),
+	/// The slots of the given selection, in address order.
+	Fill(Select),
+}
+
+
+impl Region {
+	/// Returns up to the given number of slots of the region in the given 
+	/// palette data, in address order.
+	pub fn slots(&self, data: &Data, count: usize) -> Vec<Address> {
+		match *self {
+			Region::At(start) => {
+				let mut slots = Vec::with_capacity(count);
+				let mut next = if is_slot(data, start) {
+					Some(start)
+				} else {
+					None
+				};
+				while let Some(address) = next {
+					if slots.len() >= count { break; }
+					slots.push(address);
+					next = next_slot(data, address);
+				}
+				slots
+			},
+			Region::Fill(ref select) => select.slots_up_to(data, count),
+		}
+	}
+}
+
+
+impl From<Address> for Region {
+	fn from(address: Address) -> Region {
+		Region::At(address)
+	}
+}
+
+
+impl From<Select> for Region {
+	fn from(select: Select) -> Region {
+		Region::Fill(select)
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ImportReport
+////////////////////////////////////////////////////////////////////////////////
+/// A summary of the colors placed by an import into a region.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ImportReport {
+	/// The addresses the imported colors were placed at, in import order.
+	pub placed: Vec<Address>,
+	/// The number of imported colors which did not fit in the region.
+	pub dropped: usize,
+}
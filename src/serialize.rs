@@ -0,0 +1,368 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides `serde` serialization for palette `Data`.
+//!
+//! `Expression`s refer to their source cells directly, so they can not be
+//! serialized on their own. Instead, the palette `Data` is serialized as a 
+//! whole, with each source reference replaced by the `Address` of the source
+//! cell. The format's `prepare_new_page` and `prepare_new_line` functions are
+//! not serialized, and must be restored after deserialization if needed.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{Address, Reference, Page, Line, Column};
+use animation::Cycle;
+use cell::{Cell, Shared, Weak};
+use data::{Constraint, Data, MetaData};
+use expression::Expression;
+use meta::{MetaSchema, MetaValue};
+use ramp::RampSpec;
+use space::{Adjustment, Cmyk, Easing, Interpolation};
+
+// Non-local imports.
+use color::Color;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error as DeError;
+use serde::ser::Error as SerError;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+use std::collections::HashMap;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ExpressionRepr
+////////////////////////////////////////////////////////////////////////////////
+/// The serialized form of an `Expression`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ExpressionRepr {
+	Empty,
+	Color {
+		color: [u8; 3],
+	},
+	Cmyk {
+		cmyk: Cmyk,
+	},
+	Blend {
+		a: Address,
+		b: Address,
+		ratio: f32,
+		#[serde(default)]
+		interpolation: Interpolation,
+		#[serde(default)]
+		easing: Easing,
+	},
+	Lighten {
+		a: Address,
+		amount: f32,
+	},
+	Darken {
+		a: Address,
+		amount: f32,
+	},
+	Complement {
+		a: Address,
+	},
+	Adjust {
+		a: Address,
+		adjustment: Adjustment,
+	},
+	Frozen {
+		color: [u8; 3],
+		recipe: Box<ExpressionRepr>,
+	},
+}
+
+
+impl ExpressionRepr {
+	/// Converts the `Expression` into its serialized form, using the given map
+	/// to resolve source cell addresses.
+	fn from_expression(
+		expr: &Expression, 
+		addresses: &HashMap<*const Cell, Address>)
+		-> Option<ExpressionRepr>
+	{
+		let address = |source: &Weak<Cell>| source
+			.upgrade()
+			.and_then(|cell| addresses.get(&(&*cell as *const Cell)).cloned());
+
+		Some(match *expr {
+			Expression::Empty => ExpressionRepr::Empty,
+
+			Expression::Color(color) => ExpressionRepr::Color {
+				color: [color.r(), color.g(), color.b()],
+			},
+
+			Expression::Cmyk(cmyk) => ExpressionRepr::Cmyk {cmyk: cmyk},
+
+			Expression::Blend {ref a, ref b, ratio, interpolation, easing} 
+				=> ExpressionRepr::Blend {
+					a: address(a)?,
+					b: address(b)?,
+					ratio: ratio,
+					interpolation: interpolation,
+					easing: easing,
+				},
+
+			Expression::Lighten {ref a, amount} => ExpressionRepr::Lighten {
+				a: address(a)?,
+				amount: amount,
+			},
+
+			Expression::Darken {ref a, amount} => ExpressionRepr::Darken {
+				a: address(a)?,
+				amount: amount,
+			},
+
+			Expression::Complement {ref a} => ExpressionRepr::Complement {
+				a: address(a)?,
+			},
+
+			Expression::Adjust {ref a, adjustment} => ExpressionRepr::Adjust {
+				a: address(a)?,
+				adjustment: adjustment,
+			},
+
+			Expression::Frozen {color, ref recipe} => ExpressionRepr::Frozen {
+				color: [color.r(), color.g(), color.b()],
+				recipe: Box::new(
+					ExpressionRepr::from_expression(recipe, addresses)?),
+			},
+		})
+	}
+
+	/// Converts the serialized form into an `Expression`, using the given 
+	/// cells to resolve source addresses.
+	fn into_expression(self, data: &Data) -> Option<Expression> {
+		let source = |address: Address| data.cell(address)
+			.map(|cell| Shared::downgrade(&cell));
+
+		Some(match self {
+			ExpressionRepr::Empty => Expression::Empty,
+
+			ExpressionRepr::Color {color} 
+				=> Expression::Color(Color::new(color[0], color[1], color[2])),
+
+			ExpressionRepr::Cmyk {cmyk} => Expression::Cmyk(cmyk),
+
+			ExpressionRepr::Blend {a, b, ratio, interpolation, easing} 
+				=> Expression::Blend {
+					a: source(a)?,
+					b: source(b)?,
+					ratio: ratio,
+					interpolation: interpolation,
+					easing: easing,
+				},
+
+			ExpressionRepr::Lighten {a, amount} => Expression::Lighten {
+				a: source(a)?,
+				amount: amount,
+			},
+
+			ExpressionRepr::Darken {a, amount} => Expression::Darken {
+				a: source(a)?,
+				amount: amount,
+			},
+
+			ExpressionRepr::Complement {a} => Expression::Complement {
+				a: source(a)?,
+			},
+
+			ExpressionRepr::Adjust {a, adjustment} => Expression::Adjust {
+				a: source(a)?,
+				adjustment: adjustment,
+			},
+
+			ExpressionRepr::Frozen {color, recipe} => Expression::Frozen {
+				color: Color::new(color[0], color[1], color[2]),
+				recipe: Box::new((*recipe).into_expression(data)?),
+			},
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// MetaValueRepr
+////////////////////////////////////////////////////////////////////////////////
+/// The serialized form of a `MetaValue`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+enum MetaValueRepr {
+	String(String),
+	Int(i64),
+	Color([u8; 3]),
+	Bool(bool),
+}
+
+
+impl Serialize for MetaValue {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+		where S: Serializer
+	{
+		match *self {
+			MetaValue::String(ref value) 
+				=> MetaValueRepr::String(value.clone()),
+			MetaValue::Int(value) => MetaValueRepr::Int(value),
+			MetaValue::Color(color) 
+				=> MetaValueRepr::Color([color.r(), color.g(), color.b()]),
+			MetaValue::Bool(value) => MetaValueRepr::Bool(value),
+		}.serialize(serializer)
+	}
+}
+
+
+impl<'de> Deserialize<'de> for MetaValue {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+		where D: Deserializer<'de>
+	{
+		Ok(match MetaValueRepr::deserialize(deserializer)? {
+			MetaValueRepr::String(value) => MetaValue::String(value),
+			MetaValueRepr::Int(value) => MetaValue::Int(value),
+			MetaValueRepr::Color(color) 
+				=> MetaValue::Color(Color::new(color[0], color[1], color[2])),
+			MetaValueRepr::Bool(value) => MetaValue::Bool(value),
+		})
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// DataRepr
+////////////////////////////////////////////////////////////////////////////////
+/// The serialized form of a palette's `Data`.
+#[derive(Debug, Serialize, Deserialize)]
+struct DataRepr {
+	cells: Vec<CellRepr>,
+	names: HashMap<String, Reference>,
+	metadata: Vec<(Reference, MetaData)>,
+	maximum_page_count: Page,
+	default_line_count: Line,
+	default_column_count: Column,
+	#[serde(default)]
+	cycles: Vec<Cycle>,
+	#[serde(default)]
+	ramps: Vec<RampSpec>,
+	#[serde(default)]
+	constraints: Vec<(Address, Constraint)>,
+	#[serde(default)]
+	schema: MetaSchema,
+}
+
+
+/// The serialized form of a single `Cell`.
+#[derive(Debug, Serialize, Deserialize)]
+struct CellRepr {
+	address: Address,
+	expression: ExpressionRepr,
+}
+
+
+impl Serialize for Data {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+		where S: Serializer
+	{
+		let addresses: HashMap<*const Cell, Address> = self.cells
+			.iter()
+			.map(|(address, cell)| (&**cell as *const Cell, address))
+			.collect();
+
+		let mut cells = Vec::with_capacity(self.cells.len());
+		for (address, cell) in self.cells.iter() {
+			let expression = ExpressionRepr::from_expression(
+					&*cell.borrow(), 
+					&addresses)
+				.ok_or_else(|| S::Error::custom(format!(
+					"unresolved source reference in cell {}", address)))?;
+			cells.push(CellRepr {address: address, expression: expression});
+		}
+
+		DataRepr {
+			cells: cells,
+			names: self.names.clone(),
+			metadata: self.metadata
+				.iter()
+				.map(|(group, meta)| (group.clone(), meta.clone()))
+				.collect(),
+			maximum_page_count: self.maximum_page_count,
+			default_line_count: self.default_line_count,
+			default_column_count: self.default_column_count,
+			cycles: self.cycles.clone(),
+			ramps: self.ramps.clone(),
+			constraints: self.constraints
+				.iter()
+				.map(|(&address, &constraint)| (address, constraint))
+				.collect(),
+			schema: self.schema.clone(),
+		}.serialize(serializer)
+	}
+}
+
+
+impl<'de> Deserialize<'de> for Data {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+		where D: Deserializer<'de>
+	{
+		let repr = DataRepr::deserialize(deserializer)?;
+
+		let mut data = Data {
+			names: repr.names,
+			metadata: repr.metadata.into_iter().collect(),
+			maximum_page_count: repr.maximum_page_count,
+			default_line_count: repr.default_line_count,
+			default_column_count: repr.default_column_count,
+			cycles: repr.cycles,
+			ramps: repr.ramps,
+			constraints: repr.constraints.into_iter().collect(),
+			schema: repr.schema,
+			.. Default::default()
+		};
+
+		// Create all cells before resolving source references.
+		for cell in &repr.cells {
+			data.insert_cell(
+				cell.address, 
+				Shared::new(Cell::new(Expression::Empty)));
+		}
+
+		for cell in repr.cells {
+			let address = cell.address;
+			let expr = cell.expression.into_expression(&data)
+				.ok_or_else(|| D::Error::custom(format!(
+					"invalid source reference in cell {}", address)))?;
+			*data.cells[&address].borrow_mut() = expr;
+		}
+
+		// Reject cyclic references from untrusted input.
+		data.validate().map_err(D::Error::custom)?;
+		Ok(data)
+	}
+}
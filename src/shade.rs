@@ -0,0 +1,203 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides generation of shading ramps from a single base color.
+//!
+//! Shading ramps in the style commonly used for pixel art do not simply 
+//! darken and lighten a color; the hue also shifts toward cool colors in the 
+//! shadows and toward warm colors in the highlights, and saturation falls off
+//! toward either end of the ramp.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use space::{normalize_hue, Easing, Hsv};
+use utilities::{clamped, lerp_f32};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
+
+/// The hue, in degrees, which highlights are shifted toward.
+const WARM_HUE: f32 = 60.0;
+
+/// The hue, in degrees, which shadows are shifted toward.
+const COOL_HUE: f32 = 240.0;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// ShadeOptions
+////////////////////////////////////////////////////////////////////////////////
+/// Options controlling the shape of a generated shading ramp.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ShadeOptions {
+	/// The maximum hue shift in degrees at either end of the ramp. Shadows 
+	/// shift toward blue and highlights toward yellow. A NaN shift is treated
+	/// as no shift, and an infinite shift as a full shift to the target hue.
+	pub hue_shift: f32,
+	/// The fraction of the base saturation lost at either end of the ramp, 
+	/// between 0 and 1.
+	pub saturation_falloff: f32,
+	/// The curve applied to the distance from the base color.
+	pub value_curve: Easing,
+	/// The HSV value of the darkest color, between 0 and 1.
+	pub shadow_value: f32,
+	/// The HSV value of the lightest color, between 0 and 1.
+	pub highlight_value: f32,
+}
+
+
+impl ShadeOptions {
+	/// Creates a new `ShadeOptions` with the default settings.
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	/// Sets the maximum hue shift in degrees.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::Color;
+	/// use palette::shade::{generate_shades, ShadeOptions};
+	/// 
+	/// let base = Color::new(60, 140, 70);
+	/// let unshifted = ShadeOptions::new().hue_shift(0.0);
+	/// let nan = ShadeOptions::new().hue_shift(::std::f32::NAN);
+	///
+	/// assert_eq!(generate_shades(base, 5, &nan), 
+	/// 	generate_shades(base, 5, &unshifted));
+	/// assert_eq!(
+	/// 	generate_shades(base, 5, &ShadeOptions::new().hue_shift(1.0e30)),
+	/// 	generate_shades(base, 5, 
+	/// 		&ShadeOptions::new().hue_shift(::std::f32::INFINITY)));
+	/// ```
+	pub fn hue_shift(mut self, hue_shift: f32) -> Self {
+		self.hue_shift = hue_shift;
+		self
+	}
+
+	/// Sets the fraction of saturation lost at either end of the ramp.
+	pub fn saturation_falloff(mut self, saturation_falloff: f32) -> Self {
+		self.saturation_falloff = saturation_falloff;
+		self
+	}
+
+	/// Sets the curve applied to the distance from the base color.
+	pub fn value_curve(mut self, value_curve: Easing) -> Self {
+		self.value_curve = value_curve;
+		self
+	}
+
+	/// Sets the HSV values of the darkest and lightest colors.
+	pub fn value_range(mut self, shadow_value: f32, highlight_value: f32) 
+		-> Self
+	{
+		self.shadow_value = shadow_value;
+		self.highlight_value = highlight_value;
+		self
+	}
+}
+
+
+impl Default for ShadeOptions {
+	fn default() -> Self {
+		ShadeOptions {
+			hue_shift: 20.0,
+			saturation_falloff: 0.3,
+			value_curve: Easing::Linear,
+			shadow_value: 0.1,
+			highlight_value: 0.95,
+		}
+	}
+}
+
+
+/// Returns the given hue shifted toward the target hue by at most the given 
+/// number of degrees. A NaN amount leaves the hue unchanged, and amounts 
+/// beyond 180 degrees, including infinities, shift fully to the target.
+fn shift_hue(hue: f32, target: f32, amount: f32) -> f32 {
+	if amount.is_nan() { return hue; }
+	let amount = amount.abs().min(180.0);
+	let mut delta = normalize_hue(target - hue);
+	if delta > 180.0 { delta -= 360.0; }
+	normalize_hue(hue + clamped(delta, -amount, amount))
+}
+
+
+/// Generates a shading ramp of the given number of colors from darkest to 
+/// lightest. The base color is placed at the center of the ramp, or just 
+/// above the center if the count is even.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Color;
+/// use palette::shade::{generate_shades, ShadeOptions};
+/// use palette::space::Hsv;
+/// 
+/// let base = Color::new(60, 140, 70);
+/// let shades = generate_shades(base, 5, &ShadeOptions::new());
+///
+/// assert_eq!(shades.len(), 5);
+/// assert_eq!(shades[2], base);
+/// assert!(Hsv::from(shades[0]).v < Hsv::from(shades[4]).v);
+/// ```
+pub fn generate_shades(base: Color, count: usize, options: &ShadeOptions) 
+	-> Vec<Color>
+{
+	if count <= 1 { return vec![base; count]; }
+
+	let hsv = Hsv::from(base);
+	let center = (count / 2) as f32;
+	(0..count).map(|i| {
+		// The signed distance from the base color, between -1 and 1.
+		let offset = if (i as f32) < center {
+			(i as f32 - center) / center
+		} else if count - 1 > count / 2 {
+			(i as f32 - center) / ((count - 1) as f32 - center)
+		} else {
+			0.0
+		};
+		let distance = options.value_curve.apply(offset.abs());
+		if distance <= 0.0 { return base; }
+
+		let (value, target) = if offset < 0.0 {
+			(options.shadow_value, COOL_HUE)
+		} else {
+			(options.highlight_value, WARM_HUE)
+		};
+		Color::from(Hsv::new(
+			shift_hue(hsv.h, target, options.hue_shift * distance),
+			hsv.s * (1.0 - clamped(options.saturation_falloff, 0.0, 1.0) 
+				* distance),
+			lerp_f32(hsv.v, value, distance)))
+	}).collect()
+}
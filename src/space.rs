@@ -0,0 +1,987 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides alternative color space representations and conversions to and 
+//! from RGB `Color`s.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use utilities::{clamped, lerp_f32, lerp_u8};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
+
+/// Returns the given channel value as a ratio between 0 and 1.
+#[inline]
+fn ratio(channel: u8) -> f32 {
+	channel as f32 / 255.0
+}
+
+/// Returns the channel value for the given ratio between 0 and 1.
+#[inline]
+fn channel(ratio: f32) -> u8 {
+	(clamped(ratio, 0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Returns the hue in degrees and the chroma of the given color, along with 
+/// the maximum channel ratio.
+#[cfg_attr(feature = "cargo-clippy", allow(float_cmp))]
+fn hue_chroma(color: Color) -> (f32, f32, f32) {
+	let (r, g, b) = (ratio(color.r()), ratio(color.g()), ratio(color.b()));
+	let max = r.max(g).max(b);
+	let min = r.min(g).min(b);
+	let chroma = max - min;
+
+	let hue = if chroma == 0.0 {
+		0.0
+	} else if max == r {
+		60.0 * (((g - b) / chroma) % 6.0)
+	} else if max == g {
+		60.0 * ((b - r) / chroma + 2.0)
+	} else {
+		60.0 * ((r - g) / chroma + 4.0)
+	};
+
+	(normalize_hue(hue), chroma, max)
+}
+
+/// Returns the color with the given hue in degrees, chroma, and lightness 
+/// offset.
+fn from_hue_chroma(hue: f32, chroma: f32, offset: f32) -> Color {
+	let h = normalize_hue(hue) / 60.0;
+	let x = chroma * (1.0 - ((h % 2.0) - 1.0).abs());
+	let (r, g, b) = match h as u8 {
+		0 => (chroma, x, 0.0),
+		1 => (x, chroma, 0.0),
+		2 => (0.0, chroma, x),
+		3 => (0.0, x, chroma),
+		4 => (x, 0.0, chroma),
+		_ => (chroma, 0.0, x),
+	};
+	Color::new(channel(r + offset), channel(g + offset), channel(b + offset))
+}
+
+/// Converts a gamma-encoded sRGB ratio to linear light.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::space::{linear_to_srgb, srgb_to_linear};
+/// use palette::utilities::close;
+/// 
+/// assert!(close(srgb_to_linear(0.5), 0.214, 0.001));
+/// assert!(close(linear_to_srgb(srgb_to_linear(0.5)), 0.5, 0.001));
+/// ```
+pub fn srgb_to_linear(c: f32) -> f32 {
+	if c <= 0.04045 {
+		c / 12.92
+	} else {
+		((c + 0.055) / 1.055).powf(2.4)
+	}
+}
+
+/// Converts a linear light ratio to gamma-encoded sRGB.
+pub fn linear_to_srgb(c: f32) -> f32 {
+	if c <= 0.003_130_8 {
+		c * 12.92
+	} else {
+		1.055 * c.powf(1.0 / 2.4) - 0.055
+	}
+}
+
+/// Returns the given hue in degrees wrapped to the range [0, 360).
+#[inline]
+pub fn normalize_hue(hue: f32) -> f32 {
+	let hue = hue % 360.0;
+	if hue < 0.0 { hue + 360.0 } else { hue }
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// LinearRgb
+////////////////////////////////////////////////////////////////////////////////
+/// A color in the linear-light RGB color space, with the sRGB primaries. 
+/// Unlike gamma-encoded channels, linear channels can be added and scaled to
+/// physically mix light.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Color;
+/// use palette::space::LinearRgb;
+/// 
+/// let c = Color::new(200, 40, 90);
+/// let linear = LinearRgb::from(c);
+///
+/// assert!(linear.r < 200.0 / 255.0);
+/// assert_eq!(Color::from(linear), c);
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct LinearRgb {
+	/// The red channel, between 0 and 1.
+	pub r: f32,
+	/// The green channel, between 0 and 1.
+	pub g: f32,
+	/// The blue channel, between 0 and 1.
+	pub b: f32,
+}
+
+
+impl LinearRgb {
+	/// Creates a new `LinearRgb` color.
+	pub fn new(r: f32, g: f32, b: f32) -> Self {
+		LinearRgb {r: r, g: g, b: b}
+	}
+}
+
+
+impl From<Color> for LinearRgb {
+	fn from(color: Color) -> Self {
+		LinearRgb {
+			r: srgb_to_linear(ratio(color.r())),
+			g: srgb_to_linear(ratio(color.g())),
+			b: srgb_to_linear(ratio(color.b())),
+		}
+	}
+}
+
+
+impl From<LinearRgb> for Color {
+	fn from(linear: LinearRgb) -> Self {
+		Color::new(
+			channel(linear_to_srgb(clamped(linear.r, 0.0, 1.0))),
+			channel(linear_to_srgb(clamped(linear.g, 0.0, 1.0))),
+			channel(linear_to_srgb(clamped(linear.b, 0.0, 1.0))))
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Hsl
+////////////////////////////////////////////////////////////////////////////////
+/// A color in the HSL (hue, saturation, lightness) color space.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Color;
+/// use palette::space::Hsl;
+/// 
+/// let c = Color::new(200, 40, 90);
+/// let hsl = Hsl::from(c);
+///
+/// assert_eq!(Color::from(hsl), c);
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Hsl {
+	/// The hue, in degrees.
+	pub h: f32,
+	/// The saturation, between 0 and 1.
+	pub s: f32,
+	/// The lightness, between 0 and 1.
+	pub l: f32,
+}
+
+
+impl Hsl {
+	/// Creates a new `Hsl` color.
+	pub fn new(h: f32, s: f32, l: f32) -> Self {
+		Hsl {h: normalize_hue(h), s: s, l: l}
+	}
+}
+
+
+impl From<Color> for Hsl {
+	fn from(color: Color) -> Self {
+		let (h, chroma, max) = hue_chroma(color);
+		let l = max - chroma / 2.0;
+		let s = if l <= 0.0 || l >= 1.0 {
+			0.0
+		} else {
+			chroma / (1.0 - (2.0 * l - 1.0).abs())
+		};
+		Hsl {h: h, s: s, l: l}
+	}
+}
+
+
+impl From<Hsl> for Color {
+	fn from(hsl: Hsl) -> Self {
+		let s = clamped(hsl.s, 0.0, 1.0);
+		let l = clamped(hsl.l, 0.0, 1.0);
+		let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+		from_hue_chroma(hsl.h, chroma, l - chroma / 2.0)
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Hsv
+////////////////////////////////////////////////////////////////////////////////
+/// A color in the HSV (hue, saturation, value) color space.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Color;
+/// use palette::space::Hsv;
+/// 
+/// let c = Color::new(200, 40, 90);
+/// let hsv = Hsv::from(c);
+///
+/// assert_eq!(Color::from(hsv), c);
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Hsv {
+	/// The hue, in degrees.
+	pub h: f32,
+	/// The saturation, between 0 and 1.
+	pub s: f32,
+	/// The value, between 0 and 1.
+	pub v: f32,
+}
+
+
+impl Hsv {
+	/// Creates a new `Hsv` color.
+	pub fn new(h: f32, s: f32, v: f32) -> Self {
+		Hsv {h: normalize_hue(h), s: s, v: v}
+	}
+}
+
+
+impl From<Color> for Hsv {
+	#[cfg_attr(feature = "cargo-clippy", allow(float_cmp))]
+	fn from(color: Color) -> Self {
+		let (h, chroma, max) = hue_chroma(color);
+		let s = if max == 0.0 { 0.0 } else { chroma / max };
+		Hsv {h: h, s: s, v: max}
+	}
+}
+
+
+impl From<Hsv> for Color {
+	fn from(hsv: Hsv) -> Self {
+		let s = clamped(hsv.s, 0.0, 1.0);
+		let v = clamped(hsv.v, 0.0, 1.0);
+		let chroma = v * s;
+		from_hue_chroma(hsv.h, chroma, v - chroma)
+	}
+}
+
+
+impl From<Hsl> for Hsv {
+	#[cfg_attr(feature = "cargo-clippy", allow(float_cmp))]
+	fn from(hsl: Hsl) -> Self {
+		let v = hsl.l + hsl.s * hsl.l.min(1.0 - hsl.l);
+		let s = if v == 0.0 { 0.0 } else { 2.0 * (1.0 - hsl.l / v) };
+		Hsv {h: hsl.h, s: s, v: v}
+	}
+}
+
+
+impl From<Hsv> for Hsl {
+	fn from(hsv: Hsv) -> Self {
+		let l = hsv.v * (1.0 - hsv.s / 2.0);
+		let s = if l <= 0.0 || l >= 1.0 { 
+			0.0 
+		} else { 
+			(hsv.v - l) / l.min(1.0 - l)
+		};
+		Hsl {h: hsv.h, s: s, l: l}
+	}
+}
+
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Lab
+////////////////////////////////////////////////////////////////////////////////
+/// The D65 reference white point in XYZ.
+const D65_WHITE: (f32, f32, f32) = (0.950_47, 1.0, 1.088_83);
+
+/// The CIELAB linear segment threshold.
+const LAB_EPSILON: f32 = 6.0 / 29.0;
+
+
+/// The CIELAB forward transfer function.
+fn lab_f(t: f32) -> f32 {
+	if t > LAB_EPSILON * LAB_EPSILON * LAB_EPSILON {
+		t.cbrt()
+	} else {
+		t / (3.0 * LAB_EPSILON * LAB_EPSILON) + 4.0 / 29.0
+	}
+}
+
+/// The CIELAB inverse transfer function.
+pub(crate) fn lab_f_inv(t: f32) -> f32 {
+	if t > LAB_EPSILON {
+		t * t * t
+	} else {
+		3.0 * LAB_EPSILON * LAB_EPSILON * (t - 4.0 / 29.0)
+	}
+}
+
+
+/// A color in the CIELAB color space, relative to the D65 white point.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Color;
+/// use palette::space::Lab;
+/// 
+/// let c = Color::new(200, 40, 90);
+/// let lab = Lab::from(c);
+///
+/// assert_eq!(Color::from(lab), c);
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Lab {
+	/// The lightness, between 0 and 100.
+	pub l: f32,
+	/// The green-red component.
+	pub a: f32,
+	/// The blue-yellow component.
+	pub b: f32,
+}
+
+
+impl Lab {
+	/// Creates a new `Lab` color.
+	pub fn new(l: f32, a: f32, b: f32) -> Self {
+		Lab {l: l, a: a, b: b}
+	}
+
+	/// Returns the CIE76 color difference between the given colors.
+	pub fn delta_e(&self, other: &Lab) -> f32 {
+		let dl = self.l - other.l;
+		let da = self.a - other.a;
+		let db = self.b - other.b;
+		(dl * dl + da * da + db * db).sqrt()
+	}
+}
+
+
+impl From<Color> for Lab {
+	fn from(color: Color) -> Self {
+		let r = srgb_to_linear(ratio(color.r()));
+		let g = srgb_to_linear(ratio(color.g()));
+		let b = srgb_to_linear(ratio(color.b()));
+
+		let x = 0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b;
+		let y = 0.212_672_9 * r + 0.715_152_2 * g + 0.072_175_0 * b;
+		let z = 0.019_333_9 * r + 0.119_192_0 * g + 0.950_304_1 * b;
+
+		let fx = lab_f(x / D65_WHITE.0);
+		let fy = lab_f(y / D65_WHITE.1);
+		let fz = lab_f(z / D65_WHITE.2);
+
+		Lab {
+			l: 116.0 * fy - 16.0,
+			a: 500.0 * (fx - fy),
+			b: 200.0 * (fy - fz),
+		}
+	}
+}
+
+
+impl From<Lab> for Color {
+	fn from(lab: Lab) -> Self {
+		let fy = (lab.l + 16.0) / 116.0;
+		let fx = fy + lab.a / 500.0;
+		let fz = fy - lab.b / 200.0;
+
+		let x = D65_WHITE.0 * lab_f_inv(fx);
+		let y = D65_WHITE.1 * lab_f_inv(fy);
+		let z = D65_WHITE.2 * lab_f_inv(fz);
+
+		let r =  3.240_454_2 * x - 1.537_138_5 * y - 0.498_531_4 * z;
+		let g = -0.969_266_0 * x + 1.876_010_8 * y + 0.041_556_0 * z;
+		let b =  0.055_643_4 * x - 0.204_025_9 * y + 1.057_225_2 * z;
+
+		Color::new(
+			channel(linear_to_srgb(r)),
+			channel(linear_to_srgb(g)),
+			channel(linear_to_srgb(b)))
+	}
+}
+
+
+/// Returns the CIE76 perceptual color difference between the given colors.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Color;
+/// use palette::space::delta_e;
+/// 
+/// let a = Color::new(200, 40, 90);
+///
+/// assert_eq!(delta_e(a, a), 0.0);
+/// assert!(delta_e(a, Color::new(0, 0, 0)) > 40.0);
+/// ```
+pub fn delta_e(a: Color, b: Color) -> f32 {
+	Lab::from(a).delta_e(&Lab::from(b))
+}
+
+
+
+/// The minimum WCAG contrast ratio for level AA normal text.
+pub const CONTRAST_AA: f32 = 4.5;
+
+/// The minimum WCAG contrast ratio for level AA large text.
+pub const CONTRAST_AA_LARGE: f32 = 3.0;
+
+/// The minimum WCAG contrast ratio for level AAA normal text.
+pub const CONTRAST_AAA: f32 = 7.0;
+
+
+/// Returns the WCAG relative luminance of the given color, between 0 and 1.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Color;
+/// use palette::space::relative_luminance;
+/// use palette::utilities::close;
+/// 
+/// assert_eq!(relative_luminance(Color::new(0, 0, 0)), 0.0);
+/// assert!(close(relative_luminance(Color::new(255, 255, 255)), 1.0, 0.001));
+/// ```
+pub fn relative_luminance(color: Color) -> f32 {
+	0.2126 * srgb_to_linear(ratio(color.r())) 
+		+ 0.7152 * srgb_to_linear(ratio(color.g())) 
+		+ 0.0722 * srgb_to_linear(ratio(color.b()))
+}
+
+/// Returns the WCAG contrast ratio between the given colors, between 1 and 21.
+/// The ratio is the same regardless of argument order.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Color;
+/// use palette::space::{contrast_ratio, CONTRAST_AA};
+/// use palette::utilities::close;
+/// 
+/// let black = Color::new(0, 0, 0);
+/// let white = Color::new(255, 255, 255);
+///
+/// assert!(close(contrast_ratio(black, white), 21.0, 0.001));
+/// assert!(contrast_ratio(Color::new(119, 119, 119), white) < CONTRAST_AA);
+/// ```
+pub fn contrast_ratio(a: Color, b: Color) -> f32 {
+	let (la, lb) = (relative_luminance(a), relative_luminance(b));
+	(la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Lch
+////////////////////////////////////////////////////////////////////////////////
+/// A color in the CIE LCh color space; the cylindrical form of `Lab`.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Lch {
+	/// The lightness, between 0 and 100.
+	pub l: f32,
+	/// The chroma.
+	pub c: f32,
+	/// The hue, in degrees.
+	pub h: f32,
+}
+
+
+impl Lch {
+	/// Creates a new `Lch` color.
+	pub fn new(l: f32, c: f32, h: f32) -> Self {
+		Lch {l: l, c: c, h: normalize_hue(h)}
+	}
+}
+
+
+impl From<Lab> for Lch {
+	fn from(lab: Lab) -> Self {
+		Lch {
+			l: lab.l,
+			c: (lab.a * lab.a + lab.b * lab.b).sqrt(),
+			h: normalize_hue(lab.b.atan2(lab.a).to_degrees()),
+		}
+	}
+}
+
+
+impl From<Lch> for Lab {
+	fn from(lch: Lch) -> Self {
+		let h = lch.h.to_radians();
+		Lab {
+			l: lch.l,
+			a: lch.c * h.cos(),
+			b: lch.c * h.sin(),
+		}
+	}
+}
+
+
+impl From<Color> for Lch {
+	fn from(color: Color) -> Self {
+		Lch::from(Lab::from(color))
+	}
+}
+
+
+impl From<Lch> for Color {
+	fn from(lch: Lch) -> Self {
+		Color::from(Lab::from(lch))
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Cmyk
+////////////////////////////////////////////////////////////////////////////////
+/// A color in the CMYK (cyan, magenta, yellow, key) subtractive color space.
+///
+/// The conversions to and from RGB are naive; they do not model any 
+/// particular ink or paper, so they are only suitable for storing colors 
+/// which were specified in CMYK.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::Color;
+/// use palette::space::Cmyk;
+/// 
+/// let cmyk = Cmyk::new(0.0, 1.0, 1.0, 0.0);
+/// assert_eq!(Color::from(cmyk), Color::new(255, 0, 0));
+/// assert_eq!(Cmyk::from(Color::new(255, 0, 0)), cmyk);
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Cmyk {
+	/// The cyan ink coverage, between 0 and 1.
+	pub c: f32,
+	/// The magenta ink coverage, between 0 and 1.
+	pub m: f32,
+	/// The yellow ink coverage, between 0 and 1.
+	pub y: f32,
+	/// The key (black) ink coverage, between 0 and 1.
+	pub k: f32,
+}
+
+
+impl Cmyk {
+	/// Creates a new `Cmyk` color.
+	pub fn new(c: f32, m: f32, y: f32, k: f32) -> Self {
+		Cmyk {c: c, m: m, y: y, k: k}
+	}
+}
+
+
+impl From<Color> for Cmyk {
+	fn from(color: Color) -> Self {
+		let (r, g, b) = (ratio(color.r()), ratio(color.g()), ratio(color.b()));
+		let max = r.max(g).max(b);
+		if max <= 0.0 { return Cmyk::new(0.0, 0.0, 0.0, 1.0); }
+		Cmyk {
+			c: (max - r) / max,
+			m: (max - g) / max,
+			y: (max - b) / max,
+			k: 1.0 - max,
+		}
+	}
+}
+
+
+impl From<Cmyk> for Color {
+	fn from(cmyk: Cmyk) -> Self {
+		let k = 1.0 - clamped(cmyk.k, 0.0, 1.0);
+		Color::new(
+			channel((1.0 - clamped(cmyk.c, 0.0, 1.0)) * k),
+			channel((1.0 - clamped(cmyk.m, 0.0, 1.0)) * k),
+			channel((1.0 - clamped(cmyk.y, 0.0, 1.0)) * k))
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Interpolation
+////////////////////////////////////////////////////////////////////////////////
+/// The color space in which colors are interpolated.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Interpolation {
+	/// Interpolate the gamma-encoded RGB channels.
+	Rgb,
+	/// Interpolate the linear-light RGB channels, which physically mixes the 
+	/// colors and avoids the darkened midpoints of gamma-encoded blending.
+	LinearRgb,
+	/// Interpolate in the CIELAB color space, which gives perceptually uniform
+	/// steps.
+	Lab,
+}
+
+
+impl Interpolation {
+	/// Returns the color located at the given ratio between the given colors,
+	/// which is clamped between 0 and 1.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::Color;
+	/// use palette::space::Interpolation;
+	/// 
+	/// let a = Color::new(0, 0, 0);
+	/// let b = Color::new(100, 100, 100);
+	///
+	/// assert_eq!(Interpolation::Rgb.interpolate(a, b, 0.5), 
+	/// 	Color::new(50, 50, 50));
+	/// assert_eq!(Interpolation::LinearRgb.interpolate(a, b, 0.5), 
+	/// 	Color::new(71, 71, 71));
+	/// ```
+	pub fn interpolate(self, a: Color, b: Color, ratio: f32) -> Color {
+		match self {
+			Interpolation::Rgb => Color::new(
+				lerp_u8(a.r(), b.r(), ratio),
+				lerp_u8(a.g(), b.g(), ratio),
+				lerp_u8(a.b(), b.b(), ratio),
+			),
+
+			Interpolation::LinearRgb => {
+				let a = LinearRgb::from(a);
+				let b = LinearRgb::from(b);
+				Color::from(LinearRgb {
+					r: lerp_f32(a.r, b.r, ratio),
+					g: lerp_f32(a.g, b.g, ratio),
+					b: lerp_f32(a.b, b.b, ratio),
+				})
+			},
+
+			Interpolation::Lab => {
+				let a = Lab::from(a);
+				let b = Lab::from(b);
+				Color::from(Lab {
+					l: lerp_f32(a.l, b.l, ratio),
+					a: lerp_f32(a.a, b.a, ratio),
+					b: lerp_f32(a.b, b.b, ratio),
+				})
+			},
+		}
+	}
+}
+
+
+impl Default for Interpolation {
+	fn default() -> Self {
+		Interpolation::Rgb
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Easing
+////////////////////////////////////////////////////////////////////////////////
+/// A curve remapping interpolation ratios, allowing interpolated colors to 
+/// cluster toward either end of a blend or ramp.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Easing {
+	/// Ratios are unchanged.
+	Linear,
+	/// Quadratic easing; colors cluster toward the start.
+	EaseIn,
+	/// Quadratic easing; colors cluster toward the end.
+	EaseOut,
+	/// Smoothstep easing; colors cluster toward both ends.
+	EaseInOut,
+	/// Raises ratios to the given power. Values above 1 cluster colors toward
+	/// the start, and values below 1 cluster them toward the end.
+	Gamma(f32),
+	/// A CSS-style cubic bezier curve through `(0, 0)`, the control points
+	/// `(x1, y1)` and `(x2, y2)`, and `(1, 1)`. The `x` coordinates are 
+	/// clamped between 0 and 1.
+	CubicBezier(f32, f32, f32, f32),
+}
+
+
+/// The number of bisection steps used to solve cubic bezier easing curves.
+const BEZIER_ITERATIONS: usize = 24;
+
+
+impl Easing {
+	/// Returns the eased value of the given ratio. Linear easing returns the
+	/// ratio unchanged; all other curves clamp it between 0 and 1.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::space::Easing;
+	/// use palette::utilities::close;
+	/// 
+	/// assert!(close(Easing::EaseIn.apply(0.5), 0.25, 0.001));
+	/// assert!(close(Easing::Gamma(3.0).apply(0.5), 0.125, 0.001));
+	/// assert!(close(Easing::CubicBezier(0.0, 0.0, 1.0, 1.0).apply(0.3), 0.3,
+	/// 	0.001));
+	/// ```
+	pub fn apply(self, ratio: f32) -> f32 {
+		if self == Easing::Linear { return ratio; }
+		let t = clamped(ratio, 0.0, 1.0);
+		match self {
+			Easing::Linear => t,
+			Easing::EaseIn => t * t,
+			Easing::EaseOut => t * (2.0 - t),
+			Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+			Easing::Gamma(gamma) => t.powf(gamma),
+			Easing::CubicBezier(x1, y1, x2, y2) => {
+				let x1 = clamped(x1, 0.0, 1.0);
+				let x2 = clamped(x2, 0.0, 1.0);
+				let bezier = |p1: f32, p2: f32, s: f32| {
+					let r = 1.0 - s;
+					3.0 * r * r * s * p1 + 3.0 * r * s * s * p2 + s * s * s
+				};
+				// The x coordinate is monotonic in s, so bisect to find the
+				// curve parameter for the ratio.
+				let (mut lo, mut hi) = (0.0, 1.0);
+				for _ in 0..BEZIER_ITERATIONS {
+					let mid = (lo + hi) / 2.0;
+					if bezier(x1, x2, mid) < t { lo = mid; } else { hi = mid; }
+				}
+				bezier(y1, y2, (lo + hi) / 2.0)
+			},
+		}
+	}
+}
+
+
+impl Default for Easing {
+	fn default() -> Self {
+		Easing::Linear
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Channel
+////////////////////////////////////////////////////////////////////////////////
+/// An RGB color channel.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Channel {
+	/// The red channel.
+	Red,
+	/// The green channel.
+	Green,
+	/// The blue channel.
+	Blue,
+}
+
+
+impl Channel {
+	/// Returns the index of the channel in RGB order.
+	fn index(self) -> usize {
+		match self {
+			Channel::Red => 0,
+			Channel::Green => 1,
+			Channel::Blue => 2,
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Adjustment
+////////////////////////////////////////////////////////////////////////////////
+/// A tonal or hue adjustment applied to a color.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Adjustment {
+	/// Adds the given amount, between -1 and 1, to each channel.
+	Brightness(f32),
+	/// Scales the distance of each channel from the midpoint by one plus the
+	/// given amount. Negative amounts reduce contrast, and -1 gives flat gray.
+	Contrast(f32),
+	/// Rotates the hue by the given number of degrees.
+	HueRotate(f32),
+	/// Scales the HSL saturation by one plus the given amount. Negative 
+	/// amounts desaturate, and -1 gives gray.
+	Saturate(f32),
+	/// Raises each channel to the reciprocal of the given gamma. Values above
+	/// 1 brighten the midtones, and values below 1 darken them.
+	Gamma(f32),
+	/// Stretches the channel range between `black` and `white` to the full 
+	/// range, then applies the given gamma to the midtones.
+	Levels {
+		/// The channel value mapped to 0.
+		black: u8,
+		/// The channel value mapped to 255.
+		white: u8,
+		/// The midtone gamma.
+		gamma: f32,
+	},
+	/// Exchanges the values of two channels.
+	SwapChannels(Channel, Channel),
+}
+
+
+/// Returns the color with the given function applied to each channel ratio.
+fn map_channels<F>(color: Color, f: F) -> Color where F: Fn(f32) -> f32 {
+	Color::new(
+		channel(f(ratio(color.r()))),
+		channel(f(ratio(color.g()))),
+		channel(f(ratio(color.b()))))
+}
+
+/// Returns the given ratio raised to the reciprocal of the given gamma. 
+/// Non-positive gammas leave the ratio unchanged.
+fn gamma_ratio(ratio: f32, gamma: f32) -> f32 {
+	if gamma <= 0.0 { return ratio; }
+	clamped(ratio, 0.0, 1.0).powf(1.0 / gamma)
+}
+
+
+impl Adjustment {
+	/// Returns the adjusted color.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::Color;
+	/// use palette::space::{Adjustment, Channel};
+	/// 
+	/// let c = Color::new(100, 100, 100);
+	/// assert_eq!(Adjustment::Brightness(0.2).apply(c), 
+	/// 	Color::new(151, 151, 151));
+	/// assert_eq!(Adjustment::Contrast(-1.0).apply(c), 
+	/// 	Color::new(128, 128, 128));
+	///
+	/// let c = Color::new(10, 20, 30);
+	/// let swap = Adjustment::SwapChannels(Channel::Red, Channel::Blue);
+	/// assert_eq!(swap.apply(c), Color::new(30, 20, 10));
+	/// ```
+	pub fn apply(self, color: Color) -> Color {
+		match self {
+			Adjustment::Brightness(amount) 
+				=> map_channels(color, |c| c + amount),
+
+			Adjustment::Contrast(amount) 
+				=> map_channels(color, |c| (c - 0.5) * (1.0 + amount) + 0.5),
+
+			Adjustment::HueRotate(degrees) => {
+				let hsl = Hsl::from(color);
+				Color::from(Hsl::new(hsl.h + degrees, hsl.s, hsl.l))
+			},
+
+			Adjustment::Saturate(amount) => {
+				let hsl = Hsl::from(color);
+				Color::from(Hsl::new(hsl.h, hsl.s * (1.0 + amount), hsl.l))
+			},
+
+			Adjustment::Gamma(gamma) 
+				=> map_channels(color, |c| gamma_ratio(c, gamma)),
+
+			Adjustment::Levels {black, white, gamma} => {
+				let (lo, hi) = (ratio(black), ratio(white));
+				if hi <= lo {
+					// A collapsed range thresholds each channel.
+					return map_channels(color, 
+						|c| if c >= lo { 1.0 } else { 0.0 });
+				}
+				map_channels(color, 
+					|c| gamma_ratio((c - lo) / (hi - lo), gamma))
+			},
+
+			Adjustment::SwapChannels(a, b) => {
+				let mut channels = [color.r(), color.g(), color.b()];
+				channels.swap(a.index(), b.index());
+				Color::new(channels[0], channels[1], channels[2])
+			},
+		}
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Metric
+////////////////////////////////////////////////////////////////////////////////
+/// A method of measuring the distance between colors.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Metric {
+	/// The euclidean distance between the RGB channels.
+	Rgb,
+	/// The CIE76 perceptual color difference.
+	DeltaE,
+}
+
+
+impl Metric {
+	/// Returns the distance between the given colors.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::Color;
+	/// use palette::space::Metric;
+	/// 
+	/// let a = Color::new(0, 0, 0);
+	/// let b = Color::new(3, 4, 0);
+	///
+	/// assert_eq!(Metric::Rgb.distance(a, b), 5.0);
+	/// ```
+	pub fn distance(self, a: Color, b: Color) -> f32 {
+		match self {
+			Metric::Rgb => {
+				let dr = a.r() as f32 - b.r() as f32;
+				let dg = a.g() as f32 - b.g() as f32;
+				let db = a.b() as f32 - b.b() as f32;
+				(dr * dr + dg * dg + db * db).sqrt()
+			},
+			Metric::DeltaE => delta_e(a, b),
+		}
+	}
+}
+
+
+impl Default for Metric {
+	fn default() -> Self {
+		Metric::Rgb
+	}
+}
@@ -0,0 +1,96 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides the parts of the standard library used by the crate's core 
+//! modules, assembled from `core` and `alloc`. This module stands in for `std`
+//! when the `std` feature is disabled, so that the core modules can refer to
+//! `std` paths regardless of how the crate is built.
+//!
+//! Hash maps and sets are unavailable without `std`, so ordered maps and sets
+//! are used in their place. The aliases are for internal use only; public 
+//! interfaces use ordered collections directly, so that their signatures do 
+//! not depend on the `std` feature. Floating point functions are provided by
+//! the `Float` trait, implemented using `libm`.
+//!
+////////////////////////////////////////////////////////////////////////////////
+pub use core::*;
+pub use alloc::{borrow, boxed, fmt, rc, slice, str, string, vec};
+
+
+/// Ordered collections, standing in for the standard collections.
+pub mod collections {
+	pub use alloc::collections::*;
+	pub use alloc::collections::BTreeMap as HashMap;
+	pub use alloc::collections::BTreeSet as HashSet;
+}
+
+
+/// The items of the standard prelude which are not available from `core`.
+pub mod prelude {
+	pub use alloc::borrow::ToOwned;
+	pub use alloc::boxed::Box;
+	pub use alloc::string::{String, ToString};
+	pub use alloc::vec::Vec;
+	pub use super::Float;
+}
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Float
+////////////////////////////////////////////////////////////////////////////////
+/// Provides the floating point functions which `core` lacks.
+pub trait Float {
+	/// Returns the absolute value of the number.
+	fn abs(self) -> Self;
+	/// Returns the largest integer less than or equal to the number.
+	fn floor(self) -> Self;
+	/// Returns the nearest integer to the number, rounding half-way cases 
+	/// away from zero.
+	fn round(self) -> Self;
+	/// Raises the number to a floating point power.
+	fn powf(self, n: Self) -> Self;
+	/// Returns the square root of the number.
+	fn sqrt(self) -> Self;
+	/// Returns the cube root of the number.
+	fn cbrt(self) -> Self;
+	/// Returns the sine of the number, in radians.
+	fn sin(self) -> Self;
+	/// Returns the cosine of the number, in radians.
+	fn cos(self) -> Self;
+	/// Returns the four quadrant arctangent of the number and `other`, in 
+	/// radians.
+	fn atan2(self, other: Self) -> Self;
+}
+
+impl Float for f32 {
+	fn abs(self) -> f32 { ::libm::fabsf(self) }
+	fn floor(self) -> f32 { ::libm::floorf(self) }
+	fn round(self) -> f32 { ::libm::roundf(self) }
+	fn powf(self, n: f32) -> f32 { ::libm::powf(self, n) }
+	fn sqrt(self) -> f32 { ::libm::sqrtf(self) }
+	fn cbrt(self) -> f32 { ::libm::cbrtf(self) }
+	fn sin(self) -> f32 { ::libm::sinf(self) }
+	fn cos(self) -> f32 { ::libm::cosf(self) }
+	fn atan2(self, other: f32) -> f32 { ::libm::atan2f(self, other) }
+}
@@ -0,0 +1,385 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides the storage strategies used to hold the cells of palette data.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{Address, Line, Column, PAGE_MAX, LINE_MAX, COLUMN_MAX};
+use cell::{Cell, Shared};
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+use std::collections::BTreeMap;
+use std::collections::btree_map;
+use std::fmt;
+use std::iter::Peekable;
+use std::ops::Index;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Storage
+////////////////////////////////////////////////////////////////////////////////
+/// The strategy used to store the cells of palette data.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::data::DataBuilder;
+/// use palette::storage::Storage;
+/// use palette::{Address, Color};
+/// 
+/// let mut dat = DataBuilder::new()
+/// 	.line_count(16)
+/// 	.column_count(16)
+/// 	.storage(Storage::Dense)
+/// 	.build();
+/// dat.add_color(Color::new(12, 50, 78)).unwrap();
+///
+/// assert_eq!(dat.storage(), Storage::Dense);
+/// assert_eq!(dat.cell(Address::new(0, 0, 0)).unwrap().color(), 
+/// 	Some(Color::new(12, 50, 78)));
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Storage {
+	/// Cells are stored in an ordered map keyed by address. This uses memory
+	/// in proportion to the number of cells, and suits sparsely populated 
+	/// palettes.
+	Sparse,
+	/// Cells are stored in a vector indexed by address, using the default line
+	/// and column counts of the palette. This uses memory in proportion to the
+	/// highest occupied address, and suits palettes which are mostly full. 
+	/// Cells outside of the default line and column counts are stored as in 
+	/// `Sparse` storage.
+	Dense,
+}
+
+
+impl Default for Storage {
+	fn default() -> Self {
+		Storage::Sparse
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Cells
+////////////////////////////////////////////////////////////////////////////////
+/// A map assigning addresses to `Palette` cells, using a selectable storage 
+/// strategy. Clones of the map share its cells.
+#[derive(Clone)]
+pub(crate) struct Cells {
+	/// The storage strategy used for new cells.
+	storage: Storage,
+	/// The cells which are not held in dense storage.
+	sparse: BTreeMap<Address, Shared<Cell>>,
+	/// The densely stored cells, indexed by their position.
+	dense: Vec<Option<Shared<Cell>>>,
+	/// The number of lines in each page of dense storage.
+	lines: usize,
+	/// The number of columns in each line of dense storage.
+	columns: usize,
+	/// The number of densely stored cells.
+	dense_len: usize,
+}
+
+
+impl Cells {
+	/// Creates a new, empty `Cells` using the given storage strategy and
+	/// dense page geometry.
+	pub(crate) fn new(storage: Storage, lines: Line, columns: Column) -> Self {
+		Cells {
+			storage: storage,
+			sparse: BTreeMap::new(),
+			dense: Vec::new(),
+			lines: lines as usize,
+			columns: columns as usize,
+			dense_len: 0,
+		}
+	}
+
+	/// Creates a new, empty `Cells` with the same configuration as this one.
+	pub(crate) fn empty_like(&self) -> Self {
+		Cells {
+			storage: self.storage,
+			sparse: BTreeMap::new(),
+			dense: Vec::new(),
+			lines: self.lines,
+			columns: self.columns,
+			dense_len: 0,
+		}
+	}
+
+	/// Returns the storage strategy of the cells.
+	pub(crate) fn storage(&self) -> Storage {
+		self.storage
+	}
+
+	/// Returns the number of cells.
+	pub(crate) fn len(&self) -> usize {
+		self.sparse.len() + self.dense_len
+	}
+
+	/// Returns whether there are no cells.
+	pub(crate) fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Returns the dense position of the given address, or None if the 
+	/// address is not held in dense storage.
+	fn position(&self, address: &Address) -> Option<usize> {
+		let (line, column) = (address.line as usize, address.column as usize);
+		if self.storage != Storage::Dense 
+			|| line >= self.lines 
+			|| column >= self.columns 
+		{
+			return None;
+		}
+		Some((address.page as usize * self.lines + line) 
+			* self.columns + column)
+	}
+
+	/// Returns the address of the given dense position.
+	fn address_of(&self, position: usize) -> Address {
+		let column = position % self.columns;
+		let line = (position / self.columns) % self.lines;
+		let page = position / self.columns / self.lines;
+		Address::new(page as _, line as _, column as _)
+	}
+
+	/// Returns the first dense position whose address is not before the given
+	/// address.
+	fn first_position_from(&self, address: &Address) -> usize {
+		if self.lines == 0 || self.columns == 0 { return self.dense.len(); }
+		let (page, mut line, mut column) = (
+			address.page as usize,
+			address.line as usize,
+			address.column as usize);
+		if column >= self.columns { line += 1; column = 0; }
+		if line >= self.lines { 
+			return (page + 1) * self.lines * self.columns;
+		}
+		(page * self.lines + line) * self.columns + column
+	}
+
+	/// Returns a reference to the cell at the given address.
+	pub(crate) fn get(&self, address: &Address) -> Option<&Shared<Cell>> {
+		match self.position(address) {
+			Some(position) => self.dense.get(position)
+				.and_then(|cell| cell.as_ref()),
+			None => self.sparse.get(address),
+		}
+	}
+
+	/// Returns whether there is a cell at the given address.
+	pub(crate) fn contains_key(&self, address: &Address) -> bool {
+		self.get(address).is_some()
+	}
+
+	/// Inserts a cell at the given address, returning the cell it replaces.
+	pub(crate) fn insert(&mut self, address: Address, cell: Shared<Cell>)
+		-> Option<Shared<Cell>>
+	{
+		match self.position(&address) {
+			Some(position) => {
+				if position >= self.dense.len() {
+					self.dense.resize(position + 1, None);
+				}
+				let old = self.dense[position].replace(cell);
+				if old.is_none() { self.dense_len += 1; }
+				old
+			},
+			None => self.sparse.insert(address, cell),
+		}
+	}
+
+	/// Removes the cell at the given address, returning it.
+	pub(crate) fn remove(&mut self, address: &Address) 
+		-> Option<Shared<Cell>>
+	{
+		match self.position(address) {
+			Some(position) => {
+				let old = self.dense.get_mut(position)
+					.and_then(|cell| cell.take());
+				if old.is_some() { 
+					self.dense_len -= 1;
+					// Release the unused tail of the dense storage.
+					while self.dense.last().map_or(false, |c| c.is_none()) {
+						self.dense.pop();
+					}
+				}
+				old
+			},
+			None => self.sparse.remove(address),
+		}
+	}
+
+	/// Returns the highest occupied address.
+	pub(crate) fn last_address(&self) -> Option<Address> {
+		let dense = match self.dense.len() {
+			0 => None,
+			len => Some(self.address_of(len - 1)),
+		};
+		let sparse = self.sparse.keys().next_back().cloned();
+		dense.max(sparse)
+	}
+
+	/// Returns an iterator over the addresses and cells, in address order.
+	pub(crate) fn iter(&self) -> Iter {
+		self.range(
+			Address::new(0, 0, 0), 
+			Address::new(PAGE_MAX, LINE_MAX, COLUMN_MAX))
+	}
+
+	/// Returns an iterator over the addresses and cells between the given 
+	/// addresses, inclusive, in address order.
+	pub(crate) fn range(&self, start: Address, end: Address) -> Iter {
+		Iter {
+			dense: DenseIter {
+				cells: self,
+				position: self.first_position_from(&start),
+				end: end,
+			}.peekable(),
+			sparse: self.sparse.range(start..=end).peekable(),
+		}
+	}
+
+	/// Returns an iterator over the occupied addresses, in address order.
+	pub(crate) fn keys<'a>(&'a self) -> impl Iterator<Item=Address> + 'a {
+		self.iter().map(|(address, _)| address)
+	}
+
+	/// Returns an iterator over the cells, in address order.
+	pub(crate) fn values<'a>(&'a self) 
+		-> impl Iterator<Item=&'a Shared<Cell>> + 'a
+	{
+		self.iter().map(|(_, cell)| cell)
+	}
+
+	/// Removes all cells, returning them with their addresses in address 
+	/// order.
+	pub(crate) fn drain(&mut self) -> Vec<(Address, Shared<Cell>)> {
+		let drained = self.iter()
+			.map(|(address, cell)| (address, cell.clone()))
+			.collect();
+		self.sparse.clear();
+		self.dense.clear();
+		self.dense_len = 0;
+		drained
+	}
+}
+
+
+impl<'a> Index<&'a Address> for Cells {
+	type Output = Shared<Cell>;
+
+	fn index(&self, address: &'a Address) -> &Shared<Cell> {
+		self.get(address).expect("no cell at address")
+	}
+}
+
+
+impl<'a> IntoIterator for &'a Cells {
+	type Item = (Address, &'a Shared<Cell>);
+	type IntoIter = Iter<'a>;
+
+	fn into_iter(self) -> Iter<'a> {
+		self.iter()
+	}
+}
+
+
+impl fmt::Debug for Cells {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_map().entries(self.iter()).finish()
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Iter
+////////////////////////////////////////////////////////////////////////////////
+/// An iterator over the addresses and cells of a `Cells`, in address order.
+pub(crate) struct Iter<'a> {
+	/// The remaining densely stored cells.
+	dense: Peekable<DenseIter<'a>>,
+	/// The remaining sparsely stored cells.
+	sparse: Peekable<btree_map::Range<'a, Address, Shared<Cell>>>,
+}
+
+
+impl<'a> Iterator for Iter<'a> {
+	type Item = (Address, &'a Shared<Cell>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let dense_first = match (self.dense.peek(), self.sparse.peek()) {
+			(Some(&(a, _)), Some(&(b, _))) => a < *b,
+			(Some(_), None) => true,
+			(None, _) => false,
+		};
+		if dense_first {
+			self.dense.next()
+		} else {
+			self.sparse.next().map(|(&address, cell)| (address, cell))
+		}
+	}
+}
+
+
+/// An iterator over the densely stored cells of a `Cells`, in address order.
+struct DenseIter<'a> {
+	/// The cells being iterated over.
+	cells: &'a Cells,
+	/// The next dense position to check.
+	position: usize,
+	/// The last address to yield.
+	end: Address,
+}
+
+
+impl<'a> Iterator for DenseIter<'a> {
+	type Item = (Address, &'a Shared<Cell>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		while self.position < self.cells.dense.len() {
+			let position = self.position;
+			self.position += 1;
+			if let Some(ref cell) = self.cells.dense[position] {
+				let address = self.cells.address_of(position);
+				if address > self.end {
+					self.position = self.cells.dense.len();
+					return None;
+				}
+				return Some((address, cell));
+			}
+		}
+		None
+	}
+}
@@ -0,0 +1,273 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides templates which preconfigure palette data for particular hardware
+//! and file formats, setting up their page, line, and column geometry, their
+//! reserved slots, and their fixed system colors.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{Address, Reference, Page, Line, Column};
+use data::{Constraint, Data};
+use expression::Expression;
+use format::zpl;
+use hardware::GAMEBOY_COLORS;
+use meta::MetaType;
+
+// Non-local imports.
+use color::Color;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Template
+////////////////////////////////////////////////////////////////////////////////
+/// A function which initializes a newly created page or line of palette data.
+pub type PrepareFn = fn(&mut Data, &Reference);
+
+
+/// A preset configuration for palette data.
+#[derive(Debug, Clone)]
+pub struct Template {
+	/// The format label given to the palette.
+	label: Option<&'static str>,
+	/// The maximum number of pages.
+	maximum_page_count: Page,
+	/// The default number of lines in each page.
+	default_line_count: Line,
+	/// The default number of columns in each line.
+	default_column_count: Column,
+	/// The slots which may not be used.
+	reserved: Vec<Address>,
+	/// The fixed colors of the palette and their names.
+	fixed: Vec<(Address, Color, &'static str)>,
+	/// The metadata keys registered for the palette.
+	meta_keys: Vec<(&'static str, MetaType)>,
+	/// Called when a new page is created.
+	prepare_new_page: Option<PrepareFn>,
+	/// Called when a new line is created.
+	prepare_new_line: Option<PrepareFn>,
+}
+
+
+impl Template {
+	/// Creates a new Template with the given geometry.
+	pub fn new(
+		maximum_page_count: Page, 
+		default_line_count: Line, 
+		default_column_count: Column) 
+		-> Template
+	{
+		Template {
+			label: None,
+			maximum_page_count: maximum_page_count,
+			default_line_count: default_line_count,
+			default_column_count: default_column_count,
+			reserved: Vec::new(),
+			fixed: Vec::new(),
+			meta_keys: Vec::new(),
+			prepare_new_page: None,
+			prepare_new_line: None,
+		}
+	}
+
+	/// Sets the format label given to the palette.
+	pub fn label(mut self, label: &'static str) -> Template {
+		self.label = Some(label);
+		self
+	}
+
+	/// Reserves the given slot so that it will not be used.
+	pub fn reserve(mut self, address: Address) -> Template {
+		self.reserved.push(address);
+		self
+	}
+
+	/// Places the given fixed color at the given slot, locking it so that it 
+	/// may not be changed.
+	pub fn fixed(mut self, address: Address, color: Color, name: &'static str) 
+		-> Template 
+	{
+		self.fixed.push((address, color, name));
+		self
+	}
+
+	/// Registers the given typed metadata key for the palette.
+	pub fn meta_key(mut self, key: &'static str, meta_type: MetaType) 
+		-> Template 
+	{
+		self.meta_keys.push((key, meta_type));
+		self
+	}
+
+	/// Sets the function to call when a new page is created.
+	pub fn prepare_new_page(mut self, prepare: PrepareFn) -> Template {
+		self.prepare_new_page = Some(prepare);
+		self
+	}
+
+	/// Sets the function to call when a new line is created.
+	pub fn prepare_new_line(mut self, prepare: PrepareFn) -> Template {
+		self.prepare_new_line = Some(prepare);
+		self
+	}
+
+	/// Applies the template to the given palette data, configuring its 
+	/// geometry and placing its reserved slots and fixed colors.
+	///
+	/// # Panics
+	///
+	/// Panics if a reserved slot or fixed color lies outside of the template's
+	/// geometry or is already occupied, or if a metadata key is already 
+	/// registered with another type.
+	pub fn initialize(&self, data: &mut Data) {
+		if let Some(label) = self.label {
+			data.set_label(Reference::all(), label);
+		}
+		data.maximum_page_count = self.maximum_page_count;
+		data.default_line_count = self.default_line_count;
+		data.default_column_count = self.default_column_count;
+		data.prepare_new_page = self.prepare_new_page
+			.map(|prepare| Box::new(prepare) as Box<_>);
+		data.prepare_new_line = self.prepare_new_line
+			.map(|prepare| Box::new(prepare) as Box<_>);
+
+		for &(key, meta_type) in &self.meta_keys {
+			data.register_meta(key, meta_type)
+				.expect("register template metadata key");
+		}
+		for &(address, color, name) in &self.fixed {
+			*data.create_cell(address)
+				.expect("create template fixed color")
+				.borrow_mut() = Expression::Color(color);
+			data.set_slot_name(address, name);
+			data.set_constraint(address, Some(Constraint::Locked));
+		}
+		for &address in &self.reserved {
+			data.set_constraint(address, Some(Constraint::Reserved));
+		}
+	}
+
+	/// Returns new palette data configured by the template.
+	pub fn data(&self) -> Data {
+		let mut data = Data::default();
+		self.initialize(&mut data);
+		data
+	}
+
+	/// Returns a template for the NES picture processing unit's palette RAM:
+	/// four background and four sprite palettes of four colors each. The 
+	/// first color of each palette other than the first is a mirror of the 
+	/// shared backdrop color, and so is reserved.
+	pub fn nes() -> Template {
+		(1..8).fold(
+			Template::new(1, 8, 4).label("NES"),
+			|template, line| template.reserve(Address::new(0, line, 0)))
+	}
+
+	/// Returns a template for the original Game Boy: a line of the four fixed
+	/// shades of the display, followed by lines for the background and two 
+	/// sprite palettes which select among them.
+	pub fn gameboy() -> Template {
+		GAMEBOY_COLORS.iter().enumerate().fold(
+			Template::new(1, 4, 4).label("Game Boy"),
+			|template, (column, &(name, r, g, b))| template.fixed(
+				Address::new(0, 0, column as u8), 
+				Color::new(r, g, b), 
+				name))
+	}
+
+	/// Returns a template for the SNES color generator RAM: sixteen palettes 
+	/// of sixteen colors each. The first color of each palette other than the
+	/// first is transparent, and so is reserved.
+	pub fn snes_cgram() -> Template {
+		(1..16).fold(
+			Template::new(1, 16, 16).label("SNES CGRAM"),
+			|template, line| template.reserve(Address::new(0, line, 0)))
+	}
+
+	/// Returns a template for Zelda Classic ZPL palettes: a main page of 
+	/// fourteen csets, followed by 512 level pages of thirteen csets and 
+	/// sprite pages of sixteen csets.
+	pub fn zelda_classic() -> Template {
+		Template::new(
+				zpl::ZPL_PAGE_LIMIT, 
+				zpl::ZPL_DEFAULT_LINE_LIMIT, 
+				zpl::ZPL_DEFAULT_COLUMN_LIMIT)
+			.label("ZplPalette 1.0.0")
+			.meta_key(zpl::ZPL_LEVEL_NAME_KEY, MetaType::String)
+			.prepare_new_page(zpl::prepare_new_page)
+			.prepare_new_line(zpl::prepare_new_line)
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Data constructors
+////////////////////////////////////////////////////////////////////////////////
+impl Data {
+	/// Returns new palette data configured for the NES. See 
+	/// `Template::nes`.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::data::{Constraint, Data};
+	/// use palette::{Address, Color};
+	///
+	/// let mut dat = Data::nes();
+	/// for _ in 0..4 {
+	/// 	dat.add_color(Color::new(0, 0, 0)).unwrap();
+	/// }
+	/// let next = dat.add_color(Color::new(255, 255, 255)).unwrap();
+	///
+	/// assert_eq!(dat.capacity(), 32);
+	/// assert_eq!(dat.constraint(Address::new(0, 1, 0)), 
+	/// 	Some(Constraint::Reserved));
+	/// assert_eq!(next, Address::new(0, 1, 1));
+	/// ```
+	pub fn nes() -> Data {
+		Template::nes().data()
+	}
+
+	/// Returns new palette data configured for the original Game Boy. See 
+	/// `Template::gameboy`.
+	pub fn gameboy() -> Data {
+		Template::gameboy().data()
+	}
+
+	/// Returns new palette data configured for the SNES. See 
+	/// `Template::snes_cgram`.
+	pub fn snes_cgram() -> Data {
+		Template::snes_cgram().data()
+	}
+
+	/// Returns new palette data configured for Zelda Classic. See 
+	/// `Template::zelda_classic`.
+	pub fn zelda_classic() -> Data {
+		Template::zelda_classic().data()
+	}
+}
@@ -25,7 +25,12 @@
 //! Defines general purpose functions for palette use.
 //!
 ////////////////////////////////////////////////////////////////////////////////
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
 use std::f32;
+use std::hash::Hasher;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+use std::time::{SystemTime, UNIX_EPOCH};
 
 
 /// Returns true if the given float values are nearly equal, taking into account
@@ -144,3 +149,159 @@ pub fn lerp_f32(start: f32, end:f32, amount: f32) -> f32 {
 	let e = if start > end {start} else {end};
 	(((e-s) as f32) * a) as f32 + s
 }
+
+
+/// Returns true if the given text matches the given wildcard pattern. A `*` in
+/// the pattern matches any sequence of characters, and a `?` matches any 
+/// single character. Matching is case-insensitive.
+///
+/// # Examples
+///
+/// ```rust
+/// # use palette::utilities::wildcard_match;
+/// assert!(wildcard_match("skin*", "Skin Shadow"));
+/// assert!(wildcard_match("*sh?dow", "Skin Shadow"));
+/// assert!(!wildcard_match("skin", "Skin Shadow"));
+/// ```
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+	let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+	let text: Vec<char> = text.to_lowercase().chars().collect();
+
+	// Greedy matching, backtracking to the most recent `*` on a mismatch.
+	let (mut p, mut t) = (0, 0);
+	let mut star: Option<(usize, usize)> = None;
+	while t < text.len() {
+		if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+			p += 1;
+			t += 1;
+		} else if p < pattern.len() && pattern[p] == '*' {
+			star = Some((p, t));
+			p += 1;
+		} else if let Some((sp, st)) = star {
+			p = sp + 1;
+			t = st + 1;
+			star = Some((sp, st + 1));
+		} else {
+			return false;
+		}
+	}
+	pattern[p..].iter().all(|&c| c == '*')
+}
+
+
+/// A 64-bit FNV-1a hasher. Unlike the standard library's default hasher, its
+/// output is specified, and so can be used for content hashes which are 
+/// stable between program runs.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Fnv64(u64);
+
+impl Default for Fnv64 {
+	fn default() -> Self {
+		Fnv64(0xcbf2_9ce4_8422_2325)
+	}
+}
+
+impl Hasher for Fnv64 {
+	fn finish(&self) -> u64 {
+		self.0
+	}
+
+	fn write(&mut self, bytes: &[u8]) {
+		for &byte in bytes {
+			self.0 ^= u64::from(byte);
+			self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+		}
+	}
+}
+
+
+/// Applies the given function to each of the given items, collecting the 
+/// results in order. The items are processed in parallel if the `parallel` 
+/// feature is enabled.
+#[cfg(feature = "parallel")]
+pub(crate) fn map_items<T, U, F>(items: &[T], f: F) -> Vec<U>
+	where
+		T: Sync,
+		U: Send,
+		F: Fn(&T) -> U + Sync + Send,
+{
+	use rayon::prelude::*;
+	items.par_iter().map(f).collect()
+}
+
+/// Applies the given function to each of the given items, collecting the 
+/// results in order. The items are processed in parallel if the `parallel` 
+/// feature is enabled.
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn map_items<T, U, F>(items: &[T], f: F) -> Vec<U>
+	where
+		T: Sync,
+		U: Send,
+		F: Fn(&T) -> U + Sync + Send,
+{
+	items.iter().map(f).collect()
+}
+
+/// Applies the given function to each unordered pair of the given items, 
+/// collecting the results which are not None. The results are ordered by the 
+/// index of the first item, then the second. The pairs are processed in 
+/// parallel if the `parallel` feature is enabled.
+#[cfg(feature = "parallel")]
+pub(crate) fn filter_pairs<T, U, F>(items: &[T], f: F) -> Vec<U>
+	where
+		T: Sync,
+		U: Send,
+		F: Fn(&T, &T) -> Option<U> + Sync + Send,
+{
+	use rayon::prelude::*;
+	(0..items.len())
+		.into_par_iter()
+		.flat_map(|i| items[(i + 1)..].iter()
+			.filter_map(|b| f(&items[i], b))
+			.collect::<Vec<_>>())
+		.collect()
+}
+
+/// Applies the given function to each unordered pair of the given items, 
+/// collecting the results which are not None. The results are ordered by the 
+/// index of the first item, then the second. The pairs are processed in 
+/// parallel if the `parallel` feature is enabled.
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn filter_pairs<T, U, F>(items: &[T], f: F) -> Vec<U>
+	where
+		T: Sync,
+		U: Send,
+		F: Fn(&T, &T) -> Option<U> + Sync + Send,
+{
+	(0..items.len())
+		.flat_map(|i| items[(i + 1)..].iter()
+			.filter_map(|b| f(&items[i], b))
+			.collect::<Vec<_>>())
+		.collect()
+}
+
+
+/// Returns the current time in seconds since the Unix epoch.
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub(crate) fn unix_time() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|duration| duration.as_secs())
+		.unwrap_or(0)
+}
+
+/// Returns the current time in seconds since the Unix epoch, using the 
+/// browser clock, as the system clock is unavailable on `wasm32`.
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub(crate) fn unix_time() -> u64 {
+	(::js_sys::Date::now() / 1000.0) as u64
+}
+
+/// Returns 0, as there is no clock available without the `std` feature, or on
+/// `wasm32` without the `wasm` feature.
+#[cfg(any(
+	not(feature = "std"),
+	all(target_arch = "wasm32", not(feature = "wasm"))))]
+pub(crate) fn unix_time() -> u64 {
+	0
+}
@@ -0,0 +1,305 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides borrowed views of a single page or line of palette data.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::{Address, Reference, Page, Line, Column};
+use cell::Cell;
+use data::{Constraint, Data};
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// PageView
+////////////////////////////////////////////////////////////////////////////////
+/// A read-only view of a single page of palette data. Cells are addressed by
+/// line and column relative to the page.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::data::Data;
+/// use palette::{Address, Color};
+/// 
+/// let mut dat: Data = Default::default();
+/// dat.add_color(Color::new(12, 50, 78)).unwrap();
+/// dat.add_color(Color::new(60, 25, 17)).unwrap();
+///
+/// let page = dat.page_view(0);
+/// assert_eq!(page.len(), 2);
+/// assert_eq!(page.color(0, 1), Some(Color::new(60, 25, 17)));
+/// assert_eq!(page.line(0).color(0), Some(Color::new(12, 50, 78)));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct PageView<'a> {
+	/// The viewed palette data.
+	data: &'a Data,
+	/// The viewed page.
+	page: Page,
+}
+
+impl<'a> PageView<'a> {
+	/// Constructs a new `PageView` of the given page.
+	pub fn new(data: &'a Data, page: Page) -> Self {
+		PageView {data: data, page: page}
+	}
+
+	/// Returns the underlying palette data.
+	pub fn data(&self) -> &'a Data {
+		self.data
+	}
+
+	/// Returns the viewed page.
+	pub fn page(&self) -> Page {
+		self.page
+	}
+
+	/// Returns the full address of the given line and column of the page.
+	pub fn address(&self, line: Line, column: Column) -> Address {
+		Address::new(self.page, line, column)
+	}
+
+	/// Returns a `Reference` to the viewed page.
+	pub fn reference(&self) -> Reference {
+		Reference::page_of(&self.address(0, 0))
+	}
+
+	/// Returns a view of the given line of the page.
+	pub fn line(&self, line: Line) -> LineView<'a> {
+		LineView::new(self.data, self.page, line)
+	}
+
+	/// Returns the number of occupied cells on the page.
+	pub fn len(&self) -> usize {
+		self.data.page_len(self.page)
+	}
+
+	/// Returns whether there are any cells on the page.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Returns the `Cell` at the given line and column, or None if the slot is
+	/// empty.
+	pub fn cell(&self, line: Line, column: Column) -> Option<&'a Cell> {
+		self.data.cells.get(&self.address(line, column)).map(|cell| &**cell)
+	}
+
+	/// Returns the resolved `Color` at the given line and column.
+	pub fn color(&self, line: Line, column: Column) -> Option<Color> {
+		self.cell(line, column).and_then(|cell| self.data.color_of(cell))
+	}
+
+	/// Returns the constraint on the slot at the given line and column.
+	pub fn constraint(&self, line: Line, column: Column) -> Option<Constraint> {
+		self.data.constraint(self.address(line, column))
+	}
+
+	/// Returns the name of the slot at the given line and column.
+	pub fn slot_name(&self, line: Line, column: Column) -> Option<&'a str> {
+		self.data.slot_name(self.address(line, column))
+	}
+
+	/// Returns an iterator over the occupied lines and columns of the page and
+	/// their `Cell`s, in address order.
+	pub fn iter(&self) -> impl Iterator<Item=(Line, Column, &'a Cell)> + 'a {
+		self.data.iter_page(self.page)
+			.map(|(address, cell)| (address.line, address.column, cell))
+	}
+
+	/// Returns an iterator over the occupied lines and columns of the page and
+	/// their resolved `Color`s, in address order. Cells which do not resolve 
+	/// to a color are skipped.
+	pub fn colors(&self) -> impl Iterator<Item=(Line, Column, Color)> + 'a {
+		let data = self.data;
+		self.iter()
+			.filter_map(move |(l, c, cell)| data.color_of(cell)
+				.map(|color| (l, c, color)))
+	}
+
+	/// Returns the name of the page.
+	pub fn name(&self) -> Option<&'a str> {
+		self.data.name(&self.reference())
+	}
+
+	/// Returns the label of the page.
+	pub fn label(&self) -> Option<&'a str> {
+		self.data.label(&self.reference())
+	}
+
+	/// Returns the tags of the page.
+	pub fn tags(&self) -> &'a [String] {
+		self.data.tags(&self.reference())
+	}
+
+	/// Returns the line count of the page.
+	pub fn line_count(&self) -> Line {
+		self.data.line_count(&self.reference())
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// LineView
+////////////////////////////////////////////////////////////////////////////////
+/// A read-only view of a single line of palette data. Cells are addressed by
+/// column relative to the line.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::data::Data;
+/// use palette::{Address, Color};
+/// 
+/// let mut dat: Data = Default::default();
+/// dat.add_color(Color::new(12, 50, 78)).unwrap();
+/// dat.add_color(Color::new(60, 25, 17)).unwrap();
+///
+/// let line = dat.line_view(0, 0);
+/// let colors: Vec<_> = line.colors().collect();
+/// assert_eq!(colors, vec![
+/// 	(0, Color::new(12, 50, 78)),
+/// 	(1, Color::new(60, 25, 17)),
+/// ]);
+/// assert_eq!(line.address(1), Address::new(0, 0, 1));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct LineView<'a> {
+	/// The viewed palette data.
+	data: &'a Data,
+	/// The page of the viewed line.
+	page: Page,
+	/// The viewed line.
+	line: Line,
+}
+
+impl<'a> LineView<'a> {
+	/// Constructs a new `LineView` of the given line.
+	pub fn new(data: &'a Data, page: Page, line: Line) -> Self {
+		LineView {data: data, page: page, line: line}
+	}
+
+	/// Returns the underlying palette data.
+	pub fn data(&self) -> &'a Data {
+		self.data
+	}
+
+	/// Returns the page of the viewed line.
+	pub fn page(&self) -> Page {
+		self.page
+	}
+
+	/// Returns the viewed line.
+	pub fn line(&self) -> Line {
+		self.line
+	}
+
+	/// Returns the full address of the given column of the line.
+	pub fn address(&self, column: Column) -> Address {
+		Address::new(self.page, self.line, column)
+	}
+
+	/// Returns a `Reference` to the viewed line.
+	pub fn reference(&self) -> Reference {
+		Reference::line_of(&self.address(0))
+	}
+
+	/// Returns the number of occupied cells on the line.
+	pub fn len(&self) -> usize {
+		self.data.line_len(self.page, self.line)
+	}
+
+	/// Returns whether there are any cells on the line.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Returns the `Cell` at the given column, or None if the slot is empty.
+	pub fn cell(&self, column: Column) -> Option<&'a Cell> {
+		self.data.cells.get(&self.address(column)).map(|cell| &**cell)
+	}
+
+	/// Returns the resolved `Color` at the given column.
+	pub fn color(&self, column: Column) -> Option<Color> {
+		self.cell(column).and_then(|cell| self.data.color_of(cell))
+	}
+
+	/// Returns the constraint on the slot at the given column.
+	pub fn constraint(&self, column: Column) -> Option<Constraint> {
+		self.data.constraint(self.address(column))
+	}
+
+	/// Returns the name of the slot at the given column.
+	pub fn slot_name(&self, column: Column) -> Option<&'a str> {
+		self.data.slot_name(self.address(column))
+	}
+
+	/// Returns an iterator over the occupied columns of the line and their 
+	/// `Cell`s, in address order.
+	pub fn iter(&self) -> impl Iterator<Item=(Column, &'a Cell)> + 'a {
+		self.data.iter_line(self.page, self.line)
+			.map(|(address, cell)| (address.column, cell))
+	}
+
+	/// Returns an iterator over the occupied columns of the line and their 
+	/// resolved `Color`s, in address order. Cells which do not resolve to a
+	/// color are skipped.
+	pub fn colors(&self) -> impl Iterator<Item=(Column, Color)> + 'a {
+		let data = self.data;
+		self.iter()
+			.filter_map(move |(c, cell)| data.color_of(cell)
+				.map(|color| (c, color)))
+	}
+
+	/// Returns the name of the line.
+	pub fn name(&self) -> Option<&'a str> {
+		self.data.name(&self.reference())
+	}
+
+	/// Returns the label of the line.
+	pub fn label(&self) -> Option<&'a str> {
+		self.data.label(&self.reference())
+	}
+
+	/// Returns the tags of the line.
+	pub fn tags(&self) -> &'a [String] {
+		self.data.tags(&self.reference())
+	}
+
+	/// Returns the column count of the line.
+	pub fn column_count(&self) -> Column {
+		self.data.column_count(&self.reference())
+	}
+}
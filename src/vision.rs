@@ -0,0 +1,294 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides simulation of color vision deficiencies.
+//!
+//! The dichromacy simulations use the full-severity matrices of Machado, 
+//! Oliveira and Fernandes (2009), applied in linear light. Achromatopsia is 
+//! simulated by reducing each color to its relative luminance.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use address::Address;
+use data::Data;
+use space::{linear_to_srgb, srgb_to_linear, Metric};
+use utilities::filter_pairs;
+
+// Non-local imports.
+use color::Color;
+
+// Standard imports.
+#[cfg(not(feature = "std"))]
+use std::prelude::*;
+use std::fmt;
+
+
+/// The default perceptual distance below which two colors are considered
+/// indistinguishable.
+pub const DEFAULT_THRESHOLD: f32 = 10.0;
+
+/// The protanopia simulation matrix.
+const PROTANOPIA: [[f32; 3]; 3] = [
+	[ 0.152_286,  1.052_583, -0.204_868],
+	[ 0.114_503,  0.786_281,  0.099_216],
+	[-0.003_882, -0.048_116,  1.051_998],
+];
+
+/// The deuteranopia simulation matrix.
+const DEUTERANOPIA: [[f32; 3]; 3] = [
+	[ 0.367_322,  0.860_646, -0.227_968],
+	[ 0.280_085,  0.672_501,  0.047_413],
+	[-0.011_820,  0.042_940,  0.968_881],
+];
+
+/// The tritanopia simulation matrix.
+const TRITANOPIA: [[f32; 3]; 3] = [
+	[ 1.255_528, -0.076_749, -0.178_779],
+	[-0.078_411,  0.930_809,  0.147_602],
+	[ 0.004_733,  0.691_367,  0.303_900],
+];
+
+/// The relative luminance coefficients of the linear RGB channels.
+const LUMINANCE: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Deficiency
+////////////////////////////////////////////////////////////////////////////////
+/// A color vision deficiency.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Deficiency {
+	/// Absence of the long wavelength (red) cones.
+	Protanopia,
+	/// Absence of the medium wavelength (green) cones.
+	Deuteranopia,
+	/// Absence of the short wavelength (blue) cones.
+	Tritanopia,
+	/// Absence of all color vision.
+	Achromatopsia,
+}
+
+
+impl Deficiency {
+	/// Returns all of the simulated deficiencies.
+	pub fn all() -> [Deficiency; 4] {
+		[
+			Deficiency::Protanopia,
+			Deficiency::Deuteranopia,
+			Deficiency::Tritanopia,
+			Deficiency::Achromatopsia,
+		]
+	}
+
+	/// Returns the given color as it would appear with the deficiency.
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use palette::Color;
+	/// use palette::vision::Deficiency;
+	/// 
+	/// let gray = Color::new(128, 128, 128);
+	/// assert_eq!(Deficiency::Achromatopsia.simulate(gray), gray);
+	///
+	/// let red = Deficiency::Achromatopsia.simulate(Color::new(255, 0, 0));
+	/// assert!(red.r() == red.g() && red.g() == red.b());
+	/// ```
+	pub fn simulate(self, color: Color) -> Color {
+		let linear = [
+			srgb_to_linear(color.r() as f32 / 255.0),
+			srgb_to_linear(color.g() as f32 / 255.0),
+			srgb_to_linear(color.b() as f32 / 255.0),
+		];
+		let dot = |row: &[f32; 3]| row.iter()
+			.zip(linear.iter())
+			.map(|(m, c)| m * c)
+			.sum::<f32>();
+		let channel = |c: f32| (linear_to_srgb(c.max(0.0).min(1.0)) * 255.0)
+			.round() as u8;
+
+		let (r, g, b) = match self {
+			Deficiency::Protanopia => (
+				dot(&PROTANOPIA[0]), 
+				dot(&PROTANOPIA[1]), 
+				dot(&PROTANOPIA[2])),
+			Deficiency::Deuteranopia => (
+				dot(&DEUTERANOPIA[0]), 
+				dot(&DEUTERANOPIA[1]), 
+				dot(&DEUTERANOPIA[2])),
+			Deficiency::Tritanopia => (
+				dot(&TRITANOPIA[0]), 
+				dot(&TRITANOPIA[1]), 
+				dot(&TRITANOPIA[2])),
+			Deficiency::Achromatopsia => {
+				let y = dot(&LUMINANCE);
+				(y, y, y)
+			},
+		};
+		Color::new(channel(r), channel(g), channel(b))
+	}
+}
+
+
+impl fmt::Display for Deficiency {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", match *self {
+			Deficiency::Protanopia => "protanopia",
+			Deficiency::Deuteranopia => "deuteranopia",
+			Deficiency::Tritanopia => "tritanopia",
+			Deficiency::Achromatopsia => "achromatopsia",
+		})
+	}
+}
+
+
+/// Returns a copy of the given palette data with every color replaced by its
+/// appearance under the given deficiency. Derived colors are resolved and 
+/// stored as fixed colors, while names, metadata, cycles, and ramp specs are
+/// copied unchanged.
+pub fn simulate(data: &Data, deficiency: Deficiency) -> Data {
+	data.map_colors(|color| deficiency.simulate(color))
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// Collision
+////////////////////////////////////////////////////////////////////////////////
+/// A pair of colors which are distinguishable with normal vision, but not 
+/// under a color vision deficiency.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Collision {
+	/// The deficiency under which the colors collapse.
+	pub deficiency: Deficiency,
+	/// The address of the first color.
+	pub a: Address,
+	/// The address of the second color.
+	pub b: Address,
+	/// The perceptual distance between the simulated colors.
+	pub distance: f32,
+}
+
+
+impl fmt::Display for Collision {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{} and {} collapse under {} (distance {:.1})",
+			self.a,
+			self.b,
+			self.deficiency,
+			self.distance)
+	}
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// DistinguishabilityReport
+////////////////////////////////////////////////////////////////////////////////
+/// Lists the pairs of palette colors which become indistinguishable under 
+/// each color vision deficiency.
+///
+/// # Example
+///
+/// ```rust
+/// use palette::data::Data;
+/// use palette::vision::{Deficiency, DistinguishabilityReport};
+/// use palette::Color;
+/// 
+/// let mut dat: Data = Default::default();
+/// let red = dat.add_color(Color::new(160, 90, 60)).unwrap();
+/// let green = dat.add_color(Color::new(120, 120, 60)).unwrap();
+///
+/// let report = DistinguishabilityReport::new(&dat, 10.0);
+/// assert!(report.collisions_for(Deficiency::Deuteranopia)
+/// 	.any(|c| c.a == red && c.b == green));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DistinguishabilityReport {
+	/// The colliding color pairs.
+	collisions: Vec<Collision>,
+}
+
+
+impl DistinguishabilityReport {
+	/// Creates a report for the given palette data. Colors closer than the 
+	/// given CIE76 distance are considered indistinguishable.
+	pub fn new(data: &Data, threshold: f32) -> Self {
+		let colors: Vec<(Address, Color)> = data.colors().collect();
+		let mut collisions = Vec::new();
+		for &deficiency in &Deficiency::all() {
+			let simulated: Vec<(Address, Color, Color)> = colors.iter()
+				.map(|&(address, color)| 
+					(address, color, deficiency.simulate(color)))
+				.collect();
+			collisions.extend(filter_pairs(&simulated, |a, b| {
+				let before = Metric::DeltaE.distance(a.1, b.1);
+				let after = Metric::DeltaE.distance(a.2, b.2);
+				if before >= threshold && after < threshold {
+					Some(Collision {
+						deficiency: deficiency,
+						a: a.0,
+						b: b.0,
+						distance: after,
+					})
+				} else {
+					None
+				}
+			}));
+		}
+		DistinguishabilityReport {collisions: collisions}
+	}
+
+	/// Returns all of the colliding color pairs.
+	pub fn collisions(&self) -> &[Collision] {
+		&self.collisions[..]
+	}
+
+	/// Returns the colliding color pairs for the given deficiency.
+	pub fn collisions_for<'a>(&'a self, deficiency: Deficiency) 
+		-> impl Iterator<Item=&'a Collision> + 'a
+	{
+		self.collisions.iter().filter(move |c| c.deficiency == deficiency)
+	}
+
+	/// Returns true if no colors collapse under any deficiency.
+	pub fn is_empty(&self) -> bool {
+		self.collisions.is_empty()
+	}
+}
+
+
+impl fmt::Display for DistinguishabilityReport {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.collisions.is_empty() {
+			return writeln!(f, "all colors remain distinguishable");
+		}
+		for collision in &self.collisions {
+			writeln!(f, "{}", collision)?;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,246 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2017 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Provides `wasm-bindgen` bindings for palettes, allowing a palette editor 
+//! to run entirely in the browser.
+//!
+//! Palettes are read from and written to byte buffers, as there is no file 
+//! system. Formats are given by their registered names, such as `"GPL"`, or 
+//! detected from a file name and contents. Addresses are given as 
+//! `page:line:column` strings, and colors as hexadecimal strings.
+//!
+//! This module requires the `wasm` feature. Only an `rlib` is built by 
+//! default; build the WebAssembly module with `cargo rustc --lib --target 
+//! wasm32-unknown-unknown --features wasm --crate-type cdylib`.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+// Local imports.
+use ::Palette;
+use address::{Address, Reference};
+use format::Format;
+use format::registry;
+use format::svg::{self, SvgOptions};
+use hex::{from_hex, Hex};
+use operation::InsertColor;
+use render::Renderer;
+use result::Error;
+
+// Non-local imports.
+// The generated bindings refer to the crate by name.
+use wasm_bindgen;
+use wasm_bindgen::prelude::*;
+
+
+impl From<Error> for JsValue {
+	fn from(error: Error) -> JsValue {
+		JsValue::from_str(&error.to_string())
+	}
+}
+
+/// Returns the registered format with the given name.
+fn format_named(name: &str) -> Result<Format, JsValue> {
+	registry::from_name(name)
+		.ok_or_else(|| JsValue::from_str(
+			&format!("unknown palette format {:?}", name)))
+}
+
+/// Reads a palette from the given bytes in the given format, checking that 
+/// the format is readable.
+fn read_bytes(bytes: &[u8], format: Format) -> Result<Palette, JsValue> {
+	if !registry::registration(format).map_or(false, |r| r.readable) {
+		return Err(JsValue::from_str(
+			&format!("{} palettes can not be read", format.name())));
+	}
+	Ok(format.read_palette(&mut &bytes[..])?)
+}
+
+
+/// Returns the names of the registered formats.
+#[wasm_bindgen]
+pub fn formats() -> Box<[JsValue]> {
+	registry::REGISTRY.iter()
+		.map(|r| JsValue::from_str(r.format.name()))
+		.collect::<Vec<_>>()
+		.into_boxed_slice()
+}
+
+/// Returns the name of the format of a file with the given name and 
+/// contents, or None if it is not recognized.
+#[wasm_bindgen(js_name = detectFormat)]
+pub fn detect_format(file_name: &str, bytes: &[u8]) -> Option<String> {
+	registry::detect(file_name, bytes).map(|format| format.name().into())
+}
+
+/// Converts a palette file between the named formats.
+#[wasm_bindgen]
+pub fn convert(bytes: &[u8], from: &str, to: &str) 
+	-> Result<Vec<u8>, JsValue>
+{
+	let palette = read_bytes(bytes, format_named(from)?)?;
+	let mut out_buf = Vec::new();
+	format_named(to)?.write_palette(&palette, &mut out_buf)?;
+	Ok(out_buf)
+}
+
+
+
+////////////////////////////////////////////////////////////////////////////////
+// WasmPalette
+////////////////////////////////////////////////////////////////////////////////
+/// A palette, exposed to JavaScript as `Palette`.
+#[wasm_bindgen(js_name = Palette)]
+pub struct WasmPalette {
+	palette: Palette,
+}
+
+
+#[wasm_bindgen(js_class = Palette)]
+impl WasmPalette {
+	/// Creates a new empty palette with the given name.
+	#[wasm_bindgen(constructor)]
+	pub fn new(name: &str) -> WasmPalette {
+		WasmPalette {palette: Palette::new(name, Format::Default, true)}
+	}
+
+	/// Reads a palette from the given bytes in the named format.
+	pub fn read(bytes: &[u8], format: &str) -> Result<WasmPalette, JsValue> {
+		let palette = read_bytes(bytes, format_named(format)?)?;
+		Ok(WasmPalette {palette: palette})
+	}
+
+	/// Reads a palette from the given bytes, detecting its format from the 
+	/// file name and contents.
+	pub fn load(file_name: &str, bytes: &[u8]) 
+		-> Result<WasmPalette, JsValue>
+	{
+		let format = registry::detect(file_name, bytes)
+			.ok_or_else(|| JsValue::from_str("unrecognized palette format"))?;
+		Ok(WasmPalette {palette: read_bytes(bytes, format)?})
+	}
+
+	/// Writes the palette in the named format.
+	pub fn write(&self, format: &str) -> Result<Vec<u8>, JsValue> {
+		let mut out_buf = Vec::new();
+		format_named(format)?.write_palette(&self.palette, &mut out_buf)?;
+		Ok(out_buf)
+	}
+
+	/// Returns the number of cells in the palette.
+	#[wasm_bindgen(getter)]
+	pub fn length(&self) -> usize {
+		self.palette.len()
+	}
+
+	/// Returns the name of the palette.
+	#[wasm_bindgen(getter)]
+	pub fn name(&self) -> String {
+		self.palette.data()
+			.name(&Reference::all())
+			.unwrap_or("")
+			.into()
+	}
+
+	/// Adds a color in the first free slot, returning its address.
+	#[wasm_bindgen(js_name = addColor)]
+	pub fn add_color(&mut self, color: &str) -> Result<String, JsValue> {
+		let color = from_hex(color)?;
+		let address = self.palette.data_mut().add_color(color)?;
+		Ok(address.to_string())
+	}
+
+	/// Sets the color at the given address, replacing any existing color.
+	#[wasm_bindgen(js_name = setColor)]
+	pub fn set_color(&mut self, address: &str, color: &str) 
+		-> Result<(), JsValue>
+	{
+		let op = InsertColor::new(from_hex(color)?)
+			.located_at(address.parse()?)
+			.overwrite(true);
+		Ok(self.palette.apply(Box::new(op))?)
+	}
+
+	/// Returns the color at the given address as a hexadecimal string, or 
+	/// undefined if the slot is empty.
+	pub fn color(&self, address: &str) -> Result<Option<String>, JsValue> {
+		let address: Address = address.parse()?;
+		Ok(self.palette.color(address).map(|c| Hex(c).to_string()))
+	}
+
+	/// Returns the addresses of the palette's colors in address order.
+	pub fn addresses(&self) -> Box<[JsValue]> {
+		self.palette.data()
+			.colors()
+			.map(|(address, _)| JsValue::from_str(&address.to_string()))
+			.collect::<Vec<_>>()
+			.into_boxed_slice()
+	}
+
+	/// Returns the colors of the palette in address order as packed RGB 
+	/// bytes, three per color.
+	#[wasm_bindgen(js_name = colorBytes)]
+	pub fn color_bytes(&self) -> Vec<u8> {
+		self.palette.data()
+			.colors()
+			.flat_map(|(_, c)| vec![c.r(), c.g(), c.b()])
+			.collect()
+	}
+
+	/// Reverses the most recently applied operation.
+	pub fn undo(&mut self) -> Result<(), JsValue> {
+		Ok(self.palette.undo()?)
+	}
+
+	/// Reverses the most recently undone operation.
+	pub fn redo(&mut self) -> Result<(), JsValue> {
+		Ok(self.palette.redo()?)
+	}
+
+	/// Renders the palette's swatches as a PNG image, with cells of the given
+	/// size in pixels.
+	#[wasm_bindgen(js_name = renderPng)]
+	pub fn render_png(&self, cell_size: u32) -> Result<Vec<u8>, JsValue> {
+		let image = Renderer::new()
+			.cell_size(cell_size)
+			.render(self.palette.data())?;
+		let mut out_buf = Vec::new();
+		image.write_png(&mut out_buf).map_err(Error::from)?;
+		Ok(out_buf)
+	}
+
+	/// Renders the palette's swatches as an SVG document, with swatches of 
+	/// the given size in pixels, optionally labelled with their slot names.
+	#[wasm_bindgen(js_name = renderSvg)]
+	pub fn render_svg(&self, swatch_size: u32, labels: bool) 
+		-> Result<String, JsValue>
+	{
+		let options = SvgOptions::default()
+			.swatch_size(swatch_size)
+			.labels(labels);
+		let mut out_buf = Vec::new();
+		svg::write_palette_with(&self.palette, &mut out_buf, &options)?;
+		String::from_utf8(out_buf)
+			.map_err(|e| JsValue::from_str(&e.to_string()))
+	}
+}
@@ -0,0 +1,277 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Tests for the strict and lenient parse modes of the palette format readers.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+extern crate palette;
+
+use palette::{Color, Format, Palette};
+use palette::data::Data;
+use palette::format::ParseOptions;
+use palette::result::{Error, Result};
+
+
+/// Returns the colors of the given palette in address order.
+fn colors(palette: &Palette) -> Vec<Color> {
+	palette.data().colors().map(|(_, c)| c).collect()
+}
+
+/// Returns a palette holding the given colors.
+fn palette_of(colors: &[Color]) -> Palette {
+	let mut palette = Palette::new("", Format::Default, false);
+	for &color in colors {
+		palette.data_mut().add_color(color).unwrap();
+	}
+	palette
+}
+
+/// Writes the given colors in the given format.
+fn write(colors: &[Color], format: Format) -> Vec<u8> {
+	let mut buf = Vec::new();
+	format.write_palette(&palette_of(colors), &mut buf).unwrap();
+	buf
+}
+
+/// Reads a palette from the given bytes in the given mode.
+fn read(bytes: &[u8], format: Format, options: ParseOptions) 
+	-> Result<Palette>
+{
+	format.read_palette_with(&mut &bytes[..], &options)
+}
+
+/// Returns the offset of the given parse error.
+fn offset_of(error: Error) -> u64 {
+	match error {
+		Error::Parse {offset, ..} => offset,
+		error => panic!("expected parse error, found {}", error),
+	}
+}
+
+
+#[test]
+fn lenient_skips_malformed_entries() {
+	let text = "GIMP Palette\n255 0 0 Red\nnot a color\n0 255 0\n";
+
+	let err = read(text.as_bytes(), Format::Gpl, ParseOptions::strict())
+		.unwrap_err();
+	assert_eq!(offset_of(err), 25);
+
+	let pal = read(text.as_bytes(), Format::Gpl, ParseOptions::lenient())
+		.unwrap();
+	assert_eq!(colors(&pal), vec![
+		Color::new(255, 0, 0), 
+		Color::new(0, 255, 0),
+	]);
+}
+
+#[test]
+fn default_options_are_strict() {
+	let text = "JASC-PAL\r\n0100\r\n2\r\n1 2 3\r\n";
+	assert!(Format::Jasc.read_palette(&mut text.as_bytes()).is_err());
+
+	let pal = read(text.as_bytes(), Format::Jasc, ParseOptions::lenient())
+		.unwrap();
+	assert_eq!(colors(&pal), vec![Color::new(1, 2, 3)]);
+}
+
+#[test]
+fn max_colors() {
+	let text = "#FF0000\n#00FF00\n#0000FF\n";
+
+	let strict = ParseOptions::strict().max_colors(2);
+	let err = read(text.as_bytes(), Format::Hex, strict).unwrap_err();
+	assert_eq!(offset_of(err), 16);
+
+	let lenient = ParseOptions::lenient().max_colors(2);
+	let pal = read(text.as_bytes(), Format::Hex, lenient).unwrap();
+	assert_eq!(pal.len(), 2);
+}
+
+#[test]
+fn lenient_keeps_colors_before_truncation() {
+	let written = [
+		Color::new(10, 20, 30), 
+		Color::new(40, 50, 60),
+		Color::new(70, 80, 90),
+		Color::new(100, 110, 120),
+	];
+
+	let formats = [Format::Riff, Format::Act, Format::ProMotion, Format::Aco];
+	for &format in &formats {
+		let buf = write(&written, format);
+		let truncated = match format {
+			// ACT and Pro Motion tables are padded to 256 entries.
+			Format::Act | Format::ProMotion => &buf[..7],
+			_ => &buf[..buf.len() - 6],
+		};
+
+		assert!(read(truncated, format, ParseOptions::strict()).is_err());
+		let pal = read(truncated, format, ParseOptions::lenient())
+			.unwrap_or_else(|e| panic!("{} read failed: {}", format.name(), e));
+		assert_eq!(&colors(&pal)[..2], &written[..2], "{}", format.name());
+	}
+}
+
+#[test]
+fn png_checksum() {
+	let mut buf = write(&[Color::new(1, 2, 3)], Format::Png);
+	// Corrupt the first palette entry, following the signature, the IHDR 
+	// chunk, and the PLTE chunk header.
+	buf[41] ^= 0xFF;
+
+	let err = read(&buf, Format::Png, ParseOptions::strict()).unwrap_err();
+	match err {
+		Error::Parse {offset, ref expected, ref found} => {
+			assert_eq!(offset, 41 + 3);
+			assert!(expected.starts_with("checksum"));
+			assert!(found.starts_with("checksum"));
+		},
+		error => panic!("expected parse error, found {}", error),
+	}
+
+	let pal = read(&buf, Format::Png, ParseOptions::lenient()).unwrap();
+	assert_eq!(colors(&pal), vec![Color::new(0xFE, 2, 3)]);
+}
+
+#[test]
+fn parse_errors_report_offsets() {
+	let written = [Color::new(1, 2, 3), Color::new(4, 5, 6)];
+
+	// A truncated file fails at its end.
+	let buf = write(&written, Format::Riff);
+	let truncated = &buf[..buf.len() - 1];
+	let err = read(truncated, Format::Riff, ParseOptions::strict())
+		.unwrap_err();
+	assert_eq!(offset_of(err), truncated.len() as u64);
+
+	// A bad signature fails at the start.
+	let mut buf = write(&written, Format::Ase);
+	buf[0] = b'X';
+	let err = read(&buf, Format::Ase, ParseOptions::lenient()).unwrap_err();
+	assert_eq!(offset_of(err), 0);
+
+	// An unsupported native version fails at the version.
+	let mut buf = Vec::new();
+	palette_of(&written).data().write_native(&mut buf).unwrap();
+	buf[4] = 0xFF;
+	let err = palette::data::Data::read_native(&mut &buf[..]).unwrap_err();
+	assert_eq!(offset_of(err), 4);
+}
+
+#[test]
+fn ase_group_limit() {
+	let written = [Color::new(1, 2, 3)];
+	let mut buf = write(&written, Format::Ase);
+	let len = buf.len();
+
+	// Append an empty group end block for every page.
+	let blocks = buf[8..12].iter()
+		.fold(0u32, |count, &byte| count << 8 | byte as u32) + 0xFFFF;
+	buf[8..12].copy_from_slice(&[
+		(blocks >> 24) as u8, 
+		(blocks >> 16) as u8, 
+		(blocks >> 8) as u8, 
+		blocks as u8,
+	]);
+	for _ in 0..0xFFFF {
+		buf.extend_from_slice(&[0xC0, 0x02, 0, 0, 0, 0]);
+	}
+
+	let err = read(&buf, Format::Ase, ParseOptions::strict()).unwrap_err();
+	assert!(offset_of(err) >= len as u64);
+	let pal = read(&buf, Format::Ase, ParseOptions::lenient()).unwrap();
+	assert_eq!(colors(&pal), written.to_vec());
+}
+
+#[test]
+fn aseprite_old_palette_bounds() {
+	// Builds a single frame holding a legacy palette chunk of the given 
+	// packets.
+	let file = |packets: &[(u8, u8)]| {
+		let mut body = vec![packets.len() as u8, 0];
+		for &(skip, count) in packets {
+			body.extend_from_slice(&[skip, count]);
+			let count = if count == 0 { 256 } else { count as usize };
+			body.extend(std::iter::repeat(0x80).take(count * 3));
+		}
+		let size = body.len() as u32 + 6;
+		let mut buf = vec![0u8; 128 + 16];
+		buf[4..8].copy_from_slice(&[0xE0, 0xA5, 1, 0]);
+		buf[128 + 4..128 + 8].copy_from_slice(&[0xFA, 0xF1, 1, 0]);
+		buf.extend_from_slice(&[
+			size as u8, (size >> 8) as u8, (size >> 16) as u8, 0, 
+			0x04, 0x00,
+		]);
+		buf.extend_from_slice(&body);
+		buf
+	};
+	let gray = Color::new(0x80, 0x80, 0x80);
+	let black = Color::new(0, 0, 0);
+
+	// Skipped entries keep their previous value, which is black.
+	let buf = file(&[(2, 1)]);
+	let pal = read(&buf, Format::Aseprite, ParseOptions::lenient()).unwrap();
+	assert_eq!(colors(&pal), vec![black, black, gray]);
+
+	// Entries may not go past the end of the palette.
+	let buf = file(&[(0, 0), (255, 1)]);
+	assert!(read(&buf, Format::Aseprite, ParseOptions::strict()).is_err());
+	let pal = read(&buf, Format::Aseprite, ParseOptions::lenient()).unwrap();
+	assert_eq!(colors(&pal), vec![gray; 256]);
+}
+
+#[test]
+fn native_parse_options() {
+	let written = [
+		Color::new(10, 20, 30), 
+		Color::new(40, 50, 60),
+		Color::new(70, 80, 90),
+	];
+	let mut buf = Vec::new();
+	palette_of(&written).data().write_native(&mut buf).unwrap();
+	let read_native = |bytes: &[u8], options: ParseOptions| 
+		Data::read_native_with(&mut &bytes[..], &options);
+	let native_colors = |data: Data| data.colors()
+		.map(|(_, c)| c)
+		.collect::<Vec<_>>();
+
+	let strict = ParseOptions::strict().max_colors(2);
+	assert!(read_native(&buf, strict).is_err());
+	let lenient = ParseOptions::lenient().max_colors(2);
+	let data = read_native(&buf, lenient).unwrap();
+	assert_eq!(native_colors(data), &written[..2]);
+
+	// Cut the file within the last cell.
+	let cells = buf.windows(4).position(|id| id == b"CELL").unwrap();
+	let len = buf[cells + 4..cells + 8].iter().rev()
+		.fold(0, |len, &byte| len << 8 | byte as usize);
+	let truncated = &buf[..cells + 8 + len - 1];
+
+	let err = read_native(truncated, ParseOptions::strict()).unwrap_err();
+	assert_eq!(offset_of(err), truncated.len() as u64);
+	let data = read_native(truncated, ParseOptions::lenient()).unwrap();
+	assert_eq!(native_colors(data), &written[..2]);
+}
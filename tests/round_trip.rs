@@ -0,0 +1,662 @@
+// The MIT License (MIT)
+// 
+// Copyright (c) 2016 Skylor R. Schermer
+// 
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+// 
+// The above copyright notice and this permission notice shall be included in 
+// all copies or substantial portions of the Software.
+// 
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+////////////////////////////////////////////////////////////////////////////////
+//!
+//! Round-trip tests for the palette file formats. Random palettes are written
+//! through each format and read back, and the part of each palette which the
+//! format can represent is checked for equality.
+//!
+////////////////////////////////////////////////////////////////////////////////
+
+extern crate palette;
+
+use palette::{Address, Color, Format, Palette, Reference};
+use palette::cell::Weak;
+use palette::data::{Constraint, Data};
+use palette::depth::{expand, reduce};
+use palette::expression::Expression;
+use palette::format::qst::{Packing, Quest};
+use palette::format::registry::REGISTRY;
+use palette::format::report::Loss;
+use palette::meta::{MetaType, ALPHA_KEY};
+use palette::operation::{InsertColor, SetExpression};
+
+
+/// The number of random palettes to test for each format.
+const ROUNDS: u64 = 32;
+
+/// The maximum number of colors in a random palette.
+const MAX_COLORS: usize = 256;
+
+/// The formats which read back the slot names they write.
+const NAMED_FORMATS: &[Format] = &[
+	Format::Gpl, 
+	Format::Ase, 
+	Format::Aco, 
+	Format::Aseprite,
+	Format::Csv,
+	Format::Tsv,
+];
+
+
+/// A small xorshift generator, so that failures are reproducible from their
+/// seed.
+struct Rng(u64);
+
+impl Rng {
+	fn new(seed: u64) -> Rng {
+		Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+	}
+
+	fn next(&mut self) -> u64 {
+		self.0 ^= self.0 << 13;
+		self.0 ^= self.0 >> 7;
+		self.0 ^= self.0 << 17;
+		self.0
+	}
+
+	fn below(&mut self, bound: usize) -> usize {
+		(self.next() % bound as u64) as usize
+	}
+}
+
+
+/// Returns the formats which can be both written and read.
+fn round_trip_formats() -> Vec<Format> {
+	REGISTRY.iter()
+		.filter(|registration| registration.readable && registration.writable)
+		.map(|registration| registration.format)
+		.collect()
+}
+
+/// Returns a random palette of fixed colors which fits within the given 
+/// format's color limits, with every color given a slot name.
+fn random_palette(rng: &mut Rng, format: Format) -> Palette {
+	let caps = format.capabilities();
+	let max = caps.max_colors.map_or(MAX_COLORS, |max| max.min(MAX_COLORS));
+	let min = caps.min_colors.max(1);
+	let count = min + rng.below(max - min + 1);
+
+	let mut palette = Palette::new("Round Trip", format, false);
+	for index in 0..count {
+		let value = rng.next();
+		let color = Color::new(
+			value as u8, 
+			(value >> 8) as u8, 
+			(value >> 16) as u8);
+		let address = palette.data_mut().add_color(color).unwrap();
+		palette.data_mut().set_slot_name(address, format!("Color {}", index));
+	}
+	palette
+}
+
+/// Writes the given palette in the given format and reads it back.
+fn round_trip(palette: &Palette, format: Format) -> Palette {
+	let mut buf = Vec::new();
+	format.write_palette(palette, &mut buf)
+		.unwrap_or_else(|e| panic!("{} write failed: {}", format.name(), e));
+	format.read_palette(&mut &buf[..])
+		.unwrap_or_else(|e| panic!("{} read failed: {}", format.name(), e))
+}
+
+/// Returns the colors of the given palette in address order, as stored at the
+/// given format's channel depth.
+fn stored_colors(palette: &Palette, format: Format) -> Vec<Color> {
+	let depth = format.capabilities().depth;
+	let store = |channel: u8| expand(reduce(channel, depth), depth);
+	palette.data().colors()
+		.map(|(_, c)| Color::new(store(c.r()), store(c.g()), store(c.b())))
+		.collect()
+}
+
+/// Compresses the given data as an Allegro LZSS stream, encoding runs of
+/// repeated bytes as back references.
+fn pack_runs(data: &[u8]) -> Vec<u8> {
+	let mut packed = Vec::new();
+	let mut position = 4096 - 18;
+	let mut flags = 0;
+	let mut bit = 8;
+	let mut i = 0;
+	while i < data.len() {
+		if bit == 8 {
+			flags = packed.len();
+			packed.push(0);
+			bit = 0;
+		}
+		let run = data[i..].iter()
+			.take(18)
+			.take_while(|&&byte| byte == data[i])
+			.count();
+		if i > 0 && data[i - 1] == data[i] && run >= 3 {
+			let start = (position + 4096 - 1) % 4096;
+			packed.push(start as u8);
+			packed.push(((start >> 4) & 0xF0) as u8 | (run - 3) as u8);
+			position = (position + run) % 4096;
+			i += run;
+		} else {
+			packed[flags] |= 1 << bit;
+			packed.push(data[i]);
+			position = (position + 1) % 4096;
+			i += 1;
+		}
+		bit += 1;
+	}
+	packed
+}
+
+/// Returns the given data as a compressed Allegro packfile encrypted with the
+/// given password, following Allegro's `pack_fopen_password`.
+fn packfile(data: &[u8], password: &[u8]) -> Vec<u8> {
+	// The magic number is masked by `encrypt_id`.
+	let mut mask: i32 = 0;
+	for (i, &c) in password.iter().enumerate() {
+		mask ^= (c as i8 as i32) << ((i & 3) * 8);
+	}
+	let mut pos = 0;
+	for i in 0..4 {
+		mask ^= (password[pos] as i8 as i32) << (24 - i * 8);
+		pos += 1;
+		if pos == password.len() { pos = 0; }
+	}
+	let magic = 0x736C_6821 ^ mask ^ 42;
+
+	// The password key restarts after the magic number.
+	let mut packed = pack_runs(data);
+	for (i, byte) in packed.iter_mut().enumerate() {
+		*byte ^= password[i % password.len()];
+	}
+	let mut buf = vec![
+		(magic >> 24) as u8, (magic >> 16) as u8, (magic >> 8) as u8, 
+		magic as u8];
+	buf.extend_from_slice(&packed);
+	buf
+}
+
+/// Wraps the given packfile in the Zelda Classic quest encoding with the given
+/// key, following Zelda Classic's `encode_file_007` using its newest method.
+fn encode_quest(src: &[u8], key: i32) -> Vec<u8> {
+	fn rand_007(seed: &mut i32) -> i32 {
+		let bx: i16 = (*seed >> 8) as i16;
+		let cx: i16 = ((*seed & 0xFF) << 8) as i16;
+		let al: i8 = (*seed >> 24) as i8;
+		let c: i8 = al >> 7;
+		let d: i8 = (bx >> 15) as i8;
+		let mut bx: i16 = (((bx as i32) << 1) | c as i32) as i16;
+		let mut cx: i16 = (((cx as i32) << 1) | d as i32) as i16;
+		cx = (cx as i32 + (*seed & 0xFFFF)) as i16;
+		bx = (bx as i32 + (*seed >> 16) + c as i32) as i16;
+		cx = (cx as i32 + 0xE09C) as i16;
+		bx = (bx as i32 + 0x4E8F + d as i32) as i16;
+		*seed = ((bx as i32) << 16).wrapping_add(cx as i32);
+		((cx as i32) << 16).wrapping_add(bx as i32)
+	}
+
+	let mut seed = key;
+	let mut dest = b"Zelda Classic Quest File".to_vec();
+	let masked = key ^ 0xFD09_5E94u32 as i32;
+	dest.extend_from_slice(&[
+		(masked >> 24) as u8, (masked >> 16) as u8, (masked >> 8) as u8, 
+		masked as u8]);
+
+	let mut tog = false;
+	let mut r = 0;
+	let mut c1: i16 = 0;
+	let mut c2: i16 = 0;
+	for &byte in src {
+		let c = byte as i32;
+		c1 = (c1 as i32 + c) as i16;
+		c2 = (((c2 as i32) << 4) + ((c2 as i32) >> 12) + c) as i16;
+		if tog {
+			dest.push(c.wrapping_add(r) as u8);
+		} else {
+			r = rand_007(&mut seed);
+			dest.push((c ^ r) as u8);
+		}
+		tog = !tog;
+	}
+
+	let r = rand_007(&mut seed);
+	let c1 = (c1 as i32 ^ r) as i16;
+	let c2 = (c2 as i32).wrapping_add(r) as i16;
+	dest.extend_from_slice(&[
+		(c1 >> 8) as u8, c1 as u8, (c2 >> 8) as u8, c2 as u8]);
+	dest
+}
+
+/// Returns the slot names of the given palette's colors in address order.
+fn slot_names(palette: &Palette) -> Vec<Option<String>> {
+	palette.data().colors()
+		.map(|(address, _)| palette.data().slot_name(address).map(String::from))
+		.collect()
+}
+
+
+#[test]
+fn colors_round_trip() {
+	for format in round_trip_formats() {
+		for seed in 0..ROUNDS {
+			let palette = random_palette(&mut Rng::new(seed), format);
+			let expected = stored_colors(&palette, format);
+			let read = round_trip(&palette, format);
+
+			// Formats with fixed size tables may pad the palette.
+			let actual = stored_colors(&read, format);
+			assert!(actual.len() >= expected.len(), 
+				"{} (seed {}) lost colors", format.name(), seed);
+			assert_eq!(&actual[..expected.len()], &expected[..],
+				"{} (seed {}) altered colors", format.name(), seed);
+		}
+	}
+}
+
+
+#[test]
+fn names_round_trip() {
+	for &format in NAMED_FORMATS {
+		for seed in 0..ROUNDS {
+			let palette = random_palette(&mut Rng::new(seed), format);
+			let expected = slot_names(&palette);
+			let read = round_trip(&palette, format);
+
+			let actual = slot_names(&read);
+			assert_eq!(&actual[..expected.len()], &expected[..],
+				"{} (seed {}) altered names", format.name(), seed);
+		}
+	}
+}
+
+
+#[test]
+fn layout_round_trip() {
+	for format in round_trip_formats() {
+		if !format.capabilities().groups { continue; }
+		for seed in 0..ROUNDS {
+			let palette = random_palette(&mut Rng::new(seed), format);
+			let read = round_trip(&palette, format);
+
+			let expected: Vec<Address> = palette.data().colors()
+				.map(|(address, _)| address)
+				.collect();
+			let actual: Vec<Address> = read.data().colors()
+				.map(|(address, _)| address)
+				.take(expected.len())
+				.collect();
+			assert_eq!(actual, expected,
+				"{} (seed {}) moved colors", format.name(), seed);
+		}
+	}
+}
+
+
+#[test]
+fn native_round_trip() {
+	for seed in 0..ROUNDS {
+		let mut rng = Rng::new(seed);
+		let palette = random_palette(&mut rng, Format::Default);
+		let mut data = palette.data().clone();
+		let colors: Vec<Address> = data.colors()
+			.map(|(address, _)| address)
+			.collect();
+		let tagged = colors[rng.below(colors.len())];
+		data.add_tag(Reference::from(tagged), "tagged");
+		data.set_notes(Reference::all(), format!("Seed {}", seed));
+
+		let mut buf = Vec::new();
+		data.write_native(&mut buf).unwrap();
+		let read = Data::read_native(&mut &buf[..]).unwrap();
+
+		assert!(read == data, "native (seed {}) altered data", seed);
+	}
+}
+
+
+#[test]
+fn zpl_section_layout() {
+	// A Zelda Classic palette section holds 16 main csets, 13 csets for each
+	// of 512 levels, and 30 sprite csets, each of 16 colors, followed by 512
+	// level names of 17 bytes and the palette cycle data.
+	let colors = (16 + 13 * 512 + 30) * 16 * 3;
+	let names = 512 * 17;
+	let footer = 804;
+
+	let mut palette = random_palette(&mut Rng::new(0), Format::Zpl);
+	let level = Reference::page_of(&Address::new(1, 0, 0));
+	palette.data_mut().set_name(level.clone(), "Overworld");
+	let mut zpl = Vec::new();
+	Format::Zpl.write_palette(&palette, &mut zpl).unwrap();
+
+	assert_eq!(zpl.len(), 12 + colors + names + footer);
+	let size = zpl[8] as usize 
+		| (zpl[9] as usize) << 8 
+		| (zpl[10] as usize) << 16 
+		| (zpl[11] as usize) << 24;
+	assert_eq!(size, zpl.len() - 12);
+	assert_eq!(&zpl[12 + colors..12 + colors + 9], b"Overworld");
+
+	let read = Format::Zpl.read_palette(&mut &zpl[..]).unwrap();
+	assert_eq!(read.data().name(&level), Some("Overworld"));
+}
+
+
+#[test]
+fn quest_round_trip() {
+	let palette = random_palette(&mut Rng::new(0), Format::Zpl);
+	let mut zpl = Vec::new();
+	Format::Zpl.write_palette(&palette, &mut zpl).unwrap();
+
+	// Build a quest as Zelda Classic saves it: a header section preceding the
+	// palette section, compressed and encrypted as an Allegro packfile, and 
+	// wrapped in the Zelda Classic encoding.
+	let mut data = b"AG ZC Enhanced Quest File\n".to_vec();
+	data.resize(31, 0);
+	data.extend_from_slice(b"HDR \x01\x00\x01\x00\x03\x00\x00\x00abc");
+	data.extend_from_slice(&zpl);
+	let buf = encode_quest(&packfile(&data, b"longtan"), 0x413F_1234);
+
+	let mut quest = Quest::read(&mut &buf[..]).unwrap();
+	assert_eq!(quest.encoding().map(|encoding| encoding.key), 
+		Some(0x413F_1234));
+	assert_eq!(quest.packing(), Packing::Compressed);
+	assert_eq!(quest.sections().len(), 2);
+	assert_eq!(quest.sections()[0].data, b"abc".to_vec());
+	let expected = stored_colors(&palette, Format::Zpl);
+	let read = quest.palette().unwrap();
+	assert_eq!(&stored_colors(&read, Format::Zpl)[..expected.len()], 
+		&expected[..]);
+
+	// Replacing the palette leaves the other sections in place.
+	let replacement = random_palette(&mut Rng::new(1), Format::Zpl);
+	quest.set_palette(&replacement).unwrap();
+	let mut out = Vec::new();
+	quest.write(&mut out).unwrap();
+	assert!(out.starts_with(b"Zelda Classic Quest File"));
+	let reread = Quest::read(&mut &out[..]).unwrap();
+	assert!(reread == quest, "quest sections altered");
+
+	let expected = stored_colors(&replacement, Format::Zpl);
+	let read = reread.palette().unwrap();
+	assert_eq!(&stored_colors(&read, Format::Zpl)[..expected.len()], 
+		&expected[..]);
+
+	// A corrupted quest fails its checksum.
+	let mut corrupt = buf.clone();
+	corrupt[40] ^= 1;
+	assert!(Quest::read(&mut &corrupt[..]).is_err());
+}
+
+
+#[test]
+fn packfile_password_round_trip() {
+	let palette = random_palette(&mut Rng::new(2), Format::Zpl);
+	let mut zpl = Vec::new();
+	Format::Zpl.write_palette(&palette, &mut zpl).unwrap();
+	let buf = packfile(&zpl, b"secret");
+
+	// The magic number is masked, not encrypted with the data.
+	assert!(&buf[..4] != b"slh!");
+	assert!(Quest::read(&mut &buf[..]).is_err());
+
+	let quest = Quest::read_with_password(&mut &buf[..], b"secret").unwrap();
+	assert_eq!(quest.encoding(), None);
+	assert_eq!(quest.packing(), Packing::Compressed);
+	let expected = stored_colors(&palette, Format::Zpl);
+	let read = quest.palette().unwrap();
+	assert_eq!(&stored_colors(&read, Format::Zpl)[..expected.len()], 
+		&expected[..]);
+
+	let mut out = Vec::new();
+	quest.write(&mut out).unwrap();
+	assert_eq!(&out[..4], &buf[..4]);
+	let reread = Quest::read_with_password(&mut &out[..], b"secret").unwrap();
+	assert!(reread == quest, "quest sections altered");
+}
+
+
+#[test]
+fn write_reports_losses() {
+	let mut palette = Palette::new("Losses", Format::Default, false);
+	let a = palette.data_mut().add_color(Color::new(1, 2, 3)).unwrap();
+	let b = palette.data_mut().add_color(Color::new(4, 5, 6)).unwrap();
+	palette.data_mut().set_slot_name(a, "Dark Red");
+	palette.data_mut().set_slot_name(b, "x".repeat(0x10000));
+	palette.data_mut().register_meta(ALPHA_KEY, MetaType::Int).unwrap();
+	palette.data_mut()
+		.set_meta(Reference::from(b), ALPHA_KEY, 128i64)
+		.unwrap();
+
+	let mut buf = Vec::new();
+	let report = Format::Ase.write_palette_reported(&palette, &mut buf)
+		.unwrap();
+	assert_eq!(report.losses(), &[
+		Loss::Alpha(b),
+		Loss::NameTruncated(Reference::from(b)),
+	]);
+	assert_eq!(Format::Ase.check(&palette), report);
+
+	let report = Format::Aco.check(&palette);
+	assert!(report.losses().contains(&Loss::NameTruncated(Reference::from(b))));
+
+	let report = Format::Css.check(&palette);
+	assert!(report.losses().contains(&Loss::NameRewritten(Reference::from(a))));
+	assert!(!report.losses().contains(&Loss::Alpha(a)));
+}
+
+
+#[test]
+fn png_transparency_round_trip() {
+	let mut palette = Palette::new("Transparency", Format::Default, false);
+	let mut addresses = Vec::new();
+	for &alpha in &[0i64, 255, 128, 255] {
+		let address = palette.data_mut()
+			.add_color(Color::new(alpha as u8, 1, 2))
+			.unwrap();
+		palette.data_mut().register_meta(ALPHA_KEY, MetaType::Int).unwrap();
+		palette.data_mut()
+			.set_meta(Reference::from(address), ALPHA_KEY, alpha)
+			.unwrap();
+		addresses.push(address);
+	}
+
+	let read = round_trip(&palette, Format::Png);
+	let alphas: Vec<Option<i64>> = addresses.iter()
+		.map(|&a| read.data().get_meta(&Reference::from(a), ALPHA_KEY))
+		.collect();
+	assert_eq!(alphas, vec![Some(0), None, Some(128), None]);
+	assert!(Format::Png.check(&palette).is_lossless());
+}
+
+
+#[test]
+fn act_transparency_round_trip() {
+	let mut palette = Palette::new("Transparency", Format::Default, false);
+	let mut addresses = Vec::new();
+	for &alpha in &[255i64, 0, 128, 0] {
+		let address = palette.data_mut()
+			.add_color(Color::new(alpha as u8, 1, 2))
+			.unwrap();
+		palette.data_mut().register_meta(ALPHA_KEY, MetaType::Int).unwrap();
+		palette.data_mut()
+			.set_meta(Reference::from(address), ALPHA_KEY, alpha)
+			.unwrap();
+		addresses.push(address);
+	}
+
+	// Only the first fully transparent color is stored.
+	let mut buf = Vec::new();
+	let report = Format::Act.write_palette_reported(&palette, &mut buf)
+		.unwrap();
+	assert_eq!(report.losses(), &[
+		Loss::Alpha(addresses[2]),
+		Loss::Alpha(addresses[3]),
+	]);
+	assert_eq!(&buf[768..], &[0, 4, 0, 1]);
+
+	let read = Format::Act.read_palette(&mut &buf[..]).unwrap();
+	let alphas: Vec<Option<i64>> = addresses.iter()
+		.map(|&a| read.data().get_meta(&Reference::from(a), ALPHA_KEY))
+		.collect();
+	assert_eq!(alphas, vec![None, Some(0), None, None]);
+}
+
+
+#[test]
+fn address_text_round_trip() {
+	let mut rng = Rng::new(7);
+	for _ in 0..ROUNDS {
+		let value = rng.next();
+		let address = Address::new(
+			value as u16, 
+			(value >> 16) as u8, 
+			(value >> 24) as u8);
+		for text in &[
+			address.to_string(),
+			format!("{:#X}", address),
+			format!("{:#x}", address),
+		] {
+			assert_eq!(text.parse::<Address>().ok(), Some(address), 
+				"{} did not round trip", text);
+		}
+	}
+
+	for text in &["2:*:*", "_+1/3/*", "base-2/_/_+15", "all", "page 4"] {
+		let reference = text.parse::<Reference>().unwrap();
+		assert_eq!(reference.to_string().parse::<Reference>().ok(), 
+			Some(reference), 
+			"{} did not round trip", text);
+	}
+}
+
+
+#[test]
+fn removal_rewinds_free_cursor() {
+	let mut rng = Rng::new(11);
+	for _ in 0..ROUNDS {
+		let mut dat: Data = Default::default();
+		let mut addresses = Vec::new();
+		for i in 0..300u32 {
+			let color = Color::new(i as u8, (i >> 8) as u8, 0);
+			addresses.push(dat.add_color(color).unwrap());
+		}
+
+		let removed = addresses[rng.below(addresses.len())];
+		match rng.next() % 3 {
+			0 => { dat.remove_cell(removed).unwrap(); },
+			1 => { dat.remove_color(removed).unwrap(); },
+			_ => { dat.remove_slot(removed).unwrap(); },
+		}
+
+		assert_eq!(dat.add_color(Color::new(1, 2, 3)).unwrap(), removed);
+		let next = dat.add_color(Color::new(1, 2, 3)).unwrap();
+		assert!(next > addresses[addresses.len() - 1]);
+	}
+
+	let mut dat: Data = Default::default();
+	for i in 0..300u32 {
+		dat.add_color(Color::new(i as u8, 0, 0)).unwrap();
+	}
+	assert_eq!(dat.clear_page(0).unwrap().len(), 300);
+	assert_eq!(dat.add_color(Color::new(1, 2, 3)).unwrap(), 
+		Address::new(0, 0, 0));
+}
+
+
+#[test]
+fn insertion_skips_unresolved_cells() {
+	let mut pal = Palette::new("Example", Format::Default, true);
+	let start = Address::new(0, 0, 0);
+	let dangling = Expression::Lighten {a: Weak::new(), amount: 0.5};
+	pal.apply(Box::new(SetExpression::new(start, dangling))).unwrap();
+	assert_eq!(pal.color(start), None);
+
+	pal.apply(Box::new(InsertColor::new(Color::new(1, 2, 3))
+		.located_at(start))).unwrap();
+	match *pal.data().cell(start).unwrap().borrow() {
+		Expression::Lighten {..} => (),
+		ref expr => panic!("unresolved cell was overwritten by {:?}", expr),
+	}
+	assert_eq!(pal.color(Address::new(0, 0, 1)), Some(Color::new(1, 2, 3)));
+}
+
+
+#[test]
+fn append_follows_every_used_page() {
+	let mut a: Data = Default::default();
+	a.add_color(Color::new(1, 2, 3)).unwrap();
+	a.set_constraint(Address::new(2, 0, 0), Some(Constraint::Locked));
+
+	let mut b: Data = Default::default();
+	b.add_color(Color::new(4, 5, 6)).unwrap();
+	b.set_constraint(Address::new(1, 0, 0), Some(Constraint::Protected));
+
+	assert_eq!(a.append(b).unwrap(), 3);
+	assert_eq!(a.constraint(Address::new(2, 0, 0)), Some(Constraint::Locked));
+	assert_eq!(a.constraint(Address::new(4, 0, 0)), 
+		Some(Constraint::Protected));
+
+	// Constraints past the last cell count toward the page limit.
+	let mut c: Data = Default::default();
+	c.set_constraint(Address::new(0xFFFF, 0, 0), Some(Constraint::Locked));
+	assert!(a.append(c).is_err());
+}
+
+
+#[test]
+fn moves_keep_destination_metadata() {
+	let mut dat: Data = Default::default();
+	let a = dat.add_color(Color::new(1, 2, 3)).unwrap();
+	let b = dat.add_color(Color::new(4, 5, 6)).unwrap();
+	let (empty, labeled) = (Address::new(0, 0, 8), Address::new(0, 0, 9));
+	dat.set_label(Reference::from(a), "a");
+	dat.set_label(Reference::from(b), "b");
+	dat.set_label(Reference::from(empty), "empty");
+	dat.set_label(Reference::from(labeled), "labeled");
+
+	// Metadata at a destination is kept unless its cell is moved away.
+	assert!(dat.move_cells(&[(empty, labeled)]).is_err());
+	assert!(dat.move_cells(&[(a, labeled)]).is_err());
+	assert_eq!(dat.label(&Reference::from(labeled)), Some("labeled"));
+
+	dat.move_cells(&[(a, b), (b, a)]).unwrap();
+	assert_eq!(dat.label(&Reference::from(a)), Some("b"));
+	assert_eq!(dat.label(&Reference::from(b)), Some("a"));
+
+	// Empty sources leave their metadata in place.
+	let free = Address::new(0, 0, 10);
+	dat.move_cells(&[(empty, free)]).unwrap();
+	assert_eq!(dat.label(&Reference::from(empty)), Some("empty"));
+	assert_eq!(dat.label(&Reference::from(free)), None);
+}
+
+
+#[test]
+fn depth_expand_ignores_high_bits() {
+	for bits in 1..9u8 {
+		let mask = ((1u16 << bits) - 1) as u8;
+		for value in 0..256u16 {
+			let value = value as u8;
+			assert_eq!(expand(value, bits), expand(value & mask, bits),
+				"expand({:#X}, {}) read high bits", value, bits);
+			assert_eq!(reduce(expand(value, bits), bits), value & mask);
+		}
+	}
+}